@@ -59,7 +59,7 @@ fn run_benchmark(name: &str, generator: fn(u64) -> Graph, node_count: u64) {
     println!("Target: {} nodes", node_count);
 
     let t = Instant::now();
-    let graph = generator(node_count);
+    let mut graph = generator(node_count);
     let gen_time = t.elapsed();
     println!(
         "Generated in {:.2}s — {} nodes, {} edges, ~{:.0}MB",
@@ -69,6 +69,16 @@ fn run_benchmark(name: &str, generator: fn(u64) -> Graph, node_count: u64) {
         graph.memory_usage() as f64 / 1_048_576.0
     );
 
+    let t = Instant::now();
+    graph.freeze();
+    println!(
+        "Frozen to CSR in {:.2}s — ~{:.0}MB",
+        t.elapsed().as_secs_f64(),
+        graph.memory_usage() as f64 / 1_048_576.0
+    );
+
+    bench_compressed(&graph);
+
     // BFS from node 0 (typically a hub or root)
     println!();
     println!("{:>8} {:>12} {:>12} {:>10}", "depth", "found", "visited", "time");
@@ -77,11 +87,12 @@ fn run_benchmark(name: &str, generator: fn(u64) -> Graph, node_count: u64) {
     let mut bfs_d1 = graph_accel_core::TraversalResult {
         neighbors: Vec::new(),
         nodes_visited: 0,
+        truncated: false,
     };
 
     for depth in [1, 2, 3, 5, 10, 20, 50] {
         let t = Instant::now();
-        let result = graph_accel_core::bfs_neighborhood(&graph, 0, depth, TraversalDirection::Both, None);
+        let result = graph_accel_core::bfs_neighborhood(&graph, 0, depth, TraversalDirection::Both, None, 0);
         let elapsed = t.elapsed();
         println!(
             "{:>8} {:>12} {:>12} {:>8.1}ms",
@@ -100,6 +111,9 @@ fn run_benchmark(name: &str, generator: fn(u64) -> Graph, node_count: u64) {
         }
     }
 
+    #[cfg(feature = "parallel")]
+    bench_parallel_bfs(&graph);
+
     // Shortest path: node 0 to last node
     let far_node = graph.node_count() as u64 - 1;
     println!();
@@ -145,6 +159,65 @@ fn run_benchmark(name: &str, generator: fn(u64) -> Graph, node_count: u64) {
     println!();
 }
 
+/// Size of the delta-varint adjacency against the CSR graph, and its BFS
+/// time against `bfs_distances` on the CSR.
+fn bench_compressed(graph: &Graph) {
+    let t = Instant::now();
+    let compressed = graph.compress_adjacency();
+    println!(
+        "Compressed adjacency in {:.2}s — ~{:.0}MB",
+        t.elapsed().as_secs_f64(),
+        compressed.memory_usage() as f64 / 1_048_576.0
+    );
+    let t = Instant::now();
+    let csr = graph_accel_core::bfs_distances(graph, 0, 5, TraversalDirection::Both, None);
+    let csr_time = t.elapsed();
+    let t = Instant::now();
+    let decoded = compressed.bfs_distances(0, 5, TraversalDirection::Both, None);
+    let decoded_time = t.elapsed();
+    assert_eq!(csr.len(), decoded.len(), "compressed BFS diverged");
+    println!(
+        "BFS depth 5, {} nodes: CSR {:.1}ms, compressed {:.1}ms",
+        csr.len(),
+        csr_time.as_secs_f64() * 1000.0,
+        decoded_time.as_secs_f64() * 1000.0
+    );
+}
+
+/// Time `bfs_distances` against its rayon-parallel twin and check that
+/// both return the same nodes in the same order.
+#[cfg(feature = "parallel")]
+fn bench_parallel_bfs(graph: &Graph) {
+    println!();
+    println!("{:>8} {:>12} {:>10} {:>10}", "depth", "found", "seq", "parallel");
+    println!("{:->8} {:->12} {:->10} {:->10}", "", "", "", "");
+    for depth in [3, 5, 10] {
+        let t = Instant::now();
+        let seq = graph_accel_core::bfs_distances(graph, 0, depth, TraversalDirection::Both, None);
+        let seq_time = t.elapsed();
+        let t = Instant::now();
+        let par = graph_accel_core::bfs_distances_parallel(
+            graph,
+            0,
+            depth,
+            TraversalDirection::Both,
+            None,
+        );
+        let par_time = t.elapsed();
+        assert_eq!(seq, par, "parallel BFS diverged at depth {}", depth);
+        println!(
+            "{:>8} {:>12} {:>8.1}ms {:>8.1}ms",
+            depth,
+            seq.len(),
+            seq_time.as_secs_f64() * 1000.0,
+            par_time.as_secs_f64() * 1000.0
+        );
+        if seq.len() >= graph.node_count() {
+            break;
+        }
+    }
+}
+
 /// Validate direction metadata against the known graph structure.
 ///
 /// For each depth-1 neighbor of node 0, verify that:
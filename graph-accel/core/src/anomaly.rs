@@ -0,0 +1,255 @@
+use std::collections::HashMap;
+
+use crate::graph::{Graph, NodeId, RelTypeId};
+
+/// Kind of structural anomaly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnomalyKind {
+    /// More than one node carries the same app_id; lookups by app_id
+    /// silently resolve to only one of them.
+    DuplicateAppId,
+    /// Node has one or more edges pointing back at itself.
+    SelfLoop,
+    /// Relationship type used by exactly one edge — often a typo or a
+    /// one-off extraction artifact.
+    SingletonRelType,
+    /// Node degree above the requested percentile.
+    HighDegree,
+}
+
+impl AnomalyKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnomalyKind::DuplicateAppId => "duplicate_app_id",
+            AnomalyKind::SelfLoop => "self_loop",
+            AnomalyKind::SingletonRelType => "singleton_rel_type",
+            AnomalyKind::HighDegree => "high_degree",
+        }
+    }
+}
+
+/// How serious an anomaly is for ingestion quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Breaks app_id resolution — should fail a quality gate.
+    Error,
+    /// Likely bad data, worth a look.
+    Warning,
+    /// Structurally unusual but often legitimate (e.g. hubs).
+    Info,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A single flagged anomaly.
+#[derive(Debug, Clone)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub severity: Severity,
+    /// Node the anomaly is attached to (None for rel-type anomalies).
+    /// For duplicate app_ids, the node the app_id index resolves to.
+    pub node_id: Option<NodeId>,
+    pub label: Option<String>,
+    pub app_id: Option<String>,
+    pub rel_type: Option<String>,
+    /// Human-readable specifics (counts, thresholds, conflicting IDs).
+    pub detail: String,
+}
+
+/// Scan the graph for structural anomalies.
+///
+/// `degree_percentile` (0–100) sets the high-degree threshold: nodes whose
+/// total degree is strictly above the degree at that percentile (nearest
+/// rank) are flagged. A uniform graph therefore flags nothing.
+///
+/// Results are sorted by severity (errors first), then kind, then node ID.
+pub fn detect_anomalies(graph: &Graph, degree_percentile: f64) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    let node_anomaly = |kind, severity, id: NodeId, rel_type, detail| {
+        let info = graph.node(id);
+        Anomaly {
+            kind,
+            severity,
+            node_id: Some(id),
+            label: graph.node_label(id).map(str::to_string),
+            app_id: info.and_then(|n| n.app_id.clone()),
+            rel_type,
+            detail,
+        }
+    };
+
+    // Duplicate app_ids
+    let mut by_app_id: HashMap<&str, Vec<NodeId>> = HashMap::new();
+    for (&id, info) in graph.nodes_iter() {
+        if let Some(ref aid) = info.app_id {
+            by_app_id.entry(aid.as_str()).or_default().push(id);
+        }
+    }
+    for (aid, mut ids) in by_app_id {
+        if ids.len() < 2 {
+            continue;
+        }
+        ids.sort_unstable();
+        let resolved = graph.resolve_app_id(aid).unwrap_or(ids[0]);
+        let list: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        anomalies.push(node_anomaly(
+            AnomalyKind::DuplicateAppId,
+            Severity::Error,
+            resolved,
+            None,
+            format!("app_id shared by {} nodes: {}", ids.len(), list.join(", ")),
+        ));
+    }
+
+    // Self-loops and rel-type usage
+    let mut rel_counts: HashMap<RelTypeId, usize> = HashMap::new();
+    let mut degrees: Vec<(NodeId, usize)> = Vec::with_capacity(graph.node_count());
+    for (&id, _) in graph.nodes_iter() {
+        let out = graph.neighbors_out(id);
+        let mut loops: HashMap<RelTypeId, usize> = HashMap::new();
+        for e in out {
+            *rel_counts.entry(e.rel_type).or_insert(0) += 1;
+            if e.target == id {
+                *loops.entry(e.rel_type).or_insert(0) += 1;
+            }
+        }
+        for (rt, count) in loops {
+            anomalies.push(node_anomaly(
+                AnomalyKind::SelfLoop,
+                Severity::Warning,
+                id,
+                graph.rel_type_name(rt).map(|s| s.to_string()),
+                format!("{} self-loop edge(s)", count),
+            ));
+        }
+        degrees.push((id, out.len() + graph.neighbors_in(id).len()));
+    }
+
+    for (rt, count) in rel_counts {
+        if count == 1 {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::SingletonRelType,
+                severity: Severity::Warning,
+                node_id: None,
+                label: None,
+                app_id: None,
+                rel_type: graph.rel_type_name(rt).map(|s| s.to_string()),
+                detail: "relationship type used by exactly one edge".to_string(),
+            });
+        }
+    }
+
+    // High degree: strictly above the nearest-rank percentile
+    if !degrees.is_empty() {
+        let mut sorted: Vec<usize> = degrees.iter().map(|&(_, d)| d).collect();
+        sorted.sort_unstable();
+        let p = degree_percentile.clamp(0.0, 100.0);
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil().max(1.0) as usize;
+        let threshold = sorted[rank.min(sorted.len()) - 1];
+
+        for (id, degree) in degrees {
+            if degree > threshold {
+                anomalies.push(node_anomaly(
+                    AnomalyKind::HighDegree,
+                    Severity::Info,
+                    id,
+                    None,
+                    format!(
+                        "degree {} above p{} threshold of {}",
+                        degree, degree_percentile, threshold
+                    ),
+                ));
+            }
+        }
+    }
+
+    anomalies.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then(a.kind.cmp(&b.kind))
+            .then(a.node_id.cmp(&b.node_id))
+            .then(a.rel_type.cmp(&b.rel_type))
+    });
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Edge;
+    use crate::test_util::edge;
+
+    fn kinds(anomalies: &[Anomaly]) -> Vec<AnomalyKind> {
+        anomalies.iter().map(|a| a.kind).collect()
+    }
+
+    #[test]
+    fn test_clean_cycle_has_no_anomalies() {
+        let mut g = Graph::new();
+        g.load_edges((0..10).map(|i| edge(i, (i + 1) % 10, "NEXT")));
+        assert!(detect_anomalies(&g, 99.0).is_empty());
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        assert!(detect_anomalies(&Graph::new(), 95.0).is_empty());
+    }
+
+    #[test]
+    fn test_self_loop_and_singleton_rel_type() {
+        let mut g = Graph::new();
+        g.load_edges((0..10).map(|i| edge(i, (i + 1) % 10, "NEXT")));
+        g.load_edges(vec![edge(3, 3, "REFERS"), edge(3, 3, "REFERS"), edge(4, 5, "TYPO")]);
+
+        let found = detect_anomalies(&g, 100.0);
+        assert_eq!(kinds(&found), vec![AnomalyKind::SelfLoop, AnomalyKind::SingletonRelType]);
+
+        assert_eq!(found[0].node_id, Some(3));
+        assert_eq!(found[0].rel_type.as_deref(), Some("REFERS"));
+        assert!(found[0].detail.starts_with("2 "));
+        assert_eq!(found[1].node_id, None);
+        assert_eq!(found[1].rel_type.as_deref(), Some("TYPO"));
+    }
+
+    #[test]
+    fn test_duplicate_app_id_is_error() {
+        let mut g = Graph::new();
+        g.add_node(1, "Concept".to_string(), Some("c-1".to_string()));
+        g.add_node(2, "Concept".to_string(), Some("c-1".to_string()));
+        g.add_node(3, "Concept".to_string(), Some("c-3".to_string()));
+        let rt = g.intern_rel_type("R");
+        g.add_edge(1, 3, rt, Edge::NO_CONFIDENCE);
+        g.add_edge(2, 3, rt, Edge::NO_CONFIDENCE);
+
+        let found = detect_anomalies(&g, 100.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, AnomalyKind::DuplicateAppId);
+        assert_eq!(found[0].severity, Severity::Error);
+        assert_eq!(found[0].app_id.as_deref(), Some("c-1"));
+        assert_eq!(found[0].node_id, g.resolve_app_id("c-1"));
+        assert!(found[0].detail.contains("1, 2"));
+    }
+
+    #[test]
+    fn test_high_degree_percentile() {
+        // Hub 0 with 20 leaves: leaves have degree 1, hub 20
+        let mut g = Graph::new();
+        g.load_edges((1..=20).map(|i| edge(0, i, "HAS")));
+
+        let found = detect_anomalies(&g, 90.0);
+        assert_eq!(kinds(&found), vec![AnomalyKind::HighDegree]);
+        assert_eq!(found[0].node_id, Some(0));
+        assert_eq!(found[0].severity, Severity::Info);
+
+        // At p100 nothing is strictly above the maximum
+        assert!(detect_anomalies(&g, 100.0).is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+//! Backing storage for the frozen CSR arrays: an owned Vec, or with the
+//! `mmap` feature a typed window into a memory-mapped file.
+
+use std::ops::Deref;
+
+/// A read-mostly array. Mutation goes through `make_mut`, which copies a
+/// mapped buffer onto the heap first.
+pub(crate) enum Buf<T> {
+    Owned(Vec<T>),
+    #[cfg(feature = "mmap")]
+    Mapped {
+        map: std::sync::Arc<memmap2::Mmap>,
+        offset: usize,
+        len: usize,
+        _marker: std::marker::PhantomData<T>,
+    },
+}
+
+impl<T> Buf<T> {
+    /// `len` values of `T` starting `offset` bytes into `map`.
+    ///
+    /// # Safety
+    /// The range must lie inside the map, `offset` must be aligned for `T`,
+    /// and every bit pattern of the bytes must be a valid `T`.
+    #[cfg(feature = "mmap")]
+    pub(crate) unsafe fn mapped(
+        map: std::sync::Arc<memmap2::Mmap>,
+        offset: usize,
+        len: usize,
+    ) -> Self {
+        debug_assert!(offset + len * std::mem::size_of::<T>() <= map.len());
+        debug_assert_eq!(
+            map.as_ptr()
+                .add(offset)
+                .align_offset(std::mem::align_of::<T>()),
+            0
+        );
+        Buf::Mapped {
+            map,
+            offset,
+            len,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Heap capacity in elements. Mapped pages belong to the page cache,
+    /// not the process heap, so a mapped buffer reports 0.
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            Buf::Owned(v) => v.capacity(),
+            #[cfg(feature = "mmap")]
+            Buf::Mapped { .. } => 0,
+        }
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        match self {
+            Buf::Owned(v) => v.shrink_to_fit(),
+            #[cfg(feature = "mmap")]
+            Buf::Mapped { .. } => {}
+        }
+    }
+}
+
+impl<T: Clone> Buf<T> {
+    /// The values as a mutable Vec, copying a mapped buffer first.
+    pub(crate) fn make_mut(&mut self) -> &mut Vec<T> {
+        #[cfg(feature = "mmap")]
+        if let Buf::Mapped { .. } = self {
+            *self = Buf::Owned(self.to_vec());
+        }
+        match self {
+            Buf::Owned(v) => v,
+            #[cfg(feature = "mmap")]
+            Buf::Mapped { .. } => unreachable!("copied above"),
+        }
+    }
+}
+
+impl<T> Default for Buf<T> {
+    fn default() -> Self {
+        Buf::Owned(Vec::new())
+    }
+}
+
+impl<T> From<Vec<T>> for Buf<T> {
+    fn from(v: Vec<T>) -> Self {
+        Buf::Owned(v)
+    }
+}
+
+impl<T> Deref for Buf<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Buf::Owned(v) => v,
+            #[cfg(feature = "mmap")]
+            Buf::Mapped {
+                map, offset, len, ..
+            } => {
+                // Safety: range, alignment and validity were promised to
+                // `Buf::mapped`; the Arc keeps the mapping alive for `&self`.
+                unsafe { std::slice::from_raw_parts(map.as_ptr().add(*offset).cast::<T>(), *len) }
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use crate::graph::{EdgeRecord, Graph, NodeId, RelTypeId};
+
+/// What `GraphBuilder` loaded and what it turned away.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuildStats {
+    /// Edges added to the graph.
+    pub edges_loaded: usize,
+    /// Repeats of an already loaded (from, to, rel_type) edge, dropped with
+    /// `dedupe_edges`.
+    pub duplicate_edges: usize,
+    /// Edges from a node to itself, dropped with `reject_self_loops`.
+    pub self_loops: usize,
+    /// Edges with an endpoint never passed to `add_node`, dropped with
+    /// `require_nodes`.
+    pub missing_endpoints: usize,
+}
+
+impl BuildStats {
+    /// Edges dropped for any reason.
+    pub fn rejected(&self) -> usize {
+        self.duplicate_edges + self.self_loops + self.missing_endpoints
+    }
+}
+
+/// Loads a graph with ingestion checks that `Graph::load_edges` skips.
+///
+/// `load_edges` takes every record as is, so a buggy extraction shows up
+/// as quietly doubled edge weights or phantom nodes. Each check here drops
+/// the offending edge and counts it in `BuildStats`, which callers can
+/// treat as an error or log. With every check off the builder loads
+/// exactly what `load_edges` would.
+#[derive(Default)]
+pub struct GraphBuilder {
+    graph: Graph,
+    dedupe_edges: bool,
+    reject_self_loops: bool,
+    require_nodes: bool,
+    seen: HashSet<(NodeId, NodeId, RelTypeId)>,
+    stats: BuildStats,
+}
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-size for the expected graph, as `Graph::with_capacity`.
+    pub fn with_capacity(node_count: usize, edge_count: usize) -> Self {
+        Self {
+            graph: Graph::with_capacity(node_count, edge_count),
+            ..Self::default()
+        }
+    }
+
+    /// Keep only the first edge per (from, to, rel_type). Later copies are
+    /// dropped along with their weight and properties; the kept edge takes
+    /// the highest confidence among them, as with `Graph::dedupe_edges`.
+    pub fn dedupe_edges(mut self, enabled: bool) -> Self {
+        self.dedupe_edges = enabled;
+        self
+    }
+
+    pub fn reject_self_loops(mut self, enabled: bool) -> Self {
+        self.reject_self_loops = enabled;
+        self
+    }
+
+    /// Accept only edges whose endpoints were both registered with
+    /// `add_node` beforehand, instead of creating nodes from the record's
+    /// labels.
+    pub fn require_nodes(mut self, enabled: bool) -> Self {
+        self.require_nodes = enabled;
+        self
+    }
+
+    /// Register a node, as `Graph::add_node`.
+    pub fn add_node(&mut self, id: NodeId, label: String, app_id: Option<String>) {
+        self.graph.add_node(id, label, app_id);
+    }
+
+    /// Check and load edges. May be called repeatedly.
+    pub fn add_edges<I>(&mut self, edges: I)
+    where
+        I: IntoIterator<Item = EdgeRecord>,
+    {
+        for rec in edges {
+            self.add_edge(rec);
+        }
+    }
+
+    /// Check and load one edge. Returns false if a check rejected it.
+    pub fn add_edge(&mut self, rec: EdgeRecord) -> bool {
+        if self.reject_self_loops && rec.from_id == rec.to_id {
+            self.stats.self_loops += 1;
+            return false;
+        }
+        if self.require_nodes
+            && (self.graph.node(rec.from_id).is_none() || self.graph.node(rec.to_id).is_none())
+        {
+            self.stats.missing_endpoints += 1;
+            return false;
+        }
+        if self.dedupe_edges {
+            let rel_type = self.graph.intern_rel_type(&rec.rel_type);
+            if !self.seen.insert((rec.from_id, rec.to_id, rel_type)) {
+                self.graph
+                    .raise_confidence(rec.from_id, rec.to_id, rel_type, rec.confidence);
+                self.stats.duplicate_edges += 1;
+                return false;
+            }
+        }
+        self.graph.load_record(rec);
+        self.stats.edges_loaded += 1;
+        true
+    }
+
+    /// Counts so far.
+    pub fn stats(&self) -> BuildStats {
+        self.stats
+    }
+
+    /// Finish: freeze the graph and hand it over with the final counts.
+    pub fn build(self) -> (Graph, BuildStats) {
+        let mut graph = self.graph;
+        graph.freeze();
+        (graph, self.stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(from: NodeId, to: NodeId, rel: &str, confidence: f32) -> EdgeRecord {
+        EdgeRecord {
+            confidence,
+            ..crate::test_util::edge(from, to, rel)
+        }
+    }
+
+    #[test]
+    fn test_builder_checks_and_stats() {
+        let records = || {
+            vec![
+                edge(1, 2, "A", 0.9),
+                edge(1, 2, "A", 0.1),
+                edge(1, 2, "B", 0.5),
+                edge(2, 2, "A", 0.5),
+                edge(2, 9, "A", 0.5),
+            ]
+        };
+
+        // No checks: same as load_edges
+        let mut plain = GraphBuilder::new();
+        plain.add_edges(records());
+        let (g, stats) = plain.build();
+        assert!(g.is_frozen());
+        assert_eq!((g.edge_count(), g.node_count()), (5, 3));
+        assert_eq!(stats.edges_loaded, 5);
+        assert_eq!(stats.rejected(), 0);
+
+        let mut strict = GraphBuilder::new()
+            .dedupe_edges(true)
+            .reject_self_loops(true)
+            .require_nodes(true);
+        strict.add_node(1, "Node".to_string(), None);
+        strict.add_node(2, "Node".to_string(), None);
+        strict.add_edges(records());
+        assert_eq!(strict.stats().edges_loaded, 2);
+        let (g, stats) = strict.build();
+        assert_eq!((g.edge_count(), g.node_count()), (2, 2));
+        assert_eq!(
+            stats,
+            BuildStats {
+                edges_loaded: 2,
+                duplicate_edges: 1,
+                self_loops: 1,
+                missing_endpoints: 1,
+            }
+        );
+        // The first copy of a duplicate is the one kept
+        let a = g.rel_type_id("A").unwrap();
+        let kept = g.neighbors_out(1).iter().find(|e| e.rel_type == a).unwrap();
+        assert_eq!(kept.confidence, 0.9);
+        assert!(g.node(9).is_none());
+
+        // A later, more confident copy raises the kept edge's confidence
+        let mut rising = GraphBuilder::new().dedupe_edges(true);
+        rising.add_edges(vec![edge(1, 2, "A", 0.2), edge(1, 2, "A", 0.7)]);
+        let (g, _) = rising.build();
+        assert_eq!(g.neighbors_out(1).first().unwrap().confidence, 0.7);
+        assert_eq!(g.neighbors_in(2).first().unwrap().confidence, 0.7);
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::{Graph, NodeId};
+use crate::interrupt;
+
+/// Betweenness of one connection between two nodes.
+#[derive(Debug, Clone)]
+pub struct EdgeBetweenness {
+    /// Smaller endpoint ID.
+    pub from_id: NodeId,
+    pub from_label: String,
+    pub from_app_id: Option<String>,
+    /// Larger endpoint ID.
+    pub to_id: NodeId,
+    pub to_label: String,
+    pub to_app_id: Option<String>,
+    /// Distinct rel types on the edges between the two nodes (either direction), sorted.
+    pub rel_types: Vec<String>,
+    /// Number of shortest paths through this connection, summed over all
+    /// unordered node pairs (fractional when a pair has several).
+    pub score: f64,
+}
+
+/// A node scored as a potential broker between weakly linked regions.
+#[derive(Debug, Clone)]
+pub struct Broker {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Distinct neighbors (edges undirected, parallel edges merged).
+    pub degree: usize,
+    /// Shortest paths through the node, summed over unordered pairs of
+    /// other nodes (fractional when a pair has several).
+    pub betweenness: f64,
+    /// `(1 / degree) / Σ 1 / degree(neighbor)`: high when the node is less
+    /// connected than the nodes it links.
+    pub bridging_coefficient: f64,
+    /// `betweenness × bridging_coefficient`.
+    pub bridge_score: f64,
+    /// Pieces the node's component falls into if the node is removed
+    /// (1 = not a cut vertex).
+    pub components_joined: usize,
+}
+
+/// Edge betweenness centrality (Brandes, accumulating on edges).
+///
+/// The graph is treated as simple and undirected: parallel edges and edges
+/// in both directions between the same two nodes form one connection, and
+/// self-loops are ignored. Each connection's score is the sum over node pairs
+/// `{s, t}` of the fraction of shortest s–t paths that use it. High scores
+/// mark bridges between otherwise weakly linked regions — the connections
+/// whose removal fragments the graph most.
+///
+/// Exact: one BFS per node, O(V·E). If `top_n` is 0, returns all
+/// connections; otherwise the top N by score (ties by endpoint IDs).
+pub fn edge_betweenness(graph: &Graph, top_n: usize) -> Vec<EdgeBetweenness> {
+    let SimpleGraph { ids, connections, adj } = SimpleGraph::build(graph);
+    let (_, scores) = brandes(&adj, connections.len());
+
+    let name = |id: NodeId| {
+        let info = graph.node(id);
+        (
+            graph.node_label(id).unwrap_or_default().to_string(),
+            info.and_then(|i| i.app_id.clone()),
+        )
+    };
+    let mut results: Vec<EdgeBetweenness> = connections
+        .iter()
+        .zip(&scores)
+        .map(|(&(a, b), &score)| {
+            let (from_id, to_id) = (ids[a], ids[b]);
+            let mut rel_types: Vec<String> = graph
+                .neighbors_all(from_id)
+                .filter(|(e, _)| e.target == to_id)
+                .filter_map(|(e, _)| graph.rel_type_name(e.rel_type).map(|s| s.to_string()))
+                .collect();
+            rel_types.sort_unstable();
+            rel_types.dedup();
+            let (from_label, from_app_id) = name(from_id);
+            let (to_label, to_app_id) = name(to_id);
+            EdgeBetweenness {
+                from_id,
+                from_label,
+                from_app_id,
+                to_id,
+                to_label,
+                to_app_id,
+                rel_types,
+                // Every unordered pair was counted from both ends
+                score: score / 2.0,
+            }
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.from_id.cmp(&b.from_id))
+            .then(a.to_id.cmp(&b.to_id))
+    });
+    if top_n > 0 && top_n < results.len() {
+        results.truncate(top_n);
+    }
+    results
+}
+
+/// Broker report: nodes with high betweenness but low degree.
+///
+/// Ranks nodes by bridging centrality (Hwang et al.): betweenness weighted by
+/// the bridging coefficient, which favors nodes whose neighbors are better
+/// connected than they are. These are the weak ties between otherwise
+/// separate regions. `components_joined` counts how many pieces the node's
+/// removal would leave, found in one articulation-point pass.
+///
+/// Edges are undirected and simple, as in `edge_betweenness`; the cost is
+/// the same O(V·E). Nodes scoring 0 are omitted. If `top_n` is 0, returns
+/// all; otherwise the top N by score (ties by node ID).
+pub fn brokers(graph: &Graph, top_n: usize) -> Vec<Broker> {
+    let SimpleGraph { ids, connections, adj } = SimpleGraph::build(graph);
+    let (betweenness, _) = brandes(&adj, connections.len());
+    let pieces = cut_pieces(&adj);
+
+    let mut results: Vec<Broker> = (0..ids.len())
+        .filter(|&v| betweenness[v] > 0.0)
+        .map(|v| {
+            let degree = adj[v].len();
+            let inverse_sum: f64 = adj[v].iter().map(|&(u, _)| 1.0 / adj[u].len() as f64).sum();
+            let bridging_coefficient = (1.0 / degree as f64) / inverse_sum;
+            // Every unordered pair was counted from both ends
+            let betweenness = betweenness[v] / 2.0;
+            let info = graph.node(ids[v]);
+            Broker {
+                node_id: ids[v],
+                label: graph.node_label(ids[v]).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                degree,
+                betweenness,
+                bridging_coefficient,
+                bridge_score: betweenness * bridging_coefficient,
+                components_joined: pieces[v],
+            }
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.bridge_score
+            .total_cmp(&a.bridge_score)
+            .then(a.node_id.cmp(&b.node_id))
+    });
+    if top_n > 0 && top_n < results.len() {
+        results.truncate(top_n);
+    }
+    results
+}
+
+/// Undirected simple view of the graph over dense indices.
+struct SimpleGraph {
+    /// Dense index → node ID, ascending.
+    ids: Vec<NodeId>,
+    /// Connection index → endpoints (smaller index first).
+    connections: Vec<(usize, usize)>,
+    /// `adj[v]` = (neighbor, connection index).
+    adj: Vec<Vec<(usize, usize)>>,
+}
+
+impl SimpleGraph {
+    /// Merges parallel and reverse edges into one connection; drops self-loops.
+    fn build(graph: &Graph) -> Self {
+        let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+        ids.sort_unstable();
+        let index: HashMap<NodeId, usize> =
+            ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let mut connections: Vec<(usize, usize)> = Vec::new();
+        let mut connection_of: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut adj: Vec<Vec<(usize, usize)>> = vec![Vec::new(); ids.len()];
+        for (v, &id) in ids.iter().enumerate() {
+            let neighbors: HashSet<usize> = graph
+                .neighbors_all(id)
+                .filter_map(|(e, _)| index.get(&e.target).copied())
+                .filter(|&u| u != v)
+                .collect();
+            for u in neighbors {
+                let key = (v.min(u), v.max(u));
+                let c = *connection_of.entry(key).or_insert_with(|| {
+                    connections.push(key);
+                    connections.len() - 1
+                });
+                adj[v].push((u, c));
+            }
+        }
+        SimpleGraph { ids, connections, adj }
+    }
+}
+
+/// Brandes' algorithm over an unweighted undirected graph: one BFS per node,
+/// O(V·E). Returns (node scores, connection scores), each counting every
+/// unordered pair twice.
+fn brandes(adj: &[Vec<(usize, usize)>], connection_count: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = adj.len();
+    let mut node_scores = vec![0.0f64; n];
+    let mut scores = vec![0.0f64; connection_count];
+    let mut sigma = vec![0.0f64; n];
+    let mut dist = vec![u32::MAX; n];
+    let mut delta = vec![0.0f64; n];
+    let mut preds: Vec<Vec<(usize, usize)>> = vec![Vec::new(); n];
+    let mut stack: Vec<usize> = Vec::with_capacity(n);
+    let mut queue: VecDeque<usize> = VecDeque::new();
+
+    for s in 0..n {
+        for &v in &stack {
+            sigma[v] = 0.0;
+            dist[v] = u32::MAX;
+            delta[v] = 0.0;
+            preds[v].clear();
+        }
+        stack.clear();
+
+        sigma[s] = 1.0;
+        dist[s] = 0;
+        queue.push_back(s);
+        while let Some(v) = queue.pop_front() {
+            interrupt::check();
+            stack.push(v);
+            for &(w, c) in &adj[v] {
+                if dist[w] == u32::MAX {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    sigma[w] += sigma[v];
+                    preds[w].push((v, c));
+                }
+            }
+        }
+
+        // Back-propagate dependencies, crediting each predecessor edge
+        for &w in stack.iter().rev() {
+            for &(v, c) in &preds[w] {
+                let credit = sigma[v] / sigma[w] * (1.0 + delta[w]);
+                scores[c] += credit;
+                delta[v] += credit;
+            }
+            if w != s {
+                node_scores[w] += delta[w];
+            }
+        }
+    }
+    (node_scores, scores)
+}
+
+/// For each node, the number of connected pieces its component splits into
+/// when the node is removed (Hopcroft–Tarjan articulation points, iterative).
+/// Isolated nodes leave 0 pieces.
+fn cut_pieces(adj: &[Vec<(usize, usize)>]) -> Vec<usize> {
+    let n = adj.len();
+    let mut disc = vec![u32::MAX; n];
+    let mut low = vec![0u32; n];
+    let mut pieces = vec![0usize; n];
+    let mut time = 0u32;
+
+    for root in 0..n {
+        if disc[root] != u32::MAX {
+            continue;
+        }
+        disc[root] = time;
+        low[root] = time;
+        time += 1;
+        // Frames: (node, parent, next neighbor index)
+        let mut frames: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+        while let Some(&mut (v, parent, ref mut next)) = frames.last_mut() {
+            if let Some(&(w, _)) = adj[v].get(*next) {
+                *next += 1;
+                if disc[w] == u32::MAX {
+                    disc[w] = time;
+                    low[w] = time;
+                    time += 1;
+                    frames.push((w, Some(v), 0));
+                } else if Some(w) != parent {
+                    low[v] = low[v].min(disc[w]);
+                }
+                continue;
+            }
+            frames.pop();
+            if let Some(p) = parent {
+                low[p] = low[p].min(low[v]);
+                // v's subtree reaches nothing above p: it splits off
+                if low[v] >= disc[p] {
+                    pieces[p] += 1;
+                }
+                // v itself keeps the piece holding its parent
+                pieces[v] += 1;
+            }
+        }
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn score(results: &[EdgeBetweenness], a: NodeId, b: NodeId) -> f64 {
+        results
+            .iter()
+            .find(|r| (r.from_id, r.to_id) == (a.min(b), a.max(b)))
+            .map(|r| r.score)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_chain_betweenness() {
+        // 0-1-2-3: edge (i, i+1) carries (i+1) * (3-i) pairs
+        let mut g = Graph::new();
+        g.load_edges((0..3).map(|i| edge(i, i + 1, "NEXT")));
+        let r = edge_betweenness(&g, 0);
+        assert_eq!(r.len(), 3);
+        assert_eq!(score(&r, 0, 1), 3.0);
+        assert_eq!(score(&r, 1, 2), 4.0);
+        assert_eq!(score(&r, 2, 3), 3.0);
+        assert_eq!((r[0].from_id, r[0].to_id), (1, 2));
+    }
+
+    #[test]
+    fn test_bridge_between_triangles_ranks_first() {
+        // Triangles {0,1,2} and {3,4,5} joined by bridge 2-3
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(3, 4, "R"),
+            edge(4, 5, "R"),
+            edge(5, 3, "R"),
+            edge(2, 3, "BRIDGE"),
+        ]);
+        let r = edge_betweenness(&g, 1);
+        assert_eq!(r.len(), 1);
+        assert_eq!((r[0].from_id, r[0].to_id), (2, 3));
+        assert_eq!(r[0].rel_types, vec!["BRIDGE".to_string()]);
+        // All 9 cross pairs use the bridge
+        assert_eq!(r[0].score, 9.0);
+    }
+
+    #[test]
+    fn test_parallel_and_reverse_edges_merge() {
+        // Square 0-1-2-3-0 with doubled 0-1: two shortest paths between opposite corners
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "A"),
+            edge(1, 0, "B"),
+            edge(1, 2, "A"),
+            edge(2, 3, "A"),
+            edge(3, 0, "A"),
+            edge(2, 2, "SELF"),
+        ]);
+        let r = edge_betweenness(&g, 0);
+        assert_eq!(r.len(), 4);
+        let merged = r.iter().find(|e| (e.from_id, e.to_id) == (0, 1)).unwrap();
+        assert_eq!(merged.rel_types, vec!["A".to_string(), "B".to_string()]);
+        // Each side: 1 adjacent pair + half of 2 opposite pairs
+        for e in &r {
+            assert!((e.score - 2.0).abs() < 1e-9);
+        }
+        assert!(edge_betweenness(&Graph::new(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_broker_between_triangles_ranks_first() {
+        // Triangles {0,1,2} and {4,5,6} linked through the broker 3
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(2, 3, "R"),
+            edge(3, 4, "R"),
+            edge(4, 5, "R"),
+            edge(5, 6, "R"),
+            edge(6, 4, "R"),
+        ]);
+        let r = brokers(&g, 0);
+        let ids: Vec<NodeId> = r.iter().map(|b| b.node_id).collect();
+        assert_eq!(ids, vec![3, 2, 4]);
+        let broker = &r[0];
+        assert_eq!(broker.degree, 2);
+        // All 9 cross pairs pass through 3
+        assert_eq!(broker.betweenness, 9.0);
+        assert!((broker.bridging_coefficient - 0.75).abs() < 1e-9);
+        assert!((broker.bridge_score - 6.75).abs() < 1e-9);
+        assert_eq!(broker.components_joined, 2);
+        assert_eq!(r[1].betweenness, 8.0);
+        assert_eq!(r[1].components_joined, 2);
+        assert_eq!(brokers(&g, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_broker_components_joined() {
+        // Star hub 0 with 4 spokes, and a separate square 10-11-12-13
+        let mut g = Graph::new();
+        g.load_edges((1..=4).map(|i| edge(0, i, "R")));
+        g.load_edges((10..14).map(|i| edge(i, 10 + (i - 9) % 4, "R")));
+        let r = brokers(&g, 0);
+        let hub = r.iter().find(|b| b.node_id == 0).unwrap();
+        assert_eq!(hub.betweenness, 6.0);
+        assert_eq!(hub.components_joined, 4);
+        // Square corners: half of one opposite pair each, no cut vertices
+        let corners: Vec<&Broker> = r.iter().filter(|b| b.node_id >= 10).collect();
+        assert_eq!(corners.len(), 4);
+        for c in corners {
+            assert!((c.betweenness - 0.5).abs() < 1e-9);
+            assert_eq!(c.components_joined, 1);
+        }
+        // Spokes carry no paths
+        assert!(r.iter().all(|b| !(1..=4).contains(&b.node_id)));
+        assert!(brokers(&Graph::new(), 0).is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{Graph, NodeId};
+use crate::similarity::undirected_neighbors;
+
+/// Enumerate maximal cliques with at least `min_size` nodes.
+///
+/// Edge direction, parallel edges and self-loops are ignored. Uses
+/// Bron–Kerbosch with Tomita pivoting, seeded in degeneracy order so each
+/// top-level call only sees a node's later neighbors; branches that cannot
+/// reach `min_size` are pruned.
+///
+/// The number of maximal cliques can be exponential in the worst case, so
+/// enumeration stops after `max_cliques` results (0 = no limit). Each clique
+/// is sorted by node ID; cliques are sorted by size (descending), then by
+/// their node IDs.
+pub fn maximal_cliques(graph: &Graph, min_size: usize, max_cliques: usize) -> Vec<Vec<NodeId>> {
+    let adj: HashMap<NodeId, HashSet<NodeId>> = graph
+        .nodes_iter()
+        .map(|(&id, _)| (id, undirected_neighbors(graph, id)))
+        .collect();
+
+    let limit = if max_cliques == 0 { usize::MAX } else { max_cliques };
+    let mut cliques: Vec<Vec<NodeId>> = Vec::new();
+
+    let order = degeneracy_order(&adj);
+    let position: HashMap<NodeId, usize> = order.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+    for &v in &order {
+        if cliques.len() >= limit {
+            break;
+        }
+        let neighbors = &adj[&v];
+        if neighbors.len() + 1 < min_size {
+            continue;
+        }
+        let (p, x): (Vec<NodeId>, Vec<NodeId>) = neighbors
+            .iter()
+            .partition(|&&u| position[&u] > position[&v]);
+        let mut r = vec![v];
+        bron_kerbosch(&adj, &mut r, p, x, min_size, limit, &mut cliques);
+    }
+
+    for c in &mut cliques {
+        c.sort_unstable();
+    }
+    cliques.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    cliques
+}
+
+fn bron_kerbosch(
+    adj: &HashMap<NodeId, HashSet<NodeId>>,
+    r: &mut Vec<NodeId>,
+    mut p: Vec<NodeId>,
+    mut x: Vec<NodeId>,
+    min_size: usize,
+    limit: usize,
+    out: &mut Vec<Vec<NodeId>>,
+) {
+    if p.is_empty() {
+        if x.is_empty() && r.len() >= min_size {
+            out.push(r.clone());
+        }
+        return;
+    }
+    if r.len() + p.len() < min_size {
+        return;
+    }
+
+    // Pivot: the node in P ∪ X with the most neighbors in P
+    let pivot = p
+        .iter()
+        .chain(x.iter())
+        .copied()
+        .max_by_key(|u| p.iter().filter(|w| adj[u].contains(w)).count())
+        .expect("P is non-empty");
+    let candidates: Vec<NodeId> = p.iter().copied().filter(|w| !adj[&pivot].contains(w)).collect();
+
+    for v in candidates {
+        if out.len() >= limit {
+            return;
+        }
+        let nv = &adj[&v];
+        let p_next: Vec<NodeId> = p.iter().copied().filter(|w| nv.contains(w)).collect();
+        let x_next: Vec<NodeId> = x.iter().copied().filter(|w| nv.contains(w)).collect();
+        r.push(v);
+        bron_kerbosch(adj, r, p_next, x_next, min_size, limit, out);
+        r.pop();
+        p.retain(|&w| w != v);
+        x.push(v);
+    }
+}
+
+/// Order nodes by repeatedly removing a minimum-degree node.
+fn degeneracy_order(adj: &HashMap<NodeId, HashSet<NodeId>>) -> Vec<NodeId> {
+    let mut degree: HashMap<NodeId, usize> = adj.iter().map(|(&v, n)| (v, n.len())).collect();
+    let max_degree = degree.values().copied().max().unwrap_or(0);
+
+    // Bucket queue keyed by current degree; stale entries are skipped on pop
+    let mut buckets: Vec<Vec<NodeId>> = vec![Vec::new(); max_degree + 1];
+    let mut ids: Vec<NodeId> = adj.keys().copied().collect();
+    ids.sort_unstable();
+    for &v in &ids {
+        buckets[degree[&v]].push(v);
+    }
+
+    let mut removed: HashSet<NodeId> = HashSet::with_capacity(adj.len());
+    let mut order = Vec::with_capacity(adj.len());
+    let mut d = 0;
+    while order.len() < adj.len() {
+        let Some(v) = buckets[d].pop() else {
+            d += 1;
+            continue;
+        };
+        if removed.contains(&v) || degree[&v] != d {
+            continue;
+        }
+        removed.insert(v);
+        order.push(v);
+        for &u in &adj[&v] {
+            if !removed.contains(&u) {
+                let du = degree.get_mut(&u).expect("neighbor is a node");
+                *du -= 1;
+                buckets[*du].push(u);
+                d = d.min(*du);
+            }
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn complete(g: &mut Graph, nodes: &[NodeId]) {
+        for (i, &a) in nodes.iter().enumerate() {
+            for &b in &nodes[i + 1..] {
+                g.load_edges(vec![edge(a, b, "R")]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cliques_two_overlapping() {
+        // K4 {0,1,2,3} and triangle {3,4,5} sharing node 3, plus pendant edge 5-6
+        let mut g = Graph::new();
+        complete(&mut g, &[0, 1, 2, 3]);
+        complete(&mut g, &[3, 4, 5]);
+        g.load_edges(vec![edge(5, 6, "R")]);
+
+        let all = maximal_cliques(&g, 1, 0);
+        assert_eq!(all, vec![vec![0, 1, 2, 3], vec![3, 4, 5], vec![5, 6]]);
+
+        let big = maximal_cliques(&g, 3, 0);
+        assert_eq!(big, vec![vec![0, 1, 2, 3], vec![3, 4, 5]]);
+
+        assert_eq!(maximal_cliques(&g, 4, 0), vec![vec![0, 1, 2, 3]]);
+        assert!(maximal_cliques(&g, 5, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cliques_ignore_direction_and_duplicates() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 0, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(0, 0, "R"),
+        ]);
+        assert_eq!(maximal_cliques(&g, 2, 0), vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn test_cliques_isolated_and_limit() {
+        let mut g = Graph::new();
+        g.add_node(9, "Node".to_string(), None);
+        g.load_edges((0..6).map(|i| edge(i, i + 1, "R")));
+        // Chain of 7 nodes: 6 maximal 2-cliques; isolated 9 is a 1-clique
+        assert_eq!(maximal_cliques(&g, 2, 0).len(), 6);
+        assert_eq!(maximal_cliques(&g, 1, 0).len(), 7);
+        assert_eq!(maximal_cliques(&g, 2, 3).len(), 3);
+        assert!(maximal_cliques(&Graph::new(), 1, 0).is_empty());
+    }
+
+    #[test]
+    fn test_cliques_complete_graph() {
+        let mut g = Graph::new();
+        complete(&mut g, &[0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(maximal_cliques(&g, 3, 0), vec![(0..7).collect::<Vec<NodeId>>()]);
+    }
+}
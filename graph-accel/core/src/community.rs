@@ -0,0 +1,634 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::{Graph, NodeId};
+use crate::interrupt;
+
+/// Community detection algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommunityAlgorithm {
+    /// Greedy modularity optimization (local moving + aggregation).
+    Louvain,
+    /// Louvain with the Leiden refinement step: communities are refined
+    /// before aggregation so every returned community is connected.
+    Leiden,
+}
+
+/// Community assignment for a single node.
+#[derive(Debug, Clone)]
+pub struct CommunityAssignment {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Community index. 0 is the largest community.
+    pub community: u32,
+    pub community_size: u32,
+}
+
+/// Result of community detection.
+#[derive(Debug)]
+pub struct CommunityResult {
+    /// One entry per node, sorted by community then node ID.
+    pub assignments: Vec<CommunityAssignment>,
+    pub community_count: usize,
+    /// Modularity of the returned partition (at the requested resolution).
+    pub modularity: f64,
+}
+
+/// Safety cap on aggregation levels. Each level shrinks the network, so real
+/// graphs converge in well under ten levels.
+const MAX_LEVELS: usize = 32;
+
+/// Gains below this are treated as zero to avoid oscillating on float noise.
+const GAIN_EPSILON: f64 = 1e-12;
+
+/// Weighted undirected network over dense indices — the working form for
+/// both the input graph and each aggregated level.
+struct Network {
+    /// Neighbor lists with accumulated weights (no self entries).
+    adj: Vec<Vec<(usize, f64)>>,
+    /// Self-loop weight per node (internal edges after aggregation).
+    self_weight: Vec<f64>,
+    /// Weighted degree: sum of adj weights + 2 × self weight.
+    degree: Vec<f64>,
+    /// Sum of all degrees (2m).
+    total_weight: f64,
+}
+
+impl Network {
+    fn from_parts(adj: Vec<Vec<(usize, f64)>>, self_weight: Vec<f64>) -> Self {
+        let degree: Vec<f64> = adj
+            .iter()
+            .zip(&self_weight)
+            .map(|(nbrs, &sw)| nbrs.iter().map(|&(_, w)| w).sum::<f64>() + 2.0 * sw)
+            .collect();
+        let total_weight = degree.iter().sum();
+        Self {
+            adj,
+            self_weight,
+            degree,
+            total_weight,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Collapse each community into a single node. `comm` must be dense (0..count).
+    fn aggregate(&self, comm: &[usize], count: usize) -> Network {
+        let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); count];
+        let mut self_weight = vec![0.0; count];
+
+        for (i, nbrs) in self.adj.iter().enumerate() {
+            let ci = comm[i];
+            self_weight[ci] += self.self_weight[i];
+            for &(j, w) in nbrs {
+                let cj = comm[j];
+                if ci == cj {
+                    // Each undirected edge appears in both endpoint lists
+                    self_weight[ci] += w / 2.0;
+                } else {
+                    *weights[ci].entry(cj).or_insert(0.0) += w;
+                }
+            }
+        }
+
+        let adj = weights
+            .into_iter()
+            .map(|m| {
+                let mut v: Vec<(usize, f64)> = m.into_iter().collect();
+                v.sort_by_key(|&(j, _)| j);
+                v
+            })
+            .collect();
+        Network::from_parts(adj, self_weight)
+    }
+}
+
+/// Partition the graph into communities by modularity optimization.
+///
+/// Edges are treated as undirected with unit weight; parallel edges add
+/// weight. `resolution` scales the null-model term — values above 1.0
+/// favor smaller communities, below 1.0 larger ones.
+///
+/// Node order is sorted by ID before optimization, so results are
+/// deterministic for a given graph.
+pub fn communities(graph: &Graph, algorithm: CommunityAlgorithm, resolution: f64) -> CommunityResult {
+    let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+    ids.sort_unstable();
+
+    if ids.is_empty() {
+        return CommunityResult {
+            assignments: Vec::new(),
+            community_count: 0,
+            modularity: 0.0,
+        };
+    }
+
+    let base = build_network(graph, &ids);
+
+    let membership = match algorithm {
+        CommunityAlgorithm::Louvain => louvain(&base, resolution),
+        CommunityAlgorithm::Leiden => {
+            let coarse = leiden(&base, resolution);
+            split_disconnected(&base, &coarse)
+        }
+    };
+
+    let (membership, sizes) = renumber_by_size(&membership);
+    let modularity = modularity(&base, &membership, resolution);
+
+    let mut assignments: Vec<CommunityAssignment> = ids
+        .iter()
+        .zip(&membership)
+        .map(|(&id, &c)| {
+            let info = graph.node(id);
+            CommunityAssignment {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                community: c as u32,
+                community_size: sizes[c] as u32,
+            }
+        })
+        .collect();
+    assignments.sort_by(|a, b| a.community.cmp(&b.community).then(a.node_id.cmp(&b.node_id)));
+
+    CommunityResult {
+        assignments,
+        community_count: sizes.len(),
+        modularity,
+    }
+}
+
+/// Build the undirected working network from the graph's outgoing edges.
+/// Edges to nodes without metadata are ignored.
+fn build_network(graph: &Graph, ids: &[NodeId]) -> Network {
+    let index: HashMap<NodeId, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let mut weights: Vec<HashMap<usize, f64>> = vec![HashMap::new(); ids.len()];
+    let mut self_weight = vec![0.0; ids.len()];
+
+    for (i, &id) in ids.iter().enumerate() {
+        for edge in graph.neighbors_out(id) {
+            let Some(&j) = index.get(&edge.target) else {
+                continue;
+            };
+            if i == j {
+                self_weight[i] += 1.0;
+            } else {
+                *weights[i].entry(j).or_insert(0.0) += 1.0;
+                *weights[j].entry(i).or_insert(0.0) += 1.0;
+            }
+        }
+    }
+
+    let adj = weights
+        .into_iter()
+        .map(|m| {
+            let mut v: Vec<(usize, f64)> = m.into_iter().collect();
+            v.sort_by_key(|&(j, _)| j);
+            v
+        })
+        .collect();
+    Network::from_parts(adj, self_weight)
+}
+
+/// Louvain: alternate local moving and aggregation until no node moves.
+/// Returns the community of each base-network node.
+fn louvain(base: &Network, resolution: f64) -> Vec<usize> {
+    let mut membership: Vec<usize> = (0..base.len()).collect();
+    let mut level_net: Option<Network> = None;
+
+    for _ in 0..MAX_LEVELS {
+        let net = level_net.as_ref().unwrap_or(base);
+        let mut comm: Vec<usize> = (0..net.len()).collect();
+        if !local_moving(net, &mut comm, resolution) {
+            break;
+        }
+        let count = renumber(&mut comm);
+        for m in membership.iter_mut() {
+            *m = comm[*m];
+        }
+        level_net = Some(net.aggregate(&comm, count));
+    }
+
+    membership
+}
+
+/// Leiden: local moving, then refinement inside each community, then
+/// aggregation on the refined partition (seeded with the coarse one).
+///
+/// The refinement only merges a node into a refined community it has an edge
+/// to, so every aggregated node is a connected set of base nodes.
+fn leiden(base: &Network, resolution: f64) -> Vec<usize> {
+    // Base node → aggregated node at the current level
+    let mut membership: Vec<usize> = (0..base.len()).collect();
+    let mut level_net: Option<Network> = None;
+    // Coarse partition of the current level's nodes
+    let mut comm: Vec<usize> = (0..base.len()).collect();
+
+    for _ in 0..MAX_LEVELS {
+        let net = level_net.as_ref().unwrap_or(base);
+        let moved = local_moving(net, &mut comm, resolution);
+        let mut refined = refine(net, &comm, resolution);
+        let refined_count = renumber(&mut refined);
+
+        if !moved && refined_count == net.len() {
+            break;
+        }
+
+        // Seed the next level's coarse partition from the current one
+        let mut next_comm = vec![0; refined_count];
+        for (i, &r) in refined.iter().enumerate() {
+            next_comm[r] = comm[i];
+        }
+        renumber(&mut next_comm);
+
+        for m in membership.iter_mut() {
+            *m = refined[*m];
+        }
+        level_net = Some(net.aggregate(&refined, refined_count));
+        comm = next_comm;
+    }
+
+    membership.iter().map(|&m| comm[m]).collect()
+}
+
+/// Move nodes between communities while modularity improves.
+/// Returns true if any node changed community.
+fn local_moving(net: &Network, comm: &mut [usize], resolution: f64) -> bool {
+    let n = net.len();
+    let mut tot = vec![0.0; n];
+    for i in 0..n {
+        tot[comm[i]] += net.degree[i];
+    }
+
+    let mut neigh_weight = vec![0.0; n];
+    let mut neigh_comms: Vec<usize> = Vec::new();
+    let mut any_moved = false;
+
+    loop {
+        let mut moved = false;
+        for i in 0..n {
+            interrupt::check();
+            let ci = comm[i];
+            let ki = net.degree[i];
+
+            for &(j, w) in &net.adj[i] {
+                let cj = comm[j];
+                if neigh_weight[cj] == 0.0 {
+                    neigh_comms.push(cj);
+                }
+                neigh_weight[cj] += w;
+            }
+
+            // Remove i from its community before evaluating gains
+            tot[ci] -= ki;
+            let scale = resolution * ki / net.total_weight;
+            let mut best = ci;
+            let mut best_gain = neigh_weight[ci] - tot[ci] * scale;
+            for &c in &neigh_comms {
+                let gain = neigh_weight[c] - tot[c] * scale;
+                if gain > best_gain + GAIN_EPSILON {
+                    best = c;
+                    best_gain = gain;
+                }
+            }
+            tot[best] += ki;
+            comm[i] = best;
+
+            for &c in &neigh_comms {
+                neigh_weight[c] = 0.0;
+            }
+            neigh_comms.clear();
+
+            if best != ci {
+                moved = true;
+                any_moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    any_moved
+}
+
+/// Leiden refinement: starting from singletons, merge each still-singleton
+/// node into the refined community (within its coarse community) with the
+/// best positive modularity gain.
+fn refine(net: &Network, comm: &[usize], resolution: f64) -> Vec<usize> {
+    let n = net.len();
+    let mut refined: Vec<usize> = (0..n).collect();
+    let mut tot: Vec<f64> = net.degree.clone();
+    let mut size = vec![1usize; n];
+
+    let mut neigh_weight = vec![0.0; n];
+    let mut neigh_comms: Vec<usize> = Vec::new();
+
+    for i in 0..n {
+        interrupt::check();
+        // Only singletons move; merged nodes anchor their refined community
+        if size[refined[i]] != 1 {
+            continue;
+        }
+        let ri = refined[i];
+        let ki = net.degree[i];
+
+        for &(j, w) in &net.adj[i] {
+            if comm[j] != comm[i] {
+                continue;
+            }
+            let rj = refined[j];
+            if rj == ri {
+                continue;
+            }
+            if neigh_weight[rj] == 0.0 {
+                neigh_comms.push(rj);
+            }
+            neigh_weight[rj] += w;
+        }
+
+        let scale = resolution * ki / net.total_weight;
+        let mut best = ri;
+        let mut best_gain = 0.0;
+        for &r in &neigh_comms {
+            let gain = neigh_weight[r] - tot[r] * scale;
+            if gain > best_gain + GAIN_EPSILON {
+                best = r;
+                best_gain = gain;
+            }
+        }
+
+        if best != ri {
+            tot[ri] -= ki;
+            size[ri] -= 1;
+            tot[best] += ki;
+            size[best] += 1;
+            refined[i] = best;
+        }
+
+        for &r in &neigh_comms {
+            neigh_weight[r] = 0.0;
+        }
+        neigh_comms.clear();
+    }
+
+    refined
+}
+
+/// Split any community whose members are not connected within the community
+/// into one community per connected piece.
+fn split_disconnected(net: &Network, comm: &[usize]) -> Vec<usize> {
+    let n = net.len();
+    let mut result = vec![usize::MAX; n];
+    let mut next = 0;
+    let mut queue = VecDeque::new();
+
+    for s in 0..n {
+        if result[s] != usize::MAX {
+            continue;
+        }
+        result[s] = next;
+        queue.push_back(s);
+        while let Some(u) = queue.pop_front() {
+            interrupt::check();
+            for &(v, _) in &net.adj[u] {
+                if result[v] == usize::MAX && comm[v] == comm[s] {
+                    result[v] = next;
+                    queue.push_back(v);
+                }
+            }
+        }
+        next += 1;
+    }
+
+    result
+}
+
+/// Renumber community labels densely (0..count) in order of first appearance.
+fn renumber(comm: &mut [usize]) -> usize {
+    let mut map: HashMap<usize, usize> = HashMap::new();
+    for c in comm.iter_mut() {
+        let next = map.len();
+        *c = *map.entry(*c).or_insert(next);
+    }
+    map.len()
+}
+
+/// Renumber so community 0 is the largest (ties by first appearance).
+/// Returns the new membership and the size of each community.
+fn renumber_by_size(comm: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    let mut dense = comm.to_vec();
+    let count = renumber(&mut dense);
+
+    let mut sizes = vec![0usize; count];
+    for &c in &dense {
+        sizes[c] += 1;
+    }
+
+    let mut order: Vec<usize> = (0..count).collect();
+    order.sort_by(|&a, &b| sizes[b].cmp(&sizes[a]).then(a.cmp(&b)));
+    let mut rank = vec![0usize; count];
+    for (new, &old) in order.iter().enumerate() {
+        rank[old] = new;
+    }
+
+    let membership = dense.iter().map(|&c| rank[c]).collect();
+    let sorted_sizes = order.iter().map(|&c| sizes[c]).collect();
+    (membership, sorted_sizes)
+}
+
+/// Newman modularity of a partition of the base network.
+fn modularity(net: &Network, comm: &[usize], resolution: f64) -> f64 {
+    if net.total_weight == 0.0 {
+        return 0.0;
+    }
+    let count = comm.iter().copied().max().map_or(0, |c| c + 1);
+    let mut internal = vec![0.0; count];
+    let mut tot = vec![0.0; count];
+
+    for i in 0..net.len() {
+        let ci = comm[i];
+        tot[ci] += net.degree[i];
+        internal[ci] += 2.0 * net.self_weight[i];
+        for &(j, w) in &net.adj[i] {
+            if comm[j] == ci {
+                internal[ci] += w;
+            }
+        }
+    }
+
+    let m2 = net.total_weight;
+    internal
+        .iter()
+        .zip(&tot)
+        .map(|(&inside, &t)| inside / m2 - resolution * (t / m2) * (t / m2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    use std::collections::HashSet;
+
+    /// Two 5-cliques (0..5 and 5..10) joined by a single bridge edge 4→5.
+    fn make_two_cliques() -> Graph {
+        let mut edges = Vec::new();
+        for base in [0, 5] {
+            for i in 0..5 {
+                for j in (i + 1)..5 {
+                    edges.push(edge(base + i, base + j, "REL"));
+                }
+            }
+        }
+        edges.push(edge(4, 5, "REL"));
+        let mut g = Graph::new();
+        g.load_edges(edges);
+        g
+    }
+
+    fn community_of(result: &CommunityResult, id: NodeId) -> u32 {
+        result
+            .assignments
+            .iter()
+            .find(|a| a.node_id == id)
+            .unwrap()
+            .community
+    }
+
+    /// True if every community is connected (undirected) within itself.
+    fn all_connected(g: &Graph, result: &CommunityResult) -> bool {
+        let by_comm: HashMap<u32, HashSet<NodeId>> =
+            result.assignments.iter().fold(HashMap::new(), |mut m, a| {
+                m.entry(a.community).or_default().insert(a.node_id);
+                m
+            });
+        by_comm.values().all(|members| {
+            let start = *members.iter().next().unwrap();
+            let mut seen = HashSet::from([start]);
+            let mut queue = VecDeque::from([start]);
+            while let Some(u) = queue.pop_front() {
+                for (e, _) in g.neighbors_all(u) {
+                    if members.contains(&e.target) && seen.insert(e.target) {
+                        queue.push_back(e.target);
+                    }
+                }
+            }
+            seen.len() == members.len()
+        })
+    }
+
+    #[test]
+    fn test_louvain_two_cliques() {
+        let g = make_two_cliques();
+        let result = communities(&g, CommunityAlgorithm::Louvain, 1.0);
+        assert_eq!(result.community_count, 2);
+        assert_eq!(result.assignments.len(), 10);
+        assert_eq!(community_of(&result, 0), community_of(&result, 4));
+        assert_eq!(community_of(&result, 5), community_of(&result, 9));
+        assert_ne!(community_of(&result, 0), community_of(&result, 9));
+        assert!(result.modularity > 0.3);
+    }
+
+    #[test]
+    fn test_leiden_two_cliques() {
+        let g = make_two_cliques();
+        let result = communities(&g, CommunityAlgorithm::Leiden, 1.0);
+        assert_eq!(result.community_count, 2);
+        assert_ne!(community_of(&result, 0), community_of(&result, 9));
+        assert!(result.modularity > 0.3);
+        assert!(all_connected(&g, &result));
+    }
+
+    #[test]
+    fn test_leiden_disconnected_components_never_merged() {
+        // Two separate triangles: never one community
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "REL"),
+            edge(1, 2, "REL"),
+            edge(2, 0, "REL"),
+            edge(10, 11, "REL"),
+            edge(11, 12, "REL"),
+            edge(12, 10, "REL"),
+        ]);
+        let result = communities(&g, CommunityAlgorithm::Leiden, 1.0);
+        assert_eq!(result.community_count, 2);
+        assert!(all_connected(&g, &result));
+    }
+
+    #[test]
+    fn test_leiden_communities_connected_on_ring_of_cliques() {
+        // Ring of 6 four-cliques: a classic case where greedy moves can
+        // strand nodes; Leiden must still return connected communities.
+        let mut edges = Vec::new();
+        for c in 0..6 {
+            let base = c * 4;
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    edges.push(edge(base + i, base + j, "REL"));
+                }
+            }
+            edges.push(edge(base + 3, ((c + 1) % 6) * 4, "REL"));
+        }
+        let mut g = Graph::new();
+        g.load_edges(edges);
+
+        let result = communities(&g, CommunityAlgorithm::Leiden, 1.0);
+        assert!(all_connected(&g, &result));
+        assert!(result.community_count >= 2);
+    }
+
+    #[test]
+    fn test_community_sizes_sorted() {
+        // Clique of 6 plus a separate pair: community 0 is the larger one
+        let mut edges = Vec::new();
+        for i in 0..6 {
+            for j in (i + 1)..6 {
+                edges.push(edge(i, j, "REL"));
+            }
+        }
+        edges.push(edge(100, 101, "REL"));
+        let mut g = Graph::new();
+        g.load_edges(edges);
+
+        let result = communities(&g, CommunityAlgorithm::Louvain, 1.0);
+        assert_eq!(result.community_count, 2);
+        assert_eq!(community_of(&result, 0), 0);
+        assert_eq!(community_of(&result, 100), 1);
+        let first = result.assignments.iter().find(|a| a.node_id == 0).unwrap();
+        assert_eq!(first.community_size, 6);
+    }
+
+    #[test]
+    fn test_isolated_nodes_are_singletons() {
+        let mut g = Graph::new();
+        g.add_node(1, "A".into(), None);
+        g.add_node(2, "B".into(), None);
+        let result = communities(&g, CommunityAlgorithm::Leiden, 1.0);
+        assert_eq!(result.community_count, 2);
+        assert_eq!(result.modularity, 0.0);
+    }
+
+    #[test]
+    fn test_communities_empty() {
+        let g = Graph::new();
+        let result = communities(&g, CommunityAlgorithm::Louvain, 1.0);
+        assert!(result.assignments.is_empty());
+        assert_eq!(result.community_count, 0);
+    }
+
+    #[test]
+    fn test_communities_deterministic() {
+        let g = make_two_cliques();
+        let a = communities(&g, CommunityAlgorithm::Leiden, 1.0);
+        let b = communities(&g, CommunityAlgorithm::Leiden, 1.0);
+        let ca: Vec<u32> = a.assignments.iter().map(|x| x.community).collect();
+        let cb: Vec<u32> = b.assignments.iter().map(|x| x.community).collect();
+        assert_eq!(ca, cb);
+    }
+}
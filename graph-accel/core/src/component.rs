@@ -0,0 +1,128 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::{Graph, NodeId};
+use crate::interrupt;
+
+/// Weakly connected components: node → component, plus component sizes.
+///
+/// Edge direction is ignored. Components are numbered by size, largest
+/// first, so component 0 is the main body of the graph; ties go to the
+/// component holding the lower node ID. Built once with a BFS per
+/// component, then answers membership and size lookups in O(1).
+///
+/// The index is a snapshot: it does not track later graph mutations.
+pub struct Components {
+    component: HashMap<NodeId, u32>,
+    sizes: Vec<usize>,
+}
+
+impl Components {
+    /// Label every node of `graph`.
+    pub fn build(graph: &Graph) -> Self {
+        let mut nodes: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+        nodes.sort_unstable();
+
+        // Label in discovery order, then renumber by size
+        let mut found: HashMap<NodeId, u32> = HashMap::with_capacity(nodes.len());
+        let mut sizes: Vec<usize> = Vec::new();
+        let mut queue = VecDeque::new();
+        for &root in &nodes {
+            if found.contains_key(&root) {
+                continue;
+            }
+            let label = sizes.len() as u32;
+            found.insert(root, label);
+            queue.push_back(root);
+            let mut size = 0;
+            while let Some(current) = queue.pop_front() {
+                interrupt::check();
+                size += 1;
+                for (edge, _) in graph.neighbors_all(current) {
+                    // Edges may point at IDs without node metadata
+                    if graph.node(edge.target).is_some() && !found.contains_key(&edge.target) {
+                        found.insert(edge.target, label);
+                        queue.push_back(edge.target);
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+
+        let mut order: Vec<u32> = (0..sizes.len() as u32).collect();
+        order.sort_by_key(|&c| std::cmp::Reverse(sizes[c as usize]));
+        let mut renumber = vec![0u32; sizes.len()];
+        for (new, &old) in order.iter().enumerate() {
+            renumber[old as usize] = new as u32;
+        }
+        for c in found.values_mut() {
+            *c = renumber[*c as usize];
+        }
+        Self {
+            component: found,
+            sizes: order.iter().map(|&c| sizes[c as usize]).collect(),
+        }
+    }
+
+    /// Component of `node`, or None if it was not in the graph when the
+    /// index was built.
+    pub fn component_of(&self, node: NodeId) -> Option<u32> {
+        self.component.get(&node).copied()
+    }
+
+    /// Number of nodes in `component`; 0 for an unknown component.
+    pub fn size(&self, component: u32) -> usize {
+        self.sizes.get(component as usize).copied().unwrap_or(0)
+    }
+
+    /// Number of components, isolated nodes included.
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Approximate memory usage in bytes.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+        let map = self.component.capacity() * (size_of::<NodeId>() + size_of::<u32>() + 1);
+        map + self.sizes.capacity() * size_of::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_sizes_and_order() {
+        // {0, 1, 2} via mixed directions, {5, 6}, isolated 9, and 3 → 4
+        let mut g = Graph::new();
+        let rt = g.intern_rel_type("R");
+        for id in [0, 1, 2, 3, 4, 5, 6, 9] {
+            g.add_node(id, "N".into(), None);
+        }
+        g.add_edge(0, 1, rt, 1.0);
+        g.add_edge(2, 1, rt, 1.0);
+        g.add_edge(6, 5, rt, 1.0);
+        g.add_edge(3, 4, rt, 1.0);
+        g.add_edge(4, 999, rt, 1.0);
+
+        let c = Components::build(&g);
+        assert_eq!(c.count(), 4);
+        assert_eq!(c.component_of(0), Some(0));
+        assert_eq!(c.component_of(2), Some(0));
+        assert_eq!(c.size(0), 3);
+        // Equal sizes: the component with the lower node ID comes first
+        assert_eq!(c.component_of(4), Some(1));
+        assert_eq!(c.component_of(5), Some(2));
+        assert_eq!(c.size(2), 2);
+        assert_eq!(c.component_of(9), Some(3));
+        assert_eq!(c.size(3), 1);
+        assert_eq!(c.component_of(999), None);
+        assert_eq!(c.size(7), 0);
+
+        g.freeze();
+        let frozen = Components::build(&g);
+        for id in [0, 1, 2, 3, 4, 5, 6, 9] {
+            assert_eq!(frozen.component_of(id), c.component_of(id));
+        }
+    }
+}
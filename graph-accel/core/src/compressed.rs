@@ -0,0 +1,351 @@
+//! Delta-varint adjacency encoding, trading decode time for memory on
+//! large read-mostly graphs.
+
+use std::collections::HashMap;
+
+use crate::graph::{Edge, Graph, Neighbors, NodeId, RelTypeId, TraversalDirection};
+use crate::mem;
+use crate::traversal::DenseBits;
+
+/// Low bits of each edge's tag varint, flagging which optional fields follow.
+const HAS_CONFIDENCE: u64 = 1;
+const HAS_WEIGHT: u64 = 2;
+const HAS_TIMESTAMP: u64 = 4;
+const FLAG_BITS: u32 = 3;
+
+/// Accessor for one adjacency direction.
+type Side = for<'a> fn(&'a Graph, NodeId) -> Neighbors<'a>;
+
+/// Both adjacency directions of a graph as byte streams, decoded on
+/// iteration.
+///
+/// Nodes get dense indices in ascending ID order, as in the CSR. Each
+/// node's edges are sorted by (target, rel type) and stored as the varint
+/// gap to the previous target, then a varint of the rel type with three
+/// flag bits. Confidence, a non-default weight and a timestamp follow
+/// only when present, so an edge carrying none of them takes two or three
+/// bytes against 28 in the CSR. Edges therefore come back in target order,
+/// not load order. Built from a `Graph` by `compress_adjacency`; read-only.
+pub struct CompressedAdjacency {
+    index: HashMap<NodeId, u32>,
+    ids: Vec<NodeId>,
+    out_offsets: Vec<usize>,
+    out_bytes: Vec<u8>,
+    in_offsets: Vec<usize>,
+    in_bytes: Vec<u8>,
+    edge_count: usize,
+}
+
+impl Graph {
+    /// Encode the adjacency in compressed form. The graph is left as is,
+    /// so the saving comes from traversing this and dropping the graph.
+    /// O(E log d) for max degree d.
+    pub fn compress_adjacency(&self) -> CompressedAdjacency {
+        let ids = self.sorted_ids();
+        let index: HashMap<NodeId, u32> = ids
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i as u32))
+            .collect();
+        let (out_offsets, out_bytes) = encode(self, &ids, &index, Graph::neighbors_out);
+        let (in_offsets, in_bytes) = encode(self, &ids, &index, Graph::neighbors_in);
+        CompressedAdjacency {
+            index,
+            ids,
+            out_offsets,
+            out_bytes,
+            in_offsets,
+            in_bytes,
+            edge_count: self.edge_count(),
+        }
+    }
+}
+
+impl CompressedAdjacency {
+    /// Number of nodes, including bare edge endpoints.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Dense index of a node ID, if known.
+    pub fn index_of(&self, id: NodeId) -> Option<u32> {
+        self.index.get(&id).copied()
+    }
+
+    /// Node ID at a dense index. Panics if out of range.
+    pub fn node_id(&self, idx: u32) -> NodeId {
+        self.ids[idx as usize]
+    }
+
+    /// Outgoing edges of a dense node, paired with each target's dense index.
+    pub fn out(&self, idx: u32) -> CompressedEdges<'_> {
+        self.side(idx, &self.out_offsets, &self.out_bytes)
+    }
+
+    /// Incoming edges of a dense node, paired with each source's dense index.
+    pub fn inc(&self, idx: u32) -> CompressedEdges<'_> {
+        self.side(idx, &self.in_offsets, &self.in_bytes)
+    }
+
+    /// Decoded outgoing edges of a node; empty if unknown.
+    pub fn neighbors_out(&self, id: NodeId) -> impl Iterator<Item = Edge> + '_ {
+        let run = self.index_of(id).map(|idx| self.out(idx));
+        run.into_iter().flatten().map(|(_, e)| e)
+    }
+
+    /// Decoded incoming edges of a node (`target` is the source); empty
+    /// if unknown.
+    pub fn neighbors_in(&self, id: NodeId) -> impl Iterator<Item = Edge> + '_ {
+        let run = self.index_of(id).map(|idx| self.inc(idx));
+        run.into_iter().flatten().map(|(_, e)| e)
+    }
+
+    /// `bfs_distances` over the compressed form: every node within
+    /// `max_depth` hops with its distance, in BFS order. Neighbors are
+    /// decoded in target order, so ties may come out in a different order
+    /// than on the graph itself; the distances are the same.
+    pub fn bfs_distances(
+        &self,
+        start: NodeId,
+        max_depth: u32,
+        direction: TraversalDirection,
+        min_confidence: Option<f32>,
+    ) -> Vec<(NodeId, u32)> {
+        let Some(start) = self.index_of(start) else {
+            return Vec::new();
+        };
+        let passes =
+            |e: &Edge| min_confidence.is_none_or(|min| !e.has_confidence() || e.confidence >= min);
+        let mut seen = DenseBits::new(self.len());
+        let mut order: Vec<(u32, u32)> = vec![(start, 0)];
+        seen.insert(start);
+
+        let mut head = 0;
+        while head < order.len() {
+            let (current, depth) = order[head];
+            head += 1;
+            if depth >= max_depth {
+                continue;
+            }
+            let out = match direction {
+                TraversalDirection::Incoming => None,
+                _ => Some(self.out(current)),
+            };
+            let inc = match direction {
+                TraversalDirection::Outgoing => None,
+                _ => Some(self.inc(current)),
+            };
+            for (next, edge) in out.into_iter().flatten().chain(inc.into_iter().flatten()) {
+                if passes(&edge) && seen.insert(next) {
+                    order.push((next, depth + 1));
+                }
+            }
+        }
+        order
+            .into_iter()
+            .map(|(idx, depth)| (self.node_id(idx), depth))
+            .collect()
+    }
+
+    /// Heap bytes, counted as `Graph::memory_usage` counts them.
+    pub fn memory_usage(&self) -> usize {
+        mem::hash_map(&self.index)
+            + mem::array::<NodeId>(self.ids.capacity())
+            + mem::array::<usize>(self.out_offsets.capacity())
+            + mem::array::<u8>(self.out_bytes.capacity())
+            + mem::array::<usize>(self.in_offsets.capacity())
+            + mem::array::<u8>(self.in_bytes.capacity())
+    }
+
+    fn side<'a>(&'a self, idx: u32, offsets: &[usize], bytes: &'a [u8]) -> CompressedEdges<'a> {
+        let i = idx as usize;
+        CompressedEdges {
+            bytes: &bytes[offsets[i]..offsets[i + 1]],
+            ids: &self.ids,
+            target: 0,
+        }
+    }
+}
+
+/// Decoder over one node's edges, yielding (dense target, edge).
+pub struct CompressedEdges<'a> {
+    bytes: &'a [u8],
+    ids: &'a [NodeId],
+    target: u32,
+}
+
+impl Iterator for CompressedEdges<'_> {
+    type Item = (u32, Edge);
+
+    fn next(&mut self) -> Option<(u32, Edge)> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        self.target += get_varint(&mut self.bytes) as u32;
+        let tag = get_varint(&mut self.bytes);
+        let mut edge = Edge {
+            target: self.ids[self.target as usize],
+            rel_type: (tag >> FLAG_BITS) as RelTypeId,
+            confidence: Edge::NO_CONFIDENCE,
+            weight: Edge::DEFAULT_WEIGHT,
+            created_at: Edge::NO_TIMESTAMP,
+        };
+        if tag & HAS_CONFIDENCE != 0 {
+            edge.confidence = f32::from_le_bytes(take4(&mut self.bytes));
+        }
+        if tag & HAS_WEIGHT != 0 {
+            edge.weight = f32::from_le_bytes(take4(&mut self.bytes));
+        }
+        if tag & HAS_TIMESTAMP != 0 {
+            edge.created_at = u32::from_le_bytes(take4(&mut self.bytes));
+        }
+        Some((self.target, edge))
+    }
+}
+
+/// Encode one direction: per-node byte offsets and the packed stream.
+fn encode(
+    graph: &Graph,
+    ids: &[NodeId],
+    index: &HashMap<NodeId, u32>,
+    side: Side,
+) -> (Vec<usize>, Vec<u8>) {
+    let mut offsets = Vec::with_capacity(ids.len() + 1);
+    let mut bytes = Vec::new();
+    let mut run: Vec<(u32, Edge)> = Vec::new();
+    offsets.push(0);
+    for &id in ids {
+        run.clear();
+        run.extend(side(graph, id).iter().map(|e| (index[&e.target], e)));
+        run.sort_by_key(|&(target, e)| (target, e.rel_type));
+
+        let mut prev = 0;
+        for &(target, e) in &run {
+            put_varint(&mut bytes, u64::from(target - prev));
+            prev = target;
+            let mut flags = 0;
+            if e.has_confidence() {
+                flags |= HAS_CONFIDENCE;
+            }
+            if e.weight != Edge::DEFAULT_WEIGHT {
+                flags |= HAS_WEIGHT;
+            }
+            if e.created_at != Edge::NO_TIMESTAMP {
+                flags |= HAS_TIMESTAMP;
+            }
+            put_varint(&mut bytes, (u64::from(e.rel_type) << FLAG_BITS) | flags);
+            if flags & HAS_CONFIDENCE != 0 {
+                bytes.extend_from_slice(&e.confidence.to_le_bytes());
+            }
+            if flags & HAS_WEIGHT != 0 {
+                bytes.extend_from_slice(&e.weight.to_le_bytes());
+            }
+            if flags & HAS_TIMESTAMP != 0 {
+                bytes.extend_from_slice(&e.created_at.to_le_bytes());
+            }
+        }
+        offsets.push(bytes.len());
+    }
+    bytes.shrink_to_fit();
+    (offsets, bytes)
+}
+
+/// LEB128: seven bits per byte, low bits first, high bit set on all but
+/// the last byte.
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn get_varint(bytes: &mut &[u8]) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let b = bytes[0];
+        *bytes = &bytes[1..];
+        value |= u64::from(b & 0x7f) << shift;
+        if b < 0x80 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+fn take4(bytes: &mut &[u8]) -> [u8; 4] {
+    let (head, rest) = bytes.split_at(4);
+    *bytes = rest;
+    head.try_into().expect("split at 4")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{Edge, Graph, NodeId, TraversalDirection};
+    use crate::test_util::edge;
+    use crate::traversal::bfs_distances;
+
+    fn key(e: &Edge) -> (NodeId, u16, u32, u32, u32) {
+        let conf = if e.has_confidence() {
+            e.confidence.to_bits()
+        } else {
+            0
+        };
+        (e.target, e.rel_type, conf, e.weight.to_bits(), e.created_at)
+    }
+
+    #[test]
+    fn test_compressed_adjacency_round_trips() {
+        let mut g = Graph::new();
+        g.load_edges((0..2000).map(|i| {
+            let mut rec = edge(
+                i % 300,
+                (i * 7919) % 300 + 1_000_000,
+                ["A", "B"][i as usize % 2],
+            );
+            if i % 3 == 0 {
+                rec.confidence = (i % 10) as f32 / 10.0;
+            }
+            if i % 5 == 0 {
+                rec.weight = 2.5;
+                rec.created_at = 1_700_000_000 + i as u32;
+            }
+            rec
+        }));
+        g.load_edges((0..299).map(|i| edge(i + 1_000_000, i + 1, "C")));
+        g.freeze();
+        let c = g.compress_adjacency();
+        assert_eq!((c.len(), c.edge_count()), (g.node_count(), g.edge_count()));
+
+        for id in g.sorted_ids() {
+            for (stored, decoded) in [
+                (g.neighbors_out(id), c.neighbors_out(id).collect::<Vec<_>>()),
+                (g.neighbors_in(id), c.neighbors_in(id).collect::<Vec<_>>()),
+            ] {
+                let mut want: Vec<_> = stored.iter().map(|e| key(&e)).collect();
+                want.sort_unstable();
+                let got: Vec<_> = decoded.iter().map(key).collect();
+                assert_eq!(got, want, "node {}", id);
+            }
+        }
+        assert_eq!(c.neighbors_out(42_424_242).count(), 0);
+
+        for direction in [TraversalDirection::Outgoing, TraversalDirection::Both] {
+            let mut want = bfs_distances(&g, 0, 4, direction, Some(0.5));
+            let mut got = c.bfs_distances(0, 4, direction, Some(0.5));
+            want.sort_unstable();
+            got.sort_unstable();
+            assert_eq!(got, want);
+        }
+        assert!(c.memory_usage() * 2 < g.memory_usage());
+    }
+}
@@ -0,0 +1,52 @@
+//! Edge costs for the weighted searches: `shortest_path_weighted`,
+//! `k_shortest_paths_weighted` and `steiner_tree_weighted`.
+
+use std::collections::HashMap;
+
+use crate::graph::{Edge, RelTypeId};
+
+/// Traversal cost of one edge. Any `Fn(&Edge) -> f64` is a `CostFn`, so
+/// closures, `Edge::hop_cost`, `Edge::confidence_cost` and
+/// `Edge::weight_cost` all work, and costs compose without new search
+/// variants.
+///
+/// Costs must be non-negative; the searches treat negative and NaN costs
+/// as 0.
+pub trait CostFn: Fn(&Edge) -> f64 {}
+
+impl<F: Fn(&Edge) -> f64> CostFn for F {}
+
+/// `base` multiplied by a per-rel-type factor, 1.0 for types not listed.
+///
+/// E.g. `rel_type_penalty(|e: &Edge| 1.0 / e.confidence as f64, penalties)`
+/// prefers confident edges and avoids penalized relationship types.
+pub fn rel_type_penalty<C: CostFn>(base: C, penalties: HashMap<RelTypeId, f64>) -> impl CostFn {
+    move |edge: &Edge| base(edge) * penalties.get(&edge.rel_type).copied().unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(rel_type: RelTypeId, confidence: f32) -> Edge {
+        Edge {
+            target: 0,
+            rel_type,
+            confidence,
+            weight: Edge::DEFAULT_WEIGHT,
+            created_at: Edge::NO_TIMESTAMP,
+        }
+    }
+
+    #[test]
+    fn test_rel_type_penalty_scales_base_cost() {
+        let cost = rel_type_penalty(
+            |e: &Edge| 1.0 / e.confidence as f64,
+            HashMap::from([(1, 3.0)]),
+        );
+        assert_eq!(cost(&edge(0, 0.5)), 2.0);
+        assert_eq!(cost(&edge(1, 0.5)), 6.0);
+        let hops = rel_type_penalty(Edge::hop_cost, HashMap::from([(1, 3.0)]));
+        assert_eq!((hops(&edge(0, 0.5)), hops(&edge(1, 0.5))), (1.0, 3.0));
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::{Graph, NodeId, RelTypeId};
+use crate::interrupt;
+use crate::reach::tarjan_scc;
+
+/// A directed simple cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle {
+    /// Nodes in cycle order, starting at the smallest ID. The closing edge
+    /// runs from the last node back to the first.
+    pub nodes: Vec<NodeId>,
+    /// `rel_types[i]` labels the edge leaving `nodes[i]`.
+    pub rel_types: Vec<String>,
+}
+
+/// Enumerate directed simple cycles of at most `max_len` edges.
+///
+/// Only outgoing edges whose rel type is in `rel_types` are followed (all
+/// edges if None). Parallel edges between the same ordered pair count once,
+/// reported with the lowest rel type ID; self-loops are cycles of length 1.
+///
+/// Follows Johnson's scheme: edges between strongly connected components are
+/// dropped, and each cycle is found exactly once, rooted at its smallest node,
+/// by searching only nodes above the root. Johnson's blocking sets are not
+/// sound under a length cap, so the search is pruned instead by each node's
+/// distance back to the root.
+///
+/// The number of cycles can be exponential, so enumeration stops after
+/// `max_cycles` results (0 = no limit). Cycles are searched one length at
+/// a time, so a limit keeps the shortest: the result is the first
+/// `max_cycles` cycles sorted by length, then by their node IDs.
+pub fn simple_cycles(
+    graph: &Graph,
+    max_len: usize,
+    rel_types: Option<&[RelTypeId]>,
+    max_cycles: usize,
+) -> Vec<Cycle> {
+    let allowed: Option<HashSet<RelTypeId>> = rel_types.map(|r| r.iter().copied().collect());
+
+    let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+    ids.sort_unstable();
+    let dense: HashMap<NodeId, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+    // Dense successors: one entry per target, lowest rel type wins
+    let succ: Vec<Vec<(usize, RelTypeId)>> = ids
+        .iter()
+        .map(|&id| {
+            let mut out: Vec<(usize, RelTypeId)> = graph
+                .neighbors_out(id)
+                .iter()
+                .filter(|e| allowed.as_ref().is_none_or(|a| a.contains(&e.rel_type)))
+                .filter_map(|e| dense.get(&e.target).map(|&t| (t, e.rel_type)))
+                .collect();
+            out.sort_unstable();
+            out.dedup_by_key(|&mut (t, _)| t);
+            out
+        })
+        .collect();
+
+    // Every cycle lies inside one SCC
+    let plain: Vec<Vec<usize>> = succ.iter().map(|s| s.iter().map(|&(t, _)| t).collect()).collect();
+    let comp = tarjan_scc(&plain);
+    let succ: Vec<Vec<(usize, RelTypeId)>> = succ
+        .into_iter()
+        .enumerate()
+        .map(|(v, s)| s.into_iter().filter(|&(t, _)| comp[t] == comp[v]).collect())
+        .collect();
+    let mut pred: Vec<Vec<usize>> = vec![Vec::new(); ids.len()];
+    for (v, s) in succ.iter().enumerate() {
+        for &(t, _) in s {
+            pred[t].push(v);
+        }
+    }
+
+    let limit = if max_cycles == 0 { usize::MAX } else { max_cycles };
+    let mut found: Vec<(Vec<usize>, Vec<RelTypeId>)> = Vec::new();
+    let mut back = vec![usize::MAX; ids.len()];
+    let mut touched: Vec<usize> = Vec::new();
+    let mut on_path = vec![false; ids.len()];
+
+    // One pass per cycle length, so a limit keeps the shortest cycles. Roots
+    // ascend and successors are sorted, so within a pass cycles are found
+    // in node-ID order and stopping early keeps the smallest. No simple
+    // cycle is longer than the node count.
+    for len in 1..=max_len.min(ids.len()) {
+        for root in 0..ids.len() {
+            if found.len() >= limit {
+                break;
+            }
+            if succ[root].is_empty() {
+                continue;
+            }
+
+            // Distance from each node back to root, through nodes above root
+            for &v in &touched {
+                back[v] = usize::MAX;
+            }
+            touched.clear();
+            back[root] = 0;
+            touched.push(root);
+            let mut queue = VecDeque::from([root]);
+            while let Some(v) = queue.pop_front() {
+                interrupt::check();
+                if back[v] + 1 >= len {
+                    continue;
+                }
+                for &u in &pred[v] {
+                    if u > root && back[u] == usize::MAX {
+                        back[u] = back[v] + 1;
+                        touched.push(u);
+                        queue.push_back(u);
+                    }
+                }
+            }
+
+            // Iterative DFS; frames are (node, next successor index)
+            let mut path: Vec<usize> = vec![root];
+            let mut path_rels: Vec<RelTypeId> = Vec::new();
+            let mut frames: Vec<(usize, usize)> = vec![(root, 0)];
+            on_path[root] = true;
+            while let Some(&mut (v, ref mut next)) = frames.last_mut() {
+                interrupt::check();
+                if found.len() >= limit {
+                    break;
+                }
+                let Some(&(u, rel)) = succ[v].get(*next) else {
+                    frames.pop();
+                    on_path[v] = false;
+                    path.pop();
+                    path_rels.pop();
+                    continue;
+                };
+                *next += 1;
+                if u == root {
+                    if path.len() == len {
+                        let mut rels = path_rels.clone();
+                        rels.push(rel);
+                        found.push((path.clone(), rels));
+                    }
+                } else if u > root
+                    && !on_path[u]
+                    && back[u] != usize::MAX
+                    && path.len() + back[u] <= len
+                {
+                    on_path[u] = true;
+                    path.push(u);
+                    path_rels.push(rel);
+                    frames.push((u, 0));
+                }
+            }
+            for &v in &path {
+                on_path[v] = false;
+            }
+        }
+    }
+
+    let mut cycles: Vec<Cycle> = found
+        .into_iter()
+        .map(|(nodes, rels)| Cycle {
+            nodes: nodes.into_iter().map(|v| ids[v]).collect(),
+            rel_types: rels
+                .into_iter()
+                .map(|r| graph.rel_type_name(r).unwrap_or("UNKNOWN").to_string())
+                .collect(),
+        })
+        .collect();
+    cycles.sort_by(|a, b| a.nodes.len().cmp(&b.nodes.len()).then_with(|| a.nodes.cmp(&b.nodes)));
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn node_lists(cycles: &[Cycle]) -> Vec<Vec<NodeId>> {
+        cycles.iter().map(|c| c.nodes.clone()).collect()
+    }
+
+    #[test]
+    fn test_cycles_found_once_from_smallest_node() {
+        // Triangle 1→2→3→1, 2-cycle 3⇄4, self-loop on 5, dangling 3→6
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "IMPLIES"),
+            edge(2, 3, "IMPLIES"),
+            edge(3, 1, "REQUIRES"),
+            edge(3, 4, "IMPLIES"),
+            edge(4, 3, "IMPLIES"),
+            edge(5, 5, "IMPLIES"),
+            edge(3, 6, "IMPLIES"),
+        ]);
+        let r = simple_cycles(&g, 10, None, 0);
+        assert_eq!(node_lists(&r), vec![vec![5], vec![3, 4], vec![1, 2, 3]]);
+        assert_eq!(r[2].rel_types, vec!["IMPLIES", "IMPLIES", "REQUIRES"]);
+    }
+
+    #[test]
+    fn test_cycles_length_cap_and_limit() {
+        // 4-cycle 0→1→2→3→0 with chord 1→3 making the 3-cycle 0→1→3→0
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 3, "R"),
+            edge(3, 0, "R"),
+            edge(1, 3, "R"),
+        ]);
+        assert_eq!(
+            node_lists(&simple_cycles(&g, 4, None, 0)),
+            vec![vec![0, 1, 3], vec![0, 1, 2, 3]]
+        );
+        assert_eq!(node_lists(&simple_cycles(&g, 3, None, 0)), vec![vec![0, 1, 3]]);
+        assert!(simple_cycles(&g, 2, None, 0).is_empty());
+        assert!(simple_cycles(&g, 0, None, 0).is_empty());
+        assert_eq!(simple_cycles(&g, usize::MAX, None, 0).len(), 2);
+        assert_eq!(simple_cycles(&g, 4, None, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_cycles_limit_keeps_shortest() {
+        // Root 0 owns a long cycle 0→1→2→3→0; the only short one, 4⇄5,
+        // sits on a later root
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 3, "R"),
+            edge(3, 0, "R"),
+            edge(4, 5, "R"),
+            edge(5, 4, "R"),
+        ]);
+        assert_eq!(node_lists(&simple_cycles(&g, 4, None, 1)), vec![vec![4, 5]]);
+        assert_eq!(
+            node_lists(&simple_cycles(&g, 4, None, 0)),
+            vec![vec![4, 5], vec![0, 1, 2, 3]]
+        );
+    }
+
+    #[test]
+    fn test_cycles_rel_type_filter() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "IMPLIES"),
+            edge(2, 1, "RELATED_TO"),
+            edge(2, 3, "IMPLIES"),
+            edge(3, 1, "IMPLIES"),
+        ]);
+        let implies = g.rel_type_id("IMPLIES").unwrap();
+        let r = simple_cycles(&g, 5, Some(&[implies]), 0);
+        assert_eq!(node_lists(&r), vec![vec![1, 2, 3]]);
+        assert_eq!(simple_cycles(&g, 5, None, 0).len(), 2);
+    }
+
+    #[test]
+    fn test_cycles_complete_digraph_count() {
+        // K4 with both directions: C(4,2)·1 + C(4,3)·2 + C(4,4)·6 = 20 cycles
+        let mut g = Graph::new();
+        for a in 0..4 {
+            for b in 0..4 {
+                if a != b {
+                    g.load_edges(vec![edge(a, b, "R")]);
+                }
+            }
+        }
+        let r = simple_cycles(&g, 4, None, 0);
+        assert_eq!(r.len(), 20);
+        let distinct: HashSet<Vec<NodeId>> = r.iter().map(|c| c.nodes.clone()).collect();
+        assert_eq!(distinct.len(), 20);
+        assert_eq!(simple_cycles(&g, 3, None, 0).len(), 14);
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use crate::graph::{EdgeKey, Graph, NodeId};
+
+/// Structural difference between two graphs, as returned by `Graph::diff`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphDelta {
+    /// Nodes only in the newer graph, ascending.
+    pub added_nodes: Vec<NodeId>,
+    /// Nodes only in the older graph, ascending.
+    pub removed_nodes: Vec<NodeId>,
+    /// Edges only in the newer graph, sorted by (from, to, rel_type).
+    pub added_edges: Vec<EdgeKey>,
+    /// Edges only in the older graph, sorted by (from, to, rel_type).
+    pub removed_edges: Vec<EdgeKey>,
+}
+
+impl GraphDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+impl Graph {
+    /// What changed structurally from `self` (older) to `other` (newer).
+    ///
+    /// Nodes are compared by ID, counting both nodes with metadata and bare
+    /// edge endpoints. Edges are compared as (from, to, rel_type) with rel
+    /// types matched by name, so the two graphs may intern them differently.
+    /// Parallel edges count as a multiset: going from two copies to three
+    /// reports one added edge. Edge attributes (confidence, weight,
+    /// properties) and node metadata are not compared.
+    pub fn diff(&self, other: &Graph) -> GraphDelta {
+        let mut delta = GraphDelta::default();
+
+        let (old_ids, new_ids) = (self.sorted_ids(), other.sorted_ids());
+        let missing_from = |ids: &[NodeId], from: &[NodeId]| -> Vec<NodeId> {
+            ids.iter()
+                .copied()
+                .filter(|id| from.binary_search(id).is_err())
+                .collect()
+        };
+        delta.removed_nodes = missing_from(&old_ids, &new_ids);
+        delta.added_nodes = missing_from(&new_ids, &old_ids);
+
+        // Net count per edge key: +1 per copy in self, -1 per copy in other
+        let mut counts: HashMap<(NodeId, NodeId, &str), i64> = HashMap::new();
+        for (graph, step) in [(self, 1), (other, -1)] {
+            for (from, to, rel_type, _) in graph.edges_iter() {
+                let rel_type = graph.rel_type_name(rel_type).unwrap_or_default();
+                *counts.entry((from, to, rel_type)).or_insert(0) += step;
+            }
+        }
+        for ((from_id, to_id, rel_type), count) in counts {
+            let side = if count > 0 {
+                &mut delta.removed_edges
+            } else {
+                &mut delta.added_edges
+            };
+            for _ in 0..count.unsigned_abs() {
+                side.push(EdgeKey {
+                    from_id,
+                    to_id,
+                    rel_type: rel_type.to_string(),
+                });
+            }
+        }
+        let order = |a: &EdgeKey, b: &EdgeKey| {
+            (a.from_id, a.to_id, &a.rel_type).cmp(&(b.from_id, b.to_id, &b.rel_type))
+        };
+        delta.added_edges.sort_unstable_by(order);
+        delta.removed_edges.sort_unstable_by(order);
+
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn key(from: NodeId, to: NodeId, rel: &str) -> EdgeKey {
+        EdgeKey {
+            from_id: from,
+            to_id: to,
+            rel_type: rel.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_nodes_and_edges() {
+        let mut old = Graph::new();
+        old.load_edges(vec![edge(1, 2, "A"), edge(2, 3, "B"), edge(3, 4, "A")]);
+        old.freeze();
+
+        // Interned in a different order: B gets ID 0 here
+        let mut new = Graph::new();
+        new.load_edges(vec![
+            edge(2, 3, "B"),
+            edge(1, 2, "A"),
+            edge(1, 2, "A"),
+            edge(2, 5, "B"),
+        ]);
+
+        let d = old.diff(&new);
+        assert_eq!(d.added_nodes, vec![5]);
+        assert_eq!(d.removed_nodes, vec![4]);
+        assert_eq!(d.added_edges, vec![key(1, 2, "A"), key(2, 5, "B")]);
+        assert_eq!(d.removed_edges, vec![key(3, 4, "A")]);
+
+        let back = new.diff(&old);
+        assert_eq!(back.added_nodes, d.removed_nodes);
+        assert_eq!(back.removed_edges, d.added_edges);
+
+        assert!(old.diff(&old).is_empty());
+        assert!(!d.is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::{Graph, NodeId};
+use crate::interrupt;
+
+/// Approximate diameter and radius of the graph's largest component.
+///
+/// Edge direction is ignored. Bounds are exact when
+/// `diameter_lower == diameter_upper`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiameterEstimate {
+    /// Largest eccentricity observed — a lower bound on the diameter, and
+    /// usually the exact value on real-world graphs.
+    pub diameter_lower: u32,
+    /// `2 × radius_upper`, capped below by `diameter_lower`.
+    pub diameter_upper: u32,
+    /// Smallest eccentricity observed — an upper bound on the radius.
+    pub radius_upper: u32,
+    /// Node achieving `radius_upper`. None for an empty graph.
+    pub center: Option<NodeId>,
+    /// Endpoints of the longest shortest path found. None for an empty graph.
+    pub periphery: Option<(NodeId, NodeId)>,
+    /// Nodes in the component the estimate was computed on.
+    pub component_size: usize,
+    /// Number of BFS traversals performed.
+    pub bfs_runs: usize,
+}
+
+/// Landmark-based distance oracle for approximate pairwise hop distances.
+///
+/// Precomputes undirected BFS distances from a few landmark nodes. A query
+/// `(a, b)` is then answered from the table by the triangle inequality:
+/// `max |d(a,l) - d(b,l)| <= d(a,b) <= min d(a,l) + d(l,b)`. No BFS at
+/// query time — cost is O(landmarks).
+pub struct LandmarkOracle {
+    /// Node → column in each distance row.
+    index: HashMap<NodeId, usize>,
+    landmarks: Vec<NodeId>,
+    /// One row per landmark; `UNREACHED` where the landmark can't reach.
+    distances: Vec<Vec<u32>>,
+}
+
+/// Bounds on the hop distance between two nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistanceEstimate {
+    /// Length of a real path through some landmark — the estimate to use.
+    pub upper: u32,
+    /// No path can be shorter than this.
+    pub lower: u32,
+}
+
+const UNREACHED: u32 = u32::MAX;
+
+impl LandmarkOracle {
+    /// Pick up to `count` landmarks and precompute their distance rows.
+    ///
+    /// Landmarks are chosen by descending degree, skipping nodes adjacent
+    /// to an already chosen landmark so they spread across the graph.
+    /// Every connected component with at least one node gets a landmark
+    /// before any component gets a second, so estimates exist for every
+    /// connected pair as long as `count` covers the components that matter.
+    pub fn build(graph: &Graph, count: usize) -> Self {
+        let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+        ids.sort_unstable();
+        let index: HashMap<NodeId, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+
+        let degree = |id: NodeId| graph.neighbors_out(id).len() + graph.neighbors_in(id).len();
+        let mut by_degree = ids.clone();
+        by_degree.sort_by(|&a, &b| degree(b).cmp(&degree(a)).then(a.cmp(&b)));
+
+        let mut landmarks: Vec<NodeId> = Vec::new();
+        let mut distances: Vec<Vec<u32>> = Vec::new();
+        let mut covered = vec![false; ids.len()];
+        let mut near_landmark: HashSet<NodeId> = HashSet::new();
+
+        // Pass 1 covers unreached components; pass 2 adds spread-out extras
+        for pass in 0..2 {
+            for &candidate in &by_degree {
+                if landmarks.len() >= count {
+                    break;
+                }
+                let fresh_component = !covered[index[&candidate]];
+                if (pass == 0 && !fresh_component)
+                    || (pass == 1 && near_landmark.contains(&candidate))
+                {
+                    continue;
+                }
+                let bfs = undirected_bfs(graph, candidate);
+                let mut row = vec![UNREACHED; ids.len()];
+                for (id, &(d, _)) in &bfs {
+                    row[index[id]] = d;
+                    covered[index[id]] = true;
+                }
+                near_landmark.insert(candidate);
+                near_landmark.extend(graph.neighbors_all(candidate).map(|(e, _)| e.target));
+                landmarks.push(candidate);
+                distances.push(row);
+            }
+        }
+
+        Self {
+            index,
+            landmarks,
+            distances,
+        }
+    }
+
+    /// Estimate the hop distance between `a` and `b`, ignoring edge direction.
+    ///
+    /// Returns None if either node is unknown or no landmark reaches both
+    /// (usually: they are in different components). Exact when `a == b` or
+    /// when either node is a landmark.
+    pub fn estimate(&self, a: NodeId, b: NodeId) -> Option<DistanceEstimate> {
+        let (&ia, &ib) = (self.index.get(&a)?, self.index.get(&b)?);
+        if ia == ib {
+            return Some(DistanceEstimate { upper: 0, lower: 0 });
+        }
+        let mut best: Option<DistanceEstimate> = None;
+        for row in &self.distances {
+            let (da, db) = (row[ia], row[ib]);
+            if da == UNREACHED || db == UNREACHED {
+                continue;
+            }
+            let est = best.get_or_insert(DistanceEstimate {
+                upper: u32::MAX,
+                lower: 0,
+            });
+            est.upper = est.upper.min(da + db);
+            est.lower = est.lower.max(da.abs_diff(db));
+        }
+        best
+    }
+
+    pub fn landmarks(&self) -> &[NodeId] {
+        &self.landmarks
+    }
+
+    /// Approximate memory usage in bytes.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+        let index = self.index.capacity() * (size_of::<NodeId>() + size_of::<usize>() + 1);
+        let rows: usize = self.distances.iter().map(|r| r.capacity() * size_of::<u32>()).sum();
+        index + rows + self.landmarks.capacity() * size_of::<NodeId>()
+    }
+}
+
+/// BFS from `start` over undirected edges.
+/// Returns distances and parent pointers for every reached node.
+fn undirected_bfs(graph: &Graph, start: NodeId) -> HashMap<NodeId, (u32, NodeId)> {
+    let mut visited: HashMap<NodeId, (u32, NodeId)> = HashMap::new();
+    let mut queue = VecDeque::new();
+    visited.insert(start, (0, start));
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        interrupt::check();
+        let depth = visited[&current].0;
+        for (edge, _) in graph.neighbors_all(current) {
+            visited.entry(edge.target).or_insert_with(|| {
+                queue.push_back(edge.target);
+                (depth + 1, current)
+            });
+        }
+    }
+    visited
+}
+
+/// Farthest node from the BFS root (ties broken by lowest node ID).
+fn farthest(dist: &HashMap<NodeId, (u32, NodeId)>) -> (NodeId, u32) {
+    dist.iter()
+        .map(|(&id, &(d, _))| (id, d))
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .expect("BFS result always contains the root")
+}
+
+/// Estimate diameter and radius with repeated double sweeps (4-sweep style).
+///
+/// Works on the largest connected component. Each round runs a BFS from a
+/// candidate center `r`, then from the node `a` farthest from `r` (the
+/// eccentricity of `a` bounds the diameter from below), and takes the
+/// midpoint of the `a`–`b` path as the next candidate center. Every BFS
+/// also bounds the radius from above. Cost is `O(sweeps × (V + E))` — no
+/// all-pairs computation.
+///
+/// The first candidate is the highest-degree node of the component.
+/// `sweeps = 0` is treated as 1.
+pub fn estimate_diameter(graph: &Graph, sweeps: usize) -> DiameterEstimate {
+    let mut estimate = DiameterEstimate {
+        diameter_lower: 0,
+        diameter_upper: 0,
+        radius_upper: 0,
+        center: None,
+        periphery: None,
+        component_size: 0,
+        bfs_runs: 0,
+    };
+
+    // Find the largest component, tracking its highest-degree node
+    let degree = |id: NodeId| graph.neighbors_out(id).len() + graph.neighbors_in(id).len();
+    let mut seen: HashSet<NodeId> = HashSet::with_capacity(graph.node_count());
+    let mut best: Option<(usize, NodeId)> = None;
+    let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+    ids.sort_unstable();
+    for id in ids {
+        if seen.contains(&id) {
+            continue;
+        }
+        let component = undirected_bfs(graph, id);
+        let hub = component
+            .keys()
+            .copied()
+            .max_by(|&a, &b| degree(a).cmp(&degree(b)).then(b.cmp(&a)))
+            .unwrap_or(id);
+        let size = component.len();
+        seen.extend(component.into_keys());
+        if best.is_none_or(|(s, _)| size > s) {
+            best = Some((size, hub));
+        }
+    }
+
+    let Some((size, mut candidate)) = best else {
+        return estimate;
+    };
+    estimate.component_size = size;
+    estimate.radius_upper = u32::MAX;
+
+    for _ in 0..sweeps.max(1) {
+        // Sweep 1: from the candidate center
+        let from_r = undirected_bfs(graph, candidate);
+        estimate.bfs_runs += 1;
+        let (a, ecc_r) = farthest(&from_r);
+        if ecc_r < estimate.radius_upper {
+            estimate.radius_upper = ecc_r;
+            estimate.center = Some(candidate);
+        }
+
+        // Sweep 2: from the far end
+        let from_a = undirected_bfs(graph, a);
+        estimate.bfs_runs += 1;
+        let (b, ecc_a) = farthest(&from_a);
+        if ecc_a < estimate.radius_upper {
+            estimate.radius_upper = ecc_a;
+            estimate.center = Some(a);
+        }
+        if ecc_a > estimate.diameter_lower || estimate.periphery.is_none() {
+            estimate.diameter_lower = ecc_a;
+            estimate.periphery = Some((a, b));
+        }
+
+        // Next candidate: midpoint of the a–b path
+        let mut mid = b;
+        for _ in 0..ecc_a / 2 {
+            mid = from_a[&mid].1;
+        }
+        if mid == candidate {
+            break;
+        }
+        candidate = mid;
+    }
+
+    estimate.diameter_upper = (2 * estimate.radius_upper).max(estimate.diameter_lower);
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    #[test]
+    fn test_landmarks_exact_on_chain_through_landmark() {
+        // Chain 0..=10, one landmark: the highest-degree node with lowest ID is 1
+        let mut g = Graph::new();
+        g.load_edges((0..10).map(|i| edge(i, i + 1, "R")));
+        let oracle = LandmarkOracle::build(&g, 1);
+        assert_eq!(oracle.landmarks(), &[1]);
+        // 0 and 10 lie on opposite sides of landmark 1: upper is exact
+        let est = oracle.estimate(0, 10).unwrap();
+        assert_eq!((est.lower, est.upper), (8, 10));
+        // 5 and 8 on the same side: upper overestimates, lower is exact
+        let est = oracle.estimate(5, 8).unwrap();
+        assert_eq!(est.lower, 3);
+        assert_eq!(est.upper, 11);
+        assert_eq!(oracle.estimate(4, 4), Some(DistanceEstimate { upper: 0, lower: 0 }));
+    }
+
+    #[test]
+    fn test_landmarks_bounds_hold() {
+        let mut g = Graph::new();
+        let mut rng = crate::rng::SplitMix64::new(3);
+        g.load_edges((0..150).map(|_| edge(rng.below(60) as NodeId, rng.below(60) as NodeId, "R")));
+        let oracle = LandmarkOracle::build(&g, 6);
+        assert!(oracle.landmarks().len() <= 6);
+
+        let ids: Vec<NodeId> = g.nodes_iter().map(|(&id, _)| id).collect();
+        for &a in &ids {
+            let truth = undirected_bfs(&g, a);
+            for &b in &ids {
+                match (oracle.estimate(a, b), truth.get(&b)) {
+                    (Some(est), Some(&(d, _))) => {
+                        assert!(est.lower <= d && d <= est.upper, "{a}-{b}: {d} not in {est:?}");
+                    }
+                    (None, Some(_)) => {} // component without a landmark
+                    (Some(_), None) => panic!("estimate for disconnected pair {a}-{b}"),
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_landmarks_cover_components() {
+        let mut g = Graph::new();
+        g.load_edges((0..5).map(|i| edge(i, i + 1, "R")));
+        g.load_edges(vec![edge(100, 101, "R"), edge(101, 102, "R")]);
+        let oracle = LandmarkOracle::build(&g, 2);
+        assert!(oracle.estimate(0, 5).is_some());
+        assert_eq!(oracle.estimate(100, 102).map(|e| e.upper), Some(2));
+        assert!(oracle.estimate(0, 100).is_none());
+        assert!(oracle.estimate(0, 999).is_none());
+        assert!(LandmarkOracle::build(&Graph::new(), 4).landmarks().is_empty());
+    }
+
+    #[test]
+    fn test_diameter_chain_exact() {
+        let mut g = Graph::new();
+        g.load_edges((0..10).map(|i| edge(i, i + 1, "R")));
+        let est = estimate_diameter(&g, 4);
+        assert_eq!(est.diameter_lower, 10);
+        assert_eq!(est.radius_upper, 5);
+        assert_eq!(est.diameter_upper, 10);
+        assert_eq!(est.center, Some(5));
+        let (a, b) = est.periphery.unwrap();
+        assert_eq!((a.min(b), a.max(b)), (0, 10));
+        assert_eq!(est.component_size, 11);
+    }
+
+    #[test]
+    fn test_diameter_cycle() {
+        let mut g = Graph::new();
+        g.load_edges((0..12).map(|i| edge(i, (i + 1) % 12, "R")));
+        let est = estimate_diameter(&g, 2);
+        assert_eq!(est.diameter_lower, 6);
+        assert_eq!(est.radius_upper, 6);
+        assert!(est.bfs_runs <= 4);
+    }
+
+    #[test]
+    fn test_diameter_star() {
+        let mut g = Graph::new();
+        g.load_edges((1..=20).map(|i| edge(0, i, "R")));
+        let est = estimate_diameter(&g, 4);
+        assert_eq!(est.diameter_lower, 2);
+        assert_eq!(est.radius_upper, 1);
+        assert_eq!(est.center, Some(0));
+    }
+
+    #[test]
+    fn test_diameter_uses_largest_component() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(100, 101, "R")]);
+        g.load_edges((0..5).map(|i| edge(i, i + 1, "R")));
+        let est = estimate_diameter(&g, 4);
+        assert_eq!(est.component_size, 6);
+        assert_eq!(est.diameter_lower, 5);
+    }
+
+    #[test]
+    fn test_diameter_empty_and_single() {
+        let est = estimate_diameter(&Graph::new(), 4);
+        assert_eq!(est.component_size, 0);
+        assert!(est.center.is_none());
+
+        let mut g = Graph::new();
+        g.add_node(7, "Node".to_string(), None);
+        let est = estimate_diameter(&g, 0);
+        assert_eq!(est.component_size, 1);
+        assert_eq!(est.diameter_lower, 0);
+        assert_eq!(est.radius_upper, 0);
+        assert_eq!(est.center, Some(7));
+    }
+}
@@ -0,0 +1,27 @@
+use thiserror::Error;
+
+use crate::graph::{NodeId, MAX_LABELS, MAX_REL_TYPES};
+use crate::props::MAX_PROP_KEYS;
+
+/// Failure of a fallible (`try_*`) graph operation.
+///
+/// The infallible counterparts panic with the same message prefixed by
+/// `graph_accel:`, which the extension turns into a Postgres ERROR.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GraphError {
+    #[error("exceeded maximum of {} distinct relationship types", MAX_REL_TYPES)]
+    TooManyRelTypes,
+    #[error("exceeded maximum of {} distinct node labels", MAX_LABELS)]
+    TooManyLabels,
+    #[error("exceeded maximum of {} distinct property keys", MAX_PROP_KEYS)]
+    TooManyPropKeys,
+    /// CSR indices are u32, so a frozen graph holds fewer than 2^32 nodes.
+    #[error("too many nodes for CSR index")]
+    TooManyNodes,
+    /// The node ID is not in the graph.
+    #[error("node {0} is not in the graph")]
+    UnknownNode(NodeId),
+    /// Neither an app ID nor the ID of a node in the graph.
+    #[error("node '{0}' not found")]
+    NodeNotFound(String),
+}
@@ -0,0 +1,118 @@
+//! Immutable, shareable handle on a frozen graph.
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::error::GraphError;
+use crate::graph::Graph;
+
+/// A graph packed into CSR form that only hands out shared references.
+///
+/// A `Graph` can be unpacked by any `&mut` call, so holding one says
+/// nothing about its layout. A `FrozenGraph` is frozen and compacted once,
+/// when built, and derefs to `&Graph` only, so it can sit behind an `Arc`
+/// and be read from many threads, or kept across calls, without a lock.
+/// `update()` and `into_graph()` are the only ways to change it, and both
+/// need sole ownership.
+pub struct FrozenGraph {
+    graph: Graph,
+}
+
+impl Graph {
+    /// Freeze and compact, then seal against mutation. Panics where
+    /// `freeze` would; see `try_into_frozen`.
+    pub fn into_frozen(mut self) -> FrozenGraph {
+        self.freeze();
+        self.compact();
+        FrozenGraph { graph: self }
+    }
+
+    pub fn try_into_frozen(mut self) -> Result<FrozenGraph, GraphError> {
+        self.try_freeze()?;
+        self.compact();
+        Ok(FrozenGraph { graph: self })
+    }
+
+    /// `into_frozen` behind an `Arc`, ready to clone into other threads.
+    pub fn into_shared(self) -> Arc<FrozenGraph> {
+        Arc::new(self.into_frozen())
+    }
+}
+
+impl FrozenGraph {
+    /// Mutate in place, then freeze and compact again.
+    ///
+    /// The graph is unpacked by the first mutation that needs lists, as on
+    /// any frozen `Graph`, so a batch of changes costs one O(E) repack.
+    /// Behind an `Arc`, reach this through `Arc::get_mut`, which succeeds
+    /// only while no other handle exists.
+    pub fn update<R>(&mut self, f: impl FnOnce(&mut Graph) -> R) -> R {
+        let result = f(&mut self.graph);
+        self.graph.freeze();
+        self.graph.compact();
+        result
+    }
+
+    /// Give up the seal and return the graph, still frozen.
+    pub fn into_graph(self) -> Graph {
+        self.graph
+    }
+}
+
+impl Deref for FrozenGraph {
+    type Target = Graph;
+
+    fn deref(&self) -> &Graph {
+        &self.graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::FrozenGraph;
+    use crate::graph::{Graph, TraversalDirection};
+    use crate::test_util::edge;
+    use crate::traversal::bfs_neighborhood;
+
+    #[test]
+    fn test_shared_graph_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FrozenGraph>();
+
+        let mut g = Graph::new();
+        g.load_edges((0..100).map(|i| edge(i, i + 1, "R")));
+        let mut shared = g.into_shared();
+        assert!(shared.is_frozen());
+
+        let reached: Vec<usize> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|i| {
+                    let graph = Arc::clone(&shared);
+                    s.spawn(move || {
+                        bfs_neighborhood(&graph, i * 10, 5, TraversalDirection::Outgoing, None, 0)
+                            .neighbors
+                            .len()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(reached, vec![5; 4]);
+
+        // Sole owner again: update in place, refrozen afterwards
+        let added = Arc::get_mut(&mut shared)
+            .expect("threads dropped their handles")
+            .update(|g| g.apply_delta(&[edge(101, 102, "R")], &[]).added);
+        assert_eq!(added, 1);
+        assert!(shared.is_frozen());
+        assert_eq!(shared.edge_count(), 101);
+
+        let other = Arc::clone(&shared);
+        assert!(Arc::get_mut(&mut shared).is_none());
+        drop(other);
+        let graph = Arc::try_unwrap(shared).ok().unwrap().into_graph();
+        assert_eq!(graph.node_count(), 103);
+    }
+}
@@ -1,4 +1,11 @@
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::buf::Buf;
+use crate::error::GraphError;
+use crate::mem;
+use crate::props::{PropKeyId, PropValue, PropertyBag, MAX_PROP_KEYS};
 
 /// Internal node identifier (maps to AGE's graph-internal ID in production).
 pub type NodeId = u64;
@@ -9,6 +16,12 @@ pub type RelTypeId = u16;
 /// Maximum number of distinct relationship types (u16::MAX).
 pub const MAX_REL_TYPES: usize = u16::MAX as usize;
 
+/// Interned node classification label.
+pub type LabelId = u16;
+
+/// Maximum number of distinct extra labels (u16::MAX).
+pub const MAX_LABELS: usize = u16::MAX as usize;
+
 /// Whether an edge was traversed in its original direction or against it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
@@ -31,29 +44,152 @@ pub enum TraversalDirection {
 
 /// Metadata about a node.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeInfo {
-    pub label: String,
+    /// Primary label: the AGE vertex label the node was loaded from,
+    /// interned like extra labels (see `Graph::node_label()`).
+    pub label: LabelId,
+    /// Further classification labels, sorted by ID. Usually empty, which
+    /// costs no allocation.
+    pub extra_labels: Box<[LabelId]>,
     pub app_id: Option<String>,
+    /// Extra node attributes; boxed so nodes without any cost one pointer.
+    pub properties: Option<Box<PropertyBag>>,
+}
+
+impl NodeInfo {
+    /// Primary label followed by the extra labels.
+    pub(crate) fn labels(&self) -> Vec<LabelId> {
+        std::iter::once(self.label)
+            .chain(self.extra_labels.iter().copied())
+            .collect()
+    }
 }
 
 /// A directed edge in the adjacency list.
+///
+/// `repr(C)` fixes the 24-byte layout so mapped files can hold edges as is.
 #[derive(Debug, Clone, Copy)]
+#[repr(C)]
 pub struct Edge {
     pub target: NodeId,
     pub rel_type: RelTypeId,
-    /// Edge confidence (0.0–1.0). `f32::NAN` means "not loaded" —
-    /// avoids Option<f32> which would bloat Edge from 8 to 12+ bytes.
+    /// Edge confidence (0.0–1.0). `f32::NAN` means "not loaded" — a
+    /// sentinel rather than Option<f32>, which would add 4 bytes of tag
+    /// and padding and push Edge past 24 bytes.
     pub confidence: f32,
+    /// Routing cost, independent of confidence. `DEFAULT_WEIGHT` (1.0)
+    /// unless loaded from a weight property; always finite and >= 0.
+    pub weight: f32,
+    /// Creation time in seconds since the Unix epoch, or `NO_TIMESTAMP`.
+    /// A u32 fills the padding after `weight`, so storing it is free.
+    pub created_at: u32,
 }
 
 impl Edge {
     /// Sentinel value for edges with no confidence data.
     pub const NO_CONFIDENCE: f32 = f32::NAN;
 
+    /// Weight of edges without a loaded weight: every hop costs the same.
+    pub const DEFAULT_WEIGHT: f32 = 1.0;
+
+    /// Sentinel `created_at` for edges with no timestamp loaded.
+    pub const NO_TIMESTAMP: u32 = u32::MAX;
+
     /// Returns true if this edge has a loaded confidence value.
     pub fn has_confidence(&self) -> bool {
         !self.confidence.is_nan()
     }
+
+    /// Traversal cost derived from confidence: `-ln(confidence)`.
+    ///
+    /// Summed along a path this ranks paths by the product of their
+    /// confidences, most trustworthy first. Confidence is clamped to
+    /// `[1e-6, 1]`; edges without confidence data cost 0.
+    pub fn confidence_cost(&self) -> f64 {
+        if !self.has_confidence() {
+            return 0.0;
+        }
+        -(self.confidence.clamp(1e-6, 1.0) as f64).ln()
+    }
+
+    /// Traversal cost from the edge weight, for weighted path search.
+    pub fn weight_cost(&self) -> f64 {
+        self.weight as f64
+    }
+
+    /// Unit traversal cost: weighted searches then minimize hop count.
+    pub fn hop_cost(&self) -> f64 {
+        1.0
+    }
+
+    /// Creation time (seconds since the Unix epoch), if loaded.
+    pub fn created_at(&self) -> Option<u32> {
+        (self.created_at != Self::NO_TIMESTAMP).then_some(self.created_at)
+    }
+
+    /// True if the edge existed at `as_of` (seconds since the Unix epoch).
+    /// Like the confidence filter, edges without a timestamp always pass.
+    pub fn existed_at(&self, as_of: u32) -> bool {
+        self.created_at == Self::NO_TIMESTAMP || self.created_at <= as_of
+    }
+}
+
+/// An edge as a frozen graph stores it, beside its dense target index.
+///
+/// By default that is the `Edge` itself. With the `compact-ids` feature
+/// the 8-byte `NodeId` target is left out and looked up from the dense
+/// index on access, so a stored edge takes 16 bytes instead of 24. Either
+/// way the API hands out `Edge`s with full 64-bit targets.
+#[cfg(not(feature = "compact-ids"))]
+pub(crate) type StoredEdge = Edge;
+
+/// An edge as a frozen graph stores it: `Edge` without its target, which
+/// the dense target index stands in for.
+#[cfg(feature = "compact-ids")]
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub(crate) struct StoredEdge {
+    pub(crate) rel_type: RelTypeId,
+    pub(crate) confidence: f32,
+    pub(crate) weight: f32,
+    pub(crate) created_at: u32,
+}
+
+/// `e` in stored form.
+#[cfg(not(feature = "compact-ids"))]
+fn store(e: Edge) -> StoredEdge {
+    e
+}
+
+#[cfg(feature = "compact-ids")]
+fn store(e: Edge) -> StoredEdge {
+    StoredEdge {
+        rel_type: e.rel_type,
+        confidence: e.confidence,
+        weight: e.weight,
+        created_at: e.created_at,
+    }
+}
+
+/// The `Edge` a stored edge stands for. `target` is only called when the
+/// stored form lacks it.
+#[cfg(not(feature = "compact-ids"))]
+#[inline]
+fn unstore(e: &StoredEdge, _target: impl FnOnce() -> NodeId) -> Edge {
+    *e
+}
+
+#[cfg(feature = "compact-ids")]
+#[inline]
+fn unstore(e: &StoredEdge, target: impl FnOnce() -> NodeId) -> Edge {
+    Edge {
+        target: target(),
+        rel_type: e.rel_type,
+        confidence: e.confidence,
+        weight: e.weight,
+        created_at: e.created_at,
+    }
 }
 
 /// A record describing an edge to load into the graph.
@@ -68,33 +204,644 @@ pub struct EdgeRecord {
     pub from_app_id: Option<String>,
     pub to_app_id: Option<String>,
     pub confidence: f32,
+    /// Routing cost; `Edge::DEFAULT_WEIGHT` if the source has none.
+    pub weight: f32,
+    /// Seconds since the Unix epoch; `Edge::NO_TIMESTAMP` if unknown.
+    pub created_at: u32,
+    /// Extra edge attributes to keep, by key name. Usually empty.
+    pub properties: Vec<(String, PropValue)>,
+}
+
+impl EdgeRecord {
+    /// An edge with no labels, app IDs, or properties, unknown confidence
+    /// and timestamp, and the default weight. Set the rest with struct
+    /// update syntax.
+    pub fn new(from_id: NodeId, to_id: NodeId, rel_type: impl Into<String>) -> Self {
+        EdgeRecord {
+            from_id,
+            to_id,
+            rel_type: rel_type.into(),
+            from_label: String::new(),
+            to_label: String::new(),
+            from_app_id: None,
+            to_app_id: None,
+            confidence: Edge::NO_CONFIDENCE,
+            weight: Edge::DEFAULT_WEIGHT,
+            created_at: Edge::NO_TIMESTAMP,
+            properties: Vec::new(),
+        }
+    }
+}
+
+/// Identifies the edges to drop in `Graph::apply_delta`: every stored edge
+/// `from_id → to_id` with this relationship type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EdgeKey {
+    pub from_id: NodeId,
+    pub to_id: NodeId,
+    pub rel_type: String,
+}
+
+/// Edge counts changed by `Graph::apply_delta`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeltaOutcome {
+    pub added: usize,
+    /// Stored edges dropped, counting each parallel edge.
+    pub removed: usize,
+}
+
+/// Snapshot of a streaming load, passed to the `load_stream` progress callback
+/// and returned when the stream ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// Records consumed from the stream so far.
+    pub records: usize,
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// `memory_usage()` at the time of the snapshot.
+    pub memory_bytes: usize,
+    /// True if the callback stopped the load before the stream was exhausted.
+    pub aborted: bool,
 }
 
 /// In-memory graph: adjacency lists + node metadata + relationship type interning.
 ///
 /// Edges are stored bidirectionally — `outgoing[a]` contains edges from a,
 /// `incoming[b]` contains edges into b. Both are populated on load.
+///
+/// Loading fills per-node `Vec`s, which accept edges in any order. Once the
+/// graph is complete, `freeze()` packs both directions into CSR arrays; all
+/// read APIs behave the same in either form.
 pub struct Graph {
-    outgoing: HashMap<NodeId, Vec<Edge>>,
-    incoming: HashMap<NodeId, Vec<Edge>>,
-    nodes: HashMap<NodeId, NodeInfo>,
-    app_id_index: HashMap<String, NodeId>,
-    rel_types: Vec<String>,
+    adjacency: Adjacency,
+    pub(crate) nodes: HashMap<NodeId, NodeInfo>,
+    pub(crate) app_id_index: HashMap<String, NodeId>,
+    /// Integer app IDs (see `index_int_app_id`), hashed as numbers.
+    pub(crate) int_app_id_index: HashMap<i64, NodeId>,
+    /// Named value → node indices beyond the app ID (see `index_node`).
+    pub(crate) secondary_indices: HashMap<String, HashMap<String, NodeId>>,
+    pub(crate) rel_types: Vec<String>,
     rel_type_map: HashMap<String, RelTypeId>,
+    pub(crate) labels: Vec<String>,
+    label_map: HashMap<String, LabelId>,
+    /// Nodes carrying each label (primary or extra), indexed by LabelId.
+    pub(crate) label_nodes: Vec<Vec<NodeId>>,
+    pub(crate) prop_keys: Vec<String>,
+    prop_key_map: HashMap<String, PropKeyId>,
+    /// Edge properties, outside `Edge` so edges without any pay nothing.
+    /// Keyed by stored orientation; parallel edges of one type share a bag.
+    pub(crate) edge_props: HashMap<EdgeSlot, PropertyBag>,
     /// Hint for Vec pre-allocation in add_edge. Set by with_capacity().
-    estimated_avg_degree: usize,
+    pub(crate) estimated_avg_degree: usize,
+    /// Group each node's CSR edges by rel type on freeze (see
+    /// `group_by_rel_type`).
+    group_rel_types: bool,
+    /// Sort each node's CSR edges by target on freeze (see
+    /// `sort_by_target`).
+    sort_targets: bool,
+}
+
+/// (from, to, rel_type) in stored orientation.
+type EdgeSlot = (NodeId, NodeId, RelTypeId);
+
+/// Per-node edge lists, keyed by the node the edges are stored on.
+pub(crate) type EdgeLists = HashMap<NodeId, Vec<Edge>>;
+
+/// Adjacency storage: growable per-node lists while loading, CSR once frozen.
+enum Adjacency {
+    Lists { outgoing: EdgeLists, incoming: EdgeLists },
+    Csr(Csr),
+}
+
+/// Compressed sparse row adjacency over a dense node index.
+///
+/// Dense node `i` has outgoing edges `out_edges[out_offsets[i]..out_offsets[i + 1]]`
+/// and likewise for incoming. One index lookup and two contiguous slices
+/// replace two hash lookups into separately allocated Vecs.
+///
+/// `out_targets` / `in_targets` parallel the edge arrays with each edge's
+/// target already translated to its dense index, so traversals over
+/// `DenseAdjacency` never hash inside the inner loop.
+///
+/// When `grouped`, each node's edges are sorted by rel type, so the edges
+/// of one type form a run found by binary search. When `sorted`, they are
+/// sorted by target instead, so an edge to a given node is found by binary
+/// search. At most one of the two holds.
+///
+/// The arrays are owned, or with the `mmap` feature may live in a mapped
+/// file (see `mapped.rs`). Mapped CSRs leave `index` empty and find dense
+/// indices by binary search over `ids` instead.
+#[derive(Default)]
+struct Csr {
+    /// Node ID → dense index. Covers every node with metadata or edges.
+    index: HashMap<NodeId, u32>,
+    /// Dense index → node ID (ascending), for translating results back.
+    ids: Buf<NodeId>,
+    out_offsets: Buf<usize>,
+    out_edges: Buf<StoredEdge>,
+    out_targets: Buf<u32>,
+    in_offsets: Buf<usize>,
+    in_edges: Buf<StoredEdge>,
+    in_targets: Buf<u32>,
+    grouped: bool,
+    sorted: bool,
+}
+
+impl Csr {
+    fn out(&self, id: NodeId) -> Neighbors<'_> {
+        self.slice(id, &self.out_offsets, &self.out_edges, &self.out_targets)
+    }
+
+    fn inc(&self, id: NodeId) -> Neighbors<'_> {
+        self.slice(id, &self.in_offsets, &self.in_edges, &self.in_targets)
+    }
+
+    fn slice<'a>(
+        &'a self,
+        id: NodeId,
+        offsets: &[usize],
+        edges: &'a [StoredEdge],
+        targets: &'a [u32],
+    ) -> Neighbors<'a> {
+        match self.position(id) {
+            Some(i) => {
+                let range = offsets[i as usize]..offsets[i as usize + 1];
+                Neighbors::stored(&edges[range.clone()], &targets[range], &self.ids)
+            }
+            None => Neighbors::default(),
+        }
+    }
+
+    /// Dense index of `id`, binary-searching `ids` when there is no hash
+    /// index (mapped, or empty).
+    fn position(&self, id: NodeId) -> Option<u32> {
+        if self.index.is_empty() {
+            self.ids.binary_search(&id).ok().map(|i| i as u32)
+        } else {
+            self.index.get(&id).copied()
+        }
+    }
+
+    /// Stable-sort each node's edges by rel type, keeping the target arrays
+    /// aligned.
+    fn group_by_rel_type(&mut self) {
+        self.sort_runs(|e| e.rel_type);
+        (self.grouped, self.sorted) = (true, false);
+    }
+
+    /// Stable-sort each node's edges by (target, rel type), keeping the
+    /// target arrays aligned.
+    fn sort_by_target(&mut self) {
+        self.sort_runs(|e| (e.target, e.rel_type));
+        (self.grouped, self.sorted) = (false, true);
+    }
+
+    /// Stable-sort each node's run of edges by `key`. Runs already in order
+    /// are left alone.
+    fn sort_runs<K: Ord>(&mut self, key: impl Fn(&Edge) -> K) {
+        let nodes = self.ids.len();
+        let ids = &self.ids;
+        for (offsets, edges, targets) in [
+            (
+                &self.out_offsets,
+                self.out_edges.make_mut(),
+                self.out_targets.make_mut(),
+            ),
+            (
+                &self.in_offsets,
+                self.in_edges.make_mut(),
+                self.in_targets.make_mut(),
+            ),
+        ] {
+            for i in 0..nodes {
+                let range = offsets[i]..offsets[i + 1];
+                let run = Neighbors::stored(&edges[range.clone()], &targets[range.clone()], ids);
+                if run.iter().is_sorted_by_key(|e| key(&e)) {
+                    continue;
+                }
+                let mut run: Vec<(Edge, u32)> = run
+                    .iter()
+                    .zip(targets[range.clone()].iter().copied())
+                    .collect();
+                run.sort_by_key(|(e, _)| key(e));
+                for (k, (e, t)) in run.into_iter().enumerate() {
+                    edges[range.start + k] = store(e);
+                    targets[range.start + k] = t;
+                }
+            }
+        }
+    }
+
+    /// The run of `rel_type` edges within one node's grouped edges.
+    fn typed_run(edges: Neighbors<'_>, rel_type: RelTypeId) -> Neighbors<'_> {
+        let start = edges.partition_point(|e| e.rel_type < rel_type);
+        let len = edges
+            .slice(start..edges.len())
+            .partition_point(|e| e.rel_type == rel_type);
+        edges.slice(start..start + len)
+    }
+
+    /// Every node with metadata or edges, ascending: the dense index order.
+    fn node_ids(
+        nodes: &HashMap<NodeId, NodeInfo>,
+        outgoing: &EdgeLists,
+        incoming: &EdgeLists,
+    ) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = nodes
+            .keys()
+            .chain(outgoing.keys())
+            .chain(incoming.keys())
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.index.shrink_to_fit();
+        self.ids.shrink_to_fit();
+        self.out_offsets.shrink_to_fit();
+        self.out_edges.shrink_to_fit();
+        self.out_targets.shrink_to_fit();
+        self.in_offsets.shrink_to_fit();
+        self.in_edges.shrink_to_fit();
+        self.in_targets.shrink_to_fit();
+    }
+
+    /// Pack per-node lists over `ids` from `node_ids()`, which must have
+    /// fewer than `u32::MAX` entries.
+    fn build(ids: Vec<NodeId>, outgoing: EdgeLists, incoming: EdgeLists) -> Self {
+        let pack = |mut lists: EdgeLists| {
+            let mut offsets = Vec::with_capacity(ids.len() + 1);
+            let mut edges = Vec::with_capacity(lists.values().map(|v| v.len()).sum());
+            offsets.push(0);
+            for id in &ids {
+                if let Some(list) = lists.remove(id) {
+                    edges.extend(list);
+                }
+                offsets.push(edges.len());
+            }
+            (offsets, edges)
+        };
+        let outgoing = pack(outgoing);
+        let incoming = pack(incoming);
+        Self::from_parts(ids, outgoing, incoming).expect("every edge target has an index entry")
+    }
+
+    /// Assemble from packed arrays over ascending `ids`, translating edge
+    /// targets to dense indices. None if a target is missing from `ids`.
+    fn from_parts(
+        ids: Vec<NodeId>,
+        (out_offsets, out_edges): (Vec<usize>, Vec<Edge>),
+        (in_offsets, in_edges): (Vec<usize>, Vec<Edge>),
+    ) -> Option<Self> {
+        let index: HashMap<NodeId, u32> =
+            ids.iter().enumerate().map(|(i, &id)| (id, i as u32)).collect();
+        let translate = |edges: &[Edge]| -> Option<Vec<u32>> {
+            edges.iter().map(|e| index.get(&e.target).copied()).collect()
+        };
+        let out_targets = translate(&out_edges)?;
+        let in_targets = translate(&in_edges)?;
+        let stored =
+            |edges: Vec<Edge>| -> Vec<StoredEdge> { edges.into_iter().map(store).collect() };
+        Some(Csr {
+            index,
+            ids: ids.into(),
+            out_offsets: out_offsets.into(),
+            out_edges: stored(out_edges).into(),
+            out_targets: out_targets.into(),
+            in_offsets: in_offsets.into(),
+            in_edges: stored(in_edges).into(),
+            in_targets: in_targets.into(),
+            grouped: false,
+            sorted: false,
+        })
+    }
+
+    /// Unpack into per-node lists (nodes without edges get no entry).
+    fn into_lists(self) -> (EdgeLists, EdgeLists) {
+        let unpack = |offsets: &[usize], edges: &[StoredEdge], targets: &[u32]| -> EdgeLists {
+            self.ids
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &id)| {
+                    let range = offsets[i]..offsets[i + 1];
+                    let list = Neighbors::stored(&edges[range.clone()], &targets[range], &self.ids);
+                    (!list.is_empty()).then(|| (id, list.iter().collect()))
+                })
+                .collect()
+        };
+        (
+            unpack(&self.out_offsets, &self.out_edges, &self.out_targets),
+            unpack(&self.in_offsets, &self.in_edges, &self.in_targets),
+        )
+    }
+}
+
+/// Array-indexed adjacency of a frozen graph (see `Graph::dense()`).
+///
+/// AGE graph IDs are sparse 64-bit values; here every node is numbered
+/// 0..len() in ascending ID order, and neighbors come with their dense index
+/// precomputed. Traversals can keep visited sets and distances in plain
+/// Vecs indexed by node, translating back through `node_id()` for output.
+#[derive(Clone, Copy)]
+pub struct DenseAdjacency<'a> {
+    csr: &'a Csr,
+}
+
+impl<'a> DenseAdjacency<'a> {
+    /// Number of nodes, including bare edge endpoints.
+    pub fn len(&self) -> usize {
+        self.csr.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.csr.ids.is_empty()
+    }
+
+    /// Dense index of a node ID, if the graph knows it.
+    pub fn index_of(&self, id: NodeId) -> Option<u32> {
+        self.csr.position(id)
+    }
+
+    /// Node ID at a dense index. Panics if out of range.
+    pub fn node_id(&self, idx: u32) -> NodeId {
+        self.csr.ids[idx as usize]
+    }
+
+    /// Outgoing edges of a dense node, paired with each target's dense index.
+    pub fn out(&self, idx: u32) -> impl Iterator<Item = (u32, Edge)> + 'a {
+        let csr = self.csr;
+        Self::side(
+            idx,
+            &csr.ids,
+            &csr.out_offsets,
+            &csr.out_targets,
+            &csr.out_edges,
+        )
+    }
+
+    /// Incoming edges of a dense node, paired with each source's dense index.
+    pub fn inc(&self, idx: u32) -> impl Iterator<Item = (u32, Edge)> + 'a {
+        let csr = self.csr;
+        Self::side(
+            idx,
+            &csr.ids,
+            &csr.in_offsets,
+            &csr.in_targets,
+            &csr.in_edges,
+        )
+    }
+
+    fn side(
+        idx: u32,
+        ids: &'a [NodeId],
+        offsets: &'a [usize],
+        targets: &'a [u32],
+        edges: &'a [StoredEdge],
+    ) -> impl Iterator<Item = (u32, Edge)> + 'a {
+        let range = offsets[idx as usize]..offsets[idx as usize + 1];
+        targets[range.clone()]
+            .iter()
+            .zip(&edges[range])
+            .map(move |(&t, e)| (t, unstore(e, || ids[t as usize])))
+    }
+}
+
+/// One node's edges on one side, as `Graph::neighbors_out` and
+/// `neighbors_in` return them.
+///
+/// Edges come out by value: a frozen graph built with `compact-ids` keeps
+/// no target in its stored edges, so there is no `Edge` to borrow. Copying
+/// one costs the same as reading it through a reference.
+#[derive(Debug, Clone, Copy)]
+pub struct Neighbors<'a> {
+    run: Run<'a>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Run<'a> {
+    /// Adjacency lists, or a CSR that stores whole edges
+    Edges(&'a [Edge]),
+    /// Target-less CSR edges, their dense targets and the dense ID table
+    #[cfg(feature = "compact-ids")]
+    Stored {
+        edges: &'a [StoredEdge],
+        targets: &'a [u32],
+        ids: &'a [NodeId],
+    },
+}
+
+impl<'a> Neighbors<'a> {
+    /// One node's run of CSR edges with their dense targets, over the
+    /// CSR's `ids`.
+    #[cfg(not(feature = "compact-ids"))]
+    fn stored(edges: &'a [StoredEdge], _targets: &'a [u32], _ids: &'a [NodeId]) -> Self {
+        Self::from(edges)
+    }
+
+    #[cfg(feature = "compact-ids")]
+    fn stored(edges: &'a [StoredEdge], targets: &'a [u32], ids: &'a [NodeId]) -> Self {
+        Self {
+            run: Run::Stored {
+                edges,
+                targets,
+                ids,
+            },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self.run {
+            Run::Edges(edges) => edges.len(),
+            #[cfg(feature = "compact-ids")]
+            Run::Stored { edges, .. } => edges.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `i`th edge, or None if out of range.
+    pub fn get(&self, i: usize) -> Option<Edge> {
+        (i < self.len()).then(|| self.at(i))
+    }
+
+    pub fn first(&self) -> Option<Edge> {
+        self.get(0)
+    }
+
+    pub fn iter(&self) -> NeighborIter<'a> {
+        NeighborIter {
+            run: *self,
+            range: 0..self.len(),
+        }
+    }
+
+    /// The `i`th edge. Panics if out of range.
+    #[inline]
+    fn at(&self, i: usize) -> Edge {
+        match self.run {
+            Run::Edges(edges) => edges[i],
+            #[cfg(feature = "compact-ids")]
+            Run::Stored {
+                edges,
+                targets,
+                ids,
+            } => unstore(&edges[i], || ids[targets[i] as usize]),
+        }
+    }
+
+    /// The edges in `range`. Panics if out of range.
+    pub(crate) fn slice(&self, range: std::ops::Range<usize>) -> Self {
+        let run = match self.run {
+            Run::Edges(edges) => Run::Edges(&edges[range]),
+            #[cfg(feature = "compact-ids")]
+            Run::Stored {
+                edges,
+                targets,
+                ids,
+            } => Run::Stored {
+                edges: &edges[range.clone()],
+                targets: &targets[range],
+                ids,
+            },
+        };
+        Self { run }
+    }
+
+    /// Index of the first edge for which `pred` is false, the edges being
+    /// partitioned by it, as `slice::partition_point`.
+    pub(crate) fn partition_point(&self, pred: impl Fn(&Edge) -> bool) -> usize {
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if pred(&self.at(mid)) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+impl<'a> From<&'a [Edge]> for Neighbors<'a> {
+    fn from(edges: &'a [Edge]) -> Self {
+        Self {
+            run: Run::Edges(edges),
+        }
+    }
+}
+
+impl Default for Neighbors<'_> {
+    fn default() -> Self {
+        Self::from(&[][..])
+    }
+}
+
+impl<'a> IntoIterator for Neighbors<'a> {
+    type Item = Edge;
+    type IntoIter = NeighborIter<'a>;
+
+    fn into_iter(self) -> NeighborIter<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over `Neighbors`, yielding each `Edge` by value.
+#[derive(Debug, Clone)]
+pub struct NeighborIter<'a> {
+    run: Neighbors<'a>,
+    range: std::ops::Range<usize>,
+}
+
+impl Iterator for NeighborIter<'_> {
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Edge> {
+        self.range.next().map(|i| self.run.at(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for NeighborIter<'_> {
+    fn next_back(&mut self) -> Option<Edge> {
+        self.range.next_back().map(|i| self.run.at(i))
+    }
+}
+
+impl ExactSizeIterator for NeighborIter<'_> {}
+
+/// Weight as stored: negative or non-finite weights fall back to the default.
+pub(crate) fn sanitize_weight(weight: f32) -> f32 {
+    if weight.is_finite() && weight >= 0.0 {
+        weight
+    } else {
+        Edge::DEFAULT_WEIGHT
+    }
+}
+
+/// Fold repeated (target, rel_type) entries of each list into the first,
+/// keeping the highest confidence. Returns the entries dropped.
+fn dedupe_lists(lists: &mut EdgeLists) -> usize {
+    let mut removed = 0;
+    let mut first: HashMap<(NodeId, RelTypeId), usize> = HashMap::new();
+    for list in lists.values_mut() {
+        first.clear();
+        let mut kept = 0;
+        for i in 0..list.len() {
+            let e = list[i];
+            match first.entry((e.target, e.rel_type)) {
+                Entry::Occupied(slot) => {
+                    let k = &mut list[*slot.get()];
+                    k.confidence = k.confidence.max(e.confidence);
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert(kept);
+                    list[kept] = e;
+                    kept += 1;
+                }
+            }
+        }
+        removed += list.len() - kept;
+        list.truncate(kept);
+    }
+    removed
+}
+
+/// Panic of the infallible wrappers around the `try_*` methods.
+fn fail(err: GraphError) -> ! {
+    panic!("graph_accel: {}", err)
 }
 
 impl Graph {
     pub fn new() -> Self {
         Self {
-            outgoing: HashMap::new(),
-            incoming: HashMap::new(),
+            adjacency: Adjacency::Lists {
+                outgoing: HashMap::new(),
+                incoming: HashMap::new(),
+            },
             nodes: HashMap::new(),
             app_id_index: HashMap::new(),
+            int_app_id_index: HashMap::new(),
+            secondary_indices: HashMap::new(),
             rel_types: Vec::new(),
             rel_type_map: HashMap::new(),
+            labels: Vec::new(),
+            label_map: HashMap::new(),
+            label_nodes: Vec::new(),
+            prop_keys: Vec::new(),
+            prop_key_map: HashMap::new(),
+            edge_props: HashMap::new(),
             estimated_avg_degree: 4,
+            group_rel_types: false,
+            sort_targets: false,
         }
     }
 
@@ -104,17 +851,29 @@ impl Graph {
     /// in `add_edge`, avoiding repeated re-allocation during bulk loading.
     pub fn with_capacity(node_count: usize, edge_count: usize) -> Self {
         Self {
-            outgoing: HashMap::with_capacity(node_count),
-            incoming: HashMap::with_capacity(node_count),
+            adjacency: Adjacency::Lists {
+                outgoing: HashMap::with_capacity(node_count),
+                incoming: HashMap::with_capacity(node_count),
+            },
             nodes: HashMap::with_capacity(node_count),
             app_id_index: HashMap::with_capacity(node_count),
+            int_app_id_index: HashMap::new(),
+            secondary_indices: HashMap::new(),
             rel_types: Vec::new(),
             rel_type_map: HashMap::new(),
+            labels: Vec::new(),
+            label_map: HashMap::new(),
+            label_nodes: Vec::new(),
+            prop_keys: Vec::new(),
+            prop_key_map: HashMap::new(),
+            edge_props: HashMap::new(),
             estimated_avg_degree: if node_count > 0 {
                 (edge_count / node_count).max(1)
             } else {
                 4
             },
+            group_rel_types: false,
+            sort_targets: false,
         }
     }
 
@@ -125,18 +884,22 @@ impl Graph {
     /// In the pgrx extension this panic is caught by `#[pg_guard]` and
     /// converted to a Postgres ERROR.
     pub fn intern_rel_type(&mut self, rel_type: &str) -> RelTypeId {
+        self.try_intern_rel_type(rel_type)
+            .unwrap_or_else(|e| fail(e))
+    }
+
+    /// `intern_rel_type`, failing with `TooManyRelTypes` instead of panicking.
+    pub fn try_intern_rel_type(&mut self, rel_type: &str) -> Result<RelTypeId, GraphError> {
         if let Some(&id) = self.rel_type_map.get(rel_type) {
-            return id;
+            return Ok(id);
+        }
+        if self.rel_types.len() >= MAX_REL_TYPES {
+            return Err(GraphError::TooManyRelTypes);
         }
-        assert!(
-            self.rel_types.len() < MAX_REL_TYPES,
-            "graph_accel: exceeded maximum of {} distinct relationship types",
-            MAX_REL_TYPES
-        );
         let id = self.rel_types.len() as RelTypeId;
         self.rel_types.push(rel_type.to_string());
         self.rel_type_map.insert(rel_type.to_string(), id);
-        id
+        Ok(id)
     }
 
     /// Resolve a RelTypeId back to its string name.
@@ -145,88 +908,1040 @@ impl Graph {
         self.rel_types.get(id as usize).map(|s| s.as_str())
     }
 
-    /// Register a node with metadata.
-    pub fn add_node(&mut self, id: NodeId, label: String, app_id: Option<String>) {
-        if let Some(ref aid) = app_id {
-            self.app_id_index.insert(aid.clone(), id);
+    /// Look up the interned ID of a relationship type by name.
+    /// Returns None if no edge of that type has been loaded.
+    pub fn rel_type_id(&self, rel_type: &str) -> Option<RelTypeId> {
+        self.rel_type_map.get(rel_type).copied()
+    }
+
+    /// Intern a node label (primary or extra), returning its compact ID.
+    ///
+    /// # Panics
+    /// Panics if more than 65,535 distinct labels are interned.
+    pub fn intern_label(&mut self, label: &str) -> LabelId {
+        self.try_intern_label(label).unwrap_or_else(|e| fail(e))
+    }
+
+    /// `intern_label`, failing with `TooManyLabels` instead of panicking.
+    pub fn try_intern_label(&mut self, label: &str) -> Result<LabelId, GraphError> {
+        if let Some(&id) = self.label_map.get(label) {
+            return Ok(id);
         }
-        self.nodes.insert(id, NodeInfo { label, app_id });
+        if self.labels.len() >= MAX_LABELS {
+            return Err(GraphError::TooManyLabels);
+        }
+        let id = self.labels.len() as LabelId;
+        self.labels.push(label.to_string());
+        self.label_map.insert(label.to_string(), id);
+        Ok(id)
     }
 
-    /// Add a directed edge. Also inserts into the incoming adjacency list.
-    pub fn add_edge(&mut self, from: NodeId, to: NodeId, rel_type: RelTypeId, confidence: f32) {
-        let avg = self.estimated_avg_degree;
-        self.outgoing
-            .entry(from)
-            .or_insert_with(|| Vec::with_capacity(avg))
-            .push(Edge {
-                target: to,
-                rel_type,
-                confidence,
-            });
-        self.incoming
-            .entry(to)
-            .or_insert_with(|| Vec::with_capacity(avg))
-            .push(Edge {
-                target: from,
-                rel_type,
-                confidence,
-            });
+    /// Resolve a LabelId back to its name.
+    pub fn label_name(&self, id: LabelId) -> Option<&str> {
+        self.labels.get(id as usize).map(|s| s.as_str())
     }
 
-    /// Bulk load from EdgeRecord structs.
-    /// This is the primary load path — mirrors what the SPI query returns from AGE.
-    pub fn load_edges<I>(&mut self, edges: I)
+    /// Look up the interned ID of a label.
+    /// Returns None if no node has been given it.
+    pub fn label_id(&self, label: &str) -> Option<LabelId> {
+        self.label_map.get(label).copied()
+    }
+
+    /// Give a node further classification labels. Labels equal to the
+    /// primary label or already present are skipped. Returns false if the
+    /// node is not in the graph.
+    pub fn add_node_labels<I, S>(&mut self, id: NodeId, labels: I) -> bool
     where
-        I: IntoIterator<Item = EdgeRecord>,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
     {
-        for rec in edges {
-            // Register app IDs (first occurrence wins)
-            if let Some(ref aid) = rec.from_app_id {
-                self.app_id_index.entry(aid.clone()).or_insert(rec.from_id);
-            }
-            if let Some(ref aid) = rec.to_app_id {
-                self.app_id_index.entry(aid.clone()).or_insert(rec.to_id);
-            }
-
-            // Register nodes (first occurrence wins for label/app_id)
-            self.nodes.entry(rec.from_id).or_insert_with(|| NodeInfo {
-                label: rec.from_label,
-                app_id: rec.from_app_id,
-            });
-            self.nodes.entry(rec.to_id).or_insert_with(|| NodeInfo {
-                label: rec.to_label,
-                app_id: rec.to_app_id,
-            });
+        match self.try_add_node_labels(id, labels) {
+            Ok(()) => true,
+            Err(GraphError::UnknownNode(_)) => false,
+            Err(e) => fail(e),
+        }
+    }
 
-            let rt = self.intern_rel_type(&rec.rel_type);
-            self.add_edge(rec.from_id, rec.to_id, rt, rec.confidence);
+    /// `add_node_labels`, failing with `UnknownNode` or `TooManyLabels`.
+    pub fn try_add_node_labels<I, S>(&mut self, id: NodeId, labels: I) -> Result<(), GraphError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        if !self.nodes.contains_key(&id) {
+            return Err(GraphError::UnknownNode(id));
+        }
+        let mut new = labels
+            .into_iter()
+            .map(|l| self.try_intern_label(l.as_ref()))
+            .collect::<Result<Vec<LabelId>, _>>()?;
+        let info = self.nodes.get_mut(&id).expect("checked above");
+        new.retain(|&l| l != info.label && info.extra_labels.binary_search(&l).is_err());
+        new.sort_unstable();
+        new.dedup();
+        if new.is_empty() {
+            return Ok(());
         }
+        let mut merged = info.extra_labels.to_vec();
+        merged.extend_from_slice(&new);
+        merged.sort_unstable();
+        info.extra_labels = merged.into_boxed_slice();
+        self.partition_add(id, &new);
+        Ok(())
     }
 
-    /// Look up a node by its application-level ID (e.g. concept_id).
-    pub fn resolve_app_id(&self, app_id: &str) -> Option<NodeId> {
-        self.app_id_index.get(app_id).copied()
+    /// Primary label of a node. None if the node is unknown.
+    pub fn node_label(&self, id: NodeId) -> Option<&str> {
+        self.label_name(self.nodes.get(&id)?.label)
     }
 
-    /// Get node metadata.
-    pub fn node(&self, id: NodeId) -> Option<&NodeInfo> {
-        self.nodes.get(&id)
+    /// All labels of a node, primary first. Empty if the node is unknown.
+    pub fn node_labels(&self, id: NodeId) -> Vec<&str> {
+        let Some(info) = self.nodes.get(&id) else {
+            return Vec::new();
+        };
+        std::iter::once(info.label)
+            .chain(info.extra_labels.iter().copied())
+            .filter_map(|l| self.label_name(l))
+            .collect()
     }
 
-    /// Get outgoing edges for a node.
-    pub fn neighbors_out(&self, id: NodeId) -> &[Edge] {
-        self.outgoing.get(&id).map(|v| v.as_slice()).unwrap_or(&[])
+    /// True if the node carries `label`, as its primary or an extra label.
+    /// Suitable as a traversal node filter.
+    pub fn node_has_label(&self, id: NodeId, label: &str) -> bool {
+        let Some(info) = self.nodes.get(&id) else {
+            return false;
+        };
+        self.label_id(label)
+            .is_some_and(|l| info.label == l || info.extra_labels.binary_search(&l).is_ok())
     }
 
-    /// Get incoming edges for a node.
-    pub fn neighbors_in(&self, id: NodeId) -> &[Edge] {
-        self.incoming.get(&id).map(|v| v.as_slice()).unwrap_or(&[])
+    /// Intern a property key, returning its compact ID.
+    ///
+    /// # Panics
+    /// Panics if more than 65,535 distinct property keys are interned.
+    pub fn intern_prop_key(&mut self, key: &str) -> PropKeyId {
+        self.try_intern_prop_key(key).unwrap_or_else(|e| fail(e))
     }
 
-    /// Get both outgoing and incoming edges, tagged with traversal direction.
-    pub fn neighbors_all(&self, id: NodeId) -> impl Iterator<Item = (&Edge, Direction)> {
-        self.neighbors_out(id)
+    /// `intern_prop_key`, failing with `TooManyPropKeys` instead of panicking.
+    pub fn try_intern_prop_key(&mut self, key: &str) -> Result<PropKeyId, GraphError> {
+        if let Some(&id) = self.prop_key_map.get(key) {
+            return Ok(id);
+        }
+        if self.prop_keys.len() >= MAX_PROP_KEYS {
+            return Err(GraphError::TooManyPropKeys);
+        }
+        let id = self.prop_keys.len() as PropKeyId;
+        self.prop_keys.push(key.to_string());
+        self.prop_key_map.insert(key.to_string(), id);
+        Ok(id)
+    }
+
+    /// Resolve a PropKeyId back to its name.
+    pub fn prop_key_name(&self, id: PropKeyId) -> Option<&str> {
+        self.prop_keys.get(id as usize).map(|s| s.as_str())
+    }
+
+    /// Look up the interned ID of a property key.
+    /// Returns None if no property with that key has been stored.
+    pub fn prop_key_id(&self, key: &str) -> Option<PropKeyId> {
+        self.prop_key_map.get(key).copied()
+    }
+
+    /// Attach properties to the edges `from → to` of `rel_type`, merging
+    /// with (and overriding) any already stored. An empty input is a no-op.
+    pub fn set_edge_properties<I>(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        rel_type: RelTypeId,
+        props: I,
+    ) where
+        I: IntoIterator<Item = (String, PropValue)>,
+    {
+        self.try_set_edge_properties(from, to, rel_type, props)
+            .unwrap_or_else(|e| fail(e))
+    }
+
+    /// `set_edge_properties`, failing with `TooManyPropKeys`. Nothing is
+    /// stored unless every key could be interned.
+    pub fn try_set_edge_properties<I>(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        rel_type: RelTypeId,
+        props: I,
+    ) -> Result<(), GraphError>
+    where
+        I: IntoIterator<Item = (String, PropValue)>,
+    {
+        let props = self.try_intern_props(props)?;
+        self.store_edge_properties((from, to, rel_type), props);
+        Ok(())
+    }
+
+    fn try_intern_props<I>(&mut self, props: I) -> Result<Vec<(PropKeyId, PropValue)>, GraphError>
+    where
+        I: IntoIterator<Item = (String, PropValue)>,
+    {
+        props
+            .into_iter()
+            .map(|(key, value)| Ok((self.try_intern_prop_key(&key)?, value)))
+            .collect()
+    }
+
+    fn store_edge_properties(
+        &mut self,
+        key: (NodeId, NodeId, RelTypeId),
+        props: Vec<(PropKeyId, PropValue)>,
+    ) {
+        if props.is_empty() {
+            return;
+        }
+        let bag = self.edge_props.entry(key).or_default();
+        for (key, value) in props {
+            bag.insert(key, value);
+        }
+    }
+
+    /// Properties of the edges `from → to` of `rel_type`, in stored orientation.
+    pub fn edge_properties(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        rel_type: RelTypeId,
+    ) -> Option<&PropertyBag> {
+        self.edge_props.get(&(from, to, rel_type))
+    }
+
+    /// One property of an edge reached during traversal.
+    ///
+    /// `at` is the node whose adjacency `edge` came from and `dir` the
+    /// direction it was found in, as yielded by `neighbors_all`.
+    pub fn edge_property(
+        &self,
+        at: NodeId,
+        edge: &Edge,
+        dir: Direction,
+        key: &str,
+    ) -> Option<&PropValue> {
+        let key = self.prop_key_id(key)?;
+        let (from, to) = match dir {
+            Direction::Outgoing => (at, edge.target),
+            Direction::Incoming => (edge.target, at),
+        };
+        self.edge_properties(from, to, edge.rel_type)?.get(key)
+    }
+
+    /// Attach properties to a node, merging with (and overriding) any
+    /// already stored. Returns false if the node is not in the graph.
+    pub fn set_node_properties<I>(&mut self, id: NodeId, props: I) -> bool
+    where
+        I: IntoIterator<Item = (String, PropValue)>,
+    {
+        match self.try_set_node_properties(id, props) {
+            Ok(()) => true,
+            Err(GraphError::UnknownNode(_)) => false,
+            Err(e) => fail(e),
+        }
+    }
+
+    /// `set_node_properties`, failing with `UnknownNode` or
+    /// `TooManyPropKeys`. Nothing is stored on failure.
+    pub fn try_set_node_properties<I>(&mut self, id: NodeId, props: I) -> Result<(), GraphError>
+    where
+        I: IntoIterator<Item = (String, PropValue)>,
+    {
+        if !self.nodes.contains_key(&id) {
+            return Err(GraphError::UnknownNode(id));
+        }
+        let props = self.try_intern_props(props)?;
+        if props.is_empty() {
+            return Ok(());
+        }
+        let info = self.nodes.get_mut(&id).expect("checked above");
+        let bag = info.properties.get_or_insert_default();
+        for (key, value) in props {
+            bag.insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// One property of a node, e.g. for a traversal node filter.
+    pub fn node_property(&self, id: NodeId, key: &str) -> Option<&PropValue> {
+        let key = self.prop_key_id(key)?;
+        self.nodes.get(&id)?.properties.as_ref()?.get(key)
+    }
+
+    /// Register a node with metadata.
+    pub fn add_node(&mut self, id: NodeId, label: String, app_id: Option<String>) {
+        self.try_add_node(id, label, app_id)
+            .unwrap_or_else(|e| fail(e))
+    }
+
+    /// `add_node`, failing with `TooManyLabels` instead of panicking.
+    pub fn try_add_node(
+        &mut self,
+        id: NodeId,
+        label: String,
+        app_id: Option<String>,
+    ) -> Result<(), GraphError> {
+        let label = self.try_intern_label(&label)?;
+        if let Some(ref aid) = app_id {
+            self.app_id_index.insert(aid.clone(), id);
+        }
+        let old = self.nodes.insert(
+            id,
+            NodeInfo {
+                label,
+                extra_labels: Box::default(),
+                app_id,
+                properties: None,
+            },
+        );
+        if let Some(old) = old {
+            self.partition_remove(id, &old.labels());
+        }
+        self.partition_add(id, &[label]);
+        Ok(())
+    }
+
+    /// Remove a node together with every edge into or out of it.
+    ///
+    /// Returns the number of edges removed (a self-loop counts once), or None
+    /// if the node is not in the graph. The node's app ID is dropped from the
+    /// index unless it resolves to a different node, and its secondary index
+    /// entries are dropped. On a frozen graph this unpacks and re-packs the
+    /// CSR arrays, so it costs O(E).
+    pub fn remove_node(&mut self, id: NodeId) -> Option<usize> {
+        let info = self.nodes.remove(&id)?;
+        self.partition_remove(id, &info.labels());
+        if let Some(aid) = info.app_id {
+            if self.app_id_index.get(&aid) == Some(&id) {
+                self.app_id_index.remove(&aid);
+            }
+        }
+        self.int_app_id_index.retain(|_, node| *node != id);
+        for entries in self.secondary_indices.values_mut() {
+            entries.retain(|_, node| *node != id);
+        }
+
+        let was_frozen = self.is_frozen();
+        let (outgoing, incoming) = self.lists_mut();
+        let out = outgoing.remove(&id).unwrap_or_default();
+        let inc = incoming.remove(&id).unwrap_or_default();
+        for e in &out {
+            if let Some(list) = incoming.get_mut(&e.target) {
+                list.retain(|back| back.target != id);
+            }
+        }
+        for e in &inc {
+            if let Some(list) = outgoing.get_mut(&e.target) {
+                list.retain(|back| back.target != id);
+            }
+        }
+        let self_loops = out.iter().filter(|e| e.target == id).count();
+        for e in &out {
+            self.edge_props.remove(&(id, e.target, e.rel_type));
+        }
+        for e in &inc {
+            self.edge_props.remove(&(e.target, id, e.rel_type));
+        }
+
+        if was_frozen {
+            self.freeze();
+        }
+        Some(out.len() + inc.len() - self_loops)
+    }
+
+    /// Add a directed edge with the default weight. Also inserts into the
+    /// incoming adjacency list.
+    ///
+    /// On a frozen graph this first unpacks the CSR arrays back into lists
+    /// (O(E)); batch mutations and call `freeze()` again afterwards.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, rel_type: RelTypeId, confidence: f32) {
+        self.add_weighted_edge(from, to, rel_type, confidence, Edge::DEFAULT_WEIGHT);
+    }
+
+    /// Add a directed edge with a routing weight (see `Edge::weight`).
+    /// Weights that are negative or not finite are replaced by the default,
+    /// keeping weighted search (Dijkstra) sound.
+    pub fn add_weighted_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        rel_type: RelTypeId,
+        confidence: f32,
+        weight: f32,
+    ) {
+        self.add_timestamped_edge(from, to, rel_type, confidence, weight, Edge::NO_TIMESTAMP);
+    }
+
+    /// Add a directed edge with a weight and a creation time in seconds
+    /// since the Unix epoch (`Edge::NO_TIMESTAMP` if unknown).
+    pub fn add_timestamped_edge(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        rel_type: RelTypeId,
+        confidence: f32,
+        weight: f32,
+        created_at: u32,
+    ) {
+        let weight = sanitize_weight(weight);
+        let avg = self.estimated_avg_degree;
+        let (outgoing, incoming) = self.lists_mut();
+        outgoing
+            .entry(from)
+            .or_insert_with(|| Vec::with_capacity(avg))
+            .push(Edge {
+                target: to,
+                rel_type,
+                confidence,
+                weight,
+                created_at,
+            });
+        incoming
+            .entry(to)
+            .or_insert_with(|| Vec::with_capacity(avg))
+            .push(Edge {
+                target: from,
+                rel_type,
+                confidence,
+                weight,
+                created_at,
+            });
+    }
+
+    /// Pack the adjacency lists into CSR form.
+    ///
+    /// Call once loading is done: both directions become two contiguous edge
+    /// arrays plus offsets over a dense node index, which cuts per-node
+    /// allocation overhead and makes neighbor scans cache-friendly. Edge
+    /// order within each node is preserved, so traversal results are
+    /// unchanged. No-op if already frozen.
+    ///
+    /// # Panics
+    /// Panics if the graph has `u32::MAX` nodes or more.
+    pub fn freeze(&mut self) {
+        self.try_freeze().unwrap_or_else(|e| fail(e))
+    }
+
+    /// `freeze`, failing with `TooManyNodes` instead of panicking. The
+    /// graph stays unfrozen on failure.
+    pub fn try_freeze(&mut self) -> Result<(), GraphError> {
+        if let Adjacency::Lists { outgoing, incoming } = &mut self.adjacency {
+            let ids = Csr::node_ids(&self.nodes, outgoing, incoming);
+            if ids.len() >= u32::MAX as usize {
+                return Err(GraphError::TooManyNodes);
+            }
+            let mut csr = Csr::build(ids, std::mem::take(outgoing), std::mem::take(incoming));
+            if self.group_rel_types {
+                csr.group_by_rel_type();
+            } else if self.sort_targets {
+                csr.sort_by_target();
+            }
+            self.adjacency = Adjacency::Csr(csr);
+        }
+        Ok(())
+    }
+
+    /// Heap bytes `freeze` allocates on top of `memory_usage()` at its
+    /// peak; 0 if already frozen.
+    ///
+    /// The lists are released as they are packed, but until then they
+    /// coexist with a packed edge array, and the packed arrays with the
+    /// dense targets, index and stored edges. The figure errs high, so a
+    /// memory cap checked against `memory_usage() + freeze_peak_bytes()`
+    /// also holds while freezing.
+    pub fn freeze_peak_bytes(&self) -> usize {
+        let Adjacency::Lists { outgoing, incoming } = &self.adjacency else {
+            return 0;
+        };
+        let nodes = self.nodes.len() + outgoing.len() + incoming.len();
+        let edges = self.edge_count();
+        mem::array::<Edge>(edges)
+            + mem::array::<StoredEdge>(edges)
+            + 2 * mem::array::<u32>(edges)
+            + 2 * mem::array::<usize>(nodes + 1)
+            + mem::array::<NodeId>(nodes)
+            + nodes * (std::mem::size_of::<NodeId>() + std::mem::size_of::<u32>() + 1)
+    }
+
+    /// Keep each node's frozen edges grouped by relationship type, so
+    /// `edges_of_type` binary-searches to the matching run instead of
+    /// scanning every edge of a high-degree node.
+    ///
+    /// Edges of one type keep their relative order, but traversals that
+    /// walk all edges see them in type order rather than load order. Takes
+    /// effect now if frozen and on every later freeze; not recorded in
+    /// snapshots. Sorting is O(E log d) for max degree d. Replaces
+    /// `sort_by_target`.
+    pub fn group_by_rel_type(&mut self) {
+        (self.group_rel_types, self.sort_targets) = (true, false);
+        if let Adjacency::Csr(csr) = &mut self.adjacency {
+            if !csr.grouped {
+                csr.group_by_rel_type();
+            }
+        }
+    }
+
+    /// Keep each node's frozen edges sorted by target, so `has_edge`
+    /// binary-searches instead of scanning, which speeds up triangle
+    /// counting in `local_clustering` on high-degree nodes.
+    ///
+    /// Parallel edges to one target stay together, ordered by rel type.
+    /// Like `group_by_rel_type`, which it replaces, this takes effect now
+    /// if frozen and on every later freeze, and is not recorded in
+    /// snapshots.
+    pub fn sort_by_target(&mut self) {
+        (self.group_rel_types, self.sort_targets) = (false, true);
+        if let Adjacency::Csr(csr) = &mut self.adjacency {
+            if !csr.sorted {
+                csr.sort_by_target();
+            }
+        }
+    }
+
+    /// True if frozen with each node's edges sorted by target (see
+    /// `sort_by_target`).
+    pub fn is_sorted_by_target(&self) -> bool {
+        matches!(&self.adjacency, Adjacency::Csr(csr) if csr.sorted)
+    }
+
+    /// True if there is an edge `from → to` of any type. O(log d) once
+    /// sorted by target (see `sort_by_target`), otherwise a scan of the
+    /// shorter of `from`'s outgoing and `to`'s incoming edges.
+    pub fn has_edge(&self, from: NodeId, to: NodeId) -> bool {
+        let out = self.neighbors_out(from);
+        let inc = self.neighbors_in(to);
+        let (edges, target) = if out.len() <= inc.len() {
+            (out, to)
+        } else {
+            (inc, from)
+        };
+        if self.is_sorted_by_target() {
+            let at = edges.partition_point(|e| e.target < target);
+            edges.get(at).is_some_and(|e| e.target == target)
+        } else {
+            edges.iter().any(|e| e.target == target)
+        }
+    }
+
+    /// Collapse parallel edges with the same (from, to, rel_type) into
+    /// their first copy, which keeps its weight and timestamp and takes the
+    /// highest confidence of the copies. Returns the number of edges removed.
+    ///
+    /// Repeated ingestion runs leave such copies behind, and each one counts
+    /// toward degrees and memory. A frozen graph is unpacked and re-packed,
+    /// so this costs O(E).
+    pub fn dedupe_edges(&mut self) -> usize {
+        let was_frozen = self.is_frozen();
+        let (outgoing, incoming) = self.lists_mut();
+        let removed = dedupe_lists(outgoing);
+        dedupe_lists(incoming);
+        if was_frozen {
+            self.freeze();
+        }
+        removed
+    }
+
+    /// Raise the first (from, to, rel_type) edge's confidence to
+    /// `confidence` if that is higher, in both directions.
+    pub(crate) fn raise_confidence(
+        &mut self,
+        from: NodeId,
+        to: NodeId,
+        rel_type: RelTypeId,
+        confidence: f32,
+    ) {
+        let (outgoing, incoming) = self.lists_mut();
+        for (list, other) in [(outgoing.get_mut(&from), to), (incoming.get_mut(&to), from)] {
+            let first = list
+                .into_iter()
+                .flatten()
+                .find(|e| e.target == other && e.rel_type == rel_type);
+            if let Some(e) = first {
+                // f32::max ignores NaN, so any confidence beats none
+                e.confidence = e.confidence.max(confidence);
+            }
+        }
+    }
+
+    /// Unpack CSR back into adjacency lists ahead of a batch of mutations.
+    ///
+    /// `apply_delta` and `remove_node` re-pack a frozen graph after each
+    /// call; on a thawed graph they leave the lists alone, so a run of
+    /// small changes costs one O(E) unpack plus one `freeze()` at the end.
+    /// No-op if not frozen.
+    pub fn thaw(&mut self) {
+        self.lists_mut();
+    }
+
+    /// True if the adjacency is packed into CSR form (see `freeze()`).
+    pub fn is_frozen(&self) -> bool {
+        matches!(self.adjacency, Adjacency::Csr(_))
+    }
+
+    /// Release capacity left over from loading.
+    ///
+    /// Adjacency lists are pre-allocated to the expected average degree and
+    /// the maps to `with_capacity` estimates, so a skewed graph keeps a lot
+    /// of capacity it never uses, all of it counted by `memory_usage()`.
+    /// This shrinks every Vec to its length and rehashes every map to its
+    /// size. CSR arrays are built to size, so on a frozen graph mostly the
+    /// maps shrink. O(V + E); call once loading is done.
+    pub fn compact(&mut self) {
+        match &mut self.adjacency {
+            Adjacency::Lists { outgoing, incoming } => {
+                for lists in [outgoing, incoming] {
+                    lists.values_mut().for_each(Vec::shrink_to_fit);
+                    lists.shrink_to_fit();
+                }
+            }
+            Adjacency::Csr(csr) => csr.shrink_to_fit(),
+        }
+        for info in self.nodes.values_mut() {
+            if let Some(props) = &mut info.properties {
+                props.shrink_to_fit();
+            }
+        }
+        self.nodes.shrink_to_fit();
+        self.app_id_index.shrink_to_fit();
+        self.int_app_id_index.shrink_to_fit();
+        for entries in self.secondary_indices.values_mut() {
+            entries.shrink_to_fit();
+        }
+        self.secondary_indices.shrink_to_fit();
+        self.rel_types.shrink_to_fit();
+        self.rel_type_map.shrink_to_fit();
+        self.labels.shrink_to_fit();
+        self.label_map.shrink_to_fit();
+        self.label_nodes.iter_mut().for_each(Vec::shrink_to_fit);
+        self.label_nodes.shrink_to_fit();
+        self.prop_keys.shrink_to_fit();
+        self.prop_key_map.shrink_to_fit();
+        self.edge_props
+            .values_mut()
+            .for_each(PropertyBag::shrink_to_fit);
+        self.edge_props.shrink_to_fit();
+    }
+
+    /// Replace the adjacency with prebuilt CSR arrays over `ids` (ascending,
+    /// as `sorted_ids()` returns). Offsets must have `ids.len() + 1` entries;
+    /// callers validate. Unpacked into lists unless `frozen`. Returns false,
+    /// leaving the graph untouched, if an edge target is not in `ids`.
+    pub(crate) fn install_csr(
+        &mut self,
+        ids: Vec<NodeId>,
+        outgoing: (Vec<usize>, Vec<Edge>),
+        incoming: (Vec<usize>, Vec<Edge>),
+        frozen: bool,
+    ) -> bool {
+        let Some(csr) = Csr::from_parts(ids, outgoing, incoming) else {
+            return false;
+        };
+        self.adjacency = Adjacency::Csr(csr);
+        if !frozen {
+            self.lists_mut();
+        }
+        true
+    }
+
+    /// Replace the adjacency with CSR arrays mapped from a file, over
+    /// `ids` matching the node table. Arrays are (offsets, edges, dense
+    /// targets) per direction; `mapped.rs` checks offsets, not targets.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn install_mapped(
+        &mut self,
+        ids: Buf<NodeId>,
+        (out_offsets, out_edges, out_targets): (Buf<usize>, Buf<StoredEdge>, Buf<u32>),
+        (in_offsets, in_edges, in_targets): (Buf<usize>, Buf<StoredEdge>, Buf<u32>),
+        grouped: bool,
+        sorted: bool,
+    ) {
+        self.adjacency = Adjacency::Csr(Csr {
+            index: HashMap::new(),
+            ids,
+            out_offsets,
+            out_edges,
+            out_targets,
+            in_offsets,
+            in_edges,
+            in_targets,
+            grouped,
+            sorted,
+        });
+        (self.group_rel_types, self.sort_targets) = (grouped, sorted);
+    }
+
+    /// True if frozen with each node's edges grouped by rel type.
+    #[cfg(feature = "mmap")]
+    pub(crate) fn is_grouped(&self) -> bool {
+        matches!(&self.adjacency, Adjacency::Csr(csr) if csr.grouped)
+    }
+
+    /// Array-indexed view of the adjacency, available once frozen.
+    pub fn dense(&self) -> Option<DenseAdjacency<'_>> {
+        match &self.adjacency {
+            Adjacency::Csr(csr) => Some(DenseAdjacency { csr }),
+            Adjacency::Lists { .. } => None,
+        }
+    }
+
+    /// Mutable adjacency lists, unpacking CSR first if frozen.
+    pub(crate) fn lists_mut(&mut self) -> (&mut EdgeLists, &mut EdgeLists) {
+        if let Adjacency::Csr(csr) = &mut self.adjacency {
+            let (outgoing, incoming) = std::mem::take(csr).into_lists();
+            self.adjacency = Adjacency::Lists { outgoing, incoming };
+        }
+        match &mut self.adjacency {
+            Adjacency::Lists { outgoing, incoming } => (outgoing, incoming),
+            Adjacency::Csr(_) => unreachable!("unpacked above"),
+        }
+    }
+
+    /// Bulk load from EdgeRecord structs.
+    /// This is the primary load path — mirrors what the SPI query returns from AGE.
+    /// Every record is accepted as is; `GraphBuilder` adds ingestion checks.
+    pub fn load_edges<I>(&mut self, edges: I)
+    where
+        I: IntoIterator<Item = EdgeRecord>,
+    {
+        for rec in edges {
+            self.load_record(rec);
+        }
+    }
+
+    /// `load_edges`, stopping at the first record that would exceed an
+    /// interning limit. Records before it stay loaded; the failing record
+    /// adds no node or edge.
+    pub fn try_load_edges<I>(&mut self, edges: I) -> Result<(), GraphError>
+    where
+        I: IntoIterator<Item = EdgeRecord>,
+    {
+        for rec in edges {
+            self.try_load_record(rec)?;
+        }
+        Ok(())
+    }
+
+    /// Streaming load with periodic progress reporting.
+    ///
+    /// Consumes `records` lazily — the source is only pulled as fast as the
+    /// graph can absorb rows, so a bounded `std::sync::mpsc::sync_channel`
+    /// receiver gives the producer natural backpressure. Every `report_every`
+    /// records (and once at the end) `on_progress` receives a snapshot of the
+    /// counts and memory usage; returning `ControlFlow::Break` stops the load,
+    /// leaving the records consumed so far in the graph.
+    ///
+    /// `report_every = 0` disables periodic reports (final report only).
+    /// Each report calls `memory_usage()`, which is O(V), so keep the
+    /// interval in the tens of thousands for large loads.
+    pub fn load_stream<I, F>(&mut self, records: I, report_every: usize, mut on_progress: F) -> LoadProgress
+    where
+        I: IntoIterator<Item = EdgeRecord>,
+        F: FnMut(&LoadProgress) -> ControlFlow<()>,
+    {
+        let mut consumed = 0usize;
+
+        for rec in records {
+            self.load_record(rec);
+            consumed += 1;
+
+            if report_every > 0 && consumed.is_multiple_of(report_every) {
+                let progress = self.load_progress(consumed, false);
+                if on_progress(&progress).is_break() {
+                    return self.load_progress(consumed, true);
+                }
+            }
+        }
+
+        let progress = self.load_progress(consumed, false);
+        // The stream is exhausted; a Break from the final report has nothing left to stop.
+        let _ = on_progress(&progress);
+        progress
+    }
+
+    fn load_progress(&self, records: usize, aborted: bool) -> LoadProgress {
+        LoadProgress {
+            records,
+            node_count: self.node_count(),
+            edge_count: self.edge_count(),
+            memory_bytes: self.memory_usage(),
+            aborted,
+        }
+    }
+
+    /// Insert one EdgeRecord: register both endpoints, intern the type, add the edge.
+    pub(crate) fn load_record(&mut self, rec: EdgeRecord) {
+        self.try_load_record(rec).unwrap_or_else(|e| fail(e))
+    }
+
+    pub(crate) fn try_load_record(&mut self, rec: EdgeRecord) -> Result<(), GraphError> {
+        // Intern everything first so a failing record leaves only names behind
+        let from_label = self.try_intern_label(&rec.from_label)?;
+        let to_label = self.try_intern_label(&rec.to_label)?;
+        let rt = self.try_intern_rel_type(&rec.rel_type)?;
+        let props = self.try_intern_props(rec.properties)?;
+
+        // Register app IDs (first occurrence wins)
+        if let Some(ref aid) = rec.from_app_id {
+            self.app_id_index.entry(aid.clone()).or_insert(rec.from_id);
+        }
+        if let Some(ref aid) = rec.to_app_id {
+            self.app_id_index.entry(aid.clone()).or_insert(rec.to_id);
+        }
+
+        // Register nodes (first occurrence wins for label/app_id)
+        let ends = [
+            (rec.from_id, from_label, rec.from_app_id),
+            (rec.to_id, to_label, rec.to_app_id),
+        ];
+        for (id, label, app_id) in ends {
+            if let Entry::Vacant(slot) = self.nodes.entry(id) {
+                slot.insert(NodeInfo {
+                    label,
+                    extra_labels: Box::default(),
+                    app_id,
+                    properties: None,
+                });
+                self.partition_add(id, &[label]);
+            }
+        }
+
+        self.add_timestamped_edge(
+            rec.from_id,
+            rec.to_id,
+            rt,
+            rec.confidence,
+            rec.weight,
+            rec.created_at,
+        );
+        self.store_edge_properties((rec.from_id, rec.to_id, rt), props);
+        Ok(())
+    }
+
+    /// Apply an incremental change: drop the `removed` edges, then load `added`.
+    ///
+    /// Removals run first, so listing an edge in both replaces it (e.g. to
+    /// change its confidence). Keys that match nothing are ignored. Added
+    /// records go through the normal load path, so endpoints not yet in the
+    /// graph are registered with the record's label and app ID. Nodes left
+    /// without edges stay in the graph.
+    ///
+    /// A frozen graph is unpacked once for the whole delta and packed again
+    /// afterwards, so cost is O(E) regardless of delta size — still far
+    /// cheaper than a reload from the database.
+    pub fn apply_delta(&mut self, added: &[EdgeRecord], removed: &[EdgeKey]) -> DeltaOutcome {
+        let was_frozen = self.is_frozen();
+        let mut outcome = DeltaOutcome::default();
+
+        let keys: Vec<(NodeId, NodeId, RelTypeId)> = removed
+            .iter()
+            .filter_map(|k| Some((k.from_id, k.to_id, self.rel_type_id(&k.rel_type)?)))
+            .collect();
+        if !keys.is_empty() {
+            for key in &keys {
+                self.edge_props.remove(key);
+            }
+            let (outgoing, incoming) = self.lists_mut();
+            for (from, to, rt) in keys {
+                if let Some(list) = outgoing.get_mut(&from) {
+                    let before = list.len();
+                    list.retain(|e| !(e.target == to && e.rel_type == rt));
+                    outcome.removed += before - list.len();
+                }
+                if let Some(list) = incoming.get_mut(&to) {
+                    list.retain(|e| !(e.target == from && e.rel_type == rt));
+                }
+            }
+        }
+
+        for rec in added {
+            self.load_record(rec.clone());
+        }
+        outcome.added = added.len();
+
+        if was_frozen {
+            self.freeze();
+        }
+        outcome
+    }
+
+    /// Look up a node by its application-level ID (e.g. concept_id).
+    pub fn resolve_app_id(&self, app_id: &str) -> Option<NodeId> {
+        self.app_id_index.get(app_id).copied()
+    }
+
+    /// Register an integer application ID, for graphs keyed by a bigint
+    /// external ID. It goes into its own index, so lookups never format or
+    /// hash it as text. As with text app IDs the first node given a value
+    /// keeps it; returns false if it already resolves to another node. A
+    /// node without a text app ID shows this one, in decimal, as `app_id`.
+    pub fn index_int_app_id(&mut self, app_id: i64, id: NodeId) -> bool {
+        if let Some(info) = self.nodes.get_mut(&id) {
+            if info.app_id.is_none() {
+                info.app_id = Some(app_id.to_string());
+            }
+        }
+        *self.int_app_id_index.entry(app_id).or_insert(id) == id
+    }
+
+    /// Look up a node by its integer application ID.
+    pub fn resolve_int_app_id(&self, app_id: i64) -> Option<NodeId> {
+        self.int_app_id_index.get(&app_id).copied()
+    }
+
+    /// Add `value` → `id` to the secondary index `index`, creating the index
+    /// on first use. As with app IDs the first node given a value keeps it;
+    /// returns false if the value already resolves to another node.
+    pub fn index_node(&mut self, index: &str, value: String, id: NodeId) -> bool {
+        let entries = match self.secondary_indices.get_mut(index) {
+            Some(entries) => entries,
+            None => self.secondary_indices.entry(index.to_string()).or_default(),
+        };
+        *entries.entry(value).or_insert(id) == id
+    }
+
+    /// Look up a node by its value in the secondary index `index`.
+    /// None if the index or the value is unknown.
+    pub fn resolve_by(&self, index: &str, value: &str) -> Option<NodeId> {
+        self.secondary_indices.get(index)?.get(value).copied()
+    }
+
+    /// Names of the secondary indices, sorted.
+    pub fn index_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.secondary_indices.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Resolve a node given as text: a text app ID first, then an integer
+    /// app ID, then a numeric node ID present in the graph.
+    pub fn resolve_node(&self, id: &str) -> Result<NodeId, GraphError> {
+        self.resolve_app_id(id)
+            .or_else(|| {
+                let int_id = id.parse::<i64>().ok()?;
+                self.resolve_int_app_id(int_id)
+            })
+            .or_else(|| {
+                id.parse::<NodeId>()
+                    .ok()
+                    .filter(|id| self.nodes.contains_key(id))
+            })
+            .ok_or_else(|| GraphError::NodeNotFound(id.to_string()))
+    }
+
+    /// Get node metadata.
+    pub fn node(&self, id: NodeId) -> Option<&NodeInfo> {
+        self.nodes.get(&id)
+    }
+
+    /// Get outgoing edges for a node.
+    pub fn neighbors_out(&self, id: NodeId) -> Neighbors<'_> {
+        match &self.adjacency {
+            Adjacency::Lists { outgoing, .. } => outgoing
+                .get(&id)
+                .map(|v| v.as_slice().into())
+                .unwrap_or_default(),
+            Adjacency::Csr(csr) => csr.out(id),
+        }
+    }
+
+    /// Get incoming edges for a node.
+    pub fn neighbors_in(&self, id: NodeId) -> Neighbors<'_> {
+        match &self.adjacency {
+            Adjacency::Lists { incoming, .. } => incoming
+                .get(&id)
+                .map(|v| v.as_slice().into())
+                .unwrap_or_default(),
+            Adjacency::Csr(csr) => csr.inc(id),
+        }
+    }
+
+    /// Every edge once, in stored orientation, as (from, to, rel type,
+    /// confidence). Ordered by source node ID, then by each node's edge
+    /// order. Confidence is `NAN` where not loaded, as on `Edge`.
+    pub fn edges_iter(&self) -> impl Iterator<Item = (NodeId, NodeId, RelTypeId, f32)> + '_ {
+        self.sorted_ids().into_iter().flat_map(move |from| {
+            self.neighbors_out(from)
+                .iter()
+                .map(move |e| (from, e.target, e.rel_type, e.confidence))
+        })
+    }
+
+    /// Edges at `node` in `direction`, each tagged with the side it was
+    /// stored on; `Both` yields outgoing edges first. On an incoming edge
+    /// `target` is the source node.
+    pub fn edges_of(
+        &self,
+        node: NodeId,
+        direction: TraversalDirection,
+    ) -> impl Iterator<Item = (Edge, Direction)> + '_ {
+        let (use_out, use_inc) = match direction {
+            TraversalDirection::Outgoing => (true, false),
+            TraversalDirection::Incoming => (false, true),
+            TraversalDirection::Both => (true, true),
+        };
+        let out = if use_out {
+            self.neighbors_out(node)
+        } else {
+            Neighbors::default()
+        };
+        let inc = if use_inc {
+            self.neighbors_in(node)
+        } else {
+            Neighbors::default()
+        };
+        out.iter()
+            .map(|e| (e, Direction::Outgoing))
+            .chain(inc.iter().map(|e| (e, Direction::Incoming)))
+    }
+
+    /// `edges_of` restricted to edges of `rel_type`. With the graph grouped
+    /// by rel type (see `group_by_rel_type`) only the matching edges are
+    /// touched; otherwise each node's edges are scanned and filtered.
+    pub fn edges_of_type(
+        &self,
+        node: NodeId,
+        rel_type: RelTypeId,
+        direction: TraversalDirection,
+    ) -> impl Iterator<Item = (Edge, Direction)> + '_ {
+        let (use_out, use_inc) = match direction {
+            TraversalDirection::Outgoing => (true, false),
+            TraversalDirection::Incoming => (false, true),
+            TraversalDirection::Both => (true, true),
+        };
+        let out = if use_out {
+            self.typed_run(self.neighbors_out(node), rel_type)
+        } else {
+            Neighbors::default()
+        };
+        let inc = if use_inc {
+            self.typed_run(self.neighbors_in(node), rel_type)
+        } else {
+            Neighbors::default()
+        };
+        out.iter()
+            .map(|e| (e, Direction::Outgoing))
+            .chain(inc.iter().map(|e| (e, Direction::Incoming)))
+            .filter(move |(e, _)| e.rel_type == rel_type)
+    }
+
+    /// Narrow one node's edges to the `rel_type` run if grouped; otherwise
+    /// return them all for the caller to filter.
+    fn typed_run<'a>(&self, edges: Neighbors<'a>, rel_type: RelTypeId) -> Neighbors<'a> {
+        match &self.adjacency {
+            Adjacency::Csr(csr) if csr.grouped => Csr::typed_run(edges, rel_type),
+            _ => edges,
+        }
+    }
+
+    /// Get both outgoing and incoming edges, tagged with traversal direction.
+    pub fn neighbors_all(&self, id: NodeId) -> impl Iterator<Item = (Edge, Direction)> + '_ {
+        self.neighbors_out(id)
             .iter()
             .map(|e| (e, Direction::Outgoing))
             .chain(
@@ -236,6 +1951,23 @@ impl Graph {
             )
     }
 
+    /// Every node ID with metadata or edges, ascending.
+    pub(crate) fn sorted_ids(&self) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = match &self.adjacency {
+            Adjacency::Lists { outgoing, incoming } => self
+                .nodes
+                .keys()
+                .chain(outgoing.keys())
+                .chain(incoming.keys())
+                .copied()
+                .collect(),
+            Adjacency::Csr(csr) => return csr.ids.to_vec(),
+        };
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
     /// Iterate over all node IDs and their metadata.
     pub fn nodes_iter(&self) -> impl Iterator<Item = (&NodeId, &NodeInfo)> {
         self.nodes.iter()
@@ -246,71 +1978,89 @@ impl Graph {
     }
 
     pub fn edge_count(&self) -> usize {
-        self.outgoing.values().map(|v| v.len()).sum()
+        match &self.adjacency {
+            Adjacency::Lists { outgoing, .. } => outgoing.values().map(|v| v.len()).sum(),
+            Adjacency::Csr(csr) => csr.out_edges.len(),
+        }
     }
 
     pub fn rel_type_count(&self) -> usize {
         self.rel_types.len()
     }
 
-    /// Approximate memory usage in bytes.
+    /// Heap memory held by the graph, in bytes.
     ///
-    /// Accounts for HashMap bucket arrays, Vec capacity (not just len),
-    /// and String heap allocations.
+    /// Counts every allocation from its actual capacity (HashMap tables
+    /// including control bytes, Vec and String capacity rather than length,
+    /// interner map keys, boxed property bags), each rounded up to the
+    /// chunk size malloc hands out. The `Graph` struct itself is not
+    /// included.
     pub fn memory_usage(&self) -> usize {
-        use std::mem::size_of;
+        // Nodes: table plus each node's app ID, extra labels and property bag
+        let nodes_mem = mem::hash_map(&self.nodes)
+            + self
+                .nodes
+                .values()
+                .map(|n| {
+                    n.app_id.iter().map(mem::string).sum::<usize>()
+                        + mem::array::<LabelId>(n.extra_labels.len())
+                        + n.properties.as_deref().map_or(0, |bag| {
+                            mem::array::<PropertyBag>(1) + bag.heap_size()
+                        })
+                })
+                .sum::<usize>();
 
-        // HashMap overhead: ~1 byte control + key-value pair per bucket, load factor ~87.5%
-        let hashmap_overhead = |len: usize, kv_size: usize| -> usize {
-            let buckets = (len * 8 / 7).next_power_of_two().max(1);
-            buckets * (1 + kv_size)
+        let edges_mem = match &self.adjacency {
+            Adjacency::Lists { outgoing, incoming } => [outgoing, incoming]
+                .iter()
+                .map(|lists| {
+                    mem::hash_map(lists)
+                        + lists
+                            .values()
+                            .map(|v| mem::array::<Edge>(v.capacity()))
+                            .sum::<usize>()
+                })
+                .sum::<usize>(),
+            Adjacency::Csr(csr) => {
+                mem::array::<StoredEdge>(csr.out_edges.capacity())
+                    + mem::array::<StoredEdge>(csr.in_edges.capacity())
+                    + mem::array::<u32>(csr.out_targets.capacity())
+                    + mem::array::<u32>(csr.in_targets.capacity())
+                    + mem::array::<usize>(csr.out_offsets.capacity())
+                    + mem::array::<usize>(csr.in_offsets.capacity())
+                    + mem::array::<NodeId>(csr.ids.capacity())
+                    + mem::hash_map(&csr.index)
+            }
         };
 
-        // Nodes: HashMap<NodeId, NodeInfo> + estimated 32 bytes avg String heap per node
-        let nodes_mem = hashmap_overhead(
-            self.nodes.len(),
-            size_of::<NodeId>() + size_of::<NodeInfo>(),
-        ) + self.nodes.len() * 32;
-
-        // Edges: use Vec capacity (not len) to account for over-allocation
-        let out_edges: usize = self
-            .outgoing
-            .values()
-            .map(|v| v.capacity() * size_of::<Edge>())
-            .sum::<usize>()
-            + hashmap_overhead(
-                self.outgoing.len(),
-                size_of::<NodeId>() + size_of::<Vec<Edge>>(),
-            );
-
-        let in_edges: usize = self
-            .incoming
-            .values()
-            .map(|v| v.capacity() * size_of::<Edge>())
-            .sum::<usize>()
-            + hashmap_overhead(
-                self.incoming.len(),
-                size_of::<NodeId>() + size_of::<Vec<Edge>>(),
-            );
-
-        // App ID index: HashMap<String, NodeId> + estimated 24 bytes avg String heap per key
-        let index_mem = hashmap_overhead(
-            self.app_id_index.len(),
-            size_of::<String>() + size_of::<NodeId>(),
-        ) + self.app_id_index.len() * 24;
+        let index_mem = mem::hash_map(&self.app_id_index)
+            + self.app_id_index.keys().map(mem::string).sum::<usize>()
+            + mem::hash_map(&self.int_app_id_index)
+            + mem::hash_map(&self.secondary_indices)
+            + self
+                .secondary_indices
+                .iter()
+                .map(|(name, entries)| {
+                    mem::string(name)
+                        + mem::hash_map(entries)
+                        + entries.keys().map(mem::string).sum::<usize>()
+                })
+                .sum::<usize>();
 
-        // Rel type interning
-        let rel_mem = self
-            .rel_types
-            .iter()
-            .map(|s| s.capacity() + size_of::<String>())
-            .sum::<usize>()
-            + hashmap_overhead(
-                self.rel_type_map.len(),
-                size_of::<String>() + size_of::<RelTypeId>(),
-            );
+        // Interned names are stored twice: in the ID-ordered Vec and as map keys
+        let rel_mem = mem::interner(&self.rel_types, &self.rel_type_map);
+        let labels_mem = mem::interner(&self.labels, &self.label_map)
+            + mem::array::<Vec<NodeId>>(self.label_nodes.capacity())
+            + self
+                .label_nodes
+                .iter()
+                .map(|members| mem::array::<NodeId>(members.capacity()))
+                .sum::<usize>();
+        let props_mem = mem::interner(&self.prop_keys, &self.prop_key_map)
+            + mem::hash_map(&self.edge_props)
+            + self.edge_props.values().map(PropertyBag::heap_size).sum::<usize>();
 
-        nodes_mem + out_edges + in_edges + index_mem + rel_mem
+        nodes_mem + edges_mem + index_mem + rel_mem + labels_mem + props_mem
     }
 }
 
@@ -319,3 +2069,610 @@ impl Default for Graph {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    #[test]
+    fn test_edge_layout_is_24_bytes() {
+        // Mapped files and memory estimates rely on this layout
+        assert_eq!(std::mem::size_of::<Edge>(), 24);
+        assert_eq!(std::mem::align_of::<Edge>(), 8);
+    }
+
+    #[cfg(feature = "compact-ids")]
+    #[test]
+    fn test_compact_ids_keep_wide_targets() {
+        assert_eq!(std::mem::size_of::<StoredEdge>(), 16);
+
+        // AGE-sized IDs come back whole from the target-less CSR
+        let (a, b, c) = (1 << 48, (3 << 48) + 7, u64::MAX - 1);
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(a, b, "A"), edge(a, c, "B"), edge(c, a, "A")]);
+        let lists = g.memory_usage();
+        g.freeze();
+        let out: Vec<NodeId> = g.neighbors_out(a).iter().map(|e| e.target).collect();
+        assert_eq!(out, vec![b, c]);
+        assert_eq!(g.neighbors_in(a).first().unwrap().target, c);
+        let dense = g.dense().unwrap();
+        let ia = dense.index_of(a).unwrap();
+        let targets: Vec<NodeId> = dense.out(ia).map(|(_, e)| e.target).collect();
+        assert_eq!(targets, vec![b, c]);
+        assert!(g.has_edge(c, a) && !g.has_edge(b, a));
+        assert!(g.memory_usage() < lists);
+
+        // Thawing restores full edges
+        g.thaw();
+        assert_eq!(g.neighbors_out(c).first().unwrap().target, a);
+        g.sort_by_target();
+        g.freeze();
+        assert!(g.has_edge(a, c));
+    }
+
+    // --- Streaming load tests ---
+
+    #[test]
+    fn test_load_stream_matches_load_edges() {
+        let records: Vec<EdgeRecord> = (0..100).map(|i| edge(i, i + 1, "NEXT")).collect();
+
+        let mut bulk = Graph::new();
+        bulk.load_edges(records.clone());
+
+        let mut streamed = Graph::new();
+        let progress = streamed.load_stream(records, 10, |_| ControlFlow::Continue(()));
+
+        assert_eq!(streamed.node_count(), bulk.node_count());
+        assert_eq!(streamed.edge_count(), bulk.edge_count());
+        assert_eq!(progress.records, 100);
+        assert_eq!(progress.edge_count, 100);
+        assert_eq!(progress.node_count, 101);
+        assert!(!progress.aborted);
+    }
+
+    #[test]
+    fn test_load_stream_reports_periodically() {
+        let mut g = Graph::new();
+        let mut reports = Vec::new();
+        g.load_stream((0..25).map(|i| edge(i, i + 1, "NEXT")), 10, |p| {
+            reports.push(p.records);
+            ControlFlow::Continue(())
+        });
+        // Every 10 records, plus the final report
+        assert_eq!(reports, vec![10, 20, 25]);
+    }
+
+    #[test]
+    fn test_load_stream_abort() {
+        let mut g = Graph::new();
+        let progress = g.load_stream((0..100).map(|i| edge(i, i + 1, "NEXT")), 10, |p| {
+            if p.edge_count >= 30 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(progress.aborted);
+        assert_eq!(progress.records, 30);
+        assert_eq!(g.edge_count(), 30);
+    }
+
+    #[test]
+    fn test_load_stream_memory_reported() {
+        let mut g = Graph::new();
+        let progress = g.load_stream((0..50).map(|i| edge(i, i + 1, "NEXT")), 0, |_| {
+            ControlFlow::Continue(())
+        });
+        assert_eq!(progress.memory_bytes, g.memory_usage());
+        assert!(progress.memory_bytes > 0);
+    }
+
+    #[test]
+    fn test_load_stream_from_bounded_channel() {
+        use std::sync::mpsc::sync_channel;
+
+        // Bounded channel: the producer blocks until the loader catches up
+        let (tx, rx) = sync_channel::<EdgeRecord>(4);
+        let producer = std::thread::spawn(move || {
+            for i in 0..50 {
+                tx.send(edge(i, i + 1, "NEXT")).unwrap();
+            }
+        });
+
+        let mut g = Graph::new();
+        let progress = g.load_stream(rx, 16, |_| ControlFlow::Continue(()));
+        producer.join().unwrap();
+
+        assert_eq!(progress.records, 50);
+        assert_eq!(g.edge_count(), 50);
+    }
+
+    // --- CSR tests ---
+
+    #[test]
+    fn test_freeze_preserves_adjacency() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "A"),
+            edge(1, 3, "B"),
+            edge(3, 1, "A"),
+            edge(2, 2, "SELF"),
+        ]);
+        g.add_node(9, "Lonely".to_string(), None);
+        let snapshot = |g: &Graph| {
+            let pairs = |edges: Neighbors| -> Vec<(NodeId, RelTypeId)> {
+                edges.iter().map(|e| (e.target, e.rel_type)).collect()
+            };
+            [1, 2, 3, 9, 42]
+                .iter()
+                .map(|&id| (id, pairs(g.neighbors_out(id)), pairs(g.neighbors_in(id))))
+                .collect::<Vec<_>>()
+        };
+
+        let before = snapshot(&g);
+        assert!(!g.is_frozen());
+        g.freeze();
+        assert!(g.is_frozen());
+        assert_eq!(snapshot(&g), before);
+        assert_eq!(g.edge_count(), 4);
+        assert_eq!(g.node_count(), 4);
+        g.freeze();
+        assert_eq!(snapshot(&g), before);
+    }
+
+    #[test]
+    fn test_dense_adjacency_translates_sparse_ids() {
+        use crate::traversal::bfs_distances;
+
+        let (a, b, c) = (1 << 48, (1 << 48) + 7, 5 << 40);
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(a, b, "A"), edge(b, c, "B"), edge(c, a, "A")]);
+        assert!(g.dense().is_none());
+        let unfrozen = bfs_distances(&g, a, 3, TraversalDirection::Both, None);
+
+        g.freeze();
+        let dense = g.dense().unwrap();
+        assert_eq!(dense.len(), 3);
+        // Dense order follows ascending node ID
+        assert_eq!((dense.node_id(0), dense.node_id(1), dense.node_id(2)), (c, a, b));
+        assert_eq!(dense.index_of(b), Some(2));
+        assert_eq!(dense.index_of(42), None);
+        let out: Vec<(u32, NodeId)> = dense.out(1).map(|(t, e)| (t, e.target)).collect();
+        assert_eq!(out, vec![(2, b)]);
+        let inc: Vec<u32> = dense.inc(1).map(|(s, _)| s).collect();
+        assert_eq!(inc, vec![0]);
+
+        assert_eq!(bfs_distances(&g, a, 3, TraversalDirection::Both, None), unfrozen);
+    }
+
+    #[test]
+    fn test_edges_iter_and_edges_of() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(3, 1, "A"), edge(1, 2, "B"), edge(1, 3, "A")]);
+        let (a, b) = (g.rel_type_id("A").unwrap(), g.rel_type_id("B").unwrap());
+
+        for frozen in [false, true] {
+            if frozen {
+                g.freeze();
+            }
+            let all: Vec<(NodeId, NodeId, RelTypeId)> =
+                g.edges_iter().map(|(f, t, r, _)| (f, t, r)).collect();
+            assert_eq!(all, vec![(1, 2, b), (1, 3, a), (3, 1, a)]);
+            assert!(g.edges_iter().all(|(_, _, _, c)| c.is_nan()));
+
+            let at = |dir| -> Vec<(NodeId, Direction)> {
+                g.edges_of(1, dir).map(|(e, d)| (e.target, d)).collect()
+            };
+            assert_eq!(at(TraversalDirection::Incoming), vec![(3, Direction::Incoming)]);
+            assert_eq!(
+                at(TraversalDirection::Both),
+                vec![
+                    (2, Direction::Outgoing),
+                    (3, Direction::Outgoing),
+                    (3, Direction::Incoming)
+                ]
+            );
+            assert_eq!(g.edges_of(42, TraversalDirection::Both).count(), 0);
+        }
+    }
+
+    #[test]
+    fn test_frozen_graph_accepts_new_edges() {
+        let mut g = Graph::new();
+        g.load_edges((0..10).map(|i| edge(i, i + 1, "NEXT")));
+        g.freeze();
+        g.load_edges(vec![edge(10, 0, "BACK")]);
+        assert!(!g.is_frozen());
+        assert_eq!(g.edge_count(), 11);
+        assert_eq!(g.neighbors_out(10).first().unwrap().target, 0);
+        assert_eq!(g.neighbors_in(0).first().unwrap().target, 10);
+        assert_eq!(g.neighbors_out(3).first().unwrap().target, 4);
+    }
+
+    fn key(from: NodeId, to: NodeId, rel: &str) -> EdgeKey {
+        EdgeKey {
+            from_id: from,
+            to_id: to,
+            rel_type: rel.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_delta_adds_and_removes() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "A"), edge(1, 2, "A"), edge(1, 2, "B"), edge(2, 3, "A")]);
+
+        let out = g.apply_delta(
+            &[edge(3, 4, "A")],
+            &[key(1, 2, "A"), key(9, 9, "A"), key(2, 3, "UNKNOWN")],
+        );
+        assert_eq!(out, DeltaOutcome { added: 1, removed: 2 });
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.neighbors_out(1).len(), 1);
+        assert_eq!(g.neighbors_in(2).len(), 1);
+        assert_eq!(g.neighbors_in(4).first().unwrap().target, 3);
+        // Endpoints stay even when their last edge goes
+        assert_eq!(g.node_count(), 4);
+    }
+
+    #[test]
+    fn test_apply_delta_replaces_and_keeps_frozen() {
+        let mut g = Graph::new();
+        g.load_edges((0..5).map(|i| edge(i, i + 1, "NEXT")));
+        g.freeze();
+
+        let mut updated = edge(0, 1, "NEXT");
+        updated.confidence = 0.5;
+        let out = g.apply_delta(&[updated], &[key(0, 1, "NEXT")]);
+        assert_eq!(out, DeltaOutcome { added: 1, removed: 1 });
+        assert!(g.is_frozen());
+        assert_eq!(g.edge_count(), 5);
+        assert_eq!(g.neighbors_out(0).first().unwrap().confidence, 0.5);
+        assert_eq!(g.neighbors_in(1).first().unwrap().confidence, 0.5);
+    }
+
+    #[test]
+    fn test_thaw_batches_mutations() {
+        let mut g = Graph::new();
+        g.load_edges((0..5).map(|i| edge(i, i + 1, "NEXT")));
+        g.freeze();
+
+        g.thaw();
+        assert!(!g.is_frozen());
+        g.apply_delta(&[edge(5, 0, "BACK")], &[key(0, 1, "NEXT")]);
+        assert_eq!(g.remove_node(3), Some(2));
+        assert!(!g.is_frozen());
+        g.freeze();
+        assert_eq!(g.edge_count(), 3);
+        assert_eq!(g.neighbors_out(5).first().unwrap().target, 0);
+        assert!(g.neighbors_in(1).is_empty());
+    }
+
+    #[test]
+    fn test_remove_node_cleans_edges_and_index() {
+        let mut g = Graph::new();
+        let mut rec = edge(1, 2, "A");
+        rec.from_app_id = Some("c1".to_string());
+        g.load_edges(vec![
+            rec,
+            edge(2, 1, "B"),
+            edge(1, 1, "SELF"),
+            edge(3, 1, "A"),
+            edge(2, 3, "A"),
+        ]);
+        g.freeze();
+
+        assert_eq!(g.remove_node(1), Some(4));
+        assert!(g.is_frozen());
+        assert!(g.node(1).is_none());
+        assert_eq!(g.resolve_app_id("c1"), None);
+        assert_eq!(g.edge_count(), 1);
+        assert!(g.neighbors_out(1).is_empty());
+        assert_eq!(g.neighbors_out(2).len(), 1);
+        assert!(g.neighbors_in(2).is_empty());
+        assert!(g.neighbors_out(3).is_empty());
+        assert_eq!(g.remove_node(1), None);
+        assert_eq!(g.remove_node(999), None);
+    }
+
+    #[test]
+    fn test_edge_properties_follow_edges() {
+        let mut rec = edge(1, 2, "CITES");
+        rec.properties = vec![
+            ("category".to_string(), PropValue::Text("survey".to_string())),
+            ("source_count".to_string(), PropValue::Int(3)),
+        ];
+        let mut g = Graph::new();
+        g.load_edges(vec![rec, edge(2, 3, "CITES")]);
+        let before = g.memory_usage();
+        g.freeze();
+
+        let (e, dir) = g.neighbors_all(2).find(|(e, _)| e.target == 1).unwrap();
+        assert_eq!(dir, Direction::Incoming);
+        let count = g.edge_property(2, &e, dir, "source_count");
+        assert_eq!(count.and_then(PropValue::as_i64), Some(3));
+        let (e, dir) = g.neighbors_all(1).next().unwrap();
+        let category = g.edge_property(1, &e, dir, "category");
+        assert_eq!(category.and_then(PropValue::as_str), Some("survey"));
+        assert!(g.edge_property(1, &e, dir, "missing").is_none());
+        assert!(g.edge_properties(2, 3, e.rel_type).is_none());
+        assert!(before > Graph::new().memory_usage());
+
+        let cites = e.rel_type;
+        g.apply_delta(&[], &[key(1, 2, "CITES")]);
+        assert!(g.edge_properties(1, 2, cites).is_none());
+    }
+
+    #[test]
+    fn test_edge_timestamps_fit_in_padding() {
+        assert_eq!(size_of::<Edge>(), 24);
+
+        let mut rec = edge(1, 2, "R");
+        rec.created_at = 1_700_000_000;
+        let mut g = Graph::new();
+        g.load_edges(vec![rec, edge(2, 3, "R")]);
+        g.freeze();
+
+        let out = g.neighbors_out(1).first().unwrap();
+        assert_eq!(out.created_at(), Some(1_700_000_000));
+        assert_eq!(
+            g.neighbors_in(2).first().unwrap().created_at(),
+            Some(1_700_000_000)
+        );
+        assert!(out.existed_at(1_700_000_000));
+        assert!(!out.existed_at(1_699_999_999));
+
+        // No timestamp: never filtered out
+        let untimed = g.neighbors_out(2).first().unwrap();
+        assert_eq!(untimed.created_at(), None);
+        assert!(untimed.existed_at(0));
+    }
+
+    #[test]
+    fn test_extra_labels() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "R")]);
+        assert!(g.add_node_labels(1, ["Person", "Author", "Node", "Author"]));
+        assert!(g.add_node_labels(1, vec!["Editor".to_string()]));
+        assert!(!g.add_node_labels(999, ["Person"]));
+
+        assert_eq!(g.node_labels(1), vec!["Node", "Person", "Author", "Editor"]);
+        assert_eq!(g.node_labels(2), vec!["Node"]);
+        assert!(g.node_labels(999).is_empty());
+        assert!(g.node_has_label(1, "Author"));
+        assert!(g.node_has_label(1, "Node"));
+        assert!(!g.node_has_label(2, "Author"));
+        assert!(!g.node_has_label(1, "Missing"));
+        // Primary labels share the interner with extra labels
+        let node = g.label_id("Node").unwrap();
+        assert_eq!(g.node(2).unwrap().label, node);
+        assert_eq!(g.node_label(2), Some("Node"));
+        assert_eq!(g.node_label(999), None);
+    }
+
+    #[test]
+    fn test_freeze_reduces_memory() {
+        let mut g = Graph::with_capacity(1000, 8000);
+        g.load_edges((0..1000).map(|i| edge(i, (i * 7 + 1) % 1000, "R")));
+        let lists = g.memory_usage();
+        let peak = g.freeze_peak_bytes();
+        assert!(peak > 0);
+        g.freeze();
+        assert!(g.memory_usage() < lists, "{} >= {}", g.memory_usage(), lists);
+        assert!(g.memory_usage() <= lists + peak);
+        assert_eq!(g.freeze_peak_bytes(), 0);
+    }
+
+    #[test]
+    fn test_compact_releases_spare_capacity() {
+        // Star graph: the hub's list is full, every leaf's mostly empty
+        let mut g = Graph::with_capacity(1000, 50_000);
+        g.load_edges((1..1000).map(|i| edge(0, i, "R")));
+        g.set_node_properties(1, [("k".to_string(), PropValue::Int(1))]);
+        let before = g.memory_usage();
+        g.compact();
+        let compacted = g.memory_usage();
+        assert!(compacted < before / 2, "{} vs {}", compacted, before);
+        assert_eq!((g.node_count(), g.edge_count()), (1000, 999));
+        assert_eq!(g.neighbors_in(5).first().unwrap().target, 0);
+        assert_eq!(g.node_property(1, "k"), Some(&PropValue::Int(1)));
+
+        g.freeze();
+        let frozen = g.memory_usage();
+        g.compact();
+        assert!(g.memory_usage() <= frozen);
+        assert_eq!(g.neighbors_out(0).len(), 999);
+    }
+
+    #[test]
+    fn test_group_by_rel_type() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "A"),
+            edge(1, 3, "B"),
+            edge(1, 4, "A"),
+            edge(1, 5, "C"),
+            edge(1, 6, "B"),
+            edge(7, 1, "B"),
+        ]);
+        let (a, b) = (g.rel_type_id("A").unwrap(), g.rel_type_id("B").unwrap());
+        let typed = |g: &Graph, rt, dir| -> Vec<NodeId> {
+            g.edges_of_type(1, rt, dir).map(|(e, _)| e.target).collect()
+        };
+        // Ungrouped: filtered scan, in load order
+        assert_eq!(typed(&g, b, TraversalDirection::Outgoing), vec![3, 6]);
+        g.freeze();
+        assert_eq!(typed(&g, a, TraversalDirection::Outgoing), vec![2, 4]);
+
+        g.group_by_rel_type();
+        let targets: Vec<NodeId> = g.neighbors_out(1).iter().map(|e| e.target).collect();
+        assert_eq!(targets, vec![2, 4, 3, 6, 5]);
+        assert_eq!(typed(&g, a, TraversalDirection::Outgoing), vec![2, 4]);
+        assert_eq!(typed(&g, b, TraversalDirection::Both), vec![3, 6, 7]);
+        assert!(typed(&g, 99, TraversalDirection::Both).is_empty());
+
+        // Dense targets stay aligned with the reordered edges
+        let dense = g.dense().unwrap();
+        let from = dense.index_of(1).unwrap();
+        for (idx, e) in dense.out(from) {
+            assert_eq!(dense.node_id(idx), e.target);
+        }
+
+        // Regrouped on the next freeze
+        g.add_edge(1, 8, a, Edge::NO_CONFIDENCE);
+        assert!(!g.is_frozen());
+        g.freeze();
+        assert_eq!(typed(&g, a, TraversalDirection::Outgoing), vec![2, 4, 8]);
+        assert_eq!(g.neighbors_out(1).get(2).unwrap().target, 8);
+    }
+
+    #[test]
+    fn test_sort_by_target() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 5, "A"),
+            edge(1, 3, "B"),
+            edge(1, 9, "A"),
+            edge(1, 3, "A"),
+            edge(7, 1, "B"),
+            edge(2, 1, "A"),
+        ]);
+        assert!(g.has_edge(1, 9) && !g.has_edge(9, 1) && !g.has_edge(1, 4));
+        g.freeze();
+        assert!(!g.is_sorted_by_target());
+
+        g.group_by_rel_type();
+        g.sort_by_target();
+        assert!(g.is_sorted_by_target());
+        let targets: Vec<NodeId> = g.neighbors_out(1).iter().map(|e| e.target).collect();
+        assert_eq!(targets, vec![3, 3, 5, 9]);
+        let a = g.rel_type_id("A").unwrap();
+        assert_eq!(g.neighbors_out(1).first().unwrap().rel_type, a);
+        let sources: Vec<NodeId> = g.neighbors_in(1).iter().map(|e| e.target).collect();
+        assert_eq!(sources, vec![2, 7]);
+        for (from, to, expected) in [(1, 3, true), (1, 9, true), (7, 1, true), (1, 7, false)] {
+            assert_eq!(g.has_edge(from, to), expected, "{from} -> {to}");
+        }
+        assert!(!g.has_edge(1, 4) && !g.has_edge(42, 1));
+
+        // Dense targets stay aligned with the reordered edges
+        let dense = g.dense().unwrap();
+        let from = dense.index_of(1).unwrap();
+        for (idx, e) in dense.out(from) {
+            assert_eq!(dense.node_id(idx), e.target);
+        }
+
+        // Grouping replaces the target order
+        g.group_by_rel_type();
+        assert!(!g.is_sorted_by_target());
+        assert!(g.has_edge(1, 9));
+    }
+
+    #[test]
+    fn test_secondary_indices() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "R"), edge(2, 3, "R")]);
+        assert!(g.index_node("slug", "one".to_string(), 1));
+        assert!(g.index_node("slug", "two".to_string(), 2));
+        assert!(g.index_node("doi", "10.1/x".to_string(), 2));
+        // First node given a value keeps it
+        assert!(!g.index_node("slug", "one".to_string(), 3));
+        assert!(g.index_node("slug", "one".to_string(), 1));
+
+        assert_eq!(g.resolve_by("slug", "one"), Some(1));
+        assert_eq!(g.resolve_by("doi", "10.1/x"), Some(2));
+        assert_eq!(g.resolve_by("doi", "one"), None);
+        assert_eq!(g.resolve_by("missing", "one"), None);
+        assert_eq!(g.index_names(), vec!["doi", "slug"]);
+
+        g.remove_node(2);
+        assert_eq!(g.resolve_by("slug", "two"), None);
+        assert_eq!(g.resolve_by("doi", "10.1/x"), None);
+        assert_eq!(g.resolve_by("slug", "one"), Some(1));
+    }
+
+    #[test]
+    fn test_dedupe_edges() {
+        let mut low = edge(1, 2, "R");
+        low.confidence = 0.2;
+        let mut high = edge(1, 2, "R");
+        high.confidence = 0.8;
+        high.weight = 5.0;
+        let mut g = Graph::new();
+        g.load_edges(vec![low, edge(1, 3, "R"), high, edge(1, 2, "S")]);
+        g.freeze();
+
+        assert_eq!(g.dedupe_edges(), 1);
+        assert!(g.is_frozen());
+        assert_eq!(g.edge_count(), 3);
+        let kept = g.neighbors_out(1).first().unwrap();
+        assert_eq!((kept.target, kept.confidence, kept.weight), (2, 0.8, 1.0));
+        let back = g.neighbors_in(2).first().unwrap();
+        assert_eq!((back.target, back.confidence), (1, 0.8));
+        assert_eq!(g.neighbors_in(2).len(), 2);
+        assert_eq!(g.dedupe_edges(), 0);
+    }
+
+    #[test]
+    fn test_int_app_ids() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "R"), edge(2, 3, "R")]);
+        assert!(g.index_int_app_id(9001, 1));
+        assert!(g.index_int_app_id(-7, 2));
+        assert!(!g.index_int_app_id(9001, 3));
+        assert_eq!(g.resolve_int_app_id(9001), Some(1));
+        assert_eq!(g.node(2).unwrap().app_id.as_deref(), Some("-7"));
+
+        // Text app IDs win, then integer app IDs, then node IDs
+        g.add_node(3, "Node".to_string(), Some("1".to_string()));
+        assert_eq!(g.resolve_node("9001"), Ok(1));
+        assert_eq!(g.resolve_node("-7"), Ok(2));
+        assert_eq!(g.resolve_node("1"), Ok(3));
+        assert_eq!(g.resolve_node("2"), Ok(2));
+        assert_eq!(g.resolve_app_id("9001"), None);
+
+        let mut bytes = Vec::new();
+        g.write_snapshot(&mut bytes).unwrap();
+        let back = Graph::read_snapshot(bytes.as_slice()).unwrap();
+        assert_eq!(back.resolve_int_app_id(-7), Some(2));
+
+        g.remove_node(1);
+        assert_eq!(g.resolve_int_app_id(9001), None);
+        assert!(g.resolve_node("9001").is_err());
+    }
+
+    #[test]
+    fn test_try_api_reports_errors() {
+        let mut g = Graph::new();
+        g.try_load_edges(vec![edge(1, 2, "R")]).unwrap();
+        for i in 1..MAX_LABELS {
+            g.try_intern_label(&format!("L{}", i)).unwrap();
+        }
+        assert_eq!(g.try_intern_label("L7"), Ok(7));
+        assert_eq!(g.try_intern_label("New"), Err(GraphError::TooManyLabels));
+
+        // The failing record adds nothing; the ones before it stay
+        let mut bad = edge(3, 4, "R");
+        bad.to_label = "New".to_string();
+        bad.from_app_id = Some("three".to_string());
+        let err = g.try_load_edges(vec![edge(2, 3, "R"), bad, edge(5, 6, "R")]);
+        assert_eq!(err, Err(GraphError::TooManyLabels));
+        assert_eq!((g.node_count(), g.edge_count()), (3, 2));
+        assert_eq!(g.resolve_app_id("three"), None);
+
+        let props = [("k".to_string(), PropValue::Int(1))];
+        assert_eq!(
+            g.try_set_node_properties(99, props.clone()),
+            Err(GraphError::UnknownNode(99))
+        );
+        assert_eq!(g.try_add_node_labels(1, ["New"]), Err(GraphError::TooManyLabels));
+        assert!(g.try_set_node_properties(1, props).is_ok());
+
+        assert_eq!(g.resolve_node("2"), Ok(2));
+        let missing = g.resolve_node("99").unwrap_err();
+        assert_eq!(missing.to_string(), "node '99' not found");
+        assert!(g.try_freeze().is_ok());
+        assert!(g.is_frozen());
+    }
+}
@@ -0,0 +1,210 @@
+//! GraphML export, for loading a graph into Gephi, yEd or NetworkX.
+//!
+//! The document is produced one line at a time, so a large graph can be
+//! streamed out without building the whole text in memory. Nodes carry
+//! their primary label and app ID, edges their rel type and, when loaded,
+//! their confidence. Node IDs are written as `n<id>`.
+
+use std::collections::HashSet;
+use std::ops::Deref;
+
+use crate::frozen::FrozenGraph;
+use crate::graph::{Graph, NodeId};
+
+const HEADER: [&str; 7] = [
+    r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+    r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#,
+    r#"  <key id="label" for="node" attr.name="label" attr.type="string"/>"#,
+    r#"  <key id="app_id" for="node" attr.name="app_id" attr.type="string"/>"#,
+    r#"  <key id="rel_type" for="edge" attr.name="rel_type" attr.type="string"/>"#,
+    r#"  <key id="confidence" for="edge" attr.name="confidence" attr.type="double"/>"#,
+    r#"  <graph id="G" edgedefault="directed">"#,
+];
+
+const FOOTER: [&str; 2] = ["  </graph>", "</graphml>"];
+
+/// GraphML for `graph`, or for the subgraph induced by `nodes`, as lines
+/// without terminators.
+///
+/// `nodes` IDs not in the graph are skipped. Edges are written in their
+/// stored direction when both ends are exported, and edges with a loaded
+/// confidence below `min_confidence` are left out. Nodes come in ID order,
+/// each followed later by its outgoing edges in the same order, so the
+/// output is deterministic. The stream owns `graph`, like
+/// `NeighborhoodStream`.
+pub fn graphml_lines<G>(
+    graph: G,
+    nodes: Option<Vec<NodeId>>,
+    min_confidence: Option<f32>,
+) -> GraphmlLines<G>
+where
+    G: Deref<Target = FrozenGraph>,
+{
+    let g: &Graph = &graph;
+    let mut nodes = match nodes {
+        Some(ids) => ids.into_iter().filter(|&id| g.node(id).is_some()).collect(),
+        None => g.nodes_iter().map(|(&id, _)| id).collect::<Vec<_>>(),
+    };
+    nodes.sort_unstable();
+    nodes.dedup();
+    GraphmlLines {
+        members: nodes.iter().copied().collect(),
+        graph,
+        nodes,
+        min_confidence,
+        section: Section::Header(0),
+    }
+}
+
+/// Lines of a `graphml_lines` document, built on demand.
+pub struct GraphmlLines<G> {
+    graph: G,
+    /// Exported nodes, ascending
+    nodes: Vec<NodeId>,
+    members: HashSet<NodeId>,
+    min_confidence: Option<f32>,
+    section: Section,
+}
+
+/// Position in the document: the index of the next header or footer line,
+/// node, or (node, outgoing edge) pair.
+#[derive(Clone, Copy)]
+enum Section {
+    Header(usize),
+    Nodes(usize),
+    Edges(usize, usize),
+    Footer(usize),
+}
+
+impl<G: Deref<Target = FrozenGraph>> Iterator for GraphmlLines<G> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let graph: &Graph = &self.graph;
+        loop {
+            match self.section {
+                Section::Header(i) => {
+                    self.section = match i + 1 {
+                        n if n < HEADER.len() => Section::Header(n),
+                        _ => Section::Nodes(0),
+                    };
+                    return Some(HEADER[i].to_string());
+                }
+                Section::Nodes(i) => {
+                    let Some(&id) = self.nodes.get(i) else {
+                        self.section = Section::Edges(0, 0);
+                        continue;
+                    };
+                    self.section = Section::Nodes(i + 1);
+                    let mut line = format!(r#"    <node id="n{}">"#, id);
+                    data(&mut line, "label", graph.node_label(id).unwrap_or_default());
+                    if let Some(app_id) = graph.node(id).and_then(|n| n.app_id.as_deref()) {
+                        data(&mut line, "app_id", app_id);
+                    }
+                    line.push_str("</node>");
+                    return Some(line);
+                }
+                Section::Edges(i, j) => {
+                    let Some(&from) = self.nodes.get(i) else {
+                        self.section = Section::Footer(0);
+                        continue;
+                    };
+                    let Some(edge) = graph.neighbors_out(from).get(j) else {
+                        self.section = Section::Edges(i + 1, 0);
+                        continue;
+                    };
+                    self.section = Section::Edges(i, j + 1);
+                    let below = self
+                        .min_confidence
+                        .is_some_and(|min| edge.has_confidence() && edge.confidence < min);
+                    if below || !self.members.contains(&edge.target) {
+                        continue;
+                    }
+                    let mut line =
+                        format!(r#"    <edge source="n{}" target="n{}">"#, from, edge.target);
+                    let rel_type = graph.rel_type_name(edge.rel_type).unwrap_or_default();
+                    data(&mut line, "rel_type", rel_type);
+                    if edge.has_confidence() {
+                        data(&mut line, "confidence", &edge.confidence.to_string());
+                    }
+                    line.push_str("</edge>");
+                    return Some(line);
+                }
+                Section::Footer(i) => {
+                    let line = FOOTER.get(i)?;
+                    self.section = Section::Footer(i + 1);
+                    return Some(line.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Append `<data key="key">value</data>`, escaping `value`.
+fn data(line: &mut String, key: &str, value: &str) {
+    line.push_str(r#"<data key=""#);
+    line.push_str(key);
+    line.push_str(r#"">"#);
+    for c in value.chars() {
+        match c {
+            '&' => line.push_str("&amp;"),
+            '<' => line.push_str("&lt;"),
+            '>' => line.push_str("&gt;"),
+            '"' => line.push_str("&quot;"),
+            '\'' => line.push_str("&apos;"),
+            _ => line.push(c),
+        }
+    }
+    line.push_str("</data>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_graphml_whole_graph_and_subset() {
+        let mut g = Graph::new();
+        let rt = g.intern_rel_type("CITES");
+        g.add_node(1, "Doc".into(), Some("a&b".into()));
+        g.add_node(2, "Doc".into(), None);
+        g.add_node(3, "Concept".into(), Some("c".into()));
+        g.add_edge(1, 2, rt, 0.5);
+        g.add_edge(2, 3, rt, f32::NAN);
+        g.add_edge(3, 1, rt, 0.1);
+        let g = Arc::new(g.into_frozen());
+
+        let lines: Vec<String> = graphml_lines(g.clone(), None, Some(0.2)).collect();
+        assert_eq!(lines.len(), HEADER.len() + 3 + 2 + FOOTER.len());
+        assert_eq!(lines[0], HEADER[0]);
+        assert_eq!(
+            lines[HEADER.len()],
+            concat!(
+                r#"    <node id="n1"><data key="label">Doc</data>"#,
+                r#"<data key="app_id">a&amp;b</data></node>"#
+            )
+        );
+        assert_eq!(
+            lines[HEADER.len() + 1],
+            r#"    <node id="n2"><data key="label">Doc</data></node>"#
+        );
+        assert_eq!(
+            lines[HEADER.len() + 3],
+            concat!(
+                r#"    <edge source="n1" target="n2"><data key="rel_type">CITES</data>"#,
+                r#"<data key="confidence">0.5</data></edge>"#
+            )
+        );
+        assert_eq!(
+            lines[HEADER.len() + 4],
+            r#"    <edge source="n2" target="n3"><data key="rel_type">CITES</data></edge>"#
+        );
+        assert_eq!(lines.last().unwrap(), "</graphml>");
+
+        // Induced by {3, 1}: one edge, 3 → 1; unknown IDs are skipped
+        let lines: Vec<String> = graphml_lines(g, Some(vec![3, 1, 999]), None).collect();
+        assert_eq!(lines.len(), HEADER.len() + 2 + 1 + FOOTER.len());
+        assert!(lines[HEADER.len() + 2].starts_with(r#"    <edge source="n3" target="n1">"#));
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::{Direction, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::interrupt;
+use crate::reach::tarjan_scc;
+use crate::traversal::PathStep;
+
+/// A lowest common ancestor of two nodes in a hierarchy.
+#[derive(Debug, Clone)]
+pub struct AncestorResult {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Hops from the first node up to this ancestor.
+    pub distance_a: u32,
+    /// Hops from the second node up to this ancestor.
+    pub distance_b: u32,
+}
+
+/// Ancestors of `node` (including itself at distance 0), following only
+/// `rel_type` edges in the `up` direction. Distances are shortest hop counts.
+pub(crate) fn ancestors(
+    graph: &Graph,
+    node: NodeId,
+    rel_type: RelTypeId,
+    up: TraversalDirection,
+) -> HashMap<NodeId, u32> {
+    let mut dist: HashMap<NodeId, u32> = HashMap::new();
+    let mut queue = VecDeque::new();
+    dist.insert(node, 0);
+    queue.push_back(node);
+
+    while let Some(current) = queue.pop_front() {
+        interrupt::check();
+        let d = dist[&current];
+        for p in parents(graph, current, rel_type, up) {
+            dist.entry(p).or_insert_with(|| {
+                queue.push_back(p);
+                d + 1
+            });
+        }
+    }
+    dist
+}
+
+/// Immediate parents of `node` along `rel_type` edges in the `up` direction.
+fn parents(
+    graph: &Graph,
+    node: NodeId,
+    rel_type: RelTypeId,
+    up: TraversalDirection,
+) -> impl Iterator<Item = NodeId> + '_ {
+    graph
+        .edges_of_type(node, rel_type, up)
+        .map(|(e, _)| e.target)
+}
+
+/// Lowest common ancestors of `a` and `b` in the hierarchy formed by `rel_type`.
+///
+/// `up` is the direction that leads from a child to its parent: for
+/// `(parent)-[:SUBSUMES]->(child)` that is `Incoming`. Ancestor sets are
+/// computed for both nodes and intersected; a common ancestor is *lowest* if
+/// none of its children (along `rel_type`) is also a common ancestor. In a
+/// tree there is exactly one; in a DAG there may be several. A node counts as
+/// its own ancestor, so if `b` is an ancestor of `a`, the result is `b`.
+///
+/// Sorted by combined distance `distance_a + distance_b`, then node ID.
+/// Empty if either node is missing or they share no ancestor. Cycles in the
+/// hierarchy are tolerated, but nodes on a shared cycle are never lowest.
+pub fn lowest_common_ancestors(
+    graph: &Graph,
+    a: NodeId,
+    b: NodeId,
+    rel_type: RelTypeId,
+    up: TraversalDirection,
+) -> Vec<AncestorResult> {
+    if graph.node(a).is_none() || graph.node(b).is_none() {
+        return Vec::new();
+    }
+
+    let from_a = ancestors(graph, a, rel_type, up);
+    let from_b = ancestors(graph, b, rel_type, up);
+    let common: HashSet<NodeId> = from_a
+        .keys()
+        .copied()
+        .filter(|id| from_b.contains_key(id))
+        .collect();
+
+    // Every ancestor of a common ancestor is itself common, so one step up
+    // from each common node marks every non-lowest one.
+    let dominated: HashSet<NodeId> = common
+        .iter()
+        .flat_map(|&c| parents(graph, c, rel_type, up).filter(move |&p| p != c))
+        .filter(|p| common.contains(p))
+        .collect();
+
+    let mut results: Vec<AncestorResult> = common
+        .iter()
+        .filter(|c| !dominated.contains(c))
+        .map(|&c| {
+            let info = graph.node(c);
+            AncestorResult {
+                node_id: c,
+                label: graph.node_label(c).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                distance_a: from_a[&c],
+                distance_b: from_b[&c],
+            }
+        })
+        .collect();
+
+    results.sort_by_key(|r| (r.distance_a + r.distance_b, r.node_id));
+    results
+}
+
+/// Longest directed path (critical path) over edges of the given rel types.
+///
+/// Follows outgoing edges whose rel type is in `rel_types` (all edges if
+/// None), which must form a DAG — e.g. REQUIRES chains modelling
+/// prerequisite sequences. Length is the number of edges. Runs one
+/// topological pass, O(V + E). Ties go to the path found first in ID order.
+///
+/// Returns the path from its first node to its last, with each step's
+/// `rel_type` naming the edge that reached it; empty if no edge matches.
+/// `Err(node)` if the selected edges contain a cycle; `node` is the smallest
+/// ID on one.
+pub fn critical_path(
+    graph: &Graph,
+    rel_types: Option<&[RelTypeId]>,
+) -> Result<Vec<PathStep>, NodeId> {
+    let allowed: Option<HashSet<RelTypeId>> = rel_types.map(|r| r.iter().copied().collect());
+
+    let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+    ids.sort_unstable();
+    let dense: HashMap<NodeId, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let succ: Vec<Vec<(usize, RelTypeId)>> = ids
+        .iter()
+        .map(|&id| {
+            graph
+                .neighbors_out(id)
+                .iter()
+                .filter(|e| allowed.as_ref().is_none_or(|a| a.contains(&e.rel_type)))
+                .filter_map(|e| dense.get(&e.target).map(|&t| (t, e.rel_type)))
+                .collect()
+        })
+        .collect();
+
+    // A DAG has only singleton components and no self-loops
+    let plain: Vec<Vec<usize>> = succ.iter().map(|s| s.iter().map(|&(t, _)| t).collect()).collect();
+    let comp = tarjan_scc(&plain);
+    let mut size = vec![0usize; ids.len()];
+    for &c in &comp {
+        size[c as usize] += 1;
+    }
+    let cyclic = (0..ids.len()).find(|&v| size[comp[v] as usize] > 1 || plain[v].contains(&v));
+    if let Some(v) = cyclic {
+        return Err(ids[v]);
+    }
+
+    // Components are numbered sinks first, so descending order is topological
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_by_key(|&v| std::cmp::Reverse(comp[v]));
+
+    let mut length = vec![0u32; ids.len()];
+    let mut reached_by: Vec<Option<(usize, RelTypeId)>> = vec![None; ids.len()];
+    for &v in &order {
+        for &(t, rel) in &succ[v] {
+            if length[v] + 1 > length[t] {
+                length[t] = length[v] + 1;
+                reached_by[t] = Some((v, rel));
+            }
+        }
+    }
+
+    let longest = (0..ids.len())
+        .filter(|&v| length[v] > 0)
+        .max_by_key(|&v| (length[v], std::cmp::Reverse(v)));
+    let Some(end) = longest else {
+        return Ok(Vec::new());
+    };
+
+    let mut chain: Vec<(usize, Option<RelTypeId>)> = vec![(end, None)];
+    let mut current = end;
+    while let Some((prev, rel)) = reached_by[current] {
+        chain.last_mut().expect("chain starts non-empty").1 = Some(rel);
+        chain.push((prev, None));
+        current = prev;
+    }
+    chain.reverse();
+
+    Ok(chain
+        .into_iter()
+        .map(|(v, rel)| {
+            let info = graph.node(ids[v]);
+            PathStep {
+                node_id: ids[v],
+                label: graph.node_label(ids[v]).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                rel_type: rel.and_then(|r| graph.rel_type_name(r)).map(|s| s.to_string()),
+                direction: rel.map(|_| Direction::Outgoing),
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    /// Tree:      0
+    ///          /   \
+    ///         1     2
+    ///        / \     \
+    ///       3   4     5
+    fn make_tree() -> Graph {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "SUBSUMES"),
+            edge(0, 2, "SUBSUMES"),
+            edge(1, 3, "SUBSUMES"),
+            edge(1, 4, "SUBSUMES"),
+            edge(2, 5, "SUBSUMES"),
+            edge(3, 5, "RELATED"), // other rel types are ignored
+        ]);
+        g
+    }
+
+    fn lca_ids(g: &Graph, a: NodeId, b: NodeId) -> Vec<NodeId> {
+        let rt = g.rel_type_id("SUBSUMES").unwrap();
+        lowest_common_ancestors(g, a, b, rt, TraversalDirection::Incoming)
+            .iter()
+            .map(|r| r.node_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_lca_tree() {
+        let g = make_tree();
+        assert_eq!(lca_ids(&g, 3, 4), vec![1]);
+        assert_eq!(lca_ids(&g, 3, 5), vec![0]);
+        assert_eq!(lca_ids(&g, 4, 2), vec![0]);
+
+        let rt = g.rel_type_id("SUBSUMES").unwrap();
+        let r = lowest_common_ancestors(&g, 3, 5, rt, TraversalDirection::Incoming);
+        assert_eq!((r[0].distance_a, r[0].distance_b), (2, 2));
+    }
+
+    #[test]
+    fn test_lca_ancestor_of_other() {
+        let g = make_tree();
+        assert_eq!(lca_ids(&g, 3, 1), vec![1]);
+        assert_eq!(lca_ids(&g, 4, 4), vec![4]);
+    }
+
+    #[test]
+    fn test_lca_dag_multiple() {
+        // 10 and 11 are both parents of 20 and 21 — two incomparable LCAs
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 10, "SUBSUMES"),
+            edge(1, 11, "SUBSUMES"),
+            edge(10, 20, "SUBSUMES"),
+            edge(11, 20, "SUBSUMES"),
+            edge(10, 21, "SUBSUMES"),
+            edge(11, 21, "SUBSUMES"),
+        ]);
+        assert_eq!(lca_ids(&g, 20, 21), vec![10, 11]);
+    }
+
+    #[test]
+    fn test_lca_none_and_missing() {
+        let mut g = make_tree();
+        g.load_edges(vec![edge(100, 101, "SUBSUMES")]);
+        assert!(lca_ids(&g, 3, 101).is_empty());
+        assert!(lca_ids(&g, 3, 999).is_empty());
+        // Wrong direction: descendants of 3 and 4 don't meet
+        let rt = g.rel_type_id("SUBSUMES").unwrap();
+        assert!(lowest_common_ancestors(&g, 3, 4, rt, TraversalDirection::Outgoing).is_empty());
+    }
+
+    #[test]
+    fn test_critical_path_longest_chain() {
+        // 0→1→2→3 and shortcut 0→3; 4→2 joins midway; unrelated RELATED edge
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "REQUIRES"),
+            edge(1, 2, "REQUIRES"),
+            edge(2, 3, "REQUIRES"),
+            edge(0, 3, "REQUIRES"),
+            edge(4, 2, "REQUIRES"),
+            edge(3, 9, "RELATED"),
+            edge(9, 0, "RELATED"),
+        ]);
+        let requires = g.rel_type_id("REQUIRES").unwrap();
+        let path = critical_path(&g, Some(&[requires])).unwrap();
+        let ids: Vec<NodeId> = path.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+        assert_eq!(path[0].rel_type, None);
+        assert_eq!(path[3].rel_type.as_deref(), Some("REQUIRES"));
+        assert_eq!(path[3].direction, Some(Direction::Outgoing));
+    }
+
+    #[test]
+    fn test_critical_path_rejects_cycles() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(5, 6, "R"), edge(6, 7, "R"), edge(7, 6, "R")]);
+        assert_eq!(critical_path(&g, None).unwrap_err(), 6);
+
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "R"), edge(2, 2, "R")]);
+        assert_eq!(critical_path(&g, None).unwrap_err(), 2);
+    }
+
+    #[test]
+    fn test_critical_path_without_matching_edges_is_empty() {
+        let g = make_tree();
+        assert!(critical_path(&g, Some(&[])).unwrap().is_empty());
+        // Tree depth is 2; the RELATED edge 3→5 extends it to 0→1→3→5
+        let subsumes = g.rel_type_id("SUBSUMES").unwrap();
+        assert_eq!(critical_path(&g, Some(&[subsumes])).unwrap().len(), 3);
+        assert_eq!(critical_path(&g, None).unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use crate::graph::{Graph, NodeId, TraversalDirection};
+use crate::rng::SplitMix64;
+use crate::traversal::iter_neighbors;
+
+/// Smallest and largest supported HyperLogLog precision (log2 of registers).
+pub const MIN_HLL_PRECISION: u32 = 4;
+pub const MAX_HLL_PRECISION: u32 = 16;
+
+/// Estimated number of nodes within `depth` hops of a node.
+#[derive(Debug, Clone)]
+pub struct ReachEstimate {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Estimated size of the ball, including the node itself.
+    pub estimate: f64,
+}
+
+/// Approximate k-hop reach of every node (HyperANF).
+///
+/// Each node keeps a HyperLogLog counter of `2^precision` one-byte registers
+/// for the set of nodes within t hops, seeded with itself. One round merges
+/// every node's counter with its neighbors' (register-wise max), so after
+/// `depth` rounds each counter holds the ball of radius `depth` — V BFS
+/// traversals replaced by `depth` linear passes. Rounds stop early once no
+/// register changes.
+///
+/// Neighbors follow `direction` and `min_confidence` as in BFS: with
+/// `Outgoing`, the estimate is how many nodes the node can reach. The
+/// relative standard error is about `1.04 / sqrt(2^precision)` (6.5% at the
+/// default of 8); small balls are counted almost exactly. Memory is
+/// `2 × 2^precision` bytes per node. `precision` is clamped to
+/// [`MIN_HLL_PRECISION`, `MAX_HLL_PRECISION`]. Deterministic; sorted by
+/// node ID.
+pub fn reach_estimates(
+    graph: &Graph,
+    depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    precision: u32,
+) -> Vec<ReachEstimate> {
+    let p = precision.clamp(MIN_HLL_PRECISION, MAX_HLL_PRECISION);
+    let m = 1usize << p;
+
+    let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+    ids.sort_unstable();
+    let dense: HashMap<NodeId, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let adj: Vec<Vec<usize>> = ids
+        .iter()
+        .map(|&id| {
+            let mut out: Vec<usize> = iter_neighbors(graph, id, direction, min_confidence)
+                .filter_map(|(e, _)| dense.get(&e.target).copied())
+                .collect();
+            out.sort_unstable();
+            out.dedup();
+            out
+        })
+        .collect();
+
+    // Counters are stored back to back: node v owns registers[v*m .. (v+1)*m]
+    let mut registers = vec![0u8; ids.len() * m];
+    for (v, &id) in ids.iter().enumerate() {
+        let hash = SplitMix64::new(id).next_u64();
+        let bucket = (hash >> (64 - p)) as usize;
+        // Rank of the first set bit in the remaining 64 - p bits
+        let rank = ((hash << p).leading_zeros() + 1).min(64 - p + 1) as u8;
+        registers[v * m + bucket] = rank;
+    }
+
+    let mut next = registers.clone();
+    for _ in 0..depth {
+        let mut changed = false;
+        for v in 0..ids.len() {
+            let own = &mut next[v * m..(v + 1) * m];
+            for &u in &adj[v] {
+                let theirs = &registers[u * m..(u + 1) * m];
+                for (mine, &other) in own.iter_mut().zip(theirs) {
+                    if other > *mine {
+                        *mine = other;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        registers.copy_from_slice(&next);
+        if !changed {
+            break;
+        }
+    }
+
+    ids.iter()
+        .enumerate()
+        .map(|(v, &id)| {
+            let info = graph.node(id);
+            ReachEstimate {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                estimate: hll_estimate(&registers[v * m..(v + 1) * m]),
+            }
+        })
+        .collect()
+}
+
+/// HyperLogLog cardinality estimate, with linear counting for small sets.
+fn hll_estimate(registers: &[u8]) -> f64 {
+    let m = registers.len() as f64;
+    let alpha = match registers.len() {
+        16 => 0.673,
+        32 => 0.697,
+        64 => 0.709,
+        _ => 0.7213 / (1.0 + 1.079 / m),
+    };
+    let harmonic: f64 = registers.iter().map(|&r| (-(r as f64)).exp2()).sum();
+    let raw = alpha * m * m / harmonic;
+    let zeros = registers.iter().filter(|&&r| r == 0).count();
+    if raw <= 2.5 * m && zeros > 0 {
+        m * (m / zeros as f64).ln()
+    } else {
+        raw
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn estimate_of(r: &[ReachEstimate], id: NodeId) -> f64 {
+        r.iter().find(|e| e.node_id == id).unwrap().estimate
+    }
+
+    #[test]
+    fn test_small_balls_are_near_exact() {
+        // Chain 0→1→…→19
+        let mut g = Graph::new();
+        g.load_edges((0..19).map(|i| edge(i, i + 1, "R")));
+
+        let out = reach_estimates(&g, 3, TraversalDirection::Outgoing, None, 10);
+        assert_eq!(out.len(), 20);
+        assert_eq!(out[0].node_id, 0);
+        assert!((estimate_of(&out, 0) - 4.0).abs() < 0.5);
+        // Near the end of the chain the ball is cut short
+        assert!((estimate_of(&out, 18) - 2.0).abs() < 0.5);
+        assert!((estimate_of(&out, 19) - 1.0).abs() < 0.5);
+
+        let both = reach_estimates(&g, 3, TraversalDirection::Both, None, 10);
+        assert!((estimate_of(&both, 10) - 7.0).abs() < 0.5);
+
+        let zero = reach_estimates(&g, 0, TraversalDirection::Both, None, 10);
+        assert!(zero.iter().all(|e| (e.estimate - 1.0).abs() < 0.5));
+    }
+
+    #[test]
+    fn test_large_ball_within_error_bound() {
+        // Hub 0 with 2000 spokes: hub reaches everything in one hop, spokes in two
+        let mut g = Graph::new();
+        g.load_edges((1..=2000).map(|i| edge(0, i, "R")));
+        let r = reach_estimates(&g, 2, TraversalDirection::Both, None, 10);
+        for id in [0, 1, 2000] {
+            let est = estimate_of(&r, id);
+            assert!((est - 2001.0).abs() / 2001.0 < 0.1, "node {}: {}", id, est);
+        }
+        let one_hop = reach_estimates(&g, 1, TraversalDirection::Both, None, 10);
+        assert!((estimate_of(&one_hop, 5) - 2.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_precision_is_clamped() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(0, 1, "R")]);
+        let low = reach_estimates(&g, 1, TraversalDirection::Both, None, 0);
+        let high = reach_estimates(&g, 1, TraversalDirection::Both, None, 99);
+        assert!((estimate_of(&low, 0) - 2.0).abs() < 0.5);
+        assert!((estimate_of(&high, 0) - 2.0).abs() < 0.5);
+        assert!(reach_estimates(&Graph::new(), 3, TraversalDirection::Both, None, 8).is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+//! Cooperative cancellation and time budgets for long-running traversals.
+//!
+//! Traversal loops call `check()` once per node they expand. Every
+//! `CHECK_INTERVAL` calls, it runs the hook installed on the current thread
+//! with `set_interrupt_hook()`, which aborts the traversal by panicking. The
+//! extension installs a hook that processes pending Postgres interrupts, so
+//! a cancel or `statement_timeout` raises an ERROR mid-search.
+//!
+//! A deadline set with `with_deadline()` ends searches that can return a
+//! partial result instead: BFS neighborhoods stop expanding and report
+//! `truncated`, and k-shortest-paths returns the paths found so far.
+//!
+//! Both are per thread: rayon workers (`parallel` feature) never run the
+//! hook, which is what Postgres needs, since only the backend's own thread
+//! may call into it.
+
+use std::cell::Cell;
+use std::time::Instant;
+
+/// Calls to `check()` between hook runs: a hook call per expanded node
+/// would cost more than the expansion.
+const CHECK_INTERVAL: u32 = 1024;
+
+thread_local! {
+    static HOOK: Cell<Option<fn()>> = const { Cell::new(None) };
+    static COUNTDOWN: Cell<u32> = const { Cell::new(CHECK_INTERVAL) };
+    static DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+    static EXPIRED: Cell<bool> = const { Cell::new(false) };
+    static CLOCK_COUNTDOWN: Cell<u32> = const { Cell::new(CLOCK_INTERVAL) };
+}
+
+/// Calls to `out_of_time()` between clock reads.
+const CLOCK_INTERVAL: u32 = 64;
+
+/// Install `hook` as this thread's interrupt check, returning the previous
+/// one. `None` removes it.
+pub fn set_interrupt_hook(hook: Option<fn()>) -> Option<fn()> {
+    HOOK.with(|h| h.replace(hook))
+}
+
+/// Run `f` with searches on this thread ending at `deadline` (`None` = no
+/// limit). Returns `f`'s result and whether the deadline cut any search
+/// short, in which case the result is partial.
+pub fn with_deadline<T>(deadline: Option<Instant>, f: impl FnOnce() -> T) -> (T, bool) {
+    // Restores the caller's deadline even if `f` is aborted by the hook
+    struct Restore(Option<Instant>, bool);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DEADLINE.with(|d| d.set(self.0));
+            EXPIRED.with(|e| e.set(self.1));
+        }
+    }
+    let _restore = Restore(
+        DEADLINE.with(|d| d.replace(deadline)),
+        EXPIRED.with(|e| e.replace(false)),
+    );
+    let result = f();
+    (result, EXPIRED.with(Cell::get))
+}
+
+/// The deadline `with_deadline` set on this thread, for work that is
+/// deferred past the call, such as a lazily expanded stream.
+pub(crate) fn current_deadline() -> Option<Instant> {
+    DEADLINE.with(Cell::get)
+}
+
+/// Count one unit of work, running the hook every `CHECK_INTERVAL` calls.
+#[inline]
+pub(crate) fn check() {
+    if tick(&COUNTDOWN, CHECK_INTERVAL) {
+        if let Some(hook) = HOOK.with(Cell::get) {
+            hook();
+        }
+    }
+}
+
+/// True once this thread's deadline has passed, recording that a search
+/// stopped early. Reads the clock every `CLOCK_INTERVAL` calls.
+#[inline]
+pub(crate) fn out_of_time() -> bool {
+    let Some(deadline) = DEADLINE.with(Cell::get) else {
+        return false;
+    };
+    if EXPIRED.with(Cell::get) {
+        return true;
+    }
+    let expired = tick(&CLOCK_COUNTDOWN, CLOCK_INTERVAL) && Instant::now() >= deadline;
+    if expired {
+        EXPIRED.with(|e| e.set(true));
+    }
+    expired
+}
+
+/// Decrement `counter`, returning true and resetting it to `interval` when
+/// it reaches zero.
+fn tick(counter: &'static std::thread::LocalKey<Cell<u32>>, interval: u32) -> bool {
+    counter.with(|c| {
+        let left = c.get() - 1;
+        c.set(if left == 0 { interval } else { left });
+        left == 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bfs_neighborhood, Graph, TraversalDirection};
+
+    fn cancel() {
+        panic!("canceled");
+    }
+
+    #[test]
+    fn test_hook_aborts_traversal() {
+        // A star: one expansion per leaf, well past CHECK_INTERVAL
+        let mut g = Graph::new();
+        let rt = g.intern_rel_type("LINKS");
+        for i in 0..=5_000 {
+            g.add_node(i, "N".into(), None);
+        }
+        for i in 1..=5_000 {
+            g.add_edge(0, i, rt, 1.0);
+        }
+        let search = || bfs_neighborhood(&g, 0, 2, TraversalDirection::Both, None, 0);
+        assert_eq!(search().neighbors.len(), 5_000);
+
+        set_interrupt_hook(Some(cancel));
+        let result = std::panic::catch_unwind(|| search().neighbors.len());
+        assert!(set_interrupt_hook(None).is_some());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deadline_truncates_traversal() {
+        // A chain: one new node per expansion
+        let mut g = Graph::new();
+        let rt = g.intern_rel_type("NEXT");
+        for i in 0..=2_000 {
+            g.add_node(i, "N".into(), None);
+        }
+        for i in 0..2_000 {
+            g.add_edge(i, i + 1, rt, 1.0);
+        }
+        let search = || bfs_neighborhood(&g, 0, 5_000, TraversalDirection::Outgoing, None, 0);
+
+        let (full, expired) = with_deadline(None, search);
+        assert!(!expired && !full.truncated);
+        assert_eq!(full.neighbors.len(), 2_000);
+
+        let (partial, expired) = with_deadline(Some(Instant::now()), search);
+        assert!(expired && partial.truncated);
+        assert!(partial.neighbors.len() < 2_000);
+        assert!(!out_of_time());
+    }
+}
@@ -7,14 +7,97 @@
 //! Designed as the core engine for the graph_accel PostgreSQL extension
 //! (ADR-201), but usable independently for benchmarking and testing.
 
+mod anomaly;
+mod buf;
+mod builder;
+mod centrality;
+mod clique;
+mod community;
+mod component;
+mod compressed;
+mod cost;
+mod cycle;
+mod diff;
+mod distance;
+mod error;
+mod frozen;
 mod graph;
+mod graphml;
+mod hierarchy;
+mod hyperanf;
+mod interrupt;
+#[cfg(feature = "mmap")]
+mod mapped;
+mod mem;
+mod merge;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod partition;
+mod pattern;
+mod props;
+mod reach;
+mod rng;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod similarity;
+mod snapshot;
+mod stats;
+mod steiner;
+mod temporal;
+#[cfg(test)]
+mod test_util;
 mod traversal;
+mod validate;
+mod view;
+mod walk;
 
+pub use anomaly::{detect_anomalies, Anomaly, AnomalyKind, Severity};
+pub use builder::{BuildStats, GraphBuilder};
+pub use centrality::{brokers, edge_betweenness, Broker, EdgeBetweenness};
+pub use clique::maximal_cliques;
+pub use community::{communities, CommunityAlgorithm, CommunityAssignment, CommunityResult};
+pub use component::Components;
+pub use compressed::{CompressedAdjacency, CompressedEdges};
+pub use cost::{rel_type_penalty, CostFn};
+pub use cycle::{simple_cycles, Cycle};
+pub use diff::GraphDelta;
+pub use distance::{estimate_diameter, DiameterEstimate, DistanceEstimate, LandmarkOracle};
+pub use error::GraphError;
+pub use frozen::FrozenGraph;
 pub use graph::{
-    Direction, Edge, EdgeRecord, Graph, NodeId, NodeInfo, RelTypeId, TraversalDirection,
-    MAX_REL_TYPES,
+    DeltaOutcome, DenseAdjacency, Direction, Edge, EdgeKey, EdgeRecord, Graph, LabelId,
+    LoadProgress, NeighborIter, Neighbors, NodeId, NodeInfo, RelTypeId, TraversalDirection,
+    MAX_LABELS, MAX_REL_TYPES,
 };
+pub use graphml::{graphml_lines, GraphmlLines};
+pub use hierarchy::{critical_path, lowest_common_ancestors, AncestorResult};
+pub use hyperanf::{reach_estimates, ReachEstimate, MAX_HLL_PRECISION, MIN_HLL_PRECISION};
+pub use interrupt::{set_interrupt_hook, with_deadline};
+#[cfg(feature = "mmap")]
+pub use mapped::MAPPED_VERSION;
+#[cfg(feature = "parallel")]
+pub use parallel::{bfs_distances_parallel, bfs_neighborhood_parallel};
+pub use partition::LabelSet;
+pub use pattern::{pattern_path, RelPattern};
+pub use props::{PropKeyId, PropValue, PropertyBag, MAX_PROP_KEYS};
+pub use reach::ReachabilityIndex;
+pub use similarity::{
+    adamic_adar, common_neighbors, edge_embeddedness, neighborhood_diff, simrank, CommonNeighbor,
+    EdgeEmbeddedness, LinkCandidate, NeighborhoodDiffEntry, Overlap,
+};
+pub use snapshot::SNAPSHOT_VERSION;
+pub use stats::GraphStats;
+pub use steiner::{steiner_tree, steiner_tree_weighted};
+pub use temporal::time_respecting_path;
 pub use traversal::{
-    bfs_neighborhood, degree_centrality, extract_subgraph, k_shortest_paths, shortest_path,
-    DegreeResult, NeighborResult, PathStep, SubgraphEdge, SubgraphResult, TraversalResult,
+    all_shortest_paths, bfs_distances, bfs_multi_source, bfs_neighborhood, bfs_neighborhood_stream,
+    bfs_visit, degree_centrality, ego_stats, extract_subgraph, hop_distance, induced_subgraph,
+    k_shortest_paths, k_shortest_paths_between, k_shortest_paths_weighted, local_clustering,
+    pairwise_distances, path_exists, shortest_path, shortest_path_to_any, shortest_path_tree,
+    shortest_path_weighted, ClusteringResult, DegreeResult, EgoStats, Exclusions, NeighborResult,
+    NeighborhoodStream, PathStep, PathTreeNode, SeededNeighborResult, SubgraphEdge, SubgraphResult,
+    TraversalResult, WeightedPath,
 };
+pub use validate::{Invariant, Violation};
+pub use view::GraphView;
+pub use walk::{random_spanning_tree, random_walks};
@@ -0,0 +1,312 @@
+//! Memory-mapped graphs: the frozen CSR arrays stay in a file and are paged
+//! in by the OS on demand, so a graph larger than the memory budget can
+//! still be traversed, and reopening a file already in the page cache is
+//! nearly instant.
+//!
+//! Layout, all integers little-endian, every section 8-byte aligned:
+//!
+//! ```text
+//! magic "GACMMAP\0", version u32, grouped u8, stored edge bytes u8,
+//! sorted u8, zero byte
+//! node count u64, edge count u64, metadata offset u64, metadata length u64
+//! node IDs                 (u64 per node, ascending)
+//! outgoing, incoming       (u64 offsets, node count + 1 of them;
+//!                           edges as the CSR stores them, 24 bytes in
+//!                           Edge's repr(C) layout or 16 without the
+//!                           target under `compact-ids`;
+//!                           u32 dense target index per edge, zero-padded)
+//! metadata                 (a snapshot with every degree 0, see snapshot.rs)
+//! ```
+//!
+//! Node metadata, indices and properties are read into memory; only the
+//! adjacency is mapped. Needs a 64-bit little-endian target, where the
+//! arrays are stored exactly as the CSR holds them; files are only
+//! readable by builds with the same `compact-ids` setting.
+
+#[cfg(not(all(target_endian = "little", target_pointer_width = "64")))]
+compile_error!("the mmap feature needs a 64-bit little-endian target");
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use memmap2::Mmap;
+
+use crate::buf::Buf;
+use crate::graph::{Edge, Graph, NodeId, StoredEdge};
+use crate::snapshot::{invalid, put_u32, put_u64};
+
+const MAGIC: &[u8; 8] = b"GACMMAP\0";
+
+/// Current mapped file format version. Files written by another version
+/// are rejected rather than migrated.
+pub const MAPPED_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = 48;
+const EDGE_LEN: usize = std::mem::size_of::<StoredEdge>();
+
+// [target,] rel_type + 2 padding bytes, confidence, weight, created_at
+#[cfg(not(feature = "compact-ids"))]
+const _: () = assert!(EDGE_LEN == 24);
+#[cfg(feature = "compact-ids")]
+const _: () = assert!(EDGE_LEN == 16);
+
+/// Byte offset of each array section, from the header counts.
+struct Layout {
+    ids: usize,
+    /// (offsets, edges, targets), outgoing then incoming
+    sides: [(usize, usize, usize); 2],
+    /// Where the metadata starts.
+    end: usize,
+}
+
+impl Layout {
+    /// None if the sizes overflow.
+    fn new(nodes: usize, edges: usize) -> Option<Layout> {
+        let mut at = HEADER_LEN;
+        let mut take = |bytes: Option<usize>| -> Option<usize> {
+            let start = at;
+            at = at.checked_add(bytes?.checked_next_multiple_of(8)?)?;
+            Some(start)
+        };
+        let ids = take(nodes.checked_mul(8))?;
+        let mut sides = [(0, 0, 0); 2];
+        for side in &mut sides {
+            *side = (
+                take(nodes.checked_add(1)?.checked_mul(8))?,
+                take(edges.checked_mul(EDGE_LEN))?,
+                take(edges.checked_mul(4))?,
+            );
+        }
+        Some(Layout {
+            ids,
+            sides,
+            end: at,
+        })
+    }
+}
+
+impl Graph {
+    /// Write a frozen graph as a file for `open_mapped()`.
+    ///
+    /// Output is deterministic, like `write_snapshot()`. Fails with
+    /// `InvalidInput` if the graph is not frozen. The writer is buffered
+    /// internally.
+    pub fn write_mapped<W: Write>(&self, writer: W) -> io::Result<()> {
+        let Some(dense) = self.dense() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_mapped needs a frozen graph",
+            ));
+        };
+        let nodes = dense.len();
+        let layout = Layout::new(nodes, self.edge_count()).expect("in-memory arrays fit");
+        let mut meta = Vec::new();
+        self.write_snapshot_with(&mut meta, false)?;
+
+        let mut w = BufWriter::new(writer);
+        w.write_all(MAGIC)?;
+        put_u32(&mut w, MAPPED_VERSION)?;
+        w.write_all(&[
+            self.is_grouped() as u8,
+            EDGE_LEN as u8,
+            self.is_sorted_by_target() as u8,
+            0,
+        ])?;
+        for v in [nodes, self.edge_count(), layout.end, meta.len()] {
+            put_u64(&mut w, v as u64)?;
+        }
+        for idx in 0..nodes {
+            put_u64(&mut w, dense.node_id(idx as u32))?;
+        }
+        put_side(&mut w, nodes, |idx| dense.out(idx))?;
+        put_side(&mut w, nodes, |idx| dense.inc(idx))?;
+        w.write_all(&meta)?;
+        w.flush()
+    }
+
+    /// Open a file written by `write_mapped()`. The graph comes back frozen,
+    /// its adjacency served from the mapping; `memory_usage()` leaves the
+    /// mapped pages out, since the OS can evict and re-read them.
+    ///
+    /// Opening reads the metadata and checks the node IDs and offsets,
+    /// O(V); edges are trusted as written, so a corrupt edge section gives
+    /// wrong answers or index panics rather than an error. Run `validate()`
+    /// to check it in full. Fails with `InvalidData` on a bad magic number,
+    /// another format version or inconsistent sections, and `UnexpectedEof`
+    /// on a truncated file.
+    ///
+    /// The file must not be modified or truncated while the graph (or any
+    /// clone of its adjacency) is alive. Mutating the graph first copies
+    /// the adjacency onto the heap.
+    pub fn open_mapped(path: impl AsRef<Path>) -> io::Result<Graph> {
+        let file = File::open(path)?;
+        // Safety: mapped read-only; the caller keeps the file unchanged
+        // for the mapping's lifetime, as documented above.
+        let map = Arc::new(unsafe { Mmap::map(&file)? });
+        let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated mapped file");
+
+        let header = map.get(..HEADER_LEN).ok_or_else(truncated)?;
+        if &header[..8] != MAGIC {
+            return Err(invalid("not a graph_accel mapped file".to_string()));
+        }
+        let word = |at: usize| {
+            let bytes: [u8; 8] = header[at..at + 8].try_into().expect("8 bytes");
+            u64::from_le_bytes(bytes) as usize
+        };
+        let version = u32::from_le_bytes(header[8..12].try_into().expect("4 bytes"));
+        if version != MAPPED_VERSION {
+            return Err(invalid(format!(
+                "mapped file version {} is not supported (expected {})",
+                version, MAPPED_VERSION
+            )));
+        }
+        if header[13] as usize != EDGE_LEN {
+            return Err(invalid(format!(
+                "mapped file stores {}-byte edges, this build {} (compact-ids differs)",
+                header[13], EDGE_LEN
+            )));
+        }
+        let (grouped, sorted) = (header[12] != 0, header[14] != 0);
+        let (nodes, edges, meta_at, meta_len) = (word(16), word(24), word(32), word(40));
+
+        let layout = Layout::new(nodes, edges)
+            .filter(|layout| layout.end == meta_at)
+            .ok_or_else(|| invalid("section sizes do not match the header".to_string()))?;
+        let meta_end = meta_at
+            .checked_add(meta_len)
+            .filter(|&end| end <= map.len())
+            .ok_or_else(truncated)?;
+        let mut graph = Graph::read_snapshot(&map[meta_at..meta_end])?;
+        if map.as_ptr().align_offset(8) != 0 {
+            return Err(invalid("mapping is not 8-byte aligned".to_string()));
+        }
+
+        // Safety: every section lies before `meta_end <= map.len()` and
+        // starts 8-aligned, and any bit pattern is a valid u64, usize, u32
+        // or StoredEdge (plain numeric fields).
+        let ids: Buf<NodeId> = unsafe { Buf::mapped(Arc::clone(&map), layout.ids, nodes) };
+        if *ids != *graph.sorted_ids() {
+            return Err(invalid("node IDs do not match the node table".to_string()));
+        }
+        let [outgoing, incoming] = layout.sides.map(|(offsets, edge_at, targets)| unsafe {
+            (
+                Buf::<usize>::mapped(Arc::clone(&map), offsets, nodes + 1),
+                Buf::<StoredEdge>::mapped(Arc::clone(&map), edge_at, edges),
+                Buf::<u32>::mapped(Arc::clone(&map), targets, edges),
+            )
+        });
+        for (offsets, _, _) in [&outgoing, &incoming] {
+            let ordered = offsets.windows(2).all(|pair| pair[0] <= pair[1]);
+            if offsets[0] != 0 || offsets[nodes] != edges || !ordered {
+                return Err(invalid("edge offsets out of range".to_string()));
+            }
+        }
+        graph.install_mapped(ids, outgoing, incoming, grouped, sorted);
+        Ok(graph)
+    }
+}
+
+/// One adjacency direction: running offsets, the edges, then their dense
+/// targets, each padded to 8 bytes.
+fn put_side<I>(w: &mut impl Write, nodes: usize, side: impl Fn(u32) -> I) -> io::Result<()>
+where
+    I: Iterator<Item = (u32, Edge)>,
+{
+    let mut end = 0;
+    put_u64(w, 0)?;
+    for idx in 0..nodes as u32 {
+        end += side(idx).count();
+        put_u64(w, end as u64)?;
+    }
+    for idx in 0..nodes as u32 {
+        for (_, e) in side(idx) {
+            // StoredEdge's repr(C) layout: 2 padding bytes after rel_type
+            #[cfg(not(feature = "compact-ids"))]
+            put_u64(w, e.target)?;
+            w.write_all(&e.rel_type.to_le_bytes())?;
+            w.write_all(&[0, 0])?;
+            w.write_all(&e.confidence.to_le_bytes())?;
+            w.write_all(&e.weight.to_le_bytes())?;
+            put_u32(w, e.created_at)?;
+        }
+    }
+    pad(w, end * EDGE_LEN)?;
+    for idx in 0..nodes as u32 {
+        for (target, _) in side(idx) {
+            put_u32(w, target)?;
+        }
+    }
+    pad(w, end * 4)
+}
+
+/// Zero bytes after a `len`-byte section, up to the next 8-byte boundary.
+fn pad(w: &mut impl Write, len: usize) -> io::Result<()> {
+    w.write_all(&[0; 8][..len.next_multiple_of(8) - len])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{Edge, EdgeRecord, Graph, NodeId, TraversalDirection};
+    use crate::traversal::bfs_neighborhood;
+
+    fn edge(from: NodeId, to: NodeId, rel: &str) -> EdgeRecord {
+        EdgeRecord {
+            from_app_id: Some(format!("n{}", from)),
+            to_app_id: Some(format!("n{}", to)),
+            confidence: 0.5,
+            ..crate::test_util::edge(from, to, rel)
+        }
+    }
+
+    #[test]
+    fn test_mapped_round_trip() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "B"),
+            edge(1, 3, "A"),
+            edge(2, 3, "A"),
+            edge(3, 40, "B"),
+        ]);
+        g.add_edge(40, 50, 0, Edge::NO_CONFIDENCE);
+        assert!(g.write_mapped(Vec::new()).is_err());
+        g.group_by_rel_type();
+        g.freeze();
+
+        let path = std::env::temp_dir().join(format!("graph_accel_{}.map", std::process::id()));
+        g.write_mapped(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        let mut m = Graph::open_mapped(&path).unwrap();
+        assert!(m.is_frozen() && m.is_grouped());
+        assert_eq!(m.node_count(), g.node_count());
+        assert_eq!(m.edge_count(), g.edge_count());
+        assert_eq!(m.resolve_node("n3").unwrap(), 3);
+        assert_eq!(m.validate(), g.validate());
+        assert!(m.memory_usage() < g.memory_usage());
+        let rels = |g: &Graph| {
+            g.neighbors_out(1)
+                .iter()
+                .map(|e| e.rel_type)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rels(&m), rels(&g));
+        assert!(rels(&m).is_sorted());
+        assert_eq!(m.neighbors_in(50).first().unwrap().target, 40);
+        let reached = bfs_neighborhood(&m, 1, 3, TraversalDirection::Outgoing, None, 0);
+        assert_eq!(reached.neighbors.len(), 4);
+
+        // Mutation copies the adjacency off the mapping
+        m.add_edge(50, 1, 0, Edge::NO_CONFIDENCE);
+        m.freeze();
+        assert_eq!(m.edge_count(), g.edge_count() + 1);
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[8] = 99;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(Graph::open_mapped(&path).is_err());
+        std::fs::write(&path, &bytes[..40]).unwrap();
+        assert!(Graph::open_mapped(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
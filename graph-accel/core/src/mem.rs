@@ -0,0 +1,101 @@
+//! Heap accounting for `memory_usage()`.
+//!
+//! Sizes come from the collections' actual capacities and layouts rather
+//! than per-item averages, and every allocation is rounded the way the
+//! allocator rounds it, so the total tracks what a load adds to the
+//! backend's resident set.
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+/// SSE2 group width of the std HashMap control bytes.
+const GROUP_WIDTH: usize = 16;
+
+/// Requests at or above this size are served by mmap (glibc's default
+/// threshold) and occupy whole pages.
+const MMAP_THRESHOLD: usize = 128 * 1024;
+const PAGE_SIZE: usize = 4096;
+
+/// Bytes a malloc of `bytes` occupies: glibc adds an 8-byte chunk header,
+/// rounds up to 16 and never hands out less than 32; large requests get
+/// their own pages plus a 16-byte header.
+pub(crate) fn alloc_size(bytes: usize) -> usize {
+    match bytes {
+        0 => 0,
+        b if b >= MMAP_THRESHOLD => (b + 16).next_multiple_of(PAGE_SIZE),
+        b => (b + 8).next_multiple_of(16).max(32),
+    }
+}
+
+/// Heap bytes of a buffer of `capacity` elements: a Vec's capacity, a
+/// String's capacity in bytes, or a boxed slice's length.
+pub(crate) fn array<T>(capacity: usize) -> usize {
+    alloc_size(capacity * size_of::<T>())
+}
+
+/// Heap bytes of a String's buffer.
+pub(crate) fn string(s: &String) -> usize {
+    array::<u8>(s.capacity())
+}
+
+/// Heap bytes of a name interner: the ID-ordered names, the name → ID
+/// map, and the map's own copy of every name.
+pub(crate) fn interner<V>(names: &Vec<String>, map: &HashMap<String, V>) -> usize {
+    array::<String>(names.capacity())
+        + names.iter().map(string).sum::<usize>()
+        + hash_map(map)
+        + map.keys().map(string).sum::<usize>()
+}
+
+/// Bucket count behind a HashMap capacity. The table keeps 7/8 of its
+/// buckets usable once it has 8 or more, and all but one below that.
+fn buckets(capacity: usize) -> usize {
+    match capacity {
+        0 => 0,
+        c if c < 8 => c + 1,
+        c => (c * 8 / 7).next_power_of_two(),
+    }
+}
+
+/// Heap bytes of a HashMap's table: one slot per bucket, padded to the
+/// group width, then one control byte per bucket plus a trailing group.
+/// Heap data owned by keys and values is not included.
+pub(crate) fn hash_map<K, V, S>(map: &HashMap<K, V, S>) -> usize {
+    let buckets = buckets(map.capacity());
+    if buckets == 0 {
+        return 0;
+    }
+    let slots = (buckets * size_of::<(K, V)>()).next_multiple_of(GROUP_WIDTH);
+    alloc_size(slots + buckets + GROUP_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sizes_follow_allocator_and_table_layout() {
+        assert_eq!(alloc_size(0), 0);
+        assert_eq!(alloc_size(1), 32);
+        assert_eq!(alloc_size(24), 32);
+        assert_eq!(alloc_size(25), 48);
+        assert_eq!(alloc_size(MMAP_THRESHOLD), MMAP_THRESHOLD + PAGE_SIZE);
+
+        // Capacity reported by the map maps back to a power-of-two table
+        // that could not hold that many entries with one bucket fewer
+        for n in [1, 3, 7, 8, 14, 15, 100, 1000, 4097] {
+            let map: HashMap<u64, u64> = HashMap::with_capacity(n);
+            let b = buckets(map.capacity());
+            assert!(b.is_power_of_two(), "n={} buckets={}", n, b);
+            assert!(b > map.capacity() && map.capacity() >= n);
+        }
+        assert_eq!(hash_map(&HashMap::<u64, u64>::new()), 0);
+
+        // 112 usable of 128 buckets × 16 bytes, + 128 control + 16 trailing
+        let map: HashMap<u64, u64> = HashMap::with_capacity(100);
+        assert_eq!(hash_map(&map), alloc_size(128 * 16 + 128 + 16));
+
+        assert_eq!(array::<u8>(100), 112);
+        assert_eq!(array::<u64>(0), 0);
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashSet;
+
+use crate::graph::{Graph, LabelId, NodeId, NodeInfo, RelTypeId};
+use crate::props::{PropKeyId, PropertyBag};
+
+impl Graph {
+    /// Union `other` into this graph.
+    ///
+    /// Rel types, labels and property keys are re-interned by name, so the
+    /// two graphs may number them differently. Nodes are matched by ID;
+    /// on a shared node this graph's label and app ID win, the other's
+    /// labels are added as extra labels and its properties fill in keys not
+    /// already set. Edges are matched on (from, to, rel type): the other
+    /// graph's edges are added only for keys this graph has no edge for,
+    /// so merging a graph with itself changes nothing. Existing app ID and
+    /// secondary index entries win over the other graph's. A frozen graph is
+    /// re-frozen.
+    pub fn merge(&mut self, other: Graph) {
+        let was_frozen = self.is_frozen();
+        let rel_map: Vec<RelTypeId> = other
+            .rel_types
+            .iter()
+            .map(|name| self.intern_rel_type(name))
+            .collect();
+        let label_map: Vec<LabelId> = other
+            .labels
+            .iter()
+            .map(|name| self.intern_label(name))
+            .collect();
+        let key_map: Vec<PropKeyId> = other
+            .prop_keys
+            .iter()
+            .map(|name| self.intern_prop_key(name))
+            .collect();
+        let remap_bag = |bag: &PropertyBag| {
+            let mut out = PropertyBag::default();
+            for (key, value) in bag.iter() {
+                out.insert(key_map[key as usize], value.clone());
+            }
+            out
+        };
+
+        let existing: HashSet<(NodeId, NodeId, RelTypeId)> = self
+            .sorted_ids()
+            .into_iter()
+            .flat_map(|id| {
+                self.neighbors_out(id)
+                    .iter()
+                    .map(move |e| (id, e.target, e.rel_type))
+            })
+            .collect();
+        for id in other.sorted_ids() {
+            for e in other.neighbors_out(id) {
+                let rel_type = rel_map[e.rel_type as usize];
+                if !existing.contains(&(id, e.target, rel_type)) {
+                    self.add_timestamped_edge(
+                        id,
+                        e.target,
+                        rel_type,
+                        e.confidence,
+                        e.weight,
+                        e.created_at,
+                    );
+                }
+            }
+        }
+
+        for (id, info) in other.nodes {
+            let mut extra: Vec<LabelId> = info
+                .extra_labels
+                .iter()
+                .map(|&l| label_map[l as usize])
+                .collect();
+            let label = label_map[info.label as usize];
+            let props = info.properties.as_deref().map(remap_bag);
+            let Some(node) = self.nodes.get_mut(&id) else {
+                extra.sort_unstable();
+                extra.dedup();
+                let info = NodeInfo {
+                    label,
+                    extra_labels: extra.into_boxed_slice(),
+                    app_id: info.app_id,
+                    properties: props.map(Box::new),
+                };
+                let labels = info.labels();
+                self.nodes.insert(id, info);
+                self.partition_add(id, &labels);
+                continue;
+            };
+            if let Some(props) = props {
+                let bag = node.properties.get_or_insert_default();
+                for (key, value) in props.iter() {
+                    if bag.get(key).is_none() {
+                        bag.insert(key, value.clone());
+                    }
+                }
+            }
+            extra.push(label);
+            extra.sort_unstable();
+            extra.dedup();
+            extra.retain(|&l| l != node.label && node.extra_labels.binary_search(&l).is_err());
+            if extra.is_empty() {
+                continue;
+            }
+            let mut merged = node.extra_labels.to_vec();
+            merged.extend_from_slice(&extra);
+            merged.sort_unstable();
+            node.extra_labels = merged.into_boxed_slice();
+            self.partition_add(id, &extra);
+        }
+
+        for ((from, to, rel_type), bag) in other.edge_props {
+            let bag = remap_bag(&bag);
+            match self
+                .edge_props
+                .entry((from, to, rel_map[rel_type as usize]))
+            {
+                Entry::Vacant(slot) => {
+                    slot.insert(bag);
+                }
+                Entry::Occupied(mut slot) => {
+                    let merged = slot.get_mut();
+                    for (key, value) in bag.iter() {
+                        if merged.get(key).is_none() {
+                            merged.insert(key, value.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        for (app_id, id) in other.app_id_index {
+            self.app_id_index.entry(app_id).or_insert(id);
+        }
+        for (app_id, id) in other.int_app_id_index {
+            self.int_app_id_index.entry(app_id).or_insert(id);
+        }
+        for (name, entries) in other.secondary_indices {
+            let index = self.secondary_indices.entry(name).or_default();
+            for (value, id) in entries {
+                index.entry(value).or_insert(id);
+            }
+        }
+
+        if was_frozen {
+            self.freeze();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{Direction, EdgeRecord, Graph, NodeId, TraversalDirection};
+    use crate::props::PropValue;
+    use crate::traversal::shortest_path;
+
+    fn edge(from: NodeId, to: NodeId, rel: &str, label: &str) -> EdgeRecord {
+        EdgeRecord {
+            from_label: label.to_string(),
+            to_label: label.to_string(),
+            from_app_id: Some(format!("{}-{}", label, from)),
+            to_app_id: Some(format!("{}-{}", label, to)),
+            ..EdgeRecord::new(from, to, rel)
+        }
+    }
+
+    #[test]
+    fn test_merge_unions_and_reconciles_interning() {
+        let mut a = Graph::new();
+        a.load_edges(vec![
+            edge(1, 2, "IMPLIES", "Concept"),
+            edge(2, 3, "PART_OF", "Concept"),
+        ]);
+        a.set_node_properties(2, [("status".to_string(), PropValue::Int(1))]);
+        a.freeze();
+
+        // Opposite interning order; node 3 is shared, 2→3 PART_OF duplicated
+        let mut rec = edge(3, 4, "IMPLIES", "Term");
+        rec.properties = vec![("source".to_string(), PropValue::Text("b".to_string()))];
+        let mut b = Graph::new();
+        b.load_edges(vec![edge(2, 3, "PART_OF", "Term"), rec]);
+        b.set_node_properties(
+            2,
+            [
+                ("status".to_string(), PropValue::Int(9)),
+                ("rank".to_string(), PropValue::Int(5)),
+            ],
+        );
+
+        a.merge(b);
+        assert!(a.is_frozen());
+        assert_eq!((a.node_count(), a.edge_count()), (4, 3));
+        assert_eq!(a.rel_type_count(), 2);
+
+        // Shared node: own label and app ID win, the other's become extras
+        assert_eq!(a.node_label(3), Some("Concept"));
+        assert_eq!(a.node_labels(3), vec!["Concept", "Term"]);
+        assert_eq!(a.resolve_app_id("Concept-3"), Some(3));
+        assert_eq!(a.resolve_app_id("Term-4"), Some(4));
+        assert_eq!(
+            a.node_property(2, "status").and_then(PropValue::as_i64),
+            Some(1)
+        );
+        assert_eq!(
+            a.node_property(2, "rank").and_then(PropValue::as_i64),
+            Some(5)
+        );
+
+        let e = a.neighbors_out(3).first().unwrap();
+        assert_eq!(a.rel_type_name(e.rel_type), Some("IMPLIES"));
+        let source = a.edge_property(3, &e, Direction::Outgoing, "source");
+        assert_eq!(source.and_then(PropValue::as_str), Some("b"));
+
+        // Traversal crosses from one source graph into the other
+        let path = shortest_path(&a, 1, 4, 10, TraversalDirection::Outgoing, None).unwrap();
+        assert_eq!(path.len(), 4);
+
+        // Merging the same edges again is a no-op
+        let mut again = Graph::new();
+        again.load_edges(vec![edge(1, 2, "IMPLIES", "Concept")]);
+        a.merge(again);
+        assert_eq!(a.edge_count(), 3);
+    }
+}
@@ -0,0 +1,466 @@
+//! Multi-threaded bulk load and BFS (feature `parallel`).
+//!
+//! The extension loads through SPI on a single-threaded backend and never
+//! enables this; it is for standalone users building and searching large
+//! graphs.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use rayon::prelude::*;
+
+use crate::graph::{
+    sanitize_weight, DenseAdjacency, Edge, EdgeLists, EdgeRecord, Graph, NodeId, NodeInfo,
+    TraversalDirection,
+};
+use crate::traversal::{
+    bfs_distances, bfs_neighborhood, dense_neighbors, iter_dense_neighbors, DenseBits, DenseHit,
+    TraversalResult,
+};
+
+/// Frontier nodes per parallel task. Smaller levels run on one thread,
+/// since splitting them costs more than it saves.
+const MIN_FRONTIER_CHUNK: usize = 256;
+
+/// One shard's slice of the graph: every structure keyed by a node ID (or
+/// app ID) that hashes to the shard. Shards never share a key.
+#[derive(Default)]
+struct Shard {
+    nodes: HashMap<NodeId, NodeInfo>,
+    outgoing: EdgeLists,
+    incoming: EdgeLists,
+    app_ids: HashMap<String, NodeId>,
+}
+
+impl Graph {
+    /// `load_edges` across all rayon threads.
+    ///
+    /// Rel types and labels are interned first, in parallel chunks merged in
+    /// record order. Edges are then sharded by node ID: each thread scans the
+    /// records and builds the adjacency lists, node metadata and app IDs for
+    /// the IDs it owns, and the shards are moved into the graph. Each shard
+    /// sees the records in order, so the result is identical to
+    /// `load_edges`: same interned IDs, same first-occurrence-wins metadata,
+    /// same edge order per node. Edge properties are applied afterwards on
+    /// the calling thread.
+    pub fn load_edges_parallel(&mut self, edges: Vec<EdgeRecord>) {
+        if edges.is_empty() {
+            return;
+        }
+        let threads = rayon::current_num_threads().max(1);
+        let chunk = edges.len().div_ceil(threads);
+
+        for name in first_seen(&edges, chunk, |r| [r.rel_type.as_str()]) {
+            self.intern_rel_type(name);
+        }
+        for name in first_seen(&edges, chunk, |r| {
+            [r.from_label.as_str(), r.to_label.as_str()]
+        }) {
+            self.intern_label(name);
+        }
+
+        let graph = &*self;
+        let shards: Vec<Shard> = (0..threads)
+            .into_par_iter()
+            .map(|shard| graph.build_shard(&edges, shard, threads))
+            .collect();
+
+        let (nodes, app_ids) = shards
+            .iter()
+            .fold((0, 0), |(n, a), s| (n + s.nodes.len(), a + s.app_ids.len()));
+        self.nodes.reserve(nodes);
+        self.app_id_index.reserve(app_ids);
+        for shard in shards {
+            // Existing entries came first, so they win as in load_edges
+            for (id, info) in shard.nodes {
+                if let Entry::Vacant(slot) = self.nodes.entry(id) {
+                    let label = info.label;
+                    slot.insert(info);
+                    self.partition_add(id, &[label]);
+                }
+            }
+            for (aid, id) in shard.app_ids {
+                self.app_id_index.entry(aid).or_insert(id);
+            }
+            let (outgoing, incoming) = self.lists_mut();
+            append_lists(outgoing, shard.outgoing);
+            append_lists(incoming, shard.incoming);
+        }
+
+        for rec in edges {
+            if !rec.properties.is_empty() {
+                let rt = self.rel_type_id(&rec.rel_type).expect("interned above");
+                self.set_edge_properties(rec.from_id, rec.to_id, rt, rec.properties);
+            }
+        }
+    }
+
+    fn build_shard(&self, edges: &[EdgeRecord], shard: usize, shards: usize) -> Shard {
+        let owns = |id: NodeId| shard_of(id, shards) == shard;
+        let owns_app_id = |aid: &str| shard_of(fnv1a(aid.as_bytes()), shards) == shard;
+        let avg = self.estimated_avg_degree;
+        let mut out = Shard::default();
+
+        for rec in edges {
+            for (aid, id) in [(&rec.from_app_id, rec.from_id), (&rec.to_app_id, rec.to_id)] {
+                if let Some(aid) = aid.as_deref().filter(|aid| owns_app_id(aid)) {
+                    out.app_ids.entry(aid.to_string()).or_insert(id);
+                }
+            }
+
+            let (from_owned, to_owned) = (owns(rec.from_id), owns(rec.to_id));
+            if !from_owned && !to_owned {
+                continue;
+            }
+            let rel_type = self.rel_type_id(&rec.rel_type).expect("interned above");
+            let edge = |target| Edge {
+                target,
+                rel_type,
+                confidence: rec.confidence,
+                weight: sanitize_weight(rec.weight),
+                created_at: rec.created_at,
+            };
+            let sides = [
+                (from_owned, rec.from_id, &rec.from_label, &rec.from_app_id),
+                (to_owned, rec.to_id, &rec.to_label, &rec.to_app_id),
+            ];
+            for (owned, id, label, app_id) in sides {
+                if owned {
+                    out.nodes.entry(id).or_insert_with(|| NodeInfo {
+                        label: self.label_id(label).expect("interned above"),
+                        extra_labels: Box::default(),
+                        app_id: app_id.clone(),
+                        properties: None,
+                    });
+                }
+            }
+            if from_owned {
+                out.outgoing
+                    .entry(rec.from_id)
+                    .or_insert_with(|| Vec::with_capacity(avg))
+                    .push(edge(rec.to_id));
+            }
+            if to_owned {
+                out.incoming
+                    .entry(rec.to_id)
+                    .or_insert_with(|| Vec::with_capacity(avg))
+                    .push(edge(rec.from_id));
+            }
+        }
+        out
+    }
+}
+
+/// `bfs_neighborhood` with each BFS level expanded across all rayon threads.
+///
+/// The search is level-synchronous: the frontier is split into chunks
+/// whose unvisited neighbors are gathered in parallel, then merged in
+/// frontier order, skipping nodes an earlier chunk already claimed. That
+/// is the order the sequential BFS discovers them in, so neighbors,
+/// distances, paths, `max_nodes` truncation and `nodes_visited` are all
+/// identical; neighbors come in BFS order. Runs on the frozen graph's dense
+/// index; an unfrozen graph gets the sequential `bfs_neighborhood`.
+pub fn bfs_neighborhood_parallel(
+    graph: &Graph,
+    start: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    max_nodes: usize,
+) -> TraversalResult {
+    let Some((dense, start_idx)) = dense_start(graph, start) else {
+        return bfs_neighborhood(
+            graph,
+            start,
+            max_depth,
+            direction,
+            min_confidence,
+            max_nodes,
+        );
+    };
+    let budget = if max_nodes == 0 {
+        usize::MAX
+    } else {
+        max_nodes
+    };
+    let (hits, truncated) = par_dense_bfs(
+        dense,
+        start_idx,
+        max_depth,
+        direction,
+        min_confidence,
+        budget,
+    );
+
+    let chunk = hits
+        .len()
+        .div_ceil(rayon::current_num_threads().max(1))
+        .max(1);
+    let neighbors: Vec<_> = (0..hits.len().div_ceil(chunk))
+        .into_par_iter()
+        .map(|c| {
+            let positions = (c * chunk).max(1)..((c + 1) * chunk).min(hits.len());
+            dense_neighbors(graph, dense, &hits, positions)
+        })
+        .collect();
+    TraversalResult {
+        neighbors: neighbors.concat(),
+        nodes_visited: hits.len(),
+        truncated,
+    }
+}
+
+/// `bfs_distances` across all rayon threads, as in
+/// `bfs_neighborhood_parallel`. Same output, in the same order.
+pub fn bfs_distances_parallel(
+    graph: &Graph,
+    start: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<(NodeId, u32)> {
+    let Some((dense, start_idx)) = dense_start(graph, start) else {
+        return bfs_distances(graph, start, max_depth, direction, min_confidence);
+    };
+    let (hits, _) = par_dense_bfs(
+        dense,
+        start_idx,
+        max_depth,
+        direction,
+        min_confidence,
+        usize::MAX,
+    );
+    hits.into_iter()
+        .map(|h| (dense.node_id(h.node), h.depth))
+        .collect()
+}
+
+/// The dense view and `start`'s index in it, if the graph is frozen and
+/// has the node.
+fn dense_start(graph: &Graph, start: NodeId) -> Option<(DenseAdjacency<'_>, u32)> {
+    graph.node(start)?;
+    let dense = graph.dense()?;
+    Some((dense, dense.index_of(start)?))
+}
+
+/// Level-synchronous BFS producing the same hits, in the same order, as
+/// the sequential dense search, plus whether the `budget` stopped it.
+fn par_dense_bfs(
+    dense: DenseAdjacency<'_>,
+    start: u32,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    budget: usize,
+) -> (Vec<DenseHit>, bool) {
+    let threads = rayon::current_num_threads().max(1);
+    let mut seen = DenseBits::new(dense.len());
+    seen.insert(start);
+    let mut hits = vec![DenseHit::start(start)];
+    let mut level = 0..1;
+
+    while !level.is_empty() && hits[level.start].depth < max_depth {
+        let chunk = level.len().div_ceil(threads).max(MIN_FRONTIER_CHUNK);
+        let (seen_now, hits_now, level_now) = (&seen, &hits, &level);
+        // Unvisited neighbors per chunk, in frontier and edge order
+        let found: Vec<Vec<DenseHit>> = (0..level.len().div_ceil(chunk))
+            .into_par_iter()
+            .map(|c| {
+                let from = level_now.start + c * chunk;
+                let end = (from + chunk).min(level_now.end);
+                let mut out = Vec::new();
+                for (pos, at) in (from..end).zip(&hits_now[from..end]) {
+                    for (next, edge, dir) in
+                        iter_dense_neighbors(dense, at.node, direction, min_confidence)
+                    {
+                        if !seen_now.contains(next) {
+                            out.push(DenseHit {
+                                node: next,
+                                depth: at.depth + 1,
+                                parent: pos as u32,
+                                rel_type: edge.rel_type,
+                                dir,
+                            });
+                        }
+                    }
+                }
+                out
+            })
+            .collect();
+
+        let next = hits.len();
+        for hit in found.into_iter().flatten() {
+            if seen.insert(hit.node) {
+                if hits.len() > budget {
+                    return (hits, true);
+                }
+                hits.push(hit);
+            }
+        }
+        level = next..hits.len();
+    }
+    (hits, false)
+}
+
+/// Distinct names in order of first occurrence. Each chunk dedups its own
+/// records in parallel; concatenating the chunks in order and interning
+/// skips repeats, which yields the same order as one sequential pass.
+fn first_seen<const N: usize>(
+    edges: &[EdgeRecord],
+    chunk: usize,
+    pick: fn(&EdgeRecord) -> [&str; N],
+) -> Vec<&str> {
+    let per_chunk: Vec<Vec<&str>> = edges
+        .par_chunks(chunk)
+        .map(|records| {
+            let mut seen = HashSet::new();
+            records
+                .iter()
+                .flat_map(pick)
+                .filter(|name| seen.insert(*name))
+                .collect()
+        })
+        .collect();
+    per_chunk.concat()
+}
+
+/// Append each shard list to the graph's list for the same node.
+fn append_lists(into: &mut EdgeLists, from: EdgeLists) {
+    into.reserve(from.len());
+    for (id, mut list) in from {
+        match into.entry(id) {
+            Entry::Vacant(slot) => {
+                slot.insert(list);
+            }
+            Entry::Occupied(mut slot) => slot.get_mut().append(&mut list),
+        }
+    }
+}
+
+/// Graph IDs are often sequential, so mix before taking the remainder.
+fn shard_of(id: u64, shards: usize) -> usize {
+    (id.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % shards
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Edge, EdgeRecord, Graph, Neighbors};
+    use crate::props::PropValue;
+
+    fn edge(from: NodeId, to: NodeId, rel: usize) -> EdgeRecord {
+        EdgeRecord {
+            from_label: format!("L{}", from % 3),
+            to_label: format!("L{}", to % 5),
+            from_app_id: Some(format!("n{}", from)),
+            // Clashing app IDs: the first record to use one wins
+            to_app_id: Some(format!("n{}", to % 50)),
+            weight: if rel == 2 { -1.0 } else { rel as f32 },
+            properties: if from % 17 == 3 {
+                vec![("k".to_string(), PropValue::Int(to as i64))]
+            } else {
+                Vec::new()
+            },
+            ..EdgeRecord::new(from, to, format!("R{}", rel))
+        }
+    }
+
+    #[test]
+    fn test_parallel_load_matches_sequential() {
+        let records: Vec<EdgeRecord> = (0..2000)
+            .map(|i| edge((i * 7919) % 300, (i * 104_729) % 400, (i % 4) as usize))
+            .collect();
+
+        let mut seq = Graph::new();
+        seq.load_edges(records[..1500].to_vec());
+        seq.freeze();
+        seq.load_edges(records[1500..].to_vec());
+        let mut par = Graph::new();
+        par.load_edges_parallel(records[..1500].to_vec());
+        par.freeze();
+        par.load_edges_parallel(records[1500..].to_vec());
+
+        assert!(seq.diff(&par).is_empty());
+        assert_eq!(par.edge_count(), 2000);
+        assert_eq!(par.rel_types, seq.rel_types);
+        assert_eq!(par.labels, seq.labels);
+        for id in seq.sorted_ids() {
+            let key = |e: &Edge| (e.target, e.rel_type, e.weight.to_bits());
+            let keys = |edges: Neighbors| edges.iter().map(|e| key(&e)).collect::<Vec<_>>();
+            assert_eq!(keys(par.neighbors_out(id)), keys(seq.neighbors_out(id)));
+            assert_eq!(keys(par.neighbors_in(id)), keys(seq.neighbors_in(id)));
+            let (p, s) = (par.node(id).unwrap(), seq.node(id).unwrap());
+            assert_eq!((p.label, &p.app_id), (s.label, &s.app_id));
+            if let Some(e) = seq.neighbors_out(id).first() {
+                let r = e.rel_type;
+                assert_eq!(
+                    par.edge_properties(id, e.target, r),
+                    seq.edge_properties(id, e.target, r)
+                );
+            }
+        }
+        for i in 0..400 {
+            let aid = format!("n{}", i);
+            assert_eq!(par.resolve_app_id(&aid), seq.resolve_app_id(&aid));
+        }
+    }
+
+    #[test]
+    fn test_parallel_bfs_matches_sequential() {
+        // Wide enough that the middle levels split across threads
+        let records: Vec<EdgeRecord> = (0..12_000)
+            .map(|i| {
+                edge(
+                    i % 3000,
+                    ((i * 2_654_435_761) >> 16) % 3000,
+                    (i % 3) as usize,
+                )
+            })
+            .collect();
+        let mut g = Graph::new();
+        g.load_edges(records);
+        let lists = bfs_neighborhood_parallel(&g, 0, 3, TraversalDirection::Both, None, 0);
+        g.freeze();
+
+        let rows = |r: &TraversalResult| {
+            r.neighbors
+                .iter()
+                .map(|n| {
+                    (
+                        n.node_id,
+                        n.distance,
+                        n.path_types.clone(),
+                        n.path_directions.clone(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        for dir in [TraversalDirection::Outgoing, TraversalDirection::Both] {
+            for max_nodes in [0, 1000] {
+                let seq = bfs_neighborhood(&g, 0, 6, dir, None, max_nodes);
+                let par = bfs_neighborhood_parallel(&g, 0, 6, dir, None, max_nodes);
+                assert!(seq.nodes_visited > 2 * MIN_FRONTIER_CHUNK);
+                assert_eq!(rows(&par), rows(&seq));
+                assert_eq!(
+                    (par.nodes_visited, par.truncated),
+                    (seq.nodes_visited, seq.truncated)
+                );
+            }
+            assert_eq!(
+                bfs_distances_parallel(&g, 0, 6, dir, None),
+                bfs_distances(&g, 0, 6, dir, None)
+            );
+        }
+
+        // Unfrozen graphs fall back to the sequential search
+        let frozen = bfs_neighborhood_parallel(&g, 0, 3, TraversalDirection::Both, None, 0);
+        assert_eq!(lists.nodes_visited, frozen.nodes_visited);
+        assert!(bfs_distances_parallel(&g, 99_999, 3, TraversalDirection::Both, None).is_empty());
+    }
+}
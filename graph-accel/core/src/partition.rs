@@ -0,0 +1,169 @@
+//! Per-label node partitions and resolved label sets, so label filters
+//! never compare label names per node.
+
+use crate::graph::{Graph, LabelId, NodeId};
+
+/// Labels resolved to IDs once, to test many nodes against.
+///
+/// A bitset over label IDs, so membership is one shift and mask. Built by
+/// `Graph::label_set`; only meaningful for the graph that built it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelSet {
+    bits: Vec<u64>,
+}
+
+impl LabelSet {
+    pub fn contains(&self, label: LabelId) -> bool {
+        let l = label as usize;
+        self.bits
+            .get(l / 64)
+            .is_some_and(|w| w & (1 << (l % 64)) != 0)
+    }
+
+    /// True if no label resolved, so no node can match.
+    pub fn is_empty(&self) -> bool {
+        self.bits.iter().all(|&w| w == 0)
+    }
+
+    fn insert(&mut self, label: LabelId) {
+        let l = label as usize;
+        if self.bits.len() <= l / 64 {
+            self.bits.resize(l / 64 + 1, 0);
+        }
+        self.bits[l / 64] |= 1 << (l % 64);
+    }
+}
+
+impl Graph {
+    /// Resolve label names into a `LabelSet`. Names no node carries are
+    /// skipped.
+    pub fn label_set<I, S>(&self, labels: I) -> LabelSet
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set = LabelSet::default();
+        for label in labels {
+            if let Some(id) = self.label_id(label.as_ref()) {
+                set.insert(id);
+            }
+        }
+        set
+    }
+
+    /// True if the node carries any label in `set`, as its primary or an
+    /// extra label. Suitable as a traversal node filter.
+    pub fn node_in_label_set(&self, id: NodeId, set: &LabelSet) -> bool {
+        self.nodes.get(&id).is_some_and(|info| {
+            set.contains(info.label) || info.extra_labels.iter().any(|&l| set.contains(l))
+        })
+    }
+
+    /// Every node carrying `label`, as its primary or an extra label, in
+    /// no particular order. Empty if no node has it.
+    pub fn nodes_with_label(&self, label: &str) -> &[NodeId] {
+        self.label_id(label)
+            .and_then(|l| self.label_nodes.get(l as usize))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Record that `id` now carries each of `labels`, which it must not
+    /// have carried before.
+    pub(crate) fn partition_add(&mut self, id: NodeId, labels: &[LabelId]) {
+        for &l in labels {
+            let l = l as usize;
+            if self.label_nodes.len() <= l {
+                self.label_nodes.resize_with(l + 1, Vec::new);
+            }
+            self.label_nodes[l].push(id);
+        }
+    }
+
+    /// Record that `id` no longer carries any of `labels`. O(partition size).
+    pub(crate) fn partition_remove(&mut self, id: NodeId, labels: &[LabelId]) {
+        for &l in labels {
+            if let Some(members) = self.label_nodes.get_mut(l as usize) {
+                members.retain(|&n| n != id);
+            }
+        }
+    }
+
+    /// Recompute every partition from the node table, for loaders that
+    /// fill it directly.
+    pub(crate) fn rebuild_label_partitions(&mut self) {
+        let mut partitions: Vec<Vec<NodeId>> = vec![Vec::new(); self.labels.len()];
+        for (&id, info) in &self.nodes {
+            for &l in std::iter::once(&info.label).chain(info.extra_labels.iter()) {
+                partitions[l as usize].push(id);
+            }
+        }
+        self.label_nodes = partitions;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{EdgeRecord, Graph, NodeId};
+
+    fn edge(from: NodeId, to: NodeId, from_label: &str, to_label: &str) -> EdgeRecord {
+        EdgeRecord {
+            from_label: from_label.to_string(),
+            to_label: to_label.to_string(),
+            ..EdgeRecord::new(from, to, "R")
+        }
+    }
+
+    fn sorted(ids: &[NodeId]) -> Vec<NodeId> {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids
+    }
+
+    #[test]
+    fn test_label_partitions_track_mutations() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "Concept", "Source"),
+            edge(2, 3, "Source", "Concept"),
+            edge(3, 4, "Concept", "Concept"),
+        ]);
+        assert_eq!(sorted(g.nodes_with_label("Concept")), vec![1, 3, 4]);
+        assert_eq!(g.nodes_with_label("Source"), &[2]);
+        assert!(g.nodes_with_label("Missing").is_empty());
+
+        g.add_node_labels(2, ["Person", "Source", "Person"]);
+        g.add_node_labels(3, ["Person"]);
+        assert_eq!(sorted(g.nodes_with_label("Person")), vec![2, 3]);
+        assert_eq!(g.nodes_with_label("Source"), &[2]);
+
+        let set = g.label_set(["Person", "Missing"]);
+        assert!(!set.is_empty());
+        assert!(g.node_in_label_set(2, &set));
+        assert!(!g.node_in_label_set(1, &set));
+        assert!(!g.node_in_label_set(99, &set));
+        assert!(g.label_set(["Missing"]).is_empty());
+
+        // Re-registering a node replaces all of its labels
+        g.add_node(3, "Source".to_string(), None);
+        g.remove_node(4);
+        assert_eq!(g.nodes_with_label("Concept"), &[1]);
+        assert_eq!(g.nodes_with_label("Person"), &[2]);
+        assert_eq!(sorted(g.nodes_with_label("Source")), vec![2, 3]);
+
+        let mut other = Graph::new();
+        other.load_edges(vec![edge(1, 5, "Person", "Concept")]);
+        g.merge(other);
+        assert_eq!(sorted(g.nodes_with_label("Person")), vec![1, 2]);
+        assert_eq!(sorted(g.nodes_with_label("Concept")), vec![1, 5]);
+
+        let mut bytes = Vec::new();
+        g.write_snapshot(&mut bytes).unwrap();
+        let back = Graph::read_snapshot(bytes.as_slice()).unwrap();
+        for label in ["Concept", "Source", "Person"] {
+            assert_eq!(
+                sorted(back.nodes_with_label(label)),
+                sorted(g.nodes_with_label(label))
+            );
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::{Direction, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::interrupt;
+use crate::traversal::{iter_neighbors, make_step, PathStep};
+
+/// How many times a pattern element may repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repeat {
+    One,
+    Optional,
+    Many,
+}
+
+/// One position in a relationship-type pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Element {
+    /// Accepted rel type names; None matches any type.
+    types: Option<Vec<String>>,
+    repeat: Repeat,
+}
+
+/// A regular pattern over the relationship types along a path.
+///
+/// Syntax is a sequence of elements separated by whitespace, `,`, `->` or
+/// `→`. Each element is a rel type name, `.` for any type, or a group of
+/// alternatives `(A|B)`, optionally followed by `?` (zero or one), `*`
+/// (zero or more) or `+` (one or more):
+///
+/// ```text
+/// IMPLIES -> IMPLIES -> SUPPORTS
+/// IMPLIES+ SUPPORTS
+/// (IMPLIES|ENABLES)* . SUPPORTS?
+/// ```
+///
+/// Names are matched exactly; a name the graph has never seen is valid but
+/// matches no edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelPattern {
+    elements: Vec<Element>,
+}
+
+impl RelPattern {
+    /// Parse a pattern, returning a description of the first syntax error.
+    pub fn parse(pattern: &str) -> Result<RelPattern, String> {
+        let mut elements = Vec::new();
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == ',' || c == '→' {
+                chars.next();
+                continue;
+            }
+            if c == '-' {
+                chars.next();
+                if chars.next() != Some('>') {
+                    return Err("expected '>' after '-'".to_string());
+                }
+                continue;
+            }
+
+            let types = match c {
+                '.' => {
+                    chars.next();
+                    None
+                }
+                '(' => {
+                    chars.next();
+                    let mut alts = Vec::new();
+                    loop {
+                        skip_spaces(&mut chars);
+                        let name = read_name(&mut chars);
+                        if name.is_empty() {
+                            return Err("expected a rel type name in group".to_string());
+                        }
+                        alts.push(name);
+                        skip_spaces(&mut chars);
+                        match chars.next() {
+                            Some('|') => continue,
+                            Some(')') => break,
+                            _ => return Err("unclosed group, expected '|' or ')'".to_string()),
+                        }
+                    }
+                    Some(alts)
+                }
+                _ if is_name_char(c) => Some(vec![read_name(&mut chars)]),
+                _ => return Err(format!("unexpected character '{}'", c)),
+            };
+
+            let repeat = match chars.peek() {
+                Some('?') => Repeat::Optional,
+                Some('*') => Repeat::Many,
+                Some('+') => {
+                    // A+ is A followed by A*
+                    elements.push(Element {
+                        types: types.clone(),
+                        repeat: Repeat::One,
+                    });
+                    Repeat::Many
+                }
+                _ => Repeat::One,
+            };
+            if matches!(chars.peek(), Some('?' | '*' | '+')) {
+                chars.next();
+            }
+            elements.push(Element { types, repeat });
+        }
+
+        if elements.is_empty() {
+            return Err("empty pattern".to_string());
+        }
+        Ok(RelPattern { elements })
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn skip_spaces(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn read_name(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut name = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_name_char(c) {
+            break;
+        }
+        name.push(c);
+        chars.next();
+    }
+    name
+}
+
+/// Pattern elements resolved against a graph's rel type table.
+struct Matcher {
+    /// Per element: accepted rel type IDs (None = any) and repeat mode.
+    steps: Vec<(Option<Vec<RelTypeId>>, Repeat)>,
+}
+
+impl Matcher {
+    fn new(graph: &Graph, pattern: &RelPattern) -> Self {
+        let steps = pattern
+            .elements
+            .iter()
+            .map(|el| {
+                let ids = el
+                    .types
+                    .as_ref()
+                    .map(|names| names.iter().filter_map(|n| graph.rel_type_id(n)).collect());
+                (ids, el.repeat)
+            })
+            .collect();
+        Matcher { steps }
+    }
+
+    /// Positions reachable from `pos` without consuming an edge.
+    fn closure(&self, pos: usize) -> std::ops::RangeInclusive<usize> {
+        let mut end = pos;
+        while end < self.steps.len() && self.steps[end].1 != Repeat::One {
+            end += 1;
+        }
+        pos..=end
+    }
+
+    fn accepts(&self, pos: usize) -> bool {
+        *self.closure(pos).end() == self.steps.len()
+    }
+
+    /// Position after consuming an edge of type `rel_type` at element `pos`.
+    fn step(&self, pos: usize, rel_type: RelTypeId) -> Option<usize> {
+        let (ids, repeat) = self.steps.get(pos)?;
+        if ids.as_ref().is_some_and(|ids| !ids.contains(&rel_type)) {
+            return None;
+        }
+        Some(if *repeat == Repeat::Many { pos } else { pos + 1 })
+    }
+}
+
+/// BFS state: a node and the pattern position reached there.
+type State = (NodeId, usize);
+
+/// State → (parent state, rel_type, direction, depth).
+type Visited = HashMap<State, (State, RelTypeId, Direction, u32)>;
+
+/// Shortest path from `start` to `target` whose sequence of relationship
+/// types matches `pattern`.
+///
+/// Runs BFS over (node, pattern position) pairs, so the result is the
+/// fewest-hop conforming path. Because the pattern may demand it, the path
+/// can pass through the same node more than once. Returns None if no
+/// conforming path exists within `max_hops`, or if either node is not in the
+/// graph. A zero-length path is returned only when `start == target` and the
+/// pattern accepts the empty sequence.
+pub fn pattern_path(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    pattern: &RelPattern,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Option<Vec<PathStep>> {
+    graph.node(start)?;
+    graph.node(target)?;
+
+    let matcher = Matcher::new(graph, pattern);
+    if start == target && matcher.accepts(0) {
+        return Some(vec![make_step(graph, start, None)]);
+    }
+
+    let mut visited: Visited = HashMap::new();
+    let mut queue: VecDeque<State> = VecDeque::new();
+
+    visited.insert((start, 0), ((start, 0), 0, Direction::Outgoing, 0));
+    queue.push_back((start, 0));
+
+    while let Some(state @ (current, pos)) = queue.pop_front() {
+        interrupt::check();
+        let depth = visited[&state].3;
+        if depth >= max_hops {
+            continue;
+        }
+
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            for p in matcher.closure(pos) {
+                let Some(next_pos) = matcher.step(p, edge.rel_type) else {
+                    continue;
+                };
+                let next = (edge.target, next_pos);
+                if let Entry::Vacant(slot) = visited.entry(next) {
+                    slot.insert((state, edge.rel_type, dir, depth + 1));
+                    if edge.target == target && matcher.accepts(next_pos) {
+                        return Some(reconstruct(graph, &visited, (start, 0), next));
+                    }
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn reconstruct(
+    graph: &Graph,
+    visited: &Visited,
+    origin: State,
+    end: State,
+) -> Vec<PathStep> {
+    let mut path = Vec::new();
+    let mut current = end;
+    while current != origin {
+        let &(parent, rel_type, dir, _) = &visited[&current];
+        path.push(make_step(graph, current.0, Some((rel_type, dir))));
+        current = parent;
+    }
+    path.push(make_step(graph, origin.0, None));
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn types(path: &[PathStep]) -> Vec<&str> {
+        path.iter().filter_map(|s| s.rel_type.as_deref()).collect()
+    }
+
+    fn find(g: &Graph, from: NodeId, to: NodeId, pattern: &str) -> Option<Vec<PathStep>> {
+        let p = RelPattern::parse(pattern).unwrap();
+        pattern_path(g, from, to, &p, 10, TraversalDirection::Outgoing, None)
+    }
+
+    /// 0 -IMPLIES-> 1 -SUPPORTS-> 5 (short, wrong shape) and
+    /// 0 -IMPLIES-> 2 -IMPLIES-> 3 -SUPPORTS-> 5 (long, conforming)
+    fn make_graph() -> Graph {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "IMPLIES"),
+            edge(1, 5, "SUPPORTS"),
+            edge(0, 2, "IMPLIES"),
+            edge(2, 3, "IMPLIES"),
+            edge(3, 5, "SUPPORTS"),
+            edge(3, 4, "CONTRADICTS"),
+        ]);
+        g
+    }
+
+    #[test]
+    fn test_parse_syntax() {
+        assert!(RelPattern::parse("IMPLIES -> IMPLIES → SUPPORTS").is_ok());
+        assert!(RelPattern::parse("(IMPLIES | ENABLES)* . SUPPORTS?").is_ok());
+        assert_eq!(
+            RelPattern::parse("A+").unwrap(),
+            RelPattern::parse("A A*").unwrap()
+        );
+        assert!(RelPattern::parse("").is_err());
+        assert!(RelPattern::parse("A - B").is_err());
+        assert!(RelPattern::parse("(A|B").is_err());
+        assert!(RelPattern::parse("()").is_err());
+        assert!(RelPattern::parse("A $").is_err());
+    }
+
+    #[test]
+    fn test_exact_sequence_skips_shorter_path() {
+        let g = make_graph();
+        let path = find(&g, 0, 5, "IMPLIES -> IMPLIES -> SUPPORTS").unwrap();
+        let ids: Vec<NodeId> = path.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![0, 2, 3, 5]);
+        assert_eq!(types(&path), vec!["IMPLIES", "IMPLIES", "SUPPORTS"]);
+
+        assert_eq!(find(&g, 0, 5, "IMPLIES SUPPORTS").unwrap().len(), 3);
+        assert!(find(&g, 0, 5, "SUPPORTS").is_none());
+        assert!(find(&g, 0, 5, "IMPLIES IMPLIES IMPLIES SUPPORTS").is_none());
+    }
+
+    #[test]
+    fn test_repetition_and_alternation() {
+        let g = make_graph();
+        assert_eq!(find(&g, 0, 5, "IMPLIES+ SUPPORTS").unwrap().len(), 3);
+        assert_eq!(find(&g, 0, 4, "IMPLIES* (SUPPORTS|CONTRADICTS)").unwrap().len(), 4);
+        assert_eq!(find(&g, 0, 4, ". . .").unwrap().len(), 4);
+        assert!(find(&g, 0, 4, ". .").is_none());
+        assert!(find(&g, 0, 5, "UNKNOWN* SUPPORTS").is_none());
+    }
+
+    #[test]
+    fn test_start_equals_target() {
+        let g = make_graph();
+        assert_eq!(find(&g, 0, 0, "IMPLIES*").unwrap().len(), 1);
+        assert!(find(&g, 0, 0, "IMPLIES").is_none());
+    }
+
+    #[test]
+    fn test_pattern_may_revisit_nodes() {
+        // Only a 2-cycle between 0 and 1: A then B returns to the start
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(0, 1, "A"), edge(1, 0, "B")]);
+        let path = find(&g, 0, 1, "A B A").unwrap();
+        let ids: Vec<NodeId> = path.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![0, 1, 0, 1]);
+
+        let p = RelPattern::parse("A B A").unwrap();
+        assert!(pattern_path(&g, 0, 1, &p, 2, TraversalDirection::Outgoing, None).is_none());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::mem;
+
+/// Interned property key. Keys are interned per graph, like relationship types.
+pub type PropKeyId = u16;
+
+/// Maximum number of distinct property keys (PropKeyId is u16).
+pub const MAX_PROP_KEYS: usize = u16::MAX as usize;
+
+/// A property value. AGE properties are JSON; only scalars are kept.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PropValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl PropValue {
+    /// Numeric value; integers are widened.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            PropValue::Int(v) => Some(*v as f64),
+            PropValue::Float(v) => Some(*v),
+            PropValue::Text(_) => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            PropValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            PropValue::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Heap bytes owned by the value.
+    fn heap_size(&self) -> usize {
+        match self {
+            PropValue::Text(s) => mem::string(s),
+            _ => 0,
+        }
+    }
+}
+
+/// A small set of properties, sorted by key ID.
+///
+/// A sorted `Vec` rather than a map: bags hold a handful of entries, so a
+/// binary search over one allocation beats hashing in both time and memory.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct PropertyBag {
+    entries: Vec<(PropKeyId, PropValue)>,
+}
+
+impl PropertyBag {
+    pub fn get(&self, key: PropKeyId) -> Option<&PropValue> {
+        self.entries
+            .binary_search_by_key(&key, |&(k, _)| k)
+            .ok()
+            .map(|i| &self.entries[i].1)
+    }
+
+    /// Set `key`, replacing any previous value.
+    pub fn insert(&mut self, key: PropKeyId, value: PropValue) {
+        match self.entries.binary_search_by_key(&key, |&(k, _)| k) {
+            Ok(i) => self.entries[i].1 = value,
+            Err(i) => self.entries.insert(i, (key, value)),
+        }
+    }
+
+    /// Entries in ascending key ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (PropKeyId, &PropValue)> {
+        self.entries.iter().map(|(k, v)| (*k, v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.entries.shrink_to_fit();
+    }
+
+    /// Heap bytes owned by the bag (entries plus text values).
+    pub(crate) fn heap_size(&self) -> usize {
+        mem::array::<(PropKeyId, PropValue)>(self.entries.capacity())
+            + self.entries.iter().map(|(_, v)| v.heap_size()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bag_insert_keeps_sorted_and_replaces() {
+        let mut bag = PropertyBag::default();
+        bag.insert(5, PropValue::Int(1));
+        bag.insert(2, PropValue::Text("x".to_string()));
+        bag.insert(5, PropValue::Float(2.5));
+        assert_eq!(bag.len(), 2);
+        assert_eq!(bag.iter().map(|(k, _)| k).collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(bag.get(5).and_then(PropValue::as_f64), Some(2.5));
+        assert_eq!(bag.get(2).and_then(PropValue::as_str), Some("x"));
+        assert_eq!(bag.get(3), None);
+        assert_eq!(PropValue::Int(3).as_f64(), Some(3.0));
+        assert_eq!(PropValue::Float(3.0).as_i64(), None);
+    }
+}
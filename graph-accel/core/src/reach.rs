@@ -0,0 +1,359 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{Graph, NodeId, RelTypeId};
+use crate::interrupt;
+
+const UNVISITED: u32 = u32::MAX;
+
+/// Precomputed reachability index over directed edges of selected rel types.
+///
+/// Built once after load, then answers "is there a directed path from a to
+/// b?" without a per-query BFS. Strongly connected components are collapsed
+/// into a DAG, and each component gets three labels:
+///
+/// - a topological level (reachability requires a strictly lower level),
+/// - a DFS spanning-tree interval (containment proves reachability),
+/// - a GRAIL-style interval `[low, post]` over all descendants (non-containment
+///   disproves reachability).
+///
+/// Most queries are settled by the labels alone in O(1); the rest fall back
+/// to a DFS over the condensed DAG that the same labels prune.
+///
+/// The index is a snapshot: it does not track later graph mutations.
+pub struct ReachabilityIndex {
+    /// Node → component. Components are numbered in reverse topological
+    /// order: every condensed edge goes from a higher to a lower ID.
+    component: HashMap<NodeId, u32>,
+    /// Condensed DAG successors, deduplicated.
+    successors: Vec<Vec<u32>>,
+    level: Vec<u32>,
+    pre: Vec<u32>,
+    post: Vec<u32>,
+    low: Vec<u32>,
+    /// Rel types the index was built over. None = all.
+    rel_types: Option<Vec<RelTypeId>>,
+}
+
+impl ReachabilityIndex {
+    /// Build the index over outgoing edges whose rel type is in `rel_types`
+    /// (or all edges if None).
+    pub fn build(graph: &Graph, rel_types: Option<&[RelTypeId]>) -> Self {
+        let allowed: Option<HashSet<RelTypeId>> = rel_types.map(|r| r.iter().copied().collect());
+
+        let mut nodes: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+        nodes.sort_unstable();
+        let dense: HashMap<NodeId, usize> = nodes.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+        let succ: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|&id| {
+                graph
+                    .neighbors_out(id)
+                    .iter()
+                    .filter(|e| allowed.as_ref().is_none_or(|a| a.contains(&e.rel_type)))
+                    .filter_map(|e| dense.get(&e.target).copied())
+                    .collect()
+            })
+            .collect();
+
+        let comp_of = tarjan_scc(&succ);
+        let comp_count = comp_of.iter().map(|&c| c as usize + 1).max().unwrap_or(0);
+
+        let mut successors: Vec<Vec<u32>> = vec![Vec::new(); comp_count];
+        for (u, targets) in succ.iter().enumerate() {
+            for &v in targets {
+                if comp_of[u] != comp_of[v] {
+                    successors[comp_of[u] as usize].push(comp_of[v]);
+                }
+            }
+        }
+        for s in &mut successors {
+            s.sort_unstable();
+            s.dedup();
+        }
+
+        // Topological order is descending component ID
+        let mut level = vec![0u32; comp_count];
+        let mut has_pred = vec![false; comp_count];
+        for c in (0..comp_count).rev() {
+            for &s in &successors[c] {
+                level[s as usize] = level[s as usize].max(level[c] + 1);
+                has_pred[s as usize] = true;
+            }
+        }
+
+        // DFS spanning forest from the sources, in topological order
+        let mut pre = vec![UNVISITED; comp_count];
+        let mut post = vec![UNVISITED; comp_count];
+        let mut counter_pre = 0u32;
+        let mut counter_post = 0u32;
+        let roots = (0..comp_count).rev().filter(|&c| !has_pred[c]);
+        for root in roots {
+            let mut stack: Vec<(u32, usize)> = vec![(root as u32, 0)];
+            pre[root] = counter_pre;
+            counter_pre += 1;
+            while let Some(&mut (c, ref mut next)) = stack.last_mut() {
+                interrupt::check();
+                if let Some(&s) = successors[c as usize].get(*next) {
+                    *next += 1;
+                    if pre[s as usize] == UNVISITED {
+                        pre[s as usize] = counter_pre;
+                        counter_pre += 1;
+                        stack.push((s, 0));
+                    }
+                } else {
+                    post[c as usize] = counter_post;
+                    counter_post += 1;
+                    stack.pop();
+                }
+            }
+        }
+
+        // low = min post over all descendants; successors have lower IDs
+        let mut low = post.clone();
+        for c in 0..comp_count {
+            for &s in &successors[c] {
+                low[c] = low[c].min(low[s as usize]);
+            }
+        }
+
+        let component = nodes
+            .iter()
+            .zip(&comp_of)
+            .map(|(&id, &c)| (id, c))
+            .collect();
+
+        Self {
+            component,
+            successors,
+            level,
+            pre,
+            post,
+            low,
+            rel_types: rel_types.map(|r| r.to_vec()),
+        }
+    }
+
+    /// True if a directed path leads from `from` to `to` (a node reaches itself).
+    /// False if either node was not in the graph when the index was built.
+    pub fn is_reachable(&self, from: NodeId, to: NodeId) -> bool {
+        let (Some(&a), Some(&b)) = (self.component.get(&from), self.component.get(&to)) else {
+            return false;
+        };
+        if a == b {
+            return true;
+        }
+        match self.label_check(a, b) {
+            Some(answer) => answer,
+            None => self.search(a, b),
+        }
+    }
+
+    /// Decide from labels alone when possible.
+    fn label_check(&self, a: u32, b: u32) -> Option<bool> {
+        let (a, b) = (a as usize, b as usize);
+        if self.level[a] >= self.level[b] {
+            return Some(false);
+        }
+        if self.pre[a] <= self.pre[b] && self.post[b] <= self.post[a] {
+            return Some(true);
+        }
+        if !(self.low[a] <= self.low[b] && self.post[b] <= self.post[a]) {
+            return Some(false);
+        }
+        None
+    }
+
+    /// Label-pruned DFS over the condensed DAG.
+    fn search(&self, a: u32, b: u32) -> bool {
+        let mut visited: HashSet<u32> = HashSet::new();
+        let mut stack = vec![a];
+        visited.insert(a);
+        while let Some(c) = stack.pop() {
+            interrupt::check();
+            for &s in &self.successors[c as usize] {
+                if s == b {
+                    return true;
+                }
+                if !visited.insert(s) {
+                    continue;
+                }
+                match self.label_check(s, b) {
+                    Some(true) => return true,
+                    Some(false) => {}
+                    None => stack.push(s),
+                }
+            }
+        }
+        false
+    }
+
+    /// Number of strongly connected components (nodes of the condensed DAG).
+    pub fn component_count(&self) -> usize {
+        self.successors.len()
+    }
+
+    /// Rel types the index covers. None = all rel types.
+    pub fn rel_types(&self) -> Option<&[RelTypeId]> {
+        self.rel_types.as_deref()
+    }
+
+    /// Approximate memory usage in bytes.
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+        let map = self.component.capacity() * (size_of::<NodeId>() + size_of::<u32>() + 1);
+        let dag: usize = self
+            .successors
+            .iter()
+            .map(|s| size_of::<Vec<u32>>() + s.capacity() * size_of::<u32>())
+            .sum();
+        let labels = 4 * self.level.capacity() * size_of::<u32>();
+        map + dag + labels
+    }
+}
+
+/// Iterative Tarjan SCC over dense adjacency. Returns the component of each
+/// node; components are emitted sinks first (reverse topological order).
+pub(crate) fn tarjan_scc(succ: &[Vec<usize>]) -> Vec<u32> {
+    let n = succ.len();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0u32; n];
+    let mut on_stack = vec![false; n];
+    let mut comp = vec![UNVISITED; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut next_index = 0u32;
+    let mut next_comp = 0u32;
+
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        let mut call: Vec<(usize, usize)> = vec![(root, 0)];
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&mut (v, ref mut pos)) = call.last_mut() {
+            interrupt::check();
+            if let Some(&w) = succ[v].get(*pos) {
+                *pos += 1;
+                if index[w] == UNVISITED {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    call.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+            } else {
+                call.pop();
+                if let Some(&(parent, _)) = call.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[v]);
+                }
+                if lowlink[v] == index[v] {
+                    loop {
+                        let w = stack.pop().expect("v is on the stack");
+                        on_stack[w] = false;
+                        comp[w] = next_comp;
+                        if w == v {
+                            break;
+                        }
+                    }
+                    next_comp += 1;
+                }
+            }
+        }
+    }
+    comp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{EdgeRecord, TraversalDirection};
+    use crate::test_util::edge;
+
+    /// Brute-force check against directed BFS for every pair.
+    fn assert_matches_bfs(g: &Graph, index: &ReachabilityIndex) {
+        let ids: Vec<NodeId> = g.nodes_iter().map(|(&id, _)| id).collect();
+        for &a in &ids {
+            let reach = crate::traversal::bfs_neighborhood(g, a, u32::MAX, TraversalDirection::Outgoing, None, 0);
+            let set: HashSet<NodeId> = reach.neighbors.iter().map(|n| n.node_id).collect();
+            for &b in &ids {
+                let expected = a == b || set.contains(&b);
+                assert_eq!(index.is_reachable(a, b), expected, "{a} -> {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_reach_dag() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(0, 2, "R"),
+            edge(1, 3, "R"),
+            edge(2, 3, "R"),
+            edge(3, 4, "R"),
+            edge(5, 4, "R"),
+            edge(2, 6, "R"),
+        ]);
+        let index = ReachabilityIndex::build(&g, None);
+        assert_eq!(index.component_count(), 7);
+        assert!(index.is_reachable(0, 4));
+        assert!(!index.is_reachable(4, 0));
+        assert!(!index.is_reachable(1, 2));
+        assert!(!index.is_reachable(5, 0));
+        assert_matches_bfs(&g, &index);
+    }
+
+    #[test]
+    fn test_reach_cycles_collapse() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(2, 3, "R"),
+            edge(3, 4, "R"),
+            edge(4, 3, "R"),
+            edge(5, 5, "R"),
+        ]);
+        let index = ReachabilityIndex::build(&g, None);
+        assert_eq!(index.component_count(), 3);
+        assert!(index.is_reachable(1, 0));
+        assert!(index.is_reachable(0, 4));
+        assert!(!index.is_reachable(3, 1));
+        assert!(index.is_reachable(5, 5));
+        assert_matches_bfs(&g, &index);
+    }
+
+    #[test]
+    fn test_reach_rel_type_filter() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(0, 1, "SUBSUMES"), edge(1, 2, "RELATED"), edge(1, 3, "SUBSUMES")]);
+        let rt = g.rel_type_id("SUBSUMES").unwrap();
+        let index = ReachabilityIndex::build(&g, Some(&[rt]));
+        assert!(index.is_reachable(0, 3));
+        assert!(!index.is_reachable(0, 2));
+        assert_eq!(index.rel_types(), Some(&[rt][..]));
+        assert!(!index.is_reachable(0, 99));
+    }
+
+    #[test]
+    fn test_reach_matches_bfs_on_pseudorandom_graph() {
+        // Deterministic pseudo-random digraph with cycles and cross edges
+        let mut g = Graph::new();
+        let mut rng = crate::rng::SplitMix64::new(11);
+        let edges: Vec<EdgeRecord> = (0..120)
+            .map(|_| edge(rng.below(40) as NodeId, rng.below(40) as NodeId, "R"))
+            .collect();
+        g.load_edges(edges);
+        let index = ReachabilityIndex::build(&g, None);
+        assert_matches_bfs(&g, &index);
+        assert!(index.memory_usage() > 0);
+    }
+}
@@ -0,0 +1,288 @@
+//! `serde` support for `Graph` (feature `serde`).
+//!
+//! A graph goes through a flat representation rather than its in-memory
+//! layout: the interned name tables in ID order, nodes and outgoing edges
+//! sorted by node ID, the text and integer app_id indices, the secondary
+//! indices and edge property bags. Incoming lists and the CSR arrays are
+//! rebuilt on load, so they take no space on disk, and a given graph always
+//! serializes to the same output.
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::graph::{Edge, Graph, NodeId, NodeInfo, RelTypeId};
+use crate::props::PropertyBag;
+
+/// One stored edge. Sentinels become `None`, so formats without NaN
+/// (JSON) round-trip edges that have no confidence.
+#[derive(Serialize, Deserialize)]
+struct EdgeRepr {
+    from: NodeId,
+    to: NodeId,
+    rel_type: RelTypeId,
+    confidence: Option<f32>,
+    weight: f32,
+    created_at: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct GraphRef<'a> {
+    rel_types: &'a [String],
+    labels: &'a [String],
+    prop_keys: &'a [String],
+    nodes: Vec<(NodeId, &'a NodeInfo)>,
+    app_ids: Vec<(&'a str, NodeId)>,
+    int_app_ids: Vec<(i64, NodeId)>,
+    indices: Vec<(&'a str, Vec<(&'a str, NodeId)>)>,
+    edges: Vec<EdgeRepr>,
+    edge_props: Vec<(NodeId, NodeId, RelTypeId, &'a PropertyBag)>,
+    frozen: bool,
+}
+
+#[derive(Deserialize)]
+struct GraphRepr {
+    rel_types: Vec<String>,
+    labels: Vec<String>,
+    prop_keys: Vec<String>,
+    nodes: Vec<(NodeId, NodeInfo)>,
+    app_ids: Vec<(String, NodeId)>,
+    /// Absent in output written before integer app IDs existed.
+    #[serde(default)]
+    int_app_ids: Vec<(i64, NodeId)>,
+    /// Absent in output written before secondary indices existed.
+    #[serde(default)]
+    indices: Vec<(String, Vec<(String, NodeId)>)>,
+    edges: Vec<EdgeRepr>,
+    edge_props: Vec<(NodeId, NodeId, RelTypeId, PropertyBag)>,
+    frozen: bool,
+}
+
+impl Serialize for Graph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ids = self.sorted_ids();
+        let nodes = ids
+            .iter()
+            .filter_map(|id| self.nodes.get(id).map(|info| (*id, info)))
+            .collect();
+        let edges = ids
+            .iter()
+            .flat_map(|&from| {
+                self.neighbors_out(from).iter().map(move |e| EdgeRepr {
+                    from,
+                    to: e.target,
+                    rel_type: e.rel_type,
+                    confidence: e.has_confidence().then_some(e.confidence),
+                    weight: e.weight,
+                    created_at: e.created_at(),
+                })
+            })
+            .collect();
+
+        let mut app_ids: Vec<(&str, NodeId)> = self
+            .app_id_index
+            .iter()
+            .map(|(aid, &id)| (aid.as_str(), id))
+            .collect();
+        app_ids.sort_unstable();
+        let mut int_app_ids: Vec<(i64, NodeId)> =
+            self.int_app_id_index.iter().map(|(&aid, &id)| (aid, id)).collect();
+        int_app_ids.sort_unstable();
+        let indices = self
+            .index_names()
+            .into_iter()
+            .map(|name| {
+                let mut entries: Vec<(&str, NodeId)> = self.secondary_indices[name]
+                    .iter()
+                    .map(|(value, &id)| (value.as_str(), id))
+                    .collect();
+                entries.sort_unstable();
+                (name, entries)
+            })
+            .collect();
+        let mut edge_props: Vec<_> = self
+            .edge_props
+            .iter()
+            .map(|(&(from, to, rt), bag)| (from, to, rt, bag))
+            .collect();
+        edge_props.sort_unstable_by_key(|&(from, to, rt, _)| (from, to, rt));
+
+        GraphRef {
+            rel_types: &self.rel_types,
+            labels: &self.labels,
+            prop_keys: &self.prop_keys,
+            nodes,
+            app_ids,
+            int_app_ids,
+            indices,
+            edges,
+            edge_props,
+            frozen: self.is_frozen(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = GraphRepr::deserialize(deserializer)?;
+        let mut graph = Graph::with_capacity(repr.nodes.len(), repr.edges.len());
+
+        // Re-intern in ID order; a duplicate name would shift every later ID
+        for (i, name) in repr.rel_types.iter().enumerate() {
+            if graph.try_intern_rel_type(name).map_err(D::Error::custom)? as usize != i {
+                return Err(D::Error::custom(format!("duplicate rel type '{}'", name)));
+            }
+        }
+        for (i, name) in repr.labels.iter().enumerate() {
+            if graph.try_intern_label(name).map_err(D::Error::custom)? as usize != i {
+                return Err(D::Error::custom(format!("duplicate label '{}'", name)));
+            }
+        }
+        for (i, name) in repr.prop_keys.iter().enumerate() {
+            if graph.try_intern_prop_key(name).map_err(D::Error::custom)? as usize != i {
+                return Err(D::Error::custom(format!(
+                    "duplicate property key '{}'",
+                    name
+                )));
+            }
+        }
+
+        let key_count = repr.prop_keys.len();
+        for (id, info) in repr.nodes {
+            let labels_ok = info.extra_labels.windows(2).all(|w| w[0] < w[1])
+                && std::iter::once(&info.label)
+                    .chain(info.extra_labels.iter())
+                    .all(|&l| (l as usize) < repr.labels.len());
+            if !labels_ok {
+                return Err(D::Error::custom(format!(
+                    "node {}: invalid labels",
+                    id
+                )));
+            }
+            if !info
+                .properties
+                .as_deref()
+                .is_none_or(|bag| bag_is_valid(bag, key_count))
+            {
+                return Err(D::Error::custom(format!("node {}: invalid properties", id)));
+            }
+            graph.nodes.insert(id, info);
+        }
+
+        for e in repr.edges {
+            if e.rel_type as usize >= repr.rel_types.len() {
+                return Err(D::Error::custom(format!(
+                    "unknown rel type id {}",
+                    e.rel_type
+                )));
+            }
+            graph.add_timestamped_edge(
+                e.from,
+                e.to,
+                e.rel_type,
+                e.confidence.unwrap_or(Edge::NO_CONFIDENCE),
+                e.weight,
+                e.created_at.unwrap_or(Edge::NO_TIMESTAMP),
+            );
+        }
+
+        graph.app_id_index = repr.app_ids.into_iter().collect();
+        graph.int_app_id_index = repr.int_app_ids.into_iter().collect();
+        for (name, entries) in repr.indices {
+            graph
+                .secondary_indices
+                .insert(name, entries.into_iter().collect());
+        }
+        for (from, to, rt, bag) in repr.edge_props {
+            if !bag_is_valid(&bag, key_count) {
+                return Err(D::Error::custom(format!(
+                    "edge {} -> {}: invalid properties",
+                    from, to
+                )));
+            }
+            graph.edge_props.insert((from, to, rt), bag);
+        }
+
+        graph.rebuild_label_partitions();
+        if repr.frozen {
+            graph.freeze();
+        }
+        Ok(graph)
+    }
+}
+
+/// Keys strictly ascending (the invariant `PropertyBag::get` relies on) and
+/// all interned.
+fn bag_is_valid(bag: &PropertyBag, key_count: usize) -> bool {
+    let keys: Vec<_> = bag.iter().map(|(k, _)| k).collect();
+    keys.windows(2).all(|w| w[0] < w[1]) && keys.iter().all(|&k| (k as usize) < key_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Direction, EdgeRecord};
+    use crate::props::PropValue;
+
+    fn edge(from: NodeId, to: NodeId, rel: &str) -> EdgeRecord {
+        EdgeRecord {
+            from_app_id: Some(format!("n{}", from)),
+            to_app_id: Some(format!("n{}", to)),
+            ..crate::test_util::edge(from, to, rel)
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut rec = edge(1, 2, "CITES");
+        rec.confidence = 0.5;
+        rec.weight = 3.0;
+        rec.created_at = 1_700_000_000;
+        rec.properties = vec![(
+            "category".to_string(),
+            PropValue::Text("survey".to_string()),
+        )];
+        let mut g = Graph::new();
+        g.load_edges(vec![rec, edge(2, 3, "IMPLIES"), edge(3, 1, "CITES")]);
+        g.add_node_labels(2, ["Person"]);
+        g.set_node_properties(3, [("rank".to_string(), PropValue::Int(7))]);
+        g.freeze();
+
+        let json = serde_json::to_string(&g).unwrap();
+        let back: Graph = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+
+        assert!(back.is_frozen());
+        assert_eq!((back.node_count(), back.edge_count()), (3, 3));
+        assert_eq!(back.resolve_app_id("n2"), Some(2));
+        assert_eq!(back.node_labels(2), vec!["Node", "Person"]);
+        assert_eq!(
+            back.node_property(3, "rank").and_then(PropValue::as_i64),
+            Some(7)
+        );
+
+        let e = back.neighbors_out(1).first().unwrap();
+        assert_eq!(back.rel_type_name(e.rel_type), Some("CITES"));
+        assert_eq!(
+            (e.confidence, e.weight, e.created_at()),
+            (0.5, 3.0, Some(1_700_000_000))
+        );
+        assert!(!back.neighbors_out(2).first().unwrap().has_confidence());
+        assert_eq!(back.neighbors_in(1).first().unwrap().target, 3);
+        let category = back.edge_property(1, &e, Direction::Outgoing, "category");
+        assert_eq!(category.and_then(PropValue::as_str), Some("survey"));
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_input() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "R")]);
+        let mut value = serde_json::to_value(&g).unwrap();
+        value["edges"][0]["rel_type"] = 5.into();
+        assert!(serde_json::from_value::<Graph>(value).is_err());
+
+        let mut value = serde_json::to_value(&g).unwrap();
+        value["rel_types"] = serde_json::json!(["R", "R"]);
+        assert!(serde_json::from_value::<Graph>(value).is_err());
+    }
+}
@@ -0,0 +1,631 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{Graph, NodeId, TraversalDirection};
+use crate::traversal::{bfs_distances, iter_neighbors};
+
+/// A candidate node for a new link, scored against a source node.
+#[derive(Debug, Clone)]
+pub struct LinkCandidate {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Neighbors shared with the source node.
+    pub common_neighbors: u32,
+    pub score: f64,
+}
+
+/// Neighborhood overlap of one stored edge's endpoints.
+#[derive(Debug, Clone)]
+pub struct EdgeEmbeddedness {
+    pub from_id: NodeId,
+    pub from_label: String,
+    pub from_app_id: Option<String>,
+    pub to_id: NodeId,
+    pub to_label: String,
+    pub to_app_id: Option<String>,
+    pub rel_type: String,
+    /// None if the edge has no confidence data.
+    pub confidence: Option<f32>,
+    /// Neighbors shared by the two endpoints.
+    pub common_neighbors: usize,
+    /// Jaccard overlap of the endpoints' neighbor sets, each endpoint
+    /// excluded from the other's set (0.0 when both have no other neighbor).
+    pub embeddedness: f64,
+}
+
+/// Which seed's neighborhood a node belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Overlap {
+    Both,
+    OnlyA,
+    OnlyB,
+}
+
+impl Overlap {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Overlap::Both => "both",
+            Overlap::OnlyA => "a_only",
+            Overlap::OnlyB => "b_only",
+        }
+    }
+}
+
+/// A node in the union of two k-hop neighborhoods.
+#[derive(Debug, Clone)]
+pub struct NeighborhoodDiffEntry {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    pub overlap: Overlap,
+    /// Hops from the first seed; None if outside its neighborhood.
+    pub distance_a: Option<u32>,
+    /// Hops from the second seed; None if outside its neighborhood.
+    pub distance_b: Option<u32>,
+}
+
+/// A neighbor shared by two nodes, with the relationship types linking it to each.
+#[derive(Debug, Clone)]
+pub struct CommonNeighbor {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Distinct rel types on edges between the first node and this one (either direction), sorted.
+    pub rel_types_a: Vec<String>,
+    /// Distinct rel types on edges between the second node and this one (either direction), sorted.
+    pub rel_types_b: Vec<String>,
+}
+
+/// Distinct neighbors of `id`, ignoring edge direction and self-loops.
+pub(crate) fn undirected_neighbors(graph: &Graph, id: NodeId) -> HashSet<NodeId> {
+    graph
+        .neighbors_all(id)
+        .map(|(e, _)| e.target)
+        .filter(|&t| t != id)
+        .collect()
+}
+
+/// Neighbors shared by `a` and `b`, ignoring edge direction and self-loops.
+///
+/// Each result lists the relationship types connecting it to `a` and to `b`.
+/// `a` and `b` themselves are never reported, even if directly linked.
+/// Sorted by node ID. Empty if either node is not in the graph.
+pub fn common_neighbors(graph: &Graph, a: NodeId, b: NodeId) -> Vec<CommonNeighbor> {
+    if graph.node(a).is_none() || graph.node(b).is_none() {
+        return Vec::new();
+    }
+
+    // neighbor → rel types, for one endpoint
+    let rel_types_by_neighbor = |id: NodeId| -> HashMap<NodeId, Vec<String>> {
+        let mut map: HashMap<NodeId, Vec<String>> = HashMap::new();
+        for (e, _) in graph.neighbors_all(id) {
+            if e.target == id {
+                continue;
+            }
+            let types = map.entry(e.target).or_default();
+            if let Some(name) = graph.rel_type_name(e.rel_type) {
+                if !types.iter().any(|t| t == name) {
+                    types.push(name.to_string());
+                }
+            }
+        }
+        map
+    };
+
+    let from_a = rel_types_by_neighbor(a);
+    let mut from_b = rel_types_by_neighbor(b);
+
+    let mut results: Vec<CommonNeighbor> = from_a
+        .into_iter()
+        .filter(|&(z, _)| z != a && z != b)
+        .filter_map(|(z, mut types_a)| {
+            let mut types_b = from_b.remove(&z)?;
+            types_a.sort();
+            types_b.sort();
+            let info = graph.node(z);
+            Some(CommonNeighbor {
+                node_id: z,
+                label: graph.node_label(z).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                rel_types_a: types_a,
+                rel_types_b: types_b,
+            })
+        })
+        .collect();
+
+    results.sort_by_key(|c| c.node_id);
+    results
+}
+
+/// Adamic-Adar link prediction: best unlinked candidates for `source`.
+///
+/// Every node two hops away that is not already linked to `source` is scored
+/// as `Σ 1 / ln(deg(z))` over shared neighbors `z`, so a rare shared neighbor
+/// counts for more than a hub. Edges are treated as undirected; degree is the
+/// number of distinct neighbors.
+///
+/// If `top_n` is 0, returns all candidates. Otherwise the top N by score
+/// (descending), ties broken by node ID. Empty if `source` is not in the graph.
+pub fn adamic_adar(graph: &Graph, source: NodeId, top_n: usize) -> Vec<LinkCandidate> {
+    if graph.node(source).is_none() {
+        return Vec::new();
+    }
+
+    let direct = undirected_neighbors(graph, source);
+    let mut scores: HashMap<NodeId, (u32, f64)> = HashMap::new();
+
+    for &z in &direct {
+        let z_neighbors = undirected_neighbors(graph, z);
+        // z is adjacent to source plus at least one candidate, so deg >= 2 and ln > 0
+        let weight = 1.0 / (z_neighbors.len() as f64).ln();
+        for y in z_neighbors {
+            if y == source || direct.contains(&y) {
+                continue;
+            }
+            let entry = scores.entry(y).or_insert((0, 0.0));
+            entry.0 += 1;
+            entry.1 += weight;
+        }
+    }
+
+    let mut results: Vec<LinkCandidate> = scores
+        .into_iter()
+        .map(|(id, (common, score))| {
+            let info = graph.node(id);
+            LinkCandidate {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                common_neighbors: common,
+                score,
+            }
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.node_id.cmp(&b.node_id)));
+
+    if top_n > 0 && top_n < results.len() {
+        results.truncate(top_n);
+    }
+
+    results
+}
+
+/// Embeddedness (neighborhood overlap) of every edge.
+///
+/// For an edge `a → b`, the score is `|N(a) ∩ N(b)| / |N(a) ∪ N(b) \ {a, b}|`
+/// with neighbor sets taken undirected and without self-loops. Edges inside
+/// tightly knit clusters score near 1; edges whose endpoints share nothing
+/// score 0 — long-range ties or extraction mistakes, worth reviewing when
+/// their confidence is high. Self-loops are skipped; parallel edges each get
+/// a row with the same score.
+///
+/// Sorted by score, descending unless `ascending` (least embedded first),
+/// ties by endpoint IDs and rel type. If `top_n` is 0, returns all edges;
+/// otherwise the first N.
+pub fn edge_embeddedness(graph: &Graph, top_n: usize, ascending: bool) -> Vec<EdgeEmbeddedness> {
+    let neighbors: HashMap<NodeId, HashSet<NodeId>> = graph
+        .nodes_iter()
+        .map(|(&id, _)| (id, undirected_neighbors(graph, id)))
+        .collect();
+
+    let mut results: Vec<EdgeEmbeddedness> = Vec::new();
+    for (&from, from_neighbors) in &neighbors {
+        for edge in graph.neighbors_out(from) {
+            let to = edge.target;
+            if to == from {
+                continue;
+            }
+            let Some(to_neighbors) = neighbors.get(&to) else {
+                continue;
+            };
+            let common = from_neighbors.intersection(to_neighbors).count();
+            // Each endpoint sits in the other's set; drop both from the union
+            let union = from_neighbors.len() + to_neighbors.len() - common - 2;
+            let embeddedness = if union == 0 { 0.0 } else { common as f64 / union as f64 };
+
+            let from_info = graph.node(from);
+            let to_info = graph.node(to);
+            results.push(EdgeEmbeddedness {
+                from_id: from,
+                from_label: graph.node_label(from).unwrap_or_default().to_string(),
+                from_app_id: from_info.and_then(|n| n.app_id.clone()),
+                to_id: to,
+                to_label: graph.node_label(to).unwrap_or_default().to_string(),
+                to_app_id: to_info.and_then(|n| n.app_id.clone()),
+                rel_type: graph.rel_type_name(edge.rel_type).unwrap_or("UNKNOWN").to_string(),
+                confidence: edge.has_confidence().then_some(edge.confidence),
+                common_neighbors: common,
+                embeddedness,
+            });
+        }
+    }
+
+    results.sort_by(|a, b| {
+        let by_score = if ascending {
+            a.embeddedness.total_cmp(&b.embeddedness)
+        } else {
+            b.embeddedness.total_cmp(&a.embeddedness)
+        };
+        by_score
+            .then(a.from_id.cmp(&b.from_id))
+            .then(a.to_id.cmp(&b.to_id))
+            .then(a.rel_type.cmp(&b.rel_type))
+    });
+    if top_n > 0 && top_n < results.len() {
+        results.truncate(top_n);
+    }
+    results
+}
+
+/// Truncated SimRank similarity between `a` and `b`.
+///
+/// SimRank scores two nodes as similar when their neighbors are similar:
+/// `s(a, b) = C / (|N(a)| |N(b)|) · Σ s(i, j)` over neighbor pairs, with
+/// `s(x, x) = 1`. It picks up structural-context similarity even when the
+/// nodes share no direct neighbors.
+///
+/// Computed for the single pair only, using the equivalent random-surfer
+/// form `s(a, b) = E[C^τ]`, where τ is the first step at which two walks
+/// from `a` and `b` (moving to a uniformly chosen distinct neighbor each
+/// step) land on the same node. The walk-pair distribution is propagated
+/// exactly for `iterations` steps, which equals `iterations` rounds of
+/// iterative SimRank. Cost depends only on the neighborhoods of `a` and
+/// `b`, not on graph size — but grows quickly with `iterations` on dense
+/// graphs.
+///
+/// `direction` picks the neighbor set: `Incoming` is classic SimRank
+/// (in-neighbors). Returns 0.0 if either node is missing, 1.0 if `a == b`.
+pub fn simrank(
+    graph: &Graph,
+    a: NodeId,
+    b: NodeId,
+    decay: f64,
+    iterations: u32,
+    direction: TraversalDirection,
+) -> f64 {
+    if graph.node(a).is_none() || graph.node(b).is_none() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let mut neighbor_cache: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    let mut neighbors_of = |id: NodeId| -> Vec<NodeId> {
+        neighbor_cache
+            .entry(id)
+            .or_insert_with(|| {
+                let mut v: Vec<NodeId> = iter_neighbors(graph, id, direction, None)
+                    .map(|(e, _)| e.target)
+                    .collect();
+                v.sort_unstable();
+                v.dedup();
+                v
+            })
+            .clone()
+    };
+
+    // Probability mass on each not-yet-met walk pair (i, j), i != j
+    let mut mass: HashMap<(NodeId, NodeId), f64> = HashMap::new();
+    mass.insert((a, b), 1.0);
+    let mut score = 0.0;
+    let mut weight = 1.0;
+
+    for _ in 0..iterations {
+        weight *= decay;
+        let mut next: HashMap<(NodeId, NodeId), f64> = HashMap::new();
+        for ((i, j), p) in mass {
+            let ni = neighbors_of(i);
+            let nj = neighbors_of(j);
+            if ni.is_empty() || nj.is_empty() {
+                continue; // a walk is stuck: the pair never meets
+            }
+            let share = p / (ni.len() * nj.len()) as f64;
+            for &x in &ni {
+                for &y in &nj {
+                    if x == y {
+                        score += weight * share;
+                    } else {
+                        // Unordered: s(x, y) = s(y, x)
+                        let key = if x < y { (x, y) } else { (y, x) };
+                        *next.entry(key).or_insert(0.0) += share;
+                    }
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        mass = next;
+    }
+
+    score
+}
+
+/// Compare the `max_depth`-hop neighborhoods of `a` and `b` in one call.
+///
+/// Every node within reach of either seed is classified as shared or unique
+/// to one side, with its distance from each. The seeds themselves are not
+/// listed. Sorted by overlap (shared first), then node ID. Empty if either
+/// seed is not in the graph.
+pub fn neighborhood_diff(
+    graph: &Graph,
+    a: NodeId,
+    b: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<NeighborhoodDiffEntry> {
+    if graph.node(a).is_none() || graph.node(b).is_none() {
+        return Vec::new();
+    }
+
+    // node → (distance from a, distance from b)
+    let mut dist: HashMap<NodeId, (Option<u32>, Option<u32>)> = HashMap::new();
+    for (id, d) in bfs_distances(graph, a, max_depth, direction, min_confidence) {
+        dist.entry(id).or_default().0 = Some(d);
+    }
+    for (id, d) in bfs_distances(graph, b, max_depth, direction, min_confidence) {
+        dist.entry(id).or_default().1 = Some(d);
+    }
+
+    let mut entries: Vec<NeighborhoodDiffEntry> = dist
+        .into_iter()
+        .filter(|&(id, _)| id != a && id != b)
+        .map(|(id, (da, db))| {
+            let overlap = match (da, db) {
+                (Some(_), Some(_)) => Overlap::Both,
+                (Some(_), None) => Overlap::OnlyA,
+                _ => Overlap::OnlyB,
+            };
+            let info = graph.node(id);
+            NeighborhoodDiffEntry {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                overlap,
+                distance_a: da,
+                distance_b: db,
+            }
+        })
+        .collect();
+
+    entries.sort_by_key(|e| (e.overlap, e.node_id));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    #[test]
+    fn test_common_neighbors_with_rel_types() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 10, "SUPPORTS"),
+            edge(10, 0, "IMPLIES"),
+            edge(1, 10, "SUPPORTS"),
+            edge(11, 1, "PART_OF"),
+            edge(0, 11, "PART_OF"),
+            edge(0, 12, "X"),      // only a's neighbor
+            edge(0, 1, "SIMILAR"), // direct link: not a common neighbor
+        ]);
+
+        let shared = common_neighbors(&g, 0, 1);
+        let ids: Vec<NodeId> = shared.iter().map(|c| c.node_id).collect();
+        assert_eq!(ids, vec![10, 11]);
+        assert_eq!(shared[0].rel_types_a, vec!["IMPLIES", "SUPPORTS"]);
+        assert_eq!(shared[0].rel_types_b, vec!["SUPPORTS"]);
+        assert_eq!(shared[1].rel_types_a, vec!["PART_OF"]);
+        assert_eq!(shared[1].rel_types_b, vec!["PART_OF"]);
+    }
+
+    #[test]
+    fn test_common_neighbors_edge_cases() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(0, 1, "R"), edge(1, 2, "R")]);
+        assert!(common_neighbors(&g, 0, 99).is_empty());
+        assert!(common_neighbors(&g, 0, 1).is_empty());
+        // Same node: its own neighbors
+        assert_eq!(common_neighbors(&g, 1, 1).len(), 2);
+        assert_eq!(common_neighbors(&g, 0, 2)[0].node_id, 1);
+    }
+
+    #[test]
+    fn test_simrank_shared_parent() {
+        // 0 → 1, 0 → 2: in-neighbor sets are both {0}, so s = C
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(0, 1, "R"), edge(0, 2, "R")]);
+        let s = simrank(&g, 1, 2, 0.8, 5, TraversalDirection::Incoming);
+        assert!((s - 0.8).abs() < 1e-12);
+        assert_eq!(simrank(&g, 1, 1, 0.8, 5, TraversalDirection::Incoming), 1.0);
+        assert_eq!(simrank(&g, 1, 99, 0.8, 5, TraversalDirection::Incoming), 0.0);
+        // 0 has no in-neighbors
+        assert_eq!(simrank(&g, 0, 1, 0.8, 5, TraversalDirection::Incoming), 0.0);
+    }
+
+    #[test]
+    fn test_simrank_structural_context_without_common_neighbors() {
+        // 0→1→3 and 0→2→4: 3 and 4 share no neighbor but have similar parents
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(0, 2, "R"),
+            edge(1, 3, "R"),
+            edge(2, 4, "R"),
+        ]);
+        let s = simrank(&g, 3, 4, 0.8, 5, TraversalDirection::Incoming);
+        assert!((s - 0.64).abs() < 1e-12);
+        // Truncating at one iteration can't see the grandparent
+        assert_eq!(simrank(&g, 3, 4, 0.8, 1, TraversalDirection::Incoming), 0.0);
+    }
+
+    #[test]
+    fn test_simrank_matches_iterative_definition() {
+        // Compare with naive all-pairs iteration on a small undirected graph
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(2, 3, "R"),
+            edge(3, 4, "R"),
+            edge(4, 1, "R"),
+        ]);
+        let n = 5usize;
+        let c = 0.6;
+        let nbrs: Vec<Vec<NodeId>> = (0..n as NodeId)
+            .map(|i| {
+                let mut v: Vec<NodeId> = undirected_neighbors(&g, i).into_iter().collect();
+                v.sort_unstable();
+                v
+            })
+            .collect();
+        let mut sim = vec![vec![0.0; n]; n];
+        for (i, row) in sim.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        for _ in 0..4 {
+            let mut next = vec![vec![0.0; n]; n];
+            for x in 0..n {
+                for y in 0..n {
+                    if x == y {
+                        next[x][y] = 1.0;
+                        continue;
+                    }
+                    let total: f64 = nbrs[x]
+                        .iter()
+                        .flat_map(|&i| nbrs[y].iter().map(move |&j| (i, j)))
+                        .map(|(i, j)| sim[i as usize][j as usize])
+                        .sum();
+                    next[x][y] = c * total / (nbrs[x].len() * nbrs[y].len()) as f64;
+                }
+            }
+            sim = next;
+        }
+        for (x, row) in sim.iter().enumerate() {
+            for (y, &expected) in row.iter().enumerate() {
+                let s = simrank(&g, x as NodeId, y as NodeId, c, 4, TraversalDirection::Both);
+                assert!((s - expected).abs() < 1e-9, "s({x},{y}) = {s} vs {expected}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_adamic_adar_prefers_rare_shared_neighbors() {
+        // 0 and 1 share neighbor 10 (degree 2); 0 and 2 share hub 20 (degree 6)
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 10, "R"),
+            edge(1, 10, "R"),
+            edge(0, 20, "R"),
+            edge(2, 20, "R"),
+        ]);
+        g.load_edges((30..34).map(|i| edge(20, i, "R")));
+
+        let results = adamic_adar(&g, 0, 0);
+        assert_eq!(results[0].node_id, 1);
+        assert!((results[0].score - 1.0 / 2f64.ln()).abs() < 1e-12);
+        let two = results.iter().find(|c| c.node_id == 2).unwrap();
+        assert!((two.score - 1.0 / 6f64.ln()).abs() < 1e-12);
+        assert_eq!(two.common_neighbors, 1);
+    }
+
+    #[test]
+    fn test_adamic_adar_excludes_linked_and_self() {
+        // Triangle 0-1-2 plus 2-3: only 3 is an unlinked candidate for 0
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(2, 3, "R"),
+            edge(0, 0, "R"),
+        ]);
+        let ids: Vec<NodeId> = adamic_adar(&g, 0, 0).iter().map(|c| c.node_id).collect();
+        assert_eq!(ids, vec![3]);
+    }
+
+    #[test]
+    fn test_adamic_adar_accumulates_and_limits() {
+        // 0 and 5 share three neighbors; 0 and 6 share one
+        let mut g = Graph::new();
+        for z in 1..=3 {
+            g.load_edges(vec![edge(0, z, "R"), edge(z, 5, "R")]);
+        }
+        g.load_edges(vec![edge(1, 6, "R")]);
+        let results = adamic_adar(&g, 0, 1);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].node_id, 5);
+        assert_eq!(results[0].common_neighbors, 3);
+        assert!(adamic_adar(&g, 99, 10).is_empty());
+    }
+
+    #[test]
+    fn test_neighborhood_diff() {
+        // 10 - 0 - 1 - 2 - 3 - 13 ; seeds 1 and 2 share 0..3 at depth 2
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(10, 0, "R"),
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 3, "R"),
+            edge(3, 13, "R"),
+        ]);
+
+        let diff = neighborhood_diff(&g, 1, 2, 2, TraversalDirection::Both, None);
+        let summary: Vec<(NodeId, Overlap, Option<u32>, Option<u32>)> = diff
+            .iter()
+            .map(|e| (e.node_id, e.overlap, e.distance_a, e.distance_b))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (0, Overlap::Both, Some(1), Some(2)),
+                (3, Overlap::Both, Some(2), Some(1)),
+                (10, Overlap::OnlyA, Some(2), None),
+                (13, Overlap::OnlyB, None, Some(2)),
+            ]
+        );
+
+        assert!(neighborhood_diff(&g, 1, 999, 2, TraversalDirection::Both, None).is_empty());
+        // Identical seeds: everything shared
+        let same = neighborhood_diff(&g, 1, 1, 1, TraversalDirection::Both, None);
+        assert!(same.iter().all(|e| e.overlap == Overlap::Both));
+        assert_eq!(same.len(), 2);
+    }
+
+    #[test]
+    fn test_edge_embeddedness() {
+        // Triangle 0-1-2 with a pendant 2→3 (and a parallel 2→3), self-loop on 0
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(2, 0, "R"),
+            edge(2, 3, "A"),
+            edge(2, 3, "B"),
+            edge(0, 0, "R"),
+        ]);
+        let r = edge_embeddedness(&g, 0, false);
+        assert_eq!(r.len(), 5);
+
+        let score = |a: NodeId, b: NodeId| {
+            r.iter().find(|e| (e.from_id, e.to_id) == (a, b)).unwrap()
+        };
+        // N(0) = {1, 2}, N(1) = {0, 2}: share 2, union without endpoints = {2}
+        assert_eq!(score(0, 1).embeddedness, 1.0);
+        assert_eq!(score(0, 1).common_neighbors, 1);
+        // N(1) = {0, 2}, N(2) = {0, 1, 3}: share 0, union without endpoints = {0, 3}
+        assert_eq!(score(1, 2).embeddedness, 0.5);
+        assert_eq!(score(2, 3).embeddedness, 0.0);
+        assert_eq!(score(2, 3).confidence, None);
+
+        let least = edge_embeddedness(&g, 2, true);
+        assert_eq!(least.len(), 2);
+        assert_eq!(least.iter().map(|e| e.rel_type.as_str()).collect::<Vec<_>>(), vec!["A", "B"]);
+        assert_eq!((r[0].from_id, r[0].to_id), (0, 1));
+    }
+}
@@ -0,0 +1,566 @@
+//! Binary snapshots: persist a loaded graph and restore it without
+//! re-reading the source tables.
+//!
+//! Layout, all integers little-endian:
+//!
+//! ```text
+//! magic "GACSNAP\0", version u32, frozen u8
+//! rel types, labels, property keys   (u32 count, then strings)
+//! node table                         (u64 count, then per ascending ID:
+//!                                     u64 id, u8 has-metadata, metadata)
+//! outgoing, incoming adjacency       (u64 edge count, u64 degree per
+//!                                     node, then the edges in node order)
+//! app_id index                       (u64 count, then string + u64 id)
+//! integer app_id index               (u64 count, then i64 + u64 id)
+//! secondary indices                  (u32 count, then per index: name,
+//!                                     u64 count, string + u64 id)
+//! edge property bags                 (u64 count, then from, to, rel type, bag)
+//! ```
+//!
+//! Strings are a u32 byte length plus UTF-8. Both adjacency directions are
+//! stored in CSR order, so restoring copies them back into place instead of
+//! re-inserting every edge through the hash maps.
+
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use crate::error::GraphError;
+use crate::graph::{Edge, Graph, Neighbors, NodeId, NodeInfo, RelTypeId};
+use crate::props::{PropValue, PropertyBag};
+
+const MAGIC: &[u8; 8] = b"GACSNAP\0";
+
+/// Current snapshot format version. Snapshots written by another version
+/// are rejected rather than migrated.
+pub const SNAPSHOT_VERSION: u32 = 4;
+
+/// Upper bound on up-front allocation from a length read off the stream,
+/// so a corrupt count fails on EOF instead of aborting on allocation.
+const PREALLOC_LIMIT: usize = 1 << 20;
+
+/// Accessor for one adjacency direction.
+type Side = for<'a> fn(&'a Graph, NodeId) -> Neighbors<'a>;
+
+impl Graph {
+    /// Write the graph in the binary snapshot format (see `snapshot.rs`).
+    ///
+    /// Output is deterministic: nodes and index entries are written in ID
+    /// order. The writer is buffered internally.
+    pub fn write_snapshot<W: Write>(&self, writer: W) -> io::Result<()> {
+        self.write_snapshot_with(writer, true)
+    }
+
+    /// `write_snapshot`, or without `edges` every node written with degree
+    /// 0, for formats that store the adjacency themselves.
+    pub(crate) fn write_snapshot_with<W: Write>(&self, writer: W, edges: bool) -> io::Result<()> {
+        let mut w = BufWriter::new(writer);
+        w.write_all(MAGIC)?;
+        put_u32(&mut w, SNAPSHOT_VERSION)?;
+        w.write_all(&[self.is_frozen() as u8])?;
+        for table in [&self.rel_types, &self.labels, &self.prop_keys] {
+            put_u32(&mut w, table.len() as u32)?;
+            for name in table.iter() {
+                put_str(&mut w, name)?;
+            }
+        }
+
+        let ids = self.sorted_ids();
+        put_u64(&mut w, ids.len() as u64)?;
+        for &id in &ids {
+            put_u64(&mut w, id)?;
+            match self.nodes.get(&id) {
+                Some(info) => {
+                    w.write_all(&[1])?;
+                    put_node(&mut w, info)?;
+                }
+                None => w.write_all(&[0])?,
+            }
+        }
+
+        let sides: [Side; 2] = [Graph::neighbors_out, Graph::neighbors_in];
+        let none: Side = |_, _| Neighbors::default();
+        for side in sides.map(|side| if edges { side } else { none }) {
+            let total: usize = ids.iter().map(|&id| side(self, id).len()).sum();
+            put_u64(&mut w, total as u64)?;
+            for &id in &ids {
+                put_u64(&mut w, side(self, id).len() as u64)?;
+            }
+            for &id in &ids {
+                for edge in side(self, id) {
+                    put_edge(&mut w, &edge)?;
+                }
+            }
+        }
+
+        let mut app_ids: Vec<(&String, &NodeId)> = self.app_id_index.iter().collect();
+        app_ids.sort_unstable();
+        put_u64(&mut w, app_ids.len() as u64)?;
+        for (aid, &id) in app_ids {
+            put_str(&mut w, aid)?;
+            put_u64(&mut w, id)?;
+        }
+
+        let mut int_app_ids: Vec<(&i64, &NodeId)> = self.int_app_id_index.iter().collect();
+        int_app_ids.sort_unstable();
+        put_u64(&mut w, int_app_ids.len() as u64)?;
+        for (&aid, &id) in int_app_ids {
+            put_u64(&mut w, aid as u64)?;
+            put_u64(&mut w, id)?;
+        }
+
+        put_u32(&mut w, self.secondary_indices.len() as u32)?;
+        for name in self.index_names() {
+            let mut entries: Vec<_> = self.secondary_indices[name].iter().collect();
+            entries.sort_unstable();
+            put_str(&mut w, name)?;
+            put_u64(&mut w, entries.len() as u64)?;
+            for (value, &id) in entries {
+                put_str(&mut w, value)?;
+                put_u64(&mut w, id)?;
+            }
+        }
+
+        let mut edge_props: Vec<_> = self.edge_props.iter().collect();
+        edge_props.sort_unstable_by_key(|&(&slot, _)| slot);
+        put_u64(&mut w, edge_props.len() as u64)?;
+        for (&(from, to, rel_type), bag) in edge_props {
+            put_u64(&mut w, from)?;
+            put_u64(&mut w, to)?;
+            put_u16(&mut w, rel_type)?;
+            put_bag(&mut w, bag)?;
+        }
+
+        w.flush()
+    }
+
+    /// Restore a graph written by `write_snapshot()`.
+    ///
+    /// The graph comes back frozen or not, as it was written. Fails with
+    /// `InvalidData` on a bad magic number, another format version, or
+    /// IDs that do not match the snapshot's own tables; truncated input
+    /// fails with `UnexpectedEof`. The reader is buffered internally.
+    pub fn read_snapshot<R: Read>(reader: R) -> io::Result<Graph> {
+        let mut r = BufReader::new(reader);
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a graph_accel snapshot".to_string()));
+        }
+        let version = get_u32(&mut r)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(invalid(format!(
+                "snapshot version {} is not supported (expected {})",
+                version, SNAPSHOT_VERSION
+            )));
+        }
+        let frozen = get_u8(&mut r)? != 0;
+
+        let mut graph = Graph::new();
+        type Intern = fn(&mut Graph, &str) -> Result<u16, GraphError>;
+        let interners: [(Intern, &str); 3] = [
+            (Graph::try_intern_rel_type, "rel type"),
+            (Graph::try_intern_label, "label"),
+            (Graph::try_intern_prop_key, "property key"),
+        ];
+        for (intern, what) in interners {
+            let count = get_u32(&mut r)? as usize;
+            for i in 0..count {
+                let name = get_str(&mut r)?;
+                // A duplicate would shift every later ID
+                let id = intern(&mut graph, &name).map_err(|e| invalid(e.to_string()))?;
+                if id as usize != i {
+                    return Err(invalid(format!("duplicate {} '{}'", what, name)));
+                }
+            }
+        }
+        let limits = Limits {
+            rel_types: graph.rel_types.len(),
+            labels: graph.labels.len(),
+            prop_keys: graph.prop_keys.len(),
+        };
+
+        let node_count = get_len(&mut r)?;
+        if node_count >= u32::MAX as usize {
+            return Err(invalid("too many nodes for CSR index".to_string()));
+        }
+        let mut ids = Vec::with_capacity(node_count.min(PREALLOC_LIMIT));
+        for _ in 0..node_count {
+            let id = get_u64(&mut r)?;
+            if ids.last().is_some_and(|&prev| prev >= id) {
+                return Err(invalid(format!("node {} out of order", id)));
+            }
+            ids.push(id);
+            if get_u8(&mut r)? != 0 {
+                let info = get_node(&mut r, &limits)?;
+                graph.nodes.insert(id, info);
+            }
+        }
+
+        let outgoing = get_side(&mut r, ids.len(), &limits)?;
+        let incoming = get_side(&mut r, ids.len(), &limits)?;
+        if !graph.install_csr(ids, outgoing, incoming, frozen) {
+            return Err(invalid("edge target not in node table".to_string()));
+        }
+
+        let count = get_len(&mut r)?;
+        graph.app_id_index.reserve(count.min(PREALLOC_LIMIT));
+        for _ in 0..count {
+            let aid = get_str(&mut r)?;
+            let id = get_u64(&mut r)?;
+            graph.app_id_index.insert(aid, id);
+        }
+
+        let count = get_len(&mut r)?;
+        graph.int_app_id_index.reserve(count.min(PREALLOC_LIMIT));
+        for _ in 0..count {
+            let aid = get_u64(&mut r)? as i64;
+            let id = get_u64(&mut r)?;
+            graph.int_app_id_index.insert(aid, id);
+        }
+
+        let index_count = get_u32(&mut r)?;
+        for _ in 0..index_count {
+            let name = get_str(&mut r)?;
+            let count = get_len(&mut r)?;
+            let mut entries = HashMap::with_capacity(count.min(PREALLOC_LIMIT));
+            for _ in 0..count {
+                let value = get_str(&mut r)?;
+                let id = get_u64(&mut r)?;
+                entries.insert(value, id);
+            }
+            graph.secondary_indices.insert(name, entries);
+        }
+
+        let count = get_len(&mut r)?;
+        for _ in 0..count {
+            let from = get_u64(&mut r)?;
+            let to = get_u64(&mut r)?;
+            let rel_type = get_rel_type(&mut r, &limits)?;
+            let bag = get_bag(&mut r, &limits)?;
+            graph.edge_props.insert((from, to, rel_type), bag);
+        }
+
+        graph.rebuild_label_partitions();
+        Ok(graph)
+    }
+}
+
+/// Sizes of the interned tables, for validating IDs as they are read.
+struct Limits {
+    rel_types: usize,
+    labels: usize,
+    prop_keys: usize,
+}
+
+pub(crate) fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// --- Encoding ---
+
+fn put_u16(w: &mut impl Write, v: u16) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+pub(crate) fn put_u32(w: &mut impl Write, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+pub(crate) fn put_u64(w: &mut impl Write, v: u64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn put_str(w: &mut impl Write, s: &str) -> io::Result<()> {
+    let len = u32::try_from(s.len()).map_err(|_| invalid("string too long".to_string()))?;
+    put_u32(w, len)?;
+    w.write_all(s.as_bytes())
+}
+
+fn put_opt_str(w: &mut impl Write, s: Option<&str>) -> io::Result<()> {
+    match s {
+        Some(s) => {
+            w.write_all(&[1])?;
+            put_str(w, s)
+        }
+        None => w.write_all(&[0]),
+    }
+}
+
+fn put_node(w: &mut impl Write, info: &NodeInfo) -> io::Result<()> {
+    put_u16(w, info.label)?;
+    put_opt_str(w, info.app_id.as_deref())?;
+    put_u16(w, info.extra_labels.len() as u16)?;
+    for &label in info.extra_labels.iter() {
+        put_u16(w, label)?;
+    }
+    match info.properties.as_deref() {
+        Some(bag) => {
+            w.write_all(&[1])?;
+            put_bag(w, bag)
+        }
+        None => w.write_all(&[0]),
+    }
+}
+
+fn put_edge(w: &mut impl Write, e: &Edge) -> io::Result<()> {
+    put_u64(w, e.target)?;
+    put_u16(w, e.rel_type)?;
+    w.write_all(&e.confidence.to_le_bytes())?;
+    w.write_all(&e.weight.to_le_bytes())?;
+    put_u32(w, e.created_at)
+}
+
+fn put_bag(w: &mut impl Write, bag: &PropertyBag) -> io::Result<()> {
+    put_u32(w, bag.len() as u32)?;
+    for (key, value) in bag.iter() {
+        put_u16(w, key)?;
+        match value {
+            PropValue::Int(v) => {
+                w.write_all(&[0])?;
+                w.write_all(&v.to_le_bytes())?;
+            }
+            PropValue::Float(v) => {
+                w.write_all(&[1])?;
+                w.write_all(&v.to_le_bytes())?;
+            }
+            PropValue::Text(s) => {
+                w.write_all(&[2])?;
+                put_str(w, s)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// --- Decoding ---
+
+fn get_bytes<const N: usize>(r: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn get_u8(r: &mut impl Read) -> io::Result<u8> {
+    Ok(get_bytes::<1>(r)?[0])
+}
+
+fn get_u16(r: &mut impl Read) -> io::Result<u16> {
+    get_bytes(r).map(u16::from_le_bytes)
+}
+
+fn get_u32(r: &mut impl Read) -> io::Result<u32> {
+    get_bytes(r).map(u32::from_le_bytes)
+}
+
+fn get_u64(r: &mut impl Read) -> io::Result<u64> {
+    get_bytes(r).map(u64::from_le_bytes)
+}
+
+fn get_len(r: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(get_u64(r)?).map_err(|_| invalid("length exceeds address space".to_string()))
+}
+
+fn get_str(r: &mut impl Read) -> io::Result<String> {
+    let len = get_u32(r)? as usize;
+    let mut buf = Vec::with_capacity(len.min(PREALLOC_LIMIT));
+    r.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(buf).map_err(|_| invalid("string is not UTF-8".to_string()))
+}
+
+fn get_rel_type(r: &mut impl Read, limits: &Limits) -> io::Result<RelTypeId> {
+    let rel_type = get_u16(r)?;
+    if rel_type as usize >= limits.rel_types {
+        return Err(invalid(format!("unknown rel type id {}", rel_type)));
+    }
+    Ok(rel_type)
+}
+
+fn get_node(r: &mut impl Read, limits: &Limits) -> io::Result<NodeInfo> {
+    let label = get_u16(r)?;
+    if label as usize >= limits.labels {
+        return Err(invalid(format!("invalid label id {}", label)));
+    }
+    let app_id = match get_u8(r)? {
+        0 => None,
+        _ => Some(get_str(r)?),
+    };
+    let count = get_u16(r)? as usize;
+    let mut extra_labels = Vec::with_capacity(count);
+    for _ in 0..count {
+        let id = get_u16(r)?;
+        let ordered = extra_labels.last().is_none_or(|&prev| prev < id);
+        if !ordered || id as usize >= limits.labels {
+            return Err(invalid(format!("invalid extra label id {}", id)));
+        }
+        extra_labels.push(id);
+    }
+    let properties = match get_u8(r)? {
+        0 => None,
+        _ => Some(Box::new(get_bag(r, limits)?)),
+    };
+    Ok(NodeInfo {
+        label,
+        extra_labels: extra_labels.into_boxed_slice(),
+        app_id,
+        properties,
+    })
+}
+
+/// One adjacency direction: per-node degrees into offsets, then the edges.
+fn get_side(
+    r: &mut impl Read,
+    node_count: usize,
+    limits: &Limits,
+) -> io::Result<(Vec<usize>, Vec<Edge>)> {
+    let total = get_len(r)?;
+    let mut offsets = Vec::with_capacity((node_count + 1).min(PREALLOC_LIMIT));
+    offsets.push(0usize);
+    for _ in 0..node_count {
+        let end = offsets[offsets.len() - 1].checked_add(get_len(r)?);
+        match end {
+            Some(end) if end <= total => offsets.push(end),
+            _ => return Err(invalid("edge degrees exceed edge count".to_string())),
+        }
+    }
+    if offsets[node_count] != total {
+        return Err(invalid("edge degrees do not sum to edge count".to_string()));
+    }
+
+    let mut edges = Vec::with_capacity(total.min(PREALLOC_LIMIT));
+    for _ in 0..total {
+        let target = get_u64(r)?;
+        let rel_type = get_rel_type(r, limits)?;
+        edges.push(Edge {
+            target,
+            rel_type,
+            confidence: f32::from_le_bytes(get_bytes(r)?),
+            weight: f32::from_le_bytes(get_bytes(r)?),
+            created_at: get_u32(r)?,
+        });
+    }
+    Ok((offsets, edges))
+}
+
+fn get_bag(r: &mut impl Read, limits: &Limits) -> io::Result<PropertyBag> {
+    let count = get_u32(r)?;
+    let mut bag = PropertyBag::default();
+    for _ in 0..count {
+        let key = get_u16(r)?;
+        if key as usize >= limits.prop_keys {
+            return Err(invalid(format!("unknown property key id {}", key)));
+        }
+        let value = match get_u8(r)? {
+            0 => PropValue::Int(i64::from_le_bytes(get_bytes(r)?)),
+            1 => PropValue::Float(f64::from_le_bytes(get_bytes(r)?)),
+            2 => PropValue::Text(get_str(r)?),
+            tag => return Err(invalid(format!("unknown property type tag {}", tag))),
+        };
+        bag.insert(key, value);
+    }
+    Ok(bag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Direction, EdgeRecord};
+
+    fn edge(from: NodeId, to: NodeId, rel: &str) -> EdgeRecord {
+        EdgeRecord {
+            from_app_id: Some(format!("n{}", from)),
+            to_app_id: Some(format!("n{}", to)),
+            ..crate::test_util::edge(from, to, rel)
+        }
+    }
+
+    fn sample() -> Graph {
+        let mut rec = edge(1, 2, "CITES");
+        rec.confidence = 0.5;
+        rec.weight = 3.0;
+        rec.created_at = 1_700_000_000;
+        rec.properties = vec![(
+            "category".to_string(),
+            PropValue::Text("survey".to_string()),
+        )];
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            rec,
+            edge(2, 3, "IMPLIES"),
+            edge(3, 1, "CITES"),
+            edge(3, 3, "R"),
+        ]);
+        g.add_node_labels(2, ["Person"]);
+        g.set_node_properties(3, [("rank".to_string(), PropValue::Float(0.25))]);
+        g.index_node("slug", "second".to_string(), 2);
+        // An edge endpoint without node metadata
+        g.add_edge(1, 99, 0, 0.9);
+        g
+    }
+
+    fn snapshot(g: &Graph) -> Vec<u8> {
+        let mut buf = Vec::new();
+        g.write_snapshot(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        for frozen in [false, true] {
+            let mut g = sample();
+            if frozen {
+                g.freeze();
+            }
+            let bytes = snapshot(&g);
+            let back = Graph::read_snapshot(bytes.as_slice()).unwrap();
+            assert_eq!(snapshot(&back), bytes);
+
+            assert_eq!(back.is_frozen(), frozen);
+            assert_eq!((back.node_count(), back.edge_count()), (3, 5));
+            assert_eq!(back.resolve_app_id("n2"), Some(2));
+            assert_eq!(back.resolve_by("slug", "second"), Some(2));
+            assert_eq!(back.node_labels(2), vec!["Node", "Person"]);
+            let rank = back.node_property(3, "rank");
+            assert_eq!(rank.and_then(PropValue::as_f64), Some(0.25));
+            assert!(back.node(99).is_none());
+            assert_eq!(back.neighbors_in(99).len(), 1);
+
+            let e = back.neighbors_out(1).first().unwrap();
+            assert_eq!(back.rel_type_name(e.rel_type), Some("CITES"));
+            assert_eq!(
+                (e.confidence, e.weight, e.created_at()),
+                (0.5, 3.0, Some(1_700_000_000))
+            );
+            assert!(!back.neighbors_out(2).first().unwrap().has_confidence());
+            let category = back.edge_property(1, &e, Direction::Outgoing, "category");
+            assert_eq!(category.and_then(PropValue::as_str), Some("survey"));
+            let incoming: Vec<NodeId> = back.neighbors_in(3).iter().map(|e| e.target).collect();
+            assert_eq!(incoming, vec![2, 3]);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_rejects_bad_input() {
+        let bytes = snapshot(&sample());
+        let err = |data: &[u8]| Graph::read_snapshot(data).err().map(|e| e.kind());
+
+        assert_eq!(
+            err(b"not a snapshot at all"),
+            Some(io::ErrorKind::InvalidData)
+        );
+        let mut future = bytes.clone();
+        future[8] = 99;
+        assert_eq!(err(&future), Some(io::ErrorKind::InvalidData));
+        assert_eq!(
+            err(&bytes[..bytes.len() - 3]),
+            Some(io::ErrorKind::UnexpectedEof)
+        );
+        assert_eq!(err(&bytes[..40]), Some(io::ErrorKind::UnexpectedEof));
+
+        // Empty graphs round-trip too
+        let empty = Graph::read_snapshot(snapshot(&Graph::new()).as_slice()).unwrap();
+        assert_eq!((empty.node_count(), empty.edge_count()), (0, 0));
+    }
+}
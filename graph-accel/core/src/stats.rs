@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use crate::graph::{Graph, RelTypeId};
+
+/// Whole-graph summary, as returned by `Graph::stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    /// Nodes with metadata, as `Graph::node_count`.
+    pub node_count: usize,
+    /// Edges stored at those nodes. Equals `Graph::edge_count` unless edges
+    /// were added with `add_edge` from nodes never registered.
+    pub edge_count: usize,
+    /// `edge_count / (node_count * (node_count - 1))`, the share of ordered
+    /// node pairs joined by an edge. Parallel edges and self-loops count,
+    /// so a multigraph can exceed 1. 0.0 with fewer than 2 nodes.
+    pub density: f64,
+    /// Mean in + out degree, `2 * edge_count / node_count`.
+    pub avg_degree: f64,
+    /// Largest in + out degree of any node.
+    pub max_degree: usize,
+    /// Nodes with no edges in either direction.
+    pub isolated_nodes: usize,
+    /// Edge count per relationship type, most frequent first, then by name.
+    pub rel_types: Vec<(String, usize)>,
+}
+
+impl Graph {
+    /// Topology summary in one pass over the node table and each node's
+    /// edge lists: O(V + E), no allocation beyond one counter per rel type.
+    pub fn stats(&self) -> GraphStats {
+        let mut by_type: HashMap<RelTypeId, usize> = HashMap::new();
+        let (mut edge_count, mut max_degree, mut isolated_nodes) = (0, 0, 0);
+        for &id in self.nodes.keys() {
+            let out = self.neighbors_out(id);
+            let degree = out.len() + self.neighbors_in(id).len();
+            edge_count += out.len();
+            max_degree = max_degree.max(degree);
+            if degree == 0 {
+                isolated_nodes += 1;
+            }
+            for e in out {
+                *by_type.entry(e.rel_type).or_insert(0) += 1;
+            }
+        }
+
+        let n = self.node_count();
+        let density = if n < 2 {
+            0.0
+        } else {
+            edge_count as f64 / (n as f64 * (n - 1) as f64)
+        };
+        let avg_degree = if n == 0 {
+            0.0
+        } else {
+            2.0 * edge_count as f64 / n as f64
+        };
+
+        let mut rel_types: Vec<(String, usize)> = by_type
+            .into_iter()
+            .map(|(rt, count)| {
+                let name = self.rel_type_name(rt).unwrap_or("UNKNOWN");
+                (name.to_string(), count)
+            })
+            .collect();
+        rel_types.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        GraphStats {
+            node_count: n,
+            edge_count,
+            density,
+            avg_degree,
+            max_degree,
+            isolated_nodes,
+            rel_types,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::test_util::edge;
+
+    #[test]
+    fn test_stats_summarize_topology() {
+        let empty = Graph::new().stats();
+        assert_eq!(
+            (empty.node_count, empty.density, empty.avg_degree),
+            (0, 0.0, 0.0)
+        );
+
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "B"),
+            edge(1, 3, "A"),
+            edge(1, 4, "B"),
+            edge(2, 3, "A"),
+        ]);
+        g.add_node(5, "Node".to_string(), None);
+        g.freeze();
+
+        let s = g.stats();
+        assert_eq!((s.node_count, s.edge_count), (5, 4));
+        assert_eq!(s.density, 4.0 / 20.0);
+        assert_eq!(s.avg_degree, 8.0 / 5.0);
+        assert_eq!(s.max_degree, 3);
+        assert_eq!(s.isolated_nodes, 1);
+        assert_eq!(
+            s.rel_types,
+            vec![("A".to_string(), 2), ("B".to_string(), 2)]
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::cost::CostFn;
+use crate::graph::{Direction, Edge, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::interrupt;
+use crate::traversal::{iter_neighbors, HeapEntry, SubgraphEdge, SubgraphResult};
+
+/// A traversed hop: (from, to, rel_type, direction from → to).
+type Hop = (NodeId, NodeId, RelTypeId, Direction);
+
+/// Approximate Steiner tree connecting `terminals` (Kou–Markowsky–Berman).
+///
+/// 1. BFS from every terminal gives hop distances between terminals (the
+///    metric closure), limited to `max_hops`.
+/// 2. A minimum spanning tree of that closure picks which terminals to join.
+/// 3. Each closure edge is expanded back into a shortest path; the union of
+///    those paths is reduced to a spanning tree.
+/// 4. Non-terminal leaves are pruned repeatedly.
+///
+/// The result is within a factor 2 of the optimal tree's edge count. Edges
+/// are reported in stored orientation; `node_count` includes the terminals.
+/// Terminals that cannot reach each other within `max_hops` end up in
+/// separate trees of the returned forest. IDs not in the graph are ignored.
+pub fn steiner_tree(
+    graph: &Graph,
+    terminals: &[NodeId],
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> SubgraphResult {
+    steiner_tree_weighted(
+        graph,
+        terminals,
+        max_hops,
+        direction,
+        min_confidence,
+        Edge::hop_cost,
+    )
+}
+
+/// `steiner_tree` minimizing the sum of `cost` over the tree's edges rather
+/// than their count: the closure holds cheapest-path costs (Dijkstra, still
+/// within `max_hops`), and step 3 takes a minimum spanning forest of the
+/// union. Within a factor 2 of the optimal total cost.
+///
+/// `cost` is any `CostFn`; negative and NaN costs are treated as 0.
+pub fn steiner_tree_weighted<C: CostFn>(
+    graph: &Graph,
+    terminals: &[NodeId],
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    cost: C,
+) -> SubgraphResult {
+    let mut terms: Vec<NodeId> = terminals
+        .iter()
+        .copied()
+        .filter(|&t| graph.node(t).is_some())
+        .collect();
+    terms.sort_unstable();
+    terms.dedup();
+
+    // 1. Metric closure: parent pointers from each terminal
+    let trees: Vec<HashMap<NodeId, Reached>> = terms
+        .iter()
+        .map(|&t| cheapest_tree(graph, t, max_hops, direction, min_confidence, &cost))
+        .collect();
+
+    // 2. Prim's MST over terminals; unreachable pairs are never joined
+    let k = terms.len();
+    let mut in_tree = vec![false; k];
+    let mut best: Vec<Option<(f64, usize)>> = vec![None; k];
+    let mut closure_edges: Vec<(usize, usize)> = Vec::new();
+    for root in 0..k {
+        if in_tree[root] {
+            continue;
+        }
+        let mut next = Some(root);
+        while let Some(i) = next {
+            in_tree[i] = true;
+            if let Some((_, j)) = best[i] {
+                closure_edges.push((j, i));
+            }
+            for (j, slot) in best.iter_mut().enumerate() {
+                if in_tree[j] {
+                    continue;
+                }
+                if let Some(reached) = trees[i].get(&terms[j]) {
+                    if slot.is_none_or(|(bd, _)| reached.dist < bd) {
+                        *slot = Some((reached.dist, i));
+                    }
+                }
+            }
+            next = (0..k)
+                .filter(|&j| !in_tree[j])
+                .filter_map(|j| best[j].map(|(d, _)| (d, j)))
+                .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+                .map(|(_, j)| j);
+        }
+    }
+
+    // 3. Expand closure edges into graph paths and union them
+    let mut union: HashMap<(NodeId, NodeId), (Hop, f64)> = HashMap::new();
+    for &(i, j) in &closure_edges {
+        let mut current = terms[j];
+        while current != terms[i] {
+            let Reached { hop, hop_cost, .. } = trees[i][&current];
+            union
+                .entry((hop.0.min(hop.1), hop.0.max(hop.1)))
+                .or_insert((hop, hop_cost));
+            current = hop.0;
+        }
+    }
+
+    // Minimum spanning forest of the union (Kruskal), cheapest hops first
+    let mut hops: Vec<((NodeId, NodeId), (Hop, f64))> = union.into_iter().collect();
+    hops.sort_by(|(ka, (_, ca)), (kb, (_, cb))| ca.total_cmp(cb).then(ka.cmp(kb)));
+    let mut parent: HashMap<NodeId, NodeId> = HashMap::new();
+    let root = |parent: &HashMap<NodeId, NodeId>, mut n: NodeId| {
+        while let Some(&p) = parent.get(&n) {
+            n = p;
+        }
+        n
+    };
+    let mut tree: HashMap<(NodeId, NodeId), Hop> = HashMap::new();
+    for ((a, b), (hop, _)) in hops {
+        let (ra, rb) = (root(&parent, a), root(&parent, b));
+        if ra != rb {
+            parent.insert(ra, rb);
+            tree.insert((a, b), hop);
+        }
+    }
+
+    // 4. Prune non-terminal leaves until none remain
+    let term_set: HashSet<NodeId> = terms.iter().copied().collect();
+    let mut degree: HashMap<NodeId, usize> = HashMap::new();
+    for &(a, b) in tree.keys() {
+        *degree.entry(a).or_insert(0) += 1;
+        *degree.entry(b).or_insert(0) += 1;
+    }
+    let mut leaves: Vec<NodeId> = degree
+        .iter()
+        .filter(|&(n, &d)| d == 1 && !term_set.contains(n))
+        .map(|(&n, _)| n)
+        .collect();
+    while let Some(leaf) = leaves.pop() {
+        let Some(&(a, b)) = tree.keys().find(|&&(a, b)| a == leaf || b == leaf) else {
+            continue;
+        };
+        tree.remove(&(a, b));
+        let other = if a == leaf { b } else { a };
+        let d = degree.get_mut(&other).expect("endpoint has a degree");
+        *d -= 1;
+        if *d == 1 && !term_set.contains(&other) {
+            leaves.push(other);
+        }
+    }
+
+    let mut nodes: HashSet<NodeId> = term_set;
+    let mut edges: Vec<SubgraphEdge> = tree
+        .values()
+        .map(|&(from, to, rel_type, dir)| {
+            nodes.insert(from);
+            nodes.insert(to);
+            // Report the stored edge, not the traversal direction
+            let (from, to) = match dir {
+                Direction::Outgoing => (from, to),
+                Direction::Incoming => (to, from),
+            };
+            SubgraphEdge::new(graph, from, to, rel_type)
+        })
+        .collect();
+    edges.sort_by_key(|e| (e.from_id, e.to_id));
+
+    SubgraphResult {
+        node_count: nodes.len(),
+        edges,
+        truncated: false,
+    }
+}
+
+/// How a closure tree reached a node.
+#[derive(Clone, Copy)]
+struct Reached {
+    /// Cheapest path cost from the root.
+    dist: f64,
+    /// The last hop of that path, and its cost.
+    hop: Hop,
+    hop_cost: f64,
+}
+
+/// Dijkstra from `root` within `max_hops`: node → how it was reached.
+/// Nodes settle in (cost, hops, ID) order, so with unit costs this is a
+/// BFS tree.
+fn cheapest_tree<C: CostFn>(
+    graph: &Graph,
+    root: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    cost: &C,
+) -> HashMap<NodeId, Reached> {
+    let mut tree: HashMap<NodeId, Reached> = HashMap::new();
+    let start = Reached {
+        dist: 0.0,
+        hop: (root, root, 0, Direction::Outgoing),
+        hop_cost: 0.0,
+    };
+    tree.insert(root, start);
+    let mut settled: HashSet<NodeId> = HashSet::new();
+    let mut heap = BinaryHeap::from([HeapEntry {
+        cost: 0.0,
+        hops: 0,
+        node: root,
+    }]);
+    while let Some(HeapEntry { cost: d, hops, node: v }) = heap.pop() {
+        interrupt::check();
+        if !settled.insert(v) || hops >= max_hops {
+            continue;
+        }
+        for (edge, dir) in iter_neighbors(graph, v, direction, min_confidence) {
+            if settled.contains(&edge.target) {
+                continue;
+            }
+            let hop_cost = cost(&edge).max(0.0);
+            let reached = Reached {
+                dist: d + hop_cost,
+                hop: (v, edge.target, edge.rel_type, dir),
+                hop_cost,
+            };
+            if tree
+                .get(&edge.target)
+                .is_none_or(|known| reached.dist < known.dist)
+            {
+                tree.insert(edge.target, reached);
+                heap.push(HeapEntry {
+                    cost: reached.dist,
+                    hops: hops + 1,
+                    node: edge.target,
+                });
+            }
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeRecord;
+    use crate::test_util::edge;
+
+    fn pairs(r: &SubgraphResult) -> Vec<(NodeId, NodeId)> {
+        r.edges.iter().map(|e| (e.from_id, e.to_id)).collect()
+    }
+
+    #[test]
+    fn test_steiner_star_uses_hub() {
+        // Hub 0 with spokes to 1..=4, plus a long rim 1-5-6-2
+        let mut g = Graph::new();
+        g.load_edges((1..=4).map(|i| edge(0, i, "R")));
+        g.load_edges(vec![edge(1, 5, "R"), edge(5, 6, "R"), edge(6, 2, "R")]);
+
+        let r = steiner_tree(&g, &[1, 2, 3], 10, TraversalDirection::Both, None);
+        assert_eq!(r.node_count, 4);
+        assert_eq!(pairs(&r), vec![(0, 1), (0, 2), (0, 3)]);
+    }
+
+    #[test]
+    fn test_steiner_prunes_dead_ends_and_keeps_orientation() {
+        // Chain 0-1-2-3-4 stored as 0→1→2→3→4; connect 1 and 3
+        let mut g = Graph::new();
+        g.load_edges((0..4).map(|i| edge(i, i + 1, "R")));
+        let r = steiner_tree(&g, &[3, 1], 10, TraversalDirection::Both, None);
+        assert_eq!(r.node_count, 3);
+        assert_eq!(pairs(&r), vec![(1, 2), (2, 3)]);
+
+        // Single and empty terminal sets
+        let r = steiner_tree(&g, &[2, 2], 10, TraversalDirection::Both, None);
+        assert_eq!((r.node_count, r.edges.len()), (1, 0));
+        let r = steiner_tree(&g, &[999], 10, TraversalDirection::Both, None);
+        assert_eq!((r.node_count, r.edges.len()), (0, 0));
+    }
+
+    #[test]
+    fn test_steiner_disconnected_terminals_form_forest() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(1, 2, "R"),
+            edge(10, 11, "R"),
+            edge(11, 12, "R"),
+        ]);
+        let r = steiner_tree(&g, &[0, 2, 10, 12], 10, TraversalDirection::Both, None);
+        assert_eq!(r.node_count, 6);
+        assert_eq!(pairs(&r), vec![(0, 1), (1, 2), (10, 11), (11, 12)]);
+
+        // Hop limit too small to join 0 and 2
+        let r = steiner_tree(&g, &[0, 2], 1, TraversalDirection::Both, None);
+        assert!(r.edges.is_empty());
+        assert_eq!(r.node_count, 2);
+    }
+
+    #[test]
+    fn test_steiner_tree_is_acyclic() {
+        // 3x3 grid; connect the four corners
+        let mut g = Graph::new();
+        for r in 0..3 {
+            for c in 0..3 {
+                let id = r * 3 + c;
+                if c < 2 {
+                    g.load_edges(vec![edge(id, id + 1, "R")]);
+                }
+                if r < 2 {
+                    g.load_edges(vec![edge(id, id + 3, "R")]);
+                }
+            }
+        }
+        let r = steiner_tree(&g, &[0, 2, 6, 8], 10, TraversalDirection::Both, None);
+        assert_eq!(r.edges.len(), r.node_count - 1);
+        assert!(r.edges.len() <= 8);
+    }
+
+    #[test]
+    fn test_steiner_weighted_avoids_costly_hub() {
+        // Same star as above, but the spokes weigh more than the long rim
+        let mut g = Graph::new();
+        g.load_edges((1..=4).map(|i| EdgeRecord {
+            weight: 10.0,
+            ..edge(0, i, "R")
+        }));
+        g.load_edges(vec![edge(1, 5, "R"), edge(5, 6, "R"), edge(6, 2, "R")]);
+        let both = TraversalDirection::Both;
+
+        let r = steiner_tree_weighted(&g, &[1, 2], 10, both, None, Edge::weight_cost);
+        assert_eq!(pairs(&r), vec![(1, 5), (5, 6), (6, 2)]);
+        let r = steiner_tree_weighted(&g, &[1, 2], 10, both, None, Edge::hop_cost);
+        assert_eq!(pairs(&r), vec![(0, 1), (0, 2)]);
+    }
+}
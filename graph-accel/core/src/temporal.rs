@@ -0,0 +1,221 @@
+//! Time-respecting paths over edge creation times.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::{Direction, Edge, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::interrupt;
+use crate::traversal::{iter_neighbors, make_step, Exclusions, PathStep};
+
+/// A search state: a node reached at `time`, and how.
+struct Arrival {
+    node: NodeId,
+    time: u32,
+    /// Index of the previous arrival; the start's is its own
+    parent: usize,
+    via: (RelTypeId, Direction),
+}
+
+/// Fewest-hop path from `start` to `target` whose timed edges were created
+/// in non-decreasing order, as information could have flowed along it.
+///
+/// Each edge's `created_at` must be no earlier than that of the timed edge
+/// before it on the path. Edges without a timestamp fit anywhere and do not
+/// move the clock, so on an untimed graph the path is as short as
+/// `shortest_path`'s. Returns None if no such path exists within
+/// `max_hops`.
+pub fn time_respecting_path(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Option<Vec<PathStep>> {
+    time_respecting_path_by(
+        graph,
+        start,
+        target,
+        max_hops,
+        direction,
+        min_confidence,
+        &Exclusions::new(),
+        |_: &Edge| true,
+    )
+}
+
+/// `time_respecting_path` routed around `exclusions` along edges `step`
+/// accepts, as in `shortest_path_by`. Returns None if either endpoint is
+/// excluded.
+///
+/// A breadth-first search over (node, arrival time) states. A node may be
+/// reached again only at a strictly earlier time than before: a later or
+/// equal arrival, no sooner in hops, cannot continue any path the first
+/// could not. So each node holds at most a few states, and paths never
+/// revisit a node.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn time_respecting_path_by<S>(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    exclusions: &Exclusions,
+    step: S,
+) -> Option<Vec<PathStep>>
+where
+    S: Fn(&Edge) -> bool,
+{
+    if graph.node(start).is_none() || graph.node(target).is_none() {
+        return None;
+    }
+    if exclusions.excludes_node(start) || exclusions.excludes_node(target) {
+        return None;
+    }
+
+    if start == target {
+        return Some(vec![make_step(graph, start, None)]);
+    }
+
+    let mut arrivals = vec![Arrival {
+        node: start,
+        time: 0,
+        parent: 0,
+        via: (0, Direction::Outgoing),
+    }];
+    // Earliest arrival time per node so far
+    let mut earliest: HashMap<NodeId, u32> = HashMap::from([(start, 0)]);
+    let mut queue: VecDeque<(usize, u32)> = VecDeque::from([(0, 0)]);
+
+    while let Some((index, depth)) = queue.pop_front() {
+        interrupt::check();
+        if depth >= max_hops {
+            continue;
+        }
+        let (current, now) = (arrivals[index].node, arrivals[index].time);
+
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            if exclusions.blocks(current, &edge) || !step(&edge) {
+                continue;
+            }
+            let time = match edge.created_at() {
+                None => now,
+                Some(created) if created >= now => created,
+                Some(_) => continue,
+            };
+            if earliest.get(&edge.target).is_some_and(|&seen| seen <= time) {
+                continue;
+            }
+            earliest.insert(edge.target, time);
+            arrivals.push(Arrival {
+                node: edge.target,
+                time,
+                parent: index,
+                via: (edge.rel_type, dir),
+            });
+
+            if edge.target == target {
+                return Some(reconstruct(graph, &arrivals, arrivals.len() - 1));
+            }
+            queue.push_back((arrivals.len() - 1, depth + 1));
+        }
+    }
+
+    None
+}
+
+/// The path ending at `arrivals[last]`, start first.
+fn reconstruct(graph: &Graph, arrivals: &[Arrival], last: usize) -> Vec<PathStep> {
+    let mut path = Vec::new();
+    let mut index = last;
+    while index != 0 {
+        let arrival = &arrivals[index];
+        path.push(make_step(graph, arrival.node, Some(arrival.via)));
+        index = arrival.parent;
+    }
+    path.push(make_step(graph, arrivals[0].node, None));
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::EdgeRecord;
+
+    fn timed(from: NodeId, to: NodeId, created_at: u32) -> EdgeRecord {
+        EdgeRecord {
+            from_label: "N".to_string(),
+            to_label: "N".to_string(),
+            created_at,
+            ..EdgeRecord::new(from, to, "LINKS")
+        }
+    }
+
+    fn ids(path: Option<Vec<PathStep>>) -> Option<Vec<NodeId>> {
+        path.map(|p| p.iter().map(|s| s.node_id).collect())
+    }
+
+    #[test]
+    fn test_time_respecting_path_orders_edges() {
+        // Short route 1 → 2 → 3 runs backwards in time; the detour through
+        // 4 and 5 is in order, with an untimed hop 5 → 3
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            timed(1, 2, 200),
+            timed(2, 3, 100),
+            timed(1, 4, 100),
+            timed(4, 5, 150),
+            timed(5, 3, Edge::NO_TIMESTAMP),
+        ]);
+        let out = TraversalDirection::Outgoing;
+        for frozen in [false, true] {
+            if frozen {
+                g.freeze();
+            }
+            assert_eq!(
+                ids(crate::shortest_path(&g, 1, 3, 5, out, None)),
+                Some(vec![1, 2, 3])
+            );
+            assert_eq!(
+                ids(time_respecting_path(&g, 1, 3, 5, out, None)),
+                Some(vec![1, 4, 5, 3])
+            );
+            assert!(time_respecting_path(&g, 1, 3, 2, out, None).is_none());
+            assert!(time_respecting_path(&g, 3, 1, 5, out, None).is_none());
+            assert_eq!(
+                ids(time_respecting_path(&g, 2, 2, 0, out, None)),
+                Some(vec![2])
+            );
+        }
+    }
+
+    #[test]
+    fn test_time_respecting_path_revisits_earlier_arrivals() {
+        // 2 is first reached late (1 → 2 at 300), then earlier through 3
+        // (1 → 3 → 2 at 100, 150), and only the earlier arrival can go on
+        // to 4 at 200
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            timed(1, 2, 300),
+            timed(1, 3, 100),
+            timed(3, 2, 150),
+            timed(2, 4, 200),
+        ]);
+        let path = time_respecting_path(&g, 1, 4, 5, TraversalDirection::Outgoing, None);
+        let path = path.unwrap();
+        assert_eq!(
+            path.iter().map(|s| s.node_id).collect::<Vec<_>>(),
+            vec![1, 3, 2, 4]
+        );
+        assert_eq!(path[1].rel_type.as_deref(), Some("LINKS"));
+        assert_eq!(path[1].direction, Some(Direction::Outgoing));
+
+        let mut ex = Exclusions::new();
+        ex.exclude_node(3);
+        let around = g.view().with_exclusions(&ex);
+        assert!(around
+            .time_respecting_path(1, 4, 5, TraversalDirection::Outgoing)
+            .is_none());
+    }
+}
@@ -0,0 +1,12 @@
+//! Fixtures shared by the unit tests.
+
+use crate::graph::{EdgeRecord, NodeId};
+
+/// An edge between two `Node`-labelled vertices.
+pub(crate) fn edge(from: NodeId, to: NodeId, rel: &str) -> EdgeRecord {
+    EdgeRecord {
+        from_label: "Node".to_string(),
+        to_label: "Node".to_string(),
+        ..EdgeRecord::new(from, to, rel)
+    }
+}
@@ -1,6 +1,12 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::ops::{ControlFlow, Deref};
 
-use crate::graph::{Direction, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::cost::CostFn;
+use crate::frozen::FrozenGraph;
+use crate::graph::{DenseAdjacency, Direction, Edge, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::interrupt;
 
 /// A node found during BFS neighborhood traversal.
 #[derive(Debug, Clone)]
@@ -15,6 +21,22 @@ pub struct NeighborResult {
     pub path_directions: Vec<Direction>,
 }
 
+/// A node found during multi-source BFS, attributed to its nearest seed.
+#[derive(Debug, Clone)]
+pub struct SeededNeighborResult {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Seed this node is closest to.
+    pub seed: NodeId,
+    /// Hops from `seed`.
+    pub distance: u32,
+    /// Relationship types on one shortest path from `seed` to this node.
+    pub path_types: Vec<String>,
+    /// Traversal direction of each edge on the path (parallel to path_types).
+    pub path_directions: Vec<Direction>,
+}
+
 /// A single step in a shortest path.
 #[derive(Debug, Clone)]
 pub struct PathStep {
@@ -26,11 +48,21 @@ pub struct PathStep {
     pub direction: Option<Direction>,
 }
 
+/// A path with its cumulative edge cost.
+#[derive(Debug, Clone)]
+pub struct WeightedPath {
+    pub steps: Vec<PathStep>,
+    pub total_cost: f64,
+}
+
 /// Result of a traversal operation.
 #[derive(Debug)]
 pub struct TraversalResult {
     pub neighbors: Vec<NeighborResult>,
     pub nodes_visited: usize,
+    /// True if the `max_nodes` budget stopped expansion before every node
+    /// within `max_depth` was found.
+    pub truncated: bool,
 }
 
 /// A single edge in an extracted subgraph.
@@ -45,11 +77,44 @@ pub struct SubgraphEdge {
     pub rel_type: String,
 }
 
+impl SubgraphEdge {
+    /// Edge `from → to` (stored orientation), with endpoint metadata from `graph`.
+    pub(crate) fn new(graph: &Graph, from: NodeId, to: NodeId, rel_type: RelTypeId) -> Self {
+        let from_info = graph.node(from);
+        let to_info = graph.node(to);
+        SubgraphEdge {
+            from_id: from,
+            from_label: graph.node_label(from).unwrap_or_default().to_string(),
+            from_app_id: from_info.and_then(|n| n.app_id.clone()),
+            to_id: to,
+            to_label: graph.node_label(to).unwrap_or_default().to_string(),
+            to_app_id: to_info.and_then(|n| n.app_id.clone()),
+            rel_type: graph.rel_type_name(rel_type).unwrap_or("UNKNOWN").to_string(),
+        }
+    }
+}
+
 /// Result of subgraph extraction.
 #[derive(Debug)]
 pub struct SubgraphResult {
     pub node_count: usize,
     pub edges: Vec<SubgraphEdge>,
+    /// True if a `max_edges` budget cut discovery or the edge list short.
+    pub truncated: bool,
+}
+
+/// Summary statistics of the k-hop ball around a node.
+#[derive(Debug, Clone)]
+pub struct EgoStats {
+    /// Nodes in the ball, including the center.
+    pub node_count: usize,
+    /// Edges with both endpoints in the ball (the induced subgraph).
+    pub edge_count: usize,
+    /// Fraction of node pairs in the ball that are directly connected,
+    /// ignoring direction, rel type and self-loops; 0.0 when fewer than 2 nodes.
+    pub density: f64,
+    /// Edge count per relationship type, most frequent first, then by name.
+    pub rel_types: Vec<(String, usize)>,
 }
 
 /// Degree information for a single node.
@@ -63,38 +128,177 @@ pub struct DegreeResult {
     pub total_degree: u32,
 }
 
-/// Iterate neighbors according to a traversal direction filter and optional
-/// minimum confidence threshold.
+/// A node in a shortest-path tree, with a pointer to its parent.
+#[derive(Debug, Clone)]
+pub struct PathTreeNode {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    pub distance: u32,
+    /// Previous node on the shortest path from the root. None for the root.
+    pub parent: Option<NodeId>,
+    /// Relationship type of the edge from `parent`. None for the root.
+    pub rel_type: Option<String>,
+    /// Direction the edge from `parent` was traversed. None for the root.
+    pub direction: Option<Direction>,
+    /// True if this node is one of the requested targets.
+    pub is_target: bool,
+}
+
+/// Local clustering coefficient for a single node.
+#[derive(Debug, Clone)]
+pub struct ClusteringResult {
+    pub node_id: NodeId,
+    pub label: String,
+    pub app_id: Option<String>,
+    /// Distinct neighbors, ignoring edge direction and self-loops.
+    pub degree: u32,
+    /// Links among those neighbors (triangles through this node).
+    pub triangles: u32,
+    /// `triangles / (degree * (degree - 1) / 2)`; 0.0 when degree < 2.
+    pub coefficient: f64,
+}
+
+/// Nodes and edges a traversal must route around.
 ///
-/// Uses boolean flags to avoid Box/dyn dispatch — the compiler optimizes
-/// this into direct slice iteration with dead-code elimination.
+/// An excluded node is never entered. An excluded edge is identified by its
+/// two endpoints and blocks every edge between them, in either direction and
+/// of any relationship type. Once any relationship type is allowed with
+/// `allow_rel_type`, edges of every other type are excluded too.
+#[derive(Debug, Clone, Default)]
+pub struct Exclusions {
+    nodes: HashSet<NodeId>,
+    /// Endpoint pairs, stored with the smaller ID first.
+    edges: HashSet<(NodeId, NodeId)>,
+    /// Indexed by RelTypeId; None allows every type.
+    rel_types: Option<Vec<bool>>,
+}
+
+impl Exclusions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exclude_node(&mut self, id: NodeId) {
+        self.nodes.insert(id);
+    }
+
+    pub fn exclude_edge(&mut self, a: NodeId, b: NodeId) {
+        self.edges.insert((a.min(b), a.max(b)));
+    }
+
+    /// Follow edges of `rel_type`, and of other allowed types only.
+    pub fn allow_rel_type(&mut self, rel_type: RelTypeId) {
+        let allowed = self.rel_types.get_or_insert_with(Vec::new);
+        let index = rel_type as usize;
+        if allowed.len() <= index {
+            allowed.resize(index + 1, false);
+        }
+        allowed[index] = true;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty() && self.rel_types.is_none()
+    }
+
+    pub fn excludes_node(&self, id: NodeId) -> bool {
+        self.nodes.contains(&id)
+    }
+
+    /// False if `allow_rel_type` was used and not for `rel_type`.
+    pub(crate) fn allows_rel_type(&self, rel_type: RelTypeId) -> bool {
+        self.rel_types
+            .as_ref()
+            .is_none_or(|allowed| allowed.get(rel_type as usize) == Some(&true))
+    }
+
+    /// Keep only the allowed rel types that are in `rel_types`; with no
+    /// allow list yet, allow exactly those. An empty slice blocks every
+    /// edge.
+    pub(crate) fn restrict_rel_types(&mut self, rel_types: &[RelTypeId]) {
+        let narrowed = rel_types.iter().fold(Vec::new(), |mut allowed, &rt| {
+            if self.allows_rel_type(rt) {
+                if allowed.len() <= rt as usize {
+                    allowed.resize(rt as usize + 1, false);
+                }
+                allowed[rt as usize] = true;
+            }
+            allowed
+        });
+        self.rel_types = Some(narrowed);
+    }
+
+    /// Add the nodes and edges `other` excludes, and narrow the allowed
+    /// rel types to those `other` allows too.
+    pub(crate) fn merge(&mut self, other: &Exclusions) {
+        self.nodes.extend(&other.nodes);
+        self.edges.extend(&other.edges);
+        if let Some(allowed) = &other.rel_types {
+            let ids: Vec<RelTypeId> = (0..allowed.len())
+                .filter(|&i| allowed[i])
+                .map(|i| i as RelTypeId)
+                .collect();
+            self.restrict_rel_types(&ids);
+        }
+    }
+
+    /// True if the hop from `from` along `edge` is blocked, by its target,
+    /// its endpoints or its relationship type.
+    pub(crate) fn blocks(&self, from: NodeId, edge: &Edge) -> bool {
+        let to = edge.target;
+        !self.is_empty()
+            && (self.nodes.contains(&to)
+                || self.edges.contains(&(from.min(to), from.max(to)))
+                || !self.allows_rel_type(edge.rel_type))
+    }
+}
+
+/// Iterate neighbors according to a traversal direction filter and optional
+/// minimum confidence threshold: `Graph::edges_of` plus the filter, with no
+/// Box/dyn dispatch.
 ///
 /// Edges with NAN confidence (not loaded) always pass the filter — safe default.
-fn iter_neighbors<'a>(
+pub(crate) fn iter_neighbors<'a>(
     graph: &'a Graph,
     node: NodeId,
     dir: TraversalDirection,
     min_confidence: Option<f32>,
-) -> impl Iterator<Item = (&'a crate::graph::Edge, Direction)> {
+) -> impl Iterator<Item = (crate::graph::Edge, Direction)> + 'a {
+    graph
+        .edges_of(node, dir)
+        .filter(move |(e, _)| match min_confidence {
+            None => true,
+            Some(min) => !e.has_confidence() || e.confidence >= min,
+        })
+}
+
+/// `iter_neighbors` over a frozen graph's dense index: yields each neighbor's
+/// dense index alongside the edge, in the same order and with the same filter.
+pub(crate) fn iter_dense_neighbors<'a>(
+    dense: DenseAdjacency<'a>,
+    node: u32,
+    dir: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> impl Iterator<Item = (u32, crate::graph::Edge, Direction)> + 'a {
     let (use_out, use_inc) = match dir {
         TraversalDirection::Outgoing => (true, false),
         TraversalDirection::Incoming => (false, true),
         TraversalDirection::Both => (true, true),
     };
 
-    let out_iter = graph
-        .neighbors_out(node)
-        .iter()
-        .map(|e| (e, Direction::Outgoing))
-        .filter(move |_| use_out);
+    let out_iter = use_out
+        .then(|| dense.out(node))
+        .into_iter()
+        .flatten()
+        .map(|(t, e)| (t, e, Direction::Outgoing));
 
-    let in_iter = graph
-        .neighbors_in(node)
-        .iter()
-        .map(|e| (e, Direction::Incoming))
-        .filter(move |_| use_inc);
+    let in_iter = use_inc
+        .then(|| dense.inc(node))
+        .into_iter()
+        .flatten()
+        .map(|(t, e)| (t, e, Direction::Incoming));
 
-    out_iter.chain(in_iter).filter(move |(e, _)| {
+    out_iter.chain(in_iter).filter(move |(_, e, _)| {
         match min_confidence {
             None => true,
             Some(min) => !e.has_confidence() || e.confidence >= min,
@@ -102,11 +306,272 @@ fn iter_neighbors<'a>(
     })
 }
 
+/// Visited set over a frozen graph's dense node indices: one bit per node,
+/// so membership is a shift and mask instead of a hash probe.
+pub(crate) struct DenseBits {
+    words: Vec<u64>,
+}
+
+impl DenseBits {
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub(crate) fn contains(&self, idx: u32) -> bool {
+        self.words[idx as usize / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// Set the bit; true if it was clear.
+    pub(crate) fn insert(&mut self, idx: u32) -> bool {
+        let word = &mut self.words[idx as usize / 64];
+        let bit = 1 << (idx % 64);
+        let fresh = *word & bit == 0;
+        *word |= bit;
+        fresh
+    }
+
+    pub(crate) fn remove(&mut self, idx: u32) {
+        self.words[idx as usize / 64] &= !(1 << (idx % 64));
+    }
+}
+
+/// A node discovered by `dense_bfs`. `parent` is the position of the node
+/// it was reached from in the same discovery list (0, the start, for the
+/// start itself).
+#[derive(Clone, Copy)]
+pub(crate) struct DenseHit {
+    pub(crate) node: u32,
+    pub(crate) depth: u32,
+    pub(crate) parent: u32,
+    pub(crate) rel_type: RelTypeId,
+    pub(crate) dir: Direction,
+}
+
+impl DenseHit {
+    pub(crate) fn start(node: u32) -> Self {
+        Self {
+            node,
+            depth: 0,
+            parent: 0,
+            rel_type: 0,
+            dir: Direction::Outgoing,
+        }
+    }
+}
+
+/// BFS over dense indices shared by the frozen-graph paths of
+/// `bfs_neighborhood_by` and `shortest_path_by`.
+///
+/// Returns every discovered node in BFS order, the starts first, with
+/// parent pointers as positions in that list, plus whether the search
+/// stopped early: on reaching a node in `targets` (then the last hit) or
+/// on finding a node past `budget`. Exclusions and `step` apply as in the
+/// hashed paths, so results match them.
+#[allow(clippy::too_many_arguments)]
+fn dense_bfs<S>(
+    dense: DenseAdjacency<'_>,
+    starts: &[u32],
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    exclusions: &Exclusions,
+    step: S,
+    budget: usize,
+    targets: Option<&DenseBits>,
+) -> (Vec<DenseHit>, bool)
+where
+    S: Fn(&Edge) -> bool,
+{
+    let mut search = DenseSearch::new(dense, starts);
+    let stopped = loop {
+        let expanded = search.expand_next(
+            dense,
+            max_depth,
+            direction,
+            min_confidence,
+            exclusions,
+            &step,
+            budget,
+            targets,
+        );
+        if let Some(stopped) = expanded {
+            break stopped;
+        }
+    };
+    (search.hits, stopped)
+}
+
+/// State of a `dense_bfs`, advanced one expanded node at a time so a
+/// stream can search only as far as its reader gets.
+struct DenseSearch {
+    seen: DenseBits,
+    /// Discovered nodes in BFS order; doubles as the queue
+    hits: Vec<DenseHit>,
+    /// Position in `hits` of the next node to expand
+    head: usize,
+}
+
+impl DenseSearch {
+    fn new(dense: DenseAdjacency<'_>, starts: &[u32]) -> Self {
+        let mut seen = DenseBits::new(dense.len());
+        let hits = starts
+            .iter()
+            .filter(|&&start| seen.insert(start))
+            .map(|&start| DenseHit::start(start))
+            .collect();
+        Self {
+            seen,
+            hits,
+            head: 0,
+        }
+    }
+
+    /// A search with nothing to expand, for a missing or excluded start.
+    fn empty() -> Self {
+        Self {
+            seen: DenseBits::new(0),
+            hits: Vec::new(),
+            head: 0,
+        }
+    }
+
+    /// Expand the next queued node. None while the search goes on;
+    /// otherwise it is over, and the flag says whether it ended early, as
+    /// `dense_bfs` reports it.
+    #[allow(clippy::too_many_arguments)]
+    fn expand_next<S>(
+        &mut self,
+        dense: DenseAdjacency<'_>,
+        max_depth: u32,
+        direction: TraversalDirection,
+        min_confidence: Option<f32>,
+        exclusions: &Exclusions,
+        step: &S,
+        budget: usize,
+        targets: Option<&DenseBits>,
+    ) -> Option<bool>
+    where
+        S: Fn(&Edge) -> bool,
+    {
+        let Some(&hit) = self.hits.get(self.head) else {
+            return Some(false);
+        };
+        interrupt::check();
+        // A deadline leaves a neighborhood partial; a path search runs on
+        if targets.is_none() && interrupt::out_of_time() {
+            return Some(true);
+        }
+        let parent = self.head as u32;
+        self.head += 1;
+        if hit.depth >= max_depth {
+            return None;
+        }
+        let current_id = dense.node_id(hit.node);
+        for (next, edge, dir) in iter_dense_neighbors(dense, hit.node, direction, min_confidence) {
+            if self.seen.contains(next) || exclusions.blocks(current_id, &edge) {
+                continue;
+            }
+            if !step(&edge) {
+                continue;
+            }
+            if self.hits.len() > budget {
+                return Some(true);
+            }
+            self.seen.insert(next);
+            self.hits.push(DenseHit {
+                node: next,
+                depth: hit.depth + 1,
+                parent,
+                rel_type: edge.rel_type,
+                dir,
+            });
+            if targets.is_some_and(|t| t.contains(next)) {
+                return Some(true);
+            }
+        }
+        None
+    }
+}
+
+/// `bfs_neighborhood` results for the hits at `positions`, which must not
+/// include the start.
+pub(crate) fn dense_neighbors(
+    graph: &Graph,
+    dense: DenseAdjacency<'_>,
+    hits: &[DenseHit],
+    positions: std::ops::Range<usize>,
+) -> Vec<NeighborResult> {
+    positions
+        .map(|pos| dense_neighbor(graph, dense, hits, pos))
+        .collect()
+}
+
+/// The `bfs_neighborhood` result for `hits[pos]`.
+fn dense_neighbor(
+    graph: &Graph,
+    dense: DenseAdjacency<'_>,
+    hits: &[DenseHit],
+    pos: usize,
+) -> NeighborResult {
+    let id = dense.node_id(hits[pos].node);
+    let mut path: Vec<&DenseHit> = dense_path(hits, pos).collect();
+    path.reverse();
+    NeighborResult {
+        node_id: id,
+        label: graph.node_label(id).unwrap_or_default().to_string(),
+        app_id: graph.node(id).and_then(|n| n.app_id.clone()),
+        distance: hits[pos].depth,
+        path_types: path
+            .iter()
+            .filter_map(|h| graph.rel_type_name(h.rel_type))
+            .map(str::to_string)
+            .collect(),
+        path_directions: path.iter().map(|h| h.dir).collect(),
+    }
+}
+
+/// Hits on the path from `hits[pos]` back to its start, excluding the start.
+fn dense_path(hits: &[DenseHit], mut pos: usize) -> impl Iterator<Item = &DenseHit> {
+    std::iter::from_fn(move || {
+        (hits[pos].depth != 0).then(|| {
+            let hit = &hits[pos];
+            pos = hit.parent as usize;
+            hit
+        })
+    })
+}
+
+/// The path from its start to `hits[pos]`, as steps.
+fn dense_steps(
+    graph: &Graph,
+    dense: DenseAdjacency<'_>,
+    hits: &[DenseHit],
+    pos: usize,
+) -> Vec<PathStep> {
+    let mut root = pos;
+    let mut path: Vec<PathStep> = dense_path(hits, pos)
+        .map(|h| {
+            root = h.parent as usize;
+            make_step(graph, dense.node_id(h.node), Some((h.rel_type, h.dir)))
+        })
+        .collect();
+    path.push(make_step(graph, dense.node_id(hits[root].node), None));
+    path.reverse();
+    path
+}
+
 /// BFS neighborhood: find all nodes reachable from `start` within `max_depth` hops.
 ///
 /// `direction` controls which edges to follow: `Both` for undirected,
 /// `Outgoing` for forward-only, `Incoming` for reverse-only.
 ///
+/// `max_nodes` caps the number of neighbors returned (0 = no limit). Once the
+/// budget is hit expansion stops and `truncated` is set; because nodes are
+/// found in BFS order, the kept nodes are the nearest ones and their
+/// distances are exact.
+///
 /// Uses visited-set pruning — each node is visited at most once, at its
 /// minimum distance. Stores parent pointers instead of cloning path Vecs
 /// at each node — paths are reconstructed lazily during result collection.
@@ -116,11 +581,70 @@ pub fn bfs_neighborhood(
     max_depth: u32,
     direction: TraversalDirection,
     min_confidence: Option<f32>,
+    max_nodes: usize,
 ) -> TraversalResult {
-    if graph.node(start).is_none() {
+    bfs_neighborhood_by(
+        graph,
+        start,
+        max_depth,
+        direction,
+        min_confidence,
+        max_nodes,
+        &Exclusions::new(),
+        |_: &Edge| true,
+    )
+}
+
+/// BFS neighborhood following only the edges `step` accepts.
+///
+/// `step` sees each candidate edge as stored at the node being expanded,
+/// so `target` is the node it leads to; a rejected edge is not taken, but
+/// its target may still be reached another way. Excluded nodes are
+/// neither reported nor expanded, so nodes reachable only through them are
+/// not found, and an excluded `start` yields an empty result. This is the
+/// search behind `GraphView::bfs_neighborhood`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn bfs_neighborhood_by<S>(
+    graph: &Graph,
+    start: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    max_nodes: usize,
+    exclusions: &Exclusions,
+    step: S,
+) -> TraversalResult
+where
+    S: Fn(&Edge) -> bool,
+{
+    if graph.node(start).is_none() || exclusions.excludes_node(start) {
         return TraversalResult {
             neighbors: Vec::new(),
             nodes_visited: 0,
+            truncated: false,
+        };
+    }
+
+    let budget = if max_nodes == 0 { usize::MAX } else { max_nodes };
+    if let Some(dense) = graph.dense() {
+        let start = dense
+            .index_of(start)
+            .expect("nodes with metadata are indexed");
+        let (hits, truncated) = dense_bfs(
+            dense,
+            &[start],
+            max_depth,
+            direction,
+            min_confidence,
+            exclusions,
+            step,
+            budget,
+            None,
+        );
+        return TraversalResult {
+            neighbors: dense_neighbors(graph, dense, &hits, 1..hits.len()),
+            nodes_visited: hits.len(),
+            truncated,
         };
     }
 
@@ -128,18 +652,36 @@ pub fn bfs_neighborhood(
     // Start node uses itself as parent with dummy rel_type and direction.
     let mut visited: HashMap<NodeId, (u32, NodeId, RelTypeId, Direction)> = HashMap::new();
     let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
+    let mut found = 0;
+    let mut truncated = false;
 
     visited.insert(start, (0, start, 0, Direction::Outgoing));
     queue.push_back((start, 0));
 
-    while let Some((current, depth)) = queue.pop_front() {
+    'bfs: while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if interrupt::out_of_time() {
+            truncated = true;
+            break;
+        }
         if depth >= max_depth {
             continue;
         }
 
         for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
-            if !visited.contains_key(&edge.target) {
-                visited.insert(edge.target, (depth + 1, current, edge.rel_type, dir));
+            if exclusions.blocks(current, &edge) {
+                continue;
+            }
+            if let Entry::Vacant(slot) = visited.entry(edge.target) {
+                if !step(&edge) {
+                    continue;
+                }
+                if found == budget {
+                    truncated = true;
+                    break 'bfs;
+                }
+                found += 1;
+                slot.insert((depth + 1, current, edge.rel_type, dir));
                 queue.push_back((edge.target, depth + 1));
             }
         }
@@ -156,7 +698,7 @@ pub fn bfs_neighborhood(
             let (path_types, path_directions) = reconstruct_path(graph, &visited, start, id);
             NeighborResult {
                 node_id: id,
-                label: info.map(|n| n.label.clone()).unwrap_or_default(),
+                label: graph.node_label(id).unwrap_or_default().to_string(),
                 app_id: info.and_then(|n| n.app_id.clone()),
                 distance,
                 path_types,
@@ -168,142 +710,1568 @@ pub fn bfs_neighborhood(
     TraversalResult {
         neighbors,
         nodes_visited,
+        truncated,
     }
 }
 
-/// Walk parent pointers from `node` back to `start`, collecting rel_type names and directions.
-fn reconstruct_path(
-    graph: &Graph,
-    visited: &HashMap<NodeId, (u32, NodeId, RelTypeId, Direction)>,
+/// `GraphView::bfs_neighborhood` over a frozen graph handle (`&FrozenGraph`,
+/// `Arc<FrozenGraph>`), searching and building rows as they are read.
+///
+/// Nothing is traversed here: each call to `next` expands the frontier
+/// just far enough to produce one more row, in the same BFS order as
+/// `bfs_neighborhood`, and only then materializes its label, app ID and
+/// path. A reader that stops early never visits the rest of the
+/// neighborhood. A deadline set with `with_deadline` around this call
+/// still bounds the expansion done while reading. The stream owns
+/// `graph`, `node_filter` and a copy of `exclusions`, so it can outlive
+/// the borrows it was created under.
+#[allow(clippy::too_many_arguments)]
+pub fn bfs_neighborhood_stream<G, F>(
+    graph: G,
     start: NodeId,
-    node: NodeId,
-) -> (Vec<String>, Vec<Direction>) {
-    let mut types = Vec::new();
-    let mut directions = Vec::new();
-    let mut current = node;
-
-    while current != start {
-        let &(_, parent, rel_type, dir) = &visited[&current];
-        if let Some(name) = graph.rel_type_name(rel_type) {
-            types.push(name.to_string());
-        }
-        directions.push(dir);
-        current = parent;
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    max_nodes: usize,
+    exclusions: &Exclusions,
+    node_filter: F,
+) -> NeighborhoodStream<G, F>
+where
+    G: Deref<Target = FrozenGraph>,
+    F: Fn(NodeId) -> bool,
+{
+    let g: &Graph = &graph;
+    let search = if g.node(start).is_none() || exclusions.excludes_node(start) {
+        DenseSearch::empty()
+    } else {
+        let dense = g.dense().expect("frozen graphs are CSR");
+        let start = dense
+            .index_of(start)
+            .expect("nodes with metadata are indexed");
+        DenseSearch::new(dense, &[start])
+    };
+    let budget = if max_nodes == 0 {
+        usize::MAX
+    } else {
+        max_nodes
+    };
+    NeighborhoodStream {
+        graph,
+        search,
+        max_depth,
+        direction,
+        min_confidence,
+        exclusions: exclusions.clone(),
+        node_filter,
+        budget,
+        deadline: interrupt::current_deadline(),
+        keep: None,
+        as_of: None,
+        next: 1,
+        finished: false,
+        truncated: false,
     }
-
-    types.reverse();
-    directions.reverse();
-    (types, directions)
 }
 
-/// Shortest path from `start` to `target` using BFS (unweighted).
-///
-/// `direction` controls which edges to follow: `Both` for undirected,
-/// `Outgoing` for forward-only, `Incoming` for reverse-only.
-///
-/// Returns None if no path exists within `max_hops`, or if either node
-/// is not in the graph.
-/// Returns the path as a sequence of steps including both endpoints.
-pub fn shortest_path(
-    graph: &Graph,
-    start: NodeId,
-    target: NodeId,
-    max_hops: u32,
+/// Rows of a `bfs_neighborhood_stream`, searched for and built on demand.
+pub struct NeighborhoodStream<G, F> {
+    graph: G,
+    search: DenseSearch,
+    max_depth: u32,
     direction: TraversalDirection,
     min_confidence: Option<f32>,
-) -> Option<Vec<PathStep>> {
-    if graph.node(start).is_none() || graph.node(target).is_none() {
-        return None;
-    }
+    exclusions: Exclusions,
+    node_filter: F,
+    budget: usize,
+    deadline: Option<std::time::Instant>,
+    /// Row filter from `retain_nodes`
+    keep: Option<Box<dyn Fn(NodeId) -> bool>>,
+    /// Edge cutoff from `as_of`
+    as_of: Option<u32>,
+    /// Position in the search's hits of the next row; the start (0) is
+    /// never one
+    next: usize,
+    /// The search has ended, so every remaining row is already found
+    finished: bool,
+    truncated: bool,
+}
 
-    if start == target {
-        let info = graph.node(start);
-        return Some(vec![PathStep {
-            node_id: start,
-            label: info.map(|n| n.label.clone()).unwrap_or_default(),
-            app_id: info.and_then(|n| n.app_id.clone()),
-            rel_type: None,
-            direction: None,
-        }]);
+impl<G, F> NeighborhoodStream<G, F> {
+    /// True if `max_nodes` or a deadline has stopped expansion early.
+    ///
+    /// The search runs as rows are read, so this is only final once the
+    /// stream is exhausted. It is settled before the row that uses up
+    /// `max_nodes` is returned, so that row already reports it.
+    pub fn truncated(&self) -> bool {
+        self.truncated
     }
 
-    if max_hops == 0 {
-        return None;
+    /// Nodes discovered so far, the start included.
+    pub fn nodes_visited(&self) -> usize {
+        self.search.hits.len()
     }
 
-    // BFS with parent tracking: node → (parent, rel_type, direction)
-    let mut visited: HashMap<NodeId, (NodeId, RelTypeId, Direction)> = HashMap::new();
-    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
+    /// Return only the rows whose node passes `keep`. Traversal is not
+    /// affected: dropped nodes are still expanded, and the remaining
+    /// rows' paths may pass through them.
+    pub fn retain_nodes<K: Fn(NodeId) -> bool + 'static>(mut self, keep: K) -> Self {
+        self.keep = Some(Box::new(keep));
+        self
+    }
 
-    // Sentinel: start node's parent is itself
-    visited.insert(start, (start, 0, Direction::Outgoing));
-    queue.push_back((start, 0));
+    /// Traverse the graph as it was at `as_of` (seconds since the Unix
+    /// epoch): edges created later are not taken. Edges without a
+    /// timestamp still are. Call before reading any row.
+    pub fn as_of(mut self, as_of: u32) -> Self {
+        self.as_of = Some(as_of);
+        self
+    }
+}
 
-    while let Some((current, depth)) = queue.pop_front() {
-        if depth >= max_hops {
-            continue;
+impl<G, F> NeighborhoodStream<G, F>
+where
+    G: Deref<Target = FrozenGraph>,
+    F: Fn(NodeId) -> bool,
+{
+    /// Expand until the row at `pos` is found or the search ends. At the
+    /// row that exhausts the budget, keep going until it is clear whether
+    /// anything was cut off; that takes at most one more discovery.
+    fn search_to(&mut self, pos: usize) {
+        let graph: &Graph = &self.graph;
+        let dense = graph.dense().expect("frozen graphs are CSR");
+        let node_filter = &self.node_filter;
+        let as_of = self.as_of;
+        let step = |e: &Edge| as_of.is_none_or(|t| e.existed_at(t)) && node_filter(e.target);
+        let search = &mut self.search;
+        let (stopped, _) = interrupt::with_deadline(self.deadline, || {
+            while search.hits.len() <= pos || pos == self.budget {
+                let expanded = search.expand_next(
+                    dense,
+                    self.max_depth,
+                    self.direction,
+                    self.min_confidence,
+                    &self.exclusions,
+                    &step,
+                    self.budget,
+                    None,
+                );
+                if expanded.is_some() {
+                    return expanded;
+                }
+            }
+            None
+        });
+        if let Some(stopped) = stopped {
+            self.finished = true;
+            self.truncated = stopped;
         }
+    }
+}
 
-        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
-            if !visited.contains_key(&edge.target) {
-                visited.insert(edge.target, (current, edge.rel_type, dir));
-
-                if edge.target == target {
-                    return Some(reconstruct_sp_path(graph, &visited, start, target));
+impl<G, F> Iterator for NeighborhoodStream<G, F>
+where
+    G: Deref<Target = FrozenGraph>,
+    F: Fn(NodeId) -> bool,
+{
+    type Item = NeighborResult;
+
+    fn next(&mut self) -> Option<NeighborResult> {
+        loop {
+            let pos = self.next;
+            if !self.finished && (pos >= self.search.hits.len() || pos == self.budget) {
+                self.search_to(pos);
+            }
+            let hit = self.search.hits.get(pos)?;
+            self.next += 1;
+            let graph: &Graph = &self.graph;
+            let dense = graph.dense().expect("frozen graphs are CSR");
+            if let Some(keep) = &self.keep {
+                if !keep(dense.node_id(hit.node)) {
+                    continue;
                 }
-
-                queue.push_back((edge.target, depth + 1));
             }
+            return Some(dense_neighbor(graph, dense, &self.search.hits, pos));
         }
     }
-
-    None
 }
 
-fn reconstruct_sp_path(
+/// Hop distance from `start` to every node within `max_depth`, including
+/// `start` itself at distance 0.
+///
+/// The same BFS as `bfs_neighborhood` without parent pointers or path
+/// reconstruction, for callers that only need distances (layout, heatmaps).
+/// Results are in BFS order, so distances are non-decreasing.
+///
+/// On a frozen graph the visited set and queue are indexed by dense node
+/// number instead of hashed by ID; the output is identical.
+pub fn bfs_distances(
     graph: &Graph,
-    visited: &HashMap<NodeId, (NodeId, RelTypeId, Direction)>,
     start: NodeId,
-    target: NodeId,
-) -> Vec<PathStep> {
-    let mut path = Vec::new();
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<(NodeId, u32)> {
+    if graph.node(start).is_none() {
+        return Vec::new();
+    }
+    if let Some(dense) = graph.dense() {
+        let start = dense
+            .index_of(start)
+            .expect("nodes with metadata are indexed");
+        return dense_bfs_distances(dense, start, max_depth, direction, min_confidence);
+    }
+
+    let mut seen: HashSet<NodeId> = HashSet::new();
+    let mut order: Vec<(NodeId, u32)> = Vec::new();
+    seen.insert(start);
+    order.push((start, 0));
+
+    // `order` doubles as the BFS queue
+    let mut head = 0;
+    while head < order.len() {
+        interrupt::check();
+        let (current, depth) = order[head];
+        head += 1;
+        if depth >= max_depth {
+            continue;
+        }
+        for (edge, _) in iter_neighbors(graph, current, direction, min_confidence) {
+            if seen.insert(edge.target) {
+                order.push((edge.target, depth + 1));
+            }
+        }
+    }
+    order
+}
+
+fn dense_bfs_distances(
+    dense: DenseAdjacency<'_>,
+    start: u32,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<(NodeId, u32)> {
+    let mut seen = DenseBits::new(dense.len());
+    let mut order: Vec<(u32, u32)> = vec![(start, 0)];
+    seen.insert(start);
+
+    let mut head = 0;
+    while head < order.len() {
+        interrupt::check();
+        let (current, depth) = order[head];
+        head += 1;
+        if depth >= max_depth {
+            continue;
+        }
+        for (next, _, _) in iter_dense_neighbors(dense, current, direction, min_confidence) {
+            if seen.insert(next) {
+                order.push((next, depth + 1));
+            }
+        }
+    }
+    order
+        .into_iter()
+        .map(|(idx, depth)| (dense.node_id(idx), depth))
+        .collect()
+}
+
+/// True if `to` is within `max_depth` hops of `from` (a node reaches
+/// itself), for existence checks that need no path.
+pub fn path_exists(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> bool {
+    hop_distance(graph, from, to, max_depth, direction, min_confidence).is_some()
+}
+
+/// Hops on a shortest path from `from` to `to`, or None if `to` is not
+/// within `max_depth` hops. 0 when `from == to`.
+///
+/// A BFS that stops as soon as it discovers `to`, without parent pointers
+/// or rows. On a frozen graph the visited set is a bitset over dense node
+/// numbers. None if either node is not in the graph.
+pub fn hop_distance(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Option<u32> {
+    hop_distance_by(
+        graph,
+        from,
+        to,
+        max_depth,
+        direction,
+        min_confidence,
+        &Exclusions::new(),
+        |_| true,
+    )
+}
+
+/// `hop_distance` routed around `exclusions` along the edges `step`
+/// accepts, as in `bfs_neighborhood_by`. None if either end is excluded.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn hop_distance_by<S>(
+    graph: &Graph,
+    from: NodeId,
+    to: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    exclusions: &Exclusions,
+    step: S,
+) -> Option<u32>
+where
+    S: Fn(&Edge) -> bool,
+{
+    graph.node(from)?;
+    graph.node(to)?;
+    if exclusions.excludes_node(from) || exclusions.excludes_node(to) {
+        return None;
+    }
+    if from == to {
+        return Some(0);
+    }
+    if let Some(dense) = graph.dense() {
+        let index = |id| dense.index_of(id).expect("nodes with metadata are indexed");
+        let (from, to) = (index(from), index(to));
+        let mut seen = DenseBits::new(dense.len());
+        let mut queue: VecDeque<(u32, u32)> = VecDeque::from([(from, 0)]);
+        seen.insert(from);
+        while let Some((current, depth)) = queue.pop_front() {
+            interrupt::check();
+            if depth >= max_depth {
+                continue;
+            }
+            for (next, edge, _) in iter_dense_neighbors(dense, current, direction, min_confidence) {
+                if exclusions.blocks(dense.node_id(current), &edge) || !step(&edge) {
+                    continue;
+                }
+                if next == to {
+                    return Some(depth + 1);
+                }
+                if seen.insert(next) {
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+        return None;
+    }
+
+    let mut seen: HashSet<NodeId> = HashSet::from([from]);
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::from([(from, 0)]);
+    while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if depth >= max_depth {
+            continue;
+        }
+        for (edge, _) in iter_neighbors(graph, current, direction, min_confidence) {
+            if exclusions.blocks(current, &edge) || !step(&edge) {
+                continue;
+            }
+            if edge.target == to {
+                return Some(depth + 1);
+            }
+            if seen.insert(edge.target) {
+                queue.push_back((edge.target, depth + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Low-level BFS that hands each node to `visit` as it is discovered, for
+/// custom searches without forking `bfs_neighborhood`.
+///
+/// `visit` receives the node, its hop distance and how it was reached:
+/// None for `start`, otherwise the parent, the edge as stored at the parent
+/// and the side it was stored on. Nodes arrive in BFS order, each once.
+/// Returning `ControlFlow::Break` stops the search and is passed back to
+/// the caller; `Continue` goes on, expanding the node unless it is at
+/// `max_depth`. An unknown `start` visits nothing. On a frozen graph the
+/// visited set is a bitset over dense node numbers.
+pub fn bfs_visit<B, F>(
+    graph: &Graph,
+    start: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    mut visit: F,
+) -> ControlFlow<B>
+where
+    F: FnMut(NodeId, u32, Option<(NodeId, &Edge, Direction)>) -> ControlFlow<B>,
+{
+    if graph.node(start).is_none() {
+        return ControlFlow::Continue(());
+    }
+    visit(start, 0, None)?;
+
+    if let Some(dense) = graph.dense() {
+        let start = dense
+            .index_of(start)
+            .expect("nodes with metadata are indexed");
+        let mut seen = DenseBits::new(dense.len());
+        seen.insert(start);
+        let mut queue: VecDeque<(u32, u32)> = VecDeque::from([(start, 0)]);
+        while let Some((current, depth)) = queue.pop_front() {
+            interrupt::check();
+            if depth >= max_depth {
+                continue;
+            }
+            let current_id = dense.node_id(current);
+            for (next, edge, dir) in iter_dense_neighbors(dense, current, direction, min_confidence)
+            {
+                if seen.insert(next) {
+                    visit(edge.target, depth + 1, Some((current_id, &edge, dir)))?;
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+        return ControlFlow::Continue(());
+    }
+
+    let mut seen: HashSet<NodeId> = HashSet::from([start]);
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::from([(start, 0)]);
+    while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if depth >= max_depth {
+            continue;
+        }
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            if seen.insert(edge.target) {
+                visit(edge.target, depth + 1, Some((current, &edge, dir)))?;
+                queue.push_back((edge.target, depth + 1));
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// Multi-source BFS: find all nodes within `max_depth` hops of any seed in
+/// `starts`, each attributed to the seed it is closest to.
+///
+/// All seeds enter the queue at distance 0, so the whole set is covered in
+/// one traversal instead of one BFS per seed. Ties between equidistant seeds
+/// go to whichever the BFS reaches first, which favors seeds listed earlier.
+/// Seeds not in the graph are ignored; seeds themselves are not returned,
+/// even when within reach of another seed.
+///
+/// Results are sorted by distance, then node ID.
+pub fn bfs_multi_source(
+    graph: &Graph,
+    starts: &[NodeId],
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<SeededNeighborResult> {
+    if let Some(dense) = graph.dense() {
+        return dense_multi_source(graph, dense, starts, max_depth, direction, min_confidence);
+    }
+
+    // Same parent-pointer layout as bfs_neighborhood, plus the owning seed
+    let mut visited: HashMap<NodeId, (u32, NodeId, RelTypeId, Direction)> = HashMap::new();
+    let mut seed_of: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
+
+    for &s in starts {
+        if graph.node(s).is_some() {
+            if let Entry::Vacant(slot) = visited.entry(s) {
+                slot.insert((0, s, 0, Direction::Outgoing));
+                seed_of.insert(s, s);
+                queue.push_back((s, 0));
+            }
+        }
+    }
+
+    while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if depth >= max_depth {
+            continue;
+        }
+        let seed = seed_of[&current];
+
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            if let Entry::Vacant(slot) = visited.entry(edge.target) {
+                slot.insert((depth + 1, current, edge.rel_type, dir));
+                seed_of.insert(edge.target, seed);
+                queue.push_back((edge.target, depth + 1));
+            }
+        }
+    }
+
+    let mut neighbors: Vec<SeededNeighborResult> = visited
+        .iter()
+        .filter(|&(_, &(distance, _, _, _))| distance > 0)
+        .map(|(&id, &(distance, _, _, _))| {
+            let info = graph.node(id);
+            let seed = seed_of[&id];
+            let (path_types, path_directions) = reconstruct_path(graph, &visited, seed, id);
+            SeededNeighborResult {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                seed,
+                distance,
+                path_types,
+                path_directions,
+            }
+        })
+        .collect();
+
+    neighbors.sort_by_key(|n| (n.distance, n.node_id));
+    neighbors
+}
+
+/// `bfs_multi_source` over a frozen graph's dense index: one hit list for
+/// all seeds, each seed's own hit at depth 0.
+fn dense_multi_source(
+    graph: &Graph,
+    dense: DenseAdjacency<'_>,
+    starts: &[NodeId],
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<SeededNeighborResult> {
+    let mut seen = DenseBits::new(dense.len());
+    let mut hits: Vec<DenseHit> = Vec::new();
+    for &s in starts {
+        if graph.node(s).is_some() {
+            let s = dense.index_of(s).expect("nodes with metadata are indexed");
+            if seen.insert(s) {
+                hits.push(DenseHit::start(s));
+            }
+        }
+    }
+
+    // `hits` doubles as the BFS queue
+    let mut head = 0;
+    while head < hits.len() {
+        interrupt::check();
+        let at = hits[head];
+        let parent = head as u32;
+        head += 1;
+        if at.depth >= max_depth {
+            continue;
+        }
+        for (next, edge, dir) in iter_dense_neighbors(dense, at.node, direction, min_confidence) {
+            if seen.insert(next) {
+                hits.push(DenseHit {
+                    node: next,
+                    depth: at.depth + 1,
+                    parent,
+                    rel_type: edge.rel_type,
+                    dir,
+                });
+            }
+        }
+    }
+
+    // A hit's seed is its parent's; parents come first
+    let mut seed_pos: Vec<usize> = Vec::with_capacity(hits.len());
+    for (pos, hit) in hits.iter().enumerate() {
+        let seed = if hit.depth == 0 {
+            pos
+        } else {
+            seed_pos[hit.parent as usize]
+        };
+        seed_pos.push(seed);
+    }
+
+    let mut neighbors: Vec<SeededNeighborResult> = (0..hits.len())
+        .filter(|&pos| hits[pos].depth > 0)
+        .map(|pos| {
+            let id = dense.node_id(hits[pos].node);
+            let mut path: Vec<&DenseHit> = dense_path(&hits, pos).collect();
+            path.reverse();
+            SeededNeighborResult {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: graph.node(id).and_then(|n| n.app_id.clone()),
+                seed: dense.node_id(hits[seed_pos[pos]].node),
+                distance: hits[pos].depth,
+                path_types: path
+                    .iter()
+                    .filter_map(|h| graph.rel_type_name(h.rel_type))
+                    .map(str::to_string)
+                    .collect(),
+                path_directions: path.iter().map(|h| h.dir).collect(),
+            }
+        })
+        .collect();
+
+    neighbors.sort_by_key(|n| (n.distance, n.node_id));
+    neighbors
+}
+
+/// Hop distance between every ordered pair of distinct `nodes` within
+/// `max_depth` of each other, as `(from, to, distance)` sorted by `from`
+/// then `to`.
+///
+/// A bit-parallel multi-source BFS: up to 64 sources share each pass, one
+/// bit per source in every visited node's mask, so a set of N nodes costs
+/// about N/64 traversals instead of N² path searches. A pass ends once
+/// each of its sources has reached every node of the set. Pairs farther
+/// apart than `max_depth` are omitted, as are nodes not in the graph;
+/// repeated nodes count once. On a frozen graph the masks live in arrays
+/// indexed by dense node number.
+pub fn pairwise_distances(
+    graph: &Graph,
+    nodes: &[NodeId],
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<(NodeId, NodeId, u32)> {
+    let mut members: HashSet<NodeId> = HashSet::new();
+    let sources: Vec<NodeId> = nodes
+        .iter()
+        .copied()
+        .filter(|&id| graph.node(id).is_some() && members.insert(id))
+        .collect();
+    if let Some(dense) = graph.dense() {
+        return dense_pairwise_distances(dense, &sources, max_depth, direction, min_confidence);
+    }
+
+    let mut pairs = Vec::new();
+    for batch in sources.chunks(64) {
+        // Bit i of a mask stands for batch[i]
+        let mut seen: HashMap<NodeId, u64> = HashMap::new();
+        let mut frontier: Vec<(NodeId, u64)> = batch
+            .iter()
+            .enumerate()
+            .map(|(bit, &id)| (id, 1 << bit))
+            .collect();
+        let mut missing = batch.len() * members.len();
+        let mut depth = 0;
+        loop {
+            for &(node, fresh) in &frontier {
+                *seen.entry(node).or_default() |= fresh;
+                if !members.contains(&node) {
+                    continue;
+                }
+                missing -= fresh.count_ones() as usize;
+                let mut bits = fresh;
+                while bits != 0 {
+                    let from = batch[bits.trailing_zeros() as usize];
+                    bits &= bits - 1;
+                    if from != node {
+                        pairs.push((from, node, depth));
+                    }
+                }
+            }
+            if missing == 0 || depth >= max_depth {
+                break;
+            }
+
+            let mut next: HashMap<NodeId, u64> = HashMap::new();
+            for &(node, mask) in &frontier {
+                interrupt::check();
+                for (edge, _) in iter_neighbors(graph, node, direction, min_confidence) {
+                    let known = seen.get(&edge.target).copied().unwrap_or(0);
+                    let fresh = mask & !known & !next.get(&edge.target).copied().unwrap_or(0);
+                    if fresh != 0 {
+                        *next.entry(edge.target).or_default() |= fresh;
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            frontier = next.into_iter().collect();
+            depth += 1;
+        }
+    }
+
+    pairs.sort_unstable();
+    pairs
+}
+
+/// `pairwise_distances` for the distinct, known `sources`, with the masks
+/// of a pass in arrays indexed by dense node number.
+fn dense_pairwise_distances(
+    dense: DenseAdjacency<'_>,
+    sources: &[NodeId],
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<(NodeId, NodeId, u32)> {
+    let index = |id| dense.index_of(id).expect("nodes with metadata are indexed");
+    let mut members = DenseBits::new(dense.len());
+    for &id in sources {
+        members.insert(index(id));
+    }
+
+    let mut pairs = Vec::new();
+    let mut seen: Vec<u64> = vec![0; dense.len()];
+    let mut next: Vec<u64> = vec![0; dense.len()];
+    for batch in sources.chunks(64) {
+        // Nodes with a bit in `seen`, to clear before the next pass
+        let mut touched: Vec<u32> = Vec::new();
+        let mut frontier: Vec<(u32, u64)> = batch
+            .iter()
+            .enumerate()
+            .map(|(bit, &id)| (index(id), 1 << bit))
+            .collect();
+        let mut missing = batch.len() * sources.len();
+        let mut depth = 0;
+        loop {
+            for &(node, fresh) in &frontier {
+                let mask = &mut seen[node as usize];
+                if *mask == 0 {
+                    touched.push(node);
+                }
+                *mask |= fresh;
+                if !members.contains(node) {
+                    continue;
+                }
+                missing -= fresh.count_ones() as usize;
+                let to = dense.node_id(node);
+                let mut bits = fresh;
+                while bits != 0 {
+                    let from = batch[bits.trailing_zeros() as usize];
+                    bits &= bits - 1;
+                    if from != to {
+                        pairs.push((from, to, depth));
+                    }
+                }
+            }
+            if missing == 0 || depth >= max_depth {
+                break;
+            }
+
+            let mut reached: Vec<u32> = Vec::new();
+            for &(node, mask) in &frontier {
+                interrupt::check();
+                for (target, _, _) in iter_dense_neighbors(dense, node, direction, min_confidence) {
+                    let slot = &mut next[target as usize];
+                    let fresh = mask & !seen[target as usize] & !*slot;
+                    if fresh != 0 {
+                        if *slot == 0 {
+                            reached.push(target);
+                        }
+                        *slot |= fresh;
+                    }
+                }
+            }
+            if reached.is_empty() {
+                break;
+            }
+            frontier = reached
+                .into_iter()
+                .map(|node| (node, std::mem::take(&mut next[node as usize])))
+                .collect();
+            depth += 1;
+        }
+        for node in touched {
+            seen[node as usize] = 0;
+        }
+    }
+
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Walk parent pointers from `node` back to `start`, collecting rel_type names and directions.
+fn reconstruct_path(
+    graph: &Graph,
+    visited: &HashMap<NodeId, (u32, NodeId, RelTypeId, Direction)>,
+    start: NodeId,
+    node: NodeId,
+) -> (Vec<String>, Vec<Direction>) {
+    let mut types = Vec::new();
+    let mut directions = Vec::new();
+    let mut current = node;
+
+    while current != start {
+        let &(_, parent, rel_type, dir) = &visited[&current];
+        if let Some(name) = graph.rel_type_name(rel_type) {
+            types.push(name.to_string());
+        }
+        directions.push(dir);
+        current = parent;
+    }
+
+    types.reverse();
+    directions.reverse();
+    (types, directions)
+}
+
+/// Shortest path from `start` to `target` using BFS (unweighted).
+///
+/// `direction` controls which edges to follow: `Both` for undirected,
+/// `Outgoing` for forward-only, `Incoming` for reverse-only.
+///
+/// Returns None if no path exists within `max_hops`, or if either node
+/// is not in the graph.
+/// Returns the path as a sequence of steps including both endpoints.
+pub fn shortest_path(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Option<Vec<PathStep>> {
+    shortest_path_by(
+        graph,
+        start,
+        target,
+        max_hops,
+        direction,
+        min_confidence,
+        &Exclusions::new(),
+        |_: &Edge| true,
+    )
+}
+
+/// Shortest path from `start` to whichever node in `targets` is closest.
+///
+/// A single BFS that stops at the first target reached, so the cost depends
+/// on the distance to the nearest target rather than the size of the set.
+/// The chosen target is the last step of the returned path; among
+/// equidistant targets the one found first in BFS order wins.
+///
+/// Targets not in the graph are ignored. Returns a single-step path if
+/// `start` is itself a target, and None if no target is within `max_hops`.
+/// On a frozen graph the visited and target sets are bitsets over dense
+/// node numbers.
+pub fn shortest_path_to_any(
+    graph: &Graph,
+    start: NodeId,
+    targets: &[NodeId],
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Option<Vec<PathStep>> {
+    graph.node(start)?;
+    let target_set: HashSet<NodeId> = targets
+        .iter()
+        .copied()
+        .filter(|&t| graph.node(t).is_some())
+        .collect();
+    if target_set.is_empty() {
+        return None;
+    }
+    if target_set.contains(&start) {
+        return Some(vec![make_step(graph, start, None)]);
+    }
+    if let Some(dense) = graph.dense() {
+        let index = |id| dense.index_of(id).expect("nodes with metadata are indexed");
+        let mut goal = DenseBits::new(dense.len());
+        for &t in &target_set {
+            goal.insert(index(t));
+        }
+        let (hits, found) = dense_bfs(
+            dense,
+            &[index(start)],
+            max_hops,
+            direction,
+            min_confidence,
+            &Exclusions::new(),
+            |_: &Edge| true,
+            usize::MAX,
+            Some(&goal),
+        );
+        return found.then(|| dense_steps(graph, dense, &hits, hits.len() - 1));
+    }
+
+    let mut visited: HashMap<NodeId, (NodeId, RelTypeId, Direction)> = HashMap::new();
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
+
+    visited.insert(start, (start, 0, Direction::Outgoing));
+    queue.push_back((start, 0));
+
+    while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if depth >= max_hops {
+            continue;
+        }
+
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            if let Entry::Vacant(slot) = visited.entry(edge.target) {
+                slot.insert((current, edge.rel_type, dir));
+
+                if target_set.contains(&edge.target) {
+                    return Some(reconstruct_sp_path(graph, &visited, start, edge.target));
+                }
+
+                queue.push_back((edge.target, depth + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Shortest paths from `start` to every node in `targets` in a single BFS.
+///
+/// Returns the shortest-path tree pruned to the branches that lead to a
+/// reached target: each path is recovered by following `parent` pointers
+/// from a target back to the root. Nodes are ordered by distance, then ID.
+/// The BFS stops as soon as every target has been reached, so the cost is
+/// one traversal instead of one per target.
+///
+/// Targets that are not in the graph or not reachable within `max_hops` are
+/// absent from the result. Returns an empty Vec if `start` is not in the graph.
+/// On a frozen graph the search runs over dense node numbers, with the same
+/// result.
+pub fn shortest_path_tree(
+    graph: &Graph,
+    start: NodeId,
+    targets: &[NodeId],
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<PathTreeNode> {
+    if graph.node(start).is_none() {
+        return Vec::new();
+    }
+
+    let target_set: HashSet<NodeId> = targets
+        .iter()
+        .copied()
+        .filter(|&t| graph.node(t).is_some())
+        .collect();
+    if let Some(dense) = graph.dense() {
+        return dense_path_tree(
+            graph,
+            dense,
+            start,
+            &target_set,
+            max_hops,
+            direction,
+            min_confidence,
+        );
+    }
+    let mut remaining = target_set.len();
+
+    // visited maps node → (distance, parent_node, edge_rel_type, direction)
+    let mut visited: HashMap<NodeId, (u32, NodeId, RelTypeId, Direction)> = HashMap::new();
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
+
+    visited.insert(start, (0, start, 0, Direction::Outgoing));
+    queue.push_back((start, 0));
+    if target_set.contains(&start) {
+        remaining -= 1;
+    }
+
+    'bfs: while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if remaining == 0 {
+            break;
+        }
+        if depth >= max_hops {
+            continue;
+        }
+
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            if let Entry::Vacant(slot) = visited.entry(edge.target) {
+                slot.insert((depth + 1, current, edge.rel_type, dir));
+                if target_set.contains(&edge.target) {
+                    remaining -= 1;
+                    if remaining == 0 {
+                        break 'bfs;
+                    }
+                }
+                queue.push_back((edge.target, depth + 1));
+            }
+        }
+    }
+
+    // Keep only nodes on a path to a reached target
+    let mut keep: HashSet<NodeId> = HashSet::new();
+    for &t in &target_set {
+        if !visited.contains_key(&t) {
+            continue;
+        }
+        let mut current = t;
+        while keep.insert(current) && current != start {
+            current = visited[&current].1;
+        }
+    }
+
+    let mut tree: Vec<PathTreeNode> = keep
+        .into_iter()
+        .map(|id| {
+            let &(distance, parent, rel_type, dir) = &visited[&id];
+            let info = graph.node(id);
+            let is_root = id == start;
+            PathTreeNode {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: info.and_then(|n| n.app_id.clone()),
+                distance,
+                parent: if is_root { None } else { Some(parent) },
+                rel_type: if is_root {
+                    None
+                } else {
+                    graph.rel_type_name(rel_type).map(|s| s.to_string())
+                },
+                direction: if is_root { None } else { Some(dir) },
+                is_target: target_set.contains(&id),
+            }
+        })
+        .collect();
+
+    tree.sort_by(|a, b| a.distance.cmp(&b.distance).then(a.node_id.cmp(&b.node_id)));
+    tree
+}
+
+/// `shortest_path_tree` over a frozen graph's dense index, for targets
+/// already filtered to known nodes.
+fn dense_path_tree(
+    graph: &Graph,
+    dense: DenseAdjacency<'_>,
+    start: NodeId,
+    target_set: &HashSet<NodeId>,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<PathTreeNode> {
+    let index = |id| dense.index_of(id).expect("nodes with metadata are indexed");
+    let mut goal = DenseBits::new(dense.len());
+    for &t in target_set {
+        goal.insert(index(t));
+    }
+    let start = index(start);
+    let mut remaining = target_set.len() - usize::from(goal.contains(start));
+
+    let mut seen = DenseBits::new(dense.len());
+    seen.insert(start);
+    // `hits` doubles as the BFS queue
+    let mut hits = vec![DenseHit::start(start)];
+    let mut head = 0;
+    'bfs: while head < hits.len() {
+        interrupt::check();
+        if remaining == 0 {
+            break;
+        }
+        let at = hits[head];
+        let parent = head as u32;
+        head += 1;
+        if at.depth >= max_hops {
+            continue;
+        }
+        for (next, edge, dir) in iter_dense_neighbors(dense, at.node, direction, min_confidence) {
+            if seen.insert(next) {
+                hits.push(DenseHit {
+                    node: next,
+                    depth: at.depth + 1,
+                    parent,
+                    rel_type: edge.rel_type,
+                    dir,
+                });
+                if goal.contains(next) {
+                    remaining -= 1;
+                    if remaining == 0 {
+                        break 'bfs;
+                    }
+                }
+            }
+        }
+    }
+
+    // Keep only hits on a path to a reached target, by position
+    let mut keep = DenseBits::new(hits.len());
+    for pos in 0..hits.len() {
+        if !goal.contains(hits[pos].node) {
+            continue;
+        }
+        let mut current = pos;
+        while keep.insert(current as u32) && current != 0 {
+            current = hits[current].parent as usize;
+        }
+    }
+
+    let mut tree: Vec<PathTreeNode> = (0..hits.len())
+        .filter(|&pos| keep.contains(pos as u32))
+        .map(|pos| {
+            let hit = hits[pos];
+            let id = dense.node_id(hit.node);
+            let is_root = pos == 0;
+            PathTreeNode {
+                node_id: id,
+                label: graph.node_label(id).unwrap_or_default().to_string(),
+                app_id: graph.node(id).and_then(|n| n.app_id.clone()),
+                distance: hit.depth,
+                parent: (!is_root).then(|| dense.node_id(hits[hit.parent as usize].node)),
+                rel_type: if is_root {
+                    None
+                } else {
+                    graph.rel_type_name(hit.rel_type).map(|s| s.to_string())
+                },
+                direction: (!is_root).then_some(hit.dir),
+                is_target: goal.contains(hit.node),
+            }
+        })
+        .collect();
+
+    tree.sort_by(|a, b| a.distance.cmp(&b.distance).then(a.node_id.cmp(&b.node_id)));
+    tree
+}
+
+fn reconstruct_sp_path(
+    graph: &Graph,
+    visited: &HashMap<NodeId, (NodeId, RelTypeId, Direction)>,
+    start: NodeId,
+    target: NodeId,
+) -> Vec<PathStep> {
+    let mut path = Vec::new();
     let mut current = target;
 
-    loop {
-        let info = graph.node(current);
-        let &(parent, rel_type, dir) = &visited[&current];
+    loop {
+        let info = graph.node(current);
+        let &(parent, rel_type, dir) = &visited[&current];
+
+        path.push(PathStep {
+            node_id: current,
+            label: graph.node_label(current).unwrap_or_default().to_string(),
+            app_id: info.and_then(|n| n.app_id.clone()),
+            rel_type: if current == start {
+                None
+            } else {
+                graph.rel_type_name(rel_type).map(|s| s.to_string())
+            },
+            direction: if current == start { None } else { Some(dir) },
+        });
+
+        if current == start {
+            break;
+        }
+        current = parent;
+    }
+
+    path.reverse();
+    path
+}
+
+/// Find up to `k` shortest simple paths between two nodes using Yen's algorithm.
+///
+/// Returns paths sorted by hop count (shortest first). Each path is loop-free.
+/// Uses BFS as the inner pathfinding step, with node and edge exclusion sets
+/// to force alternative routes at each spur node.
+///
+/// Complexity: O(k * L * (V + E)) where L is the longest path length.
+/// For typical use (k=5, L~4, 1K nodes / 400K edges) this runs in microseconds.
+pub fn k_shortest_paths(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    k: usize,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<Vec<PathStep>> {
+    k_shortest_paths_by(
+        graph,
+        start,
+        target,
+        max_hops,
+        k,
+        direction,
+        min_confidence,
+        &Exclusions::new(),
+        &|_: &Edge| true,
+    )
+}
+
+/// `k_shortest_paths` whose paths all route around `base` along edges
+/// `step` accepts, as in `bfs_neighborhood_by`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn k_shortest_paths_by<S>(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    k: usize,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    base: &Exclusions,
+    step: &S,
+) -> Vec<Vec<PathStep>>
+where
+    S: Fn(&Edge) -> bool,
+{
+    if k == 0 {
+        return Vec::new();
+    }
+
+    // A[0]: first shortest path via standard BFS
+    let first = match shortest_path_by(
+        graph,
+        start,
+        target,
+        max_hops,
+        direction,
+        min_confidence,
+        base,
+        step,
+    ) {
+        Some(path) => path,
+        None => return Vec::new(),
+    };
+
+    let mut result: Vec<Vec<PathStep>> = vec![first];
+    // Candidate pool: paths found but not yet selected, sorted by length when picking
+    let mut candidates: Vec<Vec<PathStep>> = Vec::new();
+
+    for ki in 1..k {
+        // Out of time: the paths found so far
+        if interrupt::out_of_time() {
+            break;
+        }
+        let prev_path = &result[ki - 1];
+
+        // For each spur node in the previous path (skip the last — no edge to deviate from)
+        for spur_idx in 0..prev_path.len().saturating_sub(1) {
+            let spur_node = prev_path[spur_idx].node_id;
+
+            // Root path: start → spur_node (spur_idx hops)
+            let root_path: Vec<PathStep> = prev_path[..=spur_idx].to_vec();
+            let root_ids: Vec<NodeId> = root_path.iter().map(|s| s.node_id).collect();
+
+            // Exclude edges leaving the spur node that are used by paths sharing this root
+            let mut exclusions = base.clone();
+            for path in &result {
+                if path.len() > spur_idx
+                    && path[..=spur_idx]
+                        .iter()
+                        .map(|s| s.node_id)
+                        .eq(root_ids.iter().copied())
+                {
+                    exclusions.exclude_edge(path[spur_idx].node_id, path[spur_idx + 1].node_id);
+                }
+            }
+
+            // Exclude root-path nodes (except the spur node) to force simple paths
+            for &id in &root_ids[..spur_idx] {
+                exclusions.exclude_node(id);
+            }
+
+            // Remaining hop budget for the spur path
+            let remaining_hops = max_hops.saturating_sub(spur_idx as u32);
+            if remaining_hops == 0 {
+                continue;
+            }
+
+            // Find spur path: spur_node → target avoiding excluded nodes/edges
+            if let Some(spur_path) = shortest_path_by(
+                graph,
+                spur_node,
+                target,
+                remaining_hops,
+                direction,
+                min_confidence,
+                &exclusions,
+                step,
+            ) {
+                // Combine root + spur (skip spur_node duplicate)
+                let mut candidate = root_path.clone();
+                candidate.extend(spur_path.into_iter().skip(1));
+
+                // Deduplicate by node sequence
+                let candidate_ids: Vec<NodeId> =
+                    candidate.iter().map(|s| s.node_id).collect();
+                let is_dup = result
+                    .iter()
+                    .chain(candidates.iter())
+                    .any(|p| {
+                        p.len() == candidate_ids.len()
+                            && p.iter()
+                                .map(|s| s.node_id)
+                                .eq(candidate_ids.iter().copied())
+                    });
+
+                if !is_dup {
+                    candidates.push(candidate);
+                }
+            }
+        }
 
-        path.push(PathStep {
-            node_id: current,
-            label: info.map(|n| n.label.clone()).unwrap_or_default(),
-            app_id: info.and_then(|n| n.app_id.clone()),
-            rel_type: if current == start {
-                None
-            } else {
-                graph.rel_type_name(rel_type).map(|s| s.to_string())
-            },
-            direction: if current == start { None } else { Some(dir) },
-        });
+        if candidates.is_empty() {
+            break;
+        }
 
-        if current == start {
+        // Pick the shortest candidate (fewest hops)
+        candidates.sort_by_key(|p| p.len());
+        result.push(candidates.remove(0));
+    }
+
+    result
+}
+
+/// Find up to `k` shortest simple paths that connect any node of `sources`
+/// to any node of `targets`, shortest first (Yen's algorithm over a
+/// multi-source BFS).
+///
+/// Each path runs from a source to a target with no other member of either
+/// set inside it, so it shows one direct link between the two sets rather
+/// than a detour through them. A node in both sets is a zero-hop path of
+/// its own. The search behaves as Yen's algorithm over a virtual node
+/// joined to every source: after the shortest path, each round also
+/// retries from the sources not yet used as a starting point.
+///
+/// Nodes not in the graph or excluded are dropped from both sets, and
+/// nodes failing `node_filter` are neither entered nor reached. Out of
+/// time, returns the paths found so far.
+#[allow(clippy::too_many_arguments)]
+pub fn k_shortest_paths_between<F>(
+    graph: &Graph,
+    sources: &[NodeId],
+    targets: &[NodeId],
+    max_hops: u32,
+    k: usize,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    exclusions: &Exclusions,
+    node_filter: F,
+) -> Vec<Vec<PathStep>>
+where
+    F: Fn(NodeId) -> bool,
+{
+    let usable = |id: &NodeId| graph.node(*id).is_some() && !exclusions.excludes_node(*id);
+    let mut starts: Vec<NodeId> = Vec::new();
+    for &id in sources {
+        if usable(&id) && !starts.contains(&id) {
+            starts.push(id);
+        }
+    }
+    let source_set: HashSet<NodeId> = starts.iter().copied().collect();
+    let target_set: HashSet<NodeId> = targets.iter().copied().filter(usable).collect();
+    if k == 0 || starts.is_empty() || target_set.is_empty() {
+        return Vec::new();
+    }
+
+    // Only the first node of a path may be a source
+    let step = |e: &Edge| node_filter(e.target) && !source_set.contains(&e.target);
+    let search = |from: &[NodeId], hops: u32, exclusions: &Exclusions| {
+        shortest_path_to_set(
+            graph,
+            from,
+            &target_set,
+            hops,
+            direction,
+            min_confidence,
+            exclusions,
+            &step,
+        )
+    };
+    let same_nodes = |a: &[PathStep], b: &[PathStep]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.node_id == y.node_id)
+    };
+
+    // Nodes in both sets come first, then the Yen search over real edges
+    let mut result: Vec<Vec<PathStep>> = starts
+        .iter()
+        .filter(|s| target_set.contains(s))
+        .take(k)
+        .map(|&s| vec![make_step(graph, s, None)])
+        .collect();
+    let shared = result.len();
+    if shared == k {
+        return result;
+    }
+    let Some(first) = search(&starts, max_hops, exclusions) else {
+        return result;
+    };
+    result.push(first);
+    let mut candidates: Vec<Vec<PathStep>> = Vec::new();
+
+    while result.len() < k {
+        if interrupt::out_of_time() {
             break;
         }
-        current = parent;
+        let prev_path = &result[result.len() - 1];
+        let mut spurs: Vec<Vec<PathStep>> = Vec::new();
+
+        // Spur at the virtual root: start from a source no path has used
+        let fresh: Vec<NodeId> = starts
+            .iter()
+            .copied()
+            .filter(|&s| result[shared..].iter().all(|p| p[0].node_id != s))
+            .collect();
+        if !fresh.is_empty() {
+            spurs.extend(search(&fresh, max_hops, exclusions));
+        }
+
+        // Spur at each node of the previous path, as in `k_shortest_paths`
+        for spur_idx in 0..prev_path.len().saturating_sub(1) {
+            let root_path = &prev_path[..=spur_idx];
+            let mut spur_exclusions = exclusions.clone();
+            for path in &result {
+                if path.len() > spur_idx + 1 && same_nodes(&path[..=spur_idx], root_path) {
+                    let (from, to) = (path[spur_idx].node_id, path[spur_idx + 1].node_id);
+                    spur_exclusions.exclude_edge(from, to);
+                }
+            }
+            for node in &root_path[..spur_idx] {
+                spur_exclusions.exclude_node(node.node_id);
+            }
+            let remaining_hops = max_hops.saturating_sub(spur_idx as u32);
+            if remaining_hops == 0 {
+                continue;
+            }
+            let spur_node = root_path[spur_idx].node_id;
+            if let Some(spur_path) = search(&[spur_node], remaining_hops, &spur_exclusions) {
+                let mut candidate = root_path.to_vec();
+                candidate.extend(spur_path.into_iter().skip(1));
+                spurs.push(candidate);
+            }
+        }
+
+        for candidate in spurs {
+            let is_dup = result
+                .iter()
+                .chain(candidates.iter())
+                .any(|p| same_nodes(p, &candidate));
+            if !is_dup {
+                candidates.push(candidate);
+            }
+        }
+        if candidates.is_empty() {
+            break;
+        }
+
+        // Pick the shortest candidate (fewest hops)
+        candidates.sort_by_key(|p| p.len());
+        result.push(candidates.remove(0));
     }
 
-    path.reverse();
-    path
+    result
 }
 
-/// Find up to `k` shortest simple paths between two nodes using Yen's algorithm.
+/// Shortest path of at least one hop from any of `starts` to any node in
+/// `targets`, along edges `step` accepts. Runs on the dense index of a
+/// frozen graph, as `shortest_path_by` does.
+#[allow(clippy::too_many_arguments)]
+fn shortest_path_to_set<S>(
+    graph: &Graph,
+    starts: &[NodeId],
+    targets: &HashSet<NodeId>,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    exclusions: &Exclusions,
+    step: &S,
+) -> Option<Vec<PathStep>>
+where
+    S: Fn(&Edge) -> bool,
+{
+    let starts: Vec<NodeId> = starts
+        .iter()
+        .copied()
+        .filter(|&s| graph.node(s).is_some() && !exclusions.excludes_node(s))
+        .collect();
+
+    if let Some(dense) = graph.dense() {
+        let mut goal = DenseBits::new(dense.len());
+        for &t in targets {
+            if let Some(t) = dense.index_of(t) {
+                goal.insert(t);
+            }
+        }
+        let starts: Vec<u32> = starts
+            .iter()
+            .map(|&s| dense.index_of(s).expect("nodes with metadata are indexed"))
+            .collect();
+        let (hits, found) = dense_bfs(
+            dense,
+            &starts,
+            max_hops,
+            direction,
+            min_confidence,
+            exclusions,
+            step,
+            usize::MAX,
+            Some(&goal),
+        );
+        return found.then(|| dense_steps(graph, dense, &hits, hits.len() - 1));
+    }
+
+    // node → (parent, rel_type, direction); a start is its own parent
+    let mut visited: HashMap<NodeId, (NodeId, RelTypeId, Direction)> = HashMap::new();
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
+    for &s in &starts {
+        visited.insert(s, (s, 0, Direction::Outgoing));
+        queue.push_back((s, 0));
+    }
+
+    while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
+        if depth >= max_hops {
+            continue;
+        }
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            if exclusions.blocks(current, &edge) || !step(&edge) {
+                continue;
+            }
+            if let Entry::Vacant(slot) = visited.entry(edge.target) {
+                slot.insert((current, edge.rel_type, dir));
+                if targets.contains(&edge.target) {
+                    let mut path = Vec::new();
+                    let mut node = edge.target;
+                    loop {
+                        let (parent, rel_type, dir) = visited[&node];
+                        if parent == node {
+                            path.push(make_step(graph, node, None));
+                            break;
+                        }
+                        path.push(make_step(graph, node, Some((rel_type, dir))));
+                        node = parent;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back((edge.target, depth + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Cheapest path from `start` to `target` by the sum of `cost` over its
+/// edges (Dijkstra), within `max_hops`.
 ///
-/// Returns paths sorted by hop count (shortest first). Each path is loop-free.
-/// Uses BFS as the inner pathfinding step, with node and edge exclusion sets
-/// to force alternative routes at each spur node.
+/// `cost` is any `CostFn`; negative and NaN costs are treated as 0. Among
+/// equal-cost paths the one with fewer hops wins. Returns None if no path
+/// exists within `max_hops`, or if either node is not in the graph.
+pub fn shortest_path_weighted<C: CostFn>(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    cost: C,
+) -> Option<WeightedPath> {
+    let search = WeightedSearch {
+        graph,
+        direction,
+        min_confidence,
+        cost: &cost,
+        step: &|_| true,
+    };
+    let (steps, costs) = search.cheapest(start, target, max_hops, &Exclusions::new())?;
+    Some(WeightedPath {
+        total_cost: costs[costs.len() - 1],
+        steps,
+    })
+}
+
+/// Find up to `k` cheapest simple paths between two nodes, ranked by the sum
+/// of `cost` over their edges (Yen's algorithm over Dijkstra).
 ///
-/// Complexity: O(k * L * (V + E)) where L is the longest path length.
-/// For typical use (k=5, L~4, 1K nodes / 400K edges) this runs in microseconds.
-pub fn k_shortest_paths(
+/// `cost` is any `CostFn`; negative and NaN costs are treated as 0.
+/// Paths still obey `max_hops`: the inner search runs over (node, hops)
+/// states, so a cheap path is never cut off by a shorter, pricier one.
+/// Equal-cost paths are ordered by hop count.
+#[allow(clippy::too_many_arguments)]
+pub fn k_shortest_paths_weighted<C: CostFn>(
     graph: &Graph,
     start: NodeId,
     target: NodeId,
@@ -311,161 +2279,563 @@ pub fn k_shortest_paths(
     k: usize,
     direction: TraversalDirection,
     min_confidence: Option<f32>,
-) -> Vec<Vec<PathStep>> {
+    cost: C,
+) -> Vec<WeightedPath> {
+    k_shortest_paths_weighted_by(
+        graph,
+        start,
+        target,
+        max_hops,
+        k,
+        direction,
+        min_confidence,
+        cost,
+        &Exclusions::new(),
+        &|_| true,
+    )
+}
+
+/// `k_shortest_paths_weighted` whose paths all route around `base` along
+/// edges `step` accepts, as in `bfs_neighborhood_by`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn k_shortest_paths_weighted_by<C>(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    k: usize,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    cost: C,
+    base: &Exclusions,
+    step: &dyn Fn(&Edge) -> bool,
+) -> Vec<WeightedPath>
+where
+    C: CostFn,
+{
     if k == 0 {
         return Vec::new();
     }
+    let search = WeightedSearch {
+        graph,
+        direction,
+        min_confidence,
+        cost: &cost,
+        step,
+    };
 
-    // A[0]: first shortest path via standard BFS
-    let first = match shortest_path(graph, start, target, max_hops, direction, min_confidence) {
-        Some(path) => path,
+    // Each path carries the cumulative cost at every step (0.0 at the start)
+    let first = match search.cheapest(start, target, max_hops, base) {
+        Some(p) => p,
         None => return Vec::new(),
     };
-
-    let mut result: Vec<Vec<PathStep>> = vec![first];
-    // Candidate pool: paths found but not yet selected, sorted by length when picking
-    let mut candidates: Vec<Vec<PathStep>> = Vec::new();
+    let mut result: Vec<(Vec<PathStep>, Vec<f64>)> = vec![first];
+    let mut candidates: Vec<(Vec<PathStep>, Vec<f64>)> = Vec::new();
 
     for ki in 1..k {
-        let prev_path = &result[ki - 1];
+        // Out of time: the paths found so far
+        if interrupt::out_of_time() {
+            break;
+        }
+        let (prev_path, prev_costs) = &result[ki - 1];
 
-        // For each spur node in the previous path (skip the last — no edge to deviate from)
         for spur_idx in 0..prev_path.len().saturating_sub(1) {
             let spur_node = prev_path[spur_idx].node_id;
+            let root_ids: Vec<NodeId> = prev_path[..=spur_idx].iter().map(|s| s.node_id).collect();
 
-            // Root path: start → spur_node (spur_idx hops)
-            let root_path: Vec<PathStep> = prev_path[..=spur_idx].to_vec();
-            let root_ids: Vec<NodeId> = root_path.iter().map(|s| s.node_id).collect();
+            let mut exclusions = base.clone();
+            for (path, _) in &result {
+                if path.len() > spur_idx
+                    && path[..=spur_idx]
+                        .iter()
+                        .map(|s| s.node_id)
+                        .eq(root_ids.iter().copied())
+                {
+                    exclusions.exclude_edge(path[spur_idx].node_id, path[spur_idx + 1].node_id);
+                }
+            }
+            for &id in &root_ids[..spur_idx] {
+                exclusions.exclude_node(id);
+            }
+
+            let remaining_hops = max_hops.saturating_sub(spur_idx as u32);
+            if remaining_hops == 0 {
+                continue;
+            }
+
+            if let Some((spur_path, spur_costs)) =
+                search.cheapest(spur_node, target, remaining_hops, &exclusions)
+            {
+                let root_cost = prev_costs[spur_idx];
+                let mut steps = prev_path[..=spur_idx].to_vec();
+                steps.extend(spur_path.into_iter().skip(1));
+                let mut costs = prev_costs[..=spur_idx].to_vec();
+                costs.extend(spur_costs.into_iter().skip(1).map(|c| root_cost + c));
+
+                let is_dup = result.iter().chain(candidates.iter()).any(|(p, _)| {
+                    p.len() == steps.len()
+                        && p.iter().map(|s| s.node_id).eq(steps.iter().map(|s| s.node_id))
+                });
+                if !is_dup {
+                    candidates.push((steps, costs));
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            break;
+        }
+
+        // Pick the cheapest candidate, fewer hops first on ties
+        let best = (0..candidates.len())
+            .min_by(|&a, &b| {
+                let (pa, ca) = &candidates[a];
+                let (pb, cb) = &candidates[b];
+                ca[ca.len() - 1]
+                    .total_cmp(&cb[cb.len() - 1])
+                    .then(pa.len().cmp(&pb.len()))
+            })
+            .expect("candidates is non-empty");
+        result.push(candidates.remove(best));
+    }
+
+    result
+        .into_iter()
+        .map(|(steps, costs)| WeightedPath {
+            total_cost: costs[costs.len() - 1],
+            steps,
+        })
+        .collect()
+}
+
+/// (node, hops) → (best cost, parent state, rel_type, direction).
+type CostStates = HashMap<(NodeId, u32), (f64, (NodeId, u32), RelTypeId, Direction)>;
+
+/// Dijkstra inner search for `k_shortest_paths_weighted`.
+struct WeightedSearch<'a, F> {
+    graph: &'a Graph,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    cost: &'a F,
+    /// Edges a path may take, as in `bfs_neighborhood_by`.
+    step: &'a dyn Fn(&Edge) -> bool,
+}
+
+/// Min-heap entry: ordered by cost, then hops, then node ID.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct HeapEntry {
+    pub(crate) cost: f64,
+    pub(crate) hops: u32,
+    pub(crate) node: NodeId,
+}
+
+impl Eq for HeapEntry {}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed: BinaryHeap is a max-heap
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then(other.hops.cmp(&self.hops))
+            .then(other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: CostFn> WeightedSearch<'_, F> {
+    /// Cheapest path within `max_hops`, with cumulative cost per step.
+    ///
+    /// States are (node, hops). A state is dominated, and skipped, once the
+    /// node has been settled with no more hops: it was settled at no greater
+    /// cost, since states pop in cost order.
+    ///
+    /// Unlike the BFS searches this stays hashed on a frozen graph. A node
+    /// can hold a state per hop count, so `best` has to be a map either way,
+    /// and each edge probes it next to `settled_hops`; a dense settled array
+    /// would not remove the per-edge hash lookup.
+    fn cheapest(
+        &self,
+        start: NodeId,
+        target: NodeId,
+        max_hops: u32,
+        exclusions: &Exclusions,
+    ) -> Option<(Vec<PathStep>, Vec<f64>)> {
+        let graph = self.graph;
+        if graph.node(start).is_none() || graph.node(target).is_none() {
+            return None;
+        }
+        if exclusions.excludes_node(start) || exclusions.excludes_node(target) {
+            return None;
+        }
+
+        let mut best: CostStates = HashMap::new();
+        let mut settled_hops: HashMap<NodeId, u32> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+
+        best.insert((start, 0), (0.0, (start, 0), 0, Direction::Outgoing));
+        heap.push(HeapEntry {
+            cost: 0.0,
+            hops: 0,
+            node: start,
+        });
+
+        while let Some(HeapEntry { cost, hops, node }) = heap.pop() {
+            interrupt::check();
+            if best[&(node, hops)].0 < cost {
+                continue;
+            }
+            if settled_hops.get(&node).is_some_and(|&h| h <= hops) {
+                continue;
+            }
+            settled_hops.insert(node, hops);
+
+            if node == target {
+                return Some(self.reconstruct(&best, start, (node, hops)));
+            }
+            if hops >= max_hops {
+                continue;
+            }
+
+            for (edge, dir) in iter_neighbors(graph, node, self.direction, self.min_confidence) {
+                if exclusions.blocks(node, &edge) || !(self.step)(&edge) {
+                    continue;
+                }
+                if settled_hops.get(&edge.target).is_some_and(|&h| h <= hops + 1) {
+                    continue;
+                }
+                let next_cost = cost + (self.cost)(&edge).max(0.0);
+                let key = (edge.target, hops + 1);
+                if best.get(&key).is_none_or(|&(c, _, _, _)| next_cost < c) {
+                    best.insert(key, (next_cost, (node, hops), edge.rel_type, dir));
+                    heap.push(HeapEntry {
+                        cost: next_cost,
+                        hops: hops + 1,
+                        node: edge.target,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn reconstruct(
+        &self,
+        best: &CostStates,
+        start: NodeId,
+        end: (NodeId, u32),
+    ) -> (Vec<PathStep>, Vec<f64>) {
+        let mut steps = Vec::new();
+        let mut costs = Vec::new();
+        let mut current = end;
+        while current != (start, 0) {
+            let &(cost, parent, rel_type, dir) = &best[&current];
+            steps.push(make_step(self.graph, current.0, Some((rel_type, dir))));
+            costs.push(cost);
+            current = parent;
+        }
+        steps.push(make_step(self.graph, start, None));
+        costs.push(0.0);
+        steps.reverse();
+        costs.reverse();
+        (steps, costs)
+    }
+}
+
+/// Every path of minimal length between `start` and `target`.
+///
+/// A layered BFS records all predecessors of each node that lie on a
+/// shortest path (stopping at the target's layer), then enumerates the
+/// predecessor DAG from `target` back to `start`. Parallel edges with
+/// different relationship types count as distinct paths; duplicates with
+/// the same type and direction are collapsed.
+///
+/// The number of equal-length paths can grow exponentially in dense graphs,
+/// so at most `max_paths` are returned (in a deterministic order). Returns an
+/// empty Vec if no path exists within `max_hops` or `max_paths` is 0.
+///
+/// On a frozen graph the layers are tracked with bitsets over dense node
+/// numbers; the predecessor lists stay keyed by ID for the enumeration.
+pub fn all_shortest_paths(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    max_paths: usize,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<Vec<PathStep>> {
+    if max_paths == 0 || graph.node(start).is_none() || graph.node(target).is_none() {
+        return Vec::new();
+    }
+    if start == target {
+        return vec![vec![make_step(graph, start, None)]];
+    }
+
+    let (mut preds, reached) = match graph.dense() {
+        Some(dense) => {
+            dense_shortest_preds(dense, start, target, max_hops, direction, min_confidence)
+        }
+        None => shortest_preds(graph, start, target, max_hops, direction, min_confidence),
+    };
+    if !reached {
+        return Vec::new();
+    }
+    for p in preds.values_mut() {
+        p.sort_by_key(|&(parent, rt, dir)| (parent, rt, dir == Direction::Incoming));
+        p.dedup();
+    }
 
-            // Exclude edges leaving the spur node that are used by paths sharing this root
-            let mut excluded_edges: HashSet<(NodeId, NodeId)> = HashSet::new();
-            for path in &result {
-                if path.len() > spur_idx
-                    && path[..=spur_idx]
-                        .iter()
-                        .map(|s| s.node_id)
-                        .eq(root_ids.iter().copied())
-                {
-                    excluded_edges.insert((
-                        path[spur_idx].node_id,
-                        path[spur_idx + 1].node_id,
-                    ));
-                }
-            }
+    // Enumerate the predecessor DAG depth-first, building paths target → start
+    let mut paths: Vec<Vec<PathStep>> = Vec::new();
+    let mut stack: Vec<(NodeId, Option<(RelTypeId, Direction)>)> = vec![(target, None)];
+    enumerate_shortest(graph, &preds, start, &mut stack, &mut paths, max_paths);
+    paths
+}
 
-            // Exclude root-path nodes (except the spur node) to force simple paths
-            let excluded_nodes: HashSet<NodeId> =
-                root_ids[..spur_idx].iter().copied().collect();
+/// node → every (parent, rel_type, direction) one layer closer to the start.
+type Predecessors = HashMap<NodeId, Vec<(NodeId, RelTypeId, Direction)>>;
 
-            // Remaining hop budget for the spur path
-            let remaining_hops = max_hops.saturating_sub(spur_idx as u32);
-            if remaining_hops == 0 {
+/// Layered BFS for `all_shortest_paths`: the shortest-path parents of each
+/// node up to the target's layer, and whether the target was reached.
+/// Parallel duplicates are left in.
+fn shortest_preds(
+    graph: &Graph,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> (Predecessors, bool) {
+    // dist: node → BFS layer
+    let mut dist: HashMap<NodeId, u32> = HashMap::new();
+    let mut preds: Predecessors = HashMap::new();
+    let mut queue: VecDeque<NodeId> = VecDeque::new();
+    let mut target_dist: Option<u32> = None;
+
+    dist.insert(start, 0);
+    queue.push_back(start);
+
+    while let Some(current) = queue.pop_front() {
+        interrupt::check();
+        let depth = dist[&current];
+        if depth >= max_hops || target_dist.is_some_and(|td| depth >= td) {
+            continue;
+        }
+
+        for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
+            let next_depth = *dist.entry(edge.target).or_insert_with(|| {
+                queue.push_back(edge.target);
+                depth + 1
+            });
+            if next_depth != depth + 1 {
                 continue;
             }
+            // The layer check above keeps only shortest-path parents;
+            // parallel duplicates are removed once, after the BFS
+            preds
+                .entry(edge.target)
+                .or_default()
+                .push((current, edge.rel_type, dir));
+            if edge.target == target {
+                target_dist = Some(depth + 1);
+            }
+        }
+    }
 
-            // Find spur path: spur_node → target avoiding excluded nodes/edges
-            if let Some(spur_path) = shortest_path_excluding(
-                graph,
-                spur_node,
-                target,
-                remaining_hops,
-                direction,
-                min_confidence,
-                &excluded_nodes,
-                &excluded_edges,
-            ) {
-                // Combine root + spur (skip spur_node duplicate)
-                let mut candidate = root_path.clone();
-                candidate.extend(spur_path.into_iter().skip(1));
-
-                // Deduplicate by node sequence
-                let candidate_ids: Vec<NodeId> =
-                    candidate.iter().map(|s| s.node_id).collect();
-                let is_dup = result
-                    .iter()
-                    .chain(candidates.iter())
-                    .any(|p| {
-                        p.len() == candidate_ids.len()
-                            && p.iter()
-                                .map(|s| s.node_id)
-                                .eq(candidate_ids.iter().copied())
-                    });
+    (preds, target_dist.is_some())
+}
 
-                if !is_dup {
-                    candidates.push(candidate);
-                }
+/// `shortest_preds` over a frozen graph's dense index. BFS order puts each
+/// layer in one run of the queue, so a bitset of the layer being filled
+/// tells a node one hop further from one already settled.
+fn dense_shortest_preds(
+    dense: DenseAdjacency<'_>,
+    start: NodeId,
+    target: NodeId,
+    max_hops: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> (Predecessors, bool) {
+    let index = |id| dense.index_of(id).expect("nodes with metadata are indexed");
+    let (start, target) = (index(start), index(target));
+    let mut seen = DenseBits::new(dense.len());
+    // Nodes discovered one layer below the node being expanded
+    let mut layer = DenseBits::new(dense.len());
+    let mut preds: Predecessors = HashMap::new();
+    let mut target_dist: Option<u32> = None;
+
+    seen.insert(start);
+    // `order` doubles as the BFS queue
+    let mut order: Vec<(u32, u32)> = vec![(start, 0)];
+    let mut head = 0;
+    let mut level = 0;
+    while head < order.len() {
+        interrupt::check();
+        let (current, depth) = order[head];
+        head += 1;
+        if depth >= max_hops || target_dist.is_some_and(|td| depth >= td) {
+            continue;
+        }
+        if depth != level {
+            // The rest of the queue is this layer; its successors come next
+            for &(node, _) in &order[head - 1..] {
+                layer.remove(node);
             }
+            level = depth;
         }
 
-        if candidates.is_empty() {
-            break;
+        let current_id = dense.node_id(current);
+        for (next, edge, dir) in iter_dense_neighbors(dense, current, direction, min_confidence) {
+            if seen.insert(next) {
+                layer.insert(next);
+                order.push((next, depth + 1));
+            } else if !layer.contains(next) {
+                continue;
+            }
+            preds
+                .entry(edge.target)
+                .or_default()
+                .push((current_id, edge.rel_type, dir));
+            if next == target {
+                target_dist = Some(depth + 1);
+            }
         }
+    }
 
-        // Pick the shortest candidate (fewest hops)
-        candidates.sort_by_key(|p| p.len());
-        result.push(candidates.remove(0));
+    (preds, target_dist.is_some())
+}
+
+/// Depth-first walk over predecessor lists for `all_shortest_paths`.
+fn enumerate_shortest(
+    graph: &Graph,
+    preds: &Predecessors,
+    start: NodeId,
+    stack: &mut Vec<(NodeId, Option<(RelTypeId, Direction)>)>,
+    paths: &mut Vec<Vec<PathStep>>,
+    max_paths: usize,
+) {
+    if paths.len() >= max_paths {
+        return;
+    }
+    let (node, _) = *stack.last().expect("stack starts with the target");
+    if node == start {
+        // stack[i] holds the edge *into* stack[i-1]'s node, so shift by one
+        let steps = (0..stack.len())
+            .rev()
+            .map(|i| {
+                let via = if i + 1 < stack.len() { stack[i + 1].1 } else { None };
+                make_step(graph, stack[i].0, via)
+            })
+            .collect();
+        paths.push(steps);
+        return;
+    }
+    for &(parent, rt, dir) in preds.get(&node).map(|v| v.as_slice()).unwrap_or(&[]) {
+        stack.push((parent, Some((rt, dir))));
+        enumerate_shortest(graph, preds, start, stack, paths, max_paths);
+        stack.pop();
+        if paths.len() >= max_paths {
+            return;
+        }
     }
+}
 
-    result
+/// Build a PathStep for `node`, reached via `via` (None for the start node).
+pub(crate) fn make_step(graph: &Graph, node: NodeId, via: Option<(RelTypeId, Direction)>) -> PathStep {
+    let info = graph.node(node);
+    PathStep {
+        node_id: node,
+        label: graph.node_label(node).unwrap_or_default().to_string(),
+        app_id: info.and_then(|n| n.app_id.clone()),
+        rel_type: via.and_then(|(rt, _)| graph.rel_type_name(rt).map(|s| s.to_string())),
+        direction: via.map(|(_, dir)| dir),
+    }
 }
 
-/// BFS shortest path with node and edge exclusion (inner loop for Yen's algorithm).
+/// Shortest path routed around `exclusions` along edges `step` accepts,
+/// as in `bfs_neighborhood_by`.
 ///
-/// `excluded_nodes`: nodes that cannot appear on the path (except start/target).
-/// `excluded_edges`: (from, to) pairs that cannot be traversed.
-fn shortest_path_excluding(
+/// Returns None if either endpoint is excluded. Also the inner search of
+/// Yen's algorithm, which excludes the root path and the edges already
+/// taken from each spur node.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn shortest_path_by<S>(
     graph: &Graph,
     start: NodeId,
     target: NodeId,
     max_hops: u32,
     direction: TraversalDirection,
     min_confidence: Option<f32>,
-    excluded_nodes: &HashSet<NodeId>,
-    excluded_edges: &HashSet<(NodeId, NodeId)>,
-) -> Option<Vec<PathStep>> {
+    exclusions: &Exclusions,
+    step: S,
+) -> Option<Vec<PathStep>>
+where
+    S: Fn(&Edge) -> bool,
+{
     if graph.node(start).is_none() || graph.node(target).is_none() {
         return None;
     }
-    if excluded_nodes.contains(&start) || excluded_nodes.contains(&target) {
+    if exclusions.excludes_node(start) || exclusions.excludes_node(target) {
         return None;
     }
 
     if start == target {
-        let info = graph.node(start);
-        return Some(vec![PathStep {
-            node_id: start,
-            label: info.map(|n| n.label.clone()).unwrap_or_default(),
-            app_id: info.and_then(|n| n.app_id.clone()),
-            rel_type: None,
-            direction: None,
-        }]);
+        return Some(vec![make_step(graph, start, None)]);
     }
 
     if max_hops == 0 {
         return None;
     }
 
+    if let Some(dense) = graph.dense() {
+        let index = |id| dense.index_of(id).expect("nodes with metadata are indexed");
+        let mut goal = DenseBits::new(dense.len());
+        goal.insert(index(target));
+        let (hits, found) = dense_bfs(
+            dense,
+            &[index(start)],
+            max_hops,
+            direction,
+            min_confidence,
+            exclusions,
+            step,
+            usize::MAX,
+            Some(&goal),
+        );
+        return found.then(|| dense_steps(graph, dense, &hits, hits.len() - 1));
+    }
+
+    // BFS with parent tracking: node → (parent, rel_type, direction)
     let mut visited: HashMap<NodeId, (NodeId, RelTypeId, Direction)> = HashMap::new();
     let mut queue: VecDeque<(NodeId, u32)> = VecDeque::new();
 
+    // Sentinel: start node's parent is itself
     visited.insert(start, (start, 0, Direction::Outgoing));
     queue.push_back((start, 0));
 
     while let Some((current, depth)) = queue.pop_front() {
+        interrupt::check();
         if depth >= max_hops {
             continue;
         }
 
         for (edge, dir) in iter_neighbors(graph, current, direction, min_confidence) {
-            if excluded_nodes.contains(&edge.target) {
-                continue;
-            }
-            if excluded_edges.contains(&(current, edge.target)) {
+            if exclusions.blocks(current, &edge) {
                 continue;
             }
 
-            if !visited.contains_key(&edge.target) {
-                visited.insert(edge.target, (current, edge.rel_type, dir));
+            if let Entry::Vacant(slot) = visited.entry(edge.target) {
+                if !step(&edge) {
+                    continue;
+                }
+                slot.insert((current, edge.rel_type, dir));
 
                 if edge.target == target {
                     return Some(reconstruct_sp_path(graph, &visited, start, target));
@@ -492,27 +2862,132 @@ pub fn extract_subgraph(
     direction: TraversalDirection,
     min_confidence: Option<f32>,
 ) -> SubgraphResult {
+    extract_subgraph_by(
+        graph,
+        start,
+        max_depth,
+        direction,
+        min_confidence,
+        0,
+        &Exclusions::new(),
+        |_: &Edge| true,
+        |_: &Edge| true,
+    )
+}
+
+/// `extract_subgraph` routed around `exclusions`, discovering nodes along
+/// the edges `step` accepts as in `bfs_neighborhood_by`. Excluded edges
+/// and those `keep` rejects are left out of the result, and an excluded
+/// `start` yields an empty subgraph.
+///
+/// With `max_edges` > 0, at most that many edges are returned, nearest
+/// nodes' first, and `truncated` is set if any were left out. Discovery
+/// stops after `max_edges + 1` nodes besides `start`: the BFS tree alone
+/// joins them with more edges than the budget.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn extract_subgraph_by<S, K>(
+    graph: &Graph,
+    start: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+    max_edges: usize,
+    exclusions: &Exclusions,
+    step: S,
+    keep: K,
+) -> SubgraphResult
+where
+    S: Fn(&Edge) -> bool,
+    K: Fn(&Edge) -> bool,
+{
     use std::collections::HashSet;
 
-    if graph.node(start).is_none() {
+    if graph.node(start).is_none() || exclusions.excludes_node(start) {
         return SubgraphResult {
             node_count: 0,
             edges: Vec::new(),
+            truncated: false,
         };
     }
 
     // Phase 1: BFS to discover reachable node set
-    let bfs = bfs_neighborhood(graph, start, max_depth, direction, min_confidence);
-    let mut node_set: HashSet<NodeId> = HashSet::with_capacity(bfs.nodes_visited);
-    node_set.insert(start);
-    for nr in &bfs.neighbors {
-        node_set.insert(nr.node_id);
+    let max_nodes = if max_edges > 0 { max_edges + 1 } else { 0 };
+    let bfs = bfs_neighborhood_by(
+        graph,
+        start,
+        max_depth,
+        direction,
+        min_confidence,
+        max_nodes,
+        exclusions,
+        step,
+    );
+    let order: Vec<NodeId> = std::iter::once(start)
+        .chain(bfs.neighbors.iter().map(|nr| nr.node_id))
+        .collect();
+    let node_set: HashSet<NodeId> = order.iter().copied().collect();
+
+    // Phase 2: collect edges between discovered nodes, in discovery order
+    let (edges, cut) = induced_edges(
+        graph,
+        &order,
+        &node_set,
+        min_confidence,
+        max_edges,
+        exclusions,
+        keep,
+    );
+    SubgraphResult {
+        node_count: node_set.len(),
+        edges,
+        truncated: bfs.truncated || cut,
+    }
+}
+
+/// The subgraph induced by `nodes`: every edge whose endpoints are both in
+/// the set, subject to `min_confidence`.
+///
+/// IDs not in the graph are ignored, as are duplicates; `node_count` is the
+/// number of distinct known nodes.
+pub fn induced_subgraph(graph: &Graph, nodes: &[NodeId], min_confidence: Option<f32>) -> SubgraphResult {
+    let node_set: HashSet<NodeId> = nodes
+        .iter()
+        .copied()
+        .filter(|&id| graph.node(id).is_some())
+        .collect();
+    let order: Vec<NodeId> = node_set.iter().copied().collect();
+    let every = |_: &Edge| true;
+    let (edges, _) = induced_edges(
+        graph,
+        &order,
+        &node_set,
+        min_confidence,
+        0,
+        &Exclusions::new(),
+        every,
+    );
+    SubgraphResult {
+        node_count: node_set.len(),
+        edges,
+        truncated: false,
     }
+}
 
-    // Phase 2: collect edges between discovered nodes
-    // Only iterate outgoing edges to avoid duplicates
+/// Edges among `node_set` that `exclusions` does not block and `keep`
+/// accepts, taking the nodes' outgoing edges in `order`, so each edge is
+/// emitted once. Stops at `max_edges` (0 = no limit), returning true if
+/// an edge was left out.
+fn induced_edges(
+    graph: &Graph,
+    order: &[NodeId],
+    node_set: &HashSet<NodeId>,
+    min_confidence: Option<f32>,
+    max_edges: usize,
+    exclusions: &Exclusions,
+    keep: impl Fn(&Edge) -> bool,
+) -> (Vec<SubgraphEdge>, bool) {
     let mut edges = Vec::new();
-    for &node_id in &node_set {
+    for &node_id in order {
         for edge in graph.neighbors_out(node_id) {
             // Apply confidence filter to emitted edges
             if let Some(min) = min_confidence {
@@ -520,29 +2995,73 @@ pub fn extract_subgraph(
                     continue;
                 }
             }
-            if node_set.contains(&edge.target) {
-                let from_info = graph.node(node_id);
-                let to_info = graph.node(edge.target);
-                edges.push(SubgraphEdge {
-                    from_id: node_id,
-                    from_label: from_info.map(|n| n.label.clone()).unwrap_or_default(),
-                    from_app_id: from_info.and_then(|n| n.app_id.clone()),
-                    to_id: edge.target,
-                    to_label: to_info.map(|n| n.label.clone()).unwrap_or_default(),
-                    to_app_id: to_info.and_then(|n| n.app_id.clone()),
-                    rel_type: graph
-                        .rel_type_name(edge.rel_type)
-                        .unwrap_or("UNKNOWN")
-                        .to_string(),
-                });
+            let inside = node_set.contains(&edge.target) && keep(&edge);
+            if inside && !exclusions.blocks(node_id, &edge) {
+                if max_edges > 0 && edges.len() == max_edges {
+                    return (edges, true);
+                }
+                edges.push(SubgraphEdge::new(graph, node_id, edge.target, edge.rel_type));
             }
         }
     }
+    (edges, false)
+}
 
-    SubgraphResult {
-        node_count: node_set.len(),
-        edges,
+/// Summary statistics of the induced subgraph within `max_depth` hops of
+/// `node`, computed without materializing its edges.
+///
+/// The ball is found with the same BFS as `extract_subgraph`, and its edges
+/// are counted under the same rules. Returns None if `node` is not in the
+/// graph.
+pub fn ego_stats(
+    graph: &Graph,
+    node: NodeId,
+    max_depth: u32,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Option<EgoStats> {
+    graph.node(node)?;
+
+    let bfs = bfs_neighborhood(graph, node, max_depth, direction, min_confidence, 0);
+    let mut ball: HashSet<NodeId> = HashSet::with_capacity(bfs.nodes_visited);
+    ball.insert(node);
+    ball.extend(bfs.neighbors.iter().map(|n| n.node_id));
+
+    let mut edge_count = 0;
+    let mut by_type: HashMap<RelTypeId, usize> = HashMap::new();
+    let mut pairs: HashSet<(NodeId, NodeId)> = HashSet::new();
+    for &id in &ball {
+        for (edge, _) in iter_neighbors(graph, id, TraversalDirection::Outgoing, min_confidence) {
+            if !ball.contains(&edge.target) {
+                continue;
+            }
+            edge_count += 1;
+            *by_type.entry(edge.rel_type).or_insert(0) += 1;
+            if edge.target != id {
+                pairs.insert((id.min(edge.target), id.max(edge.target)));
+            }
+        }
     }
+
+    let n = ball.len();
+    let density = if n < 2 {
+        0.0
+    } else {
+        pairs.len() as f64 / (n * (n - 1) / 2) as f64
+    };
+
+    let mut rel_types: Vec<(String, usize)> = by_type
+        .into_iter()
+        .map(|(rt, count)| (graph.rel_type_name(rt).unwrap_or("UNKNOWN").to_string(), count))
+        .collect();
+    rel_types.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    Some(EgoStats {
+        node_count: n,
+        edge_count,
+        density,
+        rel_types,
+    })
 }
 
 /// Return nodes ranked by degree (total connections).
@@ -557,7 +3076,7 @@ pub fn degree_centrality(graph: &Graph, top_n: usize) -> Vec<DegreeResult> {
             let in_degree = graph.neighbors_in(id).len() as u32;
             DegreeResult {
                 node_id: id,
-                label: info.label.clone(),
+                label: graph.label_name(info.label).unwrap_or_default().to_string(),
                 app_id: info.app_id.clone(),
                 out_degree,
                 in_degree,
@@ -580,46 +3099,106 @@ pub fn degree_centrality(graph: &Graph, top_n: usize) -> Vec<DegreeResult> {
     results
 }
 
+/// Return nodes ranked by local clustering coefficient.
+///
+/// Treats the graph as undirected and simple: edge direction, parallel edges,
+/// and self-loops are ignored. A coefficient near 1.0 means the node's
+/// neighbors are densely linked to each other (an embedded concept); near 0.0
+/// means it connects otherwise unrelated nodes (a bridge).
+///
+/// If `top_n` is 0, returns all nodes. Otherwise returns the top N by
+/// coefficient (descending), ties broken by degree (descending) then node ID.
+pub fn local_clustering(graph: &Graph, top_n: usize) -> Vec<ClusteringResult> {
+    let neighbor_set = |id: NodeId| -> HashSet<NodeId> {
+        graph
+            .neighbors_all(id)
+            .map(|(e, _)| e.target)
+            .filter(|&t| t != id)
+            .collect()
+    };
+
+    let mut results: Vec<ClusteringResult> = graph
+        .nodes_iter()
+        .map(|(&id, info)| {
+            let neighbors = neighbor_set(id);
+            let k = neighbors.len() as u64;
+
+            // Count each linked pair {u, w} once, from its smaller endpoint.
+            // Sorted by target, probing u for each neighbor beats scanning
+            // u's edges when u has more edges than this node has neighbors.
+            let mut links = 0u64;
+            for &u in &neighbors {
+                let degree = graph.neighbors_out(u).len() + graph.neighbors_in(u).len();
+                if graph.is_sorted_by_target() && neighbors.len() < degree {
+                    links += neighbors
+                        .iter()
+                        .filter(|&&w| w > u && (graph.has_edge(u, w) || graph.has_edge(w, u)))
+                        .count() as u64;
+                    continue;
+                }
+                let linked: HashSet<NodeId> = graph
+                    .neighbors_all(u)
+                    .map(|(e, _)| e.target)
+                    .filter(|&w| w > u && neighbors.contains(&w))
+                    .collect();
+                links += linked.len() as u64;
+            }
+
+            let coefficient = if k < 2 {
+                0.0
+            } else {
+                (2 * links) as f64 / (k * (k - 1)) as f64
+            };
+
+            ClusteringResult {
+                node_id: id,
+                label: graph.label_name(info.label).unwrap_or_default().to_string(),
+                app_id: info.app_id.clone(),
+                degree: k as u32,
+                triangles: links as u32,
+                coefficient,
+            }
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.coefficient
+            .total_cmp(&a.coefficient)
+            .then(b.degree.cmp(&a.degree))
+            .then(a.node_id.cmp(&b.node_id))
+    });
+
+    if top_n > 0 && top_n < results.len() {
+        results.truncate(top_n);
+    }
+
+    results
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::graph::{Edge, EdgeRecord, Graph, TraversalDirection};
+    use crate::props::PropValue;
+    use crate::test_util::edge;
 
-    fn edge(from: u64, to: u64, rel: &str) -> EdgeRecord {
-        EdgeRecord {
-            from_id: from,
-            to_id: to,
-            rel_type: rel.to_string(),
-            from_label: "Node".to_string(),
-            to_label: "Node".to_string(),
-            from_app_id: None,
-            to_app_id: None,
-            confidence: Edge::NO_CONFIDENCE,
-        }
-    }
-
-    fn make_chain(n: u64) -> Graph {
+    fn make_chain(n: NodeId) -> Graph {
         let mut g = Graph::new();
         g.load_edges((0..n - 1).map(|i| edge(i, i + 1, "NEXT")));
         g
     }
 
-    fn make_star(center: u64, leaves: u64) -> Graph {
+    fn make_star(center: NodeId, leaves: NodeId) -> Graph {
         let mut g = Graph::new();
         g.load_edges((1..=leaves).map(|i| EdgeRecord {
-            from_id: center,
-            to_id: i,
-            rel_type: "HAS".to_string(),
             from_label: "Hub".to_string(),
             to_label: "Leaf".to_string(),
-            from_app_id: None,
-            to_app_id: None,
-            confidence: Edge::NO_CONFIDENCE,
+            ..EdgeRecord::new(center, i, "HAS")
         }));
         g
     }
 
-    fn make_cycle(n: u64) -> Graph {
+    fn make_cycle(n: NodeId) -> Graph {
         let mut g = Graph::new();
         g.load_edges((0..n).map(|i| edge(i, (i + 1) % n, "NEXT")));
         g
@@ -630,7 +3209,7 @@ mod tests {
     #[test]
     fn test_bfs_chain() {
         let g = make_chain(6);
-        let result = bfs_neighborhood(&g, 0, 10, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 10, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 5);
         let node5 = result.neighbors.iter().find(|n| n.node_id == 5).unwrap();
         assert_eq!(node5.distance, 5);
@@ -639,7 +3218,7 @@ mod tests {
     #[test]
     fn test_bfs_chain_depth_limited() {
         let g = make_chain(10);
-        let result = bfs_neighborhood(&g, 0, 3, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 3, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 3);
         assert!(result.neighbors.iter().all(|n| n.distance <= 3));
     }
@@ -647,7 +3226,7 @@ mod tests {
     #[test]
     fn test_bfs_star() {
         let g = make_star(0, 100);
-        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 100);
         assert!(result.neighbors.iter().all(|n| n.distance == 1));
     }
@@ -655,14 +3234,14 @@ mod tests {
     #[test]
     fn test_bfs_cycle_no_infinite_loop() {
         let g = make_cycle(5);
-        let result = bfs_neighborhood(&g, 0, 100, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 100, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 4);
     }
 
     #[test]
     fn test_bfs_undirected() {
         let g = make_chain(2);
-        let result = bfs_neighborhood(&g, 1, 1, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 1, 1, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 1);
         assert_eq!(result.neighbors[0].node_id, 0);
     }
@@ -670,7 +3249,7 @@ mod tests {
     #[test]
     fn test_bfs_empty_graph() {
         let g = Graph::new();
-        let result = bfs_neighborhood(&g, 0, 10, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 10, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 0);
         assert_eq!(result.nodes_visited, 0);
     }
@@ -678,7 +3257,7 @@ mod tests {
     #[test]
     fn test_bfs_start_not_in_graph() {
         let g = make_chain(3);
-        let result = bfs_neighborhood(&g, 999, 10, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 999, 10, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 0);
         assert_eq!(result.nodes_visited, 0);
     }
@@ -686,7 +3265,7 @@ mod tests {
     #[test]
     fn test_bfs_depth_zero() {
         let g = make_chain(5);
-        let result = bfs_neighborhood(&g, 0, 0, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 0, TraversalDirection::Both, None, 0);
         // Depth 0 = only start node, no neighbors
         assert_eq!(result.neighbors.len(), 0);
         assert_eq!(result.nodes_visited, 1);
@@ -696,7 +3275,7 @@ mod tests {
     fn test_bfs_self_loop() {
         let mut g = Graph::new();
         g.load_edges(vec![edge(0, 0, "SELF")]);
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None, 0);
         // Self-loop: node 0 is already visited as start, so no neighbors
         assert_eq!(result.neighbors.len(), 0);
     }
@@ -709,7 +3288,7 @@ mod tests {
             edge(0, 1, "SUPPORTS"),
             edge(0, 1, "CONTRADICTS"),
         ]);
-        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Both, None, 0);
         // Should find node 1 once (at distance 1) despite 3 parallel edges
         assert_eq!(result.neighbors.len(), 1);
         assert_eq!(result.neighbors[0].distance, 1);
@@ -796,7 +3375,7 @@ mod tests {
         g.add_edge(0, 1, implies, Edge::NO_CONFIDENCE);
         g.add_edge(1, 2, supports, Edge::NO_CONFIDENCE);
 
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None, 0);
         let node2 = result.neighbors.iter().find(|n| n.node_id == 2).unwrap();
         assert_eq!(node2.path_types, vec!["IMPLIES", "SUPPORTS"]);
     }
@@ -844,14 +3423,11 @@ mod tests {
     fn test_edge_record_loading() {
         let mut g = Graph::new();
         g.load_edges(vec![EdgeRecord {
-            from_id: 1,
-            to_id: 2,
-            rel_type: "IMPLIES".to_string(),
             from_label: "Concept".to_string(),
             to_label: "Concept".to_string(),
             from_app_id: Some("c_1".to_string()),
             to_app_id: Some("c_2".to_string()),
-            confidence: Edge::NO_CONFIDENCE,
+            ..EdgeRecord::new(1, 2, "IMPLIES")
         }]);
         assert_eq!(g.node_count(), 2);
         assert_eq!(g.edge_count(), 1);
@@ -871,7 +3447,7 @@ mod tests {
     fn test_bfs_direction_outgoing() {
         // Chain 0→1→2, BFS from 0: both edges followed in their stored direction
         let g = make_chain(3);
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None, 0);
         let node2 = result.neighbors.iter().find(|n| n.node_id == 2).unwrap();
         assert_eq!(node2.path_directions, vec![Direction::Outgoing, Direction::Outgoing]);
     }
@@ -880,7 +3456,7 @@ mod tests {
     fn test_bfs_direction_incoming() {
         // Chain 0→1→2, BFS from 2: both edges followed against their stored direction
         let g = make_chain(3);
-        let result = bfs_neighborhood(&g, 2, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 2, 5, TraversalDirection::Both, None, 0);
         let node0 = result.neighbors.iter().find(|n| n.node_id == 0).unwrap();
         assert_eq!(node0.path_directions, vec![Direction::Incoming, Direction::Incoming]);
     }
@@ -890,7 +3466,7 @@ mod tests {
         // 0→1←2: from node 0, reach 1 via outgoing, reach 2 via 1's incoming list
         let mut g = Graph::new();
         g.load_edges(vec![edge(0, 1, "A"), edge(2, 1, "B")]);
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None, 0);
 
         let node1 = result.neighbors.iter().find(|n| n.node_id == 1).unwrap();
         assert_eq!(node1.path_directions, vec![Direction::Outgoing]);
@@ -909,7 +3485,7 @@ mod tests {
         // Verify path_types and path_directions are always the same length
         let mut g = Graph::new();
         g.load_edges(vec![edge(0, 1, "IMPLIES"), edge(1, 2, "SUPPORTS")]);
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None, 0);
         for n in &result.neighbors {
             assert_eq!(
                 n.path_types.len(),
@@ -969,11 +3545,11 @@ mod tests {
         let mut g = Graph::new();
         g.load_edges(vec![edge(0, 1, "SUPPORTS")]);
 
-        let from_0 = bfs_neighborhood(&g, 0, 1, TraversalDirection::Both, None);
+        let from_0 = bfs_neighborhood(&g, 0, 1, TraversalDirection::Both, None, 0);
         let n1 = from_0.neighbors.iter().find(|n| n.node_id == 1).unwrap();
         assert_eq!(n1.path_directions, vec![Direction::Outgoing]);
 
-        let from_1 = bfs_neighborhood(&g, 1, 1, TraversalDirection::Both, None);
+        let from_1 = bfs_neighborhood(&g, 1, 1, TraversalDirection::Both, None, 0);
         let n0 = from_1.neighbors.iter().find(|n| n.node_id == 0).unwrap();
         assert_eq!(n0.path_directions, vec![Direction::Incoming]);
     }
@@ -984,13 +3560,13 @@ mod tests {
     fn test_bfs_outgoing_only() {
         // Chain 0→1→2: outgoing-only from 0 finds 1 and 2
         let g = make_chain(3);
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Outgoing, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Outgoing, None, 0);
         assert_eq!(result.neighbors.len(), 2);
         assert!(result.neighbors.iter().any(|n| n.node_id == 1));
         assert!(result.neighbors.iter().any(|n| n.node_id == 2));
 
         // From 2, outgoing-only finds nothing (no outgoing edges from 2)
-        let result = bfs_neighborhood(&g, 2, 5, TraversalDirection::Outgoing, None);
+        let result = bfs_neighborhood(&g, 2, 5, TraversalDirection::Outgoing, None, 0);
         assert_eq!(result.neighbors.len(), 0);
     }
 
@@ -998,13 +3574,13 @@ mod tests {
     fn test_bfs_incoming_only() {
         // Chain 0→1→2: incoming-only from 2 finds 1 and 0
         let g = make_chain(3);
-        let result = bfs_neighborhood(&g, 2, 5, TraversalDirection::Incoming, None);
+        let result = bfs_neighborhood(&g, 2, 5, TraversalDirection::Incoming, None, 0);
         assert_eq!(result.neighbors.len(), 2);
         assert!(result.neighbors.iter().any(|n| n.node_id == 0));
         assert!(result.neighbors.iter().any(|n| n.node_id == 1));
 
         // From 0, incoming-only finds nothing (no incoming edges to 0)
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Incoming, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Incoming, None, 0);
         assert_eq!(result.neighbors.len(), 0);
     }
 
@@ -1038,19 +3614,19 @@ mod tests {
         let g = make_star(0, 50);
 
         // Outgoing from hub: finds all 50 leaves
-        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Outgoing, None);
+        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Outgoing, None, 0);
         assert_eq!(result.neighbors.len(), 50);
 
         // Incoming from hub: finds nothing (all edges point away from hub)
-        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Incoming, None);
+        let result = bfs_neighborhood(&g, 0, 1, TraversalDirection::Incoming, None, 0);
         assert_eq!(result.neighbors.len(), 0);
 
         // Outgoing from leaf: finds nothing (leaves have no outgoing edges)
-        let result = bfs_neighborhood(&g, 1, 1, TraversalDirection::Outgoing, None);
+        let result = bfs_neighborhood(&g, 1, 1, TraversalDirection::Outgoing, None, 0);
         assert_eq!(result.neighbors.len(), 0);
 
         // Incoming from leaf: finds hub
-        let result = bfs_neighborhood(&g, 1, 1, TraversalDirection::Incoming, None);
+        let result = bfs_neighborhood(&g, 1, 1, TraversalDirection::Incoming, None, 0);
         assert_eq!(result.neighbors.len(), 1);
         assert_eq!(result.neighbors[0].node_id, 0);
     }
@@ -1059,12 +3635,12 @@ mod tests {
     fn test_directed_both_matches_undirected() {
         // Both should give same results as the undirected tests
         let g = make_chain(6);
-        let both = bfs_neighborhood(&g, 0, 10, TraversalDirection::Both, None);
+        let both = bfs_neighborhood(&g, 0, 10, TraversalDirection::Both, None, 0);
         assert_eq!(both.neighbors.len(), 5);
 
         // Outgoing + Incoming from same start should cover all Both neighbors
-        let out = bfs_neighborhood(&g, 0, 10, TraversalDirection::Outgoing, None);
-        let inc = bfs_neighborhood(&g, 0, 10, TraversalDirection::Incoming, None);
+        let out = bfs_neighborhood(&g, 0, 10, TraversalDirection::Outgoing, None, 0);
+        let inc = bfs_neighborhood(&g, 0, 10, TraversalDirection::Incoming, None, 0);
         let mut union: Vec<NodeId> = out
             .neighbors
             .iter()
@@ -1123,36 +3699,242 @@ mod tests {
     }
 
     #[test]
-    fn test_degree_top_n() {
-        // Star with hub having highest degree
-        let g = make_star(0, 50);
-        let results = degree_centrality(&g, 5);
-        assert_eq!(results.len(), 5);
-        // Hub should be first
-        assert_eq!(results[0].node_id, 0);
-        assert_eq!(results[0].total_degree, 50);
+    fn test_degree_top_n() {
+        // Star with hub having highest degree
+        let g = make_star(0, 50);
+        let results = degree_centrality(&g, 5);
+        assert_eq!(results.len(), 5);
+        // Hub should be first
+        assert_eq!(results[0].node_id, 0);
+        assert_eq!(results[0].total_degree, 50);
+    }
+
+    #[test]
+    fn test_degree_sorted() {
+        let g = make_star(0, 50);
+        let results = degree_centrality(&g, 0);
+        // Must be sorted descending by total_degree
+        for w in results.windows(2) {
+            assert!(
+                w[0].total_degree >= w[1].total_degree,
+                "not sorted: {} >= {} failed",
+                w[0].total_degree,
+                w[1].total_degree
+            );
+        }
+    }
+
+    #[test]
+    fn test_degree_empty() {
+        let g = Graph::new();
+        let results = degree_centrality(&g, 10);
+        assert!(results.is_empty());
+    }
+
+    // --- All shortest paths tests ---
+
+    fn path_ids(path: &[PathStep]) -> Vec<NodeId> {
+        path.iter().map(|s| s.node_id).collect()
+    }
+
+    #[test]
+    fn test_all_shortest_diamond() {
+        let g = make_diamond();
+        let paths = all_shortest_paths(&g, 0, 3, 10, 100, TraversalDirection::Both, None);
+        assert_eq!(paths.len(), 2);
+        for p in &paths {
+            assert_eq!(p.len(), 3);
+            assert_eq!(p[0].node_id, 0);
+            assert!(p[0].rel_type.is_none() && p[0].direction.is_none());
+            assert_eq!(p[2].node_id, 3);
+        }
+        assert_ne!(path_ids(&paths[0]), path_ids(&paths[1]));
+    }
+
+    #[test]
+    fn test_all_shortest_grid_only_minimal() {
+        // 0→5 has four 3-hop routes: 0-1-2-5, 0-3-4-5, 0-1-4-5, 0-3-2-5
+        let g = make_grid();
+        let paths = all_shortest_paths(&g, 0, 5, 10, 100, TraversalDirection::Outgoing, None);
+        let mut ids: Vec<Vec<NodeId>> = paths.iter().map(|p| path_ids(p)).collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec![vec![0, 1, 2, 5], vec![0, 1, 4, 5], vec![0, 3, 2, 5], vec![0, 3, 4, 5]]
+        );
+        for p in &paths {
+            assert!(p[1..].iter().all(|s| s.direction == Some(Direction::Outgoing)));
+        }
+    }
+
+    #[test]
+    fn test_all_shortest_parallel_rel_types() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "A"),
+            edge(0, 1, "B"),
+            edge(0, 1, "B"), // duplicate: collapsed
+            edge(1, 2, "C"),
+        ]);
+        let paths = all_shortest_paths(&g, 0, 2, 5, 100, TraversalDirection::Both, None);
+        let mut types: Vec<String> = paths.iter().map(|p| p[1].rel_type.clone().unwrap()).collect();
+        types.sort();
+        assert_eq!(types, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_all_shortest_limits() {
+        let g = make_grid();
+        assert_eq!(
+            all_shortest_paths(&g, 0, 5, 10, 2, TraversalDirection::Outgoing, None).len(),
+            2
+        );
+        assert!(all_shortest_paths(&g, 0, 5, 10, 0, TraversalDirection::Both, None).is_empty());
+        assert!(all_shortest_paths(&g, 0, 5, 2, 10, TraversalDirection::Outgoing, None).is_empty());
+        assert!(all_shortest_paths(&g, 5, 0, 10, 10, TraversalDirection::Outgoing, None).is_empty());
+        assert!(all_shortest_paths(&g, 0, 99, 10, 10, TraversalDirection::Both, None).is_empty());
+
+        let same = all_shortest_paths(&g, 4, 4, 10, 10, TraversalDirection::Both, None);
+        assert_eq!(same.len(), 1);
+        assert_eq!(path_ids(&same[0]), vec![4]);
+    }
+
+    #[test]
+    fn test_all_shortest_agrees_with_shortest_path() {
+        let g = make_cycle(8);
+        let sp = shortest_path(&g, 0, 4, 10, TraversalDirection::Both, None).unwrap();
+        let all = all_shortest_paths(&g, 0, 4, 10, 10, TraversalDirection::Both, None);
+        // Both ways around the cycle are 4 hops
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().all(|p| p.len() == sp.len()));
+    }
+
+    // --- Shortest-path tree tests ---
+
+    #[test]
+    fn test_spt_matches_shortest_path() {
+        let g = make_grid();
+        let targets = [2, 4, 5];
+        let tree = shortest_path_tree(&g, 0, &targets, 10, TraversalDirection::Both, None);
+        let by_id: HashMap<NodeId, &PathTreeNode> = tree.iter().map(|n| (n.node_id, n)).collect();
+
+        for &t in &targets {
+            let sp = shortest_path(&g, 0, t, 10, TraversalDirection::Both, None).unwrap();
+            assert_eq!(by_id[&t].distance as usize, sp.len() - 1);
+            assert!(by_id[&t].is_target);
+
+            // Parent pointers lead back to the root
+            let mut hops = 0;
+            let mut current = t;
+            while let Some(p) = by_id[&current].parent {
+                current = p;
+                hops += 1;
+            }
+            assert_eq!(current, 0);
+            assert_eq!(hops, by_id[&t].distance);
+        }
+
+        let root = by_id[&0];
+        assert_eq!(root.distance, 0);
+        assert!(root.parent.is_none() && root.rel_type.is_none());
+    }
+
+    #[test]
+    fn test_spt_prunes_to_target_branches() {
+        // Star: only the branches to the requested leaves are returned
+        let g = make_star(0, 20);
+        let tree = shortest_path_tree(&g, 0, &[3, 7], 5, TraversalDirection::Both, None);
+        let ids: Vec<NodeId> = tree.iter().map(|n| n.node_id).collect();
+        assert_eq!(ids, vec![0, 3, 7]);
+        assert_eq!(tree[1].rel_type.as_deref(), Some("HAS"));
+        assert_eq!(tree[1].direction, Some(Direction::Outgoing));
+    }
+
+    #[test]
+    fn test_spt_unreachable_targets_absent() {
+        let g = make_chain(6);
+        // 5 is beyond max_hops, 99 is not in the graph
+        let tree = shortest_path_tree(&g, 0, &[2, 5, 99], 3, TraversalDirection::Both, None);
+        let targets: Vec<NodeId> = tree.iter().filter(|n| n.is_target).map(|n| n.node_id).collect();
+        assert_eq!(targets, vec![2]);
+
+        let outgoing = shortest_path_tree(&g, 3, &[1], 10, TraversalDirection::Outgoing, None);
+        assert!(outgoing.is_empty());
+        assert!(shortest_path_tree(&g, 99, &[1], 10, TraversalDirection::Both, None).is_empty());
+    }
+
+    #[test]
+    fn test_spt_start_is_target() {
+        let g = make_chain(3);
+        let tree = shortest_path_tree(&g, 1, &[1], 0, TraversalDirection::Both, None);
+        assert_eq!(tree.len(), 1);
+        assert!(tree[0].is_target);
+        assert_eq!(tree[0].distance, 0);
     }
 
+    // --- Local clustering tests ---
+
     #[test]
-    fn test_degree_sorted() {
-        let g = make_star(0, 50);
-        let results = degree_centrality(&g, 0);
-        // Must be sorted descending by total_degree
-        for w in results.windows(2) {
-            assert!(
-                w[0].total_degree >= w[1].total_degree,
-                "not sorted: {} >= {} failed",
-                w[0].total_degree,
-                w[1].total_degree
-            );
+    fn test_clustering_triangle() {
+        let g = make_cycle(3);
+        let results = local_clustering(&g, 0);
+        assert_eq!(results.len(), 3);
+        for r in &results {
+            assert_eq!(r.degree, 2);
+            assert_eq!(r.triangles, 1);
+            assert_eq!(r.coefficient, 1.0);
         }
     }
 
     #[test]
-    fn test_degree_empty() {
-        let g = Graph::new();
-        let results = degree_centrality(&g, 10);
-        assert!(results.is_empty());
+    fn test_clustering_star_is_bridge() {
+        // Leaves are not linked to each other; leaves have degree 1
+        let g = make_star(0, 5);
+        let results = local_clustering(&g, 0);
+        assert!(results.iter().all(|r| r.coefficient == 0.0));
+        let hub = results.iter().find(|r| r.node_id == 0).unwrap();
+        assert_eq!(hub.degree, 5);
+        assert_eq!(hub.triangles, 0);
+    }
+
+    #[test]
+    fn test_clustering_partial() {
+        // Hub 0 with neighbors 1,2,3; only 1-2 linked (plus a parallel reverse
+        // edge and a self-loop, which must not be counted)
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "R"),
+            edge(0, 2, "R"),
+            edge(3, 0, "R"),
+            edge(1, 2, "R"),
+            edge(2, 1, "R"),
+            edge(0, 0, "R"),
+        ]);
+        let results = local_clustering(&g, 0);
+        let hub = results.iter().find(|r| r.node_id == 0).unwrap();
+        assert_eq!(hub.degree, 3);
+        assert_eq!(hub.triangles, 1);
+        assert!((hub.coefficient - 1.0 / 3.0).abs() < 1e-12);
+
+        // 1 and 2 sit in a closed triangle; they rank first
+        assert_eq!(results[0].coefficient, 1.0);
+        assert_eq!(results[1].coefficient, 1.0);
+
+        // Probing sorted adjacency counts the same triangles
+        g.freeze();
+        g.sort_by_target();
+        let sorted = local_clustering(&g, 0);
+        let triangles = |r: &[ClusteringResult]| -> Vec<(NodeId, u32)> {
+            r.iter().map(|c| (c.node_id, c.triangles)).collect()
+        };
+        assert_eq!(triangles(&sorted), triangles(&results));
+    }
+
+    #[test]
+    fn test_clustering_top_n() {
+        let g = make_star(0, 10);
+        assert_eq!(local_clustering(&g, 3).len(), 3);
+        assert!(local_clustering(&Graph::new(), 5).is_empty());
     }
 
     // --- Subgraph extraction tests ---
@@ -1175,6 +3957,20 @@ mod tests {
         assert_eq!(sub.edges.len(), 10);
     }
 
+    #[test]
+    fn test_subgraph_max_edges() {
+        // Budget of 3: discovery stops at 4 leaves, whose 4 edges overflow it
+        let g = make_star(0, 10);
+        let both = TraversalDirection::Both;
+        let sub = g.view().extract_subgraph(0, 1, both, 3);
+        assert_eq!(sub.node_count, 5);
+        assert_eq!(sub.edges.len(), 3);
+        assert!(sub.truncated);
+        let sub = g.view().extract_subgraph(0, 1, both, 10);
+        assert_eq!((sub.edges.len(), sub.truncated), (10, false));
+        assert!(!extract_subgraph(&g, 0, 1, both, None).truncated);
+    }
+
     #[test]
     fn test_subgraph_directed() {
         // Chain 0→1→2→3→4, outgoing from 2: reaches 3, 4
@@ -1214,16 +4010,10 @@ mod tests {
 
     // --- Confidence filtering tests ---
 
-    fn edge_conf(from: u64, to: u64, rel: &str, conf: f32) -> EdgeRecord {
+    fn edge_conf(from: NodeId, to: NodeId, rel: &str, conf: f32) -> EdgeRecord {
         EdgeRecord {
-            from_id: from,
-            to_id: to,
-            rel_type: rel.to_string(),
-            from_label: "Node".to_string(),
-            to_label: "Node".to_string(),
-            from_app_id: None,
-            to_app_id: None,
             confidence: conf,
+            ..edge(from, to, rel)
         }
     }
 
@@ -1237,11 +4027,11 @@ mod tests {
         ]);
 
         // No filter: finds both
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 2);
 
         // Filter at 0.5: only finds node 1 (edge to 2 blocked)
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, Some(0.5));
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, Some(0.5), 0);
         assert_eq!(result.neighbors.len(), 1);
         assert_eq!(result.neighbors[0].node_id, 1);
     }
@@ -1252,7 +4042,7 @@ mod tests {
         let mut g = Graph::new();
         g.load_edges(vec![edge(0, 1, "A")]); // edge() uses NO_CONFIDENCE = NAN
 
-        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, Some(0.99));
+        let result = bfs_neighborhood(&g, 0, 5, TraversalDirection::Both, Some(0.99), 0);
         assert_eq!(result.neighbors.len(), 1);
     }
 
@@ -1537,6 +4327,47 @@ mod tests {
         assert!(paths.is_empty());
     }
 
+    #[test]
+    fn test_ksp_between_sets() {
+        // Chain 0-1-2-3-4-5 closed into a ring by 0→5
+        let mut g = make_chain(6);
+        g.add_edge(0, 5, 0, 1.0);
+        let ids = |paths: Vec<Vec<PathStep>>| -> Vec<Vec<NodeId>> {
+            paths
+                .iter()
+                .map(|p| p.iter().map(|s| s.node_id).collect())
+                .collect()
+        };
+        let between = |sources: &[NodeId], targets: &[NodeId], max_hops, ex: &Exclusions| {
+            ids(k_shortest_paths_between(
+                &g,
+                sources,
+                targets,
+                max_hops,
+                5,
+                TraversalDirection::Both,
+                None,
+                ex,
+                |_| true,
+            ))
+        };
+        let none = Exclusions::new();
+
+        // No path runs through another member of either set
+        let paths = between(&[0, 1], &[4, 5], 10, &none);
+        assert_eq!(paths, vec![vec![0, 5], vec![1, 2, 3, 4]]);
+        assert_eq!(between(&[0, 1], &[4, 5], 2, &none), vec![vec![0, 5]]);
+
+        // A shared node is a zero-hop path; both ways round the ring follow
+        let paths = between(&[2], &[2, 4], 10, &none);
+        assert_eq!(paths, vec![vec![2], vec![2, 3, 4], vec![2, 1, 0, 5, 4]]);
+
+        let mut ex = Exclusions::new();
+        ex.exclude_node(0);
+        assert_eq!(between(&[0, 1], &[4, 5], 10, &ex), vec![vec![1, 2, 3, 4]]);
+        assert!(between(&[999], &[4], 10, &none).is_empty());
+    }
+
     // --- Two-phase loading tests (mimics ext load_vertices + load_edges) ---
 
     #[test]
@@ -1559,7 +4390,7 @@ mod tests {
         assert_eq!(g.neighbors_in(200).len(), 1);
 
         // Verify BFS finds neighbors
-        let result = bfs_neighborhood(&g, 100, 2, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, 100, 2, TraversalDirection::Both, None, 0);
         assert_eq!(
             result.neighbors.len(), 2,
             "BFS should find 2 neighbors from node 100, found {}",
@@ -1584,8 +4415,810 @@ mod tests {
         assert_eq!(resolved, 100);
 
         // BFS via resolved ID
-        let result = bfs_neighborhood(&g, resolved, 1, TraversalDirection::Both, None);
+        let result = bfs_neighborhood(&g, resolved, 1, TraversalDirection::Both, None, 0);
         assert_eq!(result.neighbors.len(), 1);
         assert_eq!(result.neighbors[0].node_id, 200);
     }
+
+    #[test]
+    fn test_multi_source_nearest_seed() {
+        // Chain 0..=9 seeded at both ends: each node goes to the closer end
+        let g = make_chain(10);
+        let result = bfs_multi_source(&g, &[0, 9], 10, TraversalDirection::Both, None);
+        assert_eq!(result.len(), 8);
+        for n in &result {
+            let expected = if n.node_id <= 4 { 0 } else { 9 };
+            assert_eq!(n.seed, expected, "node {}", n.node_id);
+            assert_eq!(n.distance as NodeId, n.node_id.abs_diff(expected));
+            assert_eq!(n.path_types.len(), n.distance as usize);
+        }
+    }
+
+    #[test]
+    fn test_multi_source_matches_single_source() {
+        let g = make_star(0, 6);
+        let multi = bfs_multi_source(&g, &[0], 2, TraversalDirection::Both, None);
+        let single = bfs_neighborhood(&g, 0, 2, TraversalDirection::Both, None, 0);
+        assert_eq!(multi.len(), single.neighbors.len());
+        assert!(multi.iter().all(|n| n.seed == 0 && n.distance == 1));
+    }
+
+    #[test]
+    fn test_multi_source_depth_and_missing_seeds() {
+        let g = make_chain(10);
+        // Depth 1 around seeds 2 and 5; unknown and duplicate seeds are ignored
+        let result = bfs_multi_source(&g, &[2, 5, 999, 2], 1, TraversalDirection::Both, None);
+        let ids: Vec<NodeId> = result.iter().map(|n| n.node_id).collect();
+        assert_eq!(ids, vec![1, 3, 4, 6]);
+        assert!(bfs_multi_source(&g, &[999], 3, TraversalDirection::Both, None).is_empty());
+        assert!(bfs_multi_source(&g, &[], 3, TraversalDirection::Both, None).is_empty());
+    }
+
+    #[test]
+    fn test_path_to_any_picks_nearest() {
+        let g = make_chain(10);
+        let path = shortest_path_to_any(&g, 4, &[0, 7, 9], 10, TraversalDirection::Both, None).unwrap();
+        let ids: Vec<NodeId> = path.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![4, 5, 6, 7]);
+        assert_eq!(path[3].rel_type.as_deref(), Some("NEXT"));
+
+        // Outgoing only: 0 is behind the start and unreachable
+        let path = shortest_path_to_any(&g, 4, &[0, 9], 10, TraversalDirection::Outgoing, None).unwrap();
+        assert_eq!(path.last().unwrap().node_id, 9);
+    }
+
+    #[test]
+    fn test_path_to_any_edge_cases() {
+        let g = make_chain(10);
+        let path = shortest_path_to_any(&g, 3, &[8, 3], 10, TraversalDirection::Both, None).unwrap();
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].rel_type, None);
+
+        assert!(shortest_path_to_any(&g, 0, &[9], 5, TraversalDirection::Both, None).is_none());
+        assert!(shortest_path_to_any(&g, 0, &[999], 10, TraversalDirection::Both, None).is_none());
+        assert!(shortest_path_to_any(&g, 0, &[], 10, TraversalDirection::Both, None).is_none());
+        assert!(shortest_path_to_any(&g, 999, &[1], 10, TraversalDirection::Both, None).is_none());
+    }
+
+    #[test]
+    fn test_exclusions_route_around_node() {
+        // Diamond 0→1→3, 0→2→3 plus a long way round 0→4→5→3
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "A"),
+            edge(1, 3, "A"),
+            edge(0, 2, "B"),
+            edge(2, 3, "B"),
+            edge(0, 4, "C"),
+            edge(4, 5, "C"),
+            edge(5, 3, "C"),
+        ]);
+        let mut ex = Exclusions::new();
+        ex.exclude_node(1);
+        ex.exclude_node(2);
+        let (view, both) = (g.view().with_exclusions(&ex), TraversalDirection::Both);
+        let path = view.shortest_path(0, 3, 10, both).unwrap();
+        let ids: Vec<NodeId> = path.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![0, 4, 5, 3]);
+
+        // Excluded endpoints have no path
+        assert!(view.shortest_path(1, 3, 10, both).is_none());
+        assert!(view.shortest_path(3, 2, 10, both).is_none());
+    }
+
+    #[test]
+    fn test_exclusions_edge_blocks_both_directions() {
+        let g = make_chain(4);
+        let mut ex = Exclusions::new();
+        ex.exclude_edge(2, 1);
+        let (view, both) = (g.view().with_exclusions(&ex), TraversalDirection::Both);
+        assert!(view.shortest_path(0, 3, 10, both).is_none());
+        assert!(view.shortest_path(3, 0, 10, both).is_none());
+        assert!(view.shortest_path(2, 3, 10, both).is_some());
+    }
+
+    #[test]
+    fn test_exclusions_allow_rel_types() {
+        // Diamond as above plus an A shortcut 4→2; B and C are allowed
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "A"),
+            edge(1, 3, "A"),
+            edge(0, 2, "B"),
+            edge(2, 3, "B"),
+            edge(0, 4, "C"),
+            edge(4, 5, "C"),
+            edge(5, 3, "C"),
+            edge(4, 2, "A"),
+        ]);
+        let both = TraversalDirection::Both;
+        let mut ex = Exclusions::new();
+        ex.allow_rel_type(g.rel_type_id("C").unwrap());
+        ex.allow_rel_type(g.rel_type_id("B").unwrap());
+
+        let view = g.view().with_exclusions(&ex);
+        let hood = view.bfs_neighborhood(0, 1, both, 0);
+        let mut ids: Vec<NodeId> = hood.neighbors.iter().map(|n| n.node_id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![2, 4]);
+
+        let paths = view.k_shortest_paths(0, 3, 10, 5, both);
+        let routes: Vec<Vec<NodeId>> = paths
+            .iter()
+            .map(|p| p.iter().map(|s| s.node_id).collect())
+            .collect();
+        assert_eq!(routes, vec![vec![0, 2, 3], vec![0, 4, 5, 3]]);
+        let weighted = view.k_shortest_paths_weighted(0, 3, 10, 5, both, Edge::hop_cost);
+        assert_eq!(weighted.len(), 2);
+
+        // The ball {0, 2, 3, 4, 5}, without the A edge inside it
+        let sub = view.extract_subgraph(0, 2, both, 0);
+        assert_eq!(sub.node_count, 5);
+        assert!(sub.edges.iter().all(|e| e.rel_type != "A"));
+        assert_eq!(sub.edges.len(), 5);
+    }
+
+    #[test]
+    fn test_node_filter_on_labels() {
+        // Concepts 0, 2, 3, 4 linked directly and through Sources 1 and 5
+        let mut g = Graph::new();
+        let rt = g.intern_rel_type("LINKS");
+        for (id, label) in [(0, "Concept"), (1, "Source"), (2, "Concept")] {
+            g.add_node(id, label.into(), None);
+        }
+        for (id, label) in [(3, "Concept"), (4, "Concept"), (5, "Source")] {
+            g.add_node(id, label.into(), None);
+        }
+        let direct = [(0, 2), (2, 3), (0, 4), (4, 3)];
+        for (from, to) in direct.into_iter().chain([(0, 1), (1, 3), (4, 5), (5, 3)]) {
+            g.add_edge(from, to, rt, 1.0);
+        }
+        let concepts = g.label_set(["Concept"]);
+        let is_concept = |id| g.node_in_label_set(id, &concepts);
+        let view = g.view().with_node_filter(is_concept);
+        let both = TraversalDirection::Both;
+
+        let mut paths: Vec<Vec<NodeId>> = view
+            .k_shortest_paths(0, 3, 10, 5, both)
+            .iter()
+            .map(|p| p.iter().map(|s| s.node_id).collect())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec![vec![0, 2, 3], vec![0, 4, 3]]);
+        let weighted = view.k_shortest_paths_weighted(0, 3, 10, 5, both, Edge::hop_cost);
+        assert_eq!(weighted.len(), 2);
+        for step in weighted.iter().flat_map(|p| &p.steps) {
+            assert_eq!(step.label, "Concept");
+        }
+
+        let sub = view.extract_subgraph(0, 3, both, 0);
+        assert_eq!((sub.node_count, sub.edges.len()), (4, 4));
+    }
+
+    #[test]
+    fn test_node_filter_on_properties() {
+        // Diamond as above; node 1 is archived, node 4 has no status at all
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(0, 1, "A"),
+            edge(1, 3, "A"),
+            edge(0, 4, "C"),
+            edge(4, 5, "C"),
+            edge(5, 3, "C"),
+        ]);
+        let status = |v: &str| vec![("status".to_string(), PropValue::Text(v.to_string()))];
+        for id in [0, 3, 5] {
+            assert!(g.set_node_properties(id, status("active")));
+        }
+        g.set_node_properties(1, status("archived"));
+        assert!(!g.set_node_properties(999, status("active")));
+        g.set_node_properties(4, status("active"));
+
+        let active = g.view().with_node_filter(|id| {
+            g.node_property(id, "status").and_then(PropValue::as_str) == Some("active")
+        });
+        let both = TraversalDirection::Both;
+        let path = active.shortest_path(0, 3, 10, both).unwrap();
+        let ids: Vec<NodeId> = path.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![0, 4, 5, 3]);
+        assert!(active.shortest_path(0, 1, 10, both).is_none());
+
+        // Start is exempt from the filter
+        let result = active.bfs_neighborhood(1, 1, both, 0);
+        let mut reached: Vec<NodeId> = result.neighbors.iter().map(|n| n.node_id).collect();
+        reached.sort_unstable();
+        assert_eq!(reached, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_bfs_excluding_prunes_behind_excluded() {
+        let g = make_chain(6);
+        let mut ex = Exclusions::new();
+        ex.exclude_node(3);
+        let (view, both) = (g.view().with_exclusions(&ex), TraversalDirection::Both);
+        let result = view.bfs_neighborhood(0, 10, both, 0);
+        let mut ids: Vec<NodeId> = result.neighbors.iter().map(|n| n.node_id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![1, 2]);
+
+        assert!(view.bfs_neighborhood(3, 10, both, 0).neighbors.is_empty());
+
+        // Empty exclusions match the plain BFS
+        let plain = bfs_neighborhood(&g, 0, 10, both, None, 0);
+        let none = g.view().with_exclusions(&Exclusions::new());
+        let none = none.bfs_neighborhood(0, 10, both, 0);
+        assert_eq!(plain.neighbors.len(), none.neighbors.len());
+    }
+
+    fn weighted_edge(from: NodeId, to: NodeId, confidence: f32) -> EdgeRecord {
+        EdgeRecord {
+            confidence,
+            ..edge(from, to, "R")
+        }
+    }
+
+    #[test]
+    fn test_weighted_ksp_prefers_confident_detour() {
+        // Direct 0→3 is weak; 0→1→2→3 is strong; 0→4→3 is middling
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            weighted_edge(0, 3, 0.1),
+            weighted_edge(0, 1, 0.99),
+            weighted_edge(1, 2, 0.99),
+            weighted_edge(2, 3, 0.99),
+            weighted_edge(0, 4, 0.7),
+            weighted_edge(4, 3, 0.7),
+        ]);
+        let paths = k_shortest_paths_weighted(&g, 0, 3, 10, 5, TraversalDirection::Outgoing, None, Edge::confidence_cost);
+        let seqs: Vec<Vec<NodeId>> = paths
+            .iter()
+            .map(|p| p.steps.iter().map(|s| s.node_id).collect())
+            .collect();
+        assert_eq!(seqs, vec![vec![0, 1, 2, 3], vec![0, 4, 3], vec![0, 3]]);
+        assert!(paths.windows(2).all(|w| w[0].total_cost <= w[1].total_cost));
+        assert!((paths[2].total_cost - -(0.1f64).ln()).abs() < 1e-6);
+
+        // A hop budget of 2 rules out the cheapest path
+        let paths = k_shortest_paths_weighted(&g, 0, 3, 2, 5, TraversalDirection::Outgoing, None, Edge::confidence_cost);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].steps.len(), 3);
+    }
+
+    #[test]
+    fn test_weighted_ksp_by_edge_weight_ignores_confidence() {
+        // 0→3 is short but heavy; 0→1→2→3 is light; confidence says the opposite
+        let rec = |from: NodeId, to: NodeId, weight: f32, confidence: f32| EdgeRecord {
+            weight,
+            confidence,
+            ..edge(from, to, "R")
+        };
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            rec(0, 3, 10.0, 0.99),
+            rec(0, 1, 1.0, 0.1),
+            rec(1, 2, 1.5, 0.1),
+            rec(2, 3, 2.0, 0.1),
+            rec(1, 3, -5.0, 0.1),
+        ]);
+        let out = TraversalDirection::Outgoing;
+        let paths = k_shortest_paths_weighted(&g, 0, 3, 10, 3, out, None, Edge::weight_cost);
+        let seqs: Vec<Vec<NodeId>> = paths
+            .iter()
+            .map(|p| p.steps.iter().map(|s| s.node_id).collect())
+            .collect();
+        // The negative weight on 1→3 falls back to the default of 1.0
+        assert_eq!(seqs, vec![vec![0, 1, 3], vec![0, 1, 2, 3], vec![0, 3]]);
+        assert_eq!(paths[0].total_cost, 2.0);
+        assert_eq!(paths[1].total_cost, 4.5);
+
+        let by_conf = k_shortest_paths_weighted(&g, 0, 3, 10, 1, out, None, Edge::confidence_cost);
+        assert_eq!(by_conf[0].steps.len(), 2);
+    }
+
+    #[test]
+    fn test_weighted_ksp_unit_cost_matches_hops() {
+        let g = make_cycle(6);
+        let weighted = k_shortest_paths_weighted(&g, 0, 3, 10, 3, TraversalDirection::Both, None, |_| 1.0);
+        let hops = k_shortest_paths(&g, 0, 3, 10, 3, TraversalDirection::Both, None);
+        assert_eq!(weighted.len(), hops.len());
+        for (w, h) in weighted.iter().zip(&hops) {
+            assert_eq!(w.steps.len(), h.len());
+            assert_eq!(w.total_cost, (h.len() - 1) as f64);
+        }
+        assert!(k_shortest_paths_weighted(&g, 0, 3, 10, 0, TraversalDirection::Both, None, |_| 1.0).is_empty());
+        assert!(k_shortest_paths_weighted(&g, 0, 99, 10, 3, TraversalDirection::Both, None, |_| 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_shortest_path_weighted_matches_first_ksp() {
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            weighted_edge(0, 1, 0.1),
+            weighted_edge(1, 3, 0.1),
+            weighted_edge(0, 2, 0.9),
+            weighted_edge(2, 4, 0.9),
+            weighted_edge(4, 3, 0.9),
+        ]);
+        let out = TraversalDirection::Outgoing;
+        let best = shortest_path_weighted(&g, 0, 3, 10, out, None, Edge::confidence_cost).unwrap();
+        let ids: Vec<NodeId> = best.steps.iter().map(|s| s.node_id).collect();
+        assert_eq!(ids, vec![0, 2, 4, 3]);
+        let ksp = k_shortest_paths_weighted(&g, 0, 3, 10, 1, out, None, Edge::confidence_cost);
+        assert_eq!(best.total_cost, ksp[0].total_cost);
+
+        let hops = shortest_path_weighted(&g, 0, 3, 10, out, None, Edge::hop_cost).unwrap();
+        assert_eq!((hops.steps.len(), hops.total_cost), (3, 2.0));
+        assert!(shortest_path_weighted(&g, 0, 3, 1, out, None, Edge::hop_cost).is_none());
+    }
+
+    #[test]
+    fn test_bfs_max_nodes_truncates_nearest_first() {
+        // Star with 10 leaves, each leaf with a tail node: 10 at depth 1, 10 at depth 2
+        let mut g = make_star(0, 10);
+        g.load_edges((1..=10).map(|i| edge(i, 100 + i, "TAIL")));
+
+        let full = bfs_neighborhood(&g, 0, 2, TraversalDirection::Both, None, 0);
+        assert_eq!(full.neighbors.len(), 20);
+        assert!(!full.truncated);
+
+        let capped = bfs_neighborhood(&g, 0, 2, TraversalDirection::Both, None, 12);
+        assert_eq!(capped.neighbors.len(), 12);
+        assert!(capped.truncated);
+        assert_eq!(capped.neighbors.iter().filter(|n| n.distance == 1).count(), 10);
+
+        // A budget the result fits in exactly is not a truncation
+        let exact = bfs_neighborhood(&g, 0, 2, TraversalDirection::Both, None, 20);
+        assert_eq!(exact.neighbors.len(), 20);
+        assert!(!exact.truncated);
+    }
+
+    #[test]
+    fn test_ego_stats_star() {
+        let mut g = make_star(0, 4);
+        g.load_edges(vec![edge(1, 2, "LINK"), edge(1, 2, "LINK"), edge(4, 50, "LINK")]);
+
+        let stats = ego_stats(&g, 0, 1, TraversalDirection::Both, None).unwrap();
+        assert_eq!(stats.node_count, 5);
+        assert_eq!(stats.edge_count, 6);
+        // 5 distinct connected pairs out of 10
+        assert!((stats.density - 0.5).abs() < 1e-9);
+        assert_eq!(
+            stats.rel_types,
+            vec![("HAS".to_string(), 4), ("LINK".to_string(), 2)]
+        );
+
+        // Counts agree with the materialized subgraph
+        let sub = extract_subgraph(&g, 0, 2, TraversalDirection::Both, None);
+        let stats = ego_stats(&g, 0, 2, TraversalDirection::Both, None).unwrap();
+        assert_eq!(stats.node_count, sub.node_count);
+        assert_eq!(stats.edge_count, sub.edges.len());
+    }
+
+    #[test]
+    fn test_ego_stats_isolated_and_missing() {
+        let mut g = Graph::new();
+        g.add_node(7, "Node".to_string(), None);
+        let stats = ego_stats(&g, 7, 3, TraversalDirection::Both, None).unwrap();
+        assert_eq!((stats.node_count, stats.edge_count), (1, 0));
+        assert_eq!(stats.density, 0.0);
+        assert!(stats.rel_types.is_empty());
+        assert!(ego_stats(&g, 8, 3, TraversalDirection::Both, None).is_none());
+    }
+
+    #[test]
+    fn test_induced_subgraph() {
+        // Chain 0→1→2→3→4 plus chord 0→2
+        let mut g = make_chain(5);
+        g.load_edges(vec![edge(0, 2, "CHORD")]);
+
+        let sub = induced_subgraph(&g, &[0, 2, 1, 2, 999], None);
+        assert_eq!(sub.node_count, 3);
+        let mut pairs: Vec<(NodeId, NodeId)> = sub.edges.iter().map(|e| (e.from_id, e.to_id)).collect();
+        pairs.sort_unstable();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 2)]);
+
+        // Non-adjacent nodes have no interconnections
+        let sub = induced_subgraph(&g, &[0, 4], None);
+        assert_eq!(sub.node_count, 2);
+        assert!(sub.edges.is_empty());
+        assert_eq!(induced_subgraph(&g, &[], None).node_count, 0);
+    }
+
+    #[test]
+    fn test_bfs_distances_match_neighborhood() {
+        let mut g = make_star(0, 5);
+        g.load_edges((1..=5).map(|i| edge(i, 10 + i, "TAIL")));
+
+        let dist = bfs_distances(&g, 0, 2, TraversalDirection::Both, None);
+        assert_eq!(dist[0], (0, 0));
+        assert!(dist.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        let bfs = bfs_neighborhood(&g, 0, 2, TraversalDirection::Both, None, 0);
+        let mut expected: Vec<(NodeId, u32)> = bfs.neighbors.iter().map(|n| (n.node_id, n.distance)).collect();
+        expected.push((0, 0));
+        expected.sort_unstable();
+        let mut actual = dist.clone();
+        actual.sort_unstable();
+        assert_eq!(actual, expected);
+
+        assert_eq!(bfs_distances(&g, 0, 0, TraversalDirection::Both, None), vec![(0, 0)]);
+        assert!(bfs_distances(&g, 999, 3, TraversalDirection::Both, None).is_empty());
+    }
+
+    #[test]
+    fn test_bfs_visit_stops_early_and_reports_parents() {
+        let g = make_chain(5);
+
+        let mut seen = Vec::new();
+        let found = bfs_visit(
+            &g,
+            0,
+            10,
+            TraversalDirection::Outgoing,
+            None,
+            |node, depth, via| {
+                seen.push((node, depth, via.map(|(parent, e, _)| (parent, e.target))));
+                if node == 2 {
+                    ControlFlow::Break(depth)
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        );
+        assert_eq!(found, ControlFlow::Break(2));
+        assert_eq!(
+            seen,
+            vec![(0, 0, None), (1, 1, Some((0, 1))), (2, 2, Some((1, 2)))]
+        );
+
+        // Aggregate within a depth limit, walking edges backwards
+        let mut total = 0;
+        let done = bfs_visit(
+            &g,
+            4,
+            2,
+            TraversalDirection::Incoming,
+            None,
+            |node, _, via| {
+                assert!(via.is_none_or(|(parent, e, dir)| {
+                    (parent, e.target, dir) == (node + 1, node, Direction::Incoming)
+                }));
+                total += node;
+                ControlFlow::<()>::Continue(())
+            },
+        );
+        assert_eq!(done, ControlFlow::Continue(()));
+        assert_eq!(total, 4 + 3 + 2);
+
+        let mut calls = 0;
+        let _ = bfs_visit(&g, 999, 3, TraversalDirection::Both, None, |_, _, _| {
+            calls += 1;
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_pairwise_distances() {
+        // 0→1→2→3, plus 5→4; 9 is unknown and 2 is listed twice
+        let mut g = make_chain(4);
+        g.add_node(4, "Node".into(), None);
+        g.add_node(5, "Node".into(), None);
+        g.add_edge(5, 4, 0, 1.0);
+        let nodes = [0, 2, 3, 4, 5, 9, 2];
+
+        let both = pairwise_distances(&g, &nodes, 10, TraversalDirection::Both, None);
+        assert_eq!(
+            both,
+            vec![
+                (0, 2, 2),
+                (0, 3, 3),
+                (2, 0, 2),
+                (2, 3, 1),
+                (3, 0, 3),
+                (3, 2, 1),
+                (4, 5, 1),
+                (5, 4, 1),
+            ]
+        );
+
+        let out = pairwise_distances(&g, &nodes, 2, TraversalDirection::Outgoing, None);
+        assert_eq!(out, vec![(0, 2, 2), (2, 3, 1), (5, 4, 1)]);
+
+        // More sources than one pass holds agree with one BFS per source
+        let big = make_chain(150);
+        let ids: Vec<NodeId> = (0..150).step_by(2).collect();
+        let pairs = pairwise_distances(&big, &ids, 5, TraversalDirection::Both, None);
+        let mut expected = Vec::new();
+        for &a in &ids {
+            for (b, d) in bfs_distances(&big, a, 5, TraversalDirection::Both, None) {
+                if b != a && b % 2 == 0 {
+                    expected.push((a, b, d));
+                }
+            }
+        }
+        expected.sort_unstable();
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn test_path_exists() {
+        let chain = make_chain(5);
+        let mut frozen = make_chain(5);
+        frozen.freeze();
+        for g in [&chain, &frozen] {
+            let out = TraversalDirection::Outgoing;
+            assert!(path_exists(g, 0, 4, 10, out, None));
+            assert!(!path_exists(g, 4, 0, 10, out, None));
+            assert!(path_exists(g, 4, 0, 10, TraversalDirection::Both, None));
+            assert!(!path_exists(g, 0, 4, 3, out, None));
+            assert!(path_exists(g, 2, 2, 0, out, None));
+            assert!(!path_exists(g, 0, 999, 10, out, None));
+        }
+    }
+
+    #[test]
+    fn test_hop_distance() {
+        let mut g = make_chain(6);
+        g.add_edge(0, 3, 0, 1.0);
+        for frozen in [false, true] {
+            if frozen {
+                g.freeze();
+            }
+            let (out, both) = (TraversalDirection::Outgoing, TraversalDirection::Both);
+            assert_eq!(hop_distance(&g, 0, 5, 10, out, None), Some(3));
+            assert_eq!(hop_distance(&g, 0, 5, 2, out, None), None);
+            assert_eq!(hop_distance(&g, 5, 0, 10, out, None), None);
+            assert_eq!(hop_distance(&g, 5, 0, 10, both, None), Some(3));
+            assert_eq!(hop_distance(&g, 4, 4, 0, out, None), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_neighborhood_stream_matches_eager() {
+        let mut g = Graph::new();
+        g.load_edges((0..60).flat_map(|i| {
+            let rel = if i % 3 == 0 { "A" } else { "B" };
+            [
+                edge(i, (i * 7 + 3) % 60, rel),
+                edge(i, (i * 13 + 5) % 60, "A"),
+            ]
+        }));
+        let frozen = std::sync::Arc::new(g.into_frozen());
+        let mut exclusions = Exclusions::new();
+        exclusions.exclude_node(10);
+        let row = |n: NeighborResult| (n.node_id, n.distance, n.path_types, n.path_directions);
+
+        for max_nodes in [0, 7] {
+            let filter = |id: NodeId| id % 5 != 1;
+            let both = TraversalDirection::Both;
+            let eager = frozen
+                .view()
+                .with_exclusions(&exclusions)
+                .with_node_filter(filter)
+                .bfs_neighborhood(0, 4, both, max_nodes);
+            let stream = bfs_neighborhood_stream(
+                frozen.clone(),
+                0,
+                4,
+                both,
+                None,
+                max_nodes,
+                &exclusions,
+                filter,
+            );
+            let mut stream = stream;
+            let streamed: Vec<_> = stream.by_ref().map(row).collect();
+            assert_eq!(stream.truncated(), eager.truncated);
+            assert_eq!(
+                streamed,
+                eager.neighbors.into_iter().map(row).collect::<Vec<_>>()
+            );
+        }
+        let missing = bfs_neighborhood_stream(
+            &*frozen,
+            999,
+            4,
+            TraversalDirection::Both,
+            None,
+            0,
+            &exclusions,
+            |_| true,
+        );
+        assert_eq!(missing.count(), 0);
+    }
+
+    #[test]
+    fn test_neighborhood_stream_retain_nodes() {
+        // Only 3 is kept, but it is reached through 1 and 2
+        let frozen = std::sync::Arc::new(make_chain(5).into_frozen());
+        let out = TraversalDirection::Outgoing;
+        let stream = bfs_neighborhood_stream(
+            frozen.clone(),
+            0,
+            3,
+            out,
+            None,
+            0,
+            &Exclusions::new(),
+            |_| true,
+        )
+        .retain_nodes(|id| id == 3);
+        let rows: Vec<_> = stream.collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!((rows[0].node_id, rows[0].distance), (3, 3));
+        assert_eq!(rows[0].path_types.len(), 3);
+    }
+
+    #[test]
+    fn test_neighborhood_stream_as_of() {
+        // Chain 0 → 1 → 2 → 3, edge i → i + 1 created at 100 * i
+        let mut g = Graph::new();
+        g.load_edges((0..3).map(|i| EdgeRecord {
+            created_at: 100 * i as u32,
+            ..edge(i, i + 1, "NEXT")
+        }));
+        let frozen = std::sync::Arc::new(g.into_frozen());
+        let stream = bfs_neighborhood_stream(
+            frozen,
+            0,
+            5,
+            TraversalDirection::Outgoing,
+            None,
+            0,
+            &Exclusions::new(),
+            |_| true,
+        )
+        .as_of(150);
+        let found: Vec<NodeId> = stream.map(|nr| nr.node_id).collect();
+        assert_eq!(found, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_neighborhood_stream_expands_lazily() {
+        // A 1000-node chain: reading two rows visits only three nodes
+        let frozen = std::sync::Arc::new(make_chain(1000).into_frozen());
+        let out = TraversalDirection::Outgoing;
+        let mut stream = bfs_neighborhood_stream(
+            frozen.clone(),
+            0,
+            2000,
+            out,
+            None,
+            0,
+            &Exclusions::new(),
+            |_| true,
+        );
+        let first: Vec<NodeId> = stream.by_ref().take(2).map(|r| r.node_id).collect();
+        assert_eq!(first, vec![1, 2]);
+        assert_eq!(stream.nodes_visited(), 3);
+        assert!(!stream.truncated());
+        assert_eq!(stream.count(), 997);
+
+        // The row that uses up max_nodes already knows the rest was cut
+        let mut capped =
+            bfs_neighborhood_stream(frozen, 0, 2000, out, None, 2, &Exclusions::new(), |_| true);
+        assert_eq!(capped.next().map(|r| r.node_id), Some(1));
+        assert!(!capped.truncated());
+        assert_eq!(capped.next().map(|r| r.node_id), Some(2));
+        assert!(capped.truncated());
+        assert!(capped.next().is_none());
+        assert_eq!(capped.nodes_visited(), 3);
+    }
+
+    #[test]
+    fn test_dense_bfs_matches_hashed() {
+        let build = || {
+            let mut g = Graph::new();
+            g.load_edges((0..60).flat_map(|i| {
+                let rel = if i % 3 == 0 { "A" } else { "B" };
+                let next = (i * 13 + 5) % 60;
+                [edge(i, (i * 7 + 3) % 60, rel), edge(i, next, "A")]
+            }));
+            g
+        };
+        let lists = build();
+        let mut frozen = build();
+        frozen.freeze();
+
+        let summary = |r: TraversalResult| {
+            let mut rows: Vec<_> = r
+                .neighbors
+                .into_iter()
+                .map(|n| (n.node_id, n.distance, n.path_types, n.path_directions))
+                .collect();
+            rows.sort_by_key(|row| row.0);
+            (rows, r.nodes_visited, r.truncated)
+        };
+        let mut exclusions = Exclusions::new();
+        exclusions.exclude_node(10);
+        exclusions.exclude_edge(0, 3);
+        for max_nodes in [0, 7] {
+            let run = |g: &Graph| {
+                let view = g
+                    .view()
+                    .with_exclusions(&exclusions)
+                    .with_node_filter(|id| id % 5 != 1);
+                summary(view.bfs_neighborhood(0, 4, TraversalDirection::Both, max_nodes))
+            };
+            assert_eq!(run(&frozen), run(&lists));
+        }
+
+        let ids = |paths: Vec<Vec<PathStep>>| -> Vec<Vec<(NodeId, Option<String>)>> {
+            paths
+                .into_iter()
+                .map(|p| p.into_iter().map(|s| (s.node_id, s.rel_type)).collect())
+                .collect()
+        };
+        for dir in [TraversalDirection::Outgoing, TraversalDirection::Both] {
+            let yen = |g: &Graph| ids(k_shortest_paths(g, 0, 42, 6, 4, dir, None));
+            assert!(!yen(&frozen).is_empty());
+            assert_eq!(yen(&frozen), yen(&lists));
+        }
+        assert!(shortest_path(&frozen, 0, 10, 6, TraversalDirection::Both, None).is_some());
+        let around = frozen.view().with_exclusions(&exclusions);
+        let both = TraversalDirection::Both;
+        assert!(around.shortest_path(0, 10, 6, both).is_none());
+    }
+
+    #[test]
+    fn test_dense_searches_match_hashed() {
+        let build = || {
+            let mut g = Graph::new();
+            g.load_edges((0..60).flat_map(|i| {
+                let rel = if i % 3 == 0 { "A" } else { "B" };
+                let next = (i * 13 + 5) % 60;
+                [edge(i, (i * 7 + 3) % 60, rel), edge(i, next, "A")]
+            }));
+            g
+        };
+        let lists = build();
+        let mut frozen = build();
+        frozen.freeze();
+
+        for dir in [TraversalDirection::Outgoing, TraversalDirection::Both] {
+            // Debug output covers every field, paths included
+            let run = |g: &Graph| {
+                let mut visits = Vec::new();
+                let _ = bfs_visit(g, 0, 3, dir, None, |id, depth, via| {
+                    visits.push((id, depth, via.map(|(p, e, d)| (p, e.rel_type, d))));
+                    ControlFlow::<()>::Continue(())
+                });
+                let stop_at_five = bfs_visit(g, 0, 9, dir, None, |id, _, _| {
+                    if id == 5 {
+                        ControlFlow::Break(id)
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                });
+                let between = k_shortest_paths_between(
+                    g,
+                    &[0, 1],
+                    &[42, 17],
+                    6,
+                    4,
+                    dir,
+                    None,
+                    &Exclusions::new(),
+                    |id| id != 30,
+                );
+                format!(
+                    "{:?}",
+                    (
+                        visits,
+                        stop_at_five,
+                        bfs_multi_source(g, &[0, 20, 0, 99], 3, dir, None),
+                        pairwise_distances(g, &[0, 7, 42, 7, 99], 4, dir, None),
+                        shortest_path_to_any(g, 0, &[42, 17, 99], 6, dir, None),
+                        shortest_path_tree(g, 0, &[42, 17, 0, 99], 6, dir, None),
+                        all_shortest_paths(g, 0, 42, 6, 10, dir, None),
+                        between,
+                    )
+                )
+            };
+            assert_eq!(run(&frozen), run(&lists));
+        }
+        let all = all_shortest_paths(&frozen, 0, 42, 6, 10, TraversalDirection::Both, None);
+        assert!(all.len() > 1);
+    }
 }
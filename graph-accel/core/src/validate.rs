@@ -0,0 +1,214 @@
+//! Internal consistency checks, for asserting health after experimental
+//! load paths.
+
+use std::collections::HashMap;
+
+use crate::graph::{Graph, NodeId, RelTypeId};
+
+/// An invariant checked by `Graph::validate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Invariant {
+    /// Every edge's endpoints have a `NodeInfo`. Edges added with bare
+    /// `add_edge` between unregistered nodes fail this.
+    EdgeEndpoints,
+    /// Outgoing and incoming lists hold the same edges, so every
+    /// `a -[r]-> b` out of `a` appears as an incoming edge at `b`.
+    MirroredEdges,
+    /// App ID and secondary index entries resolve to existing nodes.
+    IndexTargets,
+    /// Every rel type ID, on edges and edge property bags, is interned.
+    RelTypeIds,
+}
+
+impl Invariant {
+    pub const ALL: [Invariant; 4] = [
+        Invariant::EdgeEndpoints,
+        Invariant::MirroredEdges,
+        Invariant::IndexTargets,
+        Invariant::RelTypeIds,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Invariant::EdgeEndpoints => "edge_endpoints",
+            Invariant::MirroredEdges => "mirrored_edges",
+            Invariant::IndexTargets => "index_targets",
+            Invariant::RelTypeIds => "rel_type_ids",
+        }
+    }
+}
+
+/// One broken invariant: how often it fails and the first failure found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub invariant: Invariant,
+    pub count: usize,
+    pub example: String,
+}
+
+/// Counts failures of each invariant, keeping the first example.
+#[derive(Default)]
+struct Report {
+    violations: Vec<Violation>,
+}
+
+impl Report {
+    fn fail(&mut self, invariant: Invariant, example: impl FnOnce() -> String) {
+        match self
+            .violations
+            .iter_mut()
+            .find(|v| v.invariant == invariant)
+        {
+            Some(v) => v.count += 1,
+            None => self.violations.push(Violation {
+                invariant,
+                count: 1,
+                example: example(),
+            }),
+        }
+    }
+}
+
+impl Graph {
+    /// Check the graph's internal invariants (see `Invariant`).
+    ///
+    /// Returns one `Violation` per broken invariant, in `Invariant` order;
+    /// empty means healthy. O(V + E) time, plus O(E) memory to match the
+    /// two adjacency directions.
+    pub fn validate(&self) -> Vec<Violation> {
+        let mut report = Report::default();
+        let rel_type_count = self.rel_type_count();
+        let rel_type_ok = |rt: RelTypeId| (rt as usize) < rel_type_count;
+
+        // +1 per outgoing copy, -1 per incoming copy; any residue is unmatched
+        let mut balance: HashMap<(NodeId, NodeId, RelTypeId), i64> = HashMap::new();
+        for id in self.sorted_ids() {
+            for e in self.neighbors_out(id) {
+                for end in [id, e.target] {
+                    if !self.nodes.contains_key(&end) {
+                        report.fail(Invariant::EdgeEndpoints, || {
+                            format!("edge {} -> {}: node {} has no metadata", id, e.target, end)
+                        });
+                    }
+                }
+                if !rel_type_ok(e.rel_type) {
+                    report.fail(Invariant::RelTypeIds, || {
+                        format!("edge {} -> {}: rel type id {}", id, e.target, e.rel_type)
+                    });
+                }
+                *balance.entry((id, e.target, e.rel_type)).or_insert(0) += 1;
+            }
+            for e in self.neighbors_in(id) {
+                *balance.entry((e.target, id, e.rel_type)).or_insert(0) -= 1;
+            }
+        }
+        let mut unmatched: Vec<_> = balance.into_iter().filter(|&(_, n)| n != 0).collect();
+        unmatched.sort_unstable();
+        for ((from, to, rt), n) in unmatched {
+            let side = if n > 0 { "outgoing" } else { "incoming" };
+            report.fail(Invariant::MirroredEdges, || {
+                format!(
+                    "edge {} -> {} (rel type id {}) only in {} lists",
+                    from, to, rt, side
+                )
+            });
+        }
+
+        let exists = |id: &NodeId| self.nodes.contains_key(id);
+        let mut app_ids: Vec<_> = self
+            .app_id_index
+            .iter()
+            .filter(|(_, id)| !exists(id))
+            .collect();
+        app_ids.sort_unstable();
+        for (aid, id) in app_ids {
+            report.fail(Invariant::IndexTargets, || {
+                format!("app id '{}' points at missing node {}", aid, id)
+            });
+        }
+        let mut int_ids: Vec<_> = self
+            .int_app_id_index
+            .iter()
+            .filter(|(_, id)| !exists(id))
+            .collect();
+        int_ids.sort_unstable();
+        for (aid, id) in int_ids {
+            report.fail(Invariant::IndexTargets, || {
+                format!("integer app id {} points at missing node {}", aid, id)
+            });
+        }
+        for name in self.index_names() {
+            let mut entries: Vec<_> = self.secondary_indices[name]
+                .iter()
+                .filter(|(_, id)| !exists(id))
+                .collect();
+            entries.sort_unstable();
+            for (value, id) in entries {
+                report.fail(Invariant::IndexTargets, || {
+                    format!(
+                        "index '{}' value '{}' points at missing node {}",
+                        name, value, id
+                    )
+                });
+            }
+        }
+
+        let mut bad_props: Vec<_> = self
+            .edge_props
+            .keys()
+            .filter(|&&(_, _, rt)| !rel_type_ok(rt))
+            .collect();
+        bad_props.sort_unstable();
+        for (from, to, rt) in bad_props {
+            report.fail(Invariant::RelTypeIds, || {
+                format!("properties of edge {} -> {}: rel type id {}", from, to, rt)
+            });
+        }
+
+        report.violations.sort_by_key(|v| v.invariant);
+        report.violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Invariant;
+    use crate::graph::{Edge, Graph};
+    use crate::test_util::edge;
+
+    #[test]
+    fn test_validate_reports_broken_invariants() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(1, 2, "R"), edge(2, 3, "R")]);
+        g.index_node("slug", "two".to_string(), 2);
+        g.freeze();
+        assert!(g.validate().is_empty());
+
+        // A bare edge, an edge only on the outgoing side and a stale index entry
+        g.add_edge(3, 4, 0, Edge::NO_CONFIDENCE);
+        let (outgoing, _) = g.lists_mut();
+        let mut stray = outgoing[&1][0];
+        stray.target = 3;
+        stray.rel_type = 7;
+        outgoing.get_mut(&1).unwrap().push(stray);
+        g.app_id_index.insert("gone".to_string(), 99);
+
+        let found = g.validate();
+        let kinds: Vec<(Invariant, usize)> = found.iter().map(|v| (v.invariant, v.count)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (Invariant::EdgeEndpoints, 1),
+                (Invariant::MirroredEdges, 1),
+                (Invariant::IndexTargets, 1),
+                (Invariant::RelTypeIds, 1),
+            ]
+        );
+        assert_eq!(found[0].example, "edge 3 -> 4: node 4 has no metadata");
+        assert_eq!(
+            found[1].example,
+            "edge 1 -> 3 (rel type id 7) only in outgoing lists"
+        );
+        assert_eq!(found[2].example, "app id 'gone' points at missing node 99");
+    }
+}
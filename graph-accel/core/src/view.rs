@@ -0,0 +1,496 @@
+//! Filtered views of a graph that borrow it instead of copying.
+
+use std::rc::Rc;
+
+use crate::cost::CostFn;
+use crate::graph::{Direction, Edge, Graph, NodeId, TraversalDirection};
+use crate::partition::LabelSet;
+use crate::temporal::time_respecting_path_by;
+use crate::traversal::{
+    bfs_neighborhood_by, extract_subgraph_by, hop_distance_by, k_shortest_paths_by,
+    k_shortest_paths_weighted_by, shortest_path_by, Exclusions, PathStep, SubgraphResult,
+    TraversalResult, WeightedPath,
+};
+
+/// A `&Graph` seen through node and edge predicates.
+///
+/// This is the filtered form of the traversals: label, node and rel type
+/// filters, a confidence floor and `Exclusions` all go through a view.
+/// Hidden nodes and edges are skipped as the traversals walk the graph, so
+/// building a view costs only resolving its filters. Without any filter a
+/// view sees the whole graph. Filters compose: a visible edge must have an
+/// allowed rel type, pass the confidence floor, not be excluded and lead to
+/// a visible node. A traversal's start is exempt from the node filters, so
+/// it is always expanded unless excluded; every other node, targets
+/// included, must pass them. Node metadata, labels and properties are read
+/// through `graph()`.
+#[derive(Clone)]
+pub struct GraphView<'g> {
+    graph: &'g Graph,
+    labels: Option<LabelSet>,
+    node_filter: Option<Rc<dyn Fn(NodeId) -> bool + 'g>>,
+    min_confidence: Option<f32>,
+    as_of: Option<u32>,
+    /// Excluded nodes and edges, and the allowed rel types.
+    exclusions: Exclusions,
+}
+
+impl Graph {
+    /// An unfiltered view, to narrow with the `GraphView::with_*` methods.
+    pub fn view(&self) -> GraphView<'_> {
+        GraphView {
+            graph: self,
+            labels: None,
+            node_filter: None,
+            min_confidence: None,
+            as_of: None,
+            exclusions: Exclusions::new(),
+        }
+    }
+}
+
+impl<'g> GraphView<'g> {
+    /// Show only nodes carrying one of `labels`, as primary or extra label.
+    /// Names no node carries are skipped, so a list of only unknown names
+    /// hides every node.
+    pub fn with_labels<I, S>(self, labels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let set = self.graph.label_set(labels);
+        self.with_label_set(set)
+    }
+
+    /// `with_labels` for a set already resolved with `Graph::label_set`.
+    pub fn with_label_set(mut self, labels: LabelSet) -> Self {
+        self.labels = Some(labels);
+        self
+    }
+
+    /// Show only nodes passing `filter`, on top of any earlier one.
+    /// Typical filters read node properties through `Graph::node_property`.
+    pub fn with_node_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(NodeId) -> bool + 'g,
+    {
+        self.node_filter = Some(match self.node_filter.take() {
+            Some(earlier) => Rc::new(move |id| earlier(id) && filter(id)),
+            None => Rc::new(filter),
+        });
+        self
+    }
+
+    /// Show only edges of the given relationship types. Unknown names are
+    /// skipped.
+    pub fn with_rel_types<I, S>(mut self, rel_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let ids: Vec<_> = rel_types
+            .into_iter()
+            .filter_map(|name| self.graph.rel_type_id(name.as_ref()))
+            .collect();
+        self.exclusions.restrict_rel_types(&ids);
+        self
+    }
+
+    /// Hide edges whose confidence is below `min`. Edges without a loaded
+    /// confidence stay visible, as with every traversal's `min_confidence`.
+    pub fn with_min_confidence(mut self, min: f32) -> Self {
+        self.min_confidence = Some(min);
+        self
+    }
+
+    /// Show the graph as it was at `as_of` (seconds since the Unix epoch):
+    /// edges created later are hidden. Edges without a loaded timestamp
+    /// stay visible, as with the confidence floor.
+    pub fn with_as_of(mut self, as_of: u32) -> Self {
+        self.as_of = Some(as_of);
+        self
+    }
+
+    /// Route around `exclusions`, on top of any earlier ones. Rel types
+    /// allowed here narrow those of `with_rel_types` rather than adding to
+    /// them.
+    pub fn with_exclusions(mut self, exclusions: &Exclusions) -> Self {
+        self.exclusions.merge(exclusions);
+        self
+    }
+
+    /// The underlying graph, unfiltered.
+    pub fn graph(&self) -> &'g Graph {
+        self.graph
+    }
+
+    /// True if `id` is a node of the graph that is neither excluded nor
+    /// hidden by the node filters.
+    pub fn contains_node(&self, id: NodeId) -> bool {
+        self.graph.node(id).is_some() && !self.exclusions.excludes_node(id) && self.admits_node(id)
+    }
+
+    /// True if the edge and the node it leads to are visible. Edges
+    /// excluded by their endpoints are only known to `edges_of`, which
+    /// sees both ends.
+    pub fn contains_edge(&self, edge: &Edge) -> bool {
+        self.exclusions.allows_rel_type(edge.rel_type)
+            && !self.exclusions.excludes_node(edge.target)
+            && self.admits_step(edge)
+            && self
+                .min_confidence
+                .is_none_or(|min| !edge.has_confidence() || edge.confidence >= min)
+    }
+
+    /// `Graph::edges_of` restricted to visible edges.
+    pub fn edges_of(
+        &self,
+        node: NodeId,
+        direction: TraversalDirection,
+    ) -> impl Iterator<Item = (Edge, Direction)> + '_ {
+        self.graph
+            .edges_of(node, direction)
+            .filter(move |(e, _)| self.contains_edge(e) && !self.exclusions.blocks(node, e))
+    }
+
+    /// `bfs_neighborhood` within the view. Nodes reachable only through
+    /// hidden ones are not found; an excluded `start` yields an empty
+    /// result.
+    pub fn bfs_neighborhood(
+        &self,
+        start: NodeId,
+        max_depth: u32,
+        direction: TraversalDirection,
+        max_nodes: usize,
+    ) -> TraversalResult {
+        bfs_neighborhood_by(
+            self.graph,
+            start,
+            max_depth,
+            direction,
+            self.min_confidence,
+            max_nodes,
+            &self.exclusions,
+            |e: &Edge| self.admits_step(e),
+        )
+    }
+
+    /// `shortest_path` within the view. None if `target` is hidden or
+    /// either endpoint is excluded.
+    pub fn shortest_path(
+        &self,
+        start: NodeId,
+        target: NodeId,
+        max_hops: u32,
+        direction: TraversalDirection,
+    ) -> Option<Vec<PathStep>> {
+        shortest_path_by(
+            self.graph,
+            start,
+            target,
+            max_hops,
+            direction,
+            self.min_confidence,
+            &self.exclusions,
+            |e: &Edge| self.admits_step(e),
+        )
+    }
+
+    /// `path_exists` within the view. False if `to` is hidden or either
+    /// end is excluded.
+    pub fn path_exists(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        max_depth: u32,
+        direction: TraversalDirection,
+    ) -> bool {
+        hop_distance_by(
+            self.graph,
+            from,
+            to,
+            max_depth,
+            direction,
+            self.min_confidence,
+            &self.exclusions,
+            |e: &Edge| self.admits_step(e),
+        )
+        .is_some()
+    }
+
+    /// `k_shortest_paths` within the view. Empty if `target` is hidden or
+    /// either endpoint is excluded.
+    pub fn k_shortest_paths(
+        &self,
+        start: NodeId,
+        target: NodeId,
+        max_hops: u32,
+        k: usize,
+        direction: TraversalDirection,
+    ) -> Vec<Vec<PathStep>> {
+        k_shortest_paths_by(
+            self.graph,
+            start,
+            target,
+            max_hops,
+            k,
+            direction,
+            self.min_confidence,
+            &self.exclusions,
+            &|e: &Edge| self.admits_step(e),
+        )
+    }
+
+    /// `k_shortest_paths_weighted` within the view, with the same endpoint
+    /// rules as `k_shortest_paths`.
+    pub fn k_shortest_paths_weighted<C: CostFn>(
+        &self,
+        start: NodeId,
+        target: NodeId,
+        max_hops: u32,
+        k: usize,
+        direction: TraversalDirection,
+        cost: C,
+    ) -> Vec<WeightedPath> {
+        k_shortest_paths_weighted_by(
+            self.graph,
+            start,
+            target,
+            max_hops,
+            k,
+            direction,
+            self.min_confidence,
+            cost,
+            &self.exclusions,
+            &|e: &Edge| self.admits_step(e),
+        )
+    }
+
+    /// `extract_subgraph` within the view: discovery enters only visible
+    /// nodes, and only visible edges among them and `start` are returned.
+    /// With `max_edges` > 0, discovery and the edge list stop at that
+    /// budget and the result is flagged `truncated`.
+    pub fn extract_subgraph(
+        &self,
+        start: NodeId,
+        max_depth: u32,
+        direction: TraversalDirection,
+        max_edges: usize,
+    ) -> SubgraphResult {
+        extract_subgraph_by(
+            self.graph,
+            start,
+            max_depth,
+            direction,
+            self.min_confidence,
+            max_edges,
+            &self.exclusions,
+            |e: &Edge| self.admits_step(e),
+            |e: &Edge| self.admits_edge(e),
+        )
+    }
+
+    /// `time_respecting_path` within the view, with the same endpoint
+    /// rules as `shortest_path`.
+    pub fn time_respecting_path(
+        &self,
+        start: NodeId,
+        target: NodeId,
+        max_hops: u32,
+        direction: TraversalDirection,
+    ) -> Option<Vec<PathStep>> {
+        time_respecting_path_by(
+            self.graph,
+            start,
+            target,
+            max_hops,
+            direction,
+            self.min_confidence,
+            &self.exclusions,
+            |e: &Edge| self.admits_step(e),
+        )
+    }
+
+    /// Edge checks the traversals do not make themselves: `as_of`.
+    fn admits_edge(&self, edge: &Edge) -> bool {
+        self.as_of.is_none_or(|as_of| edge.existed_at(as_of))
+    }
+
+    /// An edge a traversal may take: admitted itself, toward an admitted
+    /// node.
+    fn admits_step(&self, edge: &Edge) -> bool {
+        self.admits_edge(edge) && self.admits_node(edge.target)
+    }
+
+    /// The label and node filters, which nodes without metadata pass when
+    /// there are none. Exclusions are checked by the traversals.
+    fn admits_node(&self, id: NodeId) -> bool {
+        self.labels
+            .as_ref()
+            .is_none_or(|set| self.graph.node_in_label_set(id, set))
+            && self.node_filter.as_ref().is_none_or(|filter| filter(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{Edge, EdgeRecord, Graph, NodeId, TraversalDirection};
+    use crate::traversal::{Exclusions, PathStep};
+
+    fn edge(from: NodeId, to: NodeId, rel: &str, to_label: &str, confidence: f32) -> EdgeRecord {
+        EdgeRecord {
+            from_label: "Concept".to_string(),
+            to_label: to_label.to_string(),
+            confidence,
+            ..EdgeRecord::new(from, to, rel)
+        }
+    }
+
+    #[test]
+    fn test_view_filters_traversal() {
+        // Two routes from 1 to 3: all IMPLIES through 2, or via SUPPORTS through 4
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "IMPLIES", "Concept", 0.9),
+            edge(2, 3, "IMPLIES", "Concept", 0.3),
+            edge(1, 4, "SUPPORTS", "Concept", 0.9),
+            edge(4, 3, "IMPLIES", "Concept", 0.9),
+            edge(1, 5, "IMPLIES", "Source", 0.9),
+        ]);
+        g.freeze();
+        let both = TraversalDirection::Both;
+        let ids = |path: Option<Vec<PathStep>>| -> Vec<NodeId> {
+            path.unwrap_or_default().iter().map(|s| s.node_id).collect()
+        };
+
+        let all = g.view();
+        assert_eq!(all.bfs_neighborhood(1, 2, both, 0).neighbors.len(), 4);
+        assert_eq!(all.edges_of(1, TraversalDirection::Outgoing).count(), 3);
+
+        let implies = g.view().with_rel_types(["IMPLIES"]);
+        assert_eq!(ids(implies.shortest_path(1, 3, 5, both)), vec![1, 2, 3]);
+        assert!(implies.shortest_path(1, 4, 1, both).is_none());
+
+        // The floor cuts 2 → 3, leaving only the detour through 4
+        let confident = g.view().with_min_confidence(0.5);
+        assert_eq!(ids(confident.shortest_path(1, 3, 5, both)), vec![1, 4, 3]);
+        assert!(!confident.contains_edge(&g.neighbors_out(2).first().unwrap()));
+        assert!(g
+            .view()
+            .with_rel_types(["IMPLIES"])
+            .with_min_confidence(0.5)
+            .shortest_path(1, 3, 5, both)
+            .is_none());
+
+        let concepts = g.view().with_labels(["Concept"]);
+        assert!(!concepts.contains_node(5));
+        let mut found: Vec<NodeId> = concepts
+            .bfs_neighborhood(1, 3, both, 0)
+            .neighbors
+            .iter()
+            .map(|n| n.node_id)
+            .collect();
+        found.sort_unstable();
+        assert_eq!(found, vec![2, 3, 4]);
+        assert_eq!(concepts.k_shortest_paths(1, 3, 5, 3, both).len(), 2);
+        assert!(concepts.k_shortest_paths(1, 5, 5, 3, both).is_empty());
+        let none = g.view().with_labels(["Missing"]);
+        assert!(none.bfs_neighborhood(1, 3, both, 0).neighbors.is_empty());
+    }
+
+    #[test]
+    fn test_view_path_exists() {
+        // 1 reaches 3 over IMPLIES only through 2; SUPPORTS leads to 4
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "IMPLIES", "Concept", 0.9),
+            edge(2, 3, "IMPLIES", "Concept", 0.9),
+            edge(1, 4, "SUPPORTS", "Concept", 0.9),
+            edge(4, 3, "SUPPORTS", "Concept", 0.9),
+        ]);
+        let out = TraversalDirection::Outgoing;
+        for frozen in [false, true] {
+            if frozen {
+                g.freeze();
+            }
+            let implies = g.view().with_rel_types(["IMPLIES"]);
+            assert!(implies.path_exists(1, 3, 10, out));
+            assert!(!implies.path_exists(1, 4, 10, out));
+            assert!(!implies.path_exists(3, 1, 10, out));
+            assert!(!implies.path_exists(1, 3, 1, out));
+            let supports = g.view().with_rel_types(["SUPPORTS"]);
+            assert!(supports.path_exists(1, 3, 10, out));
+            assert!(!supports.path_exists(1, 2, 10, out));
+            assert!(supports.path_exists(2, 2, 0, out));
+        }
+    }
+
+    #[test]
+    fn test_view_exclusions_and_node_filter() {
+        // As above: IMPLIES 1 → 2 → 3, SUPPORTS 1 → 4 then IMPLIES 4 → 3
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            edge(1, 2, "IMPLIES", "Concept", 0.9),
+            edge(2, 3, "IMPLIES", "Concept", 0.9),
+            edge(1, 4, "SUPPORTS", "Concept", 0.9),
+            edge(4, 3, "IMPLIES", "Concept", 0.9),
+        ]);
+        let both = TraversalDirection::Both;
+        let mut ex = Exclusions::new();
+        ex.exclude_node(2);
+        let around = g.view().with_exclusions(&ex);
+        assert!(!around.contains_node(2));
+        assert_eq!(around.edges_of(1, both).count(), 1);
+        let path = around.k_shortest_paths_weighted(1, 3, 5, 3, both, Edge::hop_cost);
+        assert_eq!(path.len(), 1);
+        assert_eq!(path[0].total_cost, 2.0);
+        assert_eq!(around.extract_subgraph(1, 3, both, 0).edges.len(), 2);
+        assert!(!around.path_exists(1, 2, 5, both));
+
+        // Rel types from exclusions narrow those of with_rel_types
+        let mut supports = Exclusions::new();
+        supports.allow_rel_type(g.rel_type_id("SUPPORTS").unwrap());
+        let narrowed = g.view().with_rel_types(["IMPLIES"]);
+        let narrowed = narrowed.with_exclusions(&supports);
+        let hood = narrowed.bfs_neighborhood(1, 3, both, 0);
+        assert!(hood.neighbors.is_empty());
+
+        // Node filters stack, and the start is exempt from them
+        let odd = g.view().with_node_filter(|id| id % 2 == 1);
+        assert_eq!(odd.bfs_neighborhood(2, 1, both, 0).neighbors.len(), 2);
+        assert!(odd.bfs_neighborhood(1, 3, both, 0).neighbors.is_empty());
+        let from_even = odd.clone().with_node_filter(|id| id != 3);
+        assert!(from_even.shortest_path(2, 3, 5, both).is_none());
+        assert_eq!(odd.shortest_path(2, 3, 5, both).map(|p| p.len()), Some(2));
+        assert!(odd.path_exists(2, 2, 0, both));
+    }
+
+    #[test]
+    fn test_view_as_of() {
+        // 1 → 2 at 100, 2 → 3 at 300, and an untimed 1 → 4
+        let at = |from, to, created_at| EdgeRecord {
+            created_at,
+            ..edge(from, to, "IMPLIES", "Concept", 0.9)
+        };
+        let mut g = Graph::new();
+        g.load_edges(vec![
+            at(1, 2, 100),
+            at(2, 3, 300),
+            at(1, 4, Edge::NO_TIMESTAMP),
+        ]);
+        g.freeze();
+        let both = TraversalDirection::Both;
+
+        let then = g.view().with_as_of(200);
+        assert!(!then.contains_edge(&g.neighbors_out(2).first().unwrap()));
+        assert_eq!(then.bfs_neighborhood(1, 3, both, 0).neighbors.len(), 2);
+        assert!(then.shortest_path(1, 3, 5, both).is_none());
+        assert!(!then.path_exists(3, 1, 5, both));
+        let sub = then.extract_subgraph(1, 3, both, 0);
+        assert_eq!((sub.node_count, sub.edges.len()), (3, 2));
+
+        let now = g.view().with_as_of(300);
+        assert_eq!(now.shortest_path(1, 3, 5, both).map(|p| p.len()), Some(3));
+        assert_eq!(g.view().with_as_of(0).edges_of(1, both).count(), 1);
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::graph::{Direction, Graph, NodeId, RelTypeId, TraversalDirection};
+use crate::interrupt;
+use crate::rng::SplitMix64;
+use crate::traversal::{bfs_distances, iter_neighbors, SubgraphEdge, SubgraphResult};
+
+/// Uniform random walks, e.g. as DeepWalk-style input for embedding training.
+///
+/// Each walk starts at `start` (or, if None, `num_walks` walks start at every
+/// node in ascending ID order) and takes up to `walk_len` hops, choosing
+/// uniformly among the edges allowed by `direction` and `min_confidence`
+/// (parallel edges weigh proportionally). A walk that reaches a node with no
+/// eligible edges ends early. Each walk includes its start node, so it holds
+/// at most `walk_len + 1` nodes.
+///
+/// The same `seed` on the same graph always yields the same walks.
+/// Returns an empty Vec if `start` is not in the graph.
+pub fn random_walks(
+    graph: &Graph,
+    start: Option<NodeId>,
+    walk_len: u32,
+    num_walks: usize,
+    seed: u64,
+    direction: TraversalDirection,
+    min_confidence: Option<f32>,
+) -> Vec<Vec<NodeId>> {
+    let starts: Vec<NodeId> = match start {
+        Some(id) if graph.node(id).is_some() => vec![id],
+        Some(_) => return Vec::new(),
+        None => {
+            let mut ids: Vec<NodeId> = graph.nodes_iter().map(|(&id, _)| id).collect();
+            ids.sort_unstable();
+            ids
+        }
+    };
+
+    let mut rng = SplitMix64::new(seed);
+    let mut candidates: Vec<NodeId> = Vec::new();
+    let mut walks = Vec::with_capacity(starts.len() * num_walks);
+
+    for &s in &starts {
+        for _ in 0..num_walks {
+            let mut walk = Vec::with_capacity(walk_len as usize + 1);
+            walk.push(s);
+            let mut current = s;
+            for _ in 0..walk_len {
+                candidates.clear();
+                candidates.extend(
+                    iter_neighbors(graph, current, direction, min_confidence).map(|(e, _)| e.target),
+                );
+                if candidates.is_empty() {
+                    break;
+                }
+                current = candidates[rng.below(candidates.len())];
+                walk.push(current);
+            }
+            walks.push(walk);
+        }
+    }
+
+    walks
+}
+
+/// Uniform random spanning tree (Wilson's algorithm).
+///
+/// Covers the ball of `max_depth` hops around `start`, or the whole graph if
+/// `start` is None; edges are undirected and must pass `min_confidence`.
+/// Every spanning tree of the covered nodes is equally likely (parallel edges
+/// count as distinct trees), which makes the result an unbiased sparsifier
+/// for drawing dense neighborhoods. A disconnected graph yields a uniform
+/// spanning forest, one tree per component.
+///
+/// Wilson's algorithm adds loop-erased random walks until every node is in
+/// the tree; expected time is the mean hitting time of the walk, near-linear
+/// on well-connected graphs. Edges are reported in stored orientation, sorted
+/// by endpoints. The same `seed` on the same graph yields the same tree.
+/// Empty if `start` is not in the graph.
+pub fn random_spanning_tree(
+    graph: &Graph,
+    start: Option<NodeId>,
+    max_depth: u32,
+    seed: u64,
+    min_confidence: Option<f32>,
+) -> SubgraphResult {
+    let mut ids: Vec<NodeId> = match start {
+        Some(id) if graph.node(id).is_some() => {
+            bfs_distances(graph, id, max_depth, TraversalDirection::Both, min_confidence)
+                .into_iter()
+                .map(|(n, _)| n)
+                .collect()
+        }
+        Some(_) => Vec::new(),
+        None => graph.nodes_iter().map(|(&id, _)| id).collect(),
+    };
+    ids.sort_unstable();
+    let dense: HashMap<NodeId, usize> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    let adj: Vec<Vec<(usize, RelTypeId, Direction)>> = ids
+        .iter()
+        .map(|&id| {
+            iter_neighbors(graph, id, TraversalDirection::Both, min_confidence)
+                .filter_map(|(e, dir)| dense.get(&e.target).map(|&t| (t, e.rel_type, dir)))
+                .collect()
+        })
+        .collect();
+
+    // The smallest node of each component is its tree's root
+    let n = ids.len();
+    let mut in_tree = vec![false; n];
+    let mut seen = vec![false; n];
+    for root in 0..n {
+        if seen[root] {
+            continue;
+        }
+        in_tree[root] = true;
+        seen[root] = true;
+        let mut queue = VecDeque::from([root]);
+        while let Some(v) = queue.pop_front() {
+            interrupt::check();
+            for &(t, _, _) in &adj[v] {
+                if !seen[t] {
+                    seen[t] = true;
+                    queue.push_back(t);
+                }
+            }
+        }
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    let mut next: Vec<Option<(usize, RelTypeId, Direction)>> = vec![None; n];
+    let mut edges: Vec<SubgraphEdge> = Vec::with_capacity(n);
+    for v in 0..n {
+        // Random walk until the tree is hit; overwriting `next` erases loops
+        let mut u = v;
+        while !in_tree[u] {
+            let step = adj[u][rng.below(adj[u].len())];
+            next[u] = Some(step);
+            u = step.0;
+        }
+        let mut u = v;
+        while !in_tree[u] {
+            in_tree[u] = true;
+            let (t, rel_type, dir) = next[u].expect("walked nodes have a successor");
+            edges.push(match dir {
+                Direction::Outgoing => SubgraphEdge::new(graph, ids[u], ids[t], rel_type),
+                Direction::Incoming => SubgraphEdge::new(graph, ids[t], ids[u], rel_type),
+            });
+            u = t;
+        }
+    }
+    edges.sort_by_key(|e| (e.from_id, e.to_id));
+
+    SubgraphResult {
+        node_count: n,
+        edges,
+        truncated: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::edge;
+
+    fn make_cycle(n: NodeId) -> Graph {
+        let mut g = Graph::new();
+        g.load_edges((0..n).map(|i| edge(i, (i + 1) % n, "R")));
+        g
+    }
+
+    #[test]
+    fn test_walks_follow_edges() {
+        let g = make_cycle(10);
+        let walks = random_walks(&g, Some(0), 20, 5, 7, TraversalDirection::Both, None);
+        assert_eq!(walks.len(), 5);
+        for w in &walks {
+            assert_eq!(w.len(), 21);
+            assert_eq!(w[0], 0);
+            for pair in w.windows(2) {
+                let diff = (pair[0] + 10 - pair[1]) % 10;
+                assert!(diff == 1 || diff == 9, "not adjacent: {:?}", pair);
+            }
+        }
+    }
+
+    #[test]
+    fn test_walks_deterministic_by_seed() {
+        let g = make_cycle(50);
+        let a = random_walks(&g, None, 10, 2, 123, TraversalDirection::Both, None);
+        let b = random_walks(&g, None, 10, 2, 123, TraversalDirection::Both, None);
+        let c = random_walks(&g, None, 10, 2, 124, TraversalDirection::Both, None);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.len(), 100);
+        assert_eq!(a[0][0], 0);
+        assert_eq!(a[99][0], 49);
+    }
+
+    #[test]
+    fn test_walks_outgoing_is_forced_on_cycle() {
+        let g = make_cycle(5);
+        let walks = random_walks(&g, Some(2), 6, 1, 0, TraversalDirection::Outgoing, None);
+        assert_eq!(walks[0], vec![2, 3, 4, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_walks_stop_at_dead_end() {
+        let mut g = Graph::new();
+        g.load_edges(vec![edge(0, 1, "R"), edge(1, 2, "R")]);
+        let walks = random_walks(&g, Some(0), 10, 3, 9, TraversalDirection::Outgoing, None);
+        assert!(walks.iter().all(|w| w == &vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_walks_missing_start_or_zero() {
+        let g = make_cycle(5);
+        assert!(random_walks(&g, Some(99), 5, 5, 1, TraversalDirection::Both, None).is_empty());
+        assert!(random_walks(&g, Some(0), 5, 0, 1, TraversalDirection::Both, None).is_empty());
+        let zero_len = random_walks(&g, Some(0), 0, 2, 1, TraversalDirection::Both, None);
+        assert_eq!(zero_len, vec![vec![0], vec![0]]);
+    }
+
+    fn spanning_pairs(r: &SubgraphResult) -> Vec<(NodeId, NodeId)> {
+        r.edges.iter().map(|e| (e.from_id, e.to_id)).collect()
+    }
+
+    #[test]
+    fn test_spanning_tree_spans_each_component() {
+        // K5 on 0..5, plus a separate triangle 10-11-12 and a lone pair 20-21
+        let mut g = Graph::new();
+        for a in 0..5 {
+            for b in a + 1..5 {
+                g.load_edges(vec![edge(a, b, "R")]);
+            }
+        }
+        g.load_edges(vec![
+            edge(10, 11, "R"),
+            edge(11, 12, "R"),
+            edge(12, 10, "R"),
+            edge(20, 21, "R"),
+        ]);
+        let r = random_spanning_tree(&g, None, 0, 3, None);
+        assert_eq!(r.node_count, 10);
+        // A forest over 3 components has n - 3 edges
+        assert_eq!(r.edges.len(), 7);
+        assert!(spanning_pairs(&r).contains(&(20, 21)));
+
+        // Union-find confirms acyclicity
+        let mut parent: HashMap<NodeId, NodeId> = HashMap::new();
+        let find = |parent: &HashMap<NodeId, NodeId>, mut x: NodeId| {
+            while let Some(&up) = parent.get(&x) {
+                x = up;
+            }
+            x
+        };
+        for (a, b) in spanning_pairs(&r) {
+            let (ra, rb) = (find(&parent, a), find(&parent, b));
+            assert_ne!(ra, rb, "cycle through {}-{}", a, b);
+            parent.insert(ra, rb);
+        }
+    }
+
+    #[test]
+    fn test_spanning_tree_ball_and_determinism() {
+        let g = make_cycle(12);
+        let a = random_spanning_tree(&g, Some(0), 2, 9, None);
+        // Ball {10, 11, 0, 1, 2} is a path, so the tree is forced
+        assert_eq!(a.node_count, 5);
+        assert_eq!(spanning_pairs(&a), vec![(0, 1), (1, 2), (10, 11), (11, 0)]);
+        let b = random_spanning_tree(&g, None, 0, 5, None);
+        let c = random_spanning_tree(&g, None, 0, 5, None);
+        assert_eq!(spanning_pairs(&b), spanning_pairs(&c));
+        assert_eq!(b.edges.len(), 11);
+        assert_eq!(random_spanning_tree(&g, Some(99), 2, 1, None).node_count, 0);
+    }
+
+    #[test]
+    fn test_spanning_tree_is_uniform_on_cycle() {
+        // A 4-cycle has 4 spanning trees, each dropping one edge
+        let g = make_cycle(4);
+        let mut counts: HashMap<Vec<(NodeId, NodeId)>, usize> = HashMap::new();
+        for seed in 0..4000 {
+            let tree = random_spanning_tree(&g, None, 0, seed, None);
+            *counts.entry(spanning_pairs(&tree)).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 4);
+        for &c in counts.values() {
+            assert!((850..1150).contains(&c), "skewed counts: {:?}", counts);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Flag structural anomalies in the loaded graph.
+///
+/// One row per anomaly, errors first. Kinds: 'duplicate_app_id' (error),
+/// 'self_loop' and 'singleton_rel_type' (warning), 'high_degree' (info,
+/// degree strictly above `degree_percentile`).
+///
+/// Usage:
+///   SELECT * FROM graph_accel_anomalies();
+///   SELECT count(*) FROM graph_accel_anomalies() WHERE severity = 'error';
+#[pg_extern]
+fn graph_accel_anomalies(
+    degree_percentile: default!(f64, 99.0),
+) -> TableIterator<
+    'static,
+    (
+        name!(kind, String),
+        name!(severity, String),
+        name!(node_id, Option<i64>),
+        name!(label, Option<String>),
+        name!(app_id, Option<String>),
+        name!(rel_type, Option<String>),
+        name!(detail, String),
+    ),
+> {
+    let call = stats::call("graph_accel_anomalies");
+    crate::generation::ensure_fresh();
+    if !(0.0..=100.0).contains(&degree_percentile) {
+        error!(
+            "graph_accel: degree_percentile must be between 0 and 100, got {}",
+            degree_percentile
+        );
+    }
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::detect_anomalies(&gs.graph, degree_percentile)
+            .into_iter()
+            .map(|a| {
+                (
+                    a.kind.as_str().to_string(),
+                    a.severity.as_str().to_string(),
+                    a.node_id.map(|id| id as i64),
+                    a.label,
+                    a.app_id,
+                    a.rel_type,
+                    a.detail,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Check the loaded graph's internal invariants, one row per check.
+///
+/// Checks: 'edge_endpoints' (every edge endpoint has node metadata),
+/// 'mirrored_edges' (outgoing and incoming lists agree), 'index_targets'
+/// (app ID and secondary index entries point at loaded nodes) and
+/// 'rel_type_ids' (every rel type ID is interned). `example` describes the
+/// first violation found.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_selfcheck();
+///   SELECT bool_and(passed) FROM graph_accel_selfcheck();
+#[pg_extern]
+fn graph_accel_selfcheck() -> TableIterator<
+    'static,
+    (
+        name!(check_name, String),
+        name!(passed, bool),
+        name!(violations, i64),
+        name!(example, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_selfcheck");
+    crate::generation::ensure_fresh();
+
+    let results = state::with_graph(|gs| {
+        let found = gs.graph.validate();
+        graph_accel_core::Invariant::ALL
+            .iter()
+            .map(|&inv| {
+                let v = found.iter().find(|v| v.invariant == inv);
+                (
+                    inv.as_str().to_string(),
+                    v.is_none(),
+                    v.map_or(0, |v| v.count as i64),
+                    v.map(|v| v.example.clone()),
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
@@ -0,0 +1,109 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Connections ranked by edge betweenness centrality (exact Brandes).
+///
+/// Direction and parallel edges are merged: each row is one pair of
+/// adjacent nodes, with the rel types linking them. O(V·E) — intended for
+/// curation reports, not per-request use on large graphs.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_edge_betweenness(20);
+#[pg_extern]
+fn graph_accel_edge_betweenness(
+    top_n: default!(i32, 100),
+) -> TableIterator<
+    'static,
+    (
+        name!(from_id, i64),
+        name!(from_label, String),
+        name!(from_app_id, Option<String>),
+        name!(to_id, i64),
+        name!(to_label, String),
+        name!(to_app_id, Option<String>),
+        name!(rel_types, Vec<String>),
+        name!(betweenness, f64),
+    ),
+> {
+    let call = stats::call("graph_accel_edge_betweenness");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(top_n, "top_n") as usize;
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::edge_betweenness(&gs.graph, n)
+            .into_iter()
+            .map(|e| {
+                (
+                    e.from_id as i64,
+                    e.from_label,
+                    e.from_app_id,
+                    e.to_id as i64,
+                    e.to_label,
+                    e.to_app_id,
+                    e.rel_types,
+                    e.score,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Broker nodes: high betweenness relative to their degree.
+///
+/// Ranks nodes by bridging centrality (betweenness × bridging coefficient).
+/// `components_joined` is how many pieces the node's component would fall
+/// into without it (1 = not a cut vertex). Same O(V·E) cost as
+/// `graph_accel_edge_betweenness`.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_brokers(20);
+#[pg_extern]
+fn graph_accel_brokers(
+    top_n: default!(i32, 100),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(degree, i32),
+        name!(betweenness, f64),
+        name!(bridging_coefficient, f64),
+        name!(bridge_score, f64),
+        name!(components_joined, i32),
+    ),
+> {
+    let call = stats::call("graph_accel_brokers");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(top_n, "top_n") as usize;
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::brokers(&gs.graph, n)
+            .into_iter()
+            .map(|b| {
+                (
+                    b.node_id as i64,
+                    b.label,
+                    b.app_id,
+                    b.degree as i32,
+                    b.betweenness,
+                    b.bridging_coefficient,
+                    b.bridge_score,
+                    b.components_joined as i32,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
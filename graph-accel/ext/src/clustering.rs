@@ -0,0 +1,44 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+#[pg_extern]
+fn graph_accel_local_clustering(
+    top_n: default!(i32, 100),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(degree, i32),
+        name!(triangles, i32),
+        name!(clustering, f64),
+    ),
+> {
+    let call = stats::call("graph_accel_local_clustering");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(top_n, "top_n") as usize;
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::local_clustering(&gs.graph, n)
+            .into_iter()
+            .map(|cr| {
+                (
+                    cr.node_id as i64,
+                    cr.label,
+                    cr.app_id,
+                    cr.degree as i32,
+                    cr.triangles as i32,
+                    cr.coefficient,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
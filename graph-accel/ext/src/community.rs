@@ -0,0 +1,156 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Partition the loaded graph into communities.
+///
+/// `algorithm` is 'louvain' (default) or 'leiden'. Leiden adds a refinement
+/// step that guarantees every community is connected — use it when
+/// communities are materialized back into graph nodes.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_communities();
+///   SELECT * FROM graph_accel_communities(algorithm => 'leiden', resolution => 1.5);
+#[pg_extern]
+fn graph_accel_communities(
+    algorithm: default!(String, "'louvain'"),
+    resolution: default!(f64, 1.0),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(community_id, i32),
+        name!(community_size, i32),
+    ),
+> {
+    let call = stats::call("graph_accel_communities");
+    crate::generation::ensure_fresh();
+    let algo = crate::util::parse_community_algorithm(&algorithm);
+    if resolution.is_nan() || resolution <= 0.0 {
+        error!("graph_accel: resolution must be positive, got {}", resolution);
+    }
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::communities(&gs.graph, algo, resolution)
+            .assignments
+            .into_iter()
+            .map(|a| {
+                (
+                    a.node_id as i64,
+                    a.label,
+                    a.app_id,
+                    a.community as i32,
+                    a.community_size as i32,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Enumerate maximal cliques of at least `min_size` nodes (edges undirected).
+///
+/// One row per clique member. Cliques are numbered largest first. Dense
+/// cliques often point at over-merged extraction output. Enumeration stops
+/// after `max_cliques` cliques (0 = no limit).
+///
+/// Usage:
+///   SELECT * FROM graph_accel_cliques(4);
+#[pg_extern]
+fn graph_accel_cliques(
+    min_size: default!(i32, 3),
+    max_cliques: default!(i32, 1000),
+) -> TableIterator<
+    'static,
+    (
+        name!(clique_id, i32),
+        name!(clique_size, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_cliques");
+    crate::generation::ensure_fresh();
+    let min = crate::util::check_non_negative(min_size, "min_size") as usize;
+    let limit = crate::util::check_non_negative(max_cliques, "max_cliques") as usize;
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::maximal_cliques(&gs.graph, min, limit)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(ci, clique)| {
+                let size = clique.len() as i32;
+                clique.into_iter().map(move |id| (ci as i32, size, id))
+            })
+            .map(|(ci, size, id)| {
+                let info = gs.graph.node(id);
+                (
+                    ci,
+                    size,
+                    id as i64,
+                    gs.graph.node_label(id).unwrap_or_default().to_string(),
+                    info.and_then(|n| n.app_id.clone()),
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Weakly connected component of a node and its size.
+///
+/// Components ignore edge direction and are numbered by size, largest
+/// first: component 0 is the main body of the graph. The components are
+/// computed on first call and kept until the graph is reloaded, so later
+/// lookups cost a hash probe.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_component_of('concept_a');
+#[pg_extern]
+fn graph_accel_component_of(
+    node_id: String,
+    graph_name: default!(Option<String>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(component_id, i32),
+        name!(component_size, i64),
+        name!(largest_size, i64),
+    ),
+> {
+    let _call = stats::call("graph_accel_component_of");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+
+    let row = state::with_graph_mut_named(graph_name.as_deref(), |gs| {
+        let id = state::resolve_node(&gs.graph, &node_id);
+        let graph = &gs.graph;
+        let components = gs
+            .components
+            .get_or_insert_with(|| graph_accel_core::Components::build(graph));
+        let component = components
+            .component_of(id)
+            .expect("resolved nodes are in the graph");
+        (
+            component as i32,
+            components.size(component) as i64,
+            components.size(0) as i64,
+        )
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::once(row)
+}
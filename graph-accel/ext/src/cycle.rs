@@ -0,0 +1,78 @@
+use graph_accel_core::RelTypeId;
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Enumerate directed simple cycles of at most `max_len` edges.
+///
+/// One row per cycle member, in cycle order; `rel_type` labels the edge to
+/// the next member (the last member's edge closes the cycle). Pass NULL
+/// `rel_types` to follow every edge. Cycles in IMPLIES/REQUIRES chains point
+/// at logical inconsistencies. Enumeration stops after `max_cycles` cycles
+/// (0 = no limit).
+///
+/// Usage:
+///   SELECT * FROM graph_accel_cycles(4, ARRAY['IMPLIES', 'REQUIRES']);
+#[pg_extern]
+fn graph_accel_cycles(
+    max_len: default!(i32, 5),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    max_cycles: default!(i32, 1000),
+) -> TableIterator<
+    'static,
+    (
+        name!(cycle_id, i32),
+        name!(cycle_length, i32),
+        name!(position, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(rel_type, String),
+    ),
+> {
+    let call = stats::call("graph_accel_cycles");
+    crate::generation::ensure_fresh();
+    let len = crate::util::check_non_negative(max_len, "max_len") as usize;
+    let limit = crate::util::check_non_negative(max_cycles, "max_cycles") as usize;
+
+    let results = state::with_graph(|gs| {
+        let ids: Option<Vec<RelTypeId>> = rel_types.as_ref().map(|names| {
+            names
+                .iter()
+                .map(|n| crate::util::resolve_rel_type(&gs.graph, n))
+                .collect()
+        });
+
+        graph_accel_core::simple_cycles(&gs.graph, len, ids.as_deref(), limit)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(ci, cycle)| {
+                let length = cycle.nodes.len() as i32;
+                cycle
+                    .nodes
+                    .into_iter()
+                    .zip(cycle.rel_types)
+                    .enumerate()
+                    .map(move |(pos, (id, rel))| (ci as i32, length, pos as i32, id, rel))
+            })
+            .map(|(ci, length, pos, id, rel)| {
+                let info = gs.graph.node(id);
+                (
+                    ci,
+                    length,
+                    pos,
+                    id as i64,
+                    gs.graph.node_label(id).unwrap_or_default().to_string(),
+                    info.and_then(|n| n.app_id.clone()),
+                    rel,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
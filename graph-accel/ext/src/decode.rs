@@ -0,0 +1,499 @@
+//! Incremental sync from logical decoding.
+//!
+//! With `graph_accel.decoding_slot` set, the background worker drains a
+//! logical replication slot on the AGE label tables (output plugin
+//! `test_decoding`) and applies the row changes to its loaded graph instead
+//! of reloading: vertex inserts and updates re-register the node, vertex
+//! deletes remove it with its edges, and edge changes are applied by
+//! (from, to, type). Writes from any client are picked up, Cypher included,
+//! without triggers or calls to `graph_accel_invalidate()`.
+//!
+//! Changes the decoder cannot apply — a TRUNCATE, a delete without the old
+//! row (a table not set to `REPLICA IDENTITY FULL`), an unchanged TOASTed
+//! value — make the worker fall back to a full reload.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use graph_accel_core::{EdgeKey, Graph};
+use pgrx::prelude::*;
+use pgrx::spi::quote_literal;
+
+use crate::generation;
+use crate::guc;
+use crate::load::{self, EdgeRow, Filter, VertexRow};
+use crate::state;
+
+/// Prepare `graph_name` for `graph_accel.decoding_slot`: create a logical
+/// replication slot using `test_decoding` if none called `slot_name`
+/// exists, and set every label table of the graph to `REPLICA IDENTITY
+/// FULL`, so deletes carry the edge endpoints. Needs `wal_level = logical`
+/// and must run before any other write in its transaction. Returns the
+/// number of tables altered.
+///
+/// Changes are decoded from the slot's creation on; load the graph after
+/// this call. Re-run it after creating new labels.
+///
+/// Usage:
+///   SELECT graph_accel_setup_decoding('knowledge_graph');
+///   ALTER SYSTEM SET graph_accel.decoding_slot = 'graph_accel';
+///   SELECT pg_reload_conf();
+#[pg_extern]
+fn graph_accel_setup_decoding(
+    graph_name: String,
+    slot_name: default!(String, "'graph_accel'"),
+) -> i32 {
+    load::validate_name(&graph_name);
+    load::validate_name(&slot_name);
+
+    Spi::connect_mut(|client| {
+        // Slot creation refuses a transaction that has already written
+        let exists = client
+            .select(
+                &format!(
+                    "SELECT 1 FROM pg_catalog.pg_replication_slots WHERE slot_name = {}",
+                    quote_literal(&slot_name)
+                ),
+                None,
+                &[],
+            )?
+            .next()
+            .is_some();
+        if !exists {
+            client.select(
+                &format!(
+                    "SELECT pg_catalog.pg_create_logical_replication_slot({}, 'test_decoding')",
+                    quote_literal(&slot_name)
+                ),
+                None,
+                &[],
+            )?;
+        }
+
+        let tables = generation::label_tables(client, &graph_name, false)?;
+        for table in &tables {
+            client.update(
+                &format!("ALTER TABLE {} REPLICA IDENTITY FULL", table),
+                None,
+                &[],
+            )?;
+        }
+        Ok::<_, pgrx::spi::SpiError>(tables.len() as i32)
+    })
+    .unwrap_or_else(|e| error!("graph_accel_setup_decoding: {}", e))
+}
+
+/// Result of one `sync()`.
+pub(crate) enum Sync {
+    /// This many row changes were applied to the loaded graph.
+    Applied(usize),
+    /// A change could not be applied; the graph needs a full reload.
+    Unusable(String),
+}
+
+/// Drain `slot` and apply the changes to `graph_name` on its loaded copy.
+///
+/// The slot advances as soon as the changes are read, even if the
+/// transaction later aborts, so an ERROR here leaves the graph behind the
+/// slot. The worker exits on ERROR and starts over with a full load.
+pub(crate) fn sync(graph_name: &str, slot: &str) -> Sync {
+    let lines = Spi::connect(|client| {
+        let query = format!(
+            "SELECT data FROM pg_catalog.pg_logical_slot_get_changes({}, NULL, NULL, \
+             'include-xids', '0', 'skip-empty-xacts', '1')",
+            quote_literal(slot)
+        );
+        let mut lines = Vec::new();
+        for row in client.select(&query, None, &[])? {
+            if let Some(data) = row.get_by_name::<String, _>("data")? {
+                lines.push(data);
+            }
+        }
+        Ok::<_, pgrx::spi::SpiError>(lines)
+    })
+    .unwrap_or_else(|e| error!("graph_accel: cannot read slot '{}': {}", slot, e));
+
+    let settings = Settings::from_gucs();
+    let mut changes = Vec::new();
+    for line in &lines {
+        match parse_line(line) {
+            Ok(None) => {}
+            Ok(Some(row)) if row.schema != graph_name || row.table.starts_with("_ag") => {}
+            Ok(Some(row)) => {
+                if let Err(reason) = settings.changes(row, &mut changes) {
+                    return Sync::Unusable(reason);
+                }
+            }
+            Err(reason) => return Sync::Unusable(reason),
+        }
+    }
+    if changes.is_empty() {
+        return Sync::Applied(0);
+    }
+
+    let applied = changes.len();
+    state::with_graph_mut_named(Some(graph_name), |gs| {
+        let graph = Arc::get_mut(&mut gs.graph)
+            .unwrap_or_else(|| error!("graph_accel: graph is in use and cannot be modified"));
+        graph.update(|g| {
+            g.thaw();
+            for change in changes {
+                change.apply(g);
+            }
+        });
+        // Derived indexes describe the old edge set
+        gs.reachability = None;
+        gs.landmarks = None;
+        gs.components = None;
+    });
+    Sync::Applied(applied)
+}
+
+/// Move `slot` past everything written so far, for when a full load is
+/// about to read those changes from the tables anyway.
+pub(crate) fn skip_pending(slot: &str) {
+    Spi::run(&format!(
+        "SELECT pg_catalog.pg_replication_slot_advance({}, pg_catalog.pg_current_wal_lsn())",
+        quote_literal(slot)
+    ))
+    .unwrap_or_else(|e| error!("graph_accel: cannot advance slot '{}': {}", slot, e));
+}
+
+// ---------------------------------------------------------------------------
+// Applying changes
+// ---------------------------------------------------------------------------
+
+/// One change to the loaded graph.
+pub(crate) enum Change {
+    /// Register or re-register a vertex of a label table.
+    Vertex {
+        label: String,
+        row: VertexRow,
+    },
+    RemoveVertex(u64),
+    Edge {
+        rel_type: String,
+        row: EdgeRow,
+    },
+    /// Drop every edge with these endpoints and type.
+    RemoveEdge(EdgeKey),
+}
+
+impl Change {
+    pub(crate) fn apply(self, graph: &mut Graph) {
+        match self {
+            Change::Vertex { label, row } => load::add_vertex(graph, &label, row),
+            Change::RemoveVertex(id) => {
+                graph.remove_node(id);
+            }
+            Change::Edge { rel_type, row } => {
+                let rel_type_id = graph.intern_rel_type(&rel_type);
+                load::add_edge(graph, rel_type_id, row);
+            }
+            Change::RemoveEdge(key) => {
+                graph.apply_delta(&[], &[key]);
+            }
+        }
+    }
+}
+
+/// The load GUCs, read once per sync so changes become rows the way
+/// `graph_accel_load()` builds them.
+pub(crate) struct Settings {
+    node_label_filter: Filter,
+    edge_type_filter: Filter,
+    node_id_prop: Option<String>,
+    index_props: Vec<String>,
+    node_props: Vec<String>,
+    label_prop: Option<String>,
+    edge_props: Vec<String>,
+    confidence_prop: Option<String>,
+    weight_prop: Option<String>,
+    timestamp_prop: Option<String>,
+    predicates: HashMap<String, String>,
+    dedupe: bool,
+}
+
+impl Settings {
+    pub(crate) fn from_gucs() -> Self {
+        let filter = |setting| {
+            load::parse_filter(&guc::get_string(setting).unwrap_or_else(|| "*".to_string()))
+        };
+        Settings {
+            node_label_filter: filter(&guc::NODE_LABELS),
+            edge_type_filter: filter(&guc::EDGE_TYPES),
+            node_id_prop: guc::get_string(&guc::NODE_ID_PROPERTY),
+            index_props: load::property_keys(&guc::INDEX_PROPERTIES),
+            node_props: load::property_keys(&guc::NODE_PROPERTIES),
+            label_prop: guc::get_string(&guc::NODE_LABEL_PROPERTY),
+            edge_props: load::property_keys(&guc::EDGE_PROPERTIES),
+            confidence_prop: guc::get_string(&guc::EDGE_CONFIDENCE_PROPERTY),
+            weight_prop: guc::get_string(&guc::EDGE_WEIGHT_PROPERTY),
+            timestamp_prop: guc::get_string(&guc::EDGE_TIMESTAMP_PROPERTY),
+            predicates: load::load_predicates(),
+            dedupe: guc::DEDUPE_EDGES.get(),
+        }
+    }
+
+    /// Append the graph changes for one decoded row change. Rows the load
+    /// filters would skip produce none.
+    fn changes(&self, row: RowChange, out: &mut Vec<Change>) -> Result<(), String> {
+        match row.op {
+            Op::Insert(tuple) => self.insert(&row.table, &tuple, out),
+            Op::Delete(tuple) => self.delete(&row.table, &tuple, out),
+            Op::Update { old, new } => {
+                if is_edge(&new) {
+                    self.delete(&row.table, old.as_ref().unwrap_or(&new), out)?;
+                }
+                // A vertex keeps its edges: re-registering replaces its metadata
+                self.insert(&row.table, &new, out)
+            }
+        }
+    }
+
+    fn insert(&self, table: &str, tuple: &Tuple, out: &mut Vec<Change>) -> Result<(), String> {
+        // Only Postgres can evaluate a load predicate
+        if self.predicates.contains_key(table) {
+            return Err(format!("'{}' has a load predicate", table));
+        }
+        let props = column(tuple, "properties");
+        if is_edge(tuple) {
+            let (from_id, to_id) = (id(tuple, "start_id")?, id(tuple, "end_id")?);
+            if self.dedupe && self.keeps_rel_type(table) {
+                out.push(Change::RemoveEdge(edge_key(table, from_id, to_id)));
+            }
+            out.extend(self.edge(table, from_id, to_id, props));
+        } else {
+            out.extend(self.vertex(table, id(tuple, "id")?, props));
+        }
+        Ok(())
+    }
+
+    /// The `graph_accel.load_predicates` entry for label table `label`.
+    pub(crate) fn predicate(&self, label: &str) -> Option<&str> {
+        self.predicates.get(label).map(String::as_str)
+    }
+
+    /// True if edges of `rel_type` pass `graph_accel.edge_types`.
+    pub(crate) fn keeps_rel_type(&self, rel_type: &str) -> bool {
+        load::matches_filter(rel_type, &self.edge_type_filter)
+    }
+
+    /// Registration of a vertex of label table `label` with `properties`
+    /// JSON, or None if the node label filter drops it.
+    pub(crate) fn vertex(&self, label: &str, id: u64, props: Option<&str>) -> Option<Change> {
+        let row = load::vertex_row(
+            id,
+            props,
+            self.node_id_prop.as_deref(),
+            &self.index_props,
+            &self.node_props,
+            self.label_prop.as_deref(),
+        );
+        // A node is kept if any of its labels passes the filter
+        let keep = load::matches_filter(label, &self.node_label_filter)
+            || row
+                .labels
+                .iter()
+                .any(|l| load::matches_filter(l, &self.node_label_filter));
+        keep.then(|| Change::Vertex {
+            label: label.to_string(),
+            row,
+        })
+    }
+
+    /// Addition of an edge with `properties` JSON, or None if the edge type
+    /// filter drops it.
+    pub(crate) fn edge(
+        &self,
+        rel_type: &str,
+        from_id: u64,
+        to_id: u64,
+        props: Option<&str>,
+    ) -> Option<Change> {
+        if !self.keeps_rel_type(rel_type) {
+            return None;
+        }
+        let row = load::edge_row(
+            from_id,
+            to_id,
+            props,
+            &self.edge_props,
+            self.confidence_prop.as_deref(),
+            self.weight_prop.as_deref(),
+            self.timestamp_prop.as_deref(),
+        );
+        Some(Change::Edge {
+            rel_type: rel_type.to_string(),
+            row,
+        })
+    }
+
+    fn delete(&self, table: &str, tuple: &Tuple, out: &mut Vec<Change>) -> Result<(), String> {
+        if is_edge(tuple) {
+            let (from_id, to_id) = (id(tuple, "start_id")?, id(tuple, "end_id")?);
+            out.push(Change::RemoveEdge(edge_key(table, from_id, to_id)));
+        } else {
+            out.push(Change::RemoveVertex(id(tuple, "id")?));
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn edge_key(rel_type: &str, from_id: u64, to_id: u64) -> EdgeKey {
+    EdgeKey {
+        from_id,
+        to_id,
+        rel_type: rel_type.to_string(),
+    }
+}
+
+fn is_edge(tuple: &Tuple) -> bool {
+    tuple.iter().any(|(name, _)| name == "start_id")
+}
+
+fn column<'a>(tuple: &'a Tuple, name: &str) -> Option<&'a str> {
+    tuple
+        .iter()
+        .find(|(n, _)| n == name)
+        .and_then(|(_, value)| value.as_deref())
+}
+
+fn id(tuple: &Tuple, name: &str) -> Result<u64, String> {
+    column(tuple, name)
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| format!("decoded row without a usable {}", name))
+}
+
+// ---------------------------------------------------------------------------
+// test_decoding output
+// ---------------------------------------------------------------------------
+
+/// Column name and value of one row image; None is SQL NULL.
+type Tuple = Vec<(String, Option<String>)>;
+
+enum Op {
+    Insert(Tuple),
+    /// `old` is the old row when the table logs it.
+    Update {
+        old: Option<Tuple>,
+        new: Tuple,
+    },
+    Delete(Tuple),
+}
+
+struct RowChange {
+    schema: String,
+    table: String,
+    op: Op,
+}
+
+/// Parse one line of `test_decoding` output:
+/// `table <schema>.<table>: <ACTION>: <column>[<type>]:<value> ...`, with
+/// `old-key:` / `new-tuple:` parts for updates. Transaction markers give
+/// None; anything that cannot be applied as a row change is an Err.
+fn parse_line(line: &str) -> Result<Option<RowChange>, String> {
+    let Some(rest) = line.strip_prefix("table ") else {
+        return Ok(None);
+    };
+    let unusable = || format!("cannot decode change '{}'", line);
+    let (schema, rest) = identifier(rest).ok_or_else(unusable)?;
+    let rest = rest.strip_prefix('.').ok_or_else(unusable)?;
+    let (table, rest) = identifier(rest).ok_or_else(unusable)?;
+    let (action, rest) = rest
+        .strip_prefix(": ")
+        .and_then(|r| r.split_once(": ").or(Some((r.trim_end_matches(':'), ""))))
+        .ok_or_else(unusable)?;
+
+    let op = match action {
+        "INSERT" => Op::Insert(tuple(rest).ok_or_else(unusable)?.0),
+        "DELETE" => Op::Delete(tuple(rest).ok_or_else(unusable)?.0),
+        "UPDATE" => match rest.strip_prefix("old-key: ") {
+            Some(rest) => {
+                let (old, rest) = tuple(rest).ok_or_else(unusable)?;
+                let rest = rest.strip_prefix("new-tuple: ").ok_or_else(unusable)?;
+                Op::Update {
+                    old: Some(old),
+                    new: tuple(rest).ok_or_else(unusable)?.0,
+                }
+            }
+            None => Op::Update {
+                old: None,
+                new: tuple(rest).ok_or_else(unusable)?.0,
+            },
+        },
+        _ => return Err(format!("{} on {}.{}", action, schema, table)),
+    };
+    Ok(Some(RowChange { schema, table, op }))
+}
+
+/// A possibly double-quoted identifier, and what follows it.
+fn identifier(s: &str) -> Option<(String, &str)> {
+    let Some(quoted) = s.strip_prefix('"') else {
+        let end = s.find(['.', ':']).unwrap_or(s.len());
+        return Some((s[..end].to_string(), &s[end..]));
+    };
+    let mut name = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c == '"' {
+            if quoted[i + 1..].starts_with('"') {
+                chars.next();
+            } else {
+                return Some((name, &quoted[i + 1..]));
+            }
+        }
+        name.push(c);
+    }
+    None
+}
+
+/// Columns up to the end of `s` or a ` new-tuple:` marker, and the rest.
+/// None for row images `test_decoding` leaves out (`(no-tuple-data)`) and
+/// for unchanged TOASTed values, which it does not print.
+fn tuple(mut s: &str) -> Option<(Tuple, &str)> {
+    let mut columns = Vec::new();
+    loop {
+        s = s.trim_start();
+        if s.is_empty() || s.starts_with("new-tuple:") {
+            break;
+        }
+        let open = s.find('[')?;
+        let name = s[..open].to_string();
+        let close = open + s[open..].find("]:")?;
+        s = &s[close + 2..];
+
+        let value = if let Some(quoted) = s.strip_prefix('\'') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                if c == '\'' {
+                    if quoted[i + 1..].starts_with('\'') {
+                        chars.next();
+                    } else {
+                        end = Some(i + 1);
+                        break;
+                    }
+                }
+                value.push(c);
+            }
+            s = &quoted[end?..];
+            Some(value)
+        } else {
+            let end = s.find(' ').unwrap_or(s.len());
+            let value = &s[..end];
+            s = &s[end..];
+            match value {
+                "null" => None,
+                "unchanged-toast-datum" => return None,
+                _ => Some(value.to_string()),
+            }
+        };
+        columns.push((name, value));
+    }
+    if columns.is_empty() {
+        return None;
+    }
+    Some((columns, s))
+}
@@ -1,10 +1,12 @@
 use pgrx::prelude::*;
 
 use crate::state;
+use crate::stats;
 
 #[pg_extern]
 fn graph_accel_degree(
     top_n: default!(i32, 100),
+    graph_name: default!(Option<String>, "NULL"),
 ) -> TableIterator<
     'static,
     (
@@ -16,10 +18,11 @@ fn graph_accel_degree(
         name!(total_degree, i32),
     ),
 > {
-    crate::generation::ensure_fresh();
+    let call = stats::call("graph_accel_degree");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
     let n = crate::util::check_non_negative(top_n, "top_n") as usize;
 
-    let results = state::with_graph(|gs| {
+    let results = state::with_graph_named(graph_name.as_deref(), |gs| {
         graph_accel_core::degree_centrality(&gs.graph, n)
             .into_iter()
             .map(|dr| {
@@ -38,5 +41,5 @@ fn graph_accel_degree(
         error!("graph_accel: no graph loaded — call graph_accel_load() first");
     });
 
-    TableIterator::new(results)
+    TableIterator::new(call.rows(results))
 }
@@ -0,0 +1,145 @@
+//! Incremental updates: apply edges written since load without a full reload.
+
+use std::sync::Arc;
+
+use graph_accel_core::{Edge, EdgeKey, EdgeRecord};
+use pgrx::prelude::*;
+use serde_json::Value;
+
+use crate::generation;
+use crate::load;
+use crate::state;
+use crate::stats;
+
+/// Apply a batch of edge inserts and deletes to the loaded graph.
+///
+/// `added` is a JSON array of edge objects: `from_id`, `to_id` (AGE graph
+/// IDs) and `rel_type` are required; `confidence`, `weight`, `created_at`
+/// (epoch seconds or ISO 8601) and a `properties` object are optional, the
+/// latter filtered by `graph_accel.edge_properties` as on load.
+/// `from_label`, `to_label`, `from_app_id` and `to_app_id` only matter for
+/// endpoints not yet in the graph. `removed` is a JSON array of
+/// `{from_id, to_id, rel_type}` objects; each drops every matching edge.
+/// Removals are applied first, so listing an edge in both replaces it.
+///
+/// Bumps the generation counter like `graph_accel_invalidate()`, so other
+/// backends reload, but marks this backend's graph as current — provided it
+/// was current before the call. A graph that was already stale stays stale
+/// and reloads on its next query as usual.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_apply_delta(
+///       added => '[{"from_id": 1125899906842625, "to_id": 1125899906842626,
+///                   "rel_type": "IMPLIES", "confidence": 0.9}]');
+#[pg_extern]
+fn graph_accel_apply_delta(
+    added: default!(JsonB, "'[]'"),
+    removed: default!(JsonB, "'[]'"),
+) -> TableIterator<
+    'static,
+    (
+        name!(edges_added, i64),
+        name!(edges_removed, i64),
+        name!(generation, i64),
+    ),
+> {
+    let _call = stats::call("graph_accel_apply_delta");
+    let added = parse_added(&added.0);
+    let removed = parse_removed(&removed.0);
+
+    let (graph_name, loaded_gen) = state::with_graph(|gs| {
+        (gs.source_graph.clone(), gs.loaded_generation)
+    })
+    .unwrap_or_else(|| error!("graph_accel: no graph loaded — call graph_accel_load() first"));
+
+    // Read before bumping: only a graph that was current may claim the new generation
+    let current_gen = generation::fetch_generation(&graph_name);
+    let new_gen = generation::bump_generation(&graph_name, "graph_accel_apply_delta");
+
+    let outcome = state::with_graph_mut(|gs| {
+        let graph = Arc::get_mut(&mut gs.graph)
+            .unwrap_or_else(|| error!("graph_accel: graph is in use and cannot be modified"));
+        let outcome = graph.update(|g| g.apply_delta(&added, &removed));
+        // Derived indexes describe the old edge set
+        gs.reachability = None;
+        gs.landmarks = None;
+        gs.components = None;
+        if current_gen.is_some_and(|g| loaded_gen >= g) {
+            gs.loaded_generation = new_gen;
+        }
+        outcome
+    })
+    .unwrap_or_else(|| error!("graph_accel: no graph loaded — call graph_accel_load() first"));
+
+    TableIterator::once((outcome.added as i64, outcome.removed as i64, new_gen))
+}
+
+fn parse_added(value: &Value) -> Vec<EdgeRecord> {
+    let prop_keys = load::property_keys(&crate::guc::EDGE_PROPERTIES);
+    delta_entries(value, "added")
+        .iter()
+        .map(|entry| EdgeRecord {
+            from_id: required_id(entry, "from_id", "added"),
+            to_id: required_id(entry, "to_id", "added"),
+            rel_type: required_rel_type(entry, "added"),
+            from_label: optional_str(entry, "from_label").unwrap_or_default(),
+            to_label: optional_str(entry, "to_label").unwrap_or_default(),
+            from_app_id: optional_str(entry, "from_app_id"),
+            to_app_id: optional_str(entry, "to_app_id"),
+            confidence: entry
+                .get("confidence")
+                .and_then(Value::as_f64)
+                .map(|c| c as f32)
+                .unwrap_or(Edge::NO_CONFIDENCE),
+            weight: entry
+                .get("weight")
+                .and_then(Value::as_f64)
+                .map(|w| w as f32)
+                .unwrap_or(Edge::DEFAULT_WEIGHT),
+            created_at: entry
+                .get("created_at")
+                .map(load::json_timestamp)
+                .unwrap_or(Edge::NO_TIMESTAMP),
+            properties: entry
+                .get("properties")
+                .map(|props| load::json_properties(props, &prop_keys))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+fn parse_removed(value: &Value) -> Vec<EdgeKey> {
+    delta_entries(value, "removed")
+        .iter()
+        .map(|entry| EdgeKey {
+            from_id: required_id(entry, "from_id", "removed"),
+            to_id: required_id(entry, "to_id", "removed"),
+            rel_type: required_rel_type(entry, "removed"),
+        })
+        .collect()
+}
+
+fn delta_entries<'a>(value: &'a Value, param: &str) -> &'a [Value] {
+    match value {
+        Value::Array(entries) => entries,
+        _ => error!("graph_accel: {} must be a JSON array of edge objects", param),
+    }
+}
+
+/// Graph IDs are accepted as JSON numbers or numeric strings.
+fn required_id(entry: &Value, key: &str, param: &str) -> u64 {
+    let value = entry.get(key);
+    value
+        .and_then(Value::as_u64)
+        .or_else(|| value.and_then(Value::as_str).and_then(|s| s.parse().ok()))
+        .unwrap_or_else(|| error!("graph_accel: every {} entry needs a numeric '{}'", param, key))
+}
+
+fn required_rel_type(entry: &Value, param: &str) -> String {
+    optional_str(entry, "rel_type")
+        .unwrap_or_else(|| error!("graph_accel: every {} entry needs a 'rel_type'", param))
+}
+
+fn optional_str(entry: &Value, key: &str) -> Option<String> {
+    entry.get(key).and_then(Value::as_str).map(str::to_string)
+}
@@ -0,0 +1,55 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Estimate diameter and radius of the largest component (edges undirected).
+///
+/// Runs `sweeps` double-sweep BFS rounds — cheap enough to run after every
+/// ingestion batch. `exact` is true when the lower and upper diameter
+/// bounds meet.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_diameter();
+///   SELECT diameter, radius FROM graph_accel_diameter(sweeps => 8);
+#[pg_extern]
+fn graph_accel_diameter(
+    sweeps: default!(i32, 4),
+) -> TableIterator<
+    'static,
+    (
+        name!(diameter, i32),
+        name!(diameter_upper, i32),
+        name!(radius, i32),
+        name!(exact, bool),
+        name!(center_id, Option<i64>),
+        name!(periphery_from, Option<i64>),
+        name!(periphery_to, Option<i64>),
+        name!(component_size, i64),
+        name!(bfs_runs, i32),
+    ),
+> {
+    let _call = stats::call("graph_accel_diameter");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(sweeps, "sweeps") as usize;
+
+    let row = state::with_graph(|gs| {
+        let est = graph_accel_core::estimate_diameter(&gs.graph, n);
+        (
+            est.diameter_lower as i32,
+            est.diameter_upper as i32,
+            est.radius_upper as i32,
+            est.diameter_lower == est.diameter_upper,
+            est.center.map(|id| id as i64),
+            est.periphery.map(|(a, _)| a as i64),
+            est.periphery.map(|(_, b)| b as i64),
+            est.component_size as i64,
+            est.bfs_runs as i32,
+        )
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::once(row)
+}
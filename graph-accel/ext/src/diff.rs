@@ -0,0 +1,82 @@
+//! Structural audit: compare the loaded graph against a fresh load.
+
+use pgrx::prelude::*;
+
+use crate::load;
+use crate::state::{self, GraphState};
+use crate::stats;
+
+/// Reload the graph and report what changed since the previous load.
+///
+/// The loaded graph is set aside, its source graph is reloaded from AGE
+/// with the current GUC filters, and the two are compared: one row per
+/// added or removed node and edge. Edges are matched on (from, to,
+/// rel_type); confidence, weights, properties and node metadata are not
+/// compared. `to_id` and `rel_type` are NULL for node rows.
+///
+/// The fresh graph stays loaded. If the reload fails, the previous graph
+/// is put back.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_diff();
+///   SELECT kind, change, count(*) FROM graph_accel_diff() GROUP BY 1, 2;
+#[pg_extern]
+fn graph_accel_diff() -> TableIterator<
+    'static,
+    (
+        name!(change, String),
+        name!(kind, String),
+        name!(from_id, i64),
+        name!(to_id, Option<i64>),
+        name!(rel_type, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_diff");
+    // No ensure_fresh(): an auto-reload here would replace the very graph
+    // being audited.
+    let previous = state::take_graph()
+        .unwrap_or_else(|| error!("graph_accel: no graph loaded — call graph_accel_load() first"));
+    let source_graph = previous.source_graph.clone();
+    let source = previous.source.clone();
+
+    let mut guard = Restore(Some(previous));
+    load::reload(&source_graph, &source);
+    let previous = guard.0.take().expect("graph_accel: previous graph already restored");
+
+    let delta = state::with_graph(|gs| previous.graph.diff(&gs.graph))
+        .unwrap_or_else(|| error!("graph_accel: reload of '{}' left no graph", source_graph));
+
+    let nodes = |change: &'static str, ids: Vec<u64>| {
+        ids.into_iter()
+            .map(move |id| (change.to_string(), "node".to_string(), id as i64, None, None))
+    };
+    let edges = |change: &'static str, keys: Vec<graph_accel_core::EdgeKey>| {
+        keys.into_iter().map(move |k| {
+            (
+                change.to_string(),
+                "edge".to_string(),
+                k.from_id as i64,
+                Some(k.to_id as i64),
+                Some(k.rel_type),
+            )
+        })
+    };
+    let rows: Vec<_> = nodes("added", delta.added_nodes)
+        .chain(nodes("removed", delta.removed_nodes))
+        .chain(edges("added", delta.added_edges))
+        .chain(edges("removed", delta.removed_edges))
+        .collect();
+
+    TableIterator::new(call.rows(rows))
+}
+
+/// Puts the previous graph back if the reload errors out.
+pub(crate) struct Restore(pub(crate) Option<GraphState>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            state::set_graph(previous);
+        }
+    }
+}
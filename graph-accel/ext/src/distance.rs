@@ -0,0 +1,77 @@
+use std::time::Instant;
+
+use graph_accel_core::LandmarkOracle;
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Landmarks built implicitly by the first `graph_accel_estimate_distance()` call.
+const DEFAULT_LANDMARKS: usize = 16;
+
+/// Select landmarks and precompute their distance table.
+///
+/// Replaces any previous table. More landmarks tighten estimates at the
+/// cost of one BFS and one `4 × node_count` byte row each.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_build_landmarks(32);
+#[pg_extern]
+fn graph_accel_build_landmarks(
+    num_landmarks: default!(i32, 16),
+) -> TableIterator<
+    'static,
+    (
+        name!(landmark_count, i32),
+        name!(memory_bytes, i64),
+        name!(build_time_ms, f64),
+    ),
+> {
+    let _call = stats::call("graph_accel_build_landmarks");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(num_landmarks, "num_landmarks") as usize;
+    let start = Instant::now();
+
+    let row = state::with_graph_mut(|gs| {
+        let oracle = LandmarkOracle::build(&gs.graph, n);
+        let row = (
+            oracle.landmarks().len() as i32,
+            oracle.memory_usage() as i64,
+            start.elapsed().as_secs_f64() * 1000.0,
+        );
+        gs.landmarks = Some(oracle);
+        row
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::once(row)
+}
+
+/// Approximate undirected hop distance between two nodes from the landmark table.
+///
+/// Returns the length of a real path through the best landmark (an upper
+/// bound, exact when either node is a landmark), or NULL if no landmark
+/// reaches both nodes. Builds a default table of 16 landmarks on first use.
+///
+/// Usage:
+///   SELECT graph_accel_estimate_distance('concept_a', 'concept_b');
+#[pg_extern]
+fn graph_accel_estimate_distance(a_id: String, b_id: String) -> Option<i32> {
+    let _call = stats::call("graph_accel_estimate_distance");
+    crate::generation::ensure_fresh();
+
+    state::with_graph_mut(|gs| {
+        let a = state::resolve_node(&gs.graph, &a_id);
+        let b = state::resolve_node(&gs.graph, &b_id);
+        let graph = &gs.graph;
+        gs.landmarks
+            .get_or_insert_with(|| LandmarkOracle::build(graph, DEFAULT_LANDMARKS))
+            .estimate(a, b)
+            .map(|est| est.upper as i32)
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    })
+}
@@ -0,0 +1,50 @@
+//! Export of the loaded graph to file formats other tools read.
+
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// The loaded graph, or the neighborhood of `start_id`, as a GraphML
+/// document, one line per row.
+///
+/// Without `start_id` every node and edge is exported. With it, the export
+/// is the subgraph induced by the nodes within `max_depth` hops, as
+/// `graph_accel_neighborhood` would reach them. Nodes carry `label` and
+/// `app_id`, edges `rel_type` and `confidence`. Lines are built as they are
+/// fetched, so exporting a large graph does not hold the document in memory.
+///
+/// Usage:
+///   psql -At -c "SELECT * FROM graph_accel_export_graphml()" > graph.graphml
+///   SELECT string_agg(line, E'\n')
+///     FROM graph_accel_export_graphml('concept_a', 2) AS line;
+#[pg_extern]
+fn graph_accel_export_graphml(
+    start_id: default!(Option<String>, "NULL"),
+    max_depth: default!(i32, 3),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> SetOfIterator<'static, String> {
+    let call = stats::call("graph_accel_export_graphml");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+    let min_conf = min_confidence.map(|v| v as f32);
+
+    let lines = state::with_graph_named(graph_name.as_deref(), |gs| {
+        let nodes = start_id.map(|id| {
+            let start = state::resolve_node(&gs.graph, &id);
+            graph_accel_core::bfs_distances(&gs.graph, start, depth, direction, min_conf)
+                .into_iter()
+                .map(|(node, _)| node)
+                .collect()
+        });
+        graph_accel_core::graphml_lines(gs.graph.clone(), nodes, min_conf)
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    SetOfIterator::new(call.rows(lines))
+}
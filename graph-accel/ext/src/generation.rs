@@ -17,6 +17,7 @@ use pgrx::PgTryBuilder;
 
 use crate::guc;
 use crate::state;
+use crate::stats;
 
 // ---------------------------------------------------------------------------
 // Bootstrap SQL: schema + generation table, created at CREATE EXTENSION time.
@@ -92,10 +93,40 @@ pub fn fetch_generation(graph_name: &str) -> Option<i64> {
 /// Returns the new generation number. Creates the row on first call.
 /// Fires `pg_notify('graph_accel', graph_name)` so external tools
 /// that `LISTEN graph_accel` can react.
+///
+/// `changed_ids` lists the AGE IDs of the vertices and edges written since
+/// the last invalidation. They go to the change journal, so stale backends
+/// re-read only those instead of reloading in full. Leave it NULL if the
+/// writes are not known; a deleted edge is only covered with its endpoints
+/// journaled, which the row triggers do (`graph_accel_install_triggers`).
+///
+/// Usage:
+///   SELECT graph_accel_invalidate('my_graph');
+///   SELECT graph_accel_invalidate('my_graph', ARRAY[844424930131969]);
 #[pg_extern]
-fn graph_accel_invalidate(graph_name: String) -> i64 {
+fn graph_accel_invalidate(
+    graph_name: String,
+    changed_ids: default!(Option<Vec<i64>>, "NULL"),
+) -> i64 {
     crate::load::validate_name(&graph_name);
+    if let Some(ids) = changed_ids.filter(|ids| !ids.is_empty()) {
+        let ids: Vec<String> = ids.iter().map(i64::to_string).collect();
+        Spi::run(&format!(
+            "INSERT INTO graph_accel.change_journal (graph_name, id) \
+             SELECT {}, unnest('{{{}}}'::bigint[])",
+            quote_literal(&graph_name),
+            ids.join(",")
+        ))
+        .unwrap_or_else(|e| error!("graph_accel_invalidate: {}", e));
+    }
+    bump_generation(&graph_name, "graph_accel_invalidate")
+}
 
+/// Increment the generation row for `graph_name` and fire `pg_notify`.
+/// Journal entries written so far in this transaction are stamped with the
+/// new generation, and generations past `JOURNAL_GENERATIONS` are pruned.
+/// `caller` prefixes the error message if the update fails.
+pub(crate) fn bump_generation(graph_name: &str, caller: &str) -> i64 {
     Spi::connect_mut(|client| {
         let upsert = format!(
             "INSERT INTO graph_accel.generation (graph_name, generation, updated_at) \
@@ -104,7 +135,7 @@ fn graph_accel_invalidate(graph_name: String) -> i64 {
              DO UPDATE SET generation = graph_accel.generation.generation + 1, \
                            updated_at = now() \
              RETURNING generation",
-            quote_literal(&graph_name)
+            quote_literal(graph_name)
         );
 
         let new_gen: i64 = client
@@ -113,11 +144,25 @@ fn graph_accel_invalidate(graph_name: String) -> i64 {
             .get_one::<i64>()?
             .unwrap_or(1);
 
+        client.update(
+            &format!(
+                "UPDATE graph_accel.change_journal SET generation = {gen} \
+                 WHERE graph_name = {graph} AND generation IS NULL; \
+                 DELETE FROM graph_accel.change_journal \
+                 WHERE graph_name = {graph} AND generation <= {oldest}",
+                gen = new_gen,
+                graph = quote_literal(graph_name),
+                oldest = new_gen - crate::journal::JOURNAL_GENERATIONS
+            ),
+            None,
+            &[],
+        )?;
+
         // Fire NOTIFY so external listeners can react
         client.update(
             &format!(
                 "SELECT pg_notify('graph_accel', {})",
-                quote_literal(&graph_name)
+                quote_literal(graph_name)
             ),
             None,
             &[],
@@ -126,15 +171,179 @@ fn graph_accel_invalidate(graph_name: String) -> i64 {
         Ok::<_, pgrx::spi::SpiError>(new_gen)
     })
     .unwrap_or_else(|e| {
-        error!("graph_accel_invalidate: {}", e);
+        error!("{}: {}", caller, e);
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Invalidation triggers
+// ---------------------------------------------------------------------------
+
+extension_sql!(
+    r#"
+CREATE FUNCTION graph_accel.invalidate_trigger() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    PERFORM @extschema@.graph_accel_invalidate(TG_ARGV[0]);
+    RETURN NULL;
+END;
+$$;
+
+COMMENT ON FUNCTION graph_accel.invalidate_trigger() IS
+    'Statement trigger installed by graph_accel_install_triggers(); '
+    'invalidates the graph named by its argument.';
+"#,
+    name = "invalidate_trigger",
+    requires = ["bootstrap", graph_accel_invalidate]
+);
+
+/// Trigger name used on every label table.
+const TRIGGER_NAME: &str = "graph_accel_invalidate";
+
+/// Name of the optional row trigger feeding the change journal.
+const JOURNAL_TRIGGER_NAME: &str = "graph_accel_journal";
+
+/// Install statement-level triggers on every vertex and edge label table
+/// of `graph_name` that call `graph_accel_invalidate(graph_name)` after
+/// INSERT, UPDATE, DELETE or TRUNCATE. Replaces triggers already there.
+/// Returns the number of tables covered.
+///
+/// Labels created later are not covered until this is run again. Writes
+/// made through Cypher do not fire triggers (AGE inserts tuples directly),
+/// so these only catch SQL-level writes such as bulk loaders and manual
+/// fixes; Cypher writers still call `graph_accel_invalidate()`.
+///
+/// With `journal`, a row trigger also records each written vertex and edge
+/// in the change journal, so stale backends re-read only those rows. It
+/// costs an insert per written row.
+///
+/// Usage:
+///   SELECT graph_accel_install_triggers('my_graph');
+///   SELECT graph_accel_install_triggers('my_graph', journal => true);
+#[pg_extern]
+fn graph_accel_install_triggers(graph_name: String, journal: default!(bool, false)) -> i32 {
+    crate::load::validate_name(&graph_name);
+    Spi::connect_mut(|client| {
+        let tables = label_tables(client, &graph_name, false)?;
+        for table in &tables {
+            client.update(
+                &format!(
+                    "DROP TRIGGER IF EXISTS {trigger} ON {table}; \
+                     CREATE TRIGGER {trigger} \
+                     AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON {table} \
+                     FOR EACH STATEMENT \
+                     EXECUTE FUNCTION graph_accel.invalidate_trigger({graph}); \
+                     DROP TRIGGER IF EXISTS {journal} ON {table}",
+                    trigger = TRIGGER_NAME,
+                    journal = JOURNAL_TRIGGER_NAME,
+                    graph = quote_literal(&graph_name),
+                ),
+                None,
+                &[],
+            )?;
+            if journal {
+                client.update(
+                    &format!(
+                        "CREATE TRIGGER {} \
+                         AFTER INSERT OR UPDATE OR DELETE ON {} \
+                         FOR EACH ROW \
+                         EXECUTE FUNCTION graph_accel.journal_trigger({})",
+                        JOURNAL_TRIGGER_NAME,
+                        table,
+                        quote_literal(&graph_name),
+                    ),
+                    None,
+                    &[],
+                )?;
+            }
+        }
+        Ok::<_, pgrx::spi::SpiError>(tables.len() as i32)
+    })
+    .unwrap_or_else(|e| error!("graph_accel_install_triggers: {}", e))
+}
+
+/// Remove the triggers `graph_accel_install_triggers` put on the label
+/// tables of `graph_name`. Returns the number of tables they were removed
+/// from.
+///
+/// Usage:
+///   SELECT graph_accel_uninstall_triggers('my_graph');
+#[pg_extern]
+fn graph_accel_uninstall_triggers(graph_name: String) -> i32 {
+    crate::load::validate_name(&graph_name);
+    Spi::connect_mut(|client| {
+        let tables = label_tables(client, &graph_name, true)?;
+        for table in &tables {
+            client.update(
+                &format!(
+                    "DROP TRIGGER {} ON {}; DROP TRIGGER IF EXISTS {} ON {}",
+                    TRIGGER_NAME, table, JOURNAL_TRIGGER_NAME, table
+                ),
+                None,
+                &[],
+            )?;
+        }
+        Ok::<_, pgrx::spi::SpiError>(tables.len() as i32)
     })
+    .unwrap_or_else(|e| error!("graph_accel_uninstall_triggers: {}", e))
+}
+
+/// Label tables of `graph_name` as SQL-ready names, including AGE's base
+/// `_ag_label_vertex` / `_ag_label_edge` tables. With `triggered`, only
+/// those carrying the invalidation trigger. Errors if the graph does not
+/// exist.
+pub(crate) fn label_tables(
+    client: &pgrx::spi::SpiClient<'_>,
+    graph_name: &str,
+    triggered: bool,
+) -> Result<Vec<String>, pgrx::spi::SpiError> {
+    let exists = client
+        .select(
+            &format!(
+                "SELECT 1 FROM ag_catalog.ag_graph WHERE name = {}",
+                quote_literal(graph_name)
+            ),
+            None,
+            &[],
+        )?
+        .next()
+        .is_some();
+    if !exists {
+        error!("graph_accel: AGE graph '{}' does not exist", graph_name);
+    }
+
+    let filter = if triggered {
+        format!(
+            "AND EXISTS (SELECT 1 FROM pg_catalog.pg_trigger t \
+                         WHERE t.tgrelid = l.relation AND t.tgname = {})",
+            quote_literal(TRIGGER_NAME)
+        )
+    } else {
+        String::new()
+    };
+    let query = format!(
+        "SELECT l.relation::regclass::text AS table_name \
+         FROM ag_catalog.ag_label l \
+         JOIN ag_catalog.ag_graph g ON l.graph = g.graphid \
+         WHERE g.name = {} {} \
+         ORDER BY l.id",
+        quote_literal(graph_name),
+        filter
+    );
+    let mut tables = Vec::new();
+    for row in client.select(&query, None, &[])? {
+        if let Some(table) = row.get_by_name::<String, _>("table_name")? {
+            tables.push(table);
+        }
+    }
+    Ok(tables)
 }
 
 // ---------------------------------------------------------------------------
 // Staleness check + auto-reload
 // ---------------------------------------------------------------------------
 
-/// Check if the loaded graph is stale and optionally reload.
+/// Check if the default graph is stale and optionally reload.
 ///
 /// Called at the top of every query function. Cost: one SPI SELECT
 /// (~0.01-0.05ms) for a single-row PK lookup.
@@ -147,9 +356,16 @@ fn graph_accel_invalidate(graph_name: String) -> i64 {
 /// - Stale + auto_reload=true + debounce not elapsed → return (serve stale)
 /// - Stale + auto_reload=true + debounce elapsed → reload inline
 pub fn ensure_fresh() {
-    let (graph_name, loaded_gen, loaded_at) = match state::with_graph(|gs| {
+    ensure_fresh_graph(None);
+}
+
+/// `ensure_fresh` for the graph called `name`, or the default graph if
+/// None. Each loaded graph tracks its own generation.
+pub fn ensure_fresh_graph(name: Option<&str>) {
+    let (graph_name, source, loaded_gen, loaded_at) = match state::with_graph_named(name, |gs| {
         (
             gs.source_graph.clone(),
+            gs.source.clone(),
             gs.loaded_generation,
             gs.loaded_at,
         )
@@ -197,7 +413,12 @@ pub fn ensure_fresh() {
     );
 
     PgTryBuilder::new(|| {
-        crate::load::do_load(&graph_name);
+        let delta = matches!(source, state::GraphSource::Age)
+            && crate::journal::delta_reload(&graph_name, loaded_gen, current_gen);
+        if !delta {
+            crate::load::reload(&graph_name, &source);
+        }
+        stats::note_reload();
     })
     .catch_others(|_| {
         warning!(
@@ -207,3 +428,84 @@ pub fn ensure_fresh() {
     })
     .execute();
 }
+
+// ---------------------------------------------------------------------------
+// Explicit reload
+// ---------------------------------------------------------------------------
+
+/// Reload the loaded graph now, bypassing `auto_reload` and the debounce.
+///
+/// Without `force`, reloads only if the graph is stale (or its generation
+/// cannot be read); with `force`, always. Reports the generation, node and
+/// edge counts before and after, and how many nodes and edges were added
+/// or removed (matched as in `graph_accel_diff()`). If nothing was
+/// reloaded, `reloaded` is false and the counts are unchanged. If the
+/// reload fails, the previous graph is put back.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_reload();
+///   SELECT * FROM graph_accel_reload(force => true);
+#[pg_extern]
+fn graph_accel_reload(
+    force: default!(bool, false),
+) -> TableIterator<
+    'static,
+    (
+        name!(reloaded, bool),
+        name!(old_generation, i64),
+        name!(new_generation, i64),
+        name!(old_node_count, i64),
+        name!(new_node_count, i64),
+        name!(old_edge_count, i64),
+        name!(new_edge_count, i64),
+        name!(nodes_added, i64),
+        name!(nodes_removed, i64),
+        name!(edges_added, i64),
+        name!(edges_removed, i64),
+        name!(load_time_ms, f64),
+    ),
+> {
+    let _call = stats::call("graph_accel_reload");
+    // No ensure_fresh(): this is the reload.
+    let (graph_name, old_gen, old_nodes, old_edges) = state::with_graph(|gs| {
+        (
+            gs.source_graph.clone(),
+            gs.loaded_generation,
+            gs.graph.node_count() as i64,
+            gs.graph.edge_count() as i64,
+        )
+    })
+    .unwrap_or_else(|| error!("graph_accel: no graph loaded — call graph_accel_load() first"));
+
+    let stale = fetch_generation(&graph_name).is_none_or(|current| old_gen < current);
+    if !force && !stale {
+        return TableIterator::once((
+            false, old_gen, old_gen, old_nodes, old_nodes, old_edges, old_edges, 0, 0, 0, 0, 0.0,
+        ));
+    }
+
+    let previous = state::take_graph().expect("graph_accel: loaded graph vanished");
+    let source = previous.source.clone();
+    let mut guard = crate::diff::Restore(Some(previous));
+    let (new_nodes, new_edges, load_time_ms) = crate::load::reload(&graph_name, &source);
+    let previous = guard.0.take().expect("graph_accel: previous graph already restored");
+
+    let (new_gen, delta) =
+        state::with_graph(|gs| (gs.loaded_generation, previous.graph.diff(&gs.graph)))
+            .unwrap_or_else(|| error!("graph_accel: reload of '{}' left no graph", graph_name));
+
+    TableIterator::once((
+        true,
+        old_gen,
+        new_gen,
+        old_nodes,
+        new_nodes,
+        old_edges,
+        new_edges,
+        delta.added_nodes.len() as i64,
+        delta.removed_nodes.len() as i64,
+        delta.added_edges.len() as i64,
+        delta.removed_edges.len() as i64,
+        load_time_ms,
+    ))
+}
@@ -7,19 +7,91 @@ pub static SOURCE_GRAPH: GucSetting<Option<CString>> =
 
 pub static MAX_MEMORY_MB: GucSetting<i32> = GucSetting::<i32>::new(4096);
 
+pub static MAX_RESULT_ROWS: GucSetting<i32> = GucSetting::<i32>::new(0);
+
+pub static QUERY_TIMEOUT_MS: GucSetting<i32> = GucSetting::<i32>::new(0);
+
 pub static NODE_ID_PROPERTY: GucSetting<Option<CString>> =
     GucSetting::<Option<CString>>::new(None);
 
+pub static INDEX_PROPERTIES: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
 pub static NODE_LABELS: GucSetting<Option<CString>> =
     GucSetting::<Option<CString>>::new(Some(c"*"));
 
 pub static EDGE_TYPES: GucSetting<Option<CString>> =
     GucSetting::<Option<CString>>::new(Some(c"*"));
 
+pub static NODE_LABEL_PROPERTY: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static NODE_PROPERTIES: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static EDGE_CONFIDENCE_PROPERTY: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"confidence"));
+
+pub static EDGE_WEIGHT_PROPERTY: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static EDGE_TIMESTAMP_PROPERTY: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static EDGE_PROPERTIES: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static LOAD_PREDICATES: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static GROUP_EDGES_BY_TYPE: GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static DEDUPE_EDGES: GucSetting<bool> = GucSetting::<bool>::new(false);
+
 pub static AUTO_RELOAD: GucSetting<bool> = GucSetting::<bool>::new(true);
 
 pub static RELOAD_DEBOUNCE_SEC: GucSetting<i32> = GucSetting::<i32>::new(5);
 
+pub static DELTA_RELOAD_MAX_CHANGES: GucSetting<i32> = GucSetting::<i32>::new(10_000);
+
+pub static SHARED_GRAPH: GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static SHARED_DIR: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"pg_graph_accel"));
+
+pub static WORKER: GucSetting<bool> = GucSetting::<bool>::new(false);
+
+pub static WORKER_DATABASE: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"postgres"));
+
+pub static WORKER_INTERVAL_SEC: GucSetting<i32> = GucSetting::<i32>::new(5);
+
+pub static DECODING_SLOT: GucSetting<Option<CString>> = GucSetting::<Option<CString>>::new(None);
+
+pub static NODE_TABLE: GucSetting<Option<CString>> = GucSetting::<Option<CString>>::new(None);
+
+pub static NODE_ID_COLUMN: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"id"));
+
+pub static NODE_LABEL_COLUMN: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static NODE_APP_ID_COLUMN: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
+pub static EDGE_TABLE: GucSetting<Option<CString>> = GucSetting::<Option<CString>>::new(None);
+
+pub static EDGE_FROM_COLUMN: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"from_id"));
+
+pub static EDGE_TO_COLUMN: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(Some(c"to_id"));
+
+pub static EDGE_TYPE_COLUMN: GucSetting<Option<CString>> = GucSetting::<Option<CString>>::new(None);
+
+pub static EDGE_CONFIDENCE_COLUMN: GucSetting<Option<CString>> =
+    GucSetting::<Option<CString>>::new(None);
+
 /// Read a string GUC, returning None if unset or empty.
 pub fn get_string(setting: &GucSetting<Option<CString>>) -> Option<String> {
     setting
@@ -29,8 +101,8 @@ pub fn get_string(setting: &GucSetting<Option<CString>>) -> Option<String> {
 }
 
 pub fn register_gucs() {
-    // Userset context: per-session SET. Shared memory phase will tighten
-    // to Sighup/Postmaster where appropriate.
+    // Userset context: per-session SET, unless noted. shared_graph stays
+    // Userset because attaching checks the session's own privileges.
     GucRegistry::define_string_guc(
         c"graph_accel.source_graph",
         c"AGE graph name to load",
@@ -51,15 +123,48 @@ pub fn register_gucs() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_int_guc(
+        c"graph_accel.max_result_rows",
+        c"Most rows a neighborhood, subgraph or paths call returns",
+        c"Caps max_nodes and max_results; larger results are truncated. 0 = no limit.",
+        &MAX_RESULT_ROWS,
+        0,
+        i32::MAX,
+        GucContext::Suset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_int_guc(
+        c"graph_accel.query_timeout_ms",
+        c"Default time budget of a neighborhood, subgraph or paths call (ms)",
+        c"Searches still running at the deadline return partial results flagged truncated. \
+          0 = no budget.",
+        &QUERY_TIMEOUT_MS,
+        0,
+        i32::MAX,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_string_guc(
         c"graph_accel.node_id_property",
         c"Node property for application-level ID",
-        c"Property name to index for app-level lookups (e.g. concept_id). Empty = AGE IDs only.",
+        c"Property name to index for app-level lookups (e.g. concept_id). String and integer \
+          values are indexed separately. Empty = AGE IDs only.",
         &NODE_ID_PROPERTY,
         GucContext::Userset,
         GucFlags::default(),
     );
 
+    GucRegistry::define_string_guc(
+        c"graph_accel.index_properties",
+        c"Comma-separated node properties to index for lookup by value",
+        c"Each becomes a secondary index named after it, for graph_accel_resolve(). Empty = none.",
+        &INDEX_PROPERTIES,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_string_guc(
         c"graph_accel.node_labels",
         c"Comma-separated node labels to load, or * for all",
@@ -78,6 +183,87 @@ pub fn register_gucs() {
         GucFlags::default(),
     );
 
+    GucRegistry::define_string_guc(
+        c"graph_accel.node_label_property",
+        c"Node property holding extra classification labels",
+        c"String or array-of-strings property whose values become extra node labels. Empty = none.",
+        &NODE_LABEL_PROPERTY,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.node_properties",
+        c"Comma-separated node properties to keep in memory",
+        c"Scalar node properties (numbers, strings) for traversal node filters. Empty = none.",
+        &NODE_PROPERTIES,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_confidence_property",
+        c"Edge property holding the confidence",
+        c"Numeric edge property read by min_confidence and cost_mode 'confidence'. Empty = none.",
+        &EDGE_CONFIDENCE_PROPERTY,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_weight_property",
+        c"Edge property holding the routing weight",
+        c"Numeric edge property used as path cost by cost_mode 'weight'. Empty = unit weights.",
+        &EDGE_WEIGHT_PROPERTY,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_timestamp_property",
+        c"Edge property holding the creation time",
+        c"Epoch seconds or ISO 8601 timestamp stored as each edge's created_at. Empty = none.",
+        &EDGE_TIMESTAMP_PROPERTY,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_properties",
+        c"Comma-separated edge properties to keep in memory",
+        c"Scalar edge properties (numbers, strings) loaded alongside confidence. Empty = none.",
+        &EDGE_PROPERTIES,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.load_predicates",
+        c"JSON object of label or edge type -> SQL predicate applied on load",
+        c"Each predicate becomes the WHERE clause of that label table's SELECT. Empty = none.",
+        &LOAD_PREDICATES,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_bool_guc(
+        c"graph_accel.group_edges_by_type",
+        c"Group each node's edges by relationship type on load",
+        c"Speeds up traversals that follow one type over nodes with many mixed-type edges.",
+        &GROUP_EDGES_BY_TYPE,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_bool_guc(
+        c"graph_accel.dedupe_edges",
+        c"Collapse duplicate edges on load",
+        c"Keeps one edge per (from, to, type), with the highest confidence among the copies.",
+        &DEDUPE_EDGES,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
     GucRegistry::define_bool_guc(
         c"graph_accel.auto_reload",
         c"Automatically reload when generation mismatch detected",
@@ -97,4 +283,152 @@ pub fn register_gucs() {
         GucContext::Userset,
         GucFlags::default(),
     );
+
+    GucRegistry::define_int_guc(
+        c"graph_accel.delta_reload_max_changes",
+        c"Most journaled changes an auto-reload applies instead of reloading in full",
+        c"Larger journals reload everything. 0 disables delta reloads.",
+        &DELTA_RELOAD_MAX_CHANGES,
+        0,
+        i32::MAX,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_bool_guc(
+        c"graph_accel.shared_graph",
+        c"Share loaded graphs across backends",
+        c"Loads publish the frozen adjacency to a mapped file that other backends attach to.",
+        &SHARED_GRAPH,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.shared_dir",
+        c"Directory for shared graph files",
+        c"Relative paths are under the data directory.",
+        &SHARED_DIR,
+        GucContext::Suset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_bool_guc(
+        c"graph_accel.worker",
+        c"Run a background worker that keeps the shared graph built",
+        c"Needs shared_preload_libraries. Rebuilds source_graph at startup and on invalidation.",
+        &WORKER,
+        GucContext::Postmaster,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.worker_database",
+        c"Database the background worker connects to",
+        c"The database holding the AGE graph and the generation table.",
+        &WORKER_DATABASE,
+        GucContext::Postmaster,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_int_guc(
+        c"graph_accel.worker_interval_sec",
+        c"Seconds between background worker generation checks",
+        c"How long a published graph can lag an invalidation.",
+        &WORKER_INTERVAL_SEC,
+        1,
+        3600, // 1 hour
+        GucContext::Sighup,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.decoding_slot",
+        c"Logical replication slot the background worker applies changes from",
+        c"Set up with graph_accel_setup_decoding(). Empty = rebuild on invalidation only.",
+        &DECODING_SLOT,
+        GucContext::Sighup,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.node_table",
+        c"Relational table of nodes to load instead of an AGE graph",
+        c"Optionally schema-qualified. Set this or edge_table to load plain tables.",
+        &NODE_TABLE,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.node_id_column",
+        c"Node table column holding the integer node ID",
+        c"Must be a non-negative integer.",
+        &NODE_ID_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.node_label_column",
+        c"Node table column holding the node label",
+        c"Empty = the node table's name.",
+        &NODE_LABEL_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.node_app_id_column",
+        c"Node table column holding the application-level ID",
+        c"Empty = node IDs only.",
+        &NODE_APP_ID_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_table",
+        c"Relational table of edges to load instead of an AGE graph",
+        c"Optionally schema-qualified. Set this or node_table to load plain tables.",
+        &EDGE_TABLE,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_from_column",
+        c"Edge table column holding the source node ID",
+        c"Must hold node IDs as in node_id_column.",
+        &EDGE_FROM_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_to_column",
+        c"Edge table column holding the target node ID",
+        c"Must hold node IDs as in node_id_column.",
+        &EDGE_TO_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_type_column",
+        c"Edge table column holding the relationship type",
+        c"Empty = the edge table's name.",
+        &EDGE_TYPE_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
+
+    GucRegistry::define_string_guc(
+        c"graph_accel.edge_confidence_column",
+        c"Edge table column holding the edge confidence",
+        c"Empty = no confidence.",
+        &EDGE_CONFIDENCE_COLUMN,
+        GucContext::Userset,
+        GucFlags::default(),
+    );
 }
@@ -0,0 +1,109 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Lowest common ancestors of two nodes in a hierarchy of one rel type.
+///
+/// `direction_filter` is the direction from child to parent: the default
+/// 'incoming' fits `(parent)-[:SUBSUMES]->(child)`. A DAG can yield several
+/// incomparable ancestors; rows are ordered by combined distance.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_lca('concept_a', 'concept_b', 'SUBSUMES');
+#[pg_extern]
+fn graph_accel_lca(
+    a_id: String,
+    b_id: String,
+    rel_type: String,
+    direction_filter: default!(String, "'incoming'"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(distance_a, i32),
+        name!(distance_b, i32),
+    ),
+> {
+    let call = stats::call("graph_accel_lca");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+
+    let results = state::with_graph(|gs| {
+        let a = state::resolve_node(&gs.graph, &a_id);
+        let b = state::resolve_node(&gs.graph, &b_id);
+        let rt = crate::util::resolve_rel_type(&gs.graph, &rel_type);
+
+        graph_accel_core::lowest_common_ancestors(&gs.graph, a, b, rt, direction)
+            .into_iter()
+            .map(|r| {
+                (
+                    r.node_id as i64,
+                    r.label,
+                    r.app_id,
+                    r.distance_a as i32,
+                    r.distance_b as i32,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Longest directed chain over edges of the given rel types (critical path).
+///
+/// The selected edges must be acyclic — REQUIRES chains modelling
+/// prerequisites are the typical case. One row per step from the first node
+/// to the last; `rel_type` names the edge that reached the step. Pass NULL
+/// to use every edge. Raises an ERROR naming a node on a cycle otherwise.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_critical_path(ARRAY['REQUIRES']);
+#[pg_extern]
+fn graph_accel_critical_path(
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(step, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(rel_type, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_critical_path");
+    crate::generation::ensure_fresh();
+
+    let results = state::with_graph(|gs| {
+        let ids = rel_types.as_ref().map(|names| {
+            names
+                .iter()
+                .map(|n| crate::util::resolve_rel_type(&gs.graph, n))
+                .collect::<Vec<_>>()
+        });
+
+        match graph_accel_core::critical_path(&gs.graph, ids.as_deref()) {
+            Ok(path) => path
+                .into_iter()
+                .enumerate()
+                .map(|(i, s)| (i as i32, s.node_id as i64, s.label, s.app_id, s.rel_type))
+                .collect::<Vec<_>>(),
+            Err(node) => error!(
+                "graph_accel: selected edges contain a cycle through node {} — critical path needs an acyclic subset (see graph_accel_cycles())",
+                node
+            ),
+        }
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
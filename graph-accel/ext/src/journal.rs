@@ -0,0 +1,342 @@
+//! Change journal: reload only what changed since the loaded generation.
+//!
+//! `graph_accel.change_journal` records the AGE IDs of vertices and edges
+//! written since an invalidation, filled by `graph_accel_invalidate(graph,
+//! changed_ids)` or by the row triggers of `graph_accel_install_triggers(
+//! graph, journal => true)`. Rows are written with a NULL generation and
+//! stamped by the next generation bump of the same transaction, so each
+//! generation lists the changes it stands for.
+//!
+//! When a stale graph auto-reloads and every generation since its own is
+//! journaled, only the journaled vertices and edges are re-read and applied
+//! to the loaded graph. A generation bumped without journal rows, a journal
+//! longer than `graph_accel.delta_reload_max_changes`, or an edge deleted
+//! without its endpoints on record means a full reload instead.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Instant;
+
+use pgrx::prelude::*;
+use pgrx::spi::{quote_identifier, quote_literal, SpiClient, SpiError};
+
+use crate::decode::{self, Change, Settings};
+use crate::guc;
+use crate::shared;
+use crate::state;
+
+extension_sql!(
+    r#"
+CREATE TABLE graph_accel.change_journal (
+    graph_name  text NOT NULL,
+    generation  bigint,
+    id          bigint NOT NULL,
+    start_id    bigint,
+    end_id      bigint
+);
+
+CREATE INDEX change_journal_graph_generation
+    ON graph_accel.change_journal (graph_name, generation);
+
+COMMENT ON TABLE graph_accel.change_journal IS
+    'AGE IDs written per graph_accel generation, for delta reloads. '
+    'generation is NULL until the next graph_accel_invalidate() stamps it; '
+    'start_id and end_id are endpoints of a changed edge, old or new, if known.';
+
+CREATE FUNCTION graph_accel.journal_trigger() RETURNS trigger
+LANGUAGE plpgsql AS $$
+DECLARE
+    old_row jsonb := CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END;
+    new_row jsonb := CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END;
+BEGIN
+    -- An UPDATE can move an edge: both endpoint pairs need re-reading
+    INSERT INTO graph_accel.change_journal (graph_name, id, start_id, end_id)
+    SELECT DISTINCT
+        TG_ARGV[0],
+        (r->>'id')::bigint,
+        (r->>'start_id')::bigint,
+        (r->>'end_id')::bigint
+    FROM unnest(ARRAY[old_row, new_row]) AS r
+    WHERE r IS NOT NULL;
+    RETURN NULL;
+END;
+$$;
+
+COMMENT ON FUNCTION graph_accel.journal_trigger() IS
+    'Row trigger installed by graph_accel_install_triggers(journal => true); '
+    'journals the written vertex or edge, with its old and new endpoints, '
+    'for the graph named by its argument.';
+"#,
+    name = "change_journal",
+    requires = ["bootstrap"]
+);
+
+/// Generations of journal kept per graph. A graph loaded further back
+/// than this falls back to a full reload.
+pub(crate) const JOURNAL_GENERATIONS: i64 = 1000;
+
+/// Bring the loaded `graph_name` from `loaded_gen` to `current_gen` by
+/// applying the journaled changes. Returns false, leaving the graph
+/// untouched, if a full reload is needed instead.
+///
+/// Shared graphs always reload in full: a delta would turn the mapped
+/// adjacency into a private copy.
+pub(crate) fn delta_reload(graph_name: &str, loaded_gen: i64, current_gen: i64) -> bool {
+    let max_changes = guc::DELTA_RELOAD_MAX_CHANGES.get() as i64;
+    if max_changes == 0 || shared::enabled() {
+        return false;
+    }
+    let start = Instant::now();
+    let settings = Settings::from_gucs();
+
+    let changes = Spi::connect(|client| {
+        read_changes(
+            &client,
+            graph_name,
+            loaded_gen,
+            current_gen,
+            max_changes,
+            &settings,
+        )
+    })
+    .unwrap_or_else(|e| error!("graph_accel: cannot read change journal: {}", e));
+    let Some(changes) = changes else {
+        return false;
+    };
+
+    let applied = changes.len();
+    let dedupe = guc::DEDUPE_EDGES.get();
+    let done = state::with_graph_mut_named(Some(graph_name), |gs| {
+        let Some(graph) = Arc::get_mut(&mut gs.graph) else {
+            return false;
+        };
+        graph.update(|g| {
+            g.thaw();
+            for change in changes {
+                change.apply(g);
+            }
+            if dedupe {
+                g.dedupe_edges();
+            }
+        });
+        // Derived indexes describe the old edge set
+        gs.reachability = None;
+        gs.landmarks = None;
+        gs.components = None;
+        gs.loaded_generation = current_gen;
+        gs.loaded_at = Instant::now();
+        gs.load_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+        true
+    })
+    .unwrap_or(false);
+    if done {
+        notice!(
+            "graph_accel: applied {} journaled changes to '{}' (gen {} -> {})",
+            applied,
+            graph_name,
+            loaded_gen,
+            current_gen
+        );
+    }
+    done
+}
+
+/// Graph changes for the journal entries of generations after
+/// `loaded_gen` up to `current_gen`, or None if they do not cover every
+/// generation or exceed `max_changes`.
+fn read_changes(
+    client: &SpiClient<'_>,
+    graph_name: &str,
+    loaded_gen: i64,
+    current_gen: i64,
+    max_changes: i64,
+    settings: &Settings,
+) -> Result<Option<Vec<Change>>, SpiError> {
+    let range = format!(
+        "graph_name = {} AND generation > {} AND generation <= {}",
+        quote_literal(graph_name),
+        loaded_gen,
+        current_gen
+    );
+    let covered: Option<i64> = client
+        .select(
+            &format!(
+                "SELECT count(DISTINCT generation) AS covered \
+                 FROM graph_accel.change_journal WHERE {}",
+                range
+            ),
+            None,
+            &[],
+        )?
+        .first()
+        .get_one()?;
+    if covered != Some(current_gen - loaded_gen) {
+        return Ok(None);
+    }
+
+    let mut entries = Vec::new();
+    let query = format!(
+        "SELECT DISTINCT id, start_id, end_id FROM graph_accel.change_journal \
+         WHERE {} LIMIT {}",
+        range,
+        max_changes + 1
+    );
+    for row in client.select(&query, None, &[])? {
+        let id: Option<i64> = row.get_by_name("id")?;
+        let start_id: Option<i64> = row.get_by_name("start_id")?;
+        let end_id: Option<i64> = row.get_by_name("end_id")?;
+        let Some(id) = id else { continue };
+        entries.push((id as u64, start_id.zip(end_id)));
+    }
+    if entries.len() as i64 > max_changes {
+        return Ok(None);
+    }
+
+    // Label ID → (name, kind); AGE keeps the label ID in a graphid's top 16 bits
+    let mut labels = HashMap::new();
+    let catalog = format!(
+        "SELECT l.id::int4 AS id, l.name::text AS name, l.kind::text AS kind \
+         FROM ag_catalog.ag_label l \
+         JOIN ag_catalog.ag_graph g ON l.graph = g.graphid \
+         WHERE g.name = {}",
+        quote_literal(graph_name)
+    );
+    for row in client.select(&catalog, None, &[])? {
+        let id: Option<i32> = row.get_by_name("id")?;
+        let name: Option<String> = row.get_by_name("name")?;
+        let kind: Option<String> = row.get_by_name("kind")?;
+        if let (Some(id), Some(name), Some(kind)) = (id, name, kind) {
+            labels.insert(id as u64, (name, kind));
+        }
+    }
+
+    // Vertex IDs, and edge endpoint pairs, by label
+    let mut vertices: BTreeMap<String, BTreeSet<u64>> = BTreeMap::new();
+    let mut edges: BTreeMap<String, BTreeSet<(u64, u64)>> = BTreeMap::new();
+    let mut unknown_ends: BTreeMap<String, BTreeSet<u64>> = BTreeMap::new();
+    for (id, ends) in entries {
+        let Some((label, kind)) = labels.get(&(id >> 48)) else {
+            // The label was dropped
+            return Ok(None);
+        };
+        match (kind.as_str(), ends) {
+            ("v", _) => {
+                vertices.entry(label.clone()).or_default().insert(id);
+            }
+            (_, Some((from, to))) => {
+                edges
+                    .entry(label.clone())
+                    .or_default()
+                    .insert((from as u64, to as u64));
+            }
+            (_, None) => {
+                unknown_ends.entry(label.clone()).or_default().insert(id);
+            }
+        }
+    }
+
+    // Edges journaled by ID alone: their current endpoints, if still there
+    for (label, ids) in unknown_ends {
+        let query = format!(
+            "SELECT id::text AS id, start_id::text AS start_id, end_id::text AS end_id \
+             FROM {}.{} WHERE id = ANY({}::graphid[])",
+            quote_identifier(graph_name),
+            quote_identifier(&label),
+            id_array(ids.iter().copied())
+        );
+        let mut found = BTreeSet::new();
+        for row in client.select(&query, None, &[])? {
+            let id = parse_id(row.get_by_name("id")?);
+            let ends =
+                parse_id(row.get_by_name("start_id")?).zip(parse_id(row.get_by_name("end_id")?));
+            if let (Some(id), Some(ends)) = (id, ends) {
+                found.insert(id);
+                edges.entry(label.clone()).or_default().insert(ends);
+            }
+        }
+        if found.len() < ids.len() {
+            // Deleted without its endpoints on record
+            return Ok(None);
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (label, ids) in vertices {
+        let query = format!(
+            "SELECT id::text AS id, properties::text AS properties \
+             FROM {}.{} WHERE id = ANY({}::graphid[]){}",
+            quote_identifier(graph_name),
+            quote_identifier(&label),
+            id_array(ids.iter().copied()),
+            and_predicate(settings.predicate(&label))
+        );
+        let mut gone = ids;
+        for row in client.select(&query, None, &[])? {
+            let Some(id) = parse_id(row.get_by_name("id")?) else {
+                continue;
+            };
+            let props: Option<String> = row.get_by_name("properties")?;
+            gone.remove(&id);
+            // A vertex the filters now drop leaves the graph; one the
+            // predicate drops is not returned, and counts as gone
+            changes.push(
+                settings
+                    .vertex(&label, id, props.as_deref())
+                    .unwrap_or(Change::RemoveVertex(id)),
+            );
+        }
+        changes.extend(gone.into_iter().map(Change::RemoveVertex));
+    }
+
+    // Each (from, to) pair is replaced by the edges now stored for it
+    for (label, pairs) in edges {
+        if !settings.keeps_rel_type(&label) {
+            continue;
+        }
+        changes.extend(
+            pairs
+                .iter()
+                .map(|&(from, to)| Change::RemoveEdge(decode::edge_key(&label, from, to))),
+        );
+        let values: Vec<String> = pairs
+            .iter()
+            .map(|(from, to)| format!("('{}'::graphid, '{}'::graphid)", from, to))
+            .collect();
+        let query = format!(
+            "SELECT start_id::text AS start_id, end_id::text AS end_id, \
+                    properties::text AS properties \
+             FROM {}.{} WHERE (start_id, end_id) IN (VALUES {}){}",
+            quote_identifier(graph_name),
+            quote_identifier(&label),
+            values.join(", "),
+            and_predicate(settings.predicate(&label))
+        );
+        for row in client.select(&query, None, &[])? {
+            let from = parse_id(row.get_by_name("start_id")?);
+            let to = parse_id(row.get_by_name("end_id")?);
+            let props: Option<String> = row.get_by_name("properties")?;
+            if let (Some(from), Some(to)) = (from, to) {
+                changes.extend(settings.edge(&label, from, to, props.as_deref()));
+            }
+        }
+    }
+
+    Ok(Some(changes))
+}
+
+/// ` AND (predicate)` for a label's `graph_accel.load_predicates` entry.
+fn and_predicate(predicate: Option<&str>) -> String {
+    predicate
+        .map(|p| format!(" AND ({})", p))
+        .unwrap_or_default()
+}
+
+fn parse_id(text: Option<String>) -> Option<u64> {
+    text?.parse().ok()
+}
+
+/// `'{1,2,3}'`, ready to cast to an array type.
+fn id_array(ids: impl Iterator<Item = u64>) -> String {
+    let ids: Vec<String> = ids.map(|id| id.to_string()).collect();
+    format!("'{{{}}}'", ids.join(","))
+}
@@ -6,16 +6,37 @@
 
 use pgrx::prelude::*;
 
+mod anomaly;
+mod centrality;
+mod clustering;
+mod community;
+mod cycle;
+mod decode;
+mod delta;
 mod degree;
+mod diameter;
+mod diff;
+mod distance;
+mod export;
 mod generation;
 mod guc;
+mod hierarchy;
+mod journal;
 mod load;
 mod neighborhood;
 mod path;
+mod query;
+mod reach;
+mod resolve;
+mod shared;
+mod similarity;
 mod state;
+mod stats;
 mod status;
 mod subgraph;
 mod util;
+mod walk;
+mod worker;
 
 pg_module_magic!();
 
@@ -23,6 +44,14 @@ pg_module_magic!();
 #[pg_guard]
 pub extern "C-unwind" fn _PG_init() {
     guc::register_gucs();
+    worker::register();
+    // Let cancel and statement_timeout stop long traversals
+    graph_accel_core::set_interrupt_hook(Some(check_interrupts));
+}
+
+/// Core's interrupt hook: raises an ERROR if a cancel or timeout is pending.
+fn check_interrupts() {
+    check_for_interrupts!();
 }
 
 #[cfg(any(test, feature = "pg_test"))]
@@ -62,6 +91,696 @@ mod tests {
         let g1_again = Spi::get_one::<i64>("SELECT graph_accel_invalidate('graph_a')");
         assert_eq!(g1_again, Ok(Some(2)));
     }
+
+    #[pg_test]
+    fn test_journal_delta_matches_full_reload_after_edge_moves() {
+        Spi::run("CREATE EXTENSION IF NOT EXISTS age").unwrap();
+        Spi::run("LOAD 'age'").unwrap();
+        Spi::run("SET search_path = ag_catalog, \"$user\", public").unwrap();
+        Spi::run("SELECT create_graph('journal_graph')").unwrap();
+        Spi::run(
+            "SELECT * FROM cypher('journal_graph', $$
+                CREATE (:N {i: 1})-[:LINKS]->(:N {i: 2}), (:N {i: 3})
+            $$) AS (v agtype)",
+        )
+        .unwrap();
+        Spi::run("SELECT graph_accel_install_triggers('journal_graph', journal => true)").unwrap();
+        Spi::run("SET graph_accel.reload_debounce_sec = 0").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('journal_graph')").unwrap();
+
+        // Move the edge's end from i = 2 to i = 3; the row trigger journals
+        // both pairs and the statement trigger bumps the generation
+        Spi::run(
+            "UPDATE journal_graph.\"LINKS\" SET end_id = (
+                SELECT id FROM journal_graph.\"N\"
+                WHERE properties::text::jsonb->>'i' = '3')",
+        )
+        .unwrap();
+        let journaled = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel.change_journal
+             WHERE graph_name = 'journal_graph' AND generation IS NOT NULL",
+        );
+        assert_eq!(journaled, Ok(Some(2)));
+
+        // Auto-reload applies the delta; a full reload must then find nothing new
+        Spi::run("SELECT * FROM graph_accel_degree(graph_name => 'journal_graph')").unwrap();
+        let edges = Spi::get_one::<i64>("SELECT edge_count FROM graph_accel_status()");
+        assert_eq!(edges, Ok(Some(1)));
+        let diff = Spi::get_one::<i64>("SELECT count(*) FROM graph_accel_diff()");
+        assert_eq!(diff, Ok(Some(0)));
+    }
+
+    /// Create AGE graph `graph_name` with (:N {i: 1})-[:LINKS]->(:N {i: 2}).
+    fn create_age_graph(graph_name: &str) {
+        Spi::run("CREATE EXTENSION IF NOT EXISTS age").unwrap();
+        Spi::run("LOAD 'age'").unwrap();
+        Spi::run("SET search_path = ag_catalog, \"$user\", public").unwrap();
+        Spi::run(&format!("SELECT create_graph('{graph_name}')")).unwrap();
+        Spi::run(&format!(
+            "SELECT * FROM cypher('{graph_name}', $$
+                CREATE (:N {{i: 1}})-[:LINKS]->(:N {{i: 2}})
+            $$) AS (v agtype)"
+        ))
+        .unwrap();
+    }
+
+    /// `create_age_graph`, then turn on `graph_accel.shared_graph` and load
+    /// it, publishing the file.
+    fn publish_shared_graph(graph_name: &str) {
+        create_age_graph(graph_name);
+        Spi::run("SET graph_accel.shared_graph = on").unwrap();
+        Spi::run(&format!("SELECT * FROM graph_accel_load('{graph_name}')")).unwrap();
+    }
+
+    /// `status` and `node_count` of the latest load.
+    fn last_load() -> (Option<String>, Option<i64>) {
+        Spi::get_two::<String, i64>(
+            "SELECT status, node_count FROM graph_accel_load_history()
+             ORDER BY started_at DESC LIMIT 1",
+        )
+        .unwrap()
+    }
+
+    /// A role that can read `graph_name` and the catalogs the loader uses.
+    fn create_reader(graph_name: &str) {
+        Spi::run("CREATE ROLE graph_reader").unwrap();
+        Spi::run(&format!(
+            "GRANT USAGE ON SCHEMA ag_catalog, graph_accel, {graph_name} TO graph_reader;
+             GRANT SELECT ON ALL TABLES IN SCHEMA ag_catalog, graph_accel, {graph_name}
+                TO graph_reader"
+        ))
+        .unwrap();
+    }
+
+    #[pg_test]
+    fn test_shared_graph_attach_when_permitted() {
+        publish_shared_graph("shared_ok");
+        assert_eq!(last_load(), (Some("complete".into()), Some(2)));
+
+        // The second load maps the published file instead of reading AGE
+        Spi::run("SELECT graph_accel_unload('shared_ok')").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('shared_ok')").unwrap();
+        assert_eq!(last_load(), (Some("attached".into()), Some(2)));
+
+        // So does a role that can read every label table in full
+        create_reader("shared_ok");
+        Spi::run("SET ROLE graph_reader").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('shared_ok')").unwrap();
+        assert_eq!(last_load(), (Some("attached".into()), Some(2)));
+    }
+
+    #[pg_test]
+    fn test_shared_graph_falls_back_under_row_security() {
+        publish_shared_graph("shared_rls");
+        create_reader("shared_rls");
+        Spi::run(
+            "ALTER TABLE shared_rls.\"N\" ENABLE ROW LEVEL SECURITY;
+             CREATE POLICY hide_two ON shared_rls.\"N\" FOR SELECT
+                USING (properties::text::jsonb->>'i' <> '2')",
+        )
+        .unwrap();
+
+        // The policy hides a vertex, so the reader scans AGE and sees one
+        Spi::run("SET ROLE graph_reader").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('shared_rls')").unwrap();
+        assert_eq!(last_load(), (Some("complete".into()), Some(1)));
+    }
+
+    #[pg_test(error = "permission denied for table LINKS")]
+    fn test_shared_graph_falls_back_without_select() {
+        publish_shared_graph("shared_priv");
+        create_reader("shared_priv");
+        Spi::run("REVOKE SELECT ON shared_priv.\"LINKS\" FROM graph_reader").unwrap();
+
+        // No attach: the scan of AGE raises the usual permission error
+        Spi::run("SET ROLE graph_reader").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('shared_priv')").unwrap();
+    }
+
+    /// Load two stars as a query graph: hub n1 → n2..n4 and hub n5 →
+    /// n6..n8, all LINKS edges. Node N has app ID `nN`.
+    fn load_two_stars(graph_name: &str) {
+        Spi::run(&format!(
+            "SELECT * FROM graph_accel_load_query(
+                $$SELECT id, 'N' AS label, 'n' || id AS app_id
+                  FROM generate_series(1, 8) AS id$$,
+                $$SELECT h AS from_id, h + k AS to_id, 'LINKS' AS rel_type
+                  FROM unnest(ARRAY[1, 5]) AS h, generate_series(1, 3) AS k$$,
+                graph_name => '{graph_name}')"
+        ))
+        .unwrap();
+    }
+
+    #[pg_test]
+    fn test_neighborhood_batch_rows_per_seed() {
+        load_two_stars("batch_graph");
+        let rows = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel_neighborhood_batch(
+                ARRAY['n1', 'n5'], 1, graph_name => 'batch_graph')
+             WHERE NOT truncated",
+        );
+        assert_eq!(rows, Ok(Some(6)));
+        let per_seed = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel_neighborhood_batch(
+                ARRAY['n1', 'n5'], 1, max_nodes => 2, graph_name => 'batch_graph')",
+        );
+        assert_eq!(per_seed, Ok(Some(4)));
+    }
+
+    #[pg_test]
+    fn test_neighborhood_batch_max_result_rows_caps_total() {
+        load_two_stars("batch_graph");
+        Spi::run("SET graph_accel.max_result_rows = 4").unwrap();
+        // Each seed alone fits under the ceiling; together they do not
+        let rows = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel_neighborhood_batch(
+                ARRAY['n1', 'n5'], 1, graph_name => 'batch_graph')",
+        );
+        assert_eq!(rows, Ok(Some(4)));
+        let flagged = Spi::get_one::<String>(
+            "SELECT string_agg(seed_id, ',') FROM graph_accel_neighborhood_batch(
+                ARRAY['n1', 'n5'], 1, graph_name => 'batch_graph')
+             WHERE truncated",
+        );
+        assert_eq!(flagged, Ok(Some("n5".to_string())));
+        Spi::run("RESET graph_accel.max_result_rows").unwrap();
+    }
+
+    #[pg_test]
+    fn test_as_of_and_time_respecting_paths() {
+        // t1 → t2 in 2024 then t2 → t3 in 2023; t1 → t4 in 2023 then
+        // t4 → t3 in mid-2024
+        Spi::run(
+            "SELECT * FROM graph_accel_load_query(
+                $$SELECT id, 'N' AS label, 't' || id AS app_id
+                  FROM generate_series(1, 4) AS id$$,
+                $$SELECT 1 AS from_id, 2 AS to_id, 'LINKS' AS rel_type WHERE false$$,
+                graph_name => 'timed_graph');
+             SELECT * FROM graph_accel_apply_delta(added => '[
+                {\"from_id\": 1, \"to_id\": 2, \"rel_type\": \"LINKS\",
+                 \"created_at\": \"2024-01-01T00:00:00Z\"},
+                {\"from_id\": 2, \"to_id\": 3, \"rel_type\": \"LINKS\",
+                 \"created_at\": \"2023-01-01T00:00:00Z\"},
+                {\"from_id\": 1, \"to_id\": 4, \"rel_type\": \"LINKS\",
+                 \"created_at\": \"2023-06-01T00:00:00Z\"},
+                {\"from_id\": 4, \"to_id\": 3, \"rel_type\": \"LINKS\",
+                 \"created_at\": \"2024-06-01T00:00:00Z\"}]')",
+        )
+        .unwrap();
+
+        // At the end of 2023 only t1 → t4 exists on the way out of t1
+        let reached = Spi::get_one::<String>(
+            "SELECT string_agg(app_id, ',') FROM graph_accel_neighborhood(
+                't1', 3, 'outgoing', as_of => '2023-12-31', graph_name => 'timed_graph')",
+        );
+        assert_eq!(reached, Ok(Some("t4".to_string())));
+        let edges = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel_subgraph(
+                't1', 3, 'outgoing', as_of => '2023-12-31', graph_name => 'timed_graph')",
+        );
+        assert_eq!(edges, Ok(Some(1)));
+
+        // In March 2024 t3 is reached through t2; t4 → t3 is still to come
+        let via = |args: &str| {
+            Spi::get_one::<String>(&format!(
+                "SELECT string_agg(app_id, ',') FROM graph_accel_path('t1', 't3', 5, 'outgoing',
+                    {args}, graph_name => 'timed_graph') WHERE step = 1"
+            ))
+            .unwrap()
+        };
+        assert_eq!(via("as_of => '2024-03-01'"), Some("t2".to_string()));
+        // In time order only the route through t4 works
+        assert_eq!(via("time_respecting => true"), Some("t4".to_string()));
+        assert_eq!(via("as_of => '2024-03-01', time_respecting => true"), None);
+        let paths = Spi::get_one::<i64>(
+            "SELECT count(DISTINCT path_index) FROM graph_accel_paths(
+                't1', 't3', 5, 5, 'outgoing', as_of => '2023-12-31')",
+        );
+        assert_eq!(paths, Ok(Some(0)));
+    }
+
+    #[pg_test]
+    fn test_reachable_uses_index_only_for_its_rel_types() {
+        // r1 -A-> r2 -B-> r3
+        Spi::run(
+            "SELECT * FROM graph_accel_load_query(
+                $$SELECT id, 'N' AS label, 'r' || id AS app_id
+                  FROM generate_series(1, 3) AS id$$,
+                $$SELECT * FROM (VALUES (1, 2, 'A'), (2, 3, 'B'))
+                  AS e(from_id, to_id, rel_type)$$,
+                graph_name => 'reach_graph')",
+        )
+        .unwrap();
+        let reachable = |sql: &str| Spi::get_one::<bool>(sql).unwrap();
+
+        // No index: BFS over the requested types
+        assert_eq!(
+            reachable("SELECT graph_accel_reachable('r1', 'r3')"),
+            Some(true)
+        );
+        assert_eq!(
+            reachable("SELECT graph_accel_reachable('r1', 'r3', ARRAY['A'])"),
+            Some(false)
+        );
+
+        // An index over A answers A queries and is ignored for the others
+        Spi::run("SELECT * FROM graph_accel_build_reachability(ARRAY['A'], 'reach_graph')")
+            .unwrap();
+        assert_eq!(
+            reachable("SELECT graph_accel_reachable('r1', 'r2', ARRAY['A'])"),
+            Some(true)
+        );
+        assert_eq!(
+            reachable("SELECT graph_accel_reachable('r1', 'r3', ARRAY['A'])"),
+            Some(false)
+        );
+        assert_eq!(
+            reachable("SELECT graph_accel_reachable('r1', 'r3')"),
+            Some(true)
+        );
+        assert_eq!(
+            reachable(
+                "SELECT graph_accel_reachable('r1', 'r3', ARRAY['B', 'A'], \
+                 graph_name => 'reach_graph')"
+            ),
+            Some(true)
+        );
+    }
+
+    #[pg_test]
+    fn test_edge_row_reads_properties_only_when_asked() {
+        use graph_accel_core::{Edge, Graph};
+
+        let json = r#"{"conf": 0.5, "w": 2.0, "note": "x"}"#;
+        let mut g = Graph::new();
+        let rt = g.intern_rel_type("LINKS");
+        // No property GUCs set: the JSON is left unparsed, defaults apply
+        let row = crate::load::edge_row(1, 2, Some(json), &[], None, None, None);
+        crate::load::add_edge(&mut g, rt, row);
+        let plain = g.neighbors_out(1).first().unwrap();
+        assert!(!plain.has_confidence());
+        assert_eq!(plain.weight, Edge::DEFAULT_WEIGHT);
+        assert_eq!(plain.created_at, Edge::NO_TIMESTAMP);
+        assert!(g.edge_properties(1, 2, rt).is_none());
+
+        let keys = ["note".to_string()];
+        let row = crate::load::edge_row(1, 3, Some(json), &keys, Some("conf"), Some("w"), None);
+        crate::load::add_edge(&mut g, rt, row);
+        let read = g.neighbors_out(1).get(1).unwrap();
+        assert_eq!(read.confidence, 0.5);
+        assert_eq!(read.weight, 2.0);
+        assert_eq!(g.edge_properties(1, 3, rt).map(|bag| bag.len()), Some(1));
+    }
+
+    #[pg_test]
+    fn test_reload_reports_changes() {
+        Spi::run(
+            "CREATE TABLE reload_edges (from_id int, to_id int, rel_type text);
+             INSERT INTO reload_edges VALUES (1, 2, 'LINKS');
+             SELECT * FROM graph_accel_load_query(
+                $$SELECT id, 'N' AS label, 'n' || id AS app_id
+                  FROM generate_series(1, 3) AS id$$,
+                'SELECT * FROM reload_edges',
+                graph_name => 'reload_graph')",
+        )
+        .unwrap();
+        let reload = |sql: &str| {
+            Spi::get_one::<String>(&format!(
+                "SELECT concat_ws(' ', reloaded::text, old_edge_count, new_edge_count,
+                                  edges_added, edges_removed)
+                 FROM {sql}"
+            ))
+            .unwrap()
+        };
+
+        // Current: nothing to do unless forced
+        assert_eq!(reload("graph_accel_reload()"), Some("false 1 1 0 0".into()));
+        Spi::run(
+            "INSERT INTO reload_edges VALUES (2, 3, 'LINKS');
+             SELECT graph_accel_invalidate('reload_graph')",
+        )
+        .unwrap();
+        assert_eq!(reload("graph_accel_reload()"), Some("true 1 2 1 0".into()));
+        assert_eq!(reload("graph_accel_reload()"), Some("false 2 2 0 0".into()));
+        assert_eq!(
+            reload("graph_accel_reload(force => true)"),
+            Some("true 2 2 0 0".into())
+        );
+    }
+
+    #[pg_test]
+    fn test_graphs_loaded_side_by_side() {
+        load_two_stars("stars");
+        Spi::run(
+            "SELECT * FROM graph_accel_load_query(
+                $$SELECT id, 'N' AS label, 'n' || id AS app_id
+                  FROM generate_series(1, 2) AS id$$,
+                $$SELECT 1 AS from_id, 2 AS to_id, 'LINKS' AS rel_type$$,
+                graph_name => 'pair')",
+        )
+        .unwrap();
+        let count = |sql: &str| Spi::get_one::<i64>(sql).unwrap();
+
+        // Each graph answers for itself; the last one loaded is the default
+        assert_eq!(
+            count("SELECT count(*) FROM graph_accel_neighborhood('n1', 1, graph_name => 'stars')"),
+            Some(3)
+        );
+        assert_eq!(
+            count("SELECT count(*) FROM graph_accel_neighborhood('n1', 1)"),
+            Some(1)
+        );
+        assert_eq!(
+            count("SELECT count(*) FROM graph_accel_degree(graph_name => 'stars')"),
+            Some(8)
+        );
+
+        // Status lists both, and tracks their generations apart
+        Spi::run("SELECT graph_accel_invalidate('stars')").unwrap();
+        let status = Spi::get_one::<String>(
+            "SELECT string_agg(concat_ws(' ', source_graph, status, is_default::text), ','
+                               ORDER BY source_graph)
+             FROM graph_accel_status()",
+        );
+        assert_eq!(
+            status,
+            Ok(Some("pair loaded true,stars stale false".to_string()))
+        );
+
+        assert_eq!(
+            Spi::get_one::<bool>("SELECT graph_accel_unload('pair')"),
+            Ok(Some(true))
+        );
+        assert_eq!(count("SELECT count(*) FROM graph_accel_status()"), Some(1));
+    }
+
+    #[pg_test(
+        error = "graph_accel: graph 'missing' is not loaded — call graph_accel_load('missing') first"
+    )]
+    fn test_unloaded_graph_name_errors() {
+        load_two_stars("stars");
+        Spi::run("SELECT * FROM graph_accel_degree(graph_name => 'missing')").unwrap();
+    }
+
+    #[pg_test]
+    fn test_worker_refresh_publishes_on_invalidation() {
+        use crate::worker::refresh;
+
+        create_age_graph("worker_graph");
+        Spi::run("SET graph_accel.source_graph = 'worker_graph'").unwrap();
+        let first = refresh(None);
+        assert_eq!(first, Some(("worker_graph".to_string(), 0)));
+        // Backends map the file, so the worker drops its own copy
+        assert!(!crate::state::is_loaded("worker_graph"));
+        assert_eq!(refresh(first.clone()), first);
+
+        Spi::run("SELECT graph_accel_invalidate('worker_graph')").unwrap();
+        assert_eq!(refresh(first), Some(("worker_graph".to_string(), 1)));
+        // A backend now attaches instead of reading AGE
+        Spi::run("SELECT * FROM graph_accel_load('worker_graph')").unwrap();
+        assert_eq!(last_load(), (Some("attached".into()), Some(2)));
+    }
+
+    #[pg_test(error = "parameter \"graph_accel.worker_interval_sec\" cannot be changed now")]
+    fn test_worker_gucs() {
+        assert_eq!(
+            Spi::get_one::<String>("SHOW graph_accel.worker"),
+            Ok(Some("off".to_string()))
+        );
+        assert_eq!(
+            Spi::get_one::<String>("SHOW graph_accel.worker_interval_sec"),
+            Ok(Some("5".to_string()))
+        );
+        Spi::run("SET graph_accel.worker_interval_sec = 10").unwrap();
+    }
+
+    #[pg_test]
+    fn test_install_triggers_invalidate_on_sql_writes() {
+        create_age_graph("trig_graph");
+        let generation = || {
+            Spi::get_one::<i64>(
+                "SELECT generation FROM graph_accel.generation WHERE graph_name = 'trig_graph'",
+            )
+            .unwrap()
+        };
+
+        // Base vertex and edge tables plus N and LINKS
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT graph_accel_install_triggers('trig_graph')"),
+            Ok(Some(4))
+        );
+        Spi::run("DELETE FROM trig_graph.\"LINKS\"").unwrap();
+        assert_eq!(generation(), Some(1));
+
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT graph_accel_uninstall_triggers('trig_graph')"),
+            Ok(Some(4))
+        );
+        Spi::run("DELETE FROM trig_graph.\"N\"").unwrap();
+        assert_eq!(generation(), Some(1));
+        assert_eq!(
+            Spi::get_one::<i32>("SELECT graph_accel_uninstall_triggers('trig_graph')"),
+            Ok(Some(0))
+        );
+    }
+
+    #[pg_test(error = "graph_accel: AGE graph 'no_such_graph' does not exist")]
+    fn test_install_triggers_unknown_graph() {
+        Spi::run("CREATE EXTENSION IF NOT EXISTS age").unwrap();
+        Spi::run("SELECT graph_accel_install_triggers('no_such_graph')").unwrap();
+    }
+
+    #[pg_test]
+    fn test_load_query_reads_named_columns() {
+        // Columns in any order, extra ones ignored, app_id left out
+        let counts = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', node_count, edge_count) FROM graph_accel_load_query(
+                $$SELECT 'x' AS note, 'Doc' AS label, id FROM generate_series(7, 9) AS id$$,
+                $$SELECT 0.4 AS confidence, 'CITES' AS rel_type, 8 AS to_id, 7 AS from_id
+                  UNION ALL SELECT 0.9, 'CITES', 9, 7$$,
+                graph_name => 'docs')",
+        );
+        assert_eq!(counts, Ok(Some("3 2".to_string())));
+        let confident = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel_neighborhood('7', 1, min_confidence => 0.5)",
+        );
+        assert_eq!(confident, Ok(Some(1)));
+        let graph = Spi::get_one::<String>(
+            "SELECT source_graph FROM graph_accel_status() WHERE is_default",
+        );
+        assert_eq!(graph, Ok(Some("docs".to_string())));
+    }
+
+    #[pg_test(
+        error = "graph_accel: edge query must return columns from_id, to_id, rel_type; missing rel_type"
+    )]
+    fn test_load_query_missing_column() {
+        Spi::run(
+            "SELECT * FROM graph_accel_load_query(
+                'SELECT 1 AS id, ''N'' AS label',
+                'SELECT 1 AS from_id, 1 AS to_id')",
+        )
+        .unwrap();
+    }
+
+    #[pg_test(error = "graph_accel: node query returned id '-1', expected a non-negative integer")]
+    fn test_load_query_negative_id() {
+        Spi::run(
+            "SELECT * FROM graph_accel_load_query(
+                'SELECT -1 AS id, ''N'' AS label',
+                'SELECT 1 AS from_id, 1 AS to_id, ''R'' AS rel_type')",
+        )
+        .unwrap();
+    }
+
+    #[pg_test]
+    fn test_load_from_plain_tables() {
+        Spi::run(
+            "CREATE SCHEMA rel;
+             CREATE TABLE rel.people (pid bigint, kind text, handle text);
+             INSERT INTO rel.people VALUES (1, 'Person', 'ann'), (2, 'Person', 'bob'),
+                                           (3, 'Bot', 'cal');
+             CREATE TABLE rel.follows (src bigint, dst bigint, score float8);
+             INSERT INTO rel.follows VALUES (1, 2, 0.9), (2, 3, 0.2);
+             SET graph_accel.node_table = 'rel.people';
+             SET graph_accel.node_id_column = 'pid';
+             SET graph_accel.node_label_column = 'kind';
+             SET graph_accel.node_app_id_column = 'handle';
+             SET graph_accel.edge_table = 'rel.follows';
+             SET graph_accel.edge_from_column = 'src';
+             SET graph_accel.edge_to_column = 'dst';
+             SET graph_accel.edge_confidence_column = 'score'",
+        )
+        .unwrap();
+        let counts = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', node_count, edge_count) FROM graph_accel_load('people')",
+        );
+        assert_eq!(counts, Ok(Some("3 2".to_string())));
+
+        // Without edge_type_column the type is the table name
+        let path = Spi::get_one::<String>(
+            "SELECT string_agg(concat_ws(':', rel_type, label), ',' ORDER BY step)
+             FROM graph_accel_path('ann', 'cal')",
+        );
+        assert_eq!(
+            path,
+            Ok(Some("Person,follows:Person,follows:Bot".to_string()))
+        );
+        let confident = Spi::get_one::<i64>(
+            "SELECT count(*) FROM graph_accel_neighborhood('ann', 2, min_confidence => 0.5)",
+        );
+        assert_eq!(confident, Ok(Some(1)));
+    }
+
+    #[pg_test(error = "column \"from_id\" does not exist")]
+    fn test_load_from_plain_tables_default_columns() {
+        // Unset column GUCs fall back to from_id and to_id
+        Spi::run(
+            "CREATE TABLE pairs (a bigint, b bigint);
+             SET graph_accel.edge_table = 'pairs'",
+        )
+        .unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('pairs')").unwrap();
+    }
+
+    #[pg_test]
+    fn test_load_predicates_filter_label_tables() {
+        create_age_graph("pred_graph");
+        let load = || {
+            Spi::get_one::<String>(
+                "SELECT concat_ws(' ', node_count, edge_count)
+                 FROM graph_accel_load('pred_graph')",
+            )
+            .unwrap()
+        };
+        assert_eq!(load(), Some("2 1".to_string()));
+
+        // The edge to the filtered vertex is still loaded
+        Spi::run(
+            "SET graph_accel.load_predicates =
+                '{\"N\": \"properties::text::jsonb->>''i'' <> ''2''\"}'",
+        )
+        .unwrap();
+        assert_eq!(load(), Some("1 1".to_string()));
+        Spi::run("SET graph_accel.load_predicates = '{\"LINKS\": \"false\"}'").unwrap();
+        assert_eq!(load(), Some("2 0".to_string()));
+    }
+
+    #[pg_test(error = "graph_accel.load_predicates: predicate for 'N' must be a string")]
+    fn test_load_predicates_must_be_strings() {
+        create_age_graph("pred_graph");
+        Spi::run("SET graph_accel.load_predicates = '{\"N\": true}'").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('pred_graph')").unwrap();
+    }
+
+    #[pg_test]
+    fn test_load_mixed_case_and_hyphenated_names() {
+        Spi::run("CREATE EXTENSION IF NOT EXISTS age").unwrap();
+        Spi::run("LOAD 'age'").unwrap();
+        Spi::run("SET search_path = ag_catalog, \"$user\", public").unwrap();
+        Spi::run("SELECT create_graph('Mixed-Graph')").unwrap();
+        Spi::run(
+            "SELECT * FROM cypher('Mixed-Graph', $$
+                CREATE (:`Odd-Label` {i: 1})-[:`Has-Link`]->(:`Odd-Label` {i: 2})
+            $$) AS (v agtype)",
+        )
+        .unwrap();
+        let counts = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', node_count, edge_count) FROM graph_accel_load('Mixed-Graph')",
+        );
+        assert_eq!(counts, Ok(Some("2 1".to_string())));
+        let types = Spi::get_one::<String>(
+            "SELECT string_agg(label, ',') FROM graph_accel_degree(graph_name => 'Mixed-Graph')",
+        );
+        assert_eq!(types, Ok(Some("Odd-Label,Odd-Label".to_string())));
+    }
+
+    #[pg_test(error = "graph_accel: invalid name: ''")]
+    fn test_load_rejects_empty_name() {
+        Spi::run("SELECT * FROM graph_accel_load('')").unwrap();
+    }
+
+    #[pg_test]
+    fn test_stats_count_calls_rows_and_errors() {
+        create_age_graph("stats_graph");
+        Spi::run("SELECT * FROM graph_accel_load('stats_graph')").unwrap();
+        Spi::run("SELECT graph_accel_stats_reset()").unwrap();
+        let empty = Spi::get_one::<i64>("SELECT count(*) FROM graph_accel_stats()");
+        assert_eq!(empty, Ok(Some(0)));
+
+        Spi::run("SELECT * FROM graph_accel_degree()").unwrap();
+        Spi::run("SELECT * FROM graph_accel_degree()").unwrap();
+        // Caught in a subtransaction, so the failed call still counts
+        Spi::run(
+            "DO $$ BEGIN
+                PERFORM * FROM graph_accel_degree(graph_name => 'missing');
+            EXCEPTION WHEN OTHERS THEN NULL;
+            END $$",
+        )
+        .unwrap();
+        let degree = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', calls, errors, rows, reloads, (max_ms >= p50_ms)::text)
+             FROM graph_accel_stats() WHERE function = 'graph_accel_degree'",
+        );
+        assert_eq!(degree, Ok(Some("3 1 4 0 true".to_string())));
+
+        Spi::run("SELECT graph_accel_stats_reset()").unwrap();
+        let cleared = Spi::get_one::<i64>("SELECT count(*) FROM graph_accel_stats()");
+        assert_eq!(cleared, Ok(Some(0)));
+    }
+
+    #[pg_test]
+    fn test_resume_interrupted_load() {
+        create_age_graph("resume_graph");
+        // Fail the edge label's scan after the vertex labels are done
+        Spi::run("SET graph_accel.load_predicates = '{\"LINKS\": \"1 / 0 = 1\"}'").unwrap();
+        Spi::run(
+            "DO $$ BEGIN
+                PERFORM * FROM graph_accel_load('resume_graph');
+            EXCEPTION WHEN division_by_zero THEN NULL;
+            END $$",
+        )
+        .unwrap();
+        Spi::run("RESET graph_accel.load_predicates").unwrap();
+        Spi::run("SELECT * FROM graph_accel_load('resume_graph', resume => true)").unwrap();
+        // Nothing left to resume: starts over
+        Spi::run("SELECT * FROM graph_accel_load('resume_graph', resume => true)").unwrap();
+
+        let history = Spi::get_one::<String>(
+            "SELECT string_agg(concat_ws(' ', status, resumed::text, node_count, edge_count,
+                                         (labels_completed = labels_total)::text),
+                               ', ' ORDER BY started_at)
+             FROM graph_accel_load_history() WHERE source_graph = 'resume_graph'",
+        );
+        assert_eq!(
+            history,
+            Ok(Some(
+                "interrupted false 2 0 false, complete true 2 1 true, complete false 2 1 true"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[pg_test]
+    fn test_paths_and_subgraph_max_results() {
+        load_two_stars("cap_graph");
+        // The only path is longer than the cap, but still comes back flagged
+        let paths = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', count(*), bool_and(truncated)::text)
+             FROM graph_accel_paths('n2', 'n4', max_results => 1)",
+        );
+        assert_eq!(paths, Ok(Some("3 true".to_string())));
+        let edges = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', count(*), bool_and(truncated)::text)
+             FROM graph_accel_subgraph('n1', 1, max_results => 2)",
+        );
+        assert_eq!(edges, Ok(Some("2 true".to_string())));
+        let edges = Spi::get_one::<String>(
+            "SELECT concat_ws(' ', count(*), bool_or(truncated)::text)
+             FROM graph_accel_subgraph('n1', 1, max_results => 3)",
+        );
+        assert_eq!(edges, Ok(Some("3 false".to_string())));
+    }
 }
 
 #[cfg(test)]
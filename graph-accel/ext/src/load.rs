@@ -1,32 +1,41 @@
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::sync::Arc;
 use std::time::Instant;
 
-use graph_accel_core::{Edge, Graph};
+use graph_accel_core::{Edge, FrozenGraph, Graph, PropValue, RelTypeId};
 use pgrx::prelude::*;
-use pgrx::spi::{quote_identifier, quote_literal};
+use pgrx::guc::GucSetting;
+use pgrx::spi::quote_identifier;
 
 use crate::generation;
 use crate::guc;
-use crate::state::{self, GraphState};
+use crate::shared;
+use crate::state::{self, GraphSource, GraphState};
+use crate::stats;
 
 /// Core load logic, callable from both `graph_accel_load()` and `ensure_fresh()`.
 ///
-/// Loads the graph via SPI, captures the current generation, and sets per-backend state.
-/// Returns (node_count, edge_count, load_time_ms).
-pub(crate) fn do_load(graph_name: &str) -> (i64, i64, f64) {
+/// Loads the graph via SPI one label at a time, captures the current generation,
+/// and sets per-backend state. Returns (node_count, edge_count, load_time_ms).
+///
+/// Each label's rows are staged in full before being applied to the graph, so
+/// an ERROR mid-scan (cancel, SPI failure) never leaves a half-applied label.
+/// The graph under construction lives in a per-backend checkpoint: with
+/// `resume = true`, a retry skips labels a previous interrupted load of the
+/// same graph and generation already completed.
+pub(crate) fn do_load(graph_name: &str, resume: bool) -> (i64, i64, f64) {
     let start = Instant::now();
 
     validate_name(graph_name);
 
-    let (node_count, edge_count, loaded_gen) = Spi::connect(|client| {
+    let (node_count, edge_count) = Spi::connect(|client| {
         // Verify graph exists
         let exists = client
             .select(
-                &format!(
-                    "SELECT 1 FROM ag_catalog.ag_graph WHERE name = {}",
-                    quote_literal(graph_name)
-                ),
+                "SELECT 1 FROM ag_catalog.ag_graph WHERE name = $1",
                 None,
-                &[],
+                &[graph_name.into()],
             )?
             .next()
             .is_some();
@@ -46,69 +55,361 @@ pub(crate) fn do_load(graph_name: &str) -> (i64, i64, f64) {
             &guc::get_string(&guc::EDGE_TYPES).unwrap_or_else(|| "*".to_string()),
         );
         let node_id_prop = guc::get_string(&guc::NODE_ID_PROPERTY);
+        let index_props = property_keys(&guc::INDEX_PROPERTIES);
+        let node_props = property_keys(&guc::NODE_PROPERTIES);
+        let label_prop = guc::get_string(&guc::NODE_LABEL_PROPERTY);
+        let edge_props = property_keys(&guc::EDGE_PROPERTIES);
+        let confidence_prop = guc::get_string(&guc::EDGE_CONFIDENCE_PROPERTY);
+        let weight_prop = guc::get_string(&guc::EDGE_WEIGHT_PROPERTY);
+        let timestamp_prop = guc::get_string(&guc::EDGE_TIMESTAMP_PROPERTY);
+        let predicates = load_predicates();
 
-        let mut graph = Graph::new();
+        // Read current generation up front (0 if no row or table inaccessible).
+        // Writes that land during the load bump it past this value, so the
+        // loaded graph is conservatively reported stale.
+        let gen = generation::fetch_generation_spi(&client, graph_name).unwrap_or(0);
 
-        // Load vertices
-        for label in labels.iter().filter(|l| l.kind == 'v') {
-            if !matches_filter(&label.name, &node_label_filter) {
-                continue;
-            }
-            load_vertices(
+        // Vertices first so edges see node metadata; filtered labels are skipped.
+        // With a label property, any vertex table may hold matching nodes.
+        let plan: Vec<&LabelInfo> = labels
+            .iter()
+            .filter(|l| {
+                l.kind == 'v'
+                    && (label_prop.is_some() || matches_filter(&l.name, &node_label_filter))
+            })
+            .chain(
+                labels
+                    .iter()
+                    .filter(|l| l.kind == 'e' && matches_filter(&l.name, &edge_type_filter)),
+            )
+            .collect();
+
+        // Sharing bypasses AGE's access checks; only users who could read
+        // every planned table in full attach or publish
+        let share = shared::enabled()
+            && shared::permitted(
                 &client,
                 graph_name,
-                &label.name,
-                node_id_prop.as_deref(),
-                &mut graph,
+                plan.iter().map(|l| l.name.clone()).collect(),
             )?;
+
+        // Another backend may already have published this generation
+        if let Some(graph) = share.then(|| shared::attach(graph_name, gen)).flatten() {
+            let graph = graph.into_shared();
+            let (nc, ec) = (graph.node_count(), graph.edge_count());
+            state::push_load_history(state::LoadHistoryEntry {
+                source_graph: graph_name.to_string(),
+                started_at: client
+                    .select("SELECT clock_timestamp()", None, &[])?
+                    .first()
+                    .get_one()?,
+                outcome: state::LoadOutcome::Attached,
+                resumed: false,
+                labels_completed: plan.len(),
+                labels_total: plan.len(),
+                node_count: nc,
+                edge_count: ec,
+                load_time_ms: 0.0,
+            });
+            install(graph_name, graph, gen, start);
+            return Ok((nc as i64, ec as i64));
         }
 
-        // Load edges
-        for label in labels.iter().filter(|l| l.kind == 'e') {
-            if !matches_filter(&label.name, &edge_type_filter) {
-                continue;
-            }
-            load_edges(&client, graph_name, &label.name, &mut graph)?;
+        let resumed = resume && state::has_checkpoint(graph_name, gen);
+        if resume && !resumed {
+            notice!(
+                "graph_accel: no resumable load of '{}' at generation {}, starting over",
+                graph_name,
+                gen
+            );
+        }
+        if !resumed {
+            state::start_checkpoint(graph_name, gen);
         }
 
-        // Check memory limit
-        let memory_mb = graph.memory_usage() / (1024 * 1024);
+        let already_done = state::with_checkpoint(|cp| cp.completed.len());
+        state::push_load_history(state::LoadHistoryEntry {
+            source_graph: graph_name.to_string(),
+            started_at: client
+                .select("SELECT clock_timestamp()", None, &[])?
+                .first()
+                .get_one()?,
+            outcome: state::LoadOutcome::Interrupted,
+            resumed,
+            labels_completed: already_done,
+            labels_total: plan.len(),
+            node_count: 0,
+            edge_count: 0,
+            load_time_ms: 0.0,
+        });
+
         let max_mb = guc::MAX_MEMORY_MB.get() as usize;
-        if memory_mb > max_mb {
-            error!(
-                "graph_accel: loaded graph uses {}MB, exceeds graph_accel.max_memory_mb={}MB",
-                memory_mb, max_mb
-            );
+        let mut budget = MemoryBudget {
+            graph_bytes: state::with_checkpoint(|cp| cp.graph.memory_usage()),
+            max_mb,
+        };
+
+        for label in plan {
+            let key = (label.kind, label.name.clone());
+            if state::with_checkpoint(|cp| cp.completed.contains(&key)) {
+                continue;
+            }
+
+            if label.kind == 'v' {
+                let rows = fetch_vertices(
+                    &client,
+                    graph_name,
+                    &label.name,
+                    node_id_prop.as_deref(),
+                    &index_props,
+                    &node_props,
+                    label_prop.as_deref(),
+                    predicates.get(&label.name).map(String::as_str),
+                    &budget,
+                )?;
+                budget.check_staged(&label.name, rows.iter().map(VertexRow::footprint), true);
+                let table_matches = matches_filter(&label.name, &node_label_filter);
+                state::with_checkpoint(|cp| {
+                    for row in rows {
+                        // A node is kept if any of its labels passes the filter
+                        if !table_matches
+                            && !row.labels.iter().any(|l| matches_filter(l, &node_label_filter))
+                        {
+                            continue;
+                        }
+                        add_vertex(&mut cp.graph, &label.name, row);
+                    }
+                });
+            } else {
+                let rows = fetch_edges(
+                    &client,
+                    graph_name,
+                    &label.name,
+                    &edge_props,
+                    confidence_prop.as_deref(),
+                    weight_prop.as_deref(),
+                    timestamp_prop.as_deref(),
+                    predicates.get(&label.name).map(String::as_str),
+                    &budget,
+                )?;
+                budget.check_staged(&label.name, rows.iter().map(EdgeRow::footprint), true);
+                state::with_checkpoint(|cp| {
+                    let rel_type_id = cp.graph.intern_rel_type(&label.name);
+                    for row in rows {
+                        add_edge(&mut cp.graph, rel_type_id, row);
+                    }
+                });
+            }
+
+            let (memory_bytes, completed, nc, ec) = state::with_checkpoint(|cp| {
+                cp.completed.insert(key);
+                (
+                    cp.graph.memory_usage(),
+                    cp.completed.len(),
+                    cp.graph.node_count(),
+                    cp.graph.edge_count(),
+                )
+            });
+            state::update_load_history(|h| {
+                h.labels_completed = completed;
+                h.node_count = nc;
+                h.edge_count = ec;
+                h.load_time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            });
+
+            // Estimates can undershoot, so check the real figure after each label
+            // too. The completed labels stay checkpointed: raise the cap and resume.
+            budget.graph_bytes = memory_bytes;
+            check_memory(memory_bytes / (1024 * 1024), max_mb);
         }
 
-        // Read current generation (0 if no row or table inaccessible)
-        let gen = generation::fetch_generation_spi(&client, graph_name).unwrap_or(0);
+        // A resume may have had nothing left to load; re-check against the
+        // current cap, with what freezing adds while it packs the lists
+        state::with_checkpoint(|cp| check_freeze(&cp.graph, max_mb));
 
+        let graph = state::take_checkpoint()
+            .expect("graph_accel: no load checkpoint")
+            .graph;
+        let mut graph = seal(graph);
+        check_memory(graph.memory_usage() / (1024 * 1024), max_mb);
+        if share {
+            if let Some(mapped) = shared::publish(graph_name, gen, &graph) {
+                graph = mapped.into_shared();
+            }
+        }
         let nc = graph.node_count() as i64;
         let ec = graph.edge_count() as i64;
+        install(graph_name, graph, gen, start);
 
-        state::set_graph(GraphState {
-            graph,
-            source_graph: graph_name.to_string(),
-            load_time_ms: start.elapsed().as_secs_f64() * 1000.0,
-            loaded_at: Instant::now(),
-            loaded_generation: gen,
-        });
-
-        Ok::<_, pgrx::spi::SpiError>((nc, ec, gen))
+        Ok::<_, pgrx::spi::SpiError>((nc, ec))
     })
     .unwrap_or_else(|e| {
         error!("graph_accel_load: SPI error: {}", e);
     });
 
     let load_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-    let _ = loaded_gen; // used internally via set_graph
+    state::update_load_history(|h| {
+        if h.outcome == state::LoadOutcome::Interrupted {
+            h.outcome = state::LoadOutcome::Complete;
+        }
+        h.load_time_ms = load_time_ms;
+    });
     (node_count, edge_count, load_time_ms)
 }
 
+/// Finish a loaded graph: apply `graph_accel.dedupe_edges` and
+/// `graph_accel.group_edges_by_type`, then pack adjacency into CSR for
+/// traversal, drop the capacity the load estimates over-reserved and seal
+/// it for sharing.
+pub(crate) fn seal(mut graph: Graph) -> Arc<FrozenGraph> {
+    if guc::DEDUPE_EDGES.get() {
+        let removed = graph.dedupe_edges();
+        if removed > 0 {
+            notice!("graph_accel: collapsed {} duplicate edges", removed);
+        }
+    }
+    if guc::GROUP_EDGES_BY_TYPE.get() {
+        graph.group_by_rel_type();
+    }
+    graph.into_shared()
+}
+
+/// Reload `graph_name` from where it was first read.
+pub(crate) fn reload(graph_name: &str, source: &GraphSource) -> (i64, i64, f64) {
+    match source {
+        GraphSource::Age => do_load(graph_name, false),
+        GraphSource::Query { node_sql, edge_sql } => {
+            crate::query::do_load_query(graph_name, node_sql, edge_sql)
+        }
+    }
+}
+
+/// Make `graph`, read from AGE, the loaded copy of `graph_name`.
+fn install(graph_name: &str, graph: Arc<FrozenGraph>, gen: i64, start: Instant) {
+    install_from(graph_name, GraphSource::Age, graph, gen, start);
+}
+
+/// Make `graph`, read from `source`, the loaded copy of `graph_name`.
+pub(crate) fn install_from(
+    graph_name: &str,
+    source: GraphSource,
+    graph: Arc<FrozenGraph>,
+    gen: i64,
+    start: Instant,
+) {
+    state::set_graph(GraphState {
+        graph,
+        source_graph: graph_name.to_string(),
+        source,
+        load_time_ms: start.elapsed().as_secs_f64() * 1000.0,
+        loaded_at: Instant::now(),
+        loaded_generation: gen,
+        reachability: None,
+        landmarks: None,
+        components: None,
+    });
+}
+
+/// Rows fetched per SPI cursor batch, and so between memory checks, while
+/// a label is being staged.
+const MEMORY_CHECK_ROWS: usize = 10_000;
+
+/// Run `query` through an SPI cursor and hand `each` its rows
+/// `MEMORY_CHECK_ROWS` at a time.
+///
+/// `client.select()` materializes the whole result before the first row
+/// can be looked at, so a label too large for the memory cap would be
+/// held in full before any check ran. With a cursor only one batch of
+/// tuples is held at once, and `each` can check the budget in between.
+pub(crate) fn for_each_batch<'conn, F>(
+    client: &pgrx::spi::SpiClient<'conn>,
+    query: &str,
+    mut each: F,
+) -> Result<(), pgrx::spi::SpiError>
+where
+    F: FnMut(pgrx::spi::SpiTupleTable<'conn>) -> Result<(), pgrx::spi::SpiError>,
+{
+    let mut cursor = client.open_cursor(query, &[]);
+    loop {
+        let batch = cursor.fetch(MEMORY_CHECK_ROWS as i64)?;
+        if batch.is_empty() {
+            return Ok(());
+        }
+        each(batch)?;
+    }
+}
+
+/// Running memory estimate for one load against `graph_accel.max_memory_mb`.
+///
+/// `memory_usage()` walks every node, too slow to call every few thousand
+/// rows, so between labels the estimate is the graph's last measured size
+/// plus what the current label's staged rows take. Applying a label about
+/// doubles that: the rows move into the graph, but the staging buffer lives
+/// until the label is done. The projection is checked before applying, so
+/// an oversized label errors out without touching the checkpoint.
+struct MemoryBudget {
+    /// `memory_usage()` after the last completed label.
+    graph_bytes: usize,
+    max_mb: usize,
+}
+
+impl MemoryBudget {
+    /// Error out if the graph plus `staged` row footprints, doubled once
+    /// `applying`, would exceed the cap.
+    fn check_staged(&self, label: &str, staged: impl Iterator<Item = usize>, applying: bool) {
+        let staged: usize = staged.sum();
+        let projected = self.graph_bytes + if applying { 2 * staged } else { staged };
+        let projected_mb = projected / (1024 * 1024);
+        if projected_mb > self.max_mb {
+            error!(
+                "graph_accel: loading label '{}' would take about {}MB, \
+                 exceeds graph_accel.max_memory_mb={}MB",
+                label, projected_mb, self.max_mb
+            );
+        }
+    }
+}
+
+pub(crate) fn check_memory(memory_mb: usize, max_mb: usize) {
+    if memory_mb > max_mb {
+        error!(
+            "graph_accel: loaded graph uses {}MB, exceeds graph_accel.max_memory_mb={}MB",
+            memory_mb, max_mb
+        );
+    }
+}
+
+/// Error out if `graph` would exceed the cap at the peak of `seal`'s freeze.
+pub(crate) fn check_freeze(graph: &Graph, max_mb: usize) {
+    let peak_mb = (graph.memory_usage() + graph.freeze_peak_bytes()) / (1024 * 1024);
+    if peak_mb > max_mb {
+        error!(
+            "graph_accel: packing the loaded graph would take about {}MB, \
+             exceeds graph_accel.max_memory_mb={}MB",
+            peak_mb, max_mb
+        );
+    }
+}
+
+/// Load an AGE graph into per-backend memory.
+///
+/// Other loaded graphs stay loaded; the one loaded last becomes the
+/// default for functions called without a `graph_name`.
+///
+/// With `resume => true`, continues an interrupted load of the same graph
+/// (e.g. after a memory-cap error or cancel) instead of starting over.
+///
+/// With `graph_accel.node_table` or `graph_accel.edge_table` set, the graph
+/// is read from those plain tables instead and AGE is not needed; see
+/// `graph_accel_load_query`, which it behaves like. `resume` is ignored.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_load('my_graph');
+///   SELECT * FROM graph_accel_load('my_graph', resume => true);
 #[pg_extern]
 fn graph_accel_load(
     graph_name: default!(Option<String>, "NULL"),
+    resume: default!(bool, false),
 ) -> TableIterator<
     'static,
     (
@@ -117,6 +418,7 @@ fn graph_accel_load(
         name!(load_time_ms, f64),
     ),
 > {
+    let _call = stats::call("graph_accel_load");
     // Resolve graph name: explicit argument > GUC > error
     let gname = graph_name
         .or_else(|| guc::get_string(&guc::SOURCE_GRAPH))
@@ -124,10 +426,28 @@ fn graph_accel_load(
             error!("graph_accel: source_graph not set and no graph_name argument provided");
         });
 
-    let result = do_load(&gname);
+    // Plain tables, if configured, replace the AGE graph
+    let result = match crate::query::table_queries() {
+        Some((node_sql, edge_sql)) => {
+            validate_name(&gname);
+            crate::query::do_load_query(&gname, &node_sql, &edge_sql)
+        }
+        None => do_load(&gname, resume),
+    };
+    state::set_default_graph(&gname);
     TableIterator::once(result)
 }
 
+/// Drop a loaded graph from this backend, freeing its memory. Returns
+/// false if it was not loaded.
+///
+/// Usage:
+///   SELECT graph_accel_unload('my_graph');
+#[pg_extern]
+fn graph_accel_unload(graph_name: String) -> bool {
+    state::remove_graph(&graph_name).is_some()
+}
+
 // ---------------------------------------------------------------------------
 // Label catalog
 // ---------------------------------------------------------------------------
@@ -141,17 +461,14 @@ fn load_label_catalog(
     client: &pgrx::spi::SpiClient<'_>,
     graph_name: &str,
 ) -> Result<Vec<LabelInfo>, pgrx::spi::SpiError> {
-    let query = format!(
-        "SELECT l.name::text, l.kind::text \
-         FROM ag_catalog.ag_label l \
-         JOIN ag_catalog.ag_graph g ON l.graph = g.graphid \
-         WHERE g.name = {} \
-           AND l.name NOT LIKE '_ag%%'",
-        quote_literal(graph_name)
-    );
+    let query = "SELECT l.name::text, l.kind::text \
+                 FROM ag_catalog.ag_label l \
+                 JOIN ag_catalog.ag_graph g ON l.graph = g.graphid \
+                 WHERE g.name = $1 \
+                   AND l.name NOT LIKE '_ag%'";
 
     let mut labels = Vec::new();
-    let table = client.select(&query, None, &[])?;
+    let table = client.select(query, None, &[graph_name.into()])?;
     for row in table {
         let name: String = row
             .get_by_name("name")
@@ -172,124 +489,531 @@ fn load_label_catalog(
 // Vertex loading
 // ---------------------------------------------------------------------------
 
-fn load_vertices(
+/// A `graph_accel.node_id_property` value. Its JSON type picks the index:
+/// integers go to the integer app ID index, strings to the text one.
+enum AppId {
+    Text(String),
+    Int(i64),
+}
+
+/// One vertex as read from AGE.
+pub(crate) struct VertexRow {
+    node_id: u64,
+    app_id: Option<AppId>,
+    /// (index name, value) pairs (`graph_accel.index_properties`).
+    index_values: Vec<(String, String)>,
+    /// Kept properties (`graph_accel.node_properties`).
+    props: Vec<(String, PropValue)>,
+    /// Extra labels (`graph_accel.node_label_property`).
+    pub(crate) labels: Vec<String>,
+}
+
+impl VertexRow {
+    /// Approximate bytes held while staged.
+    fn footprint(&self) -> usize {
+        let app_id = match &self.app_id {
+            Some(AppId::Text(s)) => s.capacity(),
+            _ => 0,
+        };
+        std::mem::size_of::<Self>()
+            + app_id
+            + self
+                .index_values
+                .iter()
+                .map(|(k, v)| std::mem::size_of::<(String, String)>() + k.capacity() + v.capacity())
+                .sum::<usize>()
+            + props_footprint(&self.props)
+            + self
+                .labels
+                .iter()
+                .map(|l| std::mem::size_of::<String>() + l.capacity())
+                .sum::<usize>()
+    }
+}
+
+/// Read the vertices of one label that satisfy `predicate`, keeping the
+/// `index_keys` values for secondary indices, the `prop_keys` properties and
+/// the extra labels held in `label_prop`.
+#[allow(clippy::too_many_arguments)]
+fn fetch_vertices(
     client: &pgrx::spi::SpiClient<'_>,
     graph_name: &str,
     label_name: &str,
     node_id_prop: Option<&str>,
-    graph: &mut Graph,
-) -> Result<(), pgrx::spi::SpiError> {
+    index_keys: &[String],
+    prop_keys: &[String],
+    label_prop: Option<&str>,
+    predicate: Option<&str>,
+    budget: &MemoryBudget,
+) -> Result<Vec<VertexRow>, pgrx::spi::SpiError> {
     let query = format!(
-        "SELECT id::text, properties::text FROM {}.{}",
+        "SELECT id::text, properties::text FROM {}.{}{}",
         quote_identifier(graph_name),
-        quote_identifier(label_name)
+        quote_identifier(label_name),
+        where_clause(predicate)
     );
 
-    let table = client.select(&query, None, &[])?;
-    for row in table {
-        let id_str: Option<String> = row.get_by_name("id")?;
-        let props_str: Option<String> = row.get_by_name("properties")?;
+    let mut rows = Vec::new();
+    let mut staged = 0;
+    for_each_batch(client, &query, |batch| {
+        for row in batch {
+            check_for_interrupts!();
+            let id_str: Option<String> = row.get_by_name("id")?;
+            let props_str: Option<String> = row.get_by_name("properties")?;
 
-        let Some(id_str) = id_str else { continue };
-        let node_id: u64 = match id_str.parse() {
-            Ok(id) => id,
-            Err(_) => continue,
-        };
+            let Some(id_str) = id_str else { continue };
+            let node_id: u64 = match id_str.parse() {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
 
-        let app_id = node_id_prop.and_then(|prop| {
-            props_str
-                .as_deref()
-                .and_then(|json| extract_json_string(json, prop))
-        });
+            let row = vertex_row(
+                node_id,
+                props_str.as_deref(),
+                node_id_prop,
+                index_keys,
+                prop_keys,
+                label_prop,
+            );
+            staged += row.footprint();
+            rows.push(row);
+        }
+        budget.check_staged(label_name, std::iter::once(staged), false);
+        Ok(())
+    })?;
+
+    Ok(rows)
+}
+
+/// Build a `VertexRow` from a vertex's `properties` JSON, as `fetch_vertices`
+/// does for each row.
+pub(crate) fn vertex_row(
+    node_id: u64,
+    props_str: Option<&str>,
+    node_id_prop: Option<&str>,
+    index_keys: &[String],
+    prop_keys: &[String],
+    label_prop: Option<&str>,
+) -> VertexRow {
+    let app_id =
+        node_id_prop.and_then(|prop| props_str.and_then(|json| extract_app_id(json, prop)));
 
-        graph.add_node(node_id, label_name.to_string(), app_id);
+    // Parse once for index values, properties and labels, and only if any is wanted
+    let wanted = !index_keys.is_empty() || !prop_keys.is_empty() || label_prop.is_some();
+    let parsed: Option<serde_json::Value> = match props_str {
+        Some(json) if wanted => serde_json::from_str(json).ok(),
+        _ => None,
+    };
+    let index_values = parsed
+        .as_ref()
+        .map(|value| json_index_values(value, index_keys))
+        .unwrap_or_default();
+    let props = parsed
+        .as_ref()
+        .map(|value| json_properties(value, prop_keys))
+        .unwrap_or_default();
+    let labels = match (parsed.as_ref(), label_prop) {
+        (Some(value), Some(key)) => json_labels(value, key),
+        _ => Vec::new(),
+    };
+
+    VertexRow {
+        node_id,
+        app_id,
+        index_values,
+        props,
+        labels,
     }
+}
 
-    Ok(())
+/// Register a vertex of label table `label` with its app ID, properties,
+/// extra labels and index entries. Replaces an existing node's metadata.
+pub(crate) fn add_vertex(graph: &mut Graph, label: &str, row: VertexRow) {
+    match row.app_id {
+        Some(AppId::Int(aid)) => {
+            graph.add_node(row.node_id, label.to_string(), None);
+            graph.index_int_app_id(aid, row.node_id);
+        }
+        Some(AppId::Text(aid)) => graph.add_node(row.node_id, label.to_string(), Some(aid)),
+        None => graph.add_node(row.node_id, label.to_string(), None),
+    }
+    graph.set_node_properties(row.node_id, row.props);
+    graph.add_node_labels(row.node_id, row.labels);
+    for (index, value) in row.index_values {
+        graph.index_node(&index, value, row.node_id);
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Edge loading
 // ---------------------------------------------------------------------------
 
-fn load_edges(
+/// One edge as read from AGE.
+pub(crate) struct EdgeRow {
+    from_id: u64,
+    to_id: u64,
+    /// From `graph_accel.edge_confidence_property`, else `Edge::NO_CONFIDENCE`.
+    confidence: f32,
+    /// From `graph_accel.edge_weight_property`, else `Edge::DEFAULT_WEIGHT`.
+    weight: f32,
+    /// From `graph_accel.edge_timestamp_property`, else `Edge::NO_TIMESTAMP`.
+    created_at: u32,
+    /// Kept properties (`graph_accel.edge_properties`).
+    props: Vec<(String, PropValue)>,
+}
+
+impl EdgeRow {
+    /// Approximate bytes held while staged.
+    fn footprint(&self) -> usize {
+        std::mem::size_of::<Self>() + props_footprint(&self.props)
+    }
+}
+
+/// Read the edges of one label that satisfy `predicate`, keeping the
+/// `prop_keys` properties, the confidence held in `confidence_prop`, the
+/// routing weight held in `weight_prop` and the creation time held in
+/// `timestamp_prop`.
+#[allow(clippy::too_many_arguments)]
+fn fetch_edges(
     client: &pgrx::spi::SpiClient<'_>,
     graph_name: &str,
     label_name: &str,
-    graph: &mut Graph,
-) -> Result<(), pgrx::spi::SpiError> {
-    let rel_type_id = graph.intern_rel_type(label_name);
-
+    prop_keys: &[String],
+    confidence_prop: Option<&str>,
+    weight_prop: Option<&str>,
+    timestamp_prop: Option<&str>,
+    predicate: Option<&str>,
+    budget: &MemoryBudget,
+) -> Result<Vec<EdgeRow>, pgrx::spi::SpiError> {
     let query = format!(
-        "SELECT start_id::text, end_id::text, properties::text FROM {}.{}",
+        "SELECT start_id::text, end_id::text, properties::text FROM {}.{}{}",
         quote_identifier(graph_name),
-        quote_identifier(label_name)
+        quote_identifier(label_name),
+        where_clause(predicate)
     );
 
-    let table = client.select(&query, None, &[])?;
-    for row in table {
-        let from_str: Option<String> = row.get_by_name("start_id")?;
-        let to_str: Option<String> = row.get_by_name("end_id")?;
-        let props_str: Option<String> = row.get_by_name("properties")?;
+    let mut rows = Vec::new();
+    let mut staged = 0;
+    for_each_batch(client, &query, |batch| {
+        for row in batch {
+            check_for_interrupts!();
+            let from_str: Option<String> = row.get_by_name("start_id")?;
+            let to_str: Option<String> = row.get_by_name("end_id")?;
+            let props_str: Option<String> = row.get_by_name("properties")?;
 
-        let (Some(from_str), Some(to_str)) = (from_str, to_str) else {
-            continue;
-        };
+            let (Some(from_str), Some(to_str)) = (from_str, to_str) else {
+                continue;
+            };
 
-        let from_id: u64 = match from_str.parse() {
-            Ok(id) => id,
-            Err(_) => continue,
-        };
-        let to_id: u64 = match to_str.parse() {
-            Ok(id) => id,
-            Err(_) => continue,
-        };
+            let from_id: u64 = match from_str.parse() {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            let to_id: u64 = match to_str.parse() {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+
+            let row = edge_row(
+                from_id,
+                to_id,
+                props_str.as_deref(),
+                prop_keys,
+                confidence_prop,
+                weight_prop,
+                timestamp_prop,
+            );
+            staged += row.footprint();
+            rows.push(row);
+        }
+        budget.check_staged(label_name, std::iter::once(staged), false);
+        Ok(())
+    })?;
 
-        let confidence = props_str
-            .as_deref()
-            .and_then(|json| extract_json_float(json, "confidence"))
-            .map(|v| v as f32)
-            .unwrap_or(Edge::NO_CONFIDENCE);
+    Ok(rows)
+}
+
+/// Build an `EdgeRow` from an edge's `properties` JSON, as `fetch_edges`
+/// does for each row. The JSON is only parsed when a property, confidence,
+/// weight or timestamp key asks for something from it.
+pub(crate) fn edge_row(
+    from_id: u64,
+    to_id: u64,
+    props_str: Option<&str>,
+    prop_keys: &[String],
+    confidence_prop: Option<&str>,
+    weight_prop: Option<&str>,
+    timestamp_prop: Option<&str>,
+) -> EdgeRow {
+    // Most loads keep nothing from edge properties: skip the parse then
+    let wanted = !prop_keys.is_empty()
+        || confidence_prop.is_some()
+        || weight_prop.is_some()
+        || timestamp_prop.is_some();
+    let parsed: Option<serde_json::Value> = match props_str {
+        Some(json) if wanted => serde_json::from_str(json).ok(),
+        _ => None,
+    };
+    let float = |key: &str| parsed.as_ref().and_then(|v| v.get(key)).and_then(|v| v.as_f64());
+
+    let confidence = confidence_prop
+        .and_then(float)
+        .map(|v| v as f32)
+        .unwrap_or(Edge::NO_CONFIDENCE);
+    let weight = weight_prop
+        .and_then(float)
+        .map(|v| v as f32)
+        .unwrap_or(Edge::DEFAULT_WEIGHT);
+    let created_at = timestamp_prop
+        .and_then(|key| parsed.as_ref()?.get(key))
+        .map(json_timestamp)
+        .unwrap_or(Edge::NO_TIMESTAMP);
+    let props = parsed
+        .as_ref()
+        .map(|value| json_properties(value, prop_keys))
+        .unwrap_or_default();
 
-        graph.add_edge(from_id, to_id, rel_type_id, confidence);
+    EdgeRow {
+        from_id,
+        to_id,
+        confidence,
+        weight,
+        created_at,
+        props,
     }
+}
 
-    Ok(())
+/// Add one edge of an interned relationship type with its properties.
+pub(crate) fn add_edge(graph: &mut Graph, rel_type_id: RelTypeId, row: EdgeRow) {
+    graph.add_timestamped_edge(
+        row.from_id,
+        row.to_id,
+        rel_type_id,
+        row.confidence,
+        row.weight,
+        row.created_at,
+    );
+    graph.set_edge_properties(row.from_id, row.to_id, rel_type_id, row.props);
 }
 
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
-/// Extract a string value from a JSON object by key.
-fn extract_json_string(json: &str, key: &str) -> Option<String> {
-    let value: serde_json::Value = serde_json::from_str(json).ok()?;
-    value
-        .get(key)
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
+/// Approximate bytes of staged properties.
+fn props_footprint(props: &[(String, PropValue)]) -> usize {
+    props
+        .iter()
+        .map(|(key, value)| {
+            let text = match value {
+                PropValue::Text(s) => s.capacity(),
+                _ => 0,
+            };
+            std::mem::size_of::<(String, PropValue)>() + key.capacity() + text
+        })
+        .sum()
 }
 
-/// Extract a float value from a JSON object by key.
-fn extract_json_float(json: &str, key: &str) -> Option<f64> {
+/// Extract an app ID from a JSON object by key: a string, or an integer
+/// that fits in i64. Other values are not indexed.
+fn extract_app_id(json: &str, key: &str) -> Option<AppId> {
     let value: serde_json::Value = serde_json::from_str(json).ok()?;
-    value.get(key).and_then(|v| v.as_f64())
+    match value.get(key)? {
+        serde_json::Value::String(s) => Some(AppId::Text(s.clone())),
+        serde_json::Value::Number(n) => n.as_i64().map(AppId::Int),
+        _ => None,
+    }
+}
+
+/// Index key values of a JSON properties object: strings as is, numbers in
+/// their JSON form. Other values are not indexed.
+fn json_index_values(value: &serde_json::Value, keys: &[String]) -> Vec<(String, String)> {
+    keys.iter()
+        .filter_map(|key| {
+            let text = match value.get(key)? {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => return None,
+            };
+            Some((key.clone(), text))
+        })
+        .collect()
+}
+
+/// Property names from a comma-separated list GUC
+/// (`graph_accel.node_properties`, `graph_accel.edge_properties`,
+/// `graph_accel.index_properties`).
+pub(crate) fn property_keys(setting: &GucSetting<Option<CString>>) -> Vec<String> {
+    guc::get_string(setting)
+        .map(|spec| {
+            spec.split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Label → SQL predicate from `graph_accel.load_predicates`, a JSON object
+/// such as `{"Concept": "properties::text::jsonb->>'status' = 'active'"}`.
+/// Keys are vertex labels or edge types, i.e. label table names.
+pub(crate) fn load_predicates() -> HashMap<String, String> {
+    let Some(spec) = guc::get_string(&guc::LOAD_PREDICATES) else {
+        return HashMap::new();
+    };
+    let value: Option<serde_json::Value> = serde_json::from_str(&spec).ok();
+    let Some(entries) = value.as_ref().and_then(|v| v.as_object()) else {
+        error!("graph_accel.load_predicates must be a JSON object of label -> SQL predicate");
+    };
+    entries
+        .iter()
+        .map(|(label, predicate)| {
+            let predicate = predicate.as_str().unwrap_or_else(|| {
+                error!(
+                    "graph_accel.load_predicates: predicate for '{}' must be a string",
+                    label
+                )
+            });
+            (label.clone(), predicate.to_string())
+        })
+        .collect()
+}
+
+/// ` WHERE (predicate)`, or nothing without one.
+pub(crate) fn where_clause(predicate: Option<&str>) -> String {
+    predicate
+        .map(|p| format!(" WHERE ({})", p))
+        .unwrap_or_default()
+}
+
+/// Pick the `keys` entries of a JSON properties object that hold scalars.
+/// Integers stay integers; other numbers become floats. Booleans, nulls,
+/// arrays and objects are skipped.
+pub(crate) fn json_properties(
+    value: &serde_json::Value,
+    keys: &[String],
+) -> Vec<(String, PropValue)> {
+    keys.iter()
+        .filter_map(|key| {
+            let v = value.get(key)?;
+            let prop = if let Some(i) = v.as_i64() {
+                PropValue::Int(i)
+            } else if let Some(f) = v.as_f64() {
+                PropValue::Float(f)
+            } else {
+                PropValue::Text(v.as_str()?.to_string())
+            };
+            Some((key.clone(), prop))
+        })
+        .collect()
 }
 
-/// Validate a name contains only safe characters before use in queries.
+/// Seconds since the Unix epoch from a JSON number or an ISO 8601 string
+/// (`2024-05-01`, `2024-05-01T12:30:00.123+02:00`; no zone means UTC).
+/// Unparseable values and times outside the u32 range (before 1970 or
+/// after 2106) become `Edge::NO_TIMESTAMP`.
+pub(crate) fn json_timestamp(value: &serde_json::Value) -> u32 {
+    let secs = match value {
+        serde_json::Value::Number(n) => n.as_i64().or_else(|| n.as_f64().map(|f| f as i64)),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            s.parse::<i64>().ok().or_else(|| parse_iso8601(s))
+        }
+        _ => None,
+    };
+    secs.and_then(|s| u32::try_from(s).ok())
+        .filter(|&s| s != Edge::NO_TIMESTAMP)
+        .unwrap_or(Edge::NO_TIMESTAMP)
+}
+
+/// Parse `YYYY-MM-DD[(T| )hh:mm[:ss[.fff]][Z|±hh[:mm]]]` to epoch seconds.
+fn parse_iso8601(s: &str) -> Option<i64> {
+    let (date, time) = match s.split_once(['T', 't', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+    let mut ymd = date.splitn(3, '-');
+    let year: i64 = ymd.next()?.parse().ok()?;
+    let month: i64 = ymd.next()?.parse().ok()?;
+    let day: i64 = ymd.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut secs = days_from_civil(year, month, day) * 86_400;
+
+    if let Some(time) = time {
+        let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+            Some(i) => (&time[..i], zone_offset(&time[i..])?),
+            None => (time, 0),
+        };
+        let mut hms = clock.split(':');
+        let hours: i64 = hms.next()?.parse().ok()?;
+        let minutes: i64 = hms.next()?.parse().ok()?;
+        let seconds: f64 = hms.next().map_or(Ok(0.0), str::parse).ok()?;
+        secs += hours * 3600 + minutes * 60 + seconds as i64 - offset;
+    }
+    Some(secs)
+}
+
+/// UTC offset in seconds of a `Z`, `±hh`, `±hhmm` or `±hh:mm` suffix.
+fn zone_offset(zone: &str) -> Option<i64> {
+    if zone.eq_ignore_ascii_case("z") {
+        return Some(0);
+    }
+    let sign = if zone.starts_with('-') { -1 } else { 1 };
+    let digits = zone[1..].replace(':', "");
+    let hours: i64 = digits.get(..2)?.parse().ok()?;
+    let minutes: i64 = match digits.get(2..) {
+        Some(m) if !m.is_empty() => m.parse().ok()?,
+        _ => 0,
+    };
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Hinnant's algorithm).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Labels held in property `key`: an array of strings or a single string.
+fn json_labels(value: &serde_json::Value, key: &str) -> Vec<String> {
+    match value.get(key) {
+        Some(v) => match v.as_array() {
+            Some(items) => items
+                .iter()
+                .filter_map(|l| l.as_str().map(str::to_string))
+                .collect(),
+            None => v.as_str().map(|l| vec![l.to_string()]).unwrap_or_default(),
+        },
+        None => Vec::new(),
+    }
+}
+
+/// NAMEDATALEN - 1: longer identifiers are truncated by Postgres.
+const MAX_NAME_BYTES: usize = 63;
+
+/// Validate that a name can be a Postgres identifier: non-empty, at most
+/// 63 bytes and without NUL. Names reach SQL only as bind parameters or
+/// through `quote_identifier`/`quote_literal`, so mixed case, hyphens and
+/// other characters AGE accepts are fine.
 /// Uses pgrx error!() instead of assert!() for proper Postgres ERROR handling.
 pub(crate) fn validate_name(name: &str) {
-    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+    if name.is_empty() || name.len() > MAX_NAME_BYTES || name.contains('\0') {
         error!("graph_accel: invalid name: '{}'", name);
     }
 }
 
-enum Filter {
+pub(crate) enum Filter {
     All,
     Set(Vec<String>),
 }
 
-fn parse_filter(spec: &str) -> Filter {
+pub(crate) fn parse_filter(spec: &str) -> Filter {
     if spec.trim() == "*" {
         Filter::All
     } else {
@@ -302,7 +1026,7 @@ fn parse_filter(spec: &str) -> Filter {
     }
 }
 
-fn matches_filter(name: &str, filter: &Filter) -> bool {
+pub(crate) fn matches_filter(name: &str, filter: &Filter) -> bool {
     match filter {
         Filter::All => true,
         Filter::Set(names) => names.iter().any(|n| n == name),
@@ -1,25 +1,342 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use graph_accel_core::{FrozenGraph, NeighborResult, NeighborhoodStream, NodeId};
+use pgrx::datum::TimestampWithTimeZone;
 use pgrx::prelude::*;
 
 use crate::state;
+use crate::stats;
 use crate::util::direction_str;
 
 #[pg_extern]
+#[allow(clippy::too_many_arguments)]
 fn graph_accel_neighborhood(
     start_id: String,
     max_depth: default!(i32, 3),
     direction_filter: default!(String, "'both'"),
     min_confidence: default!(Option<f64>, "NULL"),
+    exclude_nodes: default!(Option<Vec<String>>, "NULL"),
+    exclude_edges: default!(Option<Vec<String>>, "NULL"),
+    max_nodes: default!(i32, 0),
+    node_filter: default!(Option<JsonB>, "NULL"),
+    timeout_ms: default!(Option<i32>, "NULL"),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    node_labels: default!(Option<Vec<String>>, "NULL"),
+    result_labels: default!(Option<Vec<String>>, "NULL"),
+    as_of: default!(Option<TimestampWithTimeZone>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(distance, i32),
+        name!(path_types, Vec<String>),
+        name!(path_directions, Vec<String>),
+        name!(truncated, bool),
+    ),
+> {
+    let call = stats::call("graph_accel_neighborhood");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+    let budget = crate::util::row_limit(max_nodes, "max_nodes");
+    let filter = crate::util::parse_node_filter(node_filter);
+    let deadline = crate::util::deadline(timeout_ms);
+    let as_of = crate::util::as_of_seconds(as_of);
+
+    let stream = state::with_graph_named(graph_name.as_deref(), |gs| {
+        let internal_id = state::resolve_node(&gs.graph, &start_id);
+        let exclusions =
+            crate::util::build_exclusions(&gs.graph, exclude_nodes, exclude_edges, rel_types);
+        let labels = crate::util::parse_labels(&gs.graph, node_labels, "node_labels");
+        let results = crate::util::parse_labels(&gs.graph, result_labels, "result_labels");
+
+        // The stream searches as it is read, under the deadline set here
+        let graph = gs.graph.clone();
+        let (stream, _) = graph_accel_core::with_deadline(deadline, || {
+            graph_accel_core::bfs_neighborhood_stream(
+                gs.graph.clone(),
+                internal_id,
+                depth,
+                direction,
+                min_confidence.map(|v| v as f32),
+                budget,
+                &exclusions,
+                move |id| {
+                    crate::util::node_in_labels(&graph, id, labels.as_ref())
+                        && crate::util::node_matches(&graph, id, &filter)
+                },
+            )
+        });
+        let stream = match as_of {
+            Some(as_of) => stream.as_of(as_of),
+            None => stream,
+        };
+        // Result labels only pick rows: the traversal went through every label
+        match results {
+            Some(keep) => {
+                let graph = gs.graph.clone();
+                stream.retain_nodes(move |id| graph.node_in_label_set(id, &keep))
+            }
+            None => stream,
+        }
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    // Rows are found and built as Postgres asks for them, so a LIMIT stops
+    // the traversal too
+    TableIterator::new(call.rows(with_truncated(stream).map(|(nr, truncated)| {
+        let dirs = nr.path_directions.into_iter().map(direction_str).collect();
+        (
+            nr.node_id as i64,
+            nr.label,
+            nr.app_id,
+            nr.distance as i32,
+            nr.path_types,
+            dirs,
+            truncated,
+        )
+    })))
+}
+
+/// Pair each streamed row with the stream's `truncated` flag as of that
+/// row. Expansion happens while reading, so the flag can turn on partway
+/// through, but the last row of a truncated result always carries it.
+fn with_truncated<F>(
+    mut stream: NeighborhoodStream<Arc<FrozenGraph>, F>,
+) -> impl Iterator<Item = (NeighborResult, bool)>
+where
+    F: Fn(NodeId) -> bool,
+{
+    std::iter::from_fn(move || {
+        let nr = stream.next()?;
+        Some((nr, stream.truncated()))
+    })
+}
+
+/// Stop `rows` after `ceiling` rows (0 = no ceiling), calling `flag` on
+/// the last row kept if any were left over.
+fn cap_rows<T>(
+    rows: impl Iterator<Item = T>,
+    ceiling: usize,
+    flag: fn(&mut T),
+) -> impl Iterator<Item = T> {
+    let mut rows = rows.peekable();
+    let mut left = if ceiling == 0 { usize::MAX } else { ceiling };
+    std::iter::from_fn(move || {
+        if left == 0 {
+            return None;
+        }
+        let mut row = rows.next()?;
+        left -= 1;
+        if left == 0 && rows.peek().is_some() {
+            flag(&mut row);
+        }
+        Some(row)
+    })
+}
+
+/// BFS neighborhoods of several seeds in one call, each traversed on its
+/// own as if by `graph_accel_neighborhood`.
+///
+/// Rows carry the `seed_id` they were reached from, in seed order; a node
+/// near several seeds appears once per seed. The graph is checked for
+/// freshness once and the filters are parsed once for the whole batch.
+/// `max_nodes` applies to each seed and `timeout_ms` to the whole batch:
+/// once it expires, the current seed and every later one report
+/// `truncated`. `graph_accel.max_result_rows` caps the batch's total row
+/// count, so many seeds cannot add up past it; the last row returned is
+/// flagged `truncated` when rows were dropped.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_neighborhood_batch(ARRAY['concept_a', 'concept_b'], 2);
+#[pg_extern]
+#[allow(clippy::too_many_arguments)]
+fn graph_accel_neighborhood_batch(
+    start_ids: Vec<String>,
+    max_depth: default!(i32, 3),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+    exclude_nodes: default!(Option<Vec<String>>, "NULL"),
+    exclude_edges: default!(Option<Vec<String>>, "NULL"),
+    max_nodes: default!(i32, 0),
+    node_filter: default!(Option<JsonB>, "NULL"),
+    timeout_ms: default!(Option<i32>, "NULL"),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    node_labels: default!(Option<Vec<String>>, "NULL"),
+    result_labels: default!(Option<Vec<String>>, "NULL"),
+    as_of: default!(Option<TimestampWithTimeZone>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
 ) -> TableIterator<
     'static,
     (
+        name!(seed_id, String),
         name!(node_id, i64),
         name!(label, String),
         name!(app_id, Option<String>),
         name!(distance, i32),
         name!(path_types, Vec<String>),
         name!(path_directions, Vec<String>),
+        name!(truncated, bool),
     ),
 > {
+    let call = stats::call("graph_accel_neighborhood_batch");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+    let budget = crate::util::row_limit(max_nodes, "max_nodes");
+    let filter = crate::util::parse_node_filter(node_filter);
+    let deadline = crate::util::deadline(timeout_ms);
+    let as_of = crate::util::as_of_seconds(as_of);
+
+    let streams = state::with_graph_named(graph_name.as_deref(), |gs| {
+        let seeds: Vec<_> = start_ids
+            .into_iter()
+            .map(|id| (state::resolve_node(&gs.graph, &id), id))
+            .collect();
+        let exclusions =
+            crate::util::build_exclusions(&gs.graph, exclude_nodes, exclude_edges, rel_types);
+        let labels = crate::util::parse_labels(&gs.graph, node_labels, "node_labels");
+        let results = crate::util::parse_labels(&gs.graph, result_labels, "result_labels");
+
+        // One deadline for the batch: seeds after it expires stop at once
+        let labels = Rc::new(labels);
+        let filter = Rc::new(filter);
+        let results = results.map(Rc::new);
+        let (streams, _) = graph_accel_core::with_deadline(deadline, || {
+            let mut streams = Vec::with_capacity(seeds.len());
+            for (internal_id, seed_id) in seeds {
+                let (graph, labels, filter) = (gs.graph.clone(), labels.clone(), filter.clone());
+                let stream = graph_accel_core::bfs_neighborhood_stream(
+                    gs.graph.clone(),
+                    internal_id,
+                    depth,
+                    direction,
+                    min_confidence.map(|v| v as f32),
+                    budget,
+                    &exclusions,
+                    move |id| {
+                        crate::util::node_in_labels(&graph, id, labels.as_ref().as_ref())
+                            && crate::util::node_matches(&graph, id, &filter)
+                    },
+                );
+                let stream = match as_of {
+                    Some(as_of) => stream.as_of(as_of),
+                    None => stream,
+                };
+                let stream = match &results {
+                    Some(keep) => {
+                        let (graph, keep) = (gs.graph.clone(), keep.clone());
+                        stream.retain_nodes(move |id| graph.node_in_label_set(id, &keep))
+                    }
+                    None => stream,
+                };
+                streams.push((seed_id, stream));
+            }
+            streams
+        });
+        streams
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    let rows = streams.into_iter().flat_map(|(seed_id, stream)| {
+        with_truncated(stream).map(move |(nr, truncated)| {
+            let dirs = nr.path_directions.into_iter().map(direction_str).collect();
+            (
+                seed_id.clone(),
+                nr.node_id as i64,
+                nr.label,
+                nr.app_id,
+                nr.distance as i32,
+                nr.path_types,
+                dirs,
+                truncated,
+            )
+        })
+    });
+    let ceiling = crate::guc::MAX_RESULT_ROWS.get() as usize;
+    TableIterator::new(call.rows(cap_rows(rows, ceiling, |row| row.7 = true)))
+}
+
+/// BFS neighborhood of a set of seeds in a single traversal.
+///
+/// Each reached node is reported once, attributed to its nearest seed,
+/// with the path from that seed. Seeds themselves are not returned.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_neighborhood_multi(ARRAY['concept_a', 'concept_b'], 2);
+#[pg_extern]
+fn graph_accel_neighborhood_multi(
+    seed_ids: Vec<String>,
+    max_depth: default!(i32, 3),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(seed_id, i64),
+        name!(distance, i32),
+        name!(path_types, Vec<String>),
+        name!(path_directions, Vec<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_neighborhood_multi");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+
+    let results = state::with_graph(|gs| {
+        let seeds: Vec<u64> = seed_ids
+            .iter()
+            .map(|s| state::resolve_node(&gs.graph, s))
+            .collect();
+
+        graph_accel_core::bfs_multi_source(&gs.graph, &seeds, depth, direction, min_confidence.map(|v| v as f32))
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results.into_iter().map(|nr| {
+        let dirs = nr.path_directions.into_iter().map(direction_str).collect();
+        (
+            nr.node_id as i64,
+            nr.label,
+            nr.app_id,
+            nr.seed as i64,
+            nr.distance as i32,
+            nr.path_types,
+            dirs,
+        )
+    })))
+}
+
+/// Hop distance from a start node to every node within `max_depth`.
+///
+/// Like `graph_accel_neighborhood` without the path columns, which
+/// dominate its cost. Includes the start node at distance 0; rows are in
+/// BFS order.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_distances('concept_a', 5);
+#[pg_extern]
+fn graph_accel_distances(
+    start_id: String,
+    max_depth: default!(i32, 3),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<'static, (name!(node_id, i64), name!(distance, i32))> {
+    let call = stats::call("graph_accel_distances");
     crate::generation::ensure_fresh();
     let direction = crate::util::parse_direction(&direction_filter);
     let depth = crate::util::check_non_negative(max_depth, "max_depth");
@@ -27,21 +344,60 @@ fn graph_accel_neighborhood(
     let results = state::with_graph(|gs| {
         let internal_id = state::resolve_node(&gs.graph, &start_id);
 
-        let result =
-            graph_accel_core::bfs_neighborhood(&gs.graph, internal_id, depth, direction, min_confidence.map(|v| v as f32));
+        graph_accel_core::bfs_distances(&gs.graph, internal_id, depth, direction, min_confidence.map(|v| v as f32))
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results.into_iter().map(|(id, d)| (id as i64, d as i32))))
+}
+
+/// Compare the k-hop neighborhoods of two seeds.
+///
+/// Returns the union of both neighborhoods, each node tagged `'both'`,
+/// `'a_only'` or `'b_only'` with its distance from each seed (NULL when
+/// out of reach). The seeds themselves are not listed.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_neighborhood_diff('concept_a', 'concept_b', 2);
+#[pg_extern]
+fn graph_accel_neighborhood_diff(
+    a_id: String,
+    b_id: String,
+    max_depth: default!(i32, 2),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(overlap, String),
+        name!(distance_a, Option<i32>),
+        name!(distance_b, Option<i32>),
+    ),
+> {
+    let call = stats::call("graph_accel_neighborhood_diff");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+
+    let results = state::with_graph(|gs| {
+        let a = state::resolve_node(&gs.graph, &a_id);
+        let b = state::resolve_node(&gs.graph, &b_id);
 
-        result
-            .neighbors
+        graph_accel_core::neighborhood_diff(&gs.graph, a, b, depth, direction, min_confidence.map(|v| v as f32))
             .into_iter()
-            .map(|nr| {
-                let dirs = nr.path_directions.into_iter().map(direction_str).collect();
+            .map(|e| {
                 (
-                    nr.node_id as i64,
-                    nr.label,
-                    nr.app_id,
-                    nr.distance as i32,
-                    nr.path_types,
-                    dirs,
+                    e.node_id as i64,
+                    e.label,
+                    e.app_id,
+                    e.overlap.as_str().to_string(),
+                    e.distance_a.map(|d| d as i32),
+                    e.distance_b.map(|d| d as i32),
                 )
             })
             .collect::<Vec<_>>()
@@ -50,5 +406,5 @@ fn graph_accel_neighborhood(
         error!("graph_accel: no graph loaded — call graph_accel_load() first");
     });
 
-    TableIterator::new(results)
+    TableIterator::new(call.rows(results))
 }
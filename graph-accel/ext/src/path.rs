@@ -1,15 +1,26 @@
+use pgrx::datum::TimestampWithTimeZone;
 use pgrx::prelude::*;
 
 use crate::state;
-use crate::util::direction_str;
+use crate::stats;
+use crate::util::{direction_str, CostMode};
 
 #[pg_extern]
+#[allow(clippy::too_many_arguments)]
 fn graph_accel_path(
     from_id: String,
     to_id: String,
     max_hops: default!(i32, 10),
     direction_filter: default!(String, "'both'"),
     min_confidence: default!(Option<f64>, "NULL"),
+    exclude_nodes: default!(Option<Vec<String>>, "NULL"),
+    exclude_edges: default!(Option<Vec<String>>, "NULL"),
+    node_filter: default!(Option<JsonB>, "NULL"),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    node_labels: default!(Option<Vec<String>>, "NULL"),
+    as_of: default!(Option<TimestampWithTimeZone>, "NULL"),
+    time_respecting: default!(bool, false),
+    graph_name: default!(Option<String>, "NULL"),
 ) -> TableIterator<
     'static,
     (
@@ -21,15 +32,245 @@ fn graph_accel_path(
         name!(direction, Option<String>),
     ),
 > {
+    let call = stats::call("graph_accel_path");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+    let filter = crate::util::parse_node_filter(node_filter);
+    let as_of = crate::util::as_of_seconds(as_of);
+
+    let results = state::with_graph_named(graph_name.as_deref(), |gs| {
+        let start = state::resolve_node(&gs.graph, &from_id);
+        let target = state::resolve_node(&gs.graph, &to_id);
+        let exclusions =
+            crate::util::build_exclusions(&gs.graph, exclude_nodes, exclude_edges, rel_types);
+        let labels = crate::util::parse_labels(&gs.graph, node_labels, "node_labels");
+        let view = crate::util::build_view(
+            &gs.graph,
+            min_confidence,
+            &exclusions,
+            labels,
+            filter,
+            as_of,
+        );
+
+        let path = if time_respecting {
+            view.time_respecting_path(start, target, hops, direction)
+        } else {
+            view.shortest_path(start, target, hops, direction)
+        };
+        match path {
+            Some(path) => path
+                .into_iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    let dir = s.direction.map(direction_str);
+                    (i as i32, s.node_id as i64, s.label, s.app_id, s.rel_type, dir)
+                })
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        }
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Hop count of a shortest path from `from_id` to `to_id`, or NULL if none
+/// is within `max_hops`.
+///
+/// Runs the same BFS as `graph_accel_path` but stops at `to_id` without
+/// building the path, for scalar expressions that only need the number.
+///
+/// Usage:
+///   SELECT graph_accel_distance('concept_a', 'concept_b');
+///   SELECT id FROM concepts WHERE graph_accel_distance('concept_a', id, 3) <= 2;
+#[pg_extern]
+fn graph_accel_distance(
+    from_id: String,
+    to_id: String,
+    max_hops: default!(i32, 10),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> Option<i32> {
+    let _call = stats::call("graph_accel_distance");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+
+    state::with_graph_named(graph_name.as_deref(), |gs| {
+        let start = state::resolve_node(&gs.graph, &from_id);
+        let target = state::resolve_node(&gs.graph, &to_id);
+        graph_accel_core::hop_distance(
+            &gs.graph,
+            start,
+            target,
+            hops,
+            direction,
+            min_confidence.map(|v| v as f32),
+        )
+        .map(|d| d as i32)
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    })
+}
+
+/// Hop distance between every ordered pair of `ids` within `max_hops`.
+///
+/// One multi-source BFS covers up to 64 of the nodes at a time, instead of
+/// a `graph_accel_path` call per pair. Pairs with no path within
+/// `max_hops` are omitted; with the default `'both'` direction every pair
+/// appears in both orders.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_pairwise_distances(ARRAY['a', 'b', 'c'], 4);
+#[pg_extern]
+fn graph_accel_pairwise_distances(
+    ids: Vec<String>,
+    max_hops: default!(i32, 10),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(from_id, i64),
+        name!(from_app_id, Option<String>),
+        name!(to_id, i64),
+        name!(to_app_id, Option<String>),
+        name!(distance, i32),
+    ),
+> {
+    let call = stats::call("graph_accel_pairwise_distances");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+
+    let results = state::with_graph_named(graph_name.as_deref(), |gs| {
+        let nodes: Vec<_> = ids
+            .iter()
+            .map(|id| state::resolve_node(&gs.graph, id))
+            .collect();
+        let app_id = |id| gs.graph.node(id).and_then(|n| n.app_id.clone());
+        graph_accel_core::pairwise_distances(
+            &gs.graph,
+            &nodes,
+            hops,
+            direction,
+            min_confidence.map(|v| v as f32),
+        )
+        .into_iter()
+        .map(|(from, to, d)| (from as i64, app_id(from), to as i64, app_id(to), d as i32))
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Shortest path from a node to the nearest of a set of targets.
+///
+/// Runs one BFS that stops at the first target reached; the chosen target
+/// is the last step. Empty result if no target is within `max_hops`.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_path_to_any('concept_a', ARRAY['anchor_1', 'anchor_2']);
+#[pg_extern]
+fn graph_accel_path_to_any(
+    from_id: String,
+    target_ids: Vec<String>,
+    max_hops: default!(i32, 10),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(step, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(rel_type, Option<String>),
+        name!(direction, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_path_to_any");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+
+    let results = state::with_graph(|gs| {
+        let start = state::resolve_node(&gs.graph, &from_id);
+        let targets: Vec<u64> = target_ids
+            .iter()
+            .map(|t| state::resolve_node(&gs.graph, t))
+            .collect();
+
+        match graph_accel_core::shortest_path_to_any(&gs.graph, start, &targets, hops, direction, min_confidence.map(|v| v as f32)) {
+            Some(path) => path
+                .into_iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    let dir = s.direction.map(direction_str);
+                    (i as i32, s.node_id as i64, s.label, s.app_id, s.rel_type, dir)
+                })
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        }
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Shortest path whose relationship types match a pattern.
+///
+/// `pattern` is a sequence of rel types, `.` (any type) or `(A|B)` groups,
+/// each optionally followed by `?`, `*` or `+`. Empty result if no
+/// conforming path exists within `max_hops`.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_pattern_path('a', 'b', 'IMPLIES -> IMPLIES -> SUPPORTS');
+///   SELECT * FROM graph_accel_pattern_path('a', 'b', 'IMPLIES+ SUPPORTS', 6, 'outgoing');
+#[pg_extern]
+fn graph_accel_pattern_path(
+    from_id: String,
+    to_id: String,
+    pattern: String,
+    max_hops: default!(i32, 10),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(step, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(rel_type, Option<String>),
+        name!(direction, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_pattern_path");
     crate::generation::ensure_fresh();
     let direction = crate::util::parse_direction(&direction_filter);
     let hops = crate::util::check_non_negative(max_hops, "max_hops");
+    let rel_pattern = graph_accel_core::RelPattern::parse(&pattern).unwrap_or_else(|e| {
+        error!("graph_accel: invalid pattern '{}': {}", pattern, e);
+    });
 
     let results = state::with_graph(|gs| {
         let start = state::resolve_node(&gs.graph, &from_id);
         let target = state::resolve_node(&gs.graph, &to_id);
 
-        match graph_accel_core::shortest_path(&gs.graph, start, target, hops, direction, min_confidence.map(|v| v as f32)) {
+        match graph_accel_core::pattern_path(&gs.graph, start, target, &rel_pattern, hops, direction, min_confidence.map(|v| v as f32)) {
             Some(path) => path
                 .into_iter()
                 .enumerate()
@@ -45,18 +286,29 @@ fn graph_accel_path(
         error!("graph_accel: no graph loaded — call graph_accel_load() first");
     });
 
-    TableIterator::new(results)
+    TableIterator::new(call.rows(results))
 }
 
 /// Find up to `max_paths` shortest paths between two nodes (Yen's algorithm).
 ///
 /// Each row includes a `path_index` column (0-based) identifying which path
 /// the step belongs to, and a `step` column for ordering within that path.
+/// `cost_mode` ranks alternatives by hop count (`'hops'`), by cumulative
+/// `-ln(confidence)` (`'confidence'`) or by summed edge weight (`'weight'`);
+/// `total_cost` is the path's cost. With `as_of`, edges created after
+/// that time are ignored.
 ///
 /// Usage:
 ///   SELECT * FROM graph_accel_paths('concept_a', 'concept_b', 6, 5);
 ///   SELECT * FROM graph_accel_paths('src', 'dst', 4, 3, 'outgoing', 0.5);
+///   SELECT * FROM graph_accel_paths('src', 'dst', cost_mode => 'confidence');
+///   SELECT * FROM graph_accel_paths('src', 'dst', 8, 50, max_results => 200);
+///   SELECT * FROM graph_accel_paths('src', 'dst', 8, 50, timeout_ms => 500);
+///   SELECT * FROM graph_accel_paths('src', 'dst', rel_types => ARRAY['IMPLIES', 'SUPPORTS']);
+///   SELECT * FROM graph_accel_paths('src', 'dst', node_labels => ARRAY['Concept']);
+///   SELECT * FROM graph_accel_paths('src', 'dst', as_of => '2024-01-01');
 #[pg_extern]
+#[allow(clippy::too_many_arguments)]
 fn graph_accel_paths(
     from_id: String,
     to_id: String,
@@ -64,6 +316,12 @@ fn graph_accel_paths(
     max_paths: default!(i32, 5),
     direction_filter: default!(String, "'both'"),
     min_confidence: default!(Option<f64>, "NULL"),
+    cost_mode: default!(String, "'hops'"),
+    max_results: default!(i32, 0),
+    timeout_ms: default!(Option<i32>, "NULL"),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    node_labels: default!(Option<Vec<String>>, "NULL"),
+    as_of: default!(Option<TimestampWithTimeZone>, "NULL"),
 ) -> TableIterator<
     'static,
     (
@@ -74,23 +332,234 @@ fn graph_accel_paths(
         name!(app_id, Option<String>),
         name!(rel_type, Option<String>),
         name!(direction, Option<String>),
+        name!(total_cost, f64),
+        name!(truncated, bool),
     ),
 > {
+    let call = stats::call("graph_accel_paths");
     crate::generation::ensure_fresh();
     let direction = crate::util::parse_direction(&direction_filter);
     let hops = crate::util::check_non_negative(max_hops, "max_hops");
     let k = crate::util::check_non_negative(max_paths, "max_paths") as usize;
+    let mode = crate::util::parse_cost_mode(&cost_mode);
+    let limit = crate::util::row_limit(max_results, "max_results");
+    let deadline = crate::util::deadline(timeout_ms);
+    let as_of = crate::util::as_of_seconds(as_of);
+
+    let (mut paths, timed_out) = state::with_graph(|gs| {
+        let start = state::resolve_node(&gs.graph, &from_id);
+        let target = state::resolve_node(&gs.graph, &to_id);
+        let exclusions = crate::util::build_exclusions(&gs.graph, None, None, rel_types);
+        let labels = crate::util::parse_labels(&gs.graph, node_labels, "node_labels");
+        let view = crate::util::build_view(
+            &gs.graph,
+            min_confidence,
+            &exclusions,
+            labels,
+            Vec::new(),
+            as_of,
+        );
+
+        // Out of time, Yen's algorithm returns the paths found so far
+        graph_accel_core::with_deadline(deadline, || match mode {
+            CostMode::Hops => view
+                .k_shortest_paths(start, target, hops, k, direction)
+                .into_iter()
+                .map(|p| {
+                    let cost = (p.len() - 1) as f64;
+                    (p, cost)
+                })
+                .collect(),
+            CostMode::Confidence | CostMode::Weight => {
+                let cost = match mode {
+                    CostMode::Weight => graph_accel_core::Edge::weight_cost,
+                    _ => graph_accel_core::Edge::confidence_cost,
+                };
+                view.k_shortest_paths_weighted(start, target, hops, k, direction, cost)
+                    .into_iter()
+                    .map(|p| (p.steps, p.total_cost))
+                    .collect::<Vec<(Vec<graph_accel_core::PathStep>, f64)>>()
+            }
+        })
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    // Whole paths only: keep those that fit within the row limit
+    let mut rows = 0;
+    let fit = paths
+        .iter()
+        .take_while(|(path, _)| {
+            rows += path.len();
+            limit == 0 || rows <= limit
+        })
+        .count();
+    // The best path always comes back, so a cut never reads as "no path"
+    let fit = fit.max(paths.len().min(1));
+    let truncated = timed_out || fit < paths.len();
+    paths.truncate(fit);
+
+    let rows = paths
+        .into_iter()
+        .enumerate()
+        .flat_map(move |(pi, (path, total_cost))| {
+            path.into_iter().enumerate().map(move |(si, s)| {
+                let dir = s.direction.map(direction_str);
+                (
+                    pi as i32,
+                    si as i32,
+                    s.node_id as i64,
+                    s.label,
+                    s.app_id,
+                    s.rel_type,
+                    dir,
+                    total_cost,
+                    truncated,
+                )
+            })
+        });
+    TableIterator::new(call.rows(rows))
+}
+
+/// Find up to `max_paths` shortest paths linking any node of `set_a` to any
+/// node of `set_b`, shortest first.
+///
+/// No path passes through a third member of either set, so each one is a
+/// direct link between the sets; a node in both sets is a path of one
+/// step. Same row shape as `graph_accel_paths`, with `total_cost` the hop
+/// count.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_paths_between_sets(ARRAY['a1', 'a2'], ARRAY['b1', 'b2'], 4, 10);
+#[pg_extern]
+#[allow(clippy::too_many_arguments)]
+fn graph_accel_paths_between_sets(
+    set_a: Vec<String>,
+    set_b: Vec<String>,
+    max_hops: default!(i32, 10),
+    max_paths: default!(i32, 5),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+    timeout_ms: default!(Option<i32>, "NULL"),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    node_labels: default!(Option<Vec<String>>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(path_index, i32),
+        name!(step, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(rel_type, Option<String>),
+        name!(direction, Option<String>),
+        name!(total_cost, f64),
+        name!(truncated, bool),
+    ),
+> {
+    let call = stats::call("graph_accel_paths_between_sets");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+    let k = crate::util::check_non_negative(max_paths, "max_paths") as usize;
+    let deadline = crate::util::deadline(timeout_ms);
+
+    let (paths, truncated) = state::with_graph_named(graph_name.as_deref(), |gs| {
+        let resolve = |ids: &[String]| -> Vec<_> {
+            ids.iter()
+                .map(|id| state::resolve_node(&gs.graph, id))
+                .collect()
+        };
+        let (sources, targets) = (resolve(&set_a), resolve(&set_b));
+        let exclusions = crate::util::build_exclusions(&gs.graph, None, None, rel_types);
+        let labels = crate::util::parse_labels(&gs.graph, node_labels, "node_labels");
+
+        // Out of time, the paths found so far
+        graph_accel_core::with_deadline(deadline, || {
+            graph_accel_core::k_shortest_paths_between(
+                &gs.graph,
+                &sources,
+                &targets,
+                hops,
+                k,
+                direction,
+                min_confidence.map(|v| v as f32),
+                &exclusions,
+                |id| crate::util::node_in_labels(&gs.graph, id, labels.as_ref()),
+            )
+        })
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    let rows = paths.into_iter().enumerate().flat_map(move |(pi, path)| {
+        let total_cost = (path.len() - 1) as f64;
+        path.into_iter().enumerate().map(move |(si, s)| {
+            let dir = s.direction.map(direction_str);
+            (
+                pi as i32,
+                si as i32,
+                s.node_id as i64,
+                s.label,
+                s.app_id,
+                s.rel_type,
+                dir,
+                total_cost,
+                truncated,
+            )
+        })
+    });
+    TableIterator::new(call.rows(rows))
+}
+
+/// Find every shortest path between two nodes (all paths of minimal length).
+///
+/// Same row shape as `graph_accel_paths`. Unlike `graph_accel_paths`, longer
+/// alternatives are never returned — only ties for shortest. `max_paths`
+/// caps the enumeration, since dense graphs can have very many ties.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_all_shortest_paths('concept_a', 'concept_b');
+///   SELECT * FROM graph_accel_all_shortest_paths('src', 'dst', 6, 50, 'outgoing');
+#[pg_extern]
+fn graph_accel_all_shortest_paths(
+    from_id: String,
+    to_id: String,
+    max_hops: default!(i32, 10),
+    max_paths: default!(i32, 100),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(path_index, i32),
+        name!(step, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(rel_type, Option<String>),
+        name!(direction, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_all_shortest_paths");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+    let limit = crate::util::check_non_negative(max_paths, "max_paths") as usize;
 
     let results = state::with_graph(|gs| {
         let start = state::resolve_node(&gs.graph, &from_id);
         let target = state::resolve_node(&gs.graph, &to_id);
 
-        let paths = graph_accel_core::k_shortest_paths(
+        let paths = graph_accel_core::all_shortest_paths(
             &gs.graph,
             start,
             target,
             hops,
-            k,
+            limit,
             direction,
             min_confidence.map(|v| v as f32),
         );
@@ -118,5 +587,76 @@ fn graph_accel_paths(
         error!("graph_accel: no graph loaded — call graph_accel_load() first");
     });
 
-    TableIterator::new(results)
+    TableIterator::new(call.rows(results))
+}
+
+/// Shortest paths from one node to a set of targets in a single BFS.
+///
+/// Returns the shortest-path tree pruned to the branches that reach a
+/// target: one row per node with its distance and parent pointer. Follow
+/// `parent_id` from any `is_target` row back to the start to recover that
+/// path. Unreachable targets are absent.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_sssp('concept_a', ARRAY['b', 'c', 'd']);
+#[pg_extern]
+fn graph_accel_sssp(
+    start_id: String,
+    targets: Vec<String>,
+    max_hops: default!(i32, 10),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(distance, i32),
+        name!(parent_id, Option<i64>),
+        name!(rel_type, Option<String>),
+        name!(direction, Option<String>),
+        name!(is_target, bool),
+    ),
+> {
+    let call = stats::call("graph_accel_sssp");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
+
+    let results = state::with_graph(|gs| {
+        let start = state::resolve_node(&gs.graph, &start_id);
+        let target_ids: Vec<u64> = targets
+            .iter()
+            .map(|t| state::resolve_node(&gs.graph, t))
+            .collect();
+
+        graph_accel_core::shortest_path_tree(
+            &gs.graph,
+            start,
+            &target_ids,
+            hops,
+            direction,
+            min_confidence.map(|v| v as f32),
+        )
+        .into_iter()
+        .map(|n| {
+            (
+                n.node_id as i64,
+                n.label,
+                n.app_id,
+                n.distance as i32,
+                n.parent.map(|p| p as i64),
+                n.rel_type,
+                n.direction.map(direction_str),
+                n.is_target,
+            )
+        })
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
 }
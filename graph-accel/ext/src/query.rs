@@ -0,0 +1,284 @@
+//! Load a graph from caller-provided SQL, or from plain relational tables
+//! named by GUCs, instead of an AGE graph.
+
+use std::time::Instant;
+
+use graph_accel_core::{Edge, Graph};
+use pgrx::prelude::*;
+use pgrx::spi::{quote_identifier, quote_literal, SpiClient, SpiError};
+
+use crate::generation;
+use crate::guc;
+use crate::load;
+use crate::state::{self, GraphSource};
+use crate::stats;
+
+/// Columns a node query must return, then the ones it may.
+const NODE_COLUMNS: (&[&str], &[&str]) = (&["id", "label"], &["app_id"]);
+
+/// Columns an edge query must return, then the ones it may.
+const EDGE_COLUMNS: (&[&str], &[&str]) = (&["from_id", "to_id", "rel_type"], &["confidence"]);
+
+/// Build a graph from two queries, for edge data that does not live in AGE.
+///
+/// `node_sql` returns one row per node with columns `id` (a non-negative
+/// integer), `label` and optionally `app_id`; `edge_sql` returns one row
+/// per edge with `from_id`, `to_id`, `rel_type` and optionally
+/// `confidence`. Other columns are ignored and column order does not
+/// matter; a missing column is an error. Edges may reference IDs the node
+/// query does not return, as with filtered AGE loads.
+///
+/// The graph is loaded as `graph_name` and becomes the default graph.
+/// `graph_accel.max_memory_mb`, `dedupe_edges` and `group_edges_by_type`
+/// apply; the AGE-specific load GUCs do not. `graph_accel_invalidate(
+/// graph_name)` marks it stale, and reloads run the same queries again.
+/// Query graphs are never shared across backends.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_load_query(
+///       'SELECT id, kind AS label, slug AS app_id FROM docs',
+///       'SELECT src AS from_id, dst AS to_id, ''CITES'' AS rel_type FROM citations',
+///       graph_name => 'citations');
+#[pg_extern]
+fn graph_accel_load_query(
+    node_sql: String,
+    edge_sql: String,
+    graph_name: default!(String, "'query'"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_count, i64),
+        name!(edge_count, i64),
+        name!(load_time_ms, f64),
+    ),
+> {
+    let _call = stats::call("graph_accel_load_query");
+    load::validate_name(&graph_name);
+    let result = do_load_query(&graph_name, &node_sql, &edge_sql);
+    state::set_default_graph(&graph_name);
+    TableIterator::once(result)
+}
+
+/// Run the node and edge queries and install the graph as `graph_name`.
+/// Returns (node_count, edge_count, load_time_ms).
+pub(crate) fn do_load_query(graph_name: &str, node_sql: &str, edge_sql: &str) -> (i64, i64, f64) {
+    let start = Instant::now();
+    let max_mb = guc::MAX_MEMORY_MB.get() as usize;
+
+    let (graph, gen) = Spi::connect(|client| {
+        let gen = generation::fetch_generation_spi(&client, graph_name).unwrap_or(0);
+        let mut graph = Graph::new();
+        let mut budget = QueryBudget::new(max_mb);
+
+        let nodes = checked_query(&client, node_sql, "node", NODE_COLUMNS)?;
+        load::for_each_batch(&client, &nodes, |batch| {
+            for row in batch {
+                check_for_interrupts!();
+                let id = parse_id(row.get_by_name("id")?, "node", "id");
+                let label: Option<String> = row.get_by_name("label")?;
+                let label = label
+                    .unwrap_or_else(|| error!("graph_accel: node query returned a NULL label"));
+                let app_id: Option<String> = row.get_by_name("app_id")?;
+                graph.add_node(id, label, app_id);
+            }
+            budget.check(&graph);
+            Ok(())
+        })?;
+        load::check_memory(graph.memory_usage() / (1024 * 1024), max_mb);
+
+        let edges = checked_query(&client, edge_sql, "edge", EDGE_COLUMNS)?;
+        load::for_each_batch(&client, &edges, |batch| {
+            for row in batch {
+                check_for_interrupts!();
+                let from_id = parse_id(row.get_by_name("from_id")?, "edge", "from_id");
+                let to_id = parse_id(row.get_by_name("to_id")?, "edge", "to_id");
+                let rel_type: Option<String> = row.get_by_name("rel_type")?;
+                let rel_type = rel_type
+                    .unwrap_or_else(|| error!("graph_accel: edge query returned a NULL rel_type"));
+                let confidence: Option<f64> = row.get_by_name("confidence")?;
+                let rel_type_id = graph.intern_rel_type(&rel_type);
+                graph.add_edge(
+                    from_id,
+                    to_id,
+                    rel_type_id,
+                    confidence.map_or(Edge::NO_CONFIDENCE, |c| c as f32),
+                );
+            }
+            budget.check(&graph);
+            Ok(())
+        })?;
+        load::check_freeze(&graph, max_mb);
+
+        Ok::<_, SpiError>((graph, gen))
+    })
+    .unwrap_or_else(|e| error!("graph_accel_load_query: SPI error: {}", e));
+
+    let graph = load::seal(graph);
+    load::check_memory(graph.memory_usage() / (1024 * 1024), max_mb);
+    let (nc, ec) = (graph.node_count() as i64, graph.edge_count() as i64);
+    let source = GraphSource::Query {
+        node_sql: node_sql.to_string(),
+        edge_sql: edge_sql.to_string(),
+    };
+    load::install_from(graph_name, source, graph, gen, start);
+    (nc, ec, start.elapsed().as_secs_f64() * 1000.0)
+}
+
+/// Memory check run after every fetched batch of a query load.
+///
+/// `memory_usage()` walks the whole graph, too slow for every batch, so it
+/// is measured only once the graph has doubled in nodes plus edges since
+/// the last measurement. In between, the size is projected from the bytes
+/// per node or edge measured then.
+struct QueryBudget {
+    max_mb: usize,
+    measured_bytes: usize,
+    measured_items: usize,
+}
+
+impl QueryBudget {
+    fn new(max_mb: usize) -> Self {
+        Self {
+            max_mb,
+            measured_bytes: 0,
+            measured_items: 0,
+        }
+    }
+
+    /// Error out if `graph` is, or is projected to be, over the cap.
+    fn check(&mut self, graph: &Graph) {
+        let items = graph.node_count() + graph.edge_count();
+        if items >= 2 * self.measured_items {
+            self.measured_bytes = graph.memory_usage();
+            self.measured_items = items;
+        }
+        let per_item = self.measured_bytes / self.measured_items.max(1);
+        let projected = self.measured_bytes + (items - self.measured_items) * per_item;
+        load::check_memory(projected / (1024 * 1024), self.max_mb);
+    }
+}
+
+/// Node and edge queries over `graph_accel.node_table` and
+/// `graph_accel.edge_table`, or None if neither is set. A table left unset
+/// contributes no rows; unset label and type columns fall back to the
+/// table name.
+pub(crate) fn table_queries() -> Option<(String, String)> {
+    let node_table = guc::get_string(&guc::NODE_TABLE);
+    let edge_table = guc::get_string(&guc::EDGE_TABLE);
+    if node_table.is_none() && edge_table.is_none() {
+        return None;
+    }
+    let column = |setting, default: &str| match guc::get_string(setting) {
+        Some(name) => quote_identifier(name),
+        None => default.to_string(),
+    };
+
+    let node_sql = match node_table {
+        Some(table) => {
+            let app_id = guc::get_string(&guc::NODE_APP_ID_COLUMN)
+                .map(|c| format!(", {} AS app_id", quote_identifier(c)))
+                .unwrap_or_default();
+            format!(
+                "SELECT {} AS id, {} AS label{} FROM {}",
+                column(&guc::NODE_ID_COLUMN, "id"),
+                column(&guc::NODE_LABEL_COLUMN, &quote_literal(unqualified(&table))),
+                app_id,
+                qualified(&table)
+            )
+        }
+        None => "SELECT NULL::bigint AS id, NULL::text AS label WHERE false".to_string(),
+    };
+    let edge_sql = match edge_table {
+        Some(table) => {
+            let confidence = guc::get_string(&guc::EDGE_CONFIDENCE_COLUMN)
+                .map(|c| format!(", {} AS confidence", quote_identifier(c)))
+                .unwrap_or_default();
+            format!(
+                "SELECT {} AS from_id, {} AS to_id, {} AS rel_type{} FROM {}",
+                column(&guc::EDGE_FROM_COLUMN, "from_id"),
+                column(&guc::EDGE_TO_COLUMN, "to_id"),
+                column(&guc::EDGE_TYPE_COLUMN, &quote_literal(unqualified(&table))),
+                confidence,
+                qualified(&table)
+            )
+        }
+        None => "SELECT NULL::bigint AS from_id, NULL::bigint AS to_id, NULL::text AS rel_type \
+             WHERE false"
+            .to_string(),
+    };
+    Some((node_sql, edge_sql))
+}
+
+/// `schema.table` or `table`, each part quoted.
+fn qualified(table: &str) -> String {
+    table
+        .split('.')
+        .map(|part| quote_identifier(part.trim()))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The table name without its schema.
+fn unqualified(table: &str) -> &str {
+    table.rsplit('.').next().unwrap_or(table).trim()
+}
+
+/// Check that `sql` returns the `required` columns, and wrap it to return
+/// exactly the required and optional ones, as text (`confidence` as
+/// float8). Optional columns it lacks come back NULL.
+fn checked_query(
+    client: &SpiClient<'_>,
+    sql: &str,
+    what: &str,
+    (required, optional): (&[&str], &[&str]),
+) -> Result<String, SpiError> {
+    let sql = sql.trim().trim_end_matches(';');
+    let probe = client.select(&format!("SELECT * FROM ({}) AS q LIMIT 0", sql), None, &[])?;
+    let mut columns = Vec::new();
+    for ordinal in 1..=probe.columns()? {
+        columns.push(probe.column_name(ordinal)?);
+    }
+
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|name| !columns.iter().any(|c| c == name))
+        .collect();
+    if !missing.is_empty() {
+        error!(
+            "graph_accel: {} query must return columns {}; missing {}",
+            what,
+            required.join(", "),
+            missing.join(", ")
+        );
+    }
+
+    let select: Vec<String> = required
+        .iter()
+        .chain(optional)
+        .map(|&name| {
+            let cast = if name == "confidence" {
+                "float8"
+            } else {
+                "text"
+            };
+            if columns.iter().any(|c| c == name) {
+                format!("q.{name}::{cast} AS {name}")
+            } else {
+                format!("NULL::{cast} AS {name}")
+            }
+        })
+        .collect();
+    Ok(format!("SELECT {} FROM ({}) AS q", select.join(", "), sql))
+}
+
+fn parse_id(text: Option<String>, what: &str, column: &str) -> u64 {
+    let text =
+        text.unwrap_or_else(|| error!("graph_accel: {} query returned a NULL {}", what, column));
+    text.parse().unwrap_or_else(|_| {
+        error!(
+            "graph_accel: {} query returned {} '{}', expected a non-negative integer",
+            what, column, text
+        )
+    })
+}
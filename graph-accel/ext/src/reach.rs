@@ -0,0 +1,162 @@
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use graph_accel_core::{
+    Graph, ReachabilityIndex, RelTypeId, TraversalDirection, MAX_HLL_PRECISION, MIN_HLL_PRECISION,
+};
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Build the reachability index over directed edges of the given rel types.
+///
+/// Replaces any previous index. Pass NULL to index all rel types. The index
+/// is per-backend and is dropped whenever the graph is reloaded.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_build_reachability(ARRAY['SUBSUMES']);
+///   SELECT * FROM graph_accel_build_reachability();
+#[pg_extern]
+fn graph_accel_build_reachability(
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(component_count, i64),
+        name!(memory_bytes, i64),
+        name!(build_time_ms, f64),
+    ),
+> {
+    let _call = stats::call("graph_accel_build_reachability");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+    let start = Instant::now();
+
+    let row = state::with_graph_mut_named(graph_name.as_deref(), |gs| {
+        let ids = resolve_rel_types(&gs.graph, rel_types.as_deref());
+        let index = ReachabilityIndex::build(&gs.graph, ids.as_deref());
+        let row = (
+            index.component_count() as i64,
+            index.memory_usage() as i64,
+            start.elapsed().as_secs_f64() * 1000.0,
+        );
+        gs.reachability = Some(index);
+        row
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::once(row)
+}
+
+/// True if a directed path leads from `from_id` to `to_id` over edges of
+/// `rel_types` (every type if NULL).
+///
+/// Answered from the reachability index when one was built over the same
+/// rel types (see `graph_accel_build_reachability()`), otherwise by a BFS
+/// over outgoing edges of those types that stops as soon as it finds
+/// `to_id`. An index over other types is never consulted, so the answer
+/// does not depend on what was built earlier in the session.
+///
+/// Usage:
+///   SELECT graph_accel_reachable('concept_a', 'concept_b');
+///   SELECT graph_accel_reachable('concept_a', 'concept_b', ARRAY['SUBSUMES']);
+#[pg_extern]
+fn graph_accel_reachable(
+    from_id: String,
+    to_id: String,
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
+) -> bool {
+    let _call = stats::call("graph_accel_reachable");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
+
+    state::with_graph_named(graph_name.as_deref(), |gs| {
+        let from = state::resolve_node(&gs.graph, &from_id);
+        let to = state::resolve_node(&gs.graph, &to_id);
+        let ids = resolve_rel_types(&gs.graph, rel_types.as_deref());
+        match gs.reachability.as_ref() {
+            Some(index) if same_rel_types(index.rel_types(), ids.as_deref()) => {
+                index.is_reachable(from, to)
+            }
+            _ => {
+                let view = match &rel_types {
+                    Some(names) => gs.graph.view().with_rel_types(names),
+                    None => gs.graph.view(),
+                };
+                view.path_exists(from, to, u32::MAX, TraversalDirection::Outgoing)
+            }
+        }
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    })
+}
+
+/// Rel type IDs for `names`, erroring on unknown ones; None for NULL.
+fn resolve_rel_types(graph: &Graph, names: Option<&[String]>) -> Option<Vec<RelTypeId>> {
+    names.map(|names| {
+        names
+            .iter()
+            .map(|n| crate::util::resolve_rel_type(graph, n))
+            .collect()
+    })
+}
+
+/// True if two rel type filters admit the same types, ignoring order and
+/// repeats. None (every type) only matches None.
+fn same_rel_types(a: Option<&[RelTypeId]>, b: Option<&[RelTypeId]>) -> bool {
+    let set = |ids: &[RelTypeId]| ids.iter().copied().collect::<BTreeSet<_>>();
+    a.map(set) == b.map(set)
+}
+
+/// Estimated number of nodes within `depth` hops of every node (HyperANF).
+///
+/// One HyperLogLog counter per node replaces a BFS per node: `depth` linear
+/// passes over the graph. Error is about `1.04 / sqrt(2^precision)` (6.5% at
+/// the default 8); memory is `2 × 2^precision` bytes per node while it runs.
+/// The estimate counts the node itself.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_reach_estimate(3) ORDER BY estimated_reach DESC LIMIT 20;
+#[pg_extern]
+fn graph_accel_reach_estimate(
+    depth: default!(i32, 3),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+    precision: default!(i32, 8),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(estimated_reach, f64),
+    ),
+> {
+    let call = stats::call("graph_accel_reach_estimate");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(depth, "depth");
+    let p = crate::util::check_non_negative(precision, "precision");
+    if !(MIN_HLL_PRECISION..=MAX_HLL_PRECISION).contains(&p) {
+        error!(
+            "graph_accel: precision must be between {} and {}, got {}",
+            MIN_HLL_PRECISION, MAX_HLL_PRECISION, p
+        );
+    }
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::reach_estimates(&gs.graph, depth, direction, min_confidence.map(|v| v as f32), p)
+            .into_iter()
+            .map(|r| (r.node_id as i64, r.label, r.app_id, r.estimate))
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
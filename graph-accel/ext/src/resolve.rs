@@ -0,0 +1,37 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Resolve a node to its AGE graph ID, or NULL if no node matches.
+///
+/// Without `index_name`, `node_id` is resolved as every other function
+/// resolves it: by app ID (`graph_accel.node_id_property`), then as an AGE
+/// graph ID. With it, `node_id` is looked up by value in that secondary
+/// index, one per property listed in `graph_accel.index_properties`.
+///
+/// Usage:
+///   SELECT graph_accel_resolve('concept_a');
+///   SELECT graph_accel_resolve('transformer-architecture', 'slug');
+#[pg_extern]
+fn graph_accel_resolve(
+    node_id: String,
+    index_name: default!(Option<String>, "NULL"),
+) -> Option<i64> {
+    let _call = stats::call("graph_accel_resolve");
+    crate::generation::ensure_fresh();
+
+    state::with_graph(|gs| match index_name.as_deref() {
+        None => gs.graph.resolve_node(&node_id).ok(),
+        Some(index) => {
+            if !gs.graph.index_names().contains(&index) {
+                error!("graph_accel: no secondary index '{}'", index);
+            }
+            gs.graph.resolve_by(index, &node_id)
+        }
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    })
+    .map(|id| id as i64)
+}
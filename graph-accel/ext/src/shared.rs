@@ -0,0 +1,172 @@
+//! Graphs shared across backends through memory-mapped files.
+//!
+//! With `graph_accel.shared_graph` on, the first backend to load a graph at
+//! a given generation publishes its frozen adjacency as a mapped file (see
+//! `Graph::write_mapped`) and serves the graph from a mapping of it. Other
+//! backends loading the same graph at the same generation attach to the
+//! file instead of reading AGE. The kernel page cache holds one copy of the
+//! adjacency however many backends map it; node metadata and properties
+//! are still read into each backend.
+//!
+//! Files are named `<graph>.<settings>.<generation>.map`, where `settings`
+//! fingerprints the load GUCs, so sessions with different filters never
+//! share a graph. A reload after invalidation publishes the next
+//! generation and unlinks older ones; backends still mapping an old file
+//! keep it until they reload themselves.
+//!
+//! Attaching skips the scan of AGE, and with it Postgres's access checks,
+//! so a backend only attaches or publishes when `permitted` finds that the
+//! current user can read every label table in full.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+
+use graph_accel_core::Graph;
+use pgrx::prelude::*;
+use pgrx::spi::{SpiClient, SpiError};
+
+use crate::guc;
+
+/// True if loads should go through shared files.
+pub(crate) fn enabled() -> bool {
+    guc::SHARED_GRAPH.get()
+}
+
+/// True if this backend may share the copy of `graph_name` built from
+/// `labels`: the current user has SELECT on every label table, and no row
+/// security policy applies to any of them.
+///
+/// Without this, attaching would serve rows the user's privileges or
+/// policies hide, and a copy published under a policy would hand other
+/// users a filtered graph.
+pub(crate) fn permitted(
+    client: &SpiClient<'_>,
+    graph_name: &str,
+    labels: Vec<String>,
+) -> Result<bool, SpiError> {
+    let query = "SELECT coalesce(bool_and(has_table_privilege(l.relation, 'SELECT') \
+                                          AND NOT row_security_active(l.relation)), true) \
+                 FROM ag_catalog.ag_label l \
+                 JOIN ag_catalog.ag_graph g ON l.graph = g.graphid \
+                 WHERE g.name = $1 AND l.name = ANY($2)";
+    let permitted = client
+        .select(query, None, &[graph_name.into(), labels.into()])?
+        .first()
+        .get_one::<bool>()?;
+    Ok(permitted.unwrap_or(false))
+}
+
+/// Map the published file for `graph_name` at `generation`, if there is
+/// one. A file that fails to open is reported and skipped.
+pub(crate) fn attach(graph_name: &str, generation: i64) -> Option<Graph> {
+    let path = file_path(graph_name, generation);
+    if !path.exists() {
+        return None;
+    }
+    match Graph::open_mapped(&path) {
+        Ok(graph) => Some(graph),
+        Err(e) => {
+            warning!(
+                "graph_accel: cannot attach shared graph {}: {}, loading from AGE",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Publish a frozen `graph` for `graph_name` at `generation`, returning
+/// it mapped from the published file, and unlink older generations. On
+/// failure the caller keeps its private copy.
+pub(crate) fn publish(graph_name: &str, generation: i64, graph: &Graph) -> Option<Graph> {
+    let path = file_path(graph_name, generation);
+    // Write aside and rename, so attaching backends never see a partial
+    // file; concurrent publishers of one generation write the same bytes.
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    let written = std::fs::create_dir_all(shared_dir())
+        .and_then(|()| std::fs::File::create(&tmp))
+        .and_then(|file| graph.write_mapped(file))
+        .and_then(|()| std::fs::rename(&tmp, &path))
+        .and_then(|()| Graph::open_mapped(&path));
+    let mapped = match written {
+        Ok(mapped) => mapped,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp);
+            warning!(
+                "graph_accel: cannot publish shared graph {}: {}, keeping a private copy",
+                path.display(),
+                e
+            );
+            return None;
+        }
+    };
+
+    let prefix = file_prefix(graph_name);
+    let older = std::fs::read_dir(shared_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(".map"))
+                .and_then(|gen| gen.parse::<i64>().ok())
+                .is_some_and(|gen| gen < generation)
+        });
+    for entry in older {
+        let _ = std::fs::remove_file(entry.path());
+    }
+
+    Some(mapped)
+}
+
+fn shared_dir() -> PathBuf {
+    PathBuf::from(guc::get_string(&guc::SHARED_DIR).unwrap_or_else(|| "pg_graph_accel".into()))
+}
+
+fn file_path(graph_name: &str, generation: i64) -> PathBuf {
+    shared_dir().join(format!("{}{}.map", file_prefix(graph_name), generation))
+}
+
+/// `<graph>.<settings>.`, common to every generation of one graph loaded
+/// with the current load GUCs. The fingerprint only needs to agree between
+/// backends running the same build.
+fn file_prefix(graph_name: &str) -> String {
+    let strings = [
+        &guc::NODE_ID_PROPERTY,
+        &guc::INDEX_PROPERTIES,
+        &guc::NODE_LABELS,
+        &guc::EDGE_TYPES,
+        &guc::NODE_LABEL_PROPERTY,
+        &guc::NODE_PROPERTIES,
+        &guc::EDGE_CONFIDENCE_PROPERTY,
+        &guc::EDGE_WEIGHT_PROPERTY,
+        &guc::EDGE_TIMESTAMP_PROPERTY,
+        &guc::EDGE_PROPERTIES,
+        &guc::LOAD_PREDICATES,
+    ];
+    let mut hasher = DefaultHasher::new();
+    for setting in strings {
+        guc::get_string(setting).hash(&mut hasher);
+    }
+    guc::GROUP_EDGES_BY_TYPE.get().hash(&mut hasher);
+    guc::DEDUPE_EDGES.get().hash(&mut hasher);
+    format!("{}.{:016x}.", file_safe(graph_name), hasher.finish())
+}
+
+/// `name` with every byte other than ASCII letters, digits and `_` written
+/// as `%XX`, so any graph name makes a plain file name.
+fn file_safe(name: &str) -> String {
+    name.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b == b'_' {
+                (b as char).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,188 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Suggest new links for a node, ranked by Adamic-Adar score.
+///
+/// Candidates are nodes two hops away that are not already linked to
+/// `source_id`. Shared neighbors with few connections count for more than
+/// shared hubs.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_adamic_adar('concept_a');
+///   SELECT * FROM graph_accel_adamic_adar('concept_a', top_n => 50);
+#[pg_extern]
+fn graph_accel_adamic_adar(
+    source_id: String,
+    top_n: default!(i32, 20),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(common_neighbors, i32),
+        name!(score, f64),
+    ),
+> {
+    let call = stats::call("graph_accel_adamic_adar");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(top_n, "top_n") as usize;
+
+    let results = state::with_graph(|gs| {
+        let source = state::resolve_node(&gs.graph, &source_id);
+
+        graph_accel_core::adamic_adar(&gs.graph, source, n)
+            .into_iter()
+            .map(|c| {
+                (
+                    c.node_id as i64,
+                    c.label,
+                    c.app_id,
+                    c.common_neighbors as i32,
+                    c.score,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Neighbors shared by two nodes, with the relationship types linking each side.
+///
+/// Edge direction is ignored. The two nodes themselves are never listed,
+/// even when directly linked.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_common_neighbors('concept_a', 'concept_b');
+#[pg_extern]
+fn graph_accel_common_neighbors(
+    a_id: String,
+    b_id: String,
+) -> TableIterator<
+    'static,
+    (
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+        name!(a_rel_types, Vec<String>),
+        name!(b_rel_types, Vec<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_common_neighbors");
+    crate::generation::ensure_fresh();
+
+    let results = state::with_graph(|gs| {
+        let a = state::resolve_node(&gs.graph, &a_id);
+        let b = state::resolve_node(&gs.graph, &b_id);
+
+        graph_accel_core::common_neighbors(&gs.graph, a, b)
+            .into_iter()
+            .map(|c| (c.node_id as i64, c.label, c.app_id, c.rel_types_a, c.rel_types_b))
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// SimRank similarity between two nodes (truncated after `iterations` rounds).
+///
+/// Captures structural-context similarity — nodes whose neighbors are
+/// similar — even without direct common neighbors. Use
+/// `direction_filter => 'incoming'` for classic (in-neighbor) SimRank.
+///
+/// Usage:
+///   SELECT graph_accel_simrank('concept_a', 'concept_b');
+///   SELECT graph_accel_simrank('a', 'b', decay => 0.6, iterations => 3);
+#[pg_extern]
+fn graph_accel_simrank(
+    a_id: String,
+    b_id: String,
+    decay: default!(f64, 0.8),
+    iterations: default!(i32, 5),
+    direction_filter: default!(String, "'both'"),
+) -> f64 {
+    let _call = stats::call("graph_accel_simrank");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let iters = crate::util::check_non_negative(iterations, "iterations");
+    if decay.is_nan() || decay <= 0.0 || decay > 1.0 {
+        error!("graph_accel: decay must be in (0, 1], got {}", decay);
+    }
+
+    state::with_graph(|gs| {
+        let a = state::resolve_node(&gs.graph, &a_id);
+        let b = state::resolve_node(&gs.graph, &b_id);
+        graph_accel_core::simrank(&gs.graph, a, b, decay, iters, direction)
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    })
+}
+
+/// Edges ranked by embeddedness: Jaccard overlap of their endpoints'
+/// neighbor sets.
+///
+/// `order` 'desc' lists the most embedded edges first, 'asc' the least —
+/// poorly embedded, high-confidence edges are prime review candidates.
+/// Direction is ignored when building neighbor sets; self-loops are skipped.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_edge_embeddedness(50, 'asc');
+#[pg_extern]
+fn graph_accel_edge_embeddedness(
+    top_n: default!(i32, 100),
+    order: default!(String, "'desc'"),
+) -> TableIterator<
+    'static,
+    (
+        name!(from_id, i64),
+        name!(from_label, String),
+        name!(from_app_id, Option<String>),
+        name!(to_id, i64),
+        name!(to_label, String),
+        name!(to_app_id, Option<String>),
+        name!(rel_type, String),
+        name!(confidence, Option<f64>),
+        name!(common_neighbors, i32),
+        name!(embeddedness, f64),
+    ),
+> {
+    let call = stats::call("graph_accel_edge_embeddedness");
+    crate::generation::ensure_fresh();
+    let n = crate::util::check_non_negative(top_n, "top_n") as usize;
+    let ascending = crate::util::parse_sort_order(&order);
+
+    let results = state::with_graph(|gs| {
+        graph_accel_core::edge_embeddedness(&gs.graph, n, ascending)
+            .into_iter()
+            .map(|e| {
+                (
+                    e.from_id as i64,
+                    e.from_label,
+                    e.from_app_id,
+                    e.to_id as i64,
+                    e.to_label,
+                    e.to_app_id,
+                    e.rel_type,
+                    e.confidence.map(|c| c as f64),
+                    e.common_neighbors as i32,
+                    e.embeddedness,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
@@ -1,54 +1,257 @@
 use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::sync::Arc;
 use std::time::Instant;
 
-use graph_accel_core::Graph;
+use graph_accel_core::{Components, FrozenGraph, Graph, LandmarkOracle, ReachabilityIndex};
+use pgrx::datum::TimestampWithTimeZone;
+
+/// Where a graph was read from. Reloads read it the same way.
+#[derive(Clone)]
+pub enum GraphSource {
+    /// The AGE graph named `source_graph`.
+    Age,
+    /// The node and edge queries given to `graph_accel_load_query()`.
+    Query { node_sql: String, edge_sql: String },
+}
 
 /// Metadata about the loaded graph state.
 pub struct GraphState {
-    pub graph: Graph,
+    /// Clone the Arc to keep the graph past the current call; mutation
+    /// needs `Arc::get_mut`, so it fails while such a clone is alive.
+    pub graph: Arc<FrozenGraph>,
     pub source_graph: String,
+    pub source: GraphSource,
     pub load_time_ms: f64,
     pub loaded_at: Instant,
     /// Generation counter at time of load. 0 = loaded before any invalidation.
     pub loaded_generation: i64,
+    /// Built on demand by `graph_accel_build_reachability()`; dropped on reload.
+    pub reachability: Option<ReachabilityIndex>,
+    /// Built by `graph_accel_build_landmarks()` or on first distance estimate; dropped on reload.
+    pub landmarks: Option<LandmarkOracle>,
+    /// Built on first `graph_accel_component_of()`; dropped on reload.
+    pub components: Option<Components>,
+}
+
+/// A partially built graph kept after an interrupted `graph_accel_load()`,
+/// so `graph_accel_load(resume => true)` can continue instead of restarting.
+pub struct LoadCheckpoint {
+    pub graph: Graph,
+    pub source_graph: String,
+    /// Generation when the interrupted load started. A resume against a
+    /// newer generation starts over — the completed labels may be stale.
+    pub generation: i64,
+    /// Labels fully applied to `graph`, as (kind, name).
+    pub completed: HashSet<(char, String)>,
 }
 
+/// Outcome of a load attempt, as reported by `graph_accel_load_history()`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LoadOutcome {
+    /// Recorded at start; left in place if the load errors out.
+    Interrupted,
+    Complete,
+    /// Mapped a graph another backend published; no labels were read.
+    Attached,
+}
+
+/// One entry in the per-backend load history.
+#[derive(Clone)]
+pub struct LoadHistoryEntry {
+    pub source_graph: String,
+    pub started_at: Option<TimestampWithTimeZone>,
+    pub outcome: LoadOutcome,
+    pub resumed: bool,
+    pub labels_completed: usize,
+    pub labels_total: usize,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub load_time_ms: f64,
+}
+
+/// Number of load attempts retained in the history.
+const LOAD_HISTORY_LEN: usize = 20;
+
 thread_local! {
-    /// Per-backend graph state.
+    /// Per-backend graph states, keyed by source graph name.
     ///
     /// PostgreSQL backends are single-threaded, so thread_local! + RefCell
-    /// is safe. Each connection loads its own graph copy.
-    /// Shared memory deferred to a future phase.
-    static GRAPH_STATE: RefCell<Option<GraphState>> = const { RefCell::new(None) };
+    /// is safe. Each connection loads its own graph copies.
+    /// With `graph_accel.shared_graph`, their adjacency is mapped from a
+    /// shared file instead (see `shared.rs`).
+    static GRAPH_STATES: RefCell<BTreeMap<String, GraphState>> = const { RefCell::new(BTreeMap::new()) };
+
+    /// Graph that functions without a `graph_name` argument use: the last
+    /// one passed to `graph_accel_load()`, if still loaded.
+    static DEFAULT_GRAPH: RefCell<Option<String>> = const { RefCell::new(None) };
+
+    /// Graph under construction. Survives an ERROR mid-load so the next
+    /// `graph_accel_load(resume => true)` can pick it up.
+    static LOAD_CHECKPOINT: RefCell<Option<LoadCheckpoint>> = const { RefCell::new(None) };
+
+    /// Most recent load attempts, oldest first.
+    static LOAD_HISTORY: RefCell<VecDeque<LoadHistoryEntry>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Name of the graph `name` selects: itself, or for None the default
+/// graph, falling back to the first loaded graph by name. Errors if a
+/// named graph is not loaded; None if no graph is loaded at all.
+fn select(graphs: &BTreeMap<String, GraphState>, name: Option<&str>) -> Option<String> {
+    if let Some(name) = name {
+        if !graphs.contains_key(name) {
+            pgrx::error!(
+                "graph_accel: graph '{}' is not loaded — call graph_accel_load('{}') first",
+                name,
+                name
+            );
+        }
+        return Some(name.to_string());
+    }
+    DEFAULT_GRAPH
+        .with(|cell| cell.borrow().clone())
+        .filter(|name| graphs.contains_key(name))
+        .or_else(|| graphs.keys().next().cloned())
 }
 
-/// Execute a closure with a read reference to the loaded graph.
+/// Execute a closure with a read reference to the default graph.
 /// Returns None if no graph is loaded.
 pub fn with_graph<R, F: FnOnce(&GraphState) -> R>(f: F) -> Option<R> {
-    GRAPH_STATE.with(|cell| {
-        let borrow = cell.borrow();
-        borrow.as_ref().map(f)
+    with_graph_named(None, f)
+}
+
+/// `with_graph` for the graph called `name`, or the default graph if None.
+pub fn with_graph_named<R, F: FnOnce(&GraphState) -> R>(name: Option<&str>, f: F) -> Option<R> {
+    GRAPH_STATES.with(|cell| {
+        let graphs = cell.borrow();
+        let name = select(&graphs, name)?;
+        graphs.get(&name).map(f)
+    })
+}
+
+/// Execute a closure with a mutable reference to the default graph state.
+/// Returns None if no graph is loaded.
+pub fn with_graph_mut<R, F: FnOnce(&mut GraphState) -> R>(f: F) -> Option<R> {
+    with_graph_mut_named(None, f)
+}
+
+/// `with_graph_mut` for the graph called `name`, or the default graph if
+/// None.
+pub fn with_graph_mut_named<R, F: FnOnce(&mut GraphState) -> R>(
+    name: Option<&str>,
+    f: F,
+) -> Option<R> {
+    GRAPH_STATES.with(|cell| {
+        let mut graphs = cell.borrow_mut();
+        let name = select(&graphs, name)?;
+        graphs.get_mut(&name).map(f)
     })
 }
 
-/// Replace the per-backend graph state.
+/// True if a graph called `name` is loaded.
+pub fn is_loaded(name: &str) -> bool {
+    GRAPH_STATES.with(|cell| cell.borrow().contains_key(name))
+}
+
+/// Execute a closure with a read reference to every loaded graph, in
+/// name order.
+pub fn with_each_graph<R, F: FnMut(&GraphState) -> R>(f: F) -> Vec<R> {
+    GRAPH_STATES.with(|cell| cell.borrow().values().map(f).collect())
+}
+
+/// Store a loaded graph under its source graph name, replacing any
+/// previous copy of the same graph.
 pub fn set_graph(state: GraphState) {
-    GRAPH_STATE.with(|cell| {
-        *cell.borrow_mut() = Some(state);
+    GRAPH_STATES.with(|cell| {
+        cell.borrow_mut().insert(state.source_graph.clone(), state);
     });
 }
 
+/// Make `name` the graph used by functions without a `graph_name`.
+pub fn set_default_graph(name: &str) {
+    DEFAULT_GRAPH.with(|cell| *cell.borrow_mut() = Some(name.to_string()));
+}
+
+/// Remove and return the default graph state. The default stays pointed
+/// at its name, so putting it back with `set_graph` restores it.
+pub fn take_graph() -> Option<GraphState> {
+    GRAPH_STATES.with(|cell| {
+        let mut graphs = cell.borrow_mut();
+        let name = select(&graphs, None)?;
+        graphs.remove(&name)
+    })
+}
+
+/// Remove and return the graph called `name`, if loaded.
+pub fn remove_graph(name: &str) -> Option<GraphState> {
+    GRAPH_STATES.with(|cell| cell.borrow_mut().remove(name))
+}
+
 /// Resolve a node identifier: try app_id first, then parse as AGE graphid.
 pub fn resolve_node(graph: &graph_accel_core::Graph, id_str: &str) -> u64 {
     graph
-        .resolve_app_id(id_str)
-        .or_else(|| {
-            id_str
-                .parse::<u64>()
-                .ok()
-                .filter(|id| graph.node(*id).is_some())
-        })
-        .unwrap_or_else(|| {
-            pgrx::error!("graph_accel: node '{}' not found", id_str);
-        })
+        .resolve_node(id_str)
+        .unwrap_or_else(|e| pgrx::error!("graph_accel: {}", e))
+}
+
+/// Start a fresh checkpoint for `source_graph`, discarding any previous one.
+pub fn start_checkpoint(source_graph: &str, generation: i64) {
+    LOAD_CHECKPOINT.with(|cell| {
+        *cell.borrow_mut() = Some(LoadCheckpoint {
+            graph: Graph::new(),
+            source_graph: source_graph.to_string(),
+            generation,
+            completed: HashSet::new(),
+        });
+    });
+}
+
+/// True if a checkpoint exists for `source_graph` at `generation`.
+pub fn has_checkpoint(source_graph: &str, generation: i64) -> bool {
+    LOAD_CHECKPOINT.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .is_some_and(|cp| cp.source_graph == source_graph && cp.generation == generation)
+    })
+}
+
+/// Execute a closure with a mutable reference to the in-progress checkpoint.
+///
+/// # Panics
+/// Panics if no checkpoint has been started.
+pub fn with_checkpoint<R, F: FnOnce(&mut LoadCheckpoint) -> R>(f: F) -> R {
+    LOAD_CHECKPOINT.with(|cell| {
+        let mut borrow = cell.borrow_mut();
+        f(borrow.as_mut().expect("graph_accel: no load checkpoint"))
+    })
+}
+
+/// Remove and return the checkpoint once a load completes.
+pub fn take_checkpoint() -> Option<LoadCheckpoint> {
+    LOAD_CHECKPOINT.with(|cell| cell.borrow_mut().take())
+}
+
+/// Append a load attempt to the history, evicting the oldest entry if full.
+pub fn push_load_history(entry: LoadHistoryEntry) {
+    LOAD_HISTORY.with(|cell| {
+        let mut history = cell.borrow_mut();
+        if history.len() >= LOAD_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(entry);
+    });
+}
+
+/// Update the most recent history entry in place.
+pub fn update_load_history<F: FnOnce(&mut LoadHistoryEntry)>(f: F) {
+    LOAD_HISTORY.with(|cell| {
+        if let Some(entry) = cell.borrow_mut().back_mut() {
+            f(entry);
+        }
+    });
+}
+
+/// Snapshot of the load history, oldest first.
+pub fn load_history() -> Vec<LoadHistoryEntry> {
+    LOAD_HISTORY.with(|cell| cell.borrow().iter().cloned().collect())
 }
@@ -0,0 +1,199 @@
+//! Per-backend call statistics for the SQL functions.
+//!
+//! Each instrumented function starts with `let call = stats::call(NAME)`.
+//! The guard records the call and its latency when dropped, or when it
+//! hands its result rows to `call.rows(iter)`, which counts them as
+//! Postgres fetches them. Latency therefore covers the search, not row
+//! output, and rows skipped by a `LIMIT` are not counted. A call that
+//! errors out is counted as an error when the guard unwinds.
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use pgrx::prelude::*;
+
+/// Latencies kept per function for the percentiles.
+const RECENT_CALLS: usize = 1024;
+
+#[derive(Default)]
+struct FunctionStats {
+    calls: u64,
+    errors: u64,
+    rows: u64,
+    reloads: u64,
+    total_ms: f64,
+    max_ms: f64,
+    /// The last `RECENT_CALLS` latencies, oldest overwritten first.
+    recent_ms: Vec<f64>,
+    next: usize,
+}
+
+impl FunctionStats {
+    fn record(&mut self, ms: f64) {
+        self.calls += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+        if self.recent_ms.len() < RECENT_CALLS {
+            self.recent_ms.push(ms);
+        } else {
+            self.recent_ms[self.next] = ms;
+        }
+        self.next = (self.next + 1) % RECENT_CALLS;
+    }
+}
+
+thread_local! {
+    static STATS: RefCell<BTreeMap<&'static str, FunctionStats>> =
+        const { RefCell::new(BTreeMap::new()) };
+    /// Automatic reloads so far, read before and after each call.
+    static RELOADS: Cell<u64> = const { Cell::new(0) };
+}
+
+fn with_stats<R>(function: &'static str, f: impl FnOnce(&mut FunctionStats) -> R) -> R {
+    STATS.with(|s| f(s.borrow_mut().entry(function).or_default()))
+}
+
+/// Start timing a call of `function`.
+pub(crate) fn call(function: &'static str) -> Call {
+    Call {
+        function,
+        start: Instant::now(),
+        reloads: RELOADS.with(Cell::get),
+        row_count: Some(1),
+    }
+}
+
+/// Count an automatic reload of a stale graph, charged to the running call.
+pub(crate) fn note_reload() {
+    RELOADS.with(|r| r.set(r.get() + 1));
+}
+
+/// An instrumented call in progress. A scalar or single-row result counts
+/// as one row.
+pub(crate) struct Call {
+    function: &'static str,
+    start: Instant,
+    reloads: u64,
+    row_count: Option<u64>,
+}
+
+impl Call {
+    /// End the call, counting the rows of `rows` as they are fetched.
+    pub(crate) fn rows<I: IntoIterator>(mut self, rows: I) -> Rows<I::IntoIter> {
+        self.row_count = None;
+        Rows {
+            function: self.function,
+            rows: rows.into_iter(),
+        }
+    }
+}
+
+impl Drop for Call {
+    fn drop(&mut self) {
+        let ms = self.start.elapsed().as_secs_f64() * 1000.0;
+        let reloads = RELOADS.with(Cell::get) - self.reloads;
+        let failed = std::thread::panicking();
+        with_stats(self.function, |s| {
+            s.record(ms);
+            s.reloads += reloads;
+            if failed {
+                s.errors += 1;
+            } else {
+                s.rows += self.row_count.unwrap_or(0);
+            }
+        });
+    }
+}
+
+/// Result rows of a finished call, counted as they are fetched.
+pub(crate) struct Rows<I> {
+    function: &'static str,
+    rows: I,
+}
+
+impl<I: Iterator> Iterator for Rows<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let row = self.rows.next()?;
+        with_stats(self.function, |s| s.rows += 1);
+        Some(row)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+/// Nearest-rank percentile `p` (0 to 1) of the ascending `sorted`.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Calls, errors, rows returned and latency for each graph_accel function
+/// called in this backend since it started or since
+/// `graph_accel_stats_reset()`, in function name order.
+///
+/// `reloads` counts automatic reloads of a stale graph made during the
+/// function's calls. Latency runs from the call to its result being ready,
+/// so it excludes the time Postgres spends fetching rows; `rows` counts
+/// the rows actually fetched. Percentiles are over the last 1024 calls.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_stats() ORDER BY total_ms DESC;
+#[pg_extern]
+fn graph_accel_stats() -> TableIterator<
+    'static,
+    (
+        name!(function, String),
+        name!(calls, i64),
+        name!(errors, i64),
+        name!(rows, i64),
+        name!(reloads, i64),
+        name!(total_ms, f64),
+        name!(mean_ms, f64),
+        name!(p50_ms, f64),
+        name!(p95_ms, f64),
+        name!(p99_ms, f64),
+        name!(max_ms, f64),
+    ),
+> {
+    let rows: Vec<_> = STATS.with(|stats| {
+        stats
+            .borrow()
+            .iter()
+            .map(|(function, s)| {
+                let mut recent = s.recent_ms.clone();
+                recent.sort_by(f64::total_cmp);
+                (
+                    function.to_string(),
+                    s.calls as i64,
+                    s.errors as i64,
+                    s.rows as i64,
+                    s.reloads as i64,
+                    s.total_ms,
+                    s.total_ms / s.calls.max(1) as f64,
+                    percentile(&recent, 0.50),
+                    percentile(&recent, 0.95),
+                    percentile(&recent, 0.99),
+                    s.max_ms,
+                )
+            })
+            .collect()
+    });
+    TableIterator::new(rows)
+}
+
+/// Clear this backend's `graph_accel_stats()`.
+///
+/// Usage:
+///   SELECT graph_accel_stats_reset();
+#[pg_extern]
+fn graph_accel_stats_reset() {
+    STATS.with(|s| s.borrow_mut().clear());
+}
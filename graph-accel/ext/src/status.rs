@@ -3,7 +3,14 @@ use pgrx::prelude::*;
 use crate::generation;
 use crate::guc;
 use crate::state;
+use crate::stats;
 
+/// One row per loaded graph, in name order, with `is_default` marking
+/// the graph used by functions called without a `graph_name`. With no
+/// graph loaded, one `not_loaded` row for `graph_accel.source_graph`.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_status();
 #[pg_extern]
 fn graph_accel_status() -> TableIterator<
     'static,
@@ -17,9 +24,11 @@ fn graph_accel_status() -> TableIterator<
         name!(loaded_generation, i64),
         name!(current_generation, i64),
         name!(is_stale, bool),
+        name!(is_default, bool),
     ),
 > {
-    let row = if let Some(result) = state::with_graph(|gs| {
+    let default = state::with_graph(|gs| gs.source_graph.clone());
+    let rows = state::with_each_graph(|gs| {
         let current_gen = generation::fetch_generation(&gs.source_graph).unwrap_or(0);
         let is_stale = gs.loaded_generation < current_gen;
         let status_str = if is_stale { "stale" } else { "loaded" };
@@ -34,28 +43,125 @@ fn graph_accel_status() -> TableIterator<
             gs.loaded_generation,
             current_gen,
             is_stale,
+            default.as_ref() == Some(&gs.source_graph),
         )
-    }) {
-        result
-    } else {
-        let configured = guc::get_string(&guc::SOURCE_GRAPH);
-        let current_gen = configured
-            .as_ref()
-            .and_then(|name| generation::fetch_generation(name))
-            .unwrap_or(0);
+    });
+    if !rows.is_empty() {
+        return TableIterator::new(rows);
+    }
 
+    let configured = guc::get_string(&guc::SOURCE_GRAPH);
+    let current_gen = configured
+        .as_ref()
+        .and_then(|name| generation::fetch_generation(name))
+        .unwrap_or(0);
+
+    TableIterator::new(vec![(
+        configured,
+        "not_loaded".to_string(),
+        0,
+        0,
+        0,
+        0,
+        0,
+        current_gen,
+        false,
+        false,
+    )])
+}
+
+/// Whole-graph topology summary, as one row.
+///
+/// Density is directed: edges over ordered node pairs. Degrees count
+/// in + out edges. `rel_types` and `rel_type_counts` are parallel arrays,
+/// most frequent type first.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_topology();
+#[pg_extern]
+fn graph_accel_topology() -> TableIterator<
+    'static,
+    (
+        name!(node_count, i64),
+        name!(edge_count, i64),
+        name!(density, f64),
+        name!(avg_degree, f64),
+        name!(max_degree, i64),
+        name!(isolated_nodes, i64),
+        name!(rel_types, Vec<String>),
+        name!(rel_type_counts, Vec<i64>),
+    ),
+> {
+    let _call = stats::call("graph_accel_topology");
+    crate::generation::ensure_fresh();
+
+    let row = state::with_graph(|gs| {
+        let stats = gs.graph.stats();
+        let (types, counts) = stats
+            .rel_types
+            .into_iter()
+            .map(|(name, count)| (name, count as i64))
+            .unzip();
         (
-            configured,
-            "not_loaded".to_string(),
-            0,
-            0,
-            0,
-            0,
-            0,
-            current_gen,
-            false,
+            stats.node_count as i64,
+            stats.edge_count as i64,
+            stats.density,
+            stats.avg_degree,
+            stats.max_degree as i64,
+            stats.isolated_nodes as i64,
+            types,
+            counts,
         )
-    };
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
 
     TableIterator::once(row)
 }
+
+/// Recent `graph_accel_load()` attempts in this backend, oldest first.
+///
+/// An attempt that errored out (cancel, memory cap, SPI failure) stays
+/// `interrupted` with the labels it completed; `graph_accel_load(resume => true)`
+/// continues from there.
+/// A load that attached to a published shared graph is `attached`.
+#[pg_extern]
+fn graph_accel_load_history() -> TableIterator<
+    'static,
+    (
+        name!(source_graph, String),
+        name!(started_at, Option<TimestampWithTimeZone>),
+        name!(status, String),
+        name!(resumed, bool),
+        name!(labels_completed, i32),
+        name!(labels_total, i32),
+        name!(node_count, i64),
+        name!(edge_count, i64),
+        name!(load_time_ms, f64),
+    ),
+> {
+    let rows: Vec<_> = state::load_history()
+        .into_iter()
+        .map(|h| {
+            let status = match h.outcome {
+                state::LoadOutcome::Complete => "complete",
+                state::LoadOutcome::Interrupted => "interrupted",
+                state::LoadOutcome::Attached => "attached",
+            };
+            (
+                h.source_graph,
+                h.started_at,
+                status.to_string(),
+                h.resumed,
+                h.labels_completed as i32,
+                h.labels_total as i32,
+                h.node_count as i64,
+                h.edge_count as i64,
+                h.load_time_ms,
+            )
+        })
+        .collect();
+
+    TableIterator::new(rows)
+}
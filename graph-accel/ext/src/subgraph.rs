@@ -1,13 +1,22 @@
+use pgrx::datum::TimestampWithTimeZone;
 use pgrx::prelude::*;
 
 use crate::state;
+use crate::stats;
 
 #[pg_extern]
+#[allow(clippy::too_many_arguments)]
 fn graph_accel_subgraph(
     start_id: String,
     max_depth: default!(i32, 3),
     direction_filter: default!(String, "'both'"),
     min_confidence: default!(Option<f64>, "NULL"),
+    max_results: default!(i32, 0),
+    timeout_ms: default!(Option<i32>, "NULL"),
+    rel_types: default!(Option<Vec<String>>, "NULL"),
+    node_labels: default!(Option<Vec<String>>, "NULL"),
+    as_of: default!(Option<TimestampWithTimeZone>, "NULL"),
+    graph_name: default!(Option<String>, "NULL"),
 ) -> TableIterator<
     'static,
     (
@@ -18,18 +27,146 @@ fn graph_accel_subgraph(
         name!(to_label, String),
         name!(to_app_id, Option<String>),
         name!(rel_type, String),
+        name!(truncated, bool),
     ),
 > {
-    crate::generation::ensure_fresh();
+    let call = stats::call("graph_accel_subgraph");
+    crate::generation::ensure_fresh_graph(graph_name.as_deref());
     let direction = crate::util::parse_direction(&direction_filter);
     let depth = crate::util::check_non_negative(max_depth, "max_depth");
+    let limit = crate::util::row_limit(max_results, "max_results");
+    let deadline = crate::util::deadline(timeout_ms);
+    let as_of = crate::util::as_of_seconds(as_of);
 
-    let results = state::with_graph(|gs| {
+    let (sub, timed_out) = state::with_graph_named(graph_name.as_deref(), |gs| {
         let internal_id = state::resolve_node(&gs.graph, &start_id);
+        let exclusions = crate::util::build_exclusions(&gs.graph, None, None, rel_types);
+        let labels = crate::util::parse_labels(&gs.graph, node_labels, "node_labels");
+        let view = crate::util::build_view(
+            &gs.graph,
+            min_confidence,
+            &exclusions,
+            labels,
+            Vec::new(),
+            as_of,
+        );
+
+        graph_accel_core::with_deadline(deadline, || {
+            view.extract_subgraph(internal_id, depth, direction, limit)
+        })
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+    let truncated = timed_out || sub.truncated;
+
+    TableIterator::new(call.rows(sub.edges.into_iter().map(move |e| {
+        (
+            e.from_id as i64,
+            e.from_label,
+            e.from_app_id,
+            e.to_id as i64,
+            e.to_label,
+            e.to_app_id,
+            e.rel_type,
+            truncated,
+        )
+    })))
+}
+
+/// All edges among an explicit set of nodes (the induced subgraph).
+///
+/// Same columns as `graph_accel_subgraph`, without `truncated`. Duplicate
+/// IDs are ignored.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_induced_subgraph(ARRAY['concept_a', 'concept_b', 'concept_c']);
+#[pg_extern]
+fn graph_accel_induced_subgraph(
+    node_ids: Vec<String>,
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(from_id, i64),
+        name!(from_label, String),
+        name!(from_app_id, Option<String>),
+        name!(to_id, i64),
+        name!(to_label, String),
+        name!(to_app_id, Option<String>),
+        name!(rel_type, String),
+    ),
+> {
+    let call = stats::call("graph_accel_induced_subgraph");
+    crate::generation::ensure_fresh();
+
+    let sub = state::with_graph(|gs| {
+        let ids: Vec<u64> = node_ids
+            .iter()
+            .map(|id| state::resolve_node(&gs.graph, id))
+            .collect();
+
+        graph_accel_core::induced_subgraph(&gs.graph, &ids, min_confidence.map(|v| v as f32))
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(sub.edges.into_iter().map(|e| {
+        (
+            e.from_id as i64,
+            e.from_label,
+            e.from_app_id,
+            e.to_id as i64,
+            e.to_label,
+            e.to_app_id,
+            e.rel_type,
+        )
+    })))
+}
+
+/// Small tree connecting a set of nodes (Steiner tree approximation).
+///
+/// Joins the given nodes through the fewest intermediate edges it can find
+/// (KMB heuristic, at most twice the optimum). Only shortest paths of up to
+/// `max_hops` are considered; nodes that cannot be joined that way are
+/// connected in separate trees. Same columns as `graph_accel_subgraph`,
+/// with edges in their stored direction.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_connect(ARRAY['concept_a', 'concept_b', 'concept_c']);
+#[pg_extern]
+fn graph_accel_connect(
+    node_ids: Vec<String>,
+    max_hops: default!(i32, 10),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(from_id, i64),
+        name!(from_label, String),
+        name!(from_app_id, Option<String>),
+        name!(to_id, i64),
+        name!(to_label, String),
+        name!(to_app_id, Option<String>),
+        name!(rel_type, String),
+    ),
+> {
+    let call = stats::call("graph_accel_connect");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let hops = crate::util::check_non_negative(max_hops, "max_hops");
 
-        let sub = graph_accel_core::extract_subgraph(&gs.graph, internal_id, depth, direction, min_confidence.map(|v| v as f32));
+    let results = state::with_graph(|gs| {
+        let ids: Vec<u64> = node_ids
+            .iter()
+            .map(|id| state::resolve_node(&gs.graph, id))
+            .collect();
 
-        sub.edges
+        let tree = graph_accel_core::steiner_tree(&gs.graph, &ids, hops, direction, min_confidence.map(|v| v as f32));
+
+        tree.edges
             .into_iter()
             .map(|e| {
                 (
@@ -48,5 +185,59 @@ fn graph_accel_subgraph(
         error!("graph_accel: no graph loaded — call graph_accel_load() first");
     });
 
-    TableIterator::new(results)
+    TableIterator::new(call.rows(results))
+}
+
+/// Summary statistics of the k-hop ball around a node, as one row.
+///
+/// Counts the same nodes and edges `graph_accel_subgraph` would return,
+/// without materializing them. `rel_types` and `rel_type_counts` are
+/// parallel arrays, most frequent type first.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_ego_stats('concept_a', 2);
+#[pg_extern]
+fn graph_accel_ego_stats(
+    node_id: String,
+    max_depth: default!(i32, 1),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(node_count, i64),
+        name!(edge_count, i64),
+        name!(density, f64),
+        name!(rel_types, Vec<String>),
+        name!(rel_type_counts, Vec<i64>),
+    ),
+> {
+    let _call = stats::call("graph_accel_ego_stats");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+
+    let row = state::with_graph(|gs| {
+        let internal_id = state::resolve_node(&gs.graph, &node_id);
+
+        let stats = graph_accel_core::ego_stats(&gs.graph, internal_id, depth, direction, min_confidence.map(|v| v as f32))
+            .expect("resolved node is in the graph");
+        let (types, counts) = stats
+            .rel_types
+            .into_iter()
+            .map(|(name, count)| (name, count as i64))
+            .unzip();
+        (
+            stats.node_count as i64,
+            stats.edge_count as i64,
+            stats.density,
+            types,
+            counts,
+        )
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::once(row)
 }
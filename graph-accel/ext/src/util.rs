@@ -1,4 +1,10 @@
-use graph_accel_core::{Direction, TraversalDirection};
+use std::time::{Duration, Instant};
+
+use graph_accel_core::{
+    CommunityAlgorithm, Direction, Exclusions, Graph, GraphView, LabelSet, NodeId, PropValue,
+    RelTypeId, TraversalDirection,
+};
+use pgrx::datum::TimestampWithTimeZone;
 use pgrx::prelude::*;
 
 /// Parse a direction filter string into a TraversalDirection.
@@ -19,6 +25,213 @@ pub fn parse_direction(s: &str) -> TraversalDirection {
     }
 }
 
+/// Parse a community detection algorithm name.
+///
+/// Accepts: "louvain", "leiden" (case-insensitive).
+/// Raises a PostgreSQL ERROR for unrecognized values.
+pub fn parse_community_algorithm(s: &str) -> CommunityAlgorithm {
+    match s.to_lowercase().as_str() {
+        "louvain" => CommunityAlgorithm::Louvain,
+        "leiden" => CommunityAlgorithm::Leiden,
+        other => {
+            error!(
+                "graph_accel: invalid algorithm '{}' — use 'louvain' or 'leiden'",
+                other
+            );
+        }
+    }
+}
+
+/// Edge cost used to rank alternative paths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostMode {
+    /// Every edge costs 1.
+    Hops,
+    /// `-ln(confidence)`: ranks by product of edge confidences.
+    Confidence,
+    /// Sum of edge weights (`graph_accel.edge_weight_property`).
+    Weight,
+}
+
+/// Parse a path cost mode.
+///
+/// Accepts: "hops", "confidence", "weight" (case-insensitive).
+/// Raises a PostgreSQL ERROR for unrecognized values.
+pub fn parse_cost_mode(s: &str) -> CostMode {
+    match s.to_lowercase().as_str() {
+        "hops" => CostMode::Hops,
+        "confidence" => CostMode::Confidence,
+        "weight" => CostMode::Weight,
+        other => {
+            error!(
+                "graph_accel: invalid cost_mode '{}' — use 'hops', 'confidence' or 'weight'",
+                other
+            );
+        }
+    }
+}
+
+/// Parse a sort order; returns true for ascending.
+///
+/// Accepts: "asc", "desc" (case-insensitive).
+/// Raises a PostgreSQL ERROR for unrecognized values.
+pub fn parse_sort_order(s: &str) -> bool {
+    match s.to_lowercase().as_str() {
+        "asc" => true,
+        "desc" => false,
+        other => {
+            error!(
+                "graph_accel: invalid order '{}' — use 'asc' or 'desc'",
+                other
+            );
+        }
+    }
+}
+
+/// Look up a relationship type by name in the loaded graph.
+/// Raises a PostgreSQL ERROR if no edge of that type was loaded.
+pub fn resolve_rel_type(graph: &Graph, name: &str) -> RelTypeId {
+    graph.rel_type_id(name).unwrap_or_else(|| {
+        error!("graph_accel: relationship type '{}' not found in loaded graph", name);
+    })
+}
+
+/// Build traversal exclusions from SQL array arguments.
+///
+/// `nodes` lists node IDs to route around. `edges` is a flat list of
+/// endpoint pairs (`ARRAY[a1, b1, a2, b2, ...]`); each pair blocks every
+/// edge between the two nodes. `rel_types`, if given, lists the only
+/// relationship types to follow. Raises a PostgreSQL ERROR for unknown nodes
+/// or relationship types, an odd-length edge list or an empty type list.
+pub fn build_exclusions(
+    graph: &Graph,
+    nodes: Option<Vec<String>>,
+    edges: Option<Vec<String>>,
+    rel_types: Option<Vec<String>>,
+) -> Exclusions {
+    let mut exclusions = Exclusions::new();
+    for id in nodes.unwrap_or_default() {
+        exclusions.exclude_node(crate::state::resolve_node(graph, &id));
+    }
+    let edges = edges.unwrap_or_default();
+    if !edges.len().is_multiple_of(2) {
+        error!(
+            "graph_accel: exclude_edges must list endpoint pairs, got {} elements",
+            edges.len()
+        );
+    }
+    for pair in edges.chunks(2) {
+        let a = crate::state::resolve_node(graph, &pair[0]);
+        let b = crate::state::resolve_node(graph, &pair[1]);
+        exclusions.exclude_edge(a, b);
+    }
+    if let Some(names) = rel_types {
+        if names.is_empty() {
+            error!("graph_accel: rel_types must list at least one relationship type");
+        }
+        for name in names {
+            exclusions.allow_rel_type(resolve_rel_type(graph, &name));
+        }
+    }
+    exclusions
+}
+
+/// A view of `graph` for the shared traversal filter arguments: the
+/// confidence floor, `build_exclusions`, `parse_labels` and
+/// `parse_node_filter` results, and an `as_of` cutoff.
+pub fn build_view<'g>(
+    graph: &'g Graph,
+    min_confidence: Option<f64>,
+    exclusions: &Exclusions,
+    labels: Option<LabelSet>,
+    filter: Vec<(String, PropValue)>,
+    as_of: Option<u32>,
+) -> GraphView<'g> {
+    let mut view = graph.view().with_exclusions(exclusions);
+    if let Some(min) = min_confidence {
+        view = view.with_min_confidence(min as f32);
+    }
+    if let Some(as_of) = as_of {
+        view = view.with_as_of(as_of);
+    }
+    if let Some(labels) = labels {
+        view = view.with_label_set(labels);
+    }
+    if !filter.is_empty() {
+        view = view.with_node_filter(move |id| node_matches(graph, id, &filter));
+    }
+    view
+}
+
+/// Convert an `as_of` argument to seconds since the Unix epoch, the unit
+/// of edge `created_at`. Times outside what a u32 holds are clamped, so
+/// `'infinity'` keeps every edge.
+pub fn as_of_seconds(as_of: Option<TimestampWithTimeZone>) -> Option<u32> {
+    // Postgres counts microseconds from 2000-01-01
+    const PG_EPOCH_OFFSET: i64 = 946_684_800;
+    as_of.map(|ts| {
+        let secs = i64::from(ts)
+            .div_euclid(1_000_000)
+            .saturating_add(PG_EPOCH_OFFSET);
+        secs.clamp(0, u32::MAX as i64) as u32
+    })
+}
+
+/// Parse a `node_filter` argument: a JSON object of property → value.
+///
+/// A node passes when every listed property is present and equal (numbers
+/// compare numerically). NULL means no filter. Raises a PostgreSQL ERROR
+/// for anything but an object of strings and numbers.
+pub fn parse_node_filter(filter: Option<JsonB>) -> Vec<(String, PropValue)> {
+    let Some(JsonB(value)) = filter else {
+        return Vec::new();
+    };
+    let keys: Vec<String> = match value.as_object() {
+        Some(obj) => obj.keys().cloned().collect(),
+        None => error!("graph_accel: node_filter must be a JSON object of property values"),
+    };
+    let props = crate::load::json_properties(&value, &keys);
+    if props.len() != keys.len() {
+        error!("graph_accel: node_filter values must be strings or numbers");
+    }
+    props
+}
+
+/// True if `id` satisfies a filter from `parse_node_filter`.
+pub fn node_matches(graph: &Graph, id: NodeId, filter: &[(String, PropValue)]) -> bool {
+    filter.iter().all(|(key, want)| {
+        graph.node_property(id, key).is_some_and(|have| match (have.as_str(), want.as_str()) {
+            (Some(a), Some(b)) => a == b,
+            _ => have.as_f64().is_some() && have.as_f64() == want.as_f64(),
+        })
+    })
+}
+
+/// Resolve a label list argument such as `node_labels`. None (SQL NULL)
+/// admits every node. Raises a PostgreSQL ERROR for an empty list or a
+/// label no loaded node carries.
+pub fn parse_labels(
+    graph: &Graph,
+    labels: Option<Vec<String>>,
+    param_name: &str,
+) -> Option<LabelSet> {
+    let labels = labels?;
+    if labels.is_empty() {
+        error!("graph_accel: {} must list at least one label", param_name);
+    }
+    for label in &labels {
+        if graph.label_id(label).is_none() {
+            error!("graph_accel: label '{}' not found in loaded graph", label);
+        }
+    }
+    Some(graph.label_set(&labels))
+}
+
+/// True if `id` carries one of `labels`, or `labels` is None.
+pub fn node_in_labels(graph: &Graph, id: NodeId, labels: Option<&LabelSet>) -> bool {
+    labels.is_none_or(|set| graph.node_in_label_set(id, set))
+}
+
 /// Convert an edge Direction to its SQL string representation.
 pub fn direction_str(d: Direction) -> String {
     match d {
@@ -27,6 +240,28 @@ pub fn direction_str(d: Direction) -> String {
     }
 }
 
+/// Row limit from a `max_results`-style parameter (0 = no limit), lowered
+/// to the `graph_accel.max_result_rows` ceiling when that is set.
+/// Returns 0 for no limit.
+pub fn row_limit(requested: i32, param_name: &str) -> usize {
+    let requested = check_non_negative(requested, param_name) as usize;
+    match (requested, crate::guc::MAX_RESULT_ROWS.get() as usize) {
+        (0, ceiling) => ceiling,
+        (requested, 0) => requested,
+        (requested, ceiling) => requested.min(ceiling),
+    }
+}
+
+/// Deadline for a search from a `timeout_ms` parameter, defaulting to
+/// `graph_accel.query_timeout_ms` when NULL. 0 = no time budget.
+pub fn deadline(timeout_ms: Option<i32>) -> Option<Instant> {
+    let ms = match timeout_ms {
+        Some(ms) => check_non_negative(ms, "timeout_ms"),
+        None => crate::guc::QUERY_TIMEOUT_MS.get() as u32,
+    };
+    (ms > 0).then(|| Instant::now() + Duration::from_millis(ms as u64))
+}
+
 /// Validate that a depth/hops parameter is non-negative.
 /// Raises a PostgreSQL ERROR if negative.
 pub fn check_non_negative(value: i32, param_name: &str) -> u32 {
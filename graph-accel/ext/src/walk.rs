@@ -0,0 +1,146 @@
+use pgrx::prelude::*;
+
+use crate::state;
+use crate::stats;
+
+/// Sample uniform random walks, one row per step.
+///
+/// With `start_id` NULL, `num_walks` walks start from every node — the usual
+/// input for DeepWalk-style embedding training. Walks end early at nodes with
+/// no eligible edges. Pass a `seed` for reproducible samples.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_random_walks('concept_a', walk_length => 20);
+///   SELECT * FROM graph_accel_random_walks(num_walks => 10, seed => 42);
+#[pg_extern]
+fn graph_accel_random_walks(
+    start_id: default!(Option<String>, "NULL"),
+    walk_length: default!(i32, 10),
+    num_walks: default!(i32, 1),
+    seed: default!(Option<i64>, "NULL"),
+    direction_filter: default!(String, "'both'"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(walk_index, i64),
+        name!(step, i32),
+        name!(node_id, i64),
+        name!(label, String),
+        name!(app_id, Option<String>),
+    ),
+> {
+    let call = stats::call("graph_accel_random_walks");
+    crate::generation::ensure_fresh();
+    let direction = crate::util::parse_direction(&direction_filter);
+    let len = crate::util::check_non_negative(walk_length, "walk_length");
+    let walks_per_start = crate::util::check_non_negative(num_walks, "num_walks") as usize;
+    let seed = seed.map(|s| s as u64).unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+
+    let results = state::with_graph(|gs| {
+        let start = start_id
+            .as_deref()
+            .map(|id| state::resolve_node(&gs.graph, id));
+
+        graph_accel_core::random_walks(
+            &gs.graph,
+            start,
+            len,
+            walks_per_start,
+            seed,
+            direction,
+            min_confidence.map(|v| v as f32),
+        )
+        .into_iter()
+        .enumerate()
+        .flat_map(|(wi, walk)| {
+            walk.into_iter().enumerate().map(move |(si, id)| (wi, si, id))
+        })
+        .map(|(wi, si, id)| {
+            let info = gs.graph.node(id);
+            (
+                wi as i64,
+                si as i32,
+                id as i64,
+                gs.graph.node_label(id).unwrap_or_default().to_string(),
+                info.and_then(|n| n.app_id.clone()),
+            )
+        })
+        .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
+
+/// Sample a uniform random spanning tree (Wilson's algorithm).
+///
+/// Covers the ball of `max_depth` hops around `start_id`, or the whole graph
+/// when `start_id` is NULL (one tree per component). Every spanning tree is
+/// equally likely, so the edges are an unbiased sparsification of a dense
+/// neighborhood. Same columns as `graph_accel_subgraph`.
+///
+/// Usage:
+///   SELECT * FROM graph_accel_random_spanning_tree('concept_a', 2, seed => 42);
+#[pg_extern]
+fn graph_accel_random_spanning_tree(
+    start_id: default!(Option<String>, "NULL"),
+    max_depth: default!(i32, 2),
+    seed: default!(Option<i64>, "NULL"),
+    min_confidence: default!(Option<f64>, "NULL"),
+) -> TableIterator<
+    'static,
+    (
+        name!(from_id, i64),
+        name!(from_label, String),
+        name!(from_app_id, Option<String>),
+        name!(to_id, i64),
+        name!(to_label, String),
+        name!(to_app_id, Option<String>),
+        name!(rel_type, String),
+    ),
+> {
+    let call = stats::call("graph_accel_random_spanning_tree");
+    crate::generation::ensure_fresh();
+    let depth = crate::util::check_non_negative(max_depth, "max_depth");
+    let seed = seed.map(|s| s as u64).unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    });
+
+    let results = state::with_graph(|gs| {
+        let start = start_id
+            .as_deref()
+            .map(|id| state::resolve_node(&gs.graph, id));
+
+        graph_accel_core::random_spanning_tree(&gs.graph, start, depth, seed, min_confidence.map(|v| v as f32))
+            .edges
+            .into_iter()
+            .map(|e| {
+                (
+                    e.from_id as i64,
+                    e.from_label,
+                    e.from_app_id,
+                    e.to_id as i64,
+                    e.to_label,
+                    e.to_app_id,
+                    e.rel_type,
+                )
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_else(|| {
+        error!("graph_accel: no graph loaded — call graph_accel_load() first");
+    });
+
+    TableIterator::new(call.rows(results))
+}
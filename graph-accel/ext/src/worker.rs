@@ -0,0 +1,148 @@
+//! Background worker that keeps the shared graph built.
+//!
+//! With `graph_accel.worker` on and the library in
+//! `shared_preload_libraries`, the postmaster starts a worker that loads
+//! `graph_accel.source_graph` as soon as the server accepts connections
+//! and publishes it as a shared file (see `shared.rs`). It then checks the
+//! generation every `graph_accel.worker_interval_sec` and rebuilds after
+//! each invalidation, so backends attach to a ready file instead of paying
+//! the full load on the query path.
+//!
+//! The worker polls the generation row rather than LISTENing: NOTIFY is
+//! only delivered to client connections, and the poll is one primary-key
+//! lookup.
+//!
+//! With `graph_accel.decoding_slot` set, the worker keeps its copy loaded
+//! and on each tick applies the changes decoded from the slot (see
+//! `decode.rs`), bumps the generation and publishes the result, so writes
+//! that never invalidate still reach backends. An invalidation from
+//! elsewhere, or a change it cannot decode, still means a full reload.
+
+use std::time::Duration;
+
+use pgrx::bgworkers::{BackgroundWorker, BackgroundWorkerBuilder, SignalWakeFlags};
+use pgrx::prelude::*;
+
+use crate::decode;
+use crate::generation;
+use crate::guc;
+use crate::load;
+use crate::shared;
+use crate::state;
+
+/// Register the worker if enabled. Only takes effect while the library is
+/// preloaded; a backend loading it later does nothing.
+pub(crate) fn register() {
+    // Safety: plain read of a postmaster global, set before _PG_init runs.
+    if !unsafe { pg_sys::process_shared_preload_libraries_in_progress } || !guc::WORKER.get() {
+        return;
+    }
+    BackgroundWorkerBuilder::new("graph_accel worker")
+        .set_function("graph_accel_worker_main")
+        .set_library("graph_accel")
+        .enable_spi_access()
+        .set_restart_time(Some(interval()))
+        .load();
+}
+
+fn interval() -> Duration {
+    Duration::from_secs(guc::WORKER_INTERVAL_SEC.get().max(1) as u64)
+}
+
+/// Worker entry point. A failed load raises an ERROR, which ends the
+/// worker; the postmaster restarts it after the interval.
+#[pg_guard]
+#[no_mangle]
+pub extern "C-unwind" fn graph_accel_worker_main(_arg: pg_sys::Datum) {
+    BackgroundWorker::attach_signal_handlers(SignalWakeFlags::SIGHUP | SignalWakeFlags::SIGTERM);
+    let database = guc::get_string(&guc::WORKER_DATABASE);
+    BackgroundWorker::connect_worker_to_spi(database.as_deref(), None);
+
+    // (graph, generation) last published
+    let mut published: Option<(String, i64)> = None;
+    loop {
+        if BackgroundWorker::sighup_received() {
+            // Safety: called from the worker's main loop, outside any
+            // transaction, as PostgreSQL's own workers do.
+            unsafe { pg_sys::ProcessConfigFile(pg_sys::GucContext::PGC_SIGHUP) };
+        }
+        published = BackgroundWorker::transaction(|| refresh(published));
+        if !BackgroundWorker::wait_latch(Some(interval())) {
+            break;
+        }
+    }
+}
+
+/// Publish `graph_accel.source_graph` if its generation moved past
+/// `published`, returning what is now published.
+pub(crate) fn refresh(published: Option<(String, i64)>) -> Option<(String, i64)> {
+    let Some(graph_name) = guc::get_string(&guc::SOURCE_GRAPH) else {
+        return published;
+    };
+    let mut current = generation::fetch_generation(&graph_name).unwrap_or(0);
+    let up_to_date = published.as_ref() == Some(&(graph_name.clone(), current));
+    let slot = guc::get_string(&guc::DECODING_SLOT);
+
+    if let Some(slot) = slot.as_deref() {
+        if up_to_date && state::is_loaded(&graph_name) {
+            match decode::sync(&graph_name, slot) {
+                decode::Sync::Applied(0) => return published,
+                decode::Sync::Applied(changes) => {
+                    let gen = generation::bump_generation(&graph_name, "graph_accel worker");
+                    republish(&graph_name, gen);
+                    log!(
+                        "graph_accel worker: applied {} decoded changes to '{}', \
+                         published generation {}",
+                        changes,
+                        graph_name,
+                        gen
+                    );
+                    return Some((graph_name, gen));
+                }
+                decode::Sync::Unusable(reason) => {
+                    log!("graph_accel worker: {}, reloading '{}'", reason, graph_name);
+                }
+            }
+        }
+        // The load below reads these changes from the tables. It must not
+        // attach to a file published before them, so it gets a generation
+        // of its own.
+        decode::skip_pending(slot);
+        current = generation::bump_generation(&graph_name, "graph_accel worker");
+    } else if up_to_date {
+        return published;
+    }
+
+    Spi::run("SET graph_accel.shared_graph = on")
+        .unwrap_or_else(|e| error!("graph_accel worker: {}", e));
+    let (nodes, edges, ms) = load::do_load(&graph_name, false);
+    if slot.is_none() {
+        // Backends map the published file; the worker's copy is not needed
+        state::remove_graph(&graph_name);
+    }
+    log!(
+        "graph_accel worker: published '{}' at generation {} ({} nodes, {} edges, {:.0}ms)",
+        graph_name,
+        current,
+        nodes,
+        edges,
+        ms
+    );
+    Some((graph_name, current))
+}
+
+/// Publish the worker's copy of `graph_name` as `generation` and serve it
+/// from the published file, as a load would.
+fn republish(graph_name: &str, generation: i64) {
+    let Some(graph) = state::with_graph_named(Some(graph_name), |gs| gs.graph.clone()) else {
+        return;
+    };
+    let mapped = shared::publish(graph_name, generation, &graph).map(|g| g.into_shared());
+    drop(graph);
+    state::with_graph_mut_named(Some(graph_name), |gs| {
+        if let Some(mapped) = mapped {
+            gs.graph = mapped;
+        }
+        gs.loaded_generation = generation;
+    });
+}